@@ -0,0 +1,49 @@
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use doodle::{
+    FormatModule,
+    decoder::{Compiler, Program, vm::VmProgram},
+    read::ReadCtxt,
+};
+use doodle_formats::format;
+
+const TEST_FILES: &[&str] = &[
+    "../test4.gz",
+    "../test.png",
+    "../test2.jpg",
+    "../test.elf",
+    "../test-fonts/DroidSansArabic.ttf",
+];
+
+// amortize the cost of constructing the programs, which is not what is being compared
+thread_local! {
+    static PROGRAM: Program = {
+        let mut module = FormatModule::new();
+        let format = format::main(&mut module).call();
+        Compiler::compile_program(&module, &format).unwrap()
+    };
+    static VM_PROGRAM: VmProgram = PROGRAM.with(|program| VmProgram::compile(program).unwrap());
+}
+
+/// Compares the tree-walking interpreter, the bytecode VM and the generated decoder on each test file.
+pub fn backend_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("backends");
+    for path in TEST_FILES {
+        let input = std::fs::read(path).unwrap();
+        let name = path.trim_start_matches("../");
+        group.bench_with_input(BenchmarkId::new("interpreted", name), &input, |b, input| {
+            PROGRAM
+                .with(|program| b.iter(|| black_box(program.run(ReadCtxt::new(input)).unwrap().0)))
+        });
+        group.bench_with_input(BenchmarkId::new("vm", name), &input, |b, input| {
+            VM_PROGRAM
+                .with(|program| b.iter(|| black_box(program.run(ReadCtxt::new(input)).unwrap().0)))
+        });
+        group.bench_with_input(BenchmarkId::new("generated", name), &input, |b, input| {
+            b.iter(|| black_box(doodle_gencode::decode_main(input).unwrap()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, backend_benchmark);
+criterion_main!(benches);
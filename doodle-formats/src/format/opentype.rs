@@ -1,8 +1,8 @@
 use doodle::numeric::core::MachineRep;
 use doodle::numeric::helper as num;
 use doodle::{
-    BaseType, DepFormat, Expr, Format, FormatModule, FormatRef, Label, Pattern, ValueType,
-    ViewExpr, bounds::Bounds, helper::*,
+    BaseType, DepFormat, Expr, FixedType, Format, FormatModule, FormatRef, GenericRef, Label,
+    Pattern, ValueType, ViewExpr, bounds::Bounds, helper::*,
 };
 
 mod util {
@@ -275,14 +275,14 @@ mod util {
         )
     }
 
-    /// Parses a u32 serving as the de-facto representation of a signed, 16.16 bit fixed-point number
+    /// Parses a signed, 16.16 bit fixed-point number from a big-endian u32
     pub(crate) fn fixed32be() -> Format {
-        fmt_variant("Fixed32", u32be())
+        fmt_variant("Fixed32", fixed_be(FixedType::I16F16))
     }
 
-    // Custom type for fixed-point values that are interpreted as (2bits . 14bits) within a u16be raw-parse
+    /// Parses a signed, 2.14 bit fixed-point number from a big-endian u16
     pub(crate) fn f2dot14() -> Format {
-        fmt_variant("F2Dot14", u16be())
+        fmt_variant("F2Dot14", fixed_be(FixedType::I2F14))
     }
 
     /// Helper function for parsing a big-endian u24 (3-byte) value
@@ -543,6 +543,63 @@ mod util {
         ])
     }
 
+    /// Returns the generic format for `read_phantom_view_offset16`, over the format parameter `target` and the view
+    /// parameter `_base` (which generated code never reads), defining it the first time it is needed.
+    ///
+    /// The format argument of each invocation may refer to the value parameters `args`, through which it is passed
+    /// whatever values of the invoking format it depends on.
+    pub(crate) fn phantom_offset16(
+        module: &mut FormatModule,
+        args: Vec<(Label, ValueType)>,
+    ) -> GenericRef {
+        phantom_offset(
+            module,
+            "opentype.phantom_offset16",
+            args,
+            read_phantom_view_offset16,
+        )
+    }
+
+    /// Returns the generic format for `read_phantom_view_offset32`, as with `phantom_offset16`.
+    pub(crate) fn phantom_offset32(
+        module: &mut FormatModule,
+        args: Vec<(Label, ValueType)>,
+    ) -> GenericRef {
+        phantom_offset(
+            module,
+            "opentype.phantom_offset32",
+            args,
+            read_phantom_view_offset32,
+        )
+    }
+
+    fn phantom_offset(
+        module: &mut FormatModule,
+        family: &str,
+        args: Vec<(Label, ValueType)>,
+        read_offset: fn(ViewExpr, Format) -> Format,
+    ) -> GenericRef {
+        // NOTE - each distinct set of value parameters is a generic format of its own, named after them
+        let name = args
+            .iter()
+            .fold(family.to_owned(), |name, (arg, _)| format!("{name}.{arg}"));
+        if let Some(generic) = module.find_generic(&name) {
+            assert_eq!(
+                module.get_generic(generic.get_index()).args,
+                args,
+                "{name} redefined with different parameter types"
+            );
+            return generic;
+        }
+        module.define_generic_format_args_views(
+            name,
+            vec![Label::Borrowed("target")],
+            args,
+            vec![Label::Borrowed("_base")],
+            read_offset(vvar("_base"), Format::Apply(Label::Borrowed("target"))),
+        )
+    }
+
     /// Record-format that reads (and stores) a u32be offset, along with a field `data` for the strict parse of `format` at that offset (relative to `view`).
     ///
     /// # NOTES
@@ -9,6 +9,8 @@ pub(crate) fn table(
     device_or_variation_index_table: FormatRef,
     item_variation_store: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
+    let offset32 = util::phantom_offset32(module, vec![]);
     let base_coord = base_coord(module, device_or_variation_index_table);
     let min_max = min_max(module, tag, base_coord);
     let base_values = base_values(module, base_coord);
@@ -20,7 +22,7 @@ pub(crate) fn table(
             ("base_lang_sys_tag", tag.call()),
             (
                 "min_max",
-                util::read_phantom_view_offset16(vvar("table_view"), min_max.call()),
+                offset16.apply_views(vec![min_max.call()], vec![vvar("table_view")]),
             ),
         ]),
     );
@@ -32,11 +34,11 @@ pub(crate) fn table(
                 ("table_scope", reify_view(vvar("table_view"))),
                 (
                     "base_values_offset",
-                    util::read_phantom_view_offset16(vvar("table_view"), base_values.call()),
+                    offset16.apply_views(vec![base_values.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "default_min_max_offset",
-                    util::read_phantom_view_offset16(vvar("table_view"), min_max.call()),
+                    offset16.apply_views(vec![min_max.call()], vec![vvar("table_view")]),
                 ),
                 ("base_lang_sys_count", u16be()),
                 (
@@ -56,7 +58,7 @@ pub(crate) fn table(
             ("base_script_tag", tag.call()),
             (
                 "base_script",
-                util::read_phantom_view_offset16(vvar("table_view"), base_script.call()),
+                offset16.apply_views(vec![base_script.call()], vec![vvar("table_view")]),
             ),
         ]),
     );
@@ -89,11 +91,11 @@ pub(crate) fn table(
                 ("table_scope", reify_view(vvar("table_view"))),
                 (
                     "base_tag_list_offset",
-                    util::read_phantom_view_offset16(vvar("table_view"), base_tag_list),
+                    offset16.apply_views(vec![base_tag_list], vec![vvar("table_view")]),
                 ),
                 (
                     "base_script_list_offset",
-                    util::read_phantom_view_offset16(vvar("table_view"), base_script_list),
+                    offset16.apply_views(vec![base_script_list], vec![vvar("table_view")]),
                 ),
             ]),
         ),
@@ -109,19 +111,19 @@ pub(crate) fn table(
                 ("minor_version", where_between_u16(u16be(), 0, 1)), // v1.0 and v1.1
                 (
                     "horiz_axis_offset",
-                    util::read_phantom_view_offset16(vvar("table_view"), axis_table.call()),
+                    offset16.apply_views(vec![axis_table.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "vert_axis_offset",
-                    util::read_phantom_view_offset16(vvar("table_view"), axis_table.call()),
+                    offset16.apply_views(vec![axis_table.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "item_var_store_offset",
                     cond_maybe(
                         expr_gt(var("minor_version"), Expr::U16(0)),
-                        util::read_phantom_view_offset32(
-                            vvar("table_view"),
-                            item_variation_store.call(),
+                        offset32.apply_views(
+                            vec![item_variation_store.call()],
+                            vec![vvar("table_view")],
                         ),
                     ),
                 ),
@@ -134,6 +136,7 @@ pub(crate) fn table(
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/base#basevalues-table
 fn base_values(module: &mut FormatModule, base_coord: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let base_values = module.define_format(
         "opentype.layout.base_values",
         let_view(
@@ -146,7 +149,7 @@ fn base_values(module: &mut FormatModule, base_coord: FormatRef) -> FormatRef {
                     "base_coord_offsets",
                     repeat_count(
                         var("base_coord_count"),
-                        util::read_phantom_view_offset16(vvar("table_view"), base_coord.call()),
+                        offset16.apply_views(vec![base_coord.call()], vec![vvar("table_view")]),
                     ),
                 ),
             ]),
@@ -159,6 +162,7 @@ fn base_values(module: &mut FormatModule, base_coord: FormatRef) -> FormatRef {
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/base#the-minmax-table-and-featminmax-record
 fn min_max(module: &mut FormatModule, tag: FormatRef, base_coord: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let feat_min_max = module.define_format_views(
         "opentype.layout.feat_min_max",
         vec![Label::Borrowed("table_view")],
@@ -166,11 +170,11 @@ fn min_max(module: &mut FormatModule, tag: FormatRef, base_coord: FormatRef) ->
             ("feature_tag", tag.call()),
             (
                 "min_coord_offset",
-                util::read_phantom_view_offset16(vvar("table_view"), base_coord.call()),
+                offset16.apply_views(vec![base_coord.call()], vec![vvar("table_view")]),
             ),
             (
                 "max_coord_offset",
-                util::read_phantom_view_offset16(vvar("table_view"), base_coord.call()),
+                offset16.apply_views(vec![base_coord.call()], vec![vvar("table_view")]),
             ),
         ]),
     );
@@ -182,11 +186,11 @@ fn min_max(module: &mut FormatModule, tag: FormatRef, base_coord: FormatRef) ->
                 ("table_scope", reify_view(vvar("table_view"))),
                 (
                     "min_coord_offset",
-                    util::read_phantom_view_offset16(vvar("table_view"), base_coord.call()),
+                    offset16.apply_views(vec![base_coord.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "max_coord_offset",
-                    util::read_phantom_view_offset16(vvar("table_view"), base_coord.call()),
+                    offset16.apply_views(vec![base_coord.call()], vec![vvar("table_view")]),
                 ),
                 ("feat_min_max_count", u16be()),
                 (
@@ -205,13 +209,17 @@ fn min_max(module: &mut FormatModule, tag: FormatRef, base_coord: FormatRef) ->
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/base#basecoord-tables
 fn base_coord(module: &mut FormatModule, device_or_variation_index_table: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     // NOTE - 'data' field is a nested record of any fields beyond `{ format, coordinate }` used in a given format
     let format1_data = Format::EMPTY;
     let format2_data = record([("reference_glyph", u16be()), ("base_coord_point", u16be())]);
     let format3_data = |table_view: ViewExpr| {
         record([(
             "device",
-            util::read_phantom_view_offset16(table_view, device_or_variation_index_table.call()),
+            offset16.apply_views(
+                vec![device_or_variation_index_table.call()],
+                vec![table_view],
+            ),
         )])
     };
     module.define_format(
@@ -2,6 +2,8 @@ use super::*;
 
 /// Table format definition-function for `cmap`
 pub(crate) fn table(module: &mut FormatModule) -> FormatRef {
+    let subtable_offset32 =
+        util::phantom_offset32(module, vec![(Label::Borrowed("platform"), ValueType::U16)]);
     let sequential_map_group = module.define_format(
         "opentype.types.sequential_map_record",
         record([
@@ -102,9 +104,10 @@ pub(crate) fn table(module: &mut FormatModule) -> FormatRef {
             ("encoding", encoding_id(var("platform"))), // encoding identifier
             (
                 "subtable",
-                util::read_phantom_view_offset32(
-                    vvar("table_view"),
-                    cmap_subtable.call_args(vec![var("platform")]),
+                subtable_offset32.apply_args_views(
+                    vec![cmap_subtable.call_args(vec![var("platform")])],
+                    vec![var("platform")],
+                    vec![vvar("table_view")],
                 ),
             ),
         ]),
@@ -332,6 +335,7 @@ fn subtable_format13(module: &mut FormatModule, sequential_map_group: FormatRef)
 }
 
 fn subtable_format14(module: &mut FormatModule) -> FormatRef {
+    let offset32 = util::phantom_offset32(module, vec![]);
     let unicode_range = record([
         ("start_unicode_value", util::u24be()),
         ("additional_count", u8()),
@@ -362,11 +366,11 @@ fn subtable_format14(module: &mut FormatModule) -> FormatRef {
             ("var_selector", util::u24be()),
             (
                 "default_uvs_offset",
-                util::read_phantom_view_offset32(vvar("table_view"), default_uvs_table),
+                offset32.apply_views(vec![default_uvs_table], vec![vvar("table_view")]),
             ),
             (
                 "non_default_uvs_offset",
-                util::read_phantom_view_offset32(vvar("table_view"), non_default_uvs_table),
+                offset32.apply_views(vec![non_default_uvs_table], vec![vvar("table_view")]),
             ),
         ]),
     );
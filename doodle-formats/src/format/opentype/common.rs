@@ -1,6 +1,7 @@
 use super::*;
 
 pub(crate) fn item_variation_store(module: &mut FormatModule) -> FormatRef {
+    let offset32 = util::phantom_offset32(module, vec![]);
     let variation_region_list = variation_region_list(module);
     let item_variation_data = item_variation_data(module);
     module.define_format(
@@ -12,19 +13,17 @@ pub(crate) fn item_variation_store(module: &mut FormatModule) -> FormatRef {
                 ("format", util::expect_u16be(1)),
                 (
                     "variation_region_list",
-                    util::read_phantom_view_offset32(
-                        vvar("table_view"),
-                        variation_region_list.call(),
-                    ),
+                    offset32
+                        .apply_views(vec![variation_region_list.call()], vec![vvar("table_view")]),
                 ),
                 ("item_variation_data_count", u16be()),
                 (
                     "item_variation_data_list",
                     repeat_count(
                         var("item_variation_data_count"),
-                        util::read_phantom_view_offset32(
-                            vvar("table_view"),
-                            item_variation_data.call(),
+                        offset32.apply_views(
+                            vec![item_variation_data.call()],
+                            vec![vvar("table_view")],
                         ),
                     ),
                 ),
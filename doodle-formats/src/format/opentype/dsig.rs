@@ -39,6 +39,13 @@ pub fn table(module: &mut FormatModule) -> FormatRef {
 }
 
 fn signature_record(module: &mut FormatModule) -> DepFormat<0, 1> {
+    let signature_offset32 = util::phantom_offset32(
+        module,
+        vec![
+            (Label::Borrowed("format"), ValueType::U32),
+            (Label::Borrowed("length"), ValueType::U32),
+        ],
+    );
     let sig_format1 = sig_format1(module);
     module.register_format_views(
         "opentype.dsig.signature_record",
@@ -48,12 +55,13 @@ fn signature_record(module: &mut FormatModule) -> DepFormat<0, 1> {
             ("length", u32be()),
             (
                 "signature_offset",
-                read_phantom_view_offset32(
-                    vvar("_table_view"),
-                    fmt_match(
+                signature_offset32.apply_args_views(
+                    vec![fmt_match(
                         var("format"),
                         [(Pattern::U32(1), slice(var("length"), sig_format1.call()))],
-                    ),
+                    )],
+                    vec![var("format"), var("length")],
+                    vec![vvar("_table_view")],
                 ),
             ),
         ]),
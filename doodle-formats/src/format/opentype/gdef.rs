@@ -7,22 +7,24 @@ pub(crate) fn table(
     device_or_variation_index_table: FormatRef,
     item_variation_store: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
+    let offset32 = util::phantom_offset32(module, vec![]);
     let mark_glyph_set = mark_glyph_set(module, coverage_table);
     let gdef_header_version_1_2 = |table_view: ViewExpr| {
         record([(
             "mark_glyph_sets_def",
-            util::read_phantom_view_offset16(table_view, mark_glyph_set.call()),
+            offset16.apply_views(vec![mark_glyph_set.call()], vec![table_view]),
         )])
     };
     let gdef_header_version_1_3 = |table_view: ViewExpr| {
         record([
             (
                 "mark_glyph_sets_def",
-                util::read_phantom_view_offset16(table_view.clone(), mark_glyph_set.call()),
+                offset16.apply_views(vec![mark_glyph_set.call()], vec![table_view.clone()]),
             ),
             (
                 "item_var_store",
-                util::read_phantom_view_offset32(table_view, item_variation_store.call()),
+                offset32.apply_views(vec![item_variation_store.call()], vec![table_view]),
             ),
         ])
     };
@@ -42,22 +44,22 @@ pub(crate) fn table(
                 // Class definition table for glyph type (may be NULL)
                 (
                     "glyph_class_def",
-                    util::read_phantom_view_offset16(vvar("table_view"), class_def.call()),
+                    offset16.apply_views(vec![class_def.call()], vec![vvar("table_view")]),
                 ),
                 // Attachment point list table (may be NULL)
                 (
                     "attach_list",
-                    util::read_phantom_view_offset16(vvar("table_view"), attach_list.call()),
+                    offset16.apply_views(vec![attach_list.call()], vec![vvar("table_view")]),
                 ),
                 // Ligature caret list table (may be NULL)
                 (
                     "lig_caret_list",
-                    util::read_phantom_view_offset16(vvar("table_view"), lig_caret_list.call()),
+                    offset16.apply_views(vec![lig_caret_list.call()], vec![vvar("table_view")]),
                 ),
                 // Class definition table for mark attachment type (may be NULL)
                 (
                     "mark_attach_class_def",
-                    util::read_phantom_view_offset16(vvar("table_view"), class_def.call()),
+                    offset16.apply_views(vec![class_def.call()], vec![vvar("table_view")]),
                 ),
                 // Version-specific data, if > 1.0
                 // REVIEW - do we want to flatten this variant abstraction into two Option<...> fields instead?
@@ -98,6 +100,7 @@ fn lig_caret_list(
     coverage_table: FormatRef,
     device_or_variation_index_table: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let caret_value = caret_value(module, device_or_variation_index_table);
     let lig_glyph = module.define_format(
         "opentype.gdef.lig_glyph",
@@ -110,7 +113,7 @@ fn lig_caret_list(
                     "caret_values",
                     repeat_count(
                         var("caret_count"),
-                        util::read_phantom_view_offset16(vvar("table_view"), caret_value.call()),
+                        offset16.apply_views(vec![caret_value.call()], vec![vvar("table_view")]),
                     ),
                 ),
             ]),
@@ -124,14 +127,14 @@ fn lig_caret_list(
                 ("list_scope", reify_view(vvar("list_view"))),
                 (
                     "coverage",
-                    util::read_phantom_view_offset16(vvar("list_view"), coverage_table.call()),
+                    offset16.apply_views(vec![coverage_table.call()], vec![vvar("list_view")]),
                 ),
                 ("lig_glyph_count", u16be()),
                 (
                     "lig_glyph_offsets",
                     repeat_count(
                         var("lig_glyph_count"),
-                        util::read_phantom_view_offset16(vvar("list_view"), lig_glyph.call()),
+                        offset16.apply_views(vec![lig_glyph.call()], vec![vvar("list_view")]),
                     ),
                 ),
             ]),
@@ -140,6 +143,7 @@ fn lig_caret_list(
 }
 
 fn caret_value(module: &mut FormatModule, device_or_variation_index_table: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     // REVIEW - should we make formatrefs for formats 1 and 2 for consistency?
     let caret_value_format_1 = record([("coordinate", i16be())]);
 
@@ -154,9 +158,9 @@ fn caret_value(module: &mut FormatModule, device_or_variation_index_table: Forma
             ("coordinate", i16be()),
             (
                 "table",
-                util::read_phantom_view_offset16(
-                    vvar("table_view"),
-                    device_or_variation_index_table.call(),
+                offset16.apply_views(
+                    vec![device_or_variation_index_table.call()],
+                    vec![vvar("table_view")],
                 ),
             ),
         ]),
@@ -191,6 +195,7 @@ fn caret_value(module: &mut FormatModule, device_or_variation_index_table: Forma
 }
 
 fn attach_list(module: &mut FormatModule, coverage_table: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let attach_point = module.define_format(
         "opentype.gdef.attach_point",
         record([
@@ -207,14 +212,14 @@ fn attach_list(module: &mut FormatModule, coverage_table: FormatRef) -> FormatRe
                 ("list_scope", reify_view(vvar("list_view"))),
                 (
                     "coverage",
-                    util::read_phantom_view_offset16(vvar("list_view"), coverage_table.call()),
+                    offset16.apply_views(vec![coverage_table.call()], vec![vvar("list_view")]),
                 ),
                 ("glyph_count", u16be()),
                 (
                     "attach_point_offsets",
                     repeat_count(
                         var("glyph_count"),
-                        util::read_phantom_view_offset16(vvar("list_view"), attach_point.call()),
+                        offset16.apply_views(vec![attach_point.call()], vec![vvar("list_view")]),
                     ),
                 ),
             ]),
@@ -223,6 +228,7 @@ fn attach_list(module: &mut FormatModule, coverage_table: FormatRef) -> FormatRe
 }
 
 fn mark_glyph_set(module: &mut FormatModule, coverage_table: FormatRef) -> FormatRef {
+    let offset32 = util::phantom_offset32(module, vec![]);
     module.define_format(
         "opentype.gdef.mark_glyph_set",
         let_view(
@@ -235,7 +241,7 @@ fn mark_glyph_set(module: &mut FormatModule, coverage_table: FormatRef) -> Forma
                     "coverage",
                     repeat_count(
                         var("mark_glyph_set_count"),
-                        util::read_phantom_view_offset32(vvar("table_view"), coverage_table.call()),
+                        offset32.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                 ),
             ]),
@@ -40,6 +40,7 @@ pub(crate) fn single_pos(
     value_format_flags: FormatRef,
     value_record: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let single_pos_format1 = module.define_format_views(
         "opentype.layout.single_pos.format1",
         vec![Label::Borrowed("table_view")],
@@ -47,7 +48,7 @@ pub(crate) fn single_pos(
             ("table_scope", reify_view(vvar("table_view"))),
             (
                 "coverage",
-                read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
             ),
             ("value_format", value_format_flags.call()),
             (
@@ -63,7 +64,7 @@ pub(crate) fn single_pos(
             ("table_scope", reify_view(vvar("table_view"))),
             (
                 "coverage",
-                read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
             ),
             ("value_format", value_format_flags.call()),
             ("value_count", u16be()),
@@ -120,9 +121,17 @@ pub(crate) fn pair_pos(
     value_format_flags: FormatRef,
     value_record: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let vf_flags_type = module
         .get_format_type(value_format_flags.get_level())
         .clone();
+    let pair_set_offset16 = util::phantom_offset16(
+        module,
+        vec![
+            (Label::Borrowed("value_format1"), vf_flags_type.clone()),
+            (Label::Borrowed("value_format2"), vf_flags_type.clone()),
+        ],
+    );
 
     let pair_value_record = module.define_format_args_views(
         "opentype.layout.pair_pos.pair_value_record",
@@ -176,7 +185,7 @@ pub(crate) fn pair_pos(
             ("table_scope", reify_view(vvar("table_view"))),
             (
                 "coverage",
-                util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
             ),
             ("value_format1", value_format_flags.call()),
             ("value_format2", value_format_flags.call()),
@@ -185,9 +194,10 @@ pub(crate) fn pair_pos(
                 "pair_sets",
                 repeat_count(
                     var("pair_set_count"),
-                    util::read_phantom_view_offset16(
-                        vvar("table_view"),
-                        pair_set.call_args(vec![var("value_format1"), var("value_format2")]),
+                    pair_set_offset16.apply_args_views(
+                        vec![pair_set.call_args(vec![var("value_format1"), var("value_format2")])],
+                        vec![var("value_format1"), var("value_format2")],
+                        vec![vvar("table_view")],
                     ),
                 ),
             ),
@@ -244,17 +254,17 @@ pub(crate) fn pair_pos(
             ("table_scope", reify_view(vvar("table_view"))),
             (
                 "coverage",
-                util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
             ),
             ("value_format1", value_format_flags.call()),
             ("value_format2", value_format_flags.call()),
             (
                 "class_def1",
-                util::read_phantom_view_offset16(vvar("table_view"), class_def.call()),
+                offset16.apply_views(vec![class_def.call()], vec![vvar("table_view")]),
             ),
             (
                 "class_def2",
-                util::read_phantom_view_offset16(vvar("table_view"), class_def.call()),
+                offset16.apply_views(vec![class_def.call()], vec![vvar("table_view")]),
             ),
             ("class1_count", u16be()),
             ("class2_count", u16be()),
@@ -312,12 +322,13 @@ pub(crate) fn cursive_pos(
     coverage_table: FormatRef,
     anchor_table: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let entry_exit_record = module.define_format_views(
         "opentype.layout.entry_exit_record",
         vec![Label::Borrowed("table_view")],
         record_repeat(
             ["entry_anchor", "exit_anchor"],
-            util::read_phantom_view_offset16(vvar("table_view"), anchor_table.call()),
+            offset16.apply_views(vec![anchor_table.call()], vec![vvar("table_view")]),
         ),
     );
     module.define_format(
@@ -331,7 +342,7 @@ pub(crate) fn cursive_pos(
                     ("table_scope", reify_view(vvar("table_view"))),
                     (
                         "coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                     ("entry_exit_count", u16be()),
                     (
@@ -354,6 +365,7 @@ pub(crate) fn cursive_pos(
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/gpos#mark-array-table
 pub(crate) fn mark_array(module: &mut FormatModule, anchor_table: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     // TODO - refactor into dep-format or standalone function
     let mark_record = module.define_format_views(
         "opentype.layout.mark_record",
@@ -362,7 +374,7 @@ pub(crate) fn mark_array(module: &mut FormatModule, anchor_table: FormatRef) ->
             ("mark_class", u16be()),
             (
                 "mark_anchor",
-                util::read_phantom_view_offset16(vvar("array_view"), anchor_table.call()),
+                offset16.apply_views(vec![anchor_table.call()], vec![vvar("array_view")]),
             ),
         ]),
     );
@@ -437,6 +449,11 @@ pub(crate) fn mark_base_pos(
     anchor_table: FormatRef,
     mark_array: FormatRef,
 ) -> FormatRef {
+    let mark_class_offset16 = util::phantom_offset16(
+        module,
+        vec![(Label::Borrowed("mark_class_count"), ValueType::U16)],
+    );
+    let offset16 = util::phantom_offset16(module, vec![]);
     let base_record = module.register_format_args_views(
         "opentype.layout.base_array.base_record",
         [(Label::Borrowed("mark_class_count"), ValueType::U16)],
@@ -492,22 +509,23 @@ pub(crate) fn mark_base_pos(
                     ("table_scope", reify_view(vvar("table_view"))),
                     (
                         "mark_coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                     (
                         "base_coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                     ("mark_class_count", u16be()),
                     (
                         "mark_array",
-                        util::read_phantom_view_offset16(vvar("table_view"), mark_array.call()),
+                        offset16.apply_views(vec![mark_array.call()], vec![vvar("table_view")]),
                     ),
                     (
                         "base_array",
-                        util::read_phantom_view_offset16(
-                            vvar("table_view"),
-                            base_array.invoke_args([var("mark_class_count")]),
+                        mark_class_offset16.apply_args_views(
+                            vec![base_array.invoke_args([var("mark_class_count")])],
+                            vec![var("mark_class_count")],
+                            vec![vvar("table_view")],
                         ),
                     ),
                 ],
@@ -635,6 +653,11 @@ pub(crate) fn mark_lig_pos(
     anchor_table: FormatRef,
     mark_array: FormatRef,
 ) -> FormatRef {
+    let mark_class_offset16 = util::phantom_offset16(
+        module,
+        vec![(Label::Borrowed("mark_class_count"), ValueType::U16)],
+    );
+    let offset16 = util::phantom_offset16(module, vec![]);
     let ligature_array = mark_lig::ligature_array(module, anchor_table);
 
     module.define_format(
@@ -650,22 +673,23 @@ pub(crate) fn mark_lig_pos(
                 [
                     (
                         "mark_coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                     (
                         "ligature_coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                     ("mark_class_count", u16be()),
                     (
                         "mark_array",
-                        util::read_phantom_view_offset16(vvar("table_view"), mark_array.call()),
+                        offset16.apply_views(vec![mark_array.call()], vec![vvar("table_view")]),
                     ),
                     (
                         "ligature_array",
-                        util::read_phantom_view_offset16(
-                            vvar("table_view"),
-                            ligature_array.invoke_args([var("mark_class_count")]),
+                        mark_class_offset16.apply_args_views(
+                            vec![ligature_array.invoke_args([var("mark_class_count")])],
+                            vec![var("mark_class_count")],
+                            vec![vvar("table_view")],
                         ),
                     ),
                 ],
@@ -756,6 +780,11 @@ pub(crate) fn mark_mark_pos(
     anchor_table: FormatRef,
     mark_array: FormatRef,
 ) -> FormatRef {
+    let mark_class_offset16 = util::phantom_offset16(
+        module,
+        vec![(Label::Borrowed("mark_class_count"), ValueType::U16)],
+    );
+    let offset16 = util::phantom_offset16(module, vec![]);
     let mark2_array = mark_mark::mark2_array(module, anchor_table);
     module.define_format(
         "opentype.layout.mark_mark_pos",
@@ -768,22 +797,23 @@ pub(crate) fn mark_mark_pos(
                     ("table_scope", reify_view(vvar("table_view"))),
                     (
                         "mark1_coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                     (
                         "mark2_coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                     ("mark_class_count", u16be()),
                     (
                         "mark1_array",
-                        util::read_phantom_view_offset16(vvar("table_view"), mark_array.call()),
+                        offset16.apply_views(vec![mark_array.call()], vec![vvar("table_view")]),
                     ),
                     (
                         "mark2_array",
-                        util::read_phantom_view_offset16(
-                            vvar("table_view"),
-                            mark2_array.invoke_args([var("mark_class_count")]),
+                        mark_class_offset16.apply_args_views(
+                            vec![mark2_array.invoke_args([var("mark_class_count")])],
+                            vec![var("mark_class_count")],
+                            vec![vvar("table_view")],
                         ),
                     ),
                 ],
@@ -851,6 +881,10 @@ pub(crate) fn ground_pos(
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/gpos#lookup-type-9-subtable-positioning-subtable-extension
 pub(crate) fn pos_extension(module: &mut FormatModule, ground_pos: DepFormat<1, 0>) -> FormatRef {
+    let extension_offset32 = util::phantom_offset32(
+        module,
+        vec![(Label::Borrowed("extension_lookup_type"), ValueType::U16)],
+    );
     module.define_format(
         "opentype.layout.pos_extension",
         let_view(
@@ -866,9 +900,10 @@ pub(crate) fn pos_extension(module: &mut FormatModule, ground_pos: DepFormat<1,
                     ),
                     (
                         "extension_offset",
-                        util::read_phantom_view_offset32(
-                            vvar("table_view"),
-                            ground_pos.invoke_args([var("extension_lookup_type")]),
+                        extension_offset32.apply_args_views(
+                            vec![ground_pos.invoke_args([var("extension_lookup_type")])],
+                            vec![var("extension_lookup_type")],
+                            vec![vvar("table_view")],
                         ),
                     ),
                 ],
@@ -889,16 +924,16 @@ pub(crate) fn table(
     feature_variations: FormatRef,
 ) -> FormatRef {
     let lookup_subtable = lookup_subtable(module, pos_extension, ground_pos);
-    let lookup_table = module.define_format(
-        "opentype.gpos.lookup_table",
-        layout::lookup_table(lookup_subtable),
-    );
-    let lookup_list = module.define_format(
-        "opentype.gpos.lookup_list",
-        layout::lookup_list(lookup_table),
+    let lookup_table = layout::lookup_table(module, lookup_subtable);
+    let lookup_table = module.define_format("opentype.gpos.lookup_table", lookup_table);
+    let lookup_list = layout::lookup_list(module, lookup_table);
+    let lookup_list = module.define_format("opentype.gpos.lookup_list", lookup_list);
+    let table = layout::table(
+        module,
+        script_list,
+        feature_list,
+        lookup_list,
+        feature_variations,
     );
-    module.define_format(
-        "opentype.gpos.table",
-        layout::table(script_list, feature_list, lookup_list, feature_variations),
-    )
+    module.define_format("opentype.gpos.table", table)
 }
@@ -33,6 +33,7 @@ pub(crate) fn lookup_subtable(
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/gsub#lookup-type-1-subtable-single-substitution
 pub(crate) fn single_subst(module: &mut FormatModule, coverage_table: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     // Single substitution format 1
     let format1 = module.define_format_views(
         "opentype.layout.single_subst.format1",
@@ -41,7 +42,7 @@ pub(crate) fn single_subst(module: &mut FormatModule, coverage_table: FormatRef)
             ("table_scope", reify_view(vvar("table_view"))),
             (
                 "coverage",
-                util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
             ),
             ("delta_glyph_id", i16be()),
         ]),
@@ -54,7 +55,7 @@ pub(crate) fn single_subst(module: &mut FormatModule, coverage_table: FormatRef)
             ("table_scope", reify_view(vvar("table_view"))),
             (
                 "coverage",
-                util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
             ),
             ("glyph_count", u16be()),
             (
@@ -98,6 +99,7 @@ pub(crate) fn single_subst(module: &mut FormatModule, coverage_table: FormatRef)
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/gsub#lookup-type-2-subtable-multiple-substitution
 pub(crate) fn multiple_subst(module: &mut FormatModule, coverage_table: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let sequence_table = module.define_format(
         "opentype.layout.multiple_subst.sequence_table",
         record([
@@ -119,7 +121,7 @@ pub(crate) fn multiple_subst(module: &mut FormatModule, coverage_table: FormatRe
                     ("subst_format", u16be()),
                     (
                         "coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                 ],
                 ("subst_format", 1),
@@ -156,6 +158,7 @@ pub(crate) fn multiple_subst(module: &mut FormatModule, coverage_table: FormatRe
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/gsub#lookup-type-3-subtable-alternate-substitution
 pub(crate) fn alternate_subst(module: &mut FormatModule, coverage_table: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let alternate_set = module.define_format(
         "opentype.gsub.alternate_subst.alternate_set",
         record([
@@ -176,7 +179,7 @@ pub(crate) fn alternate_subst(module: &mut FormatModule, coverage_table: FormatR
                     ("subst_format", u16be()),
                     (
                         "coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                 ],
                 ("subst_format", 1),
@@ -186,10 +189,8 @@ pub(crate) fn alternate_subst(module: &mut FormatModule, coverage_table: FormatR
                         "alternate_sets",
                         repeat_count(
                             var("alternate_set_count"),
-                            util::read_phantom_view_offset16(
-                                vvar("table_view"),
-                                alternate_set.call(),
-                            ),
+                            offset16
+                                .apply_views(vec![alternate_set.call()], vec![vvar("table_view")]),
                         ),
                     ),
                 ],
@@ -206,6 +207,7 @@ pub(crate) fn alternate_subst(module: &mut FormatModule, coverage_table: FormatR
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/gsub#lookup-type-4-subtable-ligature-substitution
 pub(crate) fn ligature_subst(module: &mut FormatModule, coverage_table: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let ligature_table = module.define_format(
         "opentype.gsub.ligature_subst.ligature_table",
         record([
@@ -228,7 +230,7 @@ pub(crate) fn ligature_subst(module: &mut FormatModule, coverage_table: FormatRe
                     "ligatures",
                     repeat_count(
                         var("ligature_count"),
-                        util::read_phantom_view_offset16(vvar("set_view"), ligature_table.call()),
+                        offset16.apply_views(vec![ligature_table.call()], vec![vvar("set_view")]),
                     ),
                 ),
             ]),
@@ -244,7 +246,7 @@ pub(crate) fn ligature_subst(module: &mut FormatModule, coverage_table: FormatRe
                     ("subst_format", u16be()),
                     (
                         "coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                 ],
                 ("subst_format", 1),
@@ -254,10 +256,8 @@ pub(crate) fn ligature_subst(module: &mut FormatModule, coverage_table: FormatRe
                         "ligature_sets",
                         repeat_count(
                             var("ligature_set_count"),
-                            util::read_phantom_view_offset16(
-                                vvar("table_view"),
-                                ligature_set.call(),
-                            ),
+                            offset16
+                                .apply_views(vec![ligature_set.call()], vec![vvar("table_view")]),
                         ),
                     ),
                 ],
@@ -277,6 +277,7 @@ pub(crate) fn reverse_chain_single_subst(
     module: &mut FormatModule,
     coverage_table: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     module.define_format(
         "opentype.layout.reverse_chain_single_subst",
         let_view(
@@ -288,17 +289,15 @@ pub(crate) fn reverse_chain_single_subst(
                     ("table_scope", reify_view(vvar("table_view"))),
                     (
                         "coverage",
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                     ("backtrack_glyph_count", u16be()),
                     (
                         "backtrack_coverage_tables",
                         repeat_count(
                             var("backtrack_glyph_count"),
-                            util::read_phantom_view_offset16(
-                                vvar("table_view"),
-                                coverage_table.call(),
-                            ),
+                            offset16
+                                .apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                         ),
                     ),
                     ("lookahead_glyph_count", u16be()),
@@ -306,10 +305,8 @@ pub(crate) fn reverse_chain_single_subst(
                         "lookahead_coverage_tables",
                         repeat_count(
                             var("lookahead_glyph_count"),
-                            util::read_phantom_view_offset16(
-                                vvar("table_view"),
-                                coverage_table.call(),
-                            ),
+                            offset16
+                                .apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                         ),
                     ),
                     ("glyph_count", u16be()),
@@ -373,6 +370,10 @@ pub(crate) fn subst_extension(
     module: &mut FormatModule,
     ground_subst: DepFormat<1, 0>,
 ) -> FormatRef {
+    let extension_offset32 = util::phantom_offset32(
+        module,
+        vec![(Label::Borrowed("extension_lookup_type"), ValueType::U16)],
+    );
     module.define_format(
         "opentype.layout.subst_extension",
         let_view(
@@ -388,9 +389,10 @@ pub(crate) fn subst_extension(
                     ),
                     (
                         "extension_offset",
-                        util::read_phantom_view_offset32(
-                            vvar("table_view"),
-                            ground_subst.invoke_args([var("extension_lookup_type")]),
+                        extension_offset32.apply_args_views(
+                            vec![ground_subst.invoke_args([var("extension_lookup_type")])],
+                            vec![var("extension_lookup_type")],
+                            vec![vvar("table_view")],
                         ),
                     ),
                 ],
@@ -411,16 +413,16 @@ pub(crate) fn table(
     feature_variations: FormatRef,
 ) -> FormatRef {
     let lookup_subtable = lookup_subtable(module, subst_extension, ground_subst);
-    let lookup_table = module.define_format(
-        "opentype.gsub.lookup_table",
-        layout::lookup_table(lookup_subtable),
+    let lookup_table = layout::lookup_table(module, lookup_subtable);
+    let lookup_table = module.define_format("opentype.gsub.lookup_table", lookup_table);
+    let lookup_list = layout::lookup_list(module, lookup_table);
+    let lookup_list = module.define_format("opentype.gsub.lookup_list", lookup_list);
+    let table = layout::table(
+        module,
+        script_list,
+        feature_list,
+        lookup_list,
+        feature_variations,
     );
-    let lookup_list = module.define_format(
-        "opentype.gsub.lookup_list",
-        layout::lookup_list(lookup_table),
-    );
-    module.define_format(
-        "opentype.gsub.table",
-        layout::table(script_list, feature_list, lookup_list, feature_variations),
-    )
+    module.define_format("opentype.gsub.table", table)
 }
@@ -173,6 +173,13 @@ fn offsets_array(is_long_offsets: Expr, glyph_count: Expr) -> Format {
 }
 
 pub(crate) fn table(module: &mut FormatModule) -> FormatRef {
+    let shared_tuples_offset32 = util::phantom_offset32(
+        module,
+        vec![
+            (Label::Borrowed("shared_tuple_count"), ValueType::U16),
+            (Label::Borrowed("axis_count"), ValueType::U16),
+        ],
+    );
     let gvar_flags = header_flags();
     let tuple_record = tuple_record(module);
     let glyph_variation_data_table = glyph_variation_data(module, tuple_record);
@@ -190,12 +197,13 @@ pub(crate) fn table(module: &mut FormatModule) -> FormatRef {
                 ("shared_tuple_count", u16be()),
                 (
                     "shared_tuples",
-                    util::read_phantom_view_offset32(
-                        vvar("table_view"),
-                        repeat_count(
+                    shared_tuples_offset32.apply_args_views(
+                        vec![repeat_count(
                             var("shared_tuple_count"),
                             tuple_record.invoke_args([var("axis_count")]),
-                        ),
+                        )],
+                        vec![var("shared_tuple_count"), var("axis_count")],
+                        vec![vvar("table_view")],
                     ),
                 ),
                 ("glyph_count", u16be()),
@@ -8,6 +8,7 @@ use super::*;
 /// requires `fvar` and `STAT` to be present.
 pub(crate) fn table(module: &mut FormatModule, item_variation_store: FormatRef) -> FormatRef {
     let dsim = delta_set_index_map(module);
+    let offset32 = util::phantom_offset32(module, vec![]);
     module.define_format(
         "opentype.hvar.table",
         let_view(
@@ -20,23 +21,21 @@ pub(crate) fn table(module: &mut FormatModule, item_variation_store: FormatRef)
                 // REVIEW[epic=validation] - this IVS must contain sufficient delta-sets that the maximum index found in any DSIM entry is in-bounds, but we cannot check this at this layer.
                 (
                     "item_variation_store",
-                    util::read_phantom_view_offset32(
-                        vvar("table_view"),
-                        item_variation_store.call(),
-                    ),
+                    offset32
+                        .apply_views(vec![item_variation_store.call()], vec![vvar("table_view")]),
                 ),
                 // NOTE - each of the three following offset-fields are specified 'may be NULL', and that glyph ids are to be used directly as indices in place of a DSIM for any which are not provided
                 (
                     "advance_width_mapping",
-                    util::read_phantom_view_offset32(vvar("table_view"), dsim.call()),
+                    offset32.apply_views(vec![dsim.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "lsb_mapping",
-                    util::read_phantom_view_offset32(vvar("table_view"), dsim.call()),
+                    offset32.apply_views(vec![dsim.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "rsb_mapping",
-                    util::read_phantom_view_offset32(vvar("table_view"), dsim.call()),
+                    offset32.apply_views(vec![dsim.call()], vec![vvar("table_view")]),
                 ),
             ]),
         ),
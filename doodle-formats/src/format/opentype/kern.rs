@@ -146,6 +146,7 @@ mod format2 {
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/kern#format-2
 fn subtable_format2(module: &mut FormatModule) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let class_table = format2::class_table(module);
     let kerning_array = format2::kerning_array(module);
     module.define_format(
@@ -157,11 +158,11 @@ fn subtable_format2(module: &mut FormatModule) -> FormatRef {
                 ("row_width", u16be()), // width (in bytes) of a table row
                 (
                     "left_class_offset",
-                    util::read_phantom_view_offset16(vvar("table_view"), class_table.call()),
+                    offset16.apply_views(vec![class_table.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "right_class_offset",
-                    util::read_phantom_view_offset16(vvar("table_view"), class_table.call()),
+                    offset16.apply_views(vec![class_table.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "kerning_array_offset",
@@ -76,6 +76,7 @@ mod chained_sequence {
         module: &mut FormatModule,
         sequence_lookup_record: FormatRef,
     ) -> FormatRef {
+        let offset16 = util::phantom_offset16(module, vec![]);
         let chained_sequence_rule = chained_sequence_rule(module, sequence_lookup_record);
         module.define_format(
             "opentype.layout.chained-sequence-rule-set",
@@ -88,9 +89,9 @@ mod chained_sequence {
                         "chained_seq_rules",
                         repeat_count(
                             var("chained_seq_rule_count"),
-                            util::read_phantom_view_offset16(
-                                vvar("table_view"),
-                                chained_sequence_rule.call(),
+                            offset16.apply_views(
+                                vec![chained_sequence_rule.call()],
+                                vec![vvar("table_view")],
                             ),
                         ),
                     ),
@@ -148,6 +149,7 @@ mod chained_sequence {
         coverage_table: FormatRef,
         rule_set: FormatRef,
     ) -> FormatRef {
+        let offset16 = util::phantom_offset16(module, vec![]);
         module.define_format_views(
             "opentype.layout.chained-sequence-context.format1",
             vec![(Label::Borrowed("table_view"))],
@@ -155,14 +157,14 @@ mod chained_sequence {
                 // REVIEW[epic=nested-format-reify-layer] - OUTER
                 (
                     "coverage",
-                    util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                    offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                 ),
                 ("chained_seq_rule_set_count", u16be()),
                 (
                     "chained_seq_rule_sets",
                     repeat_count(
                         var("chained_seq_rule_set_count"),
-                        util::read_phantom_view_offset16(vvar("table_view"), rule_set.call()),
+                        offset16.apply_views(vec![rule_set.call()], vec![vvar("table_view")]),
                     ),
                 ),
             ]),
@@ -178,6 +180,7 @@ mod chained_sequence {
         coverage_table: FormatRef,
         rule_set: FormatRef,
     ) -> FormatRef {
+        let offset16 = util::phantom_offset16(module, vec![]);
         module.define_format_views(
             "opentype.layout.chained-sequence-context.format2",
             vec![(Label::Borrowed("table_view"))],
@@ -185,26 +188,26 @@ mod chained_sequence {
                 // REVIEW[epic=nested-format-reify-layer] - OUTER
                 (
                     "coverage",
-                    util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                    offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "backtrack_class_def",
-                    util::read_phantom_view_offset16(vvar("table_view"), class_def.call()),
+                    offset16.apply_views(vec![class_def.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "input_class_def",
-                    util::read_phantom_view_offset16(vvar("table_view"), class_def.call()),
+                    offset16.apply_views(vec![class_def.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "lookahead_class_def",
-                    util::read_phantom_view_offset16(vvar("table_view"), class_def.call()),
+                    offset16.apply_views(vec![class_def.call()], vec![vvar("table_view")]),
                 ),
                 ("chained_class_seq_rule_set_count", u16be()),
                 (
                     "chained_class_seq_rule_sets",
                     repeat_count(
                         var("chained_class_seq_rule_set_count"),
-                        util::read_phantom_view_offset16(vvar("table_view"), rule_set.call()),
+                        offset16.apply_views(vec![rule_set.call()], vec![vvar("table_view")]),
                     ),
                 ),
             ]),
@@ -219,6 +222,7 @@ mod chained_sequence {
         coverage_table: FormatRef,
         sequence_lookup_record: FormatRef,
     ) -> FormatRef {
+        let offset16 = util::phantom_offset16(module, vec![]);
         module.define_format_views(
             "opentype.layout.chained-sequence-context.format3",
             vec![(Label::Borrowed("table_view"))],
@@ -229,7 +233,7 @@ mod chained_sequence {
                     "backtrack_coverages",
                     repeat_count(
                         var("backtrack_glyph_count"),
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                 ),
                 ("input_glyph_count", u16be()),
@@ -237,7 +241,7 @@ mod chained_sequence {
                     "input_coverages",
                     repeat_count(
                         var("input_glyph_count"),
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                 ),
                 ("lookahead_glyph_count", u16be()),
@@ -245,7 +249,7 @@ mod chained_sequence {
                     "lookahead_coverages",
                     repeat_count(
                         var("lookahead_glyph_count"),
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                 ),
                 ("seq_lookup_count", u16be()),
@@ -331,6 +335,7 @@ mod sequence {
         module: &mut FormatModule,
         sequence_lookup_record: FormatRef,
     ) -> FormatRef {
+        let offset16 = util::phantom_offset16(module, vec![]);
         let rule = rule(module, sequence_lookup_record);
         module.define_format(
             "opentype.layout.sequence-context.rule-set",
@@ -343,7 +348,7 @@ mod sequence {
                         "rules",
                         repeat_count(
                             var("rule_count"),
-                            util::read_phantom_view_offset16(vvar("table_view"), rule.call()),
+                            offset16.apply_views(vec![rule.call()], vec![vvar("table_view")]),
                         ),
                     ),
                 ]),
@@ -356,20 +361,21 @@ mod sequence {
         coverage_table: FormatRef,
         rule_set: FormatRef,
     ) -> FormatRef {
+        let offset16 = util::phantom_offset16(module, vec![]);
         module.define_format_views(
             "opentype.layout.sequence-context.format1",
             vec![(Label::Borrowed("table_view"))],
             record([
                 (
                     "coverage",
-                    util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                    offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                 ),
                 ("seq_rule_set_count", u16be()),
                 (
                     "seq_rule_sets",
                     repeat_count(
                         var("seq_rule_set_count"),
-                        util::read_phantom_view_offset16(vvar("table_view"), rule_set.call()),
+                        offset16.apply_views(vec![rule_set.call()], vec![vvar("table_view")]),
                     ),
                 ),
             ]),
@@ -382,24 +388,25 @@ mod sequence {
         coverage_table: FormatRef,
         rule_set: FormatRef,
     ) -> FormatRef {
+        let offset16 = util::phantom_offset16(module, vec![]);
         module.define_format_views(
             "opentype.layout.sequence-context.format2",
             vec![(Label::Borrowed("table_view"))],
             record([
                 (
                     "coverage",
-                    util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                    offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                 ),
                 (
                     "class_def",
-                    util::read_phantom_view_offset16(vvar("table_view"), class_def.call()),
+                    offset16.apply_views(vec![class_def.call()], vec![vvar("table_view")]),
                 ),
                 ("class_seq_rule_set_count", u16be()),
                 (
                     "class_seq_rule_sets",
                     repeat_count(
                         var("class_seq_rule_set_count"),
-                        util::read_phantom_view_offset16(vvar("table_view"), rule_set.call()),
+                        offset16.apply_views(vec![rule_set.call()], vec![vvar("table_view")]),
                     ),
                 ),
             ]),
@@ -411,6 +418,7 @@ mod sequence {
         coverage_table: FormatRef,
         sequence_lookup_record: FormatRef,
     ) -> FormatRef {
+        let offset16 = util::phantom_offset16(module, vec![]);
         module.define_format_views(
             "opentype.layout.sequence-context.format3",
             vec![(Label::Borrowed("table_view"))],
@@ -421,7 +429,7 @@ mod sequence {
                     "coverage_tables",
                     repeat_count(
                         var("glyph_count"),
-                        util::read_phantom_view_offset16(vvar("table_view"), coverage_table.call()),
+                        offset16.apply_views(vec![coverage_table.call()], vec![vvar("table_view")]),
                     ),
                 ),
                 (
@@ -469,6 +477,7 @@ fn feature_record(
     tag: FormatRef,
     feature_table: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     module.define_format_views(
         "opentype.layout.feature_record",
         vec![Label::Borrowed("list_view")],
@@ -476,7 +485,7 @@ fn feature_record(
             ("feature_tag", tag.call()),
             (
                 "feature",
-                util::read_phantom_view_offset16(vvar("list_view"), feature_table.call()),
+                offset16.apply_views(vec![feature_table.call()], vec![vvar("list_view")]),
             ),
         ]),
     )
@@ -509,6 +518,7 @@ pub(crate) fn feature_table(module: &mut FormatModule) -> FormatRef {
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/chapter2#scriptlist-table
 fn script_record(module: &mut FormatModule, tag: FormatRef, script_table: FormatRef) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     module.define_format_views(
         "opentype.layout.script_record",
         vec![Label::Borrowed("table_view")],
@@ -516,7 +526,7 @@ fn script_record(module: &mut FormatModule, tag: FormatRef, script_table: Format
             ("script_tag", tag.call()),
             (
                 "script",
-                util::read_phantom_view_offset16(vvar("table_view"), script_table.call()),
+                offset16.apply_views(vec![script_table.call()], vec![vvar("table_view")]),
             ),
         ]),
     )
@@ -558,6 +568,7 @@ pub(crate) fn script_table(
     tag: FormatRef,
     lang_sys: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let lang_sys_record = lang_sys_record(module, tag, lang_sys);
     module.define_format(
         "opentype.layout.script_table",
@@ -567,7 +578,7 @@ pub(crate) fn script_table(
                 ("script_scope", reify_view(vvar("script_view"))),
                 (
                     "default_lang_sys",
-                    util::read_phantom_view_offset16(vvar("script_view"), lang_sys.call()),
+                    offset16.apply_views(vec![lang_sys.call()], vec![vvar("script_view")]),
                 ),
                 ("lang_sys_count", u16be()),
                 (
@@ -642,6 +653,7 @@ pub(crate) fn value_record(
     device_or_variation_index_table: FormatRef,
     vf_flags_type: ValueType,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let opt_field = |field_name: &'static str, format: Format| {
         (
             field_name,
@@ -659,30 +671,30 @@ pub(crate) fn value_record(
             opt_field("y_advance", i16be()),
             opt_field(
                 "x_placement_device",
-                util::read_phantom_view_offset16(
-                    vvar("table_view"),
-                    device_or_variation_index_table.call(),
+                offset16.apply_views(
+                    vec![device_or_variation_index_table.call()],
+                    vec![vvar("table_view")],
                 ),
             ),
             opt_field(
                 "y_placement_device",
-                util::read_phantom_view_offset16(
-                    vvar("table_view"),
-                    device_or_variation_index_table.call(),
+                offset16.apply_views(
+                    vec![device_or_variation_index_table.call()],
+                    vec![vvar("table_view")],
                 ),
             ),
             opt_field(
                 "x_advance_device",
-                util::read_phantom_view_offset16(
-                    vvar("table_view"),
-                    device_or_variation_index_table.call(),
+                offset16.apply_views(
+                    vec![device_or_variation_index_table.call()],
+                    vec![vvar("table_view")],
                 ),
             ),
             opt_field(
                 "y_advance_device",
-                util::read_phantom_view_offset16(
-                    vvar("table_view"),
-                    device_or_variation_index_table.call(),
+                offset16.apply_views(
+                    vec![device_or_variation_index_table.call()],
+                    vec![vvar("table_view")],
                 ),
             ),
         ]),
@@ -697,6 +709,7 @@ pub(crate) fn lang_sys_record(
     tag: FormatRef,
     lang_sys: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     module.define_format_views(
         "opentype.layout.lang_sys_record",
         vec![(Label::Borrowed("script_view"))],
@@ -704,7 +717,7 @@ pub(crate) fn lang_sys_record(
             ("lang_sys_tag", tag.call()),
             (
                 "lang_sys",
-                util::read_phantom_view_offset16(vvar("script_view"), lang_sys.call()),
+                offset16.apply_views(vec![lang_sys.call()], vec![vvar("script_view")]),
             ),
         ]),
     )
@@ -733,6 +746,7 @@ pub(crate) fn anchor_table(
     module: &mut FormatModule,
     device_or_variation_index_table: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     // REVIEW - should formats 1 and 2 be defined as well?
     let anchor_format1 = record([("x_coordinate", i16be()), ("y_coordinate", i16be())]);
     let anchor_format2 = record([
@@ -751,16 +765,16 @@ pub(crate) fn anchor_table(
             // REVIEW - each offset below is individually nullable if the other is set, but it may be invalid for them to both be null simultaneously...?
             (
                 "x_device",
-                util::read_phantom_view_offset16(
-                    vvar("table_view"),
-                    device_or_variation_index_table.call(),
+                offset16.apply_views(
+                    vec![device_or_variation_index_table.call()],
+                    vec![vvar("table_view")],
                 ),
             ),
             (
                 "y_device",
-                util::read_phantom_view_offset16(
-                    vvar("table_view"),
-                    device_or_variation_index_table.call(),
+                offset16.apply_views(
+                    vec![device_or_variation_index_table.call()],
+                    vec![vvar("table_view")],
                 ),
             ),
         ]),
@@ -795,6 +809,7 @@ pub(crate) fn anchor_table(
 
 /// Feature Variation Record
 fn feature_variation_record(module: &mut FormatModule, feature_table: FormatRef) -> FormatRef {
+    let offset32 = util::phantom_offset32(module, vec![]);
     let condition_table = util::embedded_singleton_alternation(
         [("format", u16be())],
         ("format", 1),
@@ -873,13 +888,13 @@ fn feature_variation_record(module: &mut FormatModule, feature_table: FormatRef)
         record([
             (
                 "condition_set",
-                util::read_phantom_view_offset32(vvar("table_view"), condition_set),
+                offset32.apply_views(vec![condition_set], vec![vvar("table_view")]),
             ),
             (
                 "feature_table_substitution",
-                util::read_phantom_view_offset32(
-                    vvar("table_view"),
-                    feature_table_substitution.call(),
+                offset32.apply_views(
+                    vec![feature_table_substitution.call()],
+                    vec![vvar("table_view")],
                 ),
             ),
         ]),
@@ -914,7 +929,11 @@ pub(crate) fn feature_variations(module: &mut FormatModule, feature_table: Forma
 }
 
 /// Format-factory taking a `{GPOS,GSUB}`-specific `lookup_subtable` and constructing the shape of a LookupTable around it
-pub(crate) fn lookup_table(lookup_subtable: DepFormat<1, 0>) -> Format {
+pub(crate) fn lookup_table(module: &mut FormatModule, lookup_subtable: DepFormat<1, 0>) -> Format {
+    let lookup_offset16 = util::phantom_offset16(
+        module,
+        vec![(Label::Borrowed("lookup_type"), ValueType::U16)],
+    );
     // NOTE - tag is a model-external value, lookup-type is model-internal.
 
     let lookup_flag = {
@@ -948,9 +967,10 @@ pub(crate) fn lookup_table(lookup_subtable: DepFormat<1, 0>) -> Format {
                 "subtables",
                 repeat_count(
                     var("sub_table_count"),
-                    util::read_phantom_view_offset16(
-                        vvar("table_view"),
-                        lookup_subtable.invoke_args([var("lookup_type")]),
+                    lookup_offset16.apply_args_views(
+                        vec![lookup_subtable.invoke_args([var("lookup_type")])],
+                        vec![var("lookup_type")],
+                        vec![vvar("table_view")],
                     ),
                 ),
             ),
@@ -971,7 +991,8 @@ pub(crate) fn lookup_table(lookup_subtable: DepFormat<1, 0>) -> Format {
 /// Takes `lookup_table` as a GPOS/GSUB-specific definition of LookupTable (via [`lookup_table`])
 ///
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/chapter2#lookuplist-table
-pub(crate) fn lookup_list(lookup_table: FormatRef) -> Format {
+pub(crate) fn lookup_list(module: &mut FormatModule, lookup_table: FormatRef) -> Format {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let_view(
         "list_view",
         record([
@@ -981,7 +1002,7 @@ pub(crate) fn lookup_list(lookup_table: FormatRef) -> Format {
                 "lookups",
                 repeat_count(
                     var("lookup_count"),
-                    util::read_phantom_view_offset16(vvar("list_view"), lookup_table.call()),
+                    offset16.apply_views(vec![lookup_table.call()], vec![vvar("list_view")]),
                 ),
             ),
         ]),
@@ -990,11 +1011,14 @@ pub(crate) fn lookup_list(lookup_table: FormatRef) -> Format {
 
 /// Factory funtion used for defining GPOS and GSUB table-formats
 pub(crate) fn table(
+    module: &mut FormatModule,
     script_list: FormatRef,
     feature_list: FormatRef,
     lookup_list: FormatRef,
     feature_variations: FormatRef,
 ) -> Format {
+    let offset16 = util::phantom_offset16(module, vec![]);
+    let offset32 = util::phantom_offset32(module, vec![]);
     let_view(
         "table_view",
         record([
@@ -1003,21 +1027,21 @@ pub(crate) fn table(
             ("minor_version", u16be()),
             (
                 "script_list",
-                util::read_phantom_view_offset16(vvar("table_view"), script_list.call()),
+                offset16.apply_views(vec![script_list.call()], vec![vvar("table_view")]),
             ),
             (
                 "feature_list",
-                util::read_phantom_view_offset16(vvar("table_view"), feature_list.call()),
+                offset16.apply_views(vec![feature_list.call()], vec![vvar("table_view")]),
             ),
             (
                 "lookup_list",
-                util::read_phantom_view_offset16(vvar("table_view"), lookup_list.call()),
+                offset16.apply_views(vec![lookup_list.call()], vec![vvar("table_view")]),
             ),
             (
                 "feature_variations_offset",
                 cond_maybe(
                     expr_gt(var("minor_version"), Expr::U16(0)), // Since Major == 1 by assertion, minor > 0 implies v1.1 or (as yet unimplemented) greater
-                    util::read_phantom_view_offset32(vvar("table_view"), feature_variations.call()),
+                    offset32.apply_views(vec![feature_variations.call()], vec![vvar("table_view")]),
                 ),
             ),
         ]),
@@ -5,6 +5,7 @@ pub(crate) fn table(
     tag: FormatRef,
     item_variation_store: FormatRef,
 ) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let value_record = value_record(module, tag);
     module.define_format(
         "opentype.mvar.table",
@@ -20,10 +21,8 @@ pub(crate) fn table(
                 // NOTE - `value_record_count == 0` iff `item_variation_store.offset == 0`
                 (
                     "item_variation_store",
-                    util::read_phantom_view_offset16(
-                        vvar("table_view"),
-                        item_variation_store.call(),
-                    ),
+                    offset16
+                        .apply_views(vec![item_variation_store.call()], vec![vvar("table_view")]),
                 ),
                 // NOTE - the spec indicates that the value-record-size field must be used to determine the size of each value record, to allow for future expansion
                 (
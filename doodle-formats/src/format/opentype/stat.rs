@@ -2,6 +2,14 @@ use super::*;
 
 /// C.f. https://learn.microsoft.com/en-us/typography/opentype/spec/stat#style-attributes-header
 pub(crate) fn table(module: &mut FormatModule, tag: FormatRef) -> FormatRef {
+    let axis_values_offset32 = util::phantom_offset32(
+        module,
+        vec![(Label::Borrowed("axis_value_count"), ValueType::U16)],
+    );
+    let design_axes_offset32 = util::phantom_offset32(
+        module,
+        vec![(Label::Borrowed("design_axis_count"), ValueType::U16)],
+    );
     let design_axes_array = design_axes_array(module, tag);
     let axis_value_array = axis_value_array(module);
     module.define_format(
@@ -16,17 +24,19 @@ pub(crate) fn table(module: &mut FormatModule, tag: FormatRef) -> FormatRef {
                 ("design_axis_count", u16be()), // number of axis records
                 (
                     "design_axes",
-                    util::read_phantom_view_offset32(
-                        vvar("table_view"),
-                        design_axes_array.call_args(vec![var("design_axis_count")]),
+                    design_axes_offset32.apply_args_views(
+                        vec![design_axes_array.call_args(vec![var("design_axis_count")])],
+                        vec![var("design_axis_count")],
+                        vec![vvar("table_view")],
                     ),
                 ), // offset is 0 iff design_axis_count is 0
                 ("axis_value_count", u16be()),
                 (
                     "axis_value_offsets",
-                    util::read_phantom_view_offset32(
-                        vvar("table_view"),
-                        axis_value_array.call_args(vec![var("axis_value_count")]),
+                    axis_values_offset32.apply_args_views(
+                        vec![axis_value_array.call_args(vec![var("axis_value_count")])],
+                        vec![var("axis_value_count")],
+                        vec![vvar("table_view")],
                     ),
                 ), // offset is 0 iff axis_value_count is 0
                 ("elided_fallback_name_id", u16be()), // omitted in version 1.0, but said version is deprecated
@@ -52,6 +62,7 @@ fn design_axes_array(module: &mut FormatModule, tag: FormatRef) -> FormatRef {
 }
 
 fn axis_value_array(module: &mut FormatModule) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let axis_value_table = axis_value_table(module);
     module.define_format_args(
         "opentype.stat.axis_value_array",
@@ -64,10 +75,8 @@ fn axis_value_array(module: &mut FormatModule) -> FormatRef {
                     "axis_values",
                     repeat_count(
                         var("axis_value_count"),
-                        util::read_phantom_view_offset16(
-                            vvar("array_view"),
-                            axis_value_table.call(),
-                        ),
+                        offset16
+                            .apply_views(vec![axis_value_table.call()], vec![vvar("array_view")]),
                     ),
                 ),
             ]),
@@ -58,6 +58,7 @@ fn svg_document_list(module: &mut FormatModule, text_or_ztext: FormatRef) -> For
 }
 
 pub(crate) fn table(module: &mut FormatModule, text_or_ztext: FormatRef) -> FormatRef {
+    let offset32 = util::phantom_offset32(module, vec![]);
     let svg_document_list = svg_document_list(module, text_or_ztext);
     module.define_format(
         "opentype.svg.table",
@@ -68,7 +69,7 @@ pub(crate) fn table(module: &mut FormatModule, text_or_ztext: FormatRef) -> Form
                 ("version", expect_u16be(0)),
                 (
                     "svg_document_list",
-                    read_phantom_view_offset32(vvar("table_view"), svg_document_list.call()),
+                    offset32.apply_views(vec![svg_document_list.call()], vec![vvar("table_view")]),
                 ),
                 ("__reserved", expect_eq(u32be(), poly_zero())),
             ]),
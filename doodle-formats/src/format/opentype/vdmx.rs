@@ -1,6 +1,7 @@
 use super::*;
 
 pub(crate) fn table(module: &mut FormatModule) -> FormatRef {
+    let offset16 = util::phantom_offset16(module, vec![]);
     let vdmx_group = vdmx_group(module);
     let ratio_range = record_repeat(
         ["b_char_set", "x_ratio", "y_start_ratio", "y_end_ratio"],
@@ -24,7 +25,7 @@ pub(crate) fn table(module: &mut FormatModule) -> FormatRef {
                     repeat_count(
                         // NOTE - the specification uses `numRatios` as the array-length, and not `numRecs` as might otherwise be expected
                         var("num_ratios"),
-                        util::read_phantom_view_offset16(vvar("table_view"), vdmx_group.call()),
+                        offset16.apply_views(vec![vdmx_group.call()], vec![vvar("table_view")]),
                     ),
                 ),
             ]),
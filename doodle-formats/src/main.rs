@@ -3,15 +3,20 @@
 
 use anyhow::{Result as AResult, anyhow};
 use doodle::Format;
-use doodle::codegen::{ToFragment, generate_code};
+use doodle::codegen::build::write_if_changed;
+use doodle::codegen::{CodeGenOptions, ToFragment, generate_code_with};
 use std::collections::BTreeMap;
 use std::fs;
+use std::io::Read;
 use std::path::PathBuf;
 
 use clap::{Parser, ValueEnum};
 use doodle::FormatModule;
 use doodle::decoder::Compiler;
-use doodle::read::ReadCtxt;
+use doodle::decoder::stream::{StreamDecoder, streamed_format};
+use doodle::decoder::vm::VmProgram;
+use doodle::match_check::check_matches;
+use doodle::read::{Input, ReadCtxt};
 use doodle::typecheck;
 
 mod format;
@@ -47,23 +52,54 @@ enum Command {
         output: FormatOutput,
         #[arg(long, default_value = None)]
         dest: Option<PathBuf>,
-        #[arg(long)]
+        #[arg(long, conflicts_with_all = ["roots", "lazy"])]
         png_tag_only: bool,
+        /// Emit public fields and `decode_*` entry-points in generated Rust code
+        #[arg(long)]
+        pub_api: bool,
+        /// Derive `serde` traits on generated types, behind a `serde` cargo feature
+        #[arg(long)]
+        serde: bool,
+        /// Only generate Rust code for the formats reachable from the named root (may be repeated)
+        #[arg(long = "root", value_name = "NAME")]
+        roots: Vec<String>,
+        /// Defer the parses of the named format at offsets in generated Rust code (may be repeated)
+        #[arg(long = "lazy", value_name = "NAME")]
+        lazy: Vec<String>,
     },
     /// Decode a binary file
     File {
         /// How decoded values are rendered
         #[arg(long, default_value = "tree")]
         output: FileOutput,
-        /// The binary file to decode
+        /// The binary file to decode (`-` for standard input, when streaming)
         filename: PathBuf,
         #[arg(long)]
         trace: bool,
         #[arg(long, default_value = None)]
         as_format: Option<String>,
+        /// Decode successive values of the format (or the elements of a top-level repetition) as the input is read,
+        /// rather than loading it all up front
+        #[arg(long, conflicts_with = "trace")]
+        stream: bool,
+        /// Memory-map the input file instead of reading it into memory (the file must not be modified while
+        /// it is being decoded)
+        #[arg(long, conflicts_with = "stream")]
+        mmap: bool,
+        /// Reuse the outcome of repeated attempts to decode the same format at the same position (reporting
+        /// statistics under `--trace`)
+        #[arg(long)]
+        memoize: bool,
+        /// Decode using the bytecode VM rather than the tree-walking interpreter
+        #[arg(long, conflicts_with_all = ["trace", "stream", "memoize"])]
+        vm: bool,
     },
     /// Typecheck the main FormatModule
-    TypeCheck,
+    TypeCheck {
+        /// Treat non-exhaustive matches and unreachable match arms as errors rather than warnings
+        #[arg(long)]
+        deny_matches: bool,
+    },
     Census,
 }
 
@@ -151,7 +187,12 @@ fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
             output,
             dest,
             png_tag_only,
+            pub_api,
+            serde,
+            roots,
+            lazy,
         } => {
+            let mut options = CodeGenOptions::new().pub_api(pub_api).serde(serde);
             if png_tag_only {
                 let mut module = FormatModule::new();
                 let format = format::png::png_tag(&mut module).call();
@@ -161,19 +202,27 @@ fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
                         serde_json::to_writer(std::io::stdout(), &module).unwrap()
                     }
                     FormatOutput::Rust => {
-                        print_generated_code(&module, &format, dest);
+                        print_generated_code(&module, &format, dest, options)?;
                     }
                 }
             } else {
                 let mut module = FormatModule::new();
-                let format = format::main(&mut module).call();
+                let mut format = format::main(&mut module).call();
+                if let Some(first) = roots.first() {
+                    format = module
+                        .find_format(first)
+                        .ok_or_else(|| anyhow!("Unknown root format `{first}`"))?
+                        .call();
+                    options = options.roots(roots);
+                }
+                options = options.lazy(lazy);
                 match output {
                     FormatOutput::Debug => println!("{module:?}"),
                     FormatOutput::Json => {
                         serde_json::to_writer(std::io::stdout(), &module).unwrap()
                     }
                     FormatOutput::Rust => {
-                        print_generated_code(&module, &format, dest);
+                        print_generated_code(&module, &format, dest, options)?;
                     }
                 }
             }
@@ -184,6 +233,10 @@ fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
             filename,
             trace,
             as_format,
+            stream,
+            mmap,
+            memoize,
+            vm,
         } => {
             let mut module = FormatModule::new();
             let format = match as_format {
@@ -259,46 +312,101 @@ fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
                     }
                 }
             };
-            let program = Compiler::compile_program(&module, &format)?;
+            let program = Compiler::compile_program(&module, &format)?.with_memoization(memoize);
+
+            if stream {
+                let reader: Box<dyn Read> = if filename.as_os_str() == "-" {
+                    Box::new(std::io::stdin().lock())
+                } else {
+                    Box::new(fs::File::open(filename)?)
+                };
+                let format = streamed_format(&module, &format);
+                for value in StreamDecoder::new(&program, reader)? {
+                    let value = value?;
+                    match output {
+                        FileOutput::Debug => println!("{value:?}"),
+                        FileOutput::Json => {
+                            serde_json::to_writer(std::io::stdout(), &value).unwrap();
+                            println!();
+                        }
+                        FileOutput::Tree => {
+                            doodle::output::tree::print_decoded_value(&module, &value, format)
+                        }
+                        FileOutput::Flat => {
+                            doodle::output::flat::print_decoded_value(&module, &value, format)
+                        }
+                    }
+                }
+                return Ok(());
+            }
 
-            let input = fs::read(filename)?;
+            let input = if mmap {
+                // SAFETY - the input file must not be modified while it is being decoded, as documented for `--mmap`
+                unsafe { Input::map(filename)? }
+            } else {
+                Input::read(filename)?
+            };
+            let vm_program = if vm {
+                Some(VmProgram::compile(&program)?)
+            } else {
+                None
+            };
+            let decode = |input: &[u8]| match &vm_program {
+                Some(vm_program) => vm_program.run(ReadCtxt::new(input)).map(|(v, _)| v),
+                None => program.run(ReadCtxt::new(input)).map(|(v, _)| v),
+            };
+            let decode_with_loc = |input: &[u8]| {
+                let (res, stats) = program.run_with_loc_stats(ReadCtxt::new(input));
+                if memoize {
+                    eprintln!("memoization: {stats}");
+                }
+                res.map(|(p_value, _)| p_value)
+            };
             match output {
                 FileOutput::Debug => {
-                    let (value, _) = program.run(ReadCtxt::new(&input))?;
+                    let value = decode(&input)?;
                     println!("{value:?}");
                 }
                 FileOutput::Json => {
                     if trace {
-                        let (p_value, _) = program.run_with_loc(ReadCtxt::new(&input))?;
+                        let p_value = decode_with_loc(&input)?;
                         serde_json::to_writer(std::io::stdout(), &p_value).unwrap()
                     } else {
-                        let (value, _) = program.run(ReadCtxt::new(&input))?;
+                        let value = decode(&input)?;
                         serde_json::to_writer(std::io::stdout(), &value).unwrap()
                     }
                 }
                 FileOutput::Tree => {
                     if trace {
-                        let (p_value, _) = program.run_with_loc(ReadCtxt::new(&input))?;
+                        let p_value = decode_with_loc(&input)?;
                         doodle::output::tree::print_parsed_decoded_value(
                             &module, &p_value, &format,
                         );
                     } else {
-                        let (value, _) = program.run(ReadCtxt::new(&input))?;
+                        let value = decode(&input)?;
                         doodle::output::tree::print_decoded_value(&module, &value, &format);
                     }
                 }
                 FileOutput::Flat => {
-                    let (value, _) = program.run(ReadCtxt::new(&input))?;
+                    let value = decode(&input)?;
                     doodle::output::flat::print_decoded_value(&module, &value, &format);
                 }
             }
-
             Ok(())
         }
-        Command::TypeCheck => {
+        Command::TypeCheck { deny_matches } => {
             let mut module = FormatModule::new();
             let _top_format = format::main(&mut module);
-            Ok(check_all(&module)?)
+            check_all(&module)?;
+            let diagnostics = check_matches(&module);
+            let level = if deny_matches { "error" } else { "warning" };
+            for diag in diagnostics.iter() {
+                eprintln!("{level}: {diag}");
+            }
+            if deny_matches && !diagnostics.is_empty() {
+                return Err(anyhow!("{} problem(s) found in matches", diagnostics.len()).into());
+            }
+            Ok(())
         }
     }
 }
@@ -320,15 +428,17 @@ fn print_generated_code(
     module: &FormatModule,
     top_format: &Format,
     dest: Option<std::path::PathBuf>,
-) {
-    let content = generate_code(module, top_format);
+    options: CodeGenOptions,
+) -> AResult<()> {
+    let content =
+        generate_code_with(module, top_format, options).map_err(|err| anyhow!("{err}"))?;
 
     fn write_to(mut f: impl std::io::Write, content: impl ToFragment) -> std::io::Result<()> {
         write!(f, "{}", content.to_fragment())
     }
 
     match dest {
-        None => write_to(std::io::stdout().lock(), content).expect("failed to write"),
+        None => write_to(std::io::stdout().lock(), content)?,
         Some(path) => {
             if !path.exists()
                 || (path.is_file()
@@ -336,16 +446,16 @@ fn print_generated_code(
                         .file_name()
                         .is_some_and(|s| s.to_string_lossy().contains("gencode")))
             {
-                let f = std::fs::File::create(path).unwrap_or_else(|err| panic!("error: {err}"));
-                write_to(f, content).expect("failed to write");
+                write_if_changed(path, &content.to_fragment().to_string())?;
             } else {
-                panic!(
+                return Err(anyhow!(
                     "will not overwrite directory or protected file: {}",
                     path.to_string_lossy()
-                );
+                ));
             }
         }
     }
+    Ok(())
 }
 
 #[test]
@@ -353,7 +463,7 @@ fn print_generated_code(
 fn test_codegen() {
     let mut module = FormatModule::new();
     let format = format::main(&mut module).call();
-    let _ = generate_code(&module, &format);
+    let _ = doodle::codegen::generate_code(&module, &format);
 }
 
 #[test]
@@ -400,6 +510,11 @@ mod census {
                 let format = module.get_format(*level);
                 crawl(format, module, pop);
             }
+            Format::GenericVar(index, formats, ..) => {
+                pop.add_format("GenericVar");
+                let format = module.get_generic(*index).instantiate(formats);
+                crawl(&format, module, pop);
+            }
             Format::Fail => (),
             Format::EndOfInput => (),
             Format::Align(_) => (),
@@ -491,6 +606,7 @@ mod census {
             Format::Apply(..) => (),
             Format::Pos => (),
             Format::SkipRemainder => (),
+            Format::Native(..) => pop.add_format("Native"),
             Format::DecodeBytes(.., format) => {
                 pop.add_format("DecodeBytes");
                 crawl(format, module, pop);
@@ -500,6 +616,10 @@ mod census {
                 crawl(f1, module, pop);
             }
             Format::Permit(format, ..) | Format::Hint(.., format) => crawl(format, module, pop),
+            Format::Resync(format, _) => {
+                pop.add_format("Resync");
+                crawl(format, module, pop);
+            }
             #[cfg(feature = "format_enforce")]
             Format::Enforce(format) => crawl(format, module, pop),
             Format::LiftedOption(Some(format)) => crawl(format, module, pop),
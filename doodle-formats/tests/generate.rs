@@ -0,0 +1,75 @@
+//! Tests for the random input generator
+//!
+//! Inputs are generated for the top-level format, and checked against the interpreter to ensure that they
+//! are decoded as the branch they were generated for.
+
+use doodle::decoder::{Compiler, Value};
+use doodle::generate::{GenConfig, Generator};
+use doodle::read::ReadCtxt;
+use doodle::{Format, FormatModule, Label};
+
+const CONFIG: GenConfig = GenConfig {
+    seed: 0,
+    max_size: 4096,
+    max_depth: 64,
+    max_attempts: 64,
+};
+
+fn main_format() -> (FormatModule, Format) {
+    let mut module = FormatModule::new();
+    let format = doodle_formats::format::main(&mut module).call();
+    (module, format)
+}
+
+/// Returns the label of the top-level branch that `value` was decoded as.
+fn data_label(value: &Value) -> &Label {
+    let Value::Record(fields) = value else {
+        panic!("expected record, found {value:?}")
+    };
+    match fields.iter().find(|(label, _)| label == "data") {
+        Some((_, Value::Branch(_, data))) => match data.as_ref() {
+            Value::Variant(label, _) => label,
+            other => panic!("expected variant, found {other:?}"),
+        },
+        other => panic!("expected branch, found {other:?}"),
+    }
+}
+
+#[track_caller]
+fn check_branch_example(index: usize, expected: &str) {
+    let (module, format) = main_format();
+    let mut generator = Generator::new(&module, &format, CONFIG).unwrap();
+    let bytes = generator
+        .generate_branch(index)
+        .unwrap_or_else(|err| panic!("failed to generate {expected}: {err}"));
+    let program = Compiler::compile_program(&module, &format).unwrap();
+    let (value, _) = program.run(ReadCtxt::new(&bytes)).unwrap();
+    assert_eq!(data_label(&value), expected);
+}
+
+#[test]
+fn test_generate_example_peano() {
+    check_branch_example(1, "peano")
+}
+
+#[test]
+fn test_generate_example_gif() {
+    check_branch_example(2, "gif")
+}
+
+#[test]
+fn test_generate_example_riff() {
+    check_branch_example(8, "riff")
+}
+
+#[test]
+fn test_generate_reproducible() {
+    let (module, format) = main_format();
+    let run = |seed| {
+        let mut generator = Generator::new(&module, &format, GenConfig { seed, ..CONFIG }).unwrap();
+        (0..4)
+            .map(|_| generator.generate().unwrap())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(run(42), run(42));
+}
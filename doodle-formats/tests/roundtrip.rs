@@ -0,0 +1,334 @@
+//! Round-trip tests for the encoder
+//!
+//! Each test file in the repository root is decoded, encoded back into bytes, and decoded again,
+//! with the two decoded values required to be identical.
+
+use doodle::decoder::{Compiler, Value};
+use doodle::encoder::Encoder;
+use doodle::error::EncodeResult;
+use doodle::read::ReadCtxt;
+use doodle::{Format, FormatModule, FormatRef};
+
+/// Decodes `path` using the format defined by `entry`, and then encodes the resulting value.
+///
+/// Returns the decoded value along with the result of encoding it.
+fn decode_encode(
+    path: &str,
+    entry: fn(&mut FormatModule) -> FormatRef,
+) -> (FormatModule, Format, Value, EncodeResult<Vec<u8>>) {
+    let mut module = FormatModule::new();
+    let format = entry(&mut module).call();
+    let program = Compiler::compile_program(&module, &format).unwrap();
+    let input = std::fs::read(format!("../{path}")).unwrap();
+    let (value, _) = program.run(ReadCtxt::new(&input)).unwrap();
+    let deflate = module.find_format("deflate.main").unwrap();
+    let result = Encoder::new(&module)
+        .with_completion(deflate, complete_deflate(&module, deflate))
+        .encode(&format, &value);
+    (module, format, value, result)
+}
+
+/// Completes a value of `deflate.main` that only retains its inflated data, such as the contents of a
+/// compressed stream that is parsed via `DecodeBytes`, by storing that data in uncompressed blocks.
+fn complete_deflate(module: &FormatModule, deflate: FormatRef) -> impl Fn(&Value) -> Option<Value> {
+    let program =
+        Compiler::compile_program(module, &Format::Bits(Box::new(deflate.call()))).unwrap();
+    move |value| {
+        let Value::Record(fields) = value else {
+            return None;
+        };
+        let Some((_, Value::Seq(inflate))) = fields.iter().find(|(label, _)| label == "inflate")
+        else {
+            return None;
+        };
+        let data = inflate
+            .iter()
+            .map(|v| match v {
+                Value::U8(b) => Some(*b),
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()?;
+        let mut bytes = Vec::new();
+        let mut chunks = data.chunks(usize::from(u16::MAX)).peekable();
+        if chunks.peek().is_none() {
+            bytes.extend([1, 0, 0, 0xff, 0xff]);
+        }
+        while let Some(chunk) = chunks.next() {
+            let len = chunk.len() as u16;
+            bytes.push(u8::from(chunks.peek().is_none()));
+            bytes.extend(len.to_le_bytes());
+            bytes.extend((!len).to_le_bytes());
+            bytes.extend(chunk);
+        }
+        let (completed, _) = program.run(ReadCtxt::new(&bytes)).ok()?;
+        Some(completed)
+    }
+}
+
+#[track_caller]
+fn check_roundtrip(path: &str) {
+    check_roundtrip_with(path, doodle_formats::format::main)
+}
+
+#[track_caller]
+fn check_roundtrip_with(path: &str, entry: fn(&mut FormatModule) -> FormatRef) {
+    let (module, format, value, result) = decode_encode(path, entry);
+    let bytes = result.unwrap_or_else(|err| panic!("failed to encode {path}: {err}"));
+    let program = Compiler::compile_program(&module, &format).unwrap();
+    let (roundtrip, _) = program.run(ReadCtxt::new(&bytes)).unwrap();
+    assert!(roundtrip == value, "{path}: re-decoded value differs");
+}
+
+mod elf {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_elf() {
+        check_roundtrip("test.elf")
+    }
+}
+
+mod gif {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_gif() {
+        check_roundtrip("test.gif")
+    }
+}
+
+mod gzip {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test1_gz() {
+        check_roundtrip("test1.gz")
+    }
+
+    #[test]
+    fn test_roundtrip_test2_gz() {
+        check_roundtrip("test2.gz")
+    }
+
+    #[test]
+    fn test_roundtrip_test3_gz() {
+        check_roundtrip("test3.gz")
+    }
+
+    #[test]
+    fn test_roundtrip_test4_gz() {
+        check_roundtrip("test4.gz")
+    }
+
+    #[test]
+    fn test_roundtrip_test5_gz() {
+        check_roundtrip("test5.gz")
+    }
+
+    #[test]
+    fn test_roundtrip_test6_gz() {
+        check_roundtrip("test6.gz")
+    }
+
+    // NOTE - the compressed stream of a tgz file is parsed via `DecodeBytes`, which does not retain the original bytes,
+    // so it is re-compressed from the inflated data by `complete_deflate`
+    #[test]
+    fn test_roundtrip_test_tgz() {
+        check_roundtrip("test.tgz")
+    }
+}
+
+mod heic {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_heic() {
+        check_roundtrip("test.heic")
+    }
+}
+
+mod jpeg {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_jpg() {
+        check_roundtrip("test.jpg")
+    }
+
+    #[test]
+    fn test_roundtrip_test2_jpg() {
+        check_roundtrip("test2.jpg")
+    }
+}
+
+mod mpeg4 {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_mp4() {
+        check_roundtrip("test.mp4")
+    }
+}
+
+mod numbers {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test1_numbers() {
+        check_roundtrip("test1.numbers")
+    }
+}
+
+mod peano {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_peano() {
+        check_roundtrip("test.peano")
+    }
+}
+
+mod opentype {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_droid_sans_arabic() {
+        check_roundtrip("test-fonts/DroidSansArabic.ttf")
+    }
+
+    #[test]
+    fn test_roundtrip_frankenpax() {
+        check_roundtrip("test-fonts/Frankenpax.ttc")
+    }
+
+    #[test]
+    fn test_roundtrip_gzipped() {
+        check_roundtrip("test-fonts/gzipped.ttf")
+    }
+
+    #[test]
+    fn test_roundtrip_klei() {
+        check_roundtrip("test-fonts/Klei.otf")
+    }
+
+    #[test]
+    fn test_roundtrip_noto_sans_gujarati() {
+        check_roundtrip("test-fonts/NotoSansGujarati-Regular.ttf")
+    }
+
+    #[test]
+    fn test_roundtrip_numderline() {
+        check_roundtrip("test-fonts/numderline.ttf")
+    }
+
+    // NOTE - the glyph and positioning data of this font are only parsed as phantoms, which leave their bytes
+    // undetermined by the value; zero-filled, they are accepted by the (earlier) mpeg4 branch of the main format
+    #[test]
+    fn test_roundtrip_pacifico() {
+        check_roundtrip_with(
+            "test-fonts/Pacifico-Regular.ttf",
+            doodle_formats::format::opentype_standalone,
+        )
+    }
+
+    #[test]
+    fn test_roundtrip_padauk() {
+        check_roundtrip("test-fonts/Padauk-Regular.ttf")
+    }
+
+    #[test]
+    fn test_roundtrip_podkova() {
+        check_roundtrip("test-fonts/Podkova[wght].ttf")
+    }
+
+    #[test]
+    fn test_roundtrip_samanata() {
+        check_roundtrip("test-fonts/samanata.ttf")
+    }
+
+    #[test]
+    fn test_roundtrip_scheherazade() {
+        check_roundtrip("test-fonts/Scheherazade-Regular.ttf")
+    }
+
+    #[test]
+    fn test_roundtrip_sixtyfour_convergence() {
+        check_roundtrip(
+            "test-fonts/SixtyfourConvergence-Regular-VariableFont_BLED,SCAN,XELA,YELA.ttf",
+        )
+    }
+
+    #[test]
+    fn test_roundtrip_source_code_pro() {
+        check_roundtrip("test-fonts/SourceCodePro-Regular.otf")
+    }
+
+    #[test]
+    fn test_roundtrip_zycon() {
+        check_roundtrip("test-fonts/Zycon.ttf")
+    }
+}
+
+mod png {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_png() {
+        check_roundtrip("test.png")
+    }
+}
+
+mod riff {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_webp() {
+        check_roundtrip("test.webp")
+    }
+}
+
+mod run_length {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test0_rle() {
+        check_roundtrip("test0.rle")
+    }
+
+    #[test]
+    fn test_roundtrip_test1_rle() {
+        check_roundtrip("test1.rle")
+    }
+}
+
+mod tar {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_tar() {
+        check_roundtrip("test.tar")
+    }
+}
+
+mod text {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_txt() {
+        check_roundtrip("test.txt")
+    }
+
+    #[test]
+    fn test_roundtrip_test_utf8() {
+        check_roundtrip("test.utf8")
+    }
+}
+
+mod waldo {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_test_waldo() {
+        check_roundtrip("test.waldo")
+    }
+}
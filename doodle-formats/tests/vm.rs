@@ -0,0 +1,204 @@
+//! Equivalence tests for the bytecode backend
+//!
+//! Each test file in the repository root is decoded both by the tree-walking interpreter and by the
+//! bytecode VM compiled from the same program, with the two decoded values (and the number of bytes
+//! consumed) required to be identical.
+
+use doodle::FormatModule;
+use doodle::decoder::Compiler;
+use doodle::decoder::vm::VmProgram;
+use doodle::read::ReadCtxt;
+
+#[track_caller]
+fn check_vm(path: &str) {
+    let mut module = FormatModule::new();
+    let format = doodle_formats::format::main(&mut module).call();
+    let program = Compiler::compile_program(&module, &format).unwrap();
+    let vm = VmProgram::compile(&program).unwrap();
+    let input = std::fs::read(format!("../{path}")).unwrap();
+    let (expected, expected_rest) = program.run(ReadCtxt::new(&input)).unwrap();
+    let (value, rest) = vm
+        .run(ReadCtxt::new(&input))
+        .unwrap_or_else(|err| panic!("{path}: failed to decode with the VM: {err}"));
+    assert!(value == expected, "{path}: VM-decoded value differs");
+    assert_eq!(
+        rest.offset, expected_rest.offset,
+        "{path}: VM consumed a different number of bytes"
+    );
+}
+
+mod elf {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_elf() {
+        check_vm("test.elf")
+    }
+}
+
+mod gif {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_gif() {
+        check_vm("test.gif")
+    }
+}
+
+mod gzip {
+    use super::*;
+
+    #[test]
+    fn test_vm_test1_gz() {
+        check_vm("test1.gz")
+    }
+
+    #[test]
+    fn test_vm_test2_gz() {
+        check_vm("test2.gz")
+    }
+
+    #[test]
+    fn test_vm_test3_gz() {
+        check_vm("test3.gz")
+    }
+
+    #[test]
+    fn test_vm_test4_gz() {
+        check_vm("test4.gz")
+    }
+
+    #[test]
+    fn test_vm_test5_gz() {
+        check_vm("test5.gz")
+    }
+
+    #[test]
+    fn test_vm_test6_gz() {
+        check_vm("test6.gz")
+    }
+
+    #[test]
+    fn test_vm_test_tgz() {
+        check_vm("test.tgz")
+    }
+}
+
+mod heic {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_heic() {
+        check_vm("test.heic")
+    }
+}
+
+mod jpeg {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_jpg() {
+        check_vm("test.jpg")
+    }
+
+    #[test]
+    fn test_vm_test2_jpg() {
+        check_vm("test2.jpg")
+    }
+}
+
+mod mpeg4 {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_mp4() {
+        check_vm("test.mp4")
+    }
+}
+
+mod numbers {
+    use super::*;
+
+    #[test]
+    fn test_vm_test1_numbers() {
+        check_vm("test1.numbers")
+    }
+}
+
+mod peano {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_peano() {
+        check_vm("test.peano")
+    }
+}
+
+mod png {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_png() {
+        check_vm("test.png")
+    }
+}
+
+mod riff {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_webp() {
+        check_vm("test.webp")
+    }
+}
+
+mod run_length {
+    use super::*;
+
+    #[test]
+    fn test_vm_test0_rle() {
+        check_vm("test0.rle")
+    }
+
+    #[test]
+    fn test_vm_test1_rle() {
+        check_vm("test1.rle")
+    }
+}
+
+mod tar {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_tar() {
+        check_vm("test.tar")
+    }
+}
+
+mod text {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_txt() {
+        check_vm("test.txt")
+    }
+
+    #[test]
+    fn test_vm_test_utf8() {
+        check_vm("test.utf8")
+    }
+
+    #[test]
+    fn test_vm_mixed_utf8() {
+        check_vm("mixed.utf8")
+    }
+}
+
+mod waldo {
+    use super::*;
+
+    #[test]
+    fn test_vm_test_waldo() {
+        check_vm("test.waldo")
+    }
+}
@@ -6,24 +6,23 @@ pub type TestResult<T = ()> = Result<T, Box<dyn Send + Sync + std::error::Error>
 pub type Top<'a> = main_data<'a>;
 pub type OpentypeData<'a> = opentype_main<'a>;
 pub type TarBlock = tar_header_with_data;
-pub type PngData = png_main;
-pub type JpegData = jpeg_main;
-pub type JpegApp01 = jpeg_frame_initial_segment;
+pub type PngData<'a> = png_main<'a>;
+pub type JpegData<'a> = jpeg_main<'a>;
+pub type JpegApp01<'a> = jpeg_frame_initial_segment<'a>;
 pub type JfifData = jpeg_app0_jfif;
-pub type TiffData = tiff_main;
-pub type App0Data = jpeg_app0_data_data;
-pub type App1Data = jpeg_app1_data_data;
-pub type ExifData = jpeg_app1_exif;
-pub type XmpData = jpeg_app1_xmp;
-pub type GifData = gif_main;
+pub type TiffData<'a> = tiff_main<'a>;
+pub type App0Data<'a> = jpeg_app0_data_data<'a>;
+pub type App1Data<'a> = jpeg_app1_data_data<'a>;
+pub type ExifData<'a> = jpeg_app1_exif<'a>;
+pub type XmpData<'a> = jpeg_app1_xmp<'a>;
+pub type GifData<'a> = gif_main<'a>;
 pub type GifLogicalScreenDesc = gif_logical_screen_descriptor;
-pub type RiffData = riff_main;
+pub type RiffData<'a> = riff_main<'a>;
 pub type ExifByteOrder = tiff_byte_order;
-pub type GzipChunk = gzip_main;
+pub type GzipChunk<'a> = gzip_main<'a>;
 
-pub fn try_decode_gzip(test_file: &str) -> TestResult<Vec<GzipChunk>> {
-    let buffer = std::fs::read(std::path::Path::new(test_file))?;
-    let mut input = Parser::new(&buffer);
+pub fn try_decode_gzip(buffer: &[u8]) -> TestResult<Vec<GzipChunk<'_>>> {
+    let mut input = Parser::new(buffer);
     let parsed_data = Decoder1(&mut input)?.data;
     match parsed_data {
         Top::gzip(dat) => Ok(dat),
@@ -140,7 +140,7 @@ pub mod otf_types {
     impl Promote<OpentypeFixed> for Fixed {
         fn promote(orig: &OpentypeFixed) -> Self {
             match orig {
-                OpentypeFixed::Fixed32(raw) => I16F16::from_bits(*raw as i32),
+                OpentypeFixed::Fixed32(val) => *val,
             }
         }
     }
@@ -154,7 +154,7 @@ pub mod otf_types {
     impl Promote<OpentypeF2Dot14> for F2Dot14 {
         fn promote(orig: &OpentypeF2Dot14) -> Self {
             match orig {
-                OpentypeF2Dot14::F2Dot14(raw) => I2F14::from_bits(*raw as i16),
+                OpentypeF2Dot14::F2Dot14(val) => *val,
             }
         }
     }
@@ -178,7 +178,7 @@ pub mod otf_types {
 
         pub type OpentypeName = opentype_name_table<'a>;
 
-        pub type OpentypeOs2 = opentype_os2_table;
+        pub type OpentypeOs2 = opentype_os2_table<'input>;
 
         pub type OpentypePost = opentype_post_table<'a>;
 
@@ -244,7 +244,8 @@ pub use otf_types::*;
 // SECTION - Helper traits for consistent-style conversion from generated types to the types we use to represent them in the API Helper
 
 pub(crate) mod traits {
-    use super::{Link, PResult, View, value_parse::ValueParseError};
+    use super::{Link, PResult, Parser, View, value_parse::ValueParseError};
+    use doodle::prelude::{CommonObject, Lazy};
     /// Helper trait for promoting unexpectedly-null Offset-Options into non-Option values of the target type.
     pub(super) trait FromNull: Sized {
         /// Constructs the logically 'null' value of type `Self`.
@@ -426,6 +427,21 @@ pub(crate) mod traits {
         orig_opt.as_ref().map(T::promote)
     }
 
+    /// Resolves the deferred parse of a table, if present, against the limits of `input`, and promotes it.
+    pub(super) fn promote_lazy_opt<'input, O, T>(
+        orig: &Option<Lazy<'input, O>>,
+        input: &mut Parser<'_>,
+    ) -> PResult<Option<T>>
+    where
+        O: CommonObject,
+        O::Args<'input>: Clone,
+        T: Promote<O::Output<'input>>,
+    {
+        orig.as_ref()
+            .map(|lazy| Ok(T::promote(&lazy.resolve(input)?)))
+            .transpose()
+    }
+
     pub(super) fn try_promote_opt<O, T>(orig: &Option<O>) -> Result<Option<T>, T::Error>
     where
         T: TryPromote<O>,
@@ -466,8 +482,9 @@ pub(crate) mod traits {
 }
 use traits::{
     _Ref, FromNull, Promote, PromoteView, TryFromRef, TryPromote, TryPromoteView, promote_all,
-    promote_all_ok, promote_from_null, promote_opt, promote_vec, promote_vec_view,
-    try_promote_from_null, try_promote_opt, try_promote_opt_view, try_promote_vec,
+    promote_all_ok, promote_from_null, promote_lazy_opt, promote_opt, promote_vec,
+    promote_vec_view, try_promote_from_null, try_promote_opt, try_promote_opt_view,
+    try_promote_vec,
 };
 
 #[macro_use]
@@ -1051,7 +1068,7 @@ pub mod obj {
         }
     }
 
-    proxy!(GlyphHeader = GlyphHdr);
+    proxy!(GlyphHeader<'a> = GlyphHdr);
     proxy!(OpentypeLigCaretList<'a> = LigCarList);
     proxy!(OpentypeAttachList<'a> = AttList);
     proxy!(OpentypeKerningArray = KernArr);
@@ -1087,7 +1104,7 @@ pub mod obj {
     proxy!(OpentypeInstanceRecord = InstanceRec);
     proxy!(OpentypeGposLookupSubtable<'a> = PosLookup);
     proxy!(OpentypeGsubLookupSubtable<'a> = SubstLookup);
-    proxy!(OpentypeGvarSerializedData = GvarSerData);
+    proxy!(OpentypeGvarSerializedData<'a> = GvarSerData);
     proxy!(OpentypeSequenceTable = SeqTable);
     proxy!(OpentypeGposLookupSubtableExt<'a> = PosSubtable);
     proxy!(OpentypeGsubLookupSubtableExt<'a> = SubstSubtable);
@@ -2299,11 +2316,11 @@ pub mod otf_gvar {
     use crate::opentype_loca_table_offsets;
 
     alias! {
-        pub type OpentypeGvarSerializedData = opentype_gvar_serialized_data;
+        pub type OpentypeGvarSerializedData = opentype_gvar_serialized_data<'input>;
         pub type OpentypeGvarIntermediateTuples = opentype_gvar_tuple_variation_header_intermediate_tuples;
         pub type OpentypeGlyphVariationData = opentype_gvar_glyph_variation_data<'input>;
-        pub type OpentypePackedPoints = opentype_var_packed_point_numbers_run_points;
-        pub type OpentypePackedPointRun = opentype_var_packed_point_numbers_runs;
+        pub type OpentypePackedPoints = opentype_var_packed_point_numbers_run_points<'input>;
+        pub type OpentypePackedPointRun = opentype_var_packed_point_numbers_runs<'input>;
 
         pub type OpentypeCoordinateDeltaRun =
             opentype_gvar_serialized_data_per_tuple_variation_data_x_and_y_coordinate_deltas;
@@ -2311,7 +2328,7 @@ pub mod otf_gvar {
         pub type OpentypeXYCoordinateDeltaDeltas =
             opentype_gvar_serialized_data_per_tuple_variation_data_x_and_y_coordinate_deltas_deltas;
 
-        pub type OpentypeGvarPerTupleVariationData = opentype_gvar_serialized_data_per_tuple_variation_data;
+        pub type OpentypeGvarPerTupleVariationData = opentype_gvar_serialized_data_per_tuple_variation_data<'input>;
 
         pub type OpentypeGvarTupleRecord = opentype_gvar_tuple_record;
         pub type OpentypeGvarFlags = opentype_gvar_table_flags;
@@ -2320,7 +2337,7 @@ pub mod otf_gvar {
         pub type OpentypeGvarTupleVariationHeaderTupleIndex = opentype_gvar_tuple_variation_header_tuple_index;
     }
 
-    pub type OpentypePackedPointRuns = (u16, Vec<OpentypePackedPointRun>);
+    pub type OpentypePackedPointRuns<'input> = (u16, Vec<OpentypePackedPointRun<'input>>);
     pub type OpentypeXYCoordinateDeltas = (u16, Vec<OpentypeCoordinateDeltaRun>);
 
     frame!(OpentypeGvar);
@@ -2516,7 +2533,7 @@ pub(crate) mod gvar {
         pub(crate) point_numbers: Vec<PackedPointRun>,
     }
 
-    impl Promote<OpentypePackedPointRuns> for PackedPointNumbers {
+    impl Promote<OpentypePackedPointRuns<'_>> for PackedPointNumbers {
         fn promote(orig: &OpentypePackedPointRuns) -> Self {
             PackedPointNumbers {
                 point_numbers: promote_vec(&orig.1),
@@ -2530,16 +2547,16 @@ pub(crate) mod gvar {
         Long(Vec<u16>),
     }
 
-    impl Promote<OpentypePackedPointRun> for PackedPointRun {
+    impl Promote<OpentypePackedPointRun<'_>> for PackedPointRun {
         fn promote(orig: &OpentypePackedPointRun) -> Self {
             PackedPointRun::promote(&orig.points)
         }
     }
 
-    impl Promote<OpentypePackedPoints> for PackedPointRun {
+    impl Promote<OpentypePackedPoints<'_>> for PackedPointRun {
         fn promote(orig: &OpentypePackedPoints) -> Self {
             match orig {
-                OpentypePackedPoints::Points8(points) => PackedPointRun::Short(points.clone()),
+                OpentypePackedPoints::Points8(points) => PackedPointRun::Short(points.to_vec()),
                 OpentypePackedPoints::Points16(points) => PackedPointRun::Long(points.clone()),
             }
         }
@@ -2593,7 +2610,7 @@ pub(crate) mod gvar {
         pub(crate) x_and_y_coordinate_deltas: XYCoordinateDeltas,
     }
 
-    impl Promote<OpentypeGvarPerTupleVariationData> for GvarPerTupleVariationData {
+    impl Promote<OpentypeGvarPerTupleVariationData<'_>> for GvarPerTupleVariationData {
         fn promote(orig: &OpentypeGvarPerTupleVariationData) -> Self {
             GvarPerTupleVariationData {
                 private_point_numbers: promote_opt(&orig.private_point_numbers),
@@ -2610,7 +2627,7 @@ pub(crate) mod gvar {
         pub(crate) per_tuple_variation_data: Vec<GvarPerTupleVariationData>,
     }
 
-    impl Promote<OpentypeGvarSerializedData> for GvarSerializedData {
+    impl Promote<OpentypeGvarSerializedData<'_>> for GvarSerializedData {
         fn promote(orig: &OpentypeGvarSerializedData) -> Self {
             GvarSerializedData {
                 shared_point_numbers: promote_opt(&orig.shared_point_numbers),
@@ -3856,9 +3873,9 @@ impl Promote<Vec<i16>> for CvtMetrics {
     }
 }
 
-impl Promote<Vec<u8>> for ReflType<PrepMetrics, FpgmMetrics> {
-    // NOTE - both `prep` and `fpgm` share the non-adhoc type `Vec<u8>`
-    fn promote(orig: &Vec<u8>) -> Self {
+impl Promote<&[u8]> for ReflType<PrepMetrics, FpgmMetrics> {
+    // NOTE - both `prep` and `fpgm` share the non-adhoc type `&[u8]`
+    fn promote(orig: &&[u8]) -> Self {
         Self(orig.len())
     }
 }
@@ -3868,20 +3885,20 @@ pub mod otf_glyf {
     use super::{container, obj};
 
     alias! {
-        pub type OpentypeGlyfEntry = opentype_glyf_table_glyphs;
+        pub type OpentypeGlyfEntry = opentype_glyf_table_glyphs<'input>;
 
-        pub type OpentypeGlyph = opentype_glyf_table_glyphs_Glyph;
+        pub type OpentypeGlyph = opentype_glyf_table_glyphs_Glyph<'input>;
 
-        pub type GlyphHeader = opentype_glyf_entry;
+        pub type GlyphHeader = opentype_glyf_entry<'input>;
 
-        pub type SimpleGlyph = opentype_glyf_simple;
+        pub type SimpleGlyph = opentype_glyf_simple<'input>;
 
-        pub type GlyphDescription = opentype_glyf_description;
+        pub type GlyphDescription = opentype_glyf_description<'input>;
     }
 
     frame!(OpentypeGlyf);
 
-    impl container::SingleContainer<obj::GlyphHdr> for OpentypeGlyph {
+    impl container::SingleContainer<obj::GlyphHdr> for OpentypeGlyph<'_> {
         fn get_offset(&self) -> usize {
             self.offset as usize
         }
@@ -3889,7 +3906,7 @@ pub mod otf_glyf {
         fn get_args(&self) -> () {}
     }
 
-    impl container::OptContainer<obj::GlyphHdr> for OpentypeGlyfEntry {
+    impl container::OptContainer<obj::GlyphHdr> for OpentypeGlyfEntry<'_> {
         fn contains_object(&self) -> bool {
             matches!(self, OpentypeGlyfEntry::Glyph(..))
         }
@@ -3932,7 +3949,7 @@ mod glyf {
         Composite(CompositeGlyphMetric),
     }
 
-    impl PromoteView<OpentypeGlyfEntry> for GlyphMetric {
+    impl PromoteView<OpentypeGlyfEntry<'_>> for GlyphMetric {
         fn promote_view(orig: &OpentypeGlyfEntry, view: View<'_>) -> PResult<Self> {
             if let Some(raw) = fn_reify::reify_opt_dep(view, orig, obj::GlyphHdr) {
                 Ok(GlyphMetric::promote(&raw?))
@@ -3942,7 +3959,7 @@ mod glyf {
         }
     }
 
-    impl Promote<GlyphHeader> for GlyphMetric {
+    impl Promote<GlyphHeader<'_>> for GlyphMetric {
         fn promote(orig: &GlyphHeader) -> Self {
             match &orig.description {
                 GlyphDescription::HeaderOnly => GlyphMetric::Empty,
@@ -8283,7 +8300,7 @@ pub fn analyze_font(test_file: &str, extra_only: bool) -> TestResult<OpentypeMet
                         let mut font_metrics = Vec::with_capacity(v1header.table_directories.len());
                         for font in v1header.table_directories.iter() {
                             let tmp = match &font.data {
-                                Some(dir) => Some(analyze_table_directory(dir)?),
+                                Some(dir) => Some(analyze_table_directory(dir, &mut input)?),
                                 None => None,
                             };
                             font_metrics.push(tmp);
@@ -8294,7 +8311,7 @@ pub fn analyze_font(test_file: &str, extra_only: bool) -> TestResult<OpentypeMet
                         let mut font_metrics = Vec::with_capacity(v2header.table_directories.len());
                         for font in v2header.table_directories.iter() {
                             let tmp = match &font.data {
-                                Some(dir) => Some(analyze_table_directory(dir)?),
+                                Some(dir) => Some(analyze_table_directory(dir, &mut input)?),
                                 None => None,
                             };
                             font_metrics.push(tmp);
@@ -8310,7 +8327,7 @@ pub fn analyze_font(test_file: &str, extra_only: bool) -> TestResult<OpentypeMet
                 Ok(OpentypeMetrics::MultiFont(ret))
             }
             opentype_main_directory::TableDirectory(single) => Ok(OpentypeMetrics::SingleFont(
-                analyze_table_directory(&single)?,
+                analyze_table_directory(&single, &mut input)?,
             )),
         }
     }
@@ -8328,6 +8345,7 @@ fn analyze_extra_tables(dir: &otf_types::OpentypeFontDirectory, extra: &mut Vec<
 // ANCHOR - analyze_table_directory
 pub fn analyze_table_directory(
     dir: &otf_types::OpentypeFontDirectory,
+    input: &mut Parser<'_>,
 ) -> TestResult<SingleFontMetrics> {
     let required = {
         let cmap = {
@@ -8401,7 +8419,7 @@ pub fn analyze_table_directory(
         let loca = dir.table_links.loca.as_ref().map(|_| ());
         let glyf = promote_opt(&dir.table_links.glyf);
         let prep = promote_opt::<_, PrepMetrics>(&dir.table_links.prep);
-        let gasp = promote_opt(&dir.table_links.gasp);
+        let gasp = promote_lazy_opt(&dir.table_links.gasp, input)?;
         // STUB - anything beteween gasp and BASE goes here
         let base = {
             let base = &dir.table_links.base;
@@ -8433,7 +8451,7 @@ pub fn analyze_table_directory(
         let hvar = promote_opt(&dir.table_links.hvar).map(Heap::new);
         let mvar = promote_opt(&dir.table_links.mvar).map(Heap::new);
         // STUB - anything between mvar and dsig goes here
-        let dsig = promote_opt(&dir.table_links.dsig);
+        let dsig = promote_lazy_opt(&dir.table_links.dsig, input)?;
         let hdmx = try_promote_opt(&dir.table_links.hdmx)?;
 
         let kern = {
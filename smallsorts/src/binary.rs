@@ -35,3 +35,27 @@ pub enum U64Be {}
 /// Signed 64-bit binary type.
 #[derive(Copy, Clone)]
 pub enum I64Be {}
+
+/// Unsigned 16-bit little endian binary type.
+#[derive(Copy, Clone)]
+pub enum U16Le {}
+
+/// Signed 16-bit little endian binary type.
+#[derive(Copy, Clone)]
+pub enum I16Le {}
+
+/// Unsigned 32-bit little endian binary type.
+#[derive(Copy, Clone)]
+pub enum U32Le {}
+
+/// Signed 32-bit little endian binary type.
+#[derive(Copy, Clone)]
+pub enum I32Le {}
+
+/// Unsigned 64-bit little endian binary type.
+#[derive(Copy, Clone)]
+pub enum U64Le {}
+
+/// Signed 64-bit little endian binary type.
+#[derive(Copy, Clone)]
+pub enum I64Le {}
@@ -6,7 +6,10 @@
 //! is inspired by the paper,
 //! [The next 700 data description languages](https://collaborate.princeton.edu/en/publications/the-next-700-data-description-languages) by Kathleen Fisher, Yitzhak Mandelbaum, David P. Walker.
 
-use crate::binary::{I16Be, I32Be, I64Be, U16Be, U24Be, U32Be, U64Be, I8, U8};
+use crate::binary::{
+    I16Be, I16Le, I32Be, I32Le, I64Be, I64Le, U16Be, U16Le, U24Be, U32Be, U32Le, U64Be, U64Le, I8,
+    U8,
+};
 use crate::error::ParseError;
 // use crate::layout::{LayoutCache, LayoutTableType};
 use crate::size;
@@ -175,6 +178,20 @@ impl<'a, T: ReadFixedSizeDep> Clone for ReadArray<'a, T> {
 
 impl<'a, T: ReadFixedSizeDep> Copy for ReadArray<'a, T> {}
 
+#[cfg(feature = "serde")]
+impl<'a, T> serde::Serialize for ReadArray<'a, T>
+where
+    T: ReadFixedSizeDep + ReadUnchecked,
+    <T as ReadUnchecked>::HostType: serde::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.iter())
+    }
+}
+
 pub struct ReadArrayIter<'a, T: ReadUnchecked> {
     scope: ReadScope<'a>,
     index: usize,
@@ -485,6 +502,40 @@ impl<'a> ReadCtxt<'a> {
         self.read_unchecked_u64be() as i64
     }
 
+    unsafe fn read_unchecked_u16le(&mut self) -> u16 {
+        let lo = u16::from(*self.scope.data.get_unchecked(self.offset));
+        let hi = u16::from(*self.scope.data.get_unchecked(self.offset + 1));
+        self.offset += 2;
+        (hi << 8) | lo
+    }
+
+    unsafe fn read_unchecked_i16le(&mut self) -> i16 {
+        self.read_unchecked_u16le() as i16
+    }
+
+    unsafe fn read_unchecked_u32le(&mut self) -> u32 {
+        let b0 = u32::from(*self.scope.data.get_unchecked(self.offset));
+        let b1 = u32::from(*self.scope.data.get_unchecked(self.offset + 1));
+        let b2 = u32::from(*self.scope.data.get_unchecked(self.offset + 2));
+        let b3 = u32::from(*self.scope.data.get_unchecked(self.offset + 3));
+        self.offset += 4;
+        (b3 << 24) | (b2 << 16) | (b1 << 8) | b0
+    }
+
+    unsafe fn read_unchecked_i32le(&mut self) -> i32 {
+        self.read_unchecked_u32le() as i32
+    }
+
+    unsafe fn read_unchecked_u64le(&mut self) -> u64 {
+        let lo = u64::from(self.read_unchecked_u32le());
+        let hi = u64::from(self.read_unchecked_u32le());
+        (hi << 32) | lo
+    }
+
+    unsafe fn read_unchecked_i64le(&mut self) -> i64 {
+        self.read_unchecked_u64le() as i64
+    }
+
     pub fn read_u8(&mut self) -> Result<u8, ReadEof> {
         self.check_avail(1)?;
         Ok(unsafe { self.read_unchecked_u8() })
@@ -889,6 +940,12 @@ impl<'a, T: ReadUnchecked> ReadArray<'a, T> {
     }
 }
 
+impl<'a, T: ReadUnchecked> Default for ReadArray<'a, T> {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
 impl ReadUnchecked for U8 {
     type HostType = u8;
 
@@ -979,6 +1036,66 @@ impl ReadUnchecked for I64Be {
     }
 }
 
+impl ReadUnchecked for U16Le {
+    type HostType = u16;
+
+    const SIZE: usize = size::U16;
+
+    unsafe fn read_unchecked<'a>(ctxt: &mut ReadCtxt<'a>) -> u16 {
+        ctxt.read_unchecked_u16le()
+    }
+}
+
+impl ReadUnchecked for I16Le {
+    type HostType = i16;
+
+    const SIZE: usize = size::I16;
+
+    unsafe fn read_unchecked<'a>(ctxt: &mut ReadCtxt<'a>) -> i16 {
+        ctxt.read_unchecked_i16le()
+    }
+}
+
+impl ReadUnchecked for U32Le {
+    type HostType = u32;
+
+    const SIZE: usize = size::U32;
+
+    unsafe fn read_unchecked<'a>(ctxt: &mut ReadCtxt<'a>) -> u32 {
+        ctxt.read_unchecked_u32le()
+    }
+}
+
+impl ReadUnchecked for I32Le {
+    type HostType = i32;
+
+    const SIZE: usize = size::I32;
+
+    unsafe fn read_unchecked<'a>(ctxt: &mut ReadCtxt<'a>) -> i32 {
+        ctxt.read_unchecked_i32le()
+    }
+}
+
+impl ReadUnchecked for U64Le {
+    type HostType = u64;
+
+    const SIZE: usize = size::U64;
+
+    unsafe fn read_unchecked<'a>(ctxt: &mut ReadCtxt<'a>) -> u64 {
+        ctxt.read_unchecked_u64le()
+    }
+}
+
+impl ReadUnchecked for I64Le {
+    type HostType = i64;
+
+    const SIZE: usize = size::I64;
+
+    unsafe fn read_unchecked<'a>(ctxt: &mut ReadCtxt<'a>) -> i64 {
+        ctxt.read_unchecked_i64le()
+    }
+}
+
 impl<T1, T2> ReadUnchecked for (T1, T2)
 where
     T1: ReadUnchecked,
@@ -1067,6 +1184,23 @@ mod tests {
         assert_eq!(scope.read::<U24Be>().unwrap(), 0x10203);
     }
 
+    #[test]
+    fn test_read_array_le() {
+        let scope = ReadScope::new(&[1, 2, 3, 4, 5, 6, 7, 0xF8]);
+        let u16s = scope.ctxt().read_array::<U16Le>(4).unwrap();
+        assert_eq!(
+            u16s.iter().collect::<Vec<_>>(),
+            [0x201, 0x403, 0x605, 0xF807]
+        );
+        let i32s = scope.ctxt().read_array::<I32Le>(2).unwrap();
+        assert_eq!(
+            i32s.iter().collect::<Vec<_>>(),
+            [0x4030201, 0xF8070605u32 as i32]
+        );
+        let u64s = scope.ctxt().read_array::<U64Le>(1).unwrap();
+        assert_eq!(u64s.get_item(0), Some(0xF807060504030201));
+    }
+
     // Tests that offset_length does not panic when length is 0 but offset is out-of-bounds
     #[test]
     fn test_offset_length_oob() {
@@ -14,6 +14,7 @@ use serde::Serialize;
 use crate::{
     BaseKind, BaseType, ByteSet, DynFormat, Endian, Expr, Format, FormatModule, FormatRef,
     IntoLabel, IxHeap, Label, Pattern, StyleHint, TypeScope, ValueKind, ValueType, ViewExpr,
+    native::NativeFormat,
     typecheck::error::UnificationError,
     validation::Condition,
     valuetype::{Container, SignedIntType},
@@ -45,6 +46,8 @@ pub enum GroundFormat {
     Pos,
     /// Skip the remainder of the stream, up until the end of input or the last available byte within a Slice
     SkipRemainder,
+    /// Decode a value using the native format with the given index, passing it the given arguments
+    Native(usize, Vec<Expr>),
     /// Compute a value
     Compute(Box<Expr>),
     /// Apply a ViewFormat(Ext) to a specified view-level expression
@@ -81,6 +84,7 @@ pub enum MonoKind {
     #[cfg(feature = "format_enforce")]
     Enforce,
     Permit(Box<Expr>),
+    Resync(Vec<ByteSet>),
 }
 
 /// Descent-patterns for Formats that hold `Vec<Format>`
@@ -851,6 +855,7 @@ pub struct FormatModuleExt {
     views: IxHeap<Vec<Label>>,
     formats: Vec<FormatExt>,
     format_types: Vec<ValueTypeExt>,
+    natives: Vec<NativeFormat>,
 }
 
 impl FormatModuleExt {
@@ -861,6 +866,7 @@ impl FormatModuleExt {
             views: IxHeap::new(),
             formats: Vec::new(),
             format_types: Vec::new(),
+            natives: Vec::new(),
         }
     }
 
@@ -883,6 +889,9 @@ impl FormatModuleExt {
                 .into_iter()
                 .map(|t| t.reify(compiler))
                 .collect(),
+            natives: self.natives,
+            functions: Vec::new(),
+            generics: Vec::new(),
         }
     }
 
@@ -925,6 +934,23 @@ impl FormatModuleExt {
         FormatRef(level)
     }
 
+    /// Defines a format implemented outside of the `Format` language (see [`crate::native`]).
+    pub fn define_native_format<Name: IntoLabel>(
+        &mut self,
+        name: Name,
+        args: Vec<(Label, ValueTypeExt)>,
+        native: NativeFormat,
+    ) -> FormatRef {
+        let arg_exprs = args
+            .iter()
+            .map(|(arg_name, _)| Expr::Var(arg_name.clone()))
+            .collect();
+        let index = self.natives.len();
+        self.natives.push(native);
+        let format_ext = FormatExt::Ground(GroundFormat::Native(index, arg_exprs));
+        self.define_format_args(name, args, vec![], format_ext)
+    }
+
     pub fn get_args(&self, level: usize) -> &[(Label, ValueTypeExt)] {
         &self.args[level]
     }
@@ -968,12 +994,18 @@ impl FormatModuleExt {
                 }
 
                 GroundFormat::Byte(_bs) => Ok(ValueTypeExt::Base(BaseType::U8)),
+                GroundFormat::Native(index, args) => {
+                    for arg in args {
+                        arg.infer_type_ext(scope)?;
+                    }
+                    Ok(ValueTypeExt::from(self.natives[*index].value_type.clone()))
+                }
                 GroundFormat::Compute(expr) => Ok(expr.infer_type_ext(scope)?),
 
                 // REVIEW - do we want to hard-code this as U64 or make it a flexibly abstract integer type?
                 GroundFormat::Pos => Ok(ValueTypeExt::Base(BaseType::U64)),
 
-                GroundFormat::Apply(name) => match scope.get_type_by_name(name) {
+                GroundFormat::Apply(name) => match scope.get_type_by_name(name)? {
                     ValueKind::Format(t) => Ok(t.clone()),
                     ValueKind::Value(t) => Err(anyhow!("Apply: expected format, found {t:?}")),
                     ValueKind::View => Err(anyhow!("Apply: expected format, found View")),
@@ -1005,7 +1037,7 @@ impl FormatModuleExt {
                                     ));
                                 }
                             }
-                            let elt = ValueTypeExt::Base((*kind).into());
+                            let elt = ValueTypeExt::from(ValueType::from(*kind));
                             // TODO - consider if we need to add a valuetype for ReadArray in APM (?)
                             Ok(ValueTypeExt::Seq(Box::new(elt)))
                         }
@@ -1271,6 +1303,16 @@ impl FormatModuleExt {
                             let t1 = self.infer_format_ext_type(scope, f)?;
                             Ok(t0.unify(&t1)?)
                         }
+                        MonoKind::Resync(_) => {
+                            let t = self.infer_format_ext_type(scope, f)?;
+                            Ok(ValueTypeExt::Union(BTreeMap::from([
+                                (Label::Borrowed("ok"), t),
+                                (
+                                    Label::Borrowed("skipped"),
+                                    ValueTypeExt::from(ValueType::resync_skipped()),
+                                ),
+                            ])))
+                        }
                         MonoKind::Phantom => {
                             let _ = self.infer_format_ext_type(scope, f);
                             Ok(ValueTypeExt::UNIT)
@@ -1387,7 +1429,7 @@ mod __impls {
                     }
                 }
                 Self::Cast(op, _) => Ok(ValueTypeExt::from(ValueType::from(op.out_rep))),
-                Self::NumVar(name) => match scope.get_type_by_name(name) {
+                Self::NumVar(name) => match scope.get_type_by_name(name)? {
                     crate::ValueKind::View => Err(anyhow!("variable {} is a view", name)),
                     crate::ValueKind::Value(vt) => Ok(vt.clone()),
                     crate::ValueKind::Format(_) => Err(anyhow!("variable {} is a format", name)),
@@ -1402,7 +1444,7 @@ mod __impls {
             scope: &TypeScope<'_, ValueTypeExt>,
         ) -> AResult<ValueTypeExt> {
             match self {
-                Expr::Var(name) => match scope.get_type_by_name(name) {
+                Expr::Var(name) => match scope.get_type_by_name(name)? {
                     ValueKind::Value(t) => Ok(t.clone()),
                     ValueKind::Format(_t) => Err(anyhow!(
                         "expected ValueKind::Value, found ValueKind::Format for var {name}"
@@ -1543,6 +1585,22 @@ mod __impls {
                     }
                     x => Err(anyhow!("unsound type cast AsChar(_ : {x:?})")),
                 },
+                Expr::F32FromBits(x) => match x.infer_type_ext(scope)? {
+                    ValueTypeExt::Base(BaseType::U32) => Ok(ValueTypeExt::Base(BaseType::F32)),
+                    x => Err(anyhow!("unsound bitwise cast F32FromBits(_ : {x:?})")),
+                },
+                Expr::F64FromBits(x) => match x.infer_type_ext(scope)? {
+                    ValueTypeExt::Base(BaseType::U64) => Ok(ValueTypeExt::Base(BaseType::F64)),
+                    x => Err(anyhow!("unsound bitwise cast F64FromBits(_ : {x:?})")),
+                },
+                Expr::FixedFromBits(fx, x) => match x.infer_type_ext(scope)? {
+                    ValueTypeExt::Base(b) if b == fx.raw_base_type() => {
+                        Ok(ValueTypeExt::Base(BaseType::Fixed(*fx)))
+                    }
+                    x => Err(anyhow!(
+                        "unsound bitwise cast FixedFromBits({fx}, _ : {x:?})"
+                    )),
+                },
                 Expr::U16Be(bytes) => {
                     let _t = bytes.infer_type_ext(scope)?;
                     match _t.as_tuple_type() {
@@ -1734,6 +1792,63 @@ mod __impls {
                     },
                     other => Err(anyhow!("FindByKey: Expected Lambda, found {other:?}")),
                 },
+                Expr::Filter(expr, seq) | Expr::Any(expr, seq) | Expr::All(expr, seq) => {
+                    let op = match self {
+                        Expr::Filter(..) => "Filter",
+                        Expr::Any(..) => "Any",
+                        _ => "All",
+                    };
+                    match expr.as_ref() {
+                        Expr::Lambda(name, expr) => match seq.infer_type_ext(scope)? {
+                            ValueTypeExt::Seq(t) => {
+                                let mut child_scope = TypeScope::child(scope);
+                                child_scope.push(name.clone(), t.as_ref().clone());
+                                match expr.infer_type_ext(&child_scope)? {
+                                    ValueTypeExt::Base(BaseType::Bool) => match self {
+                                        Expr::Filter(..) => Ok(ValueTypeExt::Seq(t)),
+                                        _ => Ok(ValueTypeExt::Base(BaseType::Bool)),
+                                    },
+                                    other => Err(anyhow!("{op}: expected Bool, found {other:?}")),
+                                }
+                            }
+                            other => Err(anyhow!("{op}: expected Seq, found {other:?}")),
+                        },
+                        other => Err(anyhow!("{op}: expected Lambda, found {other:?}")),
+                    }
+                }
+                Expr::Zip(lhs, rhs) => {
+                    match (lhs.infer_type_ext(scope)?, rhs.infer_type_ext(scope)?) {
+                        (ValueTypeExt::Seq(t1), ValueTypeExt::Seq(t2)) => {
+                            Ok(ValueTypeExt::Seq(Box::new(ValueTypeExt::Tuple(vec![
+                                *t1, *t2,
+                            ]))))
+                        }
+                        (lhs_type, rhs_type) => Err(anyhow!(
+                            "Zip: lhs and rhs must be Seq: {lhs_type:?}, {rhs_type:?} !~ Seq(_)"
+                        )),
+                    }
+                }
+                Expr::Reverse(seq) => match seq.infer_type_ext(scope)? {
+                    ValueTypeExt::Seq(t) => Ok(ValueTypeExt::Seq(t)),
+                    other => Err(anyhow!("Reverse: expected Seq, found {other:?}")),
+                },
+                Expr::IsSortedBy(expr, seq) => match expr.as_ref() {
+                    Expr::Lambda(name, expr) => match seq.infer_type_ext(scope)? {
+                        ValueTypeExt::Seq(t) => {
+                            let mut child_scope = TypeScope::child(scope);
+                            child_scope
+                                .push(name.clone(), ValueTypeExt::Tuple(vec![(*t).clone(), *t]));
+                            match expr.infer_type_ext(&child_scope)? {
+                                ValueTypeExt::Base(BaseType::Bool) => {
+                                    Ok(ValueTypeExt::Base(BaseType::Bool))
+                                }
+                                other => Err(anyhow!("IsSortedBy: expected Bool, found {other:?}")),
+                            }
+                        }
+                        other => Err(anyhow!("IsSortedBy: expected Seq, found {other:?}")),
+                    },
+                    other => Err(anyhow!("IsSortedBy: expected Lambda, found {other:?}")),
+                },
                 Expr::FlatMapList(expr, ret_type, seq) => match expr.as_ref() {
                     Expr::Lambda(name, expr) => match seq.infer_type_ext(scope)? {
                         ValueTypeExt::Seq(t) => {
@@ -1799,6 +1914,12 @@ mod __impls {
                         )),
                     }
                 }
+                Expr::Call(_, ret, args) => {
+                    for arg in args {
+                        arg.infer_type_ext(scope)?;
+                    }
+                    Ok((**ret.into_inner()).clone().into())
+                }
             }
         }
     }
@@ -1827,6 +1948,10 @@ mod __impls {
                 Format::ItemVar(level, exprs, views) => {
                     FormatExt::Ground(GroundFormat::ItemVar(level, exprs, views))
                 }
+                // NOTE - `FormatModuleExt` has no generic formats to instantiate
+                Format::GenericVar(..) => {
+                    panic!("generic formats must be monomorphized before conversion to FormatExt")
+                }
                 Format::Fail => FormatExt::Ground(GroundFormat::Fail),
                 Format::EndOfInput => FormatExt::Ground(GroundFormat::EndOfInput),
                 Format::Align(n) => FormatExt::Ground(GroundFormat::Align(n)),
@@ -1940,6 +2065,7 @@ mod __impls {
                 Format::Apply(name) => FormatExt::Ground(GroundFormat::Apply(name)),
                 Format::Pos => FormatExt::Ground(GroundFormat::Pos),
                 Format::SkipRemainder => FormatExt::Ground(GroundFormat::SkipRemainder),
+                Format::Native(index, args) => FormatExt::Ground(GroundFormat::Native(index, args)),
                 Format::DecodeBytes(expr, format) => FormatExt::Epi(EpiFormat::Mono(
                     MonoKind::DecodeBytes(expr),
                     Box::new(FormatExt::from(*format)),
@@ -1986,6 +2112,10 @@ mod __impls {
                     MonoKind::Permit(expr),
                     Box::new(FormatExt::from(*format)),
                 )),
+                Format::Resync(format, pattern) => FormatExt::Epi(EpiFormat::Mono(
+                    MonoKind::Resync(pattern),
+                    Box::new(FormatExt::from(*format)),
+                )),
             }
         }
     }
@@ -2017,6 +2147,7 @@ mod __impls {
                 GroundFormat::Pos => Format::Pos,
                 GroundFormat::EndOfInput => Format::EndOfInput,
                 GroundFormat::SkipRemainder => Format::SkipRemainder,
+                GroundFormat::Native(index, args) => Format::Native(index, args),
                 GroundFormat::Align(n) => Format::Align(n),
                 GroundFormat::Byte(bs) => Format::Byte(bs),
                 GroundFormat::Apply(lbl) => Format::Apply(lbl),
@@ -2066,6 +2197,7 @@ mod __impls {
                 #[cfg(feature = "format_enforce")]
                 MonoKind::Enforce => Format::Enforce(inner),
                 MonoKind::Permit(expr) => Format::Permit(inner, expr),
+                MonoKind::Resync(pattern) => Format::Resync(inner, pattern),
             }
         }
     }
@@ -1,5 +1,8 @@
 pub use crate::byte_set::ByteSet;
 pub use smallsorts::{
     self as allsorts,
-    binary::{U8, U16Be, U32Be, U64Be, read::ReadArray},
+    binary::{
+        I8, I16Be, I16Le, I32Be, I32Le, I64Be, I64Le, U8, U16Be, U16Le, U32Be, U32Le, U64Be, U64Le,
+        read::ReadArray,
+    },
 };
@@ -210,6 +210,25 @@ impl ByteSet {
     }
 }
 
+/// Returns the number of bytes of `buf` to skip in order to resynchronize on `pattern`, as with `Format::Resync`.
+///
+/// This is the first offset after the start of `buf` at which every byte of `pattern` is matched (in order) by the
+/// corresponding byte of `buf`, or `buf.len()` if there is no such offset (including when `pattern` is empty).
+pub fn resync_offset(buf: &[u8], pattern: &[ByteSet]) -> usize {
+    if pattern.is_empty() {
+        return buf.len();
+    }
+    (1..buf.len())
+        .find(|&ix| {
+            buf.len() - ix >= pattern.len()
+                && pattern
+                    .iter()
+                    .zip(&buf[ix..])
+                    .all(|(bs, b)| bs.contains(*b))
+        })
+        .unwrap_or(buf.len())
+}
+
 impl<const LEN: usize> From<[u8; LEN]> for ByteSet {
     fn from(bytes: [u8; LEN]) -> ByteSet {
         let mut bs = ByteSet::new();
@@ -344,6 +363,15 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_resync_offset() {
+        let pattern = [ByteSet::singleton(0xFF), ByteSet::from(0xC0..=0xFE)];
+        assert_eq!(resync_offset(&[0xFF, 0xD8, 0x00, 0xFF, 0xD9], &pattern), 3);
+        assert_eq!(resync_offset(&[0xFF, 0xD8, 0x00, 0xFF], &pattern), 4);
+        assert_eq!(resync_offset(&[0x00, 0x01, 0x02], &[]), 3);
+        assert_eq!(resync_offset(&[], &pattern), 0);
+    }
+
     fn any_byte_set() -> impl Strategy<Value = ByteSet> {
         Strategy::prop_union(
             sample::select(vec![ByteSet::empty(), ByteSet::full()]).boxed(),
@@ -143,7 +143,7 @@ impl ByteSet {
         parts.into_iter().for_each(|(rank, mins)| {
             if rank >= THRESHOLD {
                 mins.into_iter().for_each(|lo| {
-                    tokens.push(format!("⟦{lo},{}⟧", lo + rank - 1));
+                    tokens.push(format!("⟦{lo},{}⟧", lo + (rank - 1)));
                 });
             } else {
                 for lo in mins {
@@ -212,4 +212,12 @@ mod tests {
         let set = low_range.union(&outlier);
         assert_eq!(set.to_string(), "⟦0,127⟧ ∪ [= 255]");
     }
+
+    #[test]
+    fn test_print_partition_to_max() {
+        let outlier = ByteSet::singleton(0);
+        let high_range = ByteSet::from(200..=255);
+        let set = high_range.union(&outlier);
+        assert_eq!(set.to_string(), "⟦200,255⟧ ∪ [= 0]");
+    }
 }
@@ -0,0 +1,193 @@
+//! Generation of decoders from within a `build.rs` script.
+//!
+//! Rather than committing the output of `doodle format --output rust`, a crate that defines its own
+//! formats can generate its decoders at build time:
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     let mut module = doodle::FormatModule::new();
+//!     let root = my_formats::main(&mut module);
+//!     doodle::codegen::build::Build::new(&module)
+//!         .root(root)
+//!         .pub_api(true)
+//!         .write()
+//!         .expect("failed to generate decoders");
+//! }
+//!
+//! // src/lib.rs
+//! doodle::include_generated!(gencode);
+//! ```
+//!
+//! Only the formats reachable from the specified roots are generated. The including crate must itself depend on
+//! `log` (and on `fixed`, if any fixed-point values are decoded), as the generated code refers to them directly.
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{CodeGenOptions, ToFragment, generate_code_with};
+use crate::{FormatModule, FormatRef};
+
+/// File name used for the generated code when none is specified.
+pub const DEFAULT_FILE_NAME: &str = "gencode.rs";
+
+/// Builder for generating decoders for a set of root formats into `OUT_DIR`.
+pub struct Build<'a> {
+    module: &'a FormatModule,
+    roots: Vec<FormatRef>,
+    file_name: String,
+    pub_api: bool,
+    serde: bool,
+}
+
+impl<'a> Build<'a> {
+    pub fn new(module: &'a FormatModule) -> Self {
+        Self {
+            module,
+            roots: Vec::new(),
+            file_name: DEFAULT_FILE_NAME.to_string(),
+            pub_api: false,
+            serde: false,
+        }
+    }
+
+    /// Chainable method for adding a root format.
+    ///
+    /// The first root is used as the entry-point for code generation, but the order of any subsequent
+    /// roots is immaterial.
+    pub fn root(mut self, root: FormatRef) -> Self {
+        self.roots.push(root);
+        self
+    }
+
+    /// Chainable method for adding several root formats at once.
+    pub fn roots(mut self, roots: impl IntoIterator<Item = FormatRef>) -> Self {
+        self.roots.extend(roots);
+        self
+    }
+
+    /// Chainable method for overriding the name of the generated file (default: [`DEFAULT_FILE_NAME`]).
+    pub fn file_name(mut self, file_name: impl Into<String>) -> Self {
+        self.file_name = file_name.into();
+        self
+    }
+
+    /// Chainable method for toggling the emission of a public API for each root format.
+    ///
+    /// See [`CodeGenOptions::pub_api`] for details; note that only roots whose type is a named definition
+    /// (e.g. a record or union, rather than a bare sequence) receive a `decode_*` entry-point.
+    pub fn pub_api(mut self, pub_api: bool) -> Self {
+        self.pub_api = pub_api;
+        self
+    }
+
+    /// Chainable method for toggling `serde` derives on the generated types, behind the including crate's `serde` feature.
+    ///
+    /// See [`CodeGenOptions::serde`] for details.
+    pub fn serde(mut self, serde: bool) -> Self {
+        self.serde = serde;
+        self
+    }
+
+    /// Returns the generated code as a string, without writing it anywhere.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no root formats were specified.
+    pub fn generate(&self) -> String {
+        let Some(top) = self.roots.first() else {
+            panic!("no root formats specified");
+        };
+        let options = CodeGenOptions::new()
+            .pub_api(self.pub_api)
+            .serde(self.serde)
+            .embedded(true)
+            .roots(
+                self.roots
+                    .iter()
+                    .map(|root| self.module.get_name(root.get_level()).to_string()),
+            );
+        generate_code_with(self.module, &top.call(), options)
+            .expect("roots are named after formats of the module")
+            .to_fragment()
+            .to_string()
+    }
+
+    /// Writes the generated code into `OUT_DIR`, returning the path of the generated file.
+    ///
+    /// The file is left untouched if its contents would not change, so that downstream compilation is not needlessly
+    /// invalidated.
+    pub fn write(&self) -> io::Result<PathBuf> {
+        let out_dir = std::env::var_os("OUT_DIR").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "OUT_DIR is not set (is this being called outside of a build script?)",
+            )
+        })?;
+        self.write_to(out_dir)
+    }
+
+    /// Variant of [`write`](Self::write) that writes into an arbitrary directory `dir`.
+    pub fn write_to(&self, dir: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let path = dir.as_ref().join(&self.file_name);
+        write_if_changed(&path, &self.generate())?;
+        Ok(path)
+    }
+}
+
+/// Writes `contents` to the file at `path` unless it already holds exactly those contents.
+///
+/// Returns `true` if the file was (re)written.
+pub fn write_if_changed(path: impl AsRef<Path>, contents: &str) -> io::Result<bool> {
+    let path = path.as_ref();
+    match std::fs::read(path) {
+        Ok(existing) if existing == contents.as_bytes() => return Ok(false),
+        Ok(_) => (),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+        Err(err) => return Err(err),
+    }
+    std::fs::write(path, contents)?;
+    Ok(true)
+}
+
+#[macro_export]
+/// Declares a module `$name` holding the code generated into `OUT_DIR` by [`Build`](crate::codegen::build::Build).
+///
+/// # Syntax
+///
+/// ```ignore
+/// doodle::include_generated!(gencode); // includes `$OUT_DIR/gencode.rs`
+/// doodle::include_generated!(pub formats, "formats.rs");
+/// ```
+macro_rules! include_generated {
+    ($vis:vis $name:ident) => {
+        $crate::include_generated!($vis $name, "gencode.rs");
+    };
+    ($vis:vis $name:ident, $file:literal) => {
+        #[allow(unused_imports, non_camel_case_types, non_snake_case, dead_code)]
+        #[rustfmt::skip]
+        $vis mod $name {
+            include!(concat!(env!("OUT_DIR"), "/", $file));
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::{ANY_BYTE, record};
+
+    #[test]
+    fn test_write_if_changed() -> io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("doodle-build-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let mut module = FormatModule::new();
+        let root = module.define_format("test.main", record([("x", ANY_BYTE)]));
+        let path = Build::new(&module).root(root).write_to(&dir)?;
+        let output = std::fs::read_to_string(&path)?;
+        assert!(!output.contains("#!["));
+        assert!(!output.contains("mod api_helper"));
+        assert!(!write_if_changed(&path, &output)?);
+        assert!(write_if_changed(&path, "")?);
+        std::fs::remove_dir_all(&dir)
+    }
+}
@@ -13,6 +13,7 @@ use crate::{
     IntoLabel, Label, MatchTree, Pattern, StyleHint, UnaryOp, ViewExpr, ViewFormat,
     byte_set::ByteSet,
     decoder::extract_pair,
+    error::{CodeGenError, CodeGenResult},
     numeric::elaborator::{TypedBinOp, TypedCast, TypedUnaryOp},
     parser::error::TraceHash,
     typecheck::{TypeChecker, UType, UVar, WHNFSolution},
@@ -25,6 +26,7 @@ use crate::numeric::{
     elaborator::{IntType as NumIntType, TypedExpr as TypedNumExpr},
 };
 
+pub mod build;
 pub(crate) mod catalog;
 
 mod ixlabel;
@@ -52,7 +54,10 @@ use rust_ast::{
 mod trace;
 
 mod typed_decoder;
-use typed_decoder::{GTCompiler, GTDecoder, TypedDecoder};
+use typed_decoder::{GTCompiler, GTDecoder, TypedDecoder, TypedProgram};
+
+mod typed_encoder;
+use typed_encoder::encoder_fname;
 
 pub(crate) mod typed_format;
 use typed_format::{
@@ -105,14 +110,7 @@ impl CodeGen {
     where
         T: From<PrimType>,
     {
-        match bt {
-            BaseType::Bool => PrimType::Bool.into(),
-            BaseType::U8 => PrimType::U8.into(),
-            BaseType::U16 => PrimType::U16.into(),
-            BaseType::U32 => PrimType::U32.into(),
-            BaseType::U64 => PrimType::U64.into(),
-            BaseType::Char => PrimType::Char.into(),
-        }
+        PrimType::from(bt).into()
     }
 
     fn lift_int<T>(int_t: NumIntType) -> T
@@ -249,11 +247,11 @@ impl CodeGen {
             Expansion::Seq(sol, hint) => {
                 let inner = self.lift_whnf_solution(tc, sol, lt);
                 match hint {
-                    SeqBorrowHint::ReadArray => {
-                        let Some(p) = inner.try_as_prim() else {
-                            unreachable!("unsound ReadArray over non-prim type `{inner:?}`")
+                    SeqBorrowHint::ReadArray(endian) => {
+                        let Ok(marker) = MarkerType::try_from(&inner) else {
+                            unreachable!("unsound ReadArray over non-marker type `{inner:?}`")
                         };
-                        RustType::ReadArray(lt.clone(), MarkerType::try_from(p).unwrap()).into()
+                        RustType::ReadArray(lt.clone(), marker.with_endian(endian)).into()
                     }
                     SeqBorrowHint::Constructed => CompType::Vec(Box::new(inner)).into(),
                     SeqBorrowHint::BufferView => CompType::Borrow(
@@ -286,6 +284,10 @@ impl CodeGen {
                 }
             },
             Expansion::ViewObj => GenType::Inline(model::view_obj_type(lt.clone())),
+            Expansion::Lazy(sol) => GenType::Inline(RustType::Lazy(
+                lt.clone(),
+                Box::new(self.lift_whnf_solution(tc, sol, lt)),
+            )),
         }
     }
 
@@ -299,6 +301,9 @@ impl CodeGen {
             TypedDecoder::Align(n) => CaseLogic::Simple(SimpleLogic::SkipToNextMultiple(*n)),
             TypedDecoder::Pos(nt) => CaseLogic::Simple(SimpleLogic::YieldCurrentOffsetAs(*nt)),
             TypedDecoder::SkipRemainder => CaseLogic::Simple(SimpleLogic::SkipRemainder),
+            TypedDecoder::Native(_gt, path, args) => {
+                CaseLogic::Simple(SimpleLogic::CallNative(path.clone(), args.clone()))
+            }
             TypedDecoder::Phantom => CaseLogic::Simple(SimpleLogic::PhantomData),
             TypedDecoder::Byte(bs) => CaseLogic::Simple(SimpleLogic::ByteIn(*bs)),
             TypedDecoder::Variant(gt, name, inner) => {
@@ -433,22 +438,30 @@ impl CodeGen {
                     "TypedDecoder::Sequence expected to have type CompType::Vec(..), found {other:?}"
                 ),
             },
-            TypedDecoder::Repeat0While(_gt, tree_continue, single) => {
-                CaseLogic::Repeat(RepeatLogic::Repeat0ContinueOnMatch(
+            TypedDecoder::Repeat0While(gt, tree_continue, single) => repeat_logic(
+                gt,
+                RepeatLogic::Repeat0ContinueOnMatch(
                     tree_continue.clone(),
                     Box::new(self.translate(single.get_dec())),
-                ))
-            }
+                ),
+            ),
 
-            TypedDecoder::Repeat1Until(_gt, tree_break, single) => {
-                CaseLogic::Repeat(RepeatLogic::Repeat1BreakOnMatch(
+            TypedDecoder::Repeat1Until(gt, tree_break, single) => repeat_logic(
+                gt,
+                RepeatLogic::Repeat1BreakOnMatch(
                     tree_break.clone(),
                     Box::new(self.translate(single.get_dec())),
-                ))
-            }
+                ),
+            ),
             TypedDecoder::ForEach(_gt, expr, lbl, single) => {
                 // REVIEW[epic=zealous-clone] - do we need to ensure this is cloned?
-                let cl_expr = embed_expr_owned(expr);
+                let cl_expr = if expr.get_type().is_some_and(|t| is_borrowed_slice(&t)) {
+                    embed_expr(expr, ExprInfo::Natural)
+                        .call_method("iter")
+                        .call_method("copied")
+                } else {
+                    embed_expr_owned(expr)
+                };
                 CaseLogic::Repeat(RepeatLogic::ForEach(
                     Box::new(cl_expr),
                     lbl.clone(),
@@ -463,6 +476,19 @@ impl CodeGen {
                     Box::new(cl_inner),
                 ))
             }
+            TypedDecoder::ParseFromView(t, view, inner) if t.is_lazy() => {
+                let (base, offset) = match view {
+                    TypedViewExpr::Var(name) => {
+                        (RustExpr::local(name.clone()), RustExpr::num_lit(0usize))
+                    }
+                    TypedViewExpr::Offset(base, offset) => (
+                        embed_view_expr(base),
+                        embed_expr_nat(offset).cast_as_usize(),
+                    ),
+                };
+                let deferred = deferred_parse(inner.get_dec());
+                CaseLogic::View(ViewLogic::DeferView(base, offset, deferred))
+            }
             TypedDecoder::ParseFromView(_t, view, inner) => {
                 let cl_view = embed_view_expr(view);
                 let cl_inner = self.translate(inner.get_dec());
@@ -471,29 +497,32 @@ impl CodeGen {
                     Box::new(cl_inner),
                 ))
             }
-            TypedDecoder::RepeatCount(_gt, expr_count, single) => {
-                CaseLogic::Repeat(RepeatLogic::ExactCount(
+            TypedDecoder::RepeatCount(gt, expr_count, single) => repeat_logic(
+                gt,
+                RepeatLogic::ExactCount(
                     Box::new(embed_expr_nat(expr_count)),
                     Box::new(self.translate(single.get_dec())),
-                ))
-            }
-            TypedDecoder::RepeatBetween(_gt, tree, expr_min, expr_max, single) => {
-                CaseLogic::Repeat(RepeatLogic::BetweenCounts(
+                ),
+            ),
+            TypedDecoder::RepeatBetween(gt, tree, expr_min, expr_max, single) => repeat_logic(
+                gt,
+                RepeatLogic::BetweenCounts(
                     tree.clone(),
                     Box::new(embed_expr_nat(expr_min)),
                     Box::new(embed_expr_nat(expr_max)),
                     Box::new(self.translate(single.get_dec())),
-                ))
-            }
-            TypedDecoder::RepeatUntilLast(_gt, pred_terminal, single) => {
-                CaseLogic::Repeat(RepeatLogic::ConditionTerminal(
+                ),
+            ),
+            TypedDecoder::RepeatUntilLast(gt, pred_terminal, single) => repeat_logic(
+                gt,
+                RepeatLogic::ConditionTerminal(
                     Box::new(GenLambda::from_expr(
                         *pred_terminal.clone(),
                         ClosureKind::Predicate,
                     )),
                     Box::new(self.translate(single.get_dec())),
-                ))
-            }
+                ),
+            ),
             TypedDecoder::RepeatUntilSeq(_gt, pred_complete, single) => {
                 CaseLogic::Repeat(RepeatLogic::ConditionComplete(
                     Box::new(GenLambda::from_expr(
@@ -585,10 +614,7 @@ impl CodeGen {
                 };
                 let mut cl_cases = Vec::new();
                 for (pat, dec) in cases.iter() {
-                    cl_cases.push((
-                        MatchCaseLHS::Pattern(embed_pattern(pat)),
-                        self.translate(dec.get_dec()),
-                    ));
+                    cl_cases.push((embed_match_case_lhs(pat), self.translate(dec.get_dec())));
                 }
                 let ck = refutability_check(
                     scrutinee
@@ -635,6 +661,15 @@ impl CodeGen {
                 let cl_inner = self.translate(inner.get_dec());
                 CaseLogic::Engine(EngineLogic::Bits(Box::new(cl_inner)))
             }
+            TypedDecoder::WithRelativeOffset(t, base_addr, offset, inner) if t.is_lazy() => {
+                let re_base_addr = embed_expr_nat(base_addr);
+                let re_offset = embed_expr_nat(offset);
+                let deferred = deferred_parse(inner.get_dec());
+                CaseLogic::View(ViewLogic::DeferOffset(
+                    RustExpr::add(re_base_addr, re_offset),
+                    deferred,
+                ))
+            }
             TypedDecoder::WithRelativeOffset(_t, base_addr, offset, inner) => {
                 let re_base_addr = embed_expr_nat(base_addr);
                 let re_offset = embed_expr_nat(offset);
@@ -665,6 +700,32 @@ impl CodeGen {
             TypedDecoder::ReifyView(_, view) => {
                 CaseLogic::View(ViewLogic::ReifyView(embed_view_expr(view)))
             }
+            TypedDecoder::Resync(gt, inner, pattern) => {
+                let Some((ix, type_name, _)) = gt.try_as_adhoc() else {
+                    panic!("unexpected type_hint for Decoder::Resync: {gt:?}")
+                };
+                let RustTypeDef::Enum(vars) = &self.defined_types[ix].def else {
+                    unreachable!("Decoder::Resync incoherent against type defined as struct")
+                };
+                let skipped_name = match vars.iter().find(|var| var.get_label() == "skipped") {
+                    Some(RustVariant::Tuple(_, types)) => match &types[..] {
+                        [RustType::Atom(AtomType::TypeRef(LocalType::LocalDef(_, name, _)))] => {
+                            name.clone()
+                        }
+                        other => {
+                            unreachable!("unexpected type for {type_name}::skipped: {other:?}")
+                        }
+                    },
+                    other => unreachable!("unexpected variant for {type_name}::skipped: {other:?}"),
+                };
+                let cl_inner = self.translate(inner.get_dec());
+                CaseLogic::Derived(DerivedLogic::Resync(
+                    type_name.clone(),
+                    skipped_name,
+                    Box::new(cl_inner),
+                    pattern.clone(),
+                ))
+            }
             TypedDecoder::Permit(_, inner, dft) => {
                 let cl_inner = self.translate(inner.get_dec());
                 CaseLogic::Derived(DerivedLogic::Permit(
@@ -776,14 +837,13 @@ fn embed_matchtree(tree: &MatchTree, ctxt: ProdCtxt<'_>) -> GenBlock {
                                 RustExpr::num_lit(ix),
                             )]
                         } else {
-                            let err_val = RustExpr::scoped(["ParseError"], "ExcludedBranch")
-                                .call_with([RustExpr::u64lit(get_trace(&(
-                                    tree,
-                                    "failed-descent-condition",
-                                )))]);
                             vec![RustStmt::Return(
                                 ReturnKind::Keyword,
-                                RustExpr::err(err_val),
+                                model::err_unexpected_byte(
+                                    RustExpr::local("b"),
+                                    embed_byteset(bs),
+                                    ctxt.parser(),
+                                ),
                             )]
                         }
                     };
@@ -869,6 +929,45 @@ fn embed_matchtree(tree: &MatchTree, ctxt: ProdCtxt<'_>) -> GenBlock {
     GenBlock { stmts, ret }
 }
 
+/// Returns the slice-length (if any) and the args of the parse performed by `decoder`, which must be a call to the
+/// decoder of a lazy format (possibly within a slice), for it to be deferred.
+fn deferred_parse(decoder: &GTDecoder) -> DeferredParse {
+    match decoder {
+        TypedDecoder::Slice(_, width, inner) => {
+            let DeferredParse { len: None, args } = deferred_parse(inner.get_dec()) else {
+                unreachable!("unexpected nested slices in deferred parse")
+            };
+            DeferredParse {
+                len: Some(embed_expr_nat(width).cast_as_usize()),
+                args,
+            }
+        }
+        TypedDecoder::Call(_, _, (args, views)) => {
+            let mut elems = args
+                .iter()
+                .map(|(lab, x)| {
+                    let Some(t) = x.get_type() else {
+                        panic!("unexpected lambda in arg-list of deferred parse")
+                    };
+                    if t.to_rust_type().should_borrow_for_arg() {
+                        panic!("cannot defer a parse whose argument `{lab}` is passed by reference")
+                    }
+                    embed_expr_owned(x)
+                })
+                .chain(views.iter().map(|(_lab, x)| embed_view_expr(x)))
+                .collect::<Vec<_>>();
+            // NOTE - this mirrors the shape of the `Args` of the `CommonObject` impl on the deferred type
+            let args = match elems.len() {
+                0 => RustExpr::UNIT,
+                1 => elems.pop().unwrap(),
+                _ => RustExpr::Tuple(elems),
+            };
+            DeferredParse { len: None, args }
+        }
+        other => unreachable!("unexpected decoder in deferred parse: {other:?}"),
+    }
+}
+
 fn embed_view_expr(view: &TypedViewExpr<GenType>) -> RustExpr {
     match view {
         TypedViewExpr::Var(name) => RustExpr::local(name.clone()),
@@ -879,6 +978,16 @@ fn embed_view_expr(view: &TypedViewExpr<GenType>) -> RustExpr {
     }
 }
 
+/// Embeds the pattern of a match arm, along with its guard (if any).
+fn embed_match_case_lhs(pat: &GTPattern) -> MatchCaseLHS {
+    match pat {
+        TypedPattern::Guard(inner, guard) => {
+            MatchCaseLHS::WithGuard(embed_pattern(inner), embed_expr_nat(guard))
+        }
+        _ => MatchCaseLHS::Pattern(embed_pattern(pat)),
+    }
+}
+
 fn embed_pattern(pat: &GTPattern) -> RustPattern {
     match pat {
         TypedPattern::Tuple(_, elts) => match elts.as_slice() {
@@ -999,6 +1108,33 @@ fn embed_pattern(pat: &GTPattern) -> RustPattern {
             },
         },
         TypedPattern::Char(c) => RustPattern::PrimLiteral(RustPrimLit::Char(*c)),
+        TypedPattern::Record(gt, fields) => match gt {
+            GenType::Def((_, tname), _def) => {
+                let constr = Constructor::Simple(tname.clone());
+                let field_pats = fields
+                    .iter()
+                    .filter(|(_, pat)| !matches!(pat, TypedPattern::Wildcard(..)))
+                    .map(|(fname, pat)| {
+                        let tmp = embed_pattern(pat);
+                        // TODO[epic=multiphase] - replace with Phase2 copy_hint
+                        let field_pat = if pat.get_type().is_copy() {
+                            tmp
+                        } else {
+                            tmp.ref_hack()
+                        };
+                        (fname.clone(), field_pat)
+                    })
+                    .collect();
+                RustPattern::Struct(constr, field_pats)
+            }
+            other => {
+                unreachable!("cannot inline TypedPattern::Record with abstract GenType: {other:?}")
+            }
+        },
+        TypedPattern::Or(_, alts) => RustPattern::Or(alts.iter().map(embed_pattern).collect()),
+        TypedPattern::Guard(..) => {
+            unreachable!("guards should be split off by `embed_match_case_lhs` before embedding")
+        }
     }
 }
 
@@ -1108,6 +1244,15 @@ fn embed_expr(expr: &GTExpr, info: ExprInfo) -> RustExpr {
             embed_expr(expr_seq, info).index(ix_expr)
         }
         TypedExpr::RecordProj(_, expr_rec, fld) => embed_expr(expr_rec, info).field(fld.clone()),
+        // NOTE - empty sequences are typed as views so as not to clobber them, and so may be borrowed slices
+        TypedExpr::Seq(gt @ GenType::Inline(rt), elts)
+            if elts.is_empty() && is_borrowed_slice(gt) =>
+        {
+            RustExpr::Operation(RustOp::AsCast(
+                Box::new(RustExpr::borrow_of(RustExpr::ArrayLit(Vec::new()))),
+                rt.clone(),
+            ))
+        }
         TypedExpr::Seq(_, elts) => {
             RustExpr::ArrayLit(elts.iter().map(|x| embed_expr(x, info)).collect())
                 .call_method("to_vec")
@@ -1221,6 +1366,37 @@ fn embed_expr(expr: &GTExpr, info: ExprInfo) -> RustExpr {
         TypedExpr::AsChar(codepoint) => RustExpr::scoped(["char"], "from_u32")
             .call_with([embed_expr_nat(codepoint)])
             .call_method("unwrap"),
+        TypedExpr::F32FromBits(bits) => {
+            RustExpr::scoped(["f32"], "from_bits").call_with([embed_expr_nat(bits)])
+        }
+        TypedExpr::F64FromBits(bits) => {
+            RustExpr::scoped(["f64"], "from_bits").call_with([embed_expr_nat(bits)])
+        }
+        TypedExpr::FixedFromBits(fx, bits) => {
+            let raw = embed_expr_nat(bits);
+            // NOTE - signed fixed-point types are constructed from signed bits, so the raw unsigned value must be cast first
+            let raw = if fx.is_signed() {
+                let sint = match fx.width() {
+                    8 => MachineSint::I8,
+                    16 => MachineSint::I16,
+                    32 => MachineSint::I32,
+                    64 => MachineSint::I64,
+                    _ => unreachable!("bad fixed-point width {}", fx.width()),
+                };
+                RustExpr::Operation(RustOp::AsCast(Box::new(raw), RustType::from(sint)))
+            } else {
+                raw
+            };
+            RustExpr::scoped(
+                [
+                    Label::from("fixed"),
+                    Label::from("types"),
+                    Label::from(fx.to_string()),
+                ],
+                "from_bits",
+            )
+            .call_with([raw])
+        }
         TypedExpr::SeqLength(seq) => {
             // NOTE - SeqLength is treated as U32 in Format context, so any operations on it have to be done on a U32 value rather than the natural `.len(): _ -> usize` return-value
             RustExpr::Operation(RustOp::AsCast(
@@ -1233,6 +1409,21 @@ fn embed_expr(expr: &GTExpr, info: ExprInfo) -> RustExpr {
             let rhs = embed_expr(seq1, info);
             RustExpr::FunctionCall(Box::new(RustExpr::local("seq_append")), vec![lhs, rhs])
         }
+        TypedExpr::Call(_, _, name, _, args) => {
+            let call_args = args.iter().map(|x| {
+                let Some(t) = x.get_type() else {
+                    unreachable!("unexpected lambda in arg-list of TypedExpr::Call")
+                };
+                if t.to_rust_type().should_borrow_for_arg() {
+                    RustExpr::borrow_of(embed_expr_nat(x))
+                } else {
+                    embed_expr_owned(x)
+                }
+            });
+            RustExpr::local(function_fname(name))
+                .call_with(call_args)
+                .wrap_try()
+        }
         TypedExpr::SubSeq(_, seq, ix, len) => {
             let start_expr = embed_expr_nat(ix);
             let bind_ix = RustStmt::assign(
@@ -1327,6 +1518,50 @@ fn embed_expr(expr: &GTExpr, info: ExprInfo) -> RustExpr {
                 embed_lambda_dft(f, ClosureKind::PairBorrowOwned, true),
             ])
             .wrap_try(),
+        TypedExpr::Filter(_, f, seq) => RustExpr::local("try_filter_vec")
+            .call_with([
+                embed_expr_nat(seq)
+                    .call_method("iter")
+                    .call_method("cloned"),
+                embed_lambda(f, ClosureKind::Transform, true, ExprInfo::EmbedOwned),
+            ])
+            .wrap_try(),
+        TypedExpr::Zip(_, lhs, rhs) => RustExpr::local("zip_vec").call_with([
+            embed_expr_nat(lhs)
+                .call_method("iter")
+                .call_method("cloned"),
+            embed_expr_nat(rhs)
+                .call_method("iter")
+                .call_method("cloned"),
+        ]),
+        TypedExpr::Reverse(_, seq) => {
+            RustExpr::local("reverse_vec").call_with([embed_expr_nat(seq)
+                .call_method("iter")
+                .call_method("cloned")])
+        }
+        TypedExpr::Any(f, seq) | TypedExpr::All(f, seq) => {
+            let method = if matches!(expr, TypedExpr::Any(..)) {
+                "try_any"
+            } else {
+                "try_all"
+            };
+            RustExpr::local(method)
+                .call_with([
+                    embed_expr_nat(seq)
+                        .call_method("iter")
+                        .call_method("cloned"),
+                    embed_lambda(f, ClosureKind::Transform, true, ExprInfo::EmbedOwned),
+                ])
+                .wrap_try()
+        }
+        TypedExpr::IsSortedBy(f, seq) => RustExpr::local("try_is_sorted_by")
+            .call_with([
+                embed_expr_nat(seq)
+                    .call_method("iter")
+                    .call_method("cloned"),
+                embed_lambda(f, ClosureKind::Transform, true, ExprInfo::EmbedOwned),
+            ])
+            .wrap_try(),
         TypedExpr::FindByKey(ty, is_sorted, f, query, seq) => {
             let method = if *is_sorted {
                 "find_by_key_sorted"
@@ -1424,7 +1659,7 @@ fn embed_match_expr(
         .iter()
         .map(|(pat, rhs)| {
             (
-                MatchCaseLHS::Pattern(embed_pattern(pat)),
+                embed_match_case_lhs(pat),
                 vec![RustStmt::Return(
                     ReturnKind::Implicit,
                     embed_expr(rhs, info),
@@ -1458,6 +1693,9 @@ fn try_as_matches_macro_cases(
 ) -> Option<Vec<RustPattern>> {
     let mut accum = Vec::new();
     for (pat, branch_val) in cases {
+        if matches!(pat, TypedPattern::Guard(..)) {
+            return None;
+        }
         match branch_val {
             TypedExpr::Bool(bval) => {
                 if *bval == branch_sel {
@@ -1510,6 +1748,23 @@ fn refutability_check<A: std::fmt::Debug + Clone>(
     if contains_irrefutable_pattern(cases) {
         return Refutability::Irrefutable;
     }
+    if cases
+        .iter()
+        .any(|(pat, _)| matches!(pat, TypedPattern::Or(..)))
+    {
+        // NOTE - a top-level or-pattern covers exactly what its alternatives would as separate cases
+        let flat_cases = cases
+            .iter()
+            .flat_map(|(pat, rhs)| match pat {
+                TypedPattern::Or(_, alts) => alts
+                    .iter()
+                    .map(|alt| (alt.clone(), rhs.clone()))
+                    .collect::<Vec<_>>(),
+                _ => vec![(pat.clone(), rhs.clone())],
+            })
+            .collect::<Vec<_>>();
+        return refutability_check(head_type, &flat_cases);
+    }
     match head_type {
         GenType::Inline(rt) => match rt {
             RustType::Atom(at) => match at {
@@ -1537,7 +1792,10 @@ fn refutability_check<A: std::fmt::Debug + Clone>(
                     }
                     // FIXME - handle Pattern::Int properly
                     // these cases have too many values to practically cover...
-                    PrimType::Unsigned(_) | PrimType::Char => Refutability::Indeterminate,
+                    PrimType::Unsigned(_)
+                    | PrimType::Char
+                    | PrimType::Float(_)
+                    | PrimType::Fixed(_) => Refutability::Indeterminate,
                     PrimType::Bool => {
                         // mask for inclusion with indices 0: false, 1: true
                         let mut cover_mask = [false, false];
@@ -1612,6 +1870,9 @@ fn refutability_check<A: std::fmt::Debug + Clone>(
             RustType::ViewObject(..) => {
                 unreachable!("ViewObject not expected in generated match-expressions")
             }
+            RustType::Lazy(..) => {
+                unreachable!("Lazy not expected in generated match-expressions")
+            }
         },
         GenType::Def(_, def) => {
             match &def.def {
@@ -1649,9 +1910,17 @@ fn refutability_check<A: std::fmt::Debug + Clone>(
                         .unwrap()
                 }
                 RustTypeDef::Struct(st) => {
-                    unreachable!(
-                        "there are no patterns that match simple structures in place: {st:?}, {cases:#?}"
-                    );
+                    if cases
+                        .iter()
+                        .all(|(pat, _)| matches!(pat.split_guard().0, TypedPattern::Record(..)))
+                    {
+                        // we have already checked in contains_irrefutable_pattern that there is no fully-irrefutable record pattern
+                        Refutability::Indeterminate
+                    } else {
+                        unreachable!(
+                            "there are no non-record patterns that match simple structures in place: {st:?}, {cases:#?}"
+                        );
+                    }
                 }
             }
         }
@@ -1677,6 +1946,9 @@ fn is_pattern_irrefutable(pat: &TypedPattern<GenType>) -> bool {
                     _ => unreachable!("variant pattern cannot match non-LocalDef type"),
                 })
         }
+        TypedPattern::Record(_, fields) => fields.iter().all(|(_, p)| is_pattern_irrefutable(p)),
+        TypedPattern::Or(_, alts) => alts.iter().any(is_pattern_irrefutable),
+        TypedPattern::Guard(..) => false, // a guard can always fail
         _ => false, // all the other cases are prim-types that cover only one of N > 1 possible values
     }
 }
@@ -2189,6 +2461,39 @@ enum GenExpr {
 }
 
 impl GenExpr {
+    /// Replaces explicit `return` statements at the tail of each branch of a `match` with implicit returns,
+    /// treating any refutation-case as an explicit branch.
+    ///
+    /// Only valid when `self` is in tail position of a function or closure body.
+    fn implicit_tail_returns(self) -> GenExpr {
+        let GenExpr::Control(ctrl) = self else {
+            return self;
+        };
+        let RustControl::Match(head, body) = *ctrl else {
+            return GenExpr::Control(ctrl);
+        };
+        let fix_cases = |cases: Vec<RustMatchCase<GenBlock>>| {
+            cases
+                .into_iter()
+                .map(|(lhs, block)| (lhs, block.implicit_tail_return()))
+                .collect::<Vec<_>>()
+        };
+        let body = match body {
+            RustMatchBody::Irrefutable(cases) => RustMatchBody::Irrefutable(fix_cases(cases)),
+            RustMatchBody::Refutable(mut cases, RustCatchAll::ReturnErrorValue { value }) => {
+                cases.push((
+                    MatchCaseLHS::Pattern(RustPattern::CatchAll(None)),
+                    GenBlock::implicit_return(*value),
+                ));
+                RustMatchBody::Irrefutable(fix_cases(cases))
+            }
+            RustMatchBody::Refutable(cases, catch_all) => {
+                RustMatchBody::Refutable(fix_cases(cases), catch_all)
+            }
+        };
+        GenExpr::Control(Box::new(RustControl::Match(head, body)))
+    }
+
     fn wrap_ok<Name>(self, qual: Option<Name>) -> GenExpr
     where
         Name: IntoLabel + Clone,
@@ -2456,6 +2761,7 @@ impl GenBlock {
 
     /// Inserts the statements contained in the iterable `preamble`, in order, directly before
     /// the statements contained in `self`.
+    #[expect(dead_code)]
     fn prepend_stmts(&mut self, preamble: impl IntoIterator<Item = GenStmt>) {
         let stmts =
             Iterator::chain(preamble.into_iter(), self.stmts.drain(..)).collect::<Vec<GenStmt>>();
@@ -2622,6 +2928,56 @@ impl GenBlock {
         }
     }
 
+    /// Attributes any error short-circuiting out of `self` to the format or record field named `frame`
+    /// (see `ParseError::within`), so that generated decoders can report where a failure occurred.
+    ///
+    /// Only blocks consisting of a single fallible expression are wrapped, by attaching the `map_err` directly to
+    /// that expression; errors out of any other block are instead attributed to the enclosing decoder function
+    /// as a whole. Blocks that cannot fail, or that yield no value, are returned unchanged.
+    fn within(self, frame: Label, parser: RustExpr) -> GenBlock {
+        match self {
+            GenBlock {
+                stmts,
+                ret: Some(GenExpr::Try(inner)),
+            } if stmts.is_empty() => GenBlock::simple_expr(
+                model::map_err_within(RustExpr::from(*inner), frame, parser).wrap_try(),
+            ),
+            GenBlock {
+                stmts,
+                ret: Some(GenExpr::Embed(RustExpr::Try(inner))),
+            } if stmts.is_empty() => {
+                GenBlock::simple_expr(model::map_err_within(*inner, frame, parser).wrap_try())
+            }
+            other => other,
+        }
+    }
+
+    /// Converts a value-producing block into the body of a closure returning `PResult`, whose short-circuiting
+    /// (via `?` or `return Err(..)`) is confined to the closure.
+    fn into_closure_body(self) -> RustExpr {
+        let GenBlock { stmts, ret } = self;
+        let Some(ret) = ret else {
+            unreachable!("closure body must produce a value: {stmts:?}")
+        };
+        RustExpr::from(GenBlock {
+            stmts,
+            ret: Some(ret.wrap_ok(Some("PResult")).implicit_tail_returns()),
+        })
+    }
+
+    /// Replaces a trailing `return <expr>` statement (with no subsequent value) with an implicit return.
+    ///
+    /// Only valid when the block is in tail position of a function or closure body.
+    fn implicit_tail_return(mut self) -> GenBlock {
+        if self.ret.is_none()
+            && let Some(GenStmt::Embed(RustStmt::Return(kind @ ReturnKind::Keyword, _))) =
+                self.stmts.last_mut()
+        {
+            *kind = ReturnKind::Implicit;
+        }
+        self
+    }
+
     /// Applies a lambda-abstraction to a `GenBlock` so that engine logic isn't affected
     /// by short-circuiting behavior of `?` and `return Err(...)` within the block in question
     ///
@@ -2655,8 +3011,9 @@ impl GenBlock {
                     ret: Some(ret),
                 }
             } else {
+                // NOTE - infallible blocks still need to yield a `PResult` for the caller to inspect
                 Self {
-                    ret: Some(ret),
+                    ret: Some(ret.wrap_ok(Some("PResult"))),
                     ..self
                 }
             }
@@ -2681,6 +3038,35 @@ pub(crate) trait ToAst {
     fn to_ast(&self, ctxt: ProdCtxt<'_>) -> Self::AstElem;
 }
 
+/// Returns the logic for `repeat`, whose sequence of elements has type `gt`.
+///
+/// Repetitions typed as byte-slices (see [`SeqBorrowHint::BufferView`]) borrow the bytes they read from the input,
+/// rather than accumulating them into a `Vec`.
+fn repeat_logic(gt: &GenType, repeat: RepeatLogic<GTExpr>) -> CaseLogic<GTExpr> {
+    if is_borrowed_slice(gt) {
+        CaseLogic::View(ViewLogic::ByteRun(Box::new(repeat)))
+    } else {
+        CaseLogic::Repeat(repeat)
+    }
+}
+
+/// Returns `true` if `gt` is a slice borrowed from the input (e.g. `&'input [u8]`).
+fn is_borrowed_slice(gt: &GenType) -> bool {
+    match gt {
+        GenType::Inline(RustType::Atom(AtomType::Comp(CompType::Borrow(
+            _,
+            Mut::Immutable,
+            slice,
+        )))) => {
+            matches!(
+                **slice,
+                RustType::Atom(AtomType::Comp(CompType::RawSlice(_)))
+            )
+        }
+        _ => false,
+    }
+}
+
 /// Abstraction type use to sub-categorize different Decoders and ensure that the codegen layer
 /// is more resilient to changes both upstream (in the Decoder model)
 /// and downstream (in the API made available for generated code to use)
@@ -2740,6 +3126,7 @@ enum SimpleLogic<ExprT, ViewExprT = TypedViewExpr<GenType>> {
     ByteIn(ByteSet),
     Eval(RustExpr),
     CallDynamic(Label),
+    CallNative(Label, Vec<ExprT>),
     YieldCurrentOffsetAs(NumType),
     SkipRemainder,
     ConstNone,
@@ -2787,6 +3174,26 @@ impl ToAst for SimpleLogic<GTExpr> {
                 let call = RustExpr::local(dynf_name.clone()).call_with([ctxt.parser()]);
                 GenBlock::simple_expr(call.wrap_try())
             }
+            SimpleLogic::CallNative(path, args) => {
+                let call_args = args
+                    .iter()
+                    .map(|x| {
+                        let Some(t) = x.get_type() else {
+                            panic!("unexpected lambda in arg-list of SimpleLogic::CallNative")
+                        };
+                        if t.to_rust_type().should_borrow_for_arg() {
+                            RustExpr::borrow_of(embed_expr_nat(x))
+                        } else {
+                            embed_expr_owned(x)
+                        }
+                    })
+                    .collect();
+                GenBlock::simple_expr(model::try_decode_native(
+                    ctxt.parser(),
+                    path.clone(),
+                    call_args,
+                ))
+            }
             SimpleLogic::SkipToNextMultiple(n) => {
                 GenBlock::simple_expr(model::try_skip_align(ctxt.parser(), *n))
             }
@@ -2813,9 +3220,10 @@ impl ToAst for SimpleLogic<GTExpr> {
                     let b_true = vec![RustStmt::Return(ReturnKind::Implicit, RustExpr::local("b"))];
                     let b_false = vec![RustStmt::Return(
                         ReturnKind::Keyword,
-                        RustExpr::err(
-                            RustExpr::scoped(["ParseError"], "ExcludedBranch")
-                                .call_with([RustExpr::u64lit(get_trace(bs))]),
+                        model::err_unexpected_byte(
+                            RustExpr::local("b"),
+                            embed_byteset(bs),
+                            ctxt.parser(),
                         ),
                     )];
                     let logic = RustExpr::Control(Box::new(RustControl::If(
@@ -2838,6 +3246,31 @@ enum ViewLogic<ExprT> {
     CaptureBytes(RustExpr, RustExpr),
     ReadArray(RustExpr, RustExpr, BaseKind<Endian>),
     ReifyView(RustExpr),
+    /// Repetition of plain bytes, yielding a slice of the input rather than a `Vec`
+    ByteRun(Box<RepeatLogic<ExprT>>),
+    /// DeferView(BaseView, Offset, Parse): lazy parse at an offset into a view
+    DeferView(RustExpr, RustExpr, DeferredParse),
+    /// DeferOffset(AbsOffset, Parse): lazy parse at an absolute offset into the input
+    DeferOffset(RustExpr, DeferredParse),
+}
+
+/// Parse of a lazy format, to be deferred by storing its arguments in a `Lazy` handle.
+#[derive(Clone, Debug)]
+struct DeferredParse {
+    /// Length of the slice the parse is confined to, if any
+    len: Option<RustExpr>,
+    /// Arguments to the parse, in the shape of the `Args` of its `CommonObject` impl
+    args: RustExpr,
+}
+
+impl DeferredParse {
+    fn to_lazy(&self, view: RustExpr, offset: RustExpr) -> RustExpr {
+        let lazy = model::lazy_new(view, offset, self.args.clone());
+        match &self.len {
+            None => lazy,
+            Some(len) => model::lazy_with_len(lazy, len.clone()),
+        }
+    }
 }
 
 impl ToAst for ViewLogic<GTExpr> {
@@ -2862,6 +3295,14 @@ impl ToAst for ViewLogic<GTExpr> {
                 model::read_array_from_view(view.clone(), len.clone().cast_as_usize(), *kind),
             ),
             ViewLogic::ReifyView(view) => GenBlock::simple_expr(model::reify_view(view.clone())),
+            ViewLogic::ByteRun(repeat) => repeat.to_ast_with(ctxt, Accum::ByteRun),
+            ViewLogic::DeferView(base, offset, deferred) => {
+                GenBlock::simple_expr(deferred.to_lazy(base.clone(), offset.clone()))
+            }
+            ViewLogic::DeferOffset(target, deferred) => {
+                let view = model::try_view_at(ctxt.parser(), target.clone());
+                GenBlock::simple_expr(deferred.to_lazy(view, RustExpr::num_lit(0usize)))
+            }
         }
     }
 }
@@ -2991,6 +3432,33 @@ enum RepeatLogic<ExprT> {
     ),
 }
 
+/// Accumulator for the elements of a [`RepeatLogic`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Accum {
+    /// `Vec` of the decoded elements
+    Vec,
+    /// `ByteRun` of the bytes read, borrowed from the input
+    ByteRun,
+}
+
+impl Accum {
+    /// Returns the statement declaring the accumulator `ident`.
+    fn init(self, ident: &'static str, ctxt: ProdCtxt<'_>) -> GenStmt {
+        match self {
+            Accum::Vec => model::let_mut_vec_new(ident).into(),
+            Accum::ByteRun => model::let_mut_byte_run(ident, ctxt.parser()).into(),
+        }
+    }
+
+    /// Returns the value of the sequence accumulated in `ident`.
+    fn finish(self, ident: &'static str) -> RustExpr {
+        match self {
+            Accum::Vec => RustExpr::local(ident),
+            Accum::ByteRun => model::byte_run_slice(RustExpr::local(ident)),
+        }
+    }
+}
+
 impl<ExprT> ToAst for RepeatLogic<ExprT>
 where
     CaseLogic<ExprT>: ToAst<AstElem = GenBlock>,
@@ -2998,22 +3466,46 @@ where
     type AstElem = GenBlock;
 
     fn to_ast(&self, ctxt: ProdCtxt<'_>) -> GenBlock {
+        self.to_ast_with(ctxt, Accum::Vec)
+    }
+}
+
+impl<ExprT> RepeatLogic<ExprT>
+where
+    CaseLogic<ExprT>: ToAst<AstElem = GenBlock>,
+{
+    /// Returns `true` if the elements of the repetition can be accumulated into a `ByteRun`.
+    fn supports_byte_run(&self) -> bool {
+        !matches!(
+            self,
+            RepeatLogic::ConditionComplete(..)
+                | RepeatLogic::ForEach(..)
+                | RepeatLogic::AccumUntil(..)
+        )
+    }
+
+    fn to_ast_with(&self, ctxt: ProdCtxt<'_>, accum: Accum) -> GenBlock {
+        assert!(
+            accum == Accum::Vec || self.supports_byte_run(),
+            "unexpected ByteRun accumulation for a repetition that does not support it"
+        );
         match self {
             RepeatLogic::Repeat0ContinueOnMatch(continue_tree, elt) => {
                 let mut stmts = Vec::new();
-                stmts.push(model::let_mut_vec_new(model::R0COM_ACCUM).into());
+                stmts.push(accum.init(model::R0COM_ACCUM, ctxt));
 
                 let ctrl = {
                     let tree_index_expr = invoke_matchtree(continue_tree, ctxt);
                     let bind_ix = RustStmt::assign(model::MATCH_BRANCH_IX, tree_index_expr).into();
                     let cond = model::eq0(RustExpr::local(model::MATCH_BRANCH_IX));
                     let b_continue = {
+                        let tally = RustStmt::Expr(model::try_tally_element(ctxt.parser())).into();
                         let bind_elem = GenStmt::assign(model::R0COM_ELEM, elt.to_ast(ctxt));
                         let push_elem = model::vec_push(
                             RustExpr::local(model::R0COM_ACCUM),
                             RustExpr::local(model::R0COM_ELEM),
                         );
-                        GenBlock::from_parts(vec![bind_elem], Some(push_elem.into()))
+                        GenBlock::from_parts(vec![tally, bind_elem], Some(push_elem.into()))
                     };
                     let escape_clause =
                         GenControl::If(Box::new(cond), b_continue, Some(GenControl::Break.into()));
@@ -3023,30 +3515,26 @@ where
                     )
                 };
                 stmts.push(ctrl.into());
-                GenBlock::from_parts(stmts, Some(RustExpr::local(model::R0COM_ACCUM).into()))
+                GenBlock::from_parts(stmts, Some(accum.finish(model::R0COM_ACCUM).into()))
             }
             RepeatLogic::Repeat1BreakOnMatch(break_tree, elt) => {
                 let mut stmts = Vec::new();
 
-                stmts.push(model::let_mut_vec_new(model::R1BOM_ACCUM).into());
+                stmts.push(accum.init(model::R1BOM_ACCUM, ctxt));
                 let ctrl = {
                     let tree_index_expr = invoke_matchtree(break_tree, ctxt);
                     let bind_ix = RustStmt::assign(model::MATCH_BRANCH_IX, tree_index_expr).into();
                     let cond = model::eq0(RustExpr::local(model::MATCH_BRANCH_IX));
                     let b_continue = {
+                        let tally = RustStmt::Expr(model::try_tally_element(ctxt.parser())).into();
                         let bind_elem = GenStmt::assign(model::R1BOM_ELEM, elt.to_ast(ctxt));
                         let push_elem = model::vec_push(
                             RustExpr::local(model::R1BOM_ACCUM),
                             RustExpr::local(model::R1BOM_ELEM),
                         );
-                        GenBlock::from_parts(vec![bind_elem], Some(push_elem.into()))
+                        GenBlock::from_parts(vec![tally, bind_elem], Some(push_elem.into()))
                     };
-                    let b_stop = GenControl::If(
-                        Box::new(RustExpr::local(model::R1BOM_ACCUM).vec_is_empty()),
-                        GenBlock::explicit_return(model::err_too_few()),
-                        Some(GenControl::Break.into()),
-                    )
-                    .into();
+                    let b_stop = GenControl::Break.into();
                     let escape_clause = GenControl::If(Box::new(cond), b_stop, Some(b_continue));
                     GenControl::While(
                         Box::new(model::gt0(model::rem_bytes(ctxt.parser()))),
@@ -3054,12 +3542,19 @@ where
                     )
                 };
                 stmts.push(ctrl.into());
-                GenBlock::from_parts(stmts, Some(RustExpr::local(model::R1BOM_ACCUM).into()))
+                // NOTE - the loop also ends when the input runs out, so the minimum is only checked once it has
+                let check_min = GenControl::If(
+                    Box::new(RustExpr::local(model::R1BOM_ACCUM).vec_is_empty()),
+                    GenBlock::explicit_return(model::err_too_few()),
+                    None,
+                );
+                stmts.push(check_min.into());
+                GenBlock::from_parts(stmts, Some(accum.finish(model::R1BOM_ACCUM).into()))
             }
             RepeatLogic::BetweenCounts(reps_left_tree, expr_min, expr_max, elt) => {
                 let mut stmts = Vec::new();
 
-                stmts.push(model::let_mut_vec_new(model::BETWEEN_ACCUM).into());
+                stmts.push(accum.init(model::BETWEEN_ACCUM, ctxt));
                 let ctrl = {
                     let bind_reps_left = {
                         let reps_left = invoke_matchtree(reps_left_tree, ctxt);
@@ -3073,12 +3568,13 @@ where
                         model::repeat_between_finished(out_of_reps, len_expr, min_expr, max_expr)
                     };
                     let b_continue = {
+                        let tally = RustStmt::Expr(model::try_tally_element(ctxt.parser())).into();
                         let bind_elem = GenStmt::assign(model::BETWEEN_ELEM, elt.to_ast(ctxt));
                         let push_elem = model::vec_push(
                             RustExpr::local(model::BETWEEN_ACCUM),
                             RustExpr::local(model::BETWEEN_ELEM),
                         );
-                        GenBlock::from_parts(vec![bind_elem], Some(push_elem.into()))
+                        GenBlock::from_parts(vec![tally, bind_elem], Some(push_elem.into()))
                     };
                     let b_stop = GenControl::Break.into();
                     let escape_clause = GenControl::If(Box::new(cond), b_stop, Some(b_continue));
@@ -3088,7 +3584,7 @@ where
                     )
                 };
                 stmts.push(ctrl.into());
-                GenBlock::from_parts(stmts, Some(RustExpr::local(model::BETWEEN_ACCUM).into()))
+                GenBlock::from_parts(stmts, Some(accum.finish(model::BETWEEN_ACCUM).into()))
             }
             RepeatLogic::ForEach(seq, lbl, inner) => {
                 let mut stmts = Vec::new();
@@ -3097,7 +3593,10 @@ where
 
                 let ctrl = {
                     let body = GenBlock::from_parts(
-                        vec![GenStmt::assign(model::FOREACH_ELEM, inner.to_ast(ctxt))],
+                        vec![
+                            RustStmt::Expr(model::try_tally_element(ctxt.parser())).into(),
+                            GenStmt::assign(model::FOREACH_ELEM, inner.to_ast(ctxt)),
+                        ],
                         Some(
                             model::vec_push(
                                 RustExpr::local(model::FOREACH_ACCUM),
@@ -3115,11 +3614,14 @@ where
             RepeatLogic::ExactCount(expr_n, elt) => {
                 let mut stmts = Vec::new();
 
-                stmts.push(model::let_mut_vec_new(model::EXACT_ACCUM).into());
+                stmts.push(accum.init(model::EXACT_ACCUM, ctxt));
 
                 let ctrl = {
                     let body = GenBlock::from_parts(
-                        vec![GenStmt::assign(model::EXACT_ELEM, elt.to_ast(ctxt))],
+                        vec![
+                            RustStmt::Expr(model::try_tally_element(ctxt.parser())).into(),
+                            GenStmt::assign(model::EXACT_ELEM, elt.to_ast(ctxt)),
+                        ],
                         Some(
                             model::vec_push(
                                 RustExpr::local(model::EXACT_ACCUM),
@@ -3131,11 +3633,11 @@ where
                     GenControl::ForRange0(Label::from("_"), expr_n.clone(), body)
                 };
                 stmts.push(ctrl.into());
-                GenBlock::from_parts(stmts, Some(RustExpr::local(model::EXACT_ACCUM).into()))
+                GenBlock::from_parts(stmts, Some(accum.finish(model::EXACT_ACCUM).into()))
             }
             RepeatLogic::ConditionTerminal(pred_last, elt) => {
                 let mut stmts = Vec::new();
-                stmts.push(model::let_mut_vec_new(model::UNTIL_LAST_ACCUM).into());
+                stmts.push(accum.init(model::UNTIL_LAST_ACCUM, ctxt));
                 let ctrl = {
                     let elt_bind = GenStmt::assign(model::UNTIL_LAST_ELEM, elt.to_ast(ctxt));
                     let stop_if_done = {
@@ -3160,10 +3662,14 @@ where
                         model::simplifying_if(done_cond, b_terminal, Some(b_else))
                     };
 
-                    GenControl::Loop(GenBlock::from_parts(vec![elt_bind], Some(stop_if_done)))
+                    let tally = RustStmt::Expr(model::try_tally_element(ctxt.parser())).into();
+                    GenControl::Loop(GenBlock::from_parts(
+                        vec![tally, elt_bind],
+                        Some(stop_if_done),
+                    ))
                 };
                 stmts.push(ctrl.into());
-                GenBlock::from_parts(stmts, Some(RustExpr::local(model::UNTIL_LAST_ACCUM).into()))
+                GenBlock::from_parts(stmts, Some(accum.finish(model::UNTIL_LAST_ACCUM).into()))
             }
             RepeatLogic::ConditionComplete(pred_full, elt) => {
                 let mut stmts = Vec::new();
@@ -3182,8 +3688,9 @@ where
                         );
                         model::simplifying_if(predicate, GenControl::Break.into(), None)
                     };
+                    let tally = RustStmt::Expr(model::try_tally_element(ctxt.parser())).into();
                     GenControl::Loop(GenBlock::from_parts(
-                        vec![elt_bind, elt_push],
+                        vec![tally, elt_bind, elt_push],
                         Some(stop_if_done),
                     ))
                 };
@@ -3215,6 +3722,7 @@ where
                         );
                         model::simplifying_if(predicate_done, GenControl::Break.into(), None).into()
                     };
+                    let tally = RustStmt::Expr(model::try_tally_element(ctxt.parser())).into();
                     let bind_elt = GenStmt::assign(model::UNFOLD_ELEM, elt.to_ast(ctxt));
 
                     let update_acc = {
@@ -3230,7 +3738,7 @@ where
                         RustExpr::local(model::UNFOLD_ELEM),
                     );
                     GenControl::Loop(GenBlock::from_parts(
-                        vec![break_if_done, bind_elt, update_acc],
+                        vec![break_if_done, tally, bind_elt, update_acc],
                         Some(push_elt.into()),
                     ))
                 };
@@ -3416,6 +3924,7 @@ where
                         "let binding `{name}` has empty rhs; there may be an exposed phantom in definition"
                     );
                 }
+                let prior_block = prior_block.within(name.clone(), ctxt.parser());
                 let mut inner_block = inner.to_ast(ctxt);
                 inner_block.prepend_stmt(GenStmt::assign(name.clone(), prior_block));
                 inner_block
@@ -3534,6 +4043,8 @@ enum DerivedLogic<ExprT> {
     DecodeBytes(Box<RustExpr>, Box<CaseLogic<ExprT>>),
     ParseView(Box<RustExpr>, Box<CaseLogic<ExprT>>),
     Permit(Box<CaseLogic<ExprT>>, Box<RustExpr>),
+    /// Resynchronization over the given byte-pattern, with the names of the resulting enum and of its `skipped` record
+    Resync(Label, Label, Box<CaseLogic<ExprT>>, Vec<ByteSet>),
     #[cfg(feature = "format_enforce")]
     Enforce(Box<CaseLogic<ExprT>>),
 }
@@ -3549,16 +4060,6 @@ impl ToAst for DerivedLogic<GTExpr> {
                 block
             }
             DerivedLogic::DecodeBytes(bytes_expr, inner_cl) => {
-                // boilerplate for creating a parser from the value-level buffer
-                let bind_parser_obj = GenStmt::Embed(model::let_mut_parser_new(
-                    model::DECODE_BUF_PARSER_OBJ,
-                    bytes_expr.clone().vec_as_slice(),
-                ));
-                let bind_ref_mut_parser = GenStmt::Embed(RustStmt::assign(
-                    model::DECODE_BUF_PARSER_REF,
-                    RustExpr::BorrowMut(Box::new(RustExpr::local(model::DECODE_BUF_PARSER_OBJ))),
-                ));
-
                 /*
                  * Instantiate the appropriately modified ProdCtxt for parsing from the value-level buffer,
                  * and perform the actual parsing within that context
@@ -3566,33 +4067,29 @@ impl ToAst for DerivedLogic<GTExpr> {
                 let bytes_ctxt = ProdCtxt {
                     input_varname: &Cow::Borrowed(model::DECODE_BUF_PARSER_REF),
                 };
-
-                let mut inner_block = inner_cl.to_ast(bytes_ctxt);
-                inner_block.prepend_stmts([bind_parser_obj, bind_ref_mut_parser]);
-                inner_block
+                let body = inner_cl.to_ast(bytes_ctxt).into_closure_body();
+                GenBlock::simple_expr(model::try_with_sub_parser(
+                    ctxt.parser(),
+                    bytes_expr.clone().vec_as_slice(),
+                    model::DECODE_BUF_PARSER_REF,
+                    body,
+                ))
             }
             DerivedLogic::ParseView(view, inner_cl) => {
-                // boilerplate for creating a parser from the view
-                let bind_parser_obj = GenStmt::Embed(model::let_mut_parser_from(
-                    model::PARSE_VIEW_PARSER_OBJ,
-                    (**view).clone(),
-                ));
-                let bind_ref_mut_parser = GenStmt::Embed(RustStmt::assign(
-                    model::PARSE_VIEW_PARSER_REF,
-                    RustExpr::BorrowMut(Box::new(RustExpr::local(model::PARSE_VIEW_PARSER_OBJ))),
-                ));
-
                 /*
                  * Instantiate the appropriately modified ProdCtxt for parsing from the View,
                  * and perform the actual parsing within that context
                  */
-                let bytes_ctxt = ProdCtxt {
+                let view_ctxt = ProdCtxt {
                     input_varname: &Cow::Borrowed(model::PARSE_VIEW_PARSER_REF),
                 };
-                let mut inner_block = inner_cl.to_ast(bytes_ctxt);
-
-                inner_block.prepend_stmts([bind_parser_obj, bind_ref_mut_parser]);
-                inner_block
+                let body = inner_cl.to_ast(view_ctxt).into_closure_body();
+                GenBlock::simple_expr(model::try_with_sub_parser_from(
+                    ctxt.parser(),
+                    (**view).clone(),
+                    model::PARSE_VIEW_PARSER_REF,
+                    body,
+                ))
             }
             DerivedLogic::Maybe(is_present, inner_cl) => {
                 let mut if_true = inner_cl.to_ast(ctxt);
@@ -3630,18 +4127,74 @@ impl ToAst for DerivedLogic<GTExpr> {
                 let block = GenBlock::from_parts(vec![bind_res], Some(ctrl));
                 block
             }
-            #[cfg(feature = "format_enforce")]
-            DerivedLogic::Enforce(inner) => {
-                // FIXME[epic=permit-enforce] - because soft-errors are logged transiently without any value-level record that they were encountered, we do not yet have any way of handling Enforce in generated code
-                let ret = inner.to_ast(ctxt);
-                ret
-            }
-            DerivedLogic::VariantOf(constr, inner) => {
-                let bind_inner = GenStmt::assign(model::VARIANT_INNER, inner.to_ast(ctxt));
-                let ret = GenExpr::TyValCon(RustExpr::Struct(
-                    constr.clone(),
-                    StructExpr::Tuple(vec![RustExpr::local(model::VARIANT_INNER)]),
-                ));
+            DerivedLogic::Resync(type_name, skipped_name, inner, pattern) => {
+                let start = GenStmt::Embed(RustStmt::Expr(model::start_alt(ctxt.parser())));
+                let rhs = inner.to_ast(ctxt).abstracted_try();
+                let bind_res = GenStmt::assign(model::PERMIT_BIND, rhs);
+                let if_ok = GenBlock::simple_expr(RustExpr::Struct(
+                    Constructor::Compound(type_name.clone(), Label::Borrowed("ok")),
+                    StructExpr::Tuple(vec![RustExpr::local(model::PERMIT_BIND)]),
+                ));
+                let if_err = {
+                    let skipped = RustExpr::Struct(
+                        Constructor::Simple(skipped_name.clone()),
+                        StructExpr::Record(vec![
+                            (
+                                Label::Borrowed("error"),
+                                Some(
+                                    RustExpr::local(model::PERMIT_ERR)
+                                        .call_method("to_string")
+                                        .call_method("into_bytes"),
+                                ),
+                            ),
+                            (
+                                Label::Borrowed("data"),
+                                Some(
+                                    model::try_resync(ctxt.parser(), pattern).call_method("to_vec"),
+                                ),
+                            ),
+                        ]),
+                    );
+                    GenBlock::lift_block(
+                        [RustStmt::Expr(model::try_next_alt(ctxt.parser(), true))],
+                        RustExpr::Struct(
+                            Constructor::Compound(type_name.clone(), Label::Borrowed("skipped")),
+                            StructExpr::Tuple(vec![skipped]),
+                        ),
+                    )
+                };
+                // NOTE - exceeded limits and internal errors are not parse failures, and so are propagated rather than skipped over
+                let reraise = GenBlock::explicit_return(RustExpr::ResultErr(Box::new(
+                    RustExpr::local(model::PERMIT_ERR),
+                )));
+                let ctrl = GenExpr::Control(Box::new(RustControl::Match(
+                    Box::new(RustExpr::local(model::PERMIT_BIND)),
+                    RustMatchBody::Irrefutable(
+                        [
+                            (model::match_case_ok_bind(model::PERMIT_BIND), if_ok),
+                            (
+                                model::match_case_err_unrecoverable(model::PERMIT_ERR),
+                                reraise,
+                            ),
+                            (model::match_case_err_bind(model::PERMIT_ERR), if_err),
+                        ]
+                        .to_vec(),
+                    ),
+                )));
+                GenBlock::from_parts(vec![start, bind_res], Some(ctrl))
+            }
+            #[cfg(feature = "format_enforce")]
+            DerivedLogic::Enforce(inner) => {
+                // FIXME[epic=permit-enforce] - because soft-errors are logged transiently without any value-level record that they were encountered, we do not yet have any way of handling Enforce in generated code
+                let ret = inner.to_ast(ctxt);
+                ret
+            }
+            DerivedLogic::VariantOf(constr, inner) => {
+                let bind_inner = GenStmt::assign(model::VARIANT_INNER, inner.to_ast(ctxt));
+                let ret = GenExpr::TyValCon(RustExpr::Struct(
+                    constr.clone(),
+                    StructExpr::Tuple(vec![RustExpr::local(model::VARIANT_INNER)]),
+                ));
                 GenBlock::from_parts(vec![bind_inner], Some(ret))
             }
             DerivedLogic::WrapSome(inner) => {
@@ -3755,17 +4308,150 @@ impl ToAst for DynamicLogic<GTExpr> {
     }
 }
 
+/// Options controlling the shape of the code emitted by [`generate_code_with`].
+#[derive(Clone, Debug, Default)]
+pub struct CodeGenOptions {
+    pub_api: bool,
+    embedded: bool,
+    serde: bool,
+    roots: Option<Vec<Label>>,
+    lazy: Vec<Label>,
+}
+
+impl CodeGenOptions {
+    pub const fn new() -> Self {
+        Self {
+            pub_api: false,
+            embedded: false,
+            serde: false,
+            roots: None,
+            lazy: Vec::new(),
+        }
+    }
+
+    /// Chainable method for toggling the emission of a public API over the generated code.
+    ///
+    /// When enabled, all fields of generated types are `pub`, a `pub fn decode_<root>(&[u8])` entry-point
+    /// is emitted for each root format, and a `Format` enum lists the available roots. Root formats are
+    /// those without arguments whose name is `main`, or ends in a `main` segment (e.g. `png.main`), unless
+    /// overridden by [`roots`](Self::roots).
+    pub fn pub_api(self, pub_api: bool) -> Self {
+        Self { pub_api, ..self }
+    }
+
+    /// Chainable method for toggling `serde` derives on all generated types, conditional on a `serde` cargo feature
+    /// in the crate the code is compiled into.
+    ///
+    /// Every type derives both `Serialize` and `Deserialize`. Fields borrowing from the input are marked
+    /// `#[serde(borrow)]`, while `View`s, `ReadArray`s, and `Lazy`s into the input (and variants holding
+    /// them) are marked `#[serde(skip_deserializing)]` and left empty when deserialized.
+    pub fn serde(self, serde: bool) -> Self {
+        Self { serde, ..self }
+    }
+
+    /// Attaches the feature-gated `serde` derives to a type-declaration item, if `serde` derives are enabled.
+    fn with_serde(&self, item: RustItem) -> RustItem {
+        if !self.serde {
+            return item;
+        }
+        let traits = vec![
+            Label::from("serde::Serialize"),
+            Label::from("serde::Deserialize"),
+        ];
+        item.with_serde(Label::from("serde"), DeclDerives(traits))
+    }
+
+    /// Chainable method for specifying the root formats explicitly, by name.
+    ///
+    /// Only those formats reachable from either the top-level format or one of the roots are generated, and
+    /// the roots (rather than those formats matching the naming convention) are the ones given public entry-points.
+    pub fn roots<Name: IntoLabel>(self, roots: impl IntoIterator<Item = Name>) -> Self {
+        Self {
+            roots: Some(roots.into_iter().map(Name::into).collect()),
+            ..self
+        }
+    }
+
+    /// Chainable method for specifying, by name, the formats whose parses at an offset are to be deferred.
+    ///
+    /// Wherever one of these formats is parsed through `ParseFromView` or `WithRelativeOffset` (possibly within a `Slice`),
+    /// the generated type holds a `Lazy` handle in its place, which only parses it when `.resolve(..)` is called.
+    /// Each lazy format must be the only one decoding to its type, as the handle relies on its `CommonObject` impl, and
+    /// cannot take arguments that are passed by reference. Other formats cannot inspect the deferred values.
+    pub fn lazy<Name: IntoLabel>(self, lazy: impl IntoIterator<Item = Name>) -> Self {
+        Self {
+            lazy: lazy.into_iter().map(Name::into).collect(),
+            ..self
+        }
+    }
+
+    /// Chainable method for toggling whether the generated code is to be embedded (via `include!`) within another module.
+    ///
+    /// When enabled, the crate-level attributes and submodule declarations that only make sense for the
+    /// `doodle_gencode` crate are omitted.
+    pub fn embedded(self, embedded: bool) -> Self {
+        Self { embedded, ..self }
+    }
+
+    /// Returns `true` if a decoder with the bespoke name `name` is the entry-point of a root format.
+    fn is_root(&self, name: &str) -> bool {
+        match &self.roots {
+            Some(roots) => roots.iter().any(|root| root == name),
+            None => name == "main" || name.ends_with(".main"),
+        }
+    }
+}
+
 // ANCHOR[epic=main-fn] - `generate_code` function
 /// Generates Rust code for a given `top_format` that is treated as the entry-point into a
 /// format-module `module`. Any formats within `module` that are inaccessible via exploration
 /// of `top_format` may be omitted from the resulting code-output.
 pub fn generate_code(module: &FormatModule, top_format: &Format) -> impl ToFragment + use<> {
+    generate_code_with(module, top_format, CodeGenOptions::new())
+        .expect("default options name no formats")
+}
+
+/// Variant of [`generate_code`] whose output is shaped by the specified `options`.
+///
+/// Fails if `options` name a root or lazy format that `module` does not define, or a lazy format that cannot be deferred.
+pub fn generate_code_with(
+    module: &FormatModule,
+    top_format: &Format,
+    options: CodeGenOptions,
+) -> CodeGenResult<impl ToFragment + use<>> {
+    // NOTE - each instantiation of a generic format is generated as a format in its own right
+    let (module, top_format) = crate::generic::monomorphize(module, top_format);
+    let (module, top_format) = (module.as_ref(), top_format.as_ref());
     let mut items = Vec::new();
 
     let Generator {
         sourcemap,
         mut elaborator,
-    } = Generator::compile(module, top_format);
+        program,
+    } = {
+        let roots = options
+            .roots
+            .as_ref()
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|name| match module.find_format(name) {
+                        Some(f) => Ok(f.get_level()),
+                        None => Err(CodeGenError::UnknownRoot { name: name.clone() }),
+                    })
+                    .collect::<CodeGenResult<Vec<_>>>()
+            })
+            .transpose()?;
+        let lazy = options
+            .lazy
+            .iter()
+            .map(|name| match module.find_format(name) {
+                Some(f) => Ok(f.get_level()),
+                None => Err(CodeGenError::UnknownLazy { name: name.clone() }),
+            })
+            .collect::<CodeGenResult<Vec<_>>>()?;
+        Generator::compile(module, top_format, roots.as_deref(), &lazy)
+    };
     let mut table = elaborator.codegen.name_gen.manifest_renaming_table();
     // Set of identifiers we have picked as bespoke names for decoder functions based on the type they are parsing (rather than sequentially enumerated)
     let mut fn_renames = BTreeSet::<Label>::new();
@@ -3786,6 +4472,28 @@ pub fn generate_code(module: &FormatModule, top_format: &Format) -> impl ToFragm
         ;
     let catalog = catalog::make_index(&type_decls, &sourcemap.decoder_skels);
 
+    // Deferred parses of lazy formats rely on the `CommonObject` impls of their types, which require a unique decoder
+    for decoder_fn in sourcemap.decoder_skels.iter() {
+        let Some(name) = decoder_fn.format_name.as_ref() else {
+            continue;
+        };
+        if !options.lazy.contains(name) {
+            continue;
+        }
+        let Some(ix) = decoder_fn.ret_type.as_decl_index() else {
+            return Err(CodeGenError::UnsupportedLazy {
+                name: name.clone(),
+                reason: "must decode to a type of its own",
+            });
+        };
+        if catalog.get(ix).is_none_or(|dec_ixs| dec_ixs.len() != 1) {
+            return Err(CodeGenError::UnsupportedLazy {
+                name: name.clone(),
+                reason: "must be the only format decoding to its type",
+            });
+        }
+    }
+
     let type_parse_info = model::traits::object_api::TypeParseInfo {
         catalog: &catalog,
         decoders: &sourcemap.decoder_skels,
@@ -3805,10 +4513,14 @@ pub fn generate_code(module: &FormatModule, top_format: &Format) -> impl ToFragm
         } else {
             TraitSet::DebugClone
         };
-        let it = RustItem::pub_decl_with_traits(
+        let mut it = RustItem::pub_decl_with_traits(
             RustDecl::TypeDef(name.clone(), type_decl.clone()),
             traits,
         );
+        if options.pub_api {
+            it = it.with_field_vis(Visibility::Public);
+        }
+        it = options.with_serde(it);
         let comments = {
             let mut tmp = Vec::new();
             let sz_comment = format!(
@@ -3857,6 +4569,10 @@ pub fn generate_code(module: &FormatModule, top_format: &Format) -> impl ToFragm
         items.push(it.with_comment(comments));
     }
 
+    // Bespoke names (if any) of each decoder function, to be shared with its encoder
+    let mut adhoc_names = Vec::with_capacity(sourcemap.decoder_skels.len());
+    // Signature and decoder of each root format, if a public API is to be emitted
+    let mut roots = Vec::new();
     for (ix, ref mut decoder_fn) in sourcemap.decoder_skels.into_iter().enumerate() {
         decoder_fn.rebind(&table);
         if let Some(name) = &decoder_fn.adhoc_name {
@@ -3869,8 +4585,50 @@ pub fn generate_code(module: &FormatModule, top_format: &Format) -> impl ToFragm
                 table.insert(decoder_fname(decoder_fn.ixlabel), replacement_name);
             }
         };
-        let func = decoder_fn.to_ast(ProdCtxt::default());
+        adhoc_names.push(decoder_fn.adhoc_name.clone());
+        let (func, body_func) = decoder_fn.to_ast(ProdCtxt::default());
+        if options.pub_api
+            && decoder_fn.extra_args.as_ref().is_none_or(Vec::is_empty)
+            && let Some(name) = decoder_fn.adhoc_name.as_ref()
+            && options.is_root(name)
+        {
+            roots.push((
+                name.clone(),
+                decoder_fn.ixlabel,
+                decoder_fn.ret_type.clone(),
+            ));
+        }
         items.push(RustItem::from_decl(RustDecl::Function(func)).with_comment([format!("d#{ix}")]));
+        items.push(RustItem::from_decl(RustDecl::Function(body_func)));
+    }
+
+    for (_, expr_fn) in elaborator.t_functions.iter() {
+        let Some(expr_fn) = expr_fn else {
+            unreachable!("function left unelaborated")
+        };
+        items.push(RustItem::from_decl(RustDecl::Function(
+            expr_fn.to_ast(ProdCtxt::default()),
+        )));
+    }
+
+    // NOTE - encoders are compiled only after all decoders are generated, so that decoder trace-hashes are unaffected by their presence
+    let encoder_skels = typed_encoder::compile_encoders(type_context, &program.decoders);
+    for (ix, encoder_fn) in encoder_skels.into_iter().enumerate() {
+        let Some(encoder_fn) = encoder_fn else {
+            continue;
+        };
+        if let Some(name) = &adhoc_names[ix] {
+            table.insert(
+                encoder_fname(IxLabel::from(ix)),
+                Label::from(format!("Encoder_{}", sanitize_label(name))),
+            );
+        }
+        let func = encoder_fn.to_ast(ProdCtxt::default());
+        items.push(RustItem::from_decl(RustDecl::Function(func)).with_comment([format!("e#{ix}")]));
+    }
+
+    if !roots.is_empty() {
+        items.extend(root_api(roots, &options));
     }
 
     let mut content = RustProgram::from_iter(items);
@@ -3886,22 +4644,109 @@ pub fn generate_code(module: &FormatModule, top_format: &Format) -> impl ToFragm
         path: vec!["doodle".into()],
         uses: RustImportItems::Singleton(Label::Borrowed("try_sub")),
     });
-    for attr_string in [
-        "unused_imports",
-        "non_camel_case_types",
-        "non_snake_case",
-        "dead_code",
-    ]
-    .into_iter()
-    {
-        content.add_module_attr(ModuleAttr::Allow(AllowAttr::from(Label::from(attr_string))));
+    if !options.embedded {
+        for attr_string in [
+            "unused_imports",
+            "non_camel_case_types",
+            "non_snake_case",
+            "dead_code",
+        ]
+        .into_iter()
+        {
+            content.add_module_attr(ModuleAttr::Allow(AllowAttr::from(Label::from(attr_string))));
+        }
+        content.add_module_attr(ModuleAttr::RustFmtSkip);
+        content.add_submodule(RustSubmodule::new("codegen_tests"));
+        content.add_submodule(RustSubmodule::new_pub("api_helper"));
     }
-    content.add_module_attr(ModuleAttr::RustFmtSkip);
-    content.add_submodule(RustSubmodule::new("codegen_tests"));
-    content.add_submodule(RustSubmodule::new_pub("api_helper"));
     content.rebind(&table);
     content.resolve(&src_context);
-    content
+    Ok(content)
+}
+
+/// Generates the public entry-points for each root format, along with an enum listing all of them.
+fn root_api(roots: Vec<(Label, IxLabel, RustType)>, options: &CodeGenOptions) -> Vec<RustItem> {
+    let mut items = Vec::with_capacity(roots.len() + 1);
+    let variants = roots
+        .iter()
+        .map(|(name, ..)| RustVariant::Unit(name.clone()))
+        .collect();
+    let format_decl = RustTypeDecl {
+        def: RustTypeDef::Enum(variants),
+        lt: None,
+    };
+    let format_item = options.with_serde(RustItem::pub_decl_with_traits(
+        RustDecl::TypeDef(Label::from("Format"), format_decl),
+        TraitSet::DebugCopy,
+    ));
+    items.push(format_item.with_comment(["Root formats with a public `decode_*` entry-point"]));
+    for (name, ixlabel, ret_type) in roots {
+        let lt = ret_type.lt_param().cloned();
+        let params = lt
+            .as_ref()
+            .map(|lt| DefParams::from_lt(lt.as_ref().clone()));
+        let bytes_arg = (
+            Label::from("bytes"),
+            RustType::borrow_of(lt, Mut::Immutable, RustType::vec_of(PrimType::U8.into())),
+        );
+        let ret = Some(RustType::result_of(
+            ret_type,
+            RustType::imported("ParseError"),
+        ));
+        let sig = FnSig::new(vec![bytes_arg.clone()], ret.clone());
+        let body = vec![
+            model::let_mut_parser_new("input", RustExpr::local("bytes")),
+            RustStmt::Return(
+                ReturnKind::Implicit,
+                RustExpr::local(decoder_fname(ixlabel))
+                    .call_with([RustExpr::BorrowMut(Box::new(RustExpr::local("input")))]),
+            ),
+        ];
+        let fname = Label::from(format!("decode_{}", sanitize_label(&name)));
+        items.push(
+            RustItem::pub_decl(RustDecl::Function(RustFn::new(
+                fname.clone(),
+                params.clone(),
+                sig,
+                body,
+            )))
+            .with_comment([format!("Decodes a `{name}` from the start of `bytes`")]),
+        );
+
+        let limits_sig = FnSig::new(
+            vec![
+                bytes_arg,
+                (Label::from("limits"), RustType::imported("Limits")),
+            ],
+            ret,
+        );
+        let limits_body = vec![
+            RustStmt::Let(
+                Mut::Mutable,
+                Label::from("input"),
+                None,
+                model::parser_new(RustExpr::local("bytes"))
+                    .call_method_with("with_limits", [RustExpr::local("limits")]),
+            ),
+            RustStmt::Return(
+                ReturnKind::Implicit,
+                RustExpr::local(decoder_fname(ixlabel))
+                    .call_with([RustExpr::BorrowMut(Box::new(RustExpr::local("input")))]),
+            ),
+        ];
+        items.push(
+            RustItem::pub_decl(RustDecl::Function(RustFn::new(
+                Label::from(format!("{fname}_with_limits")),
+                params,
+                limits_sig,
+                limits_body,
+            )))
+            .with_comment([format!(
+                "Decodes a `{name}` from the start of `bytes`, failing with `ParseError::LimitExceeded` if decoding would exceed `limits`"
+            )]),
+        );
+    }
+    items
 }
 
 fn decoder_fname(ixlabel: IxLabel) -> Label {
@@ -3911,6 +4756,8 @@ fn decoder_fname(ixlabel: IxLabel) -> Label {
 #[derive(Clone, Debug)]
 pub struct DecoderFn<ExprT> {
     adhoc_name: Option<Label>,
+    /// Name of the format this decoder was compiled from, if any
+    format_name: Option<Label>,
     ixlabel: IxLabel,
     logic: CaseLogic<ExprT>,
     extra_args: Option<Vec<(Label, GenType)>>,
@@ -3922,9 +4769,13 @@ where
     CaseLogic<ExprT>: ToAst<AstElem = GenBlock>,
     ExprT: std::fmt::Debug,
 {
-    type AstElem = RustFn;
+    /// The decoder function itself, followed by the function holding its body.
+    ///
+    /// The decoder enters its body one level of format-nesting deeper (subject to the resource limits of the parse),
+    /// and attributes any error out of it to the format it was compiled from.
+    type AstElem = (RustFn, RustFn);
 
-    fn to_ast(&self, _ctxt: ProdCtxt<'_>) -> RustFn {
+    fn to_ast(&self, _ctxt: ProdCtxt<'_>) -> (RustFn, RustFn) {
         let name = decoder_fname(self.ixlabel);
 
         // properly capture associated lifetimes of extra arguments
@@ -3990,9 +4841,7 @@ where
             input_varname: &Label::from("_input"),
         };
         // NOTE - this is the last place we can modify the GenBlock before it is manifested as pure RustAST constructs
-        let self_block = self.logic.to_ast(ctxt);
-
-        let (stmts, ret) = self_block.synthesize();
+        let (stmts, ret) = self.logic.to_ast(ctxt).synthesize();
         let body = if let Some(ret) = ret {
             Iterator::chain(
                 stmts.into_iter(),
@@ -4005,8 +4854,83 @@ where
         } else {
             stmts
         };
+        let body_name = Label::from(format!("{name}_body"));
+        let body_fn = RustFn::new(body_name.clone(), params.clone(), sig.clone(), body);
+
+        let call_body = {
+            let args = std::iter::once(ctxt.parser())
+                .chain(
+                    self.extra_args
+                        .iter()
+                        .flatten()
+                        .map(|(lab, _)| RustExpr::local(lab.clone())),
+                )
+                .collect::<Vec<_>>();
+            RustExpr::local(body_name).call_with(args)
+        };
+        let res = match &self.format_name {
+            Some(name) => {
+                model::map_err_within(RustExpr::local("res"), name.clone(), ctxt.parser())
+            }
+            None => RustExpr::local("res"),
+        };
+        let stmts = vec![
+            RustStmt::Expr(model::try_enter(ctxt.parser())),
+            RustStmt::assign("res", call_body),
+            RustStmt::Expr(model::leave(ctxt.parser())),
+            RustStmt::Return(ReturnKind::Implicit, res),
+        ];
+        (RustFn::new(name, params, sig, stmts), body_fn)
+    }
+}
+
+/// Returns the name of the Rust function generated for the module function `name`.
+fn function_fname(name: &Label) -> Label {
+    Label::from(format!("Function_{}", sanitize_label(name)))
+}
+
+/// Elaborated function of the module, from which a single Rust function is generated for every call to share.
+#[derive(Clone, Debug)]
+pub struct ExprFn {
+    name: Label,
+    params: Vec<(Label, GenType)>,
+    ret: GenType,
+    body: GTExpr,
+}
+
+impl ToAst for ExprFn {
+    type AstElem = RustFn;
 
-        RustFn::new(name, params, sig, body)
+    fn to_ast(&self, _ctxt: ProdCtxt<'_>) -> RustFn {
+        let ret_type = self.ret.to_rust_type();
+        let param_types = Vec::from_iter(self.params.iter().map(|(_, gt)| gt.to_rust_type()));
+        let this_lt = ret_type
+            .lt_param()
+            .or_else(|| param_types.iter().find_map(RustType::lt_param))
+            .cloned();
+        let params = this_lt.map(|lt| DefParams::from_lt(lt.as_ref().clone()));
+        let args = Iterator::zip(self.params.iter(), param_types)
+            .map(|((lab, _), ty)| {
+                (
+                    lab.clone(),
+                    RustType::selective_borrow(None, Mut::Immutable, ty),
+                )
+            })
+            .collect();
+        let sig = FnSig::new(
+            args,
+            Some(RustType::result_of(
+                ret_type,
+                RustType::imported("ParseError"),
+            )),
+        );
+        let body = embed_expr_owned(&self.body).wrap_ok(Some("PResult"));
+        RustFn::new(
+            function_fname(&self.name),
+            params,
+            sig,
+            vec![RustStmt::Return(ReturnKind::Implicit, body)],
+        )
     }
 }
 
@@ -4026,22 +4950,35 @@ impl<TypeRep> SourceMap<TypeRep> {
 pub struct Generator<'a> {
     pub(crate) elaborator: Elaborator<'a>,
     pub(crate) sourcemap: SourceMap<GTExpr>,
+    pub(crate) program: TypedProgram<GenType>,
 }
 
 impl<'a> Generator<'a> {
-    pub fn compile(module: &'a FormatModule, top_format: &Format) -> Self {
-        let tc = TypeChecker::infer_module(module, top_format)
+    /// Compiles the decoders for `top_format`, along with those of either every format in `module`, or only the
+    /// specified root levels (and any formats they reach) if `roots` is provided.
+    ///
+    /// Parses of the `lazy` levels at an offset are deferred, rather than performed in place.
+    pub fn compile(
+        module: &'a FormatModule,
+        top_format: &Format,
+        roots: Option<&[usize]>,
+        lazy: &[usize],
+    ) -> Self {
+        let tc = TypeChecker::infer_module(module, top_format, roots, lazy)
             .unwrap_or_else(|err| panic!("Failed to infer module-wide type annotations: {err}"));
         let mut cgen = Self {
             elaborator: Elaborator::new(module, tc, CodeGen::new()),
             sourcemap: SourceMap::new(),
+            program: TypedProgram::new(),
         };
         let elab = &mut cgen.elaborator;
 
-        let (top, extra) = elab.elaborate_module(module, top_format);
+        let (top, extra) = elab.elaborate_module(module, top_format, roots);
         let prog =
             GTCompiler::compile_program(module, &top, &extra).expect("failed to compile program");
-        for (ix, (dec_ext, t)) in prog.decoders.iter().enumerate() {
+        for (ix, ((dec_ext, t), format_name)) in
+            Iterator::zip(prog.decoders.iter(), prog.format_names.iter()).enumerate()
+        {
             let dec_fn = {
                 let dec = dec_ext.get_dec();
                 let args = dec_ext.get_args();
@@ -4053,6 +4990,7 @@ impl<'a> Generator<'a> {
                 let cl = elab.codegen.translate(dec);
                 DecoderFn {
                     adhoc_name,
+                    format_name: format_name.clone(),
                     ixlabel: IxLabel::from(ix),
                     logic: cl,
                     extra_args: args.clone(),
@@ -4061,6 +4999,7 @@ impl<'a> Generator<'a> {
             };
             cgen.sourcemap.decoder_skels.push(dec_fn);
         }
+        cgen.program = prog;
 
         cgen
     }
@@ -4070,6 +5009,8 @@ pub struct Elaborator<'a> {
     module: &'a FormatModule,
     next_index: usize,
     t_formats: StableMap<usize, Rc<GTFormat>, BTree>,
+    /// Elaborated functions of the module, by index (`None` while the body of the function is being elaborated)
+    t_functions: StableMap<usize, Option<ExprFn>, BTree>,
     tc: TypeChecker,
     codegen: CodeGen,
 }
@@ -4194,6 +5135,39 @@ impl<'a> Elaborator<'a> {
                 let gt = self.get_gt_from_index(index);
                 GTPattern::Option(gt, t_pat)
             }
+            Pattern::Record(fields) => {
+                let mut t_fields = Vec::with_capacity(fields.len());
+                for (fname, p) in fields {
+                    let t_p = self.elaborate_pattern(p);
+                    t_fields.push((fname.clone(), t_p));
+                }
+                let gt = self.get_gt_from_index(index);
+                GTPattern::Record(gt, t_fields)
+            }
+            Pattern::Or(alts) => {
+                let mut t_alts = Vec::with_capacity(alts.len());
+                for alt in alts {
+                    let t_alt = self.elaborate_pattern(alt);
+                    t_alts.push(t_alt);
+                }
+                let gt = self.get_gt_from_index(index);
+                GTPattern::Or(gt, t_alts)
+            }
+            Pattern::Guard(..) => {
+                unreachable!("misplaced guard should have been rejected by typechecker")
+            }
+        }
+    }
+
+    /// Elaborates the pattern of a match arm, along with its guard (if any).
+    fn elaborate_arm_pattern(&mut self, pat: &Pattern) -> TypedPattern<GenType> {
+        match pat.split_guard() {
+            (pat, None) => self.elaborate_pattern(pat),
+            (pat, Some(guard)) => {
+                let t_pat = self.elaborate_pattern(pat);
+                let t_guard = self.elaborate_expr(guard);
+                GTPattern::Guard(Box::new(t_pat), Box::new(t_guard))
+            }
         }
     }
 
@@ -4202,6 +5176,7 @@ impl<'a> Elaborator<'a> {
             module,
             next_index: 0,
             t_formats: Default::default(),
+            t_functions: Default::default(),
             tc,
             codegen,
         }
@@ -4289,6 +5264,9 @@ impl<'a> Elaborator<'a> {
                 self.codegen.name_gen.ctxt.escape();
                 TypedFormat::FormatCall(gt, *level, t_args, t_views, t_inner)
             }
+            Format::GenericVar(..) => {
+                unreachable!("generic formats must be monomorphized before elaboration")
+            }
             Format::ForEach(expr, lbl, inner) => {
                 let index = self.get_and_increment_index();
                 let t_expr = self.elaborate_expr(expr);
@@ -4338,6 +5316,14 @@ impl<'a> Elaborator<'a> {
                 let gt = self.get_gt_from_index(index);
                 TypedFormat::Pos(gt)
             }
+            Format::Native(native_index, args) => {
+                let index = self.get_and_increment_index();
+                let t_args = args.iter().map(|arg| self.elaborate_expr(arg)).collect();
+                let native = self.module.get_native(*native_index);
+                self.skip_declared_valuetype(&native.value_type);
+                let gt = self.get_gt_from_index(index);
+                TypedFormat::Native(gt, *native_index, native.rust_path.clone(), t_args)
+            }
             Format::Byte(bs) => {
                 self.increment_index();
                 TypedFormat::Byte(*bs)
@@ -4544,6 +5530,19 @@ impl<'a> Elaborator<'a> {
                 let gt = self.get_gt_from_index(index);
                 TypedFormat::Permit(gt, Box::new(t_inner), Box::new(t_dft))
             }
+            Format::Resync(inner, pattern) => {
+                let index = self.get_and_increment_index();
+                self.codegen
+                    .name_gen
+                    .ctxt
+                    .push_atom(NameAtom::Variant(Label::Borrowed("ok")));
+                let t_inner = self.elaborate_format(inner, dyn_scope);
+                self.codegen.name_gen.ctxt.escape();
+                self.increment_index();
+                self.skip_declared_valuetype(&ValueType::resync_skipped());
+                let gt = self.get_gt_from_index(index);
+                TypedFormat::Resync(gt, Box::new(t_inner), pattern.clone())
+            }
             Format::Where(inner, cond) => {
                 let index = self.get_and_increment_index();
                 let t_inner = self.elaborate_format(inner, dyn_scope);
@@ -4587,7 +5586,7 @@ impl<'a> Elaborator<'a> {
                 let mut t_branches = Vec::with_capacity(branches.len());
                 for (pat, rhs) in branches {
                     self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
-                    let t_pat = self.elaborate_pattern(pat);
+                    let t_pat = self.elaborate_arm_pattern(pat);
                     self.codegen.name_gen.ctxt.escape();
 
                     let t_rhs = self.elaborate_format(rhs, dyn_scope);
@@ -4675,15 +5674,10 @@ impl<'a> Elaborator<'a> {
                             // double-check the base kind against the type
                             // FIXME - extract into proper method that can then support future common-ops more adequately
                             let ty = gt.to_rust_type();
-                            let prim1 = PrimType::from(BaseType::from(*base_kind));
-                            let Some(prim0) = ty.try_as_prim() else {
-                                unreachable!(
-                                    "found non-primitive type for common format elaboration: {ty:?} @ {index} (expected {prim1:?})"
-                                );
-                            };
+                            let expected = RustType::from(*base_kind);
                             assert_eq!(
-                                prim0, prim1,
-                                "CommonOp: actual inner-parse type ({prim0:?}) does not match claimed type ({prim1:?})"
+                                ty, expected,
+                                "CommonOp: actual inner-parse type ({ty:?}) does not match claimed type ({expected:?}) @ {index}"
                             );
                         }
                     },
@@ -4885,6 +5879,17 @@ impl<'a> Elaborator<'a> {
                 let gt = self.get_gt_from_index(index);
                 TypedExpr::Append(gt, Box::new(t_lhs), Box::new(t_rhs))
             }
+            Expr::Call(fn_index, ret, args) => {
+                self.elaborate_function(*fn_index);
+
+                self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
+                let t_args = args.iter().map(|arg| self.elaborate_expr(arg)).collect();
+                self.codegen.name_gen.ctxt.escape();
+
+                let gt = self.get_gt_from_index(index);
+                let name = self.module.get_function(*fn_index).name.clone();
+                TypedExpr::Call(gt, *fn_index, name, ret.clone(), t_args)
+            }
             Expr::RecordProj(e, fld) => {
                 self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
                 let t_e = self.elaborate_expr(e);
@@ -4901,7 +5906,7 @@ impl<'a> Elaborator<'a> {
 
                 let mut t_branches = Vec::with_capacity(branches.len());
                 for (pat, rhs) in branches {
-                    let t_pat = self.elaborate_pattern(pat);
+                    let t_pat = self.elaborate_arm_pattern(pat);
                     let t_rhs = self.elaborate_expr(rhs);
                     t_branches.push((t_pat, t_rhs));
                 }
@@ -4913,7 +5918,7 @@ impl<'a> Elaborator<'a> {
                 let t_head = self.elaborate_expr(head);
                 self.codegen.name_gen.ctxt.escape();
 
-                let t_pat = self.elaborate_pattern(pattern);
+                let t_pat = self.elaborate_arm_pattern(pattern);
                 let t_expr = self.elaborate_expr(expr);
                 let gt = self.get_gt_from_index(index);
                 TypedExpr::Destructure(gt, Box::new(t_head), t_pat, Box::new(t_expr))
@@ -4981,6 +5986,18 @@ impl<'a> Elaborator<'a> {
                 let t_inner = self.elaborate_expr(inner);
                 TypedExpr::AsChar(Box::new(t_inner))
             }
+            Expr::F32FromBits(bits) => {
+                let t_bits = self.elaborate_expr(bits);
+                TypedExpr::F32FromBits(Box::new(t_bits))
+            }
+            Expr::F64FromBits(bits) => {
+                let t_bits = self.elaborate_expr(bits);
+                TypedExpr::F64FromBits(Box::new(t_bits))
+            }
+            Expr::FixedFromBits(fx, bits) => {
+                let t_bits = self.elaborate_expr(bits);
+                TypedExpr::FixedFromBits(*fx, Box::new(t_bits))
+            }
             Expr::U16Be(bytes) => {
                 let t_bytes = self.elaborate_expr(bytes);
                 TypedExpr::U16Be(Box::new(t_bytes))
@@ -5116,6 +6133,73 @@ impl<'a> Elaborator<'a> {
                     Box::new(t_seq),
                 )
             }
+            Expr::Filter(lambda, seq) => {
+                self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
+                let t_lambda = self.elaborate_expr_lambda(lambda);
+                self.codegen.name_gen.ctxt.escape();
+
+                self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
+                let t_seq = self.elaborate_expr(seq);
+                self.codegen.name_gen.ctxt.escape();
+
+                let gt = self.get_gt_from_index(index);
+                TypedExpr::Filter(gt, Box::new(t_lambda), Box::new(t_seq))
+            }
+            Expr::Zip(seq0, seq1) => {
+                self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
+                let t_seq0 = self.elaborate_expr(seq0);
+                self.codegen.name_gen.ctxt.escape();
+
+                self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
+                let t_seq1 = self.elaborate_expr(seq1);
+                self.codegen.name_gen.ctxt.escape();
+
+                {
+                    // account for the element variables of both sequences, and of their pairing
+                    self.increment_index();
+                    self.increment_index();
+                    self.increment_index();
+                }
+
+                let gt = self.get_gt_from_index(index);
+                TypedExpr::Zip(gt, Box::new(t_seq0), Box::new(t_seq1))
+            }
+            Expr::Reverse(seq) => {
+                let t_seq = self.elaborate_expr(seq);
+                // NOTE - for element type of sequence
+                self.increment_index();
+                let gt = self.get_gt_from_index(index);
+                TypedExpr::Reverse(gt, Box::new(t_seq))
+            }
+            Expr::Any(lambda, seq) | Expr::All(lambda, seq) => {
+                self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
+                let t_lambda = self.elaborate_expr_lambda(lambda);
+                self.codegen.name_gen.ctxt.escape();
+
+                self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
+                let t_seq = self.elaborate_expr(seq);
+                self.codegen.name_gen.ctxt.escape();
+
+                if matches!(expr, Expr::Any(..)) {
+                    TypedExpr::Any(Box::new(t_lambda), Box::new(t_seq))
+                } else {
+                    TypedExpr::All(Box::new(t_lambda), Box::new(t_seq))
+                }
+            }
+            Expr::IsSortedBy(lambda, seq) => {
+                self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
+                let t_lambda = self.elaborate_expr_lambda(lambda);
+                self.codegen.name_gen.ctxt.escape();
+
+                self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
+                let t_seq = self.elaborate_expr(seq);
+                self.codegen.name_gen.ctxt.escape();
+
+                // NOTE - for element type of sequence
+                self.increment_index();
+
+                TypedExpr::IsSortedBy(Box::new(t_lambda), Box::new(t_seq))
+            }
             Expr::FlatMapList(lambda, _ret_type, seq) => {
                 self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
                 let t_lambda = self.elaborate_expr_lambda(lambda);
@@ -5192,10 +6276,18 @@ impl<'a> Elaborator<'a> {
     }
 
     /// NOTE - this *MUST* be kept in lockstep with [`TypeChecker::infer_module`]
-    fn elaborate_module(&mut self, module: &'a FormatModule, top_format: &Format) -> ElabForest {
+    fn elaborate_module(
+        &mut self,
+        module: &'a FormatModule,
+        top_format: &Format,
+        roots: Option<&[usize]>,
+    ) -> ElabForest {
         let dyn_s = TypedDynScope::Empty;
 
-        let mut unexplored = BTreeSet::from_iter(0..module.formats.len());
+        let mut unexplored = match roots {
+            Some(levels) => BTreeSet::from_iter(levels.iter().copied()),
+            None => BTreeSet::from_iter(0..module.formats.len()),
+        };
         let top = self.elaborate_format(top_format, &dyn_s);
 
         let mut seen_levels = self.t_formats.keys().copied().collect::<BTreeSet<usize>>();
@@ -5211,17 +6303,16 @@ impl<'a> Elaborator<'a> {
             };
 
             // instantiate proper name-scope for extra levels
+            let name = Label::from(self.module.get_name(next_level).to_string());
             self.codegen
                 .name_gen
                 .ctxt
-                .push_atom(NameAtom::Explicit(Label::from(
-                    self.module.get_name(next_level).to_string(),
-                )));
+                .push_atom(NameAtom::Explicit(name.clone()));
             let local_root = self.elaborate_format(module.get_format(next_level), &dyn_s);
             // clean-up
             self.codegen.name_gen.ctxt.escape();
 
-            extra.push(local_root);
+            extra.push((name, local_root));
             let all_seen_levels = self.t_formats.keys().copied().collect::<BTreeSet<usize>>();
             for just_seen in all_seen_levels.difference(&seen_levels) {
                 unexplored.remove(just_seen);
@@ -5231,6 +6322,46 @@ impl<'a> Elaborator<'a> {
         (top, extra)
     }
 
+    /// Elaborates the function at `index` upon the first call to it, mirroring `TypeChecker::infer_vars_function`.
+    fn elaborate_function(&mut self, index: usize) {
+        if self.t_functions.contains_key(&index) {
+            return;
+        }
+        let module = self.module;
+        let func = module.get_function(index);
+        self.codegen
+            .name_gen
+            .ctxt
+            .push_atom(NameAtom::Explicit(func.name.clone()));
+
+        self.codegen.name_gen.ctxt.push_atom(NameAtom::DeadEnd);
+        let mut params = Vec::with_capacity(func.params.len());
+        for (lbl, vt) in func.params.iter() {
+            let param_index = self.get_and_increment_index();
+            self.force_unify_against_valuetype(vt);
+            params.push((lbl.clone(), self.get_gt_from_index(param_index)));
+        }
+        self.codegen.name_gen.ctxt.escape();
+
+        let ret_index = self.get_and_increment_index();
+        self.force_unify_against_valuetype(&func.ret);
+        let ret = self.get_gt_from_index(ret_index);
+
+        // registered ahead of the body, for any recursive calls within it
+        self.t_functions.insert(index, None);
+        let body = self.elaborate_expr(func.body());
+        self.codegen.name_gen.ctxt.escape();
+        self.t_functions.insert(
+            index,
+            Some(ExprFn {
+                name: func.name.clone(),
+                params,
+                ret,
+                body,
+            }),
+        );
+    }
+
     fn force_unify_against_valuetype(&mut self, vt: &ValueType) {
         match UType::from_valuetype(vt) {
             Some(_) => (),
@@ -5259,6 +6390,29 @@ impl<'a> Elaborator<'a> {
         }
     }
 
+    /// Mirrors `TypeChecker::unify_var_declared`, which allocates one `UVar` per nested component.
+    fn skip_declared_valuetype(&mut self, vt: &ValueType) {
+        match vt {
+            ValueType::Tuple(ts) => ts.iter().for_each(|t| {
+                self.increment_index();
+                self.skip_declared_valuetype(t);
+            }),
+            ValueType::Record(fs) => fs.iter().for_each(|(_, t)| {
+                self.increment_index();
+                self.skip_declared_valuetype(t);
+            }),
+            ValueType::Union(branches) => branches.iter().for_each(|(_, t)| {
+                self.increment_index();
+                self.skip_declared_valuetype(t);
+            }),
+            ValueType::Seq(inner) | ValueType::Option(inner) => {
+                self.increment_index();
+                self.skip_declared_valuetype(inner);
+            }
+            _ => self.force_unify_against_valuetype(vt),
+        }
+    }
+
     fn force_unify_against_valuetype_union<'b>(
         &mut self,
         branches: impl IntoIterator<Item = (&'b Label, &'b ValueType)> + 'b,
@@ -5272,7 +6426,7 @@ impl<'a> Elaborator<'a> {
     }
 }
 
-type ElabForest = (GTFormat, Vec<GTFormat>);
+type ElabForest = (GTFormat, Vec<(Label, GTFormat)>);
 
 type GTFormat = TypedFormat<GenType>;
 type GTExpr = TypedExpr<GenType>;
@@ -5534,19 +6688,26 @@ mod __impls {
 mod tests {
     use super::*;
     use crate::TypeHint;
-    use crate::helper::{ANY_BYTE, compute, record, succ, var};
+    use crate::helper::{
+        ANY_BYTE, add, as_u32, bind, capture_bytes, chain, compute, expr_eq, expr_gt, expr_if_else,
+        expr_lte, fmt_match, fmt_variant, from_here, index_unchecked, is_byte, is_sorted_by,
+        lambda, let_view, monad_seq, parse_from_view, pat_guard, pat_or, pat_record, pred,
+        read_array, record, record_proj, reify_view, rem, repeat, repeat_count, resync, seq_all,
+        seq_any, seq_filter, seq_length, seq_reverse, seq_zip, slice, sub_seq, succ, tuple,
+        tuple_proj, u8, union, var, vvar,
+    };
     use crate::numeric::MachineRep;
     use proptest::prelude::*;
 
     fn population_check(module: &FormatModule, f: &Format, label: Option<&'static str>) {
-        let tc = TypeChecker::infer_module(module, f).unwrap();
+        let tc = TypeChecker::infer_module(module, f, None, &[]).unwrap();
         let tc_pop = tc.size();
 
         // println!("{tc:?}");
 
         let cg = CodeGen::new();
         let mut tv = Elaborator::new(module, tc, cg);
-        let (dec_f, _extra) = tv.elaborate_module(module, f);
+        let (dec_f, _extra) = tv.elaborate_module(module, f, None);
         let re_f = Format::from(dec_f.clone());
         assert_eq!(
             &re_f,
@@ -5693,6 +6854,366 @@ mod tests {
         println!("{}", output);
     }
 
+    #[test]
+    fn test_pub_api_output() {
+        let mut module = FormatModule::new();
+        let inner = module.define_format("test.main", record([("x", ANY_BYTE), ("y", ANY_BYTE)]));
+        let f = inner.call();
+        let options = CodeGenOptions::new().pub_api(true);
+        let output = generate_code_with(&module, &f, options)
+            .unwrap()
+            .to_fragment()
+            .to_string();
+        assert!(output.contains("pub x: u8"));
+        assert!(output.contains("pub enum Format { test_main }"));
+        assert!(
+            output
+                .contains("pub fn decode_test_main(bytes: &[u8]) -> Result<test_main, ParseError>")
+        );
+        let default_output = produce_string_gencode(&module, &f);
+        assert!(!default_output.contains("pub x: u8"));
+        assert!(!default_output.contains("decode_test_main"));
+    }
+
+    #[test]
+    fn test_native_output() {
+        use crate::decoder::Value;
+        use crate::native::{NativeError, NativeFormat};
+
+        fn unused(_: &[u8], _: &[Value]) -> Result<(Value, usize), NativeError> {
+            Err(NativeError::new("unused"))
+        }
+
+        let mut module = FormatModule::new();
+        let native = module.define_native_format(
+            "test.native",
+            vec![(Label::Borrowed("n"), ValueType::Base(BaseType::U8))],
+            NativeFormat::new(
+                ValueType::Record(vec![
+                    ("x".into(), ValueType::Base(BaseType::U16)),
+                    (
+                        "y".into(),
+                        ValueType::Seq(Box::new(ValueType::Base(BaseType::U8))),
+                    ),
+                ]),
+                unused,
+                "natives::decode_test",
+            ),
+        );
+        let f = record([
+            ("len", ANY_BYTE),
+            ("data", native.call_args(vec![var("len")])),
+        ]);
+        population_check(&module, &f, None);
+        let output = produce_string_gencode(&module, &f);
+        assert!(output.contains("_input.decode_native(|_input| natives::decode_test(_input, n))"));
+        assert!(output.contains("struct test_native"));
+    }
+
+    #[test]
+    fn test_or_guard_pattern_output() {
+        let mut module = FormatModule::new();
+        let hdr = module.define_format("test.hdr", record([("kind", u8()), ("len", u8())]));
+        let kind_pat = |kind: u8| pat_record([("kind", Pattern::U8(kind))]);
+        let main = module.define_format(
+            "test.main",
+            chain(
+                hdr.call(),
+                "hdr",
+                fmt_match(
+                    var("hdr"),
+                    [
+                        (
+                            pat_guard(
+                                pat_or([kind_pat(1), kind_pat(2)]),
+                                expr_gt(record_proj(var("hdr"), "len"), Expr::U8(4)),
+                            ),
+                            compute(Expr::U8(1)),
+                        ),
+                        (
+                            pat_or([
+                                pat_record([("kind", Pattern::U8(3)), ("len", bind("n"))]),
+                                pat_record([("kind", Pattern::U8(4)), ("len", bind("n"))]),
+                            ]),
+                            compute(var("n")),
+                        ),
+                        (Pattern::Wildcard, compute(Expr::U8(255))),
+                    ],
+                ),
+            ),
+        );
+        let f = main.call();
+        population_check(&module, &f, None);
+        let output = produce_string_gencode(&module, &f);
+        assert!(output.contains(
+            "test_hdr { kind: 1u8, .. } | test_hdr { kind: 2u8, .. } if (hdr.len > 4u8) => {"
+        ));
+        assert!(output.contains(
+            "test_hdr { kind: 3u8, len: n, .. } | test_hdr { kind: 4u8, len: n, .. } => {"
+        ));
+    }
+
+    #[test]
+    fn test_resync_output() {
+        let mut module = FormatModule::new();
+        let chunk = module.define_format(
+            "test.chunk",
+            record([("marker", is_byte(0xFF)), ("payload", ANY_BYTE)]),
+        );
+        let main = module.define_format(
+            "test.main",
+            repeat(resync(chunk.call(), [ByteSet::singleton(0xFF)])),
+        );
+        let f = main.call();
+        population_check(&module, &f, None);
+        let output = produce_string_gencode(&module, &f);
+        assert!(output.contains("_input.resync(&[ByteSet::singleton(255)])?.to_vec()"));
+        assert!(output.contains("test_main::skipped(test_main_skipped {"));
+        assert!(output.contains("Err(err) if (!err.is_recoverable()) => {\nreturn Err(err);"));
+    }
+
+    #[test]
+    fn test_read_array_le_output() {
+        let f = record([
+            ("be", from_here(read_array(Expr::U16(2), BaseKind::U16BE))),
+            ("le", from_here(read_array(Expr::U16(2), BaseKind::I32LE))),
+        ]);
+        let mut module = FormatModule::new();
+        module.define_format("test.read_array", f.clone());
+        population_check(&module, &f, None);
+        let output = produce_string_gencode(&module, &f);
+        assert!(output.contains("be: ReadArray<'input, U16Be>"));
+        assert!(output.contains("le: ReadArray<'input, I32Le>"));
+        assert!(output.contains("here_view.read_array_i32le(2u16 as usize)"));
+    }
+
+    #[test]
+    fn test_seq_combinators_output() {
+        let is_odd = |x| expr_eq(rem(x, Expr::U8(2)), Expr::U8(1));
+        let f = record([
+            ("xs", repeat_count(Expr::U32(4), ANY_BYTE)),
+            (
+                "odds",
+                compute(seq_filter(lambda("x", is_odd(var("x"))), var("xs"))),
+            ),
+            ("pairs", compute(seq_zip(var("xs"), seq_reverse(var("xs"))))),
+            ("any_odd", compute(seq_any(is_odd, var("xs")))),
+            ("all_odd", compute(seq_all(is_odd, var("xs")))),
+            (
+                "ascending",
+                compute(is_sorted_by(
+                    lambda(
+                        "p",
+                        expr_lte(tuple_proj(var("p"), 0), tuple_proj(var("p"), 1)),
+                    ),
+                    var("xs"),
+                )),
+            ),
+        ]);
+        let mut module = FormatModule::new();
+        module.define_format("test.seq_combinators", f.clone());
+        population_check(&module, &f, None);
+        let output = produce_string_gencode(&module, &f);
+        assert!(output.contains("try_filter_vec(xs.iter().cloned(), |x: u8|"));
+        assert!(output.contains(
+            "zip_vec(xs.iter().cloned(), (reverse_vec(xs.iter().cloned())).iter().cloned())"
+        ));
+        assert!(output.contains("try_any(xs.iter().cloned(), |x: u8|"));
+        assert!(output.contains("try_all(xs.iter().cloned(), |x: u8|"));
+        assert!(output.contains("try_is_sorted_by(xs.iter().cloned(), |p: (u8, u8)|"));
+    }
+
+    #[test]
+    fn test_function_codegen() {
+        let mut module = FormatModule::new();
+        let sum = module.declare_function(
+            "sum",
+            vec![("xs".into(), ValueType::Seq(Box::new(ValueType::U8)))],
+            ValueType::U32,
+        );
+        let xs = || var("xs");
+        module.define_function_body(
+            &sum,
+            expr_if_else(
+                expr_eq(seq_length(xs()), Expr::U32(0)),
+                Expr::U32(0),
+                add(
+                    as_u32(index_unchecked(xs(), Expr::U32(0))),
+                    sum.call(vec![sub_seq(xs(), Expr::U32(1), pred(seq_length(xs())))]),
+                ),
+            ),
+        );
+        let f = record([
+            ("xs", repeat_count(Expr::U32(4), ANY_BYTE)),
+            ("total", compute(sum.call(vec![var("xs")]))),
+            ("again", compute(sum.call(vec![var("xs")]))),
+        ]);
+        module.define_format("test.function", f.clone());
+        population_check(&module, &f, None);
+        let output = produce_string_gencode(&module, &f);
+        assert_eq!(output.matches("fn Function_sum(").count(), 1);
+        assert_eq!(output.matches("(Function_sum(&xs))").count(), 4);
+    }
+
+    #[test]
+    fn test_generic_codegen() {
+        let mut module = FormatModule::new();
+        let pair = module.define_format("test.pair", tuple([ANY_BYTE, ANY_BYTE]));
+        let prefixed = module.define_generic_format(
+            "test.prefixed",
+            vec![Label::Borrowed("elem")],
+            record([
+                ("len", ANY_BYTE),
+                (
+                    "items",
+                    repeat_count(var("len"), Format::Apply(Label::Borrowed("elem"))),
+                ),
+            ]),
+        );
+        let f = record([
+            ("bytes", prefixed.apply(vec![ANY_BYTE])),
+            ("pairs", prefixed.apply(vec![pair.call()])),
+            ("more_pairs", prefixed.apply(vec![pair.call()])),
+        ]);
+        module.define_format("test.main", f.clone());
+        let (mono, mono_f) = crate::generic::monomorphize(&module, &f);
+        population_check(&mono, &mono_f, None);
+        let output = produce_string_gencode(&module, &f);
+        assert_eq!(output.matches("fn Decoder_test_prefixed_byte<").count(), 1);
+        assert_eq!(
+            output
+                .matches("fn Decoder_test_prefixed_test_pair(")
+                .count(),
+            1
+        );
+        assert!(output.contains("pairs: test_prefixed_test_pair,"));
+        assert!(output.contains("more_pairs: test_prefixed_test_pair\n"));
+    }
+
+    #[test]
+    fn test_serde_output() {
+        let mut module = FormatModule::new();
+        let inner = module.define_format("test.main", record([("x", ANY_BYTE), ("y", ANY_BYTE)]));
+        let f = inner.call();
+        let output = generate_code_with(&module, &f, CodeGenOptions::new().serde(true))
+            .unwrap()
+            .to_fragment()
+            .to_string();
+        assert!(output.contains(
+            r#"#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]"#
+        ));
+        assert!(!produce_string_gencode(&module, &f).contains("serde"));
+    }
+
+    #[test]
+    fn test_serde_borrowed_fields() {
+        let mut module = FormatModule::new();
+        let f = let_view(
+            "v",
+            record([
+                ("scope", reify_view(vvar("v"))),
+                ("data", from_here(capture_bytes(Expr::U8(2)))),
+                (
+                    "opt",
+                    union([
+                        fmt_variant("some", monad_seq(is_byte(0), reify_view(vvar("v")))),
+                        fmt_variant(
+                            "bytes",
+                            monad_seq(is_byte(1), from_here(capture_bytes(Expr::U8(1)))),
+                        ),
+                    ]),
+                ),
+            ]),
+        );
+        let inner = module.define_format("test.main", f);
+        let output = generate_code_with(&module, &inner.call(), CodeGenOptions::new().serde(true))
+            .unwrap()
+            .to_fragment()
+            .to_string();
+        assert!(!output.contains("derive(serde::Serialize)]"));
+        assert!(output.contains(
+            r#"#[cfg_attr(feature = "serde", serde(skip_deserializing))] scope: View<'input>"#
+        ));
+        assert!(
+            output.contains(r#"#[cfg_attr(feature = "serde", serde(borrow))] data: &'input [u8]"#)
+        );
+        assert!(output.contains(
+            r#"#[cfg_attr(feature = "serde", serde(skip_deserializing))] some(View<'input>)"#
+        ));
+        assert!(
+            output.contains(r#"bytes(#[cfg_attr(feature = "serde", serde(borrow))] &'input [u8])"#)
+        );
+    }
+
+    #[test]
+    fn test_pruned_roots_output() {
+        let mut module = FormatModule::new();
+        module.define_format("test.kept", record([("x", ANY_BYTE), ("y", ANY_BYTE)]));
+        module.define_format("test.pruned", record([("z", ANY_BYTE), ("w", ANY_BYTE)]));
+        let f = module.find_format("test.kept").unwrap().call();
+        let options = CodeGenOptions::new().roots(["test.kept"]);
+        let output = generate_code_with(&module, &f, options)
+            .unwrap()
+            .to_fragment()
+            .to_string();
+        assert!(output.contains("struct test_kept"));
+        assert!(!output.contains("struct test_pruned"));
+        let full_output = produce_string_gencode(&module, &f);
+        assert!(full_output.contains("struct test_pruned"));
+    }
+
+    #[test]
+    fn test_lazy_output() {
+        let mut module = FormatModule::new();
+        let target =
+            module.define_format("test.target", record([("x", ANY_BYTE), ("y", ANY_BYTE)]));
+        let f = let_view(
+            "base",
+            record([
+                ("offset", ANY_BYTE),
+                (
+                    "target",
+                    parse_from_view(
+                        vvar("base").offset(var("offset")),
+                        slice(Expr::U8(2), target.call()),
+                    ),
+                ),
+            ]),
+        );
+        module.define_format("test.main", f.clone());
+        let options = CodeGenOptions::new().lazy(["test.target"]);
+        let output = generate_code_with(&module, &f, options)
+            .unwrap()
+            .to_fragment()
+            .to_string();
+        assert!(output.contains("target: Lazy<'input, test_target>"));
+        assert!(output.contains("(Lazy::new(base, offset as usize, ())).with_len(2u8 as usize)"));
+        assert!(output.contains("CommonObject for test_target"));
+        let eager_output = produce_string_gencode(&module, &f);
+        assert!(!eager_output.contains("Lazy"));
+    }
+
+    #[test]
+    fn test_unknown_options() {
+        let mut module = FormatModule::new();
+        let f = module.define_format("test.main", ANY_BYTE).call();
+        let options = CodeGenOptions::new().roots(["test.main", "test.missing"]);
+        assert!(matches!(
+            generate_code_with(&module, &f, options),
+            Err(CodeGenError::UnknownRoot { name }) if name == "test.missing"
+        ));
+        let options = CodeGenOptions::new().lazy(["test.missing"]);
+        assert!(matches!(
+            generate_code_with(&module, &f, options),
+            Err(CodeGenError::UnknownLazy { name }) if name == "test.missing"
+        ));
+        let options = CodeGenOptions::new().lazy(["test.main"]);
+        assert!(matches!(
+            generate_code_with(&module, &f, options),
+            Err(CodeGenError::UnsupportedLazy { name, .. }) if name == "test.main"
+        ));
+    }
+
     #[test]
     fn test_lambda_sanity() {
         const TU16: RustType = RustType::Atom(AtomType::Prim(PrimType::U16));
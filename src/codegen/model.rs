@@ -1,4 +1,4 @@
-use crate::{BaseKind, Endian, Label};
+use crate::{BaseKind, ByteSet, Endian, Label};
 
 use super::rust_ast::*;
 use super::{GenBlock, GenExpr, GenStmt};
@@ -138,10 +138,8 @@ pub const ALT_ERR_BIND: &str = "_e";
 // !SECTION
 
 // SECTION - magic strings related to DerivedLogic
-pub const DECODE_BUF_PARSER_OBJ: &str = "buf_parser";
 pub const DECODE_BUF_PARSER_REF: &str = "buf_input";
 
-pub const PARSE_VIEW_PARSER_OBJ: &str = "view_parser";
 pub const PARSE_VIEW_PARSER_REF: &str = "view_input";
 
 pub const VARIANT_INNER: &str = "inner";
@@ -153,6 +151,15 @@ pub const PERMIT_BIND: &str = "res";
 pub const PERMIT_ERR: &str = "err";
 // !SECTION
 
+// SECTION - magic strings related to generated encoders
+pub const ENCODE_OUTPUT: &str = "_output";
+pub const ENCODE_VALUE: &str = "_value";
+pub const ENCODE_BYTE: &str = "b";
+pub const ENCODE_ELEM: &str = "elem";
+pub const ENCODE_INNER: &str = "inner";
+pub const ENCODE_SLICE_START: &str = "slice_start";
+// !SECTION
+
 // !SECTION
 
 /// Template function for constructing a ViewObject `RustType` with a given lifetime parameter
@@ -214,6 +221,55 @@ pub fn err_where_unsatisfied(trace: u64) -> RustExpr {
     )))
 }
 
+/// Model RustExpr for a byte `byte` (the last one read by `parser`) that is not in the `ByteSet` `expected`, as an `Err(<..>)`-value.
+pub fn err_unexpected_byte(byte: RustExpr, expected: RustExpr, parser: RustExpr) -> RustExpr {
+    RustExpr::ResultErr(Box::new(RustExpr::FunctionCall(
+        Box::new(RustExpr::Entity(RustEntity::Scoped(
+            vec![lbl("ParseError")],
+            lbl("unexpected_byte"),
+        ))),
+        vec![byte, expected, parser],
+    )))
+}
+
+/// Model RustExpr for `<fallible>.map_err(|e| e.within(<frame>, <parser>))`, attributing any error of `fallible`
+/// to the format or record field named `frame`.
+pub fn map_err_within(fallible: RustExpr, frame: Label, parser: RustExpr) -> RustExpr {
+    let within = call!(
+        RustExpr::local("e"),
+        within,
+        (RustExpr::str_lit(frame)),
+        parser
+    );
+    call!(
+        fallible,
+        map_err,
+        (RustExpr::Closure(RustClosure::new_transform("e", None, within)))
+    )
+}
+
+/// Model RustExpr for a byte that cannot be encoded by the corresponding `Format::Byte`, as an `Err(<..>)`-value with accompanying trace-hash.
+pub fn err_excluded_byte(byte: RustExpr, trace: u64) -> RustExpr {
+    RustExpr::ResultErr(Box::new(RustExpr::FunctionCall(
+        Box::new(RustExpr::Entity(RustEntity::Scoped(
+            vec![lbl("UnparseError")],
+            lbl("ExcludedByte"),
+        ))),
+        vec![byte, RustExpr::u64lit(trace)],
+    )))
+}
+
+/// Model RustExpr for a value that no branch of its format could have produced, while encoding.
+pub fn err_unparse_fallthrough(trace: u64) -> RustExpr {
+    RustExpr::ResultErr(Box::new(RustExpr::FunctionCall(
+        Box::new(RustExpr::Entity(RustEntity::Scoped(
+            vec![lbl("UnparseError")],
+            lbl("ExcludedBranch"),
+        ))),
+        vec![RustExpr::u64lit(trace)],
+    )))
+}
+
 // !SECTION
 
 // SECTION - Helper functions for prelude-specific AST-constructions
@@ -255,9 +311,16 @@ pub fn read_array_from_view(view: RustExpr, len: RustExpr, kind: BaseKind<Endian
         BaseKind::U16BE => try_call!(view, read_array_u16be, len),
         BaseKind::U32BE => try_call!(view, read_array_u32be, len),
         BaseKind::U64BE => try_call!(view, read_array_u64be, len),
-        BaseKind::U16LE | BaseKind::U32LE | BaseKind::U64LE => {
-            unimplemented!("little-endian read-array parses not yet implemented")
-        }
+        BaseKind::I8 => try_call!(view, read_array_i8, len),
+        BaseKind::I16BE => try_call!(view, read_array_i16be, len),
+        BaseKind::I32BE => try_call!(view, read_array_i32be, len),
+        BaseKind::I64BE => try_call!(view, read_array_i64be, len),
+        BaseKind::U16LE => try_call!(view, read_array_u16le, len),
+        BaseKind::U32LE => try_call!(view, read_array_u32le, len),
+        BaseKind::U64LE => try_call!(view, read_array_u64le, len),
+        BaseKind::I16LE => try_call!(view, read_array_i16le, len),
+        BaseKind::I32LE => try_call!(view, read_array_i32le, len),
+        BaseKind::I64LE => try_call!(view, read_array_i64le, len),
     }
 }
 
@@ -296,6 +359,40 @@ pub fn try_seek_to_target(parser: RustExpr, target: RustExpr) -> RustExpr {
     try_call!(parser, advance_or_seek, target)
 }
 
+/// Model RustExpr for entering a decoder one level of format-nesting deeper, subject to the resource limits of the parse.
+pub fn try_enter(parser: RustExpr) -> RustExpr {
+    try_call!(parser, enter)
+}
+
+/// Model RustExpr for leaving a decoder entered via [`try_enter`].
+pub fn leave(parser: RustExpr) -> RustExpr {
+    call!(parser, leave)
+}
+
+/// Model RustExpr for decoding a native format by calling `path` on the remaining bytes of the parse,
+/// followed by `args`.
+///
+/// The closure parameter shadows `parser`, which must therefore be a local variable.
+pub fn try_decode_native(parser: RustExpr, path: Label, args: Vec<RustExpr>) -> RustExpr {
+    let Some(name) = parser.as_local() else {
+        unreachable!("try_decode_native: parser must be a local variable, found {parser:?}")
+    };
+    let call = RustExpr::local(path).call_with(std::iter::once(parser.clone()).chain(args));
+    let closure = RustClosure::new_transform(name.clone(), None, call);
+    try_call!(parser, decode_native, (RustExpr::Closure(closure)))
+}
+
+/// Model RustExpr for skipping ahead to the next occurrence of `pattern`, yielding the bytes skipped over.
+pub fn try_resync(parser: RustExpr, pattern: &[ByteSet]) -> RustExpr {
+    let pattern = RustExpr::ArrayLit(pattern.iter().map(super::embed_byteset).collect());
+    try_call!(parser, resync, (pattern.borrow_of()))
+}
+
+/// Model RustExpr for counting another sequence element against the resource limits of the parse.
+pub fn try_tally_element(parser: RustExpr) -> RustExpr {
+    try_call!(parser, tally_element)
+}
+
 /// Model RustExpr for setup of `Format::Bits` parse-context in the Parser model.
 pub fn ent_bits(parser: RustExpr) -> RustExpr {
     try_call!(parser, enter_bits_mode)
@@ -319,6 +416,41 @@ pub fn try_next_alt(parser: RustExpr, next_is_final: bool) -> RustExpr {
     try_call!(parser, next_alt, RustExpr::bool_lit(next_is_final))
 }
 
+/// Model RustExpr for appending a single byte to the output buffer of an encoder.
+pub fn push_byte(output: RustExpr, byte: RustExpr) -> RustExpr {
+    call!(output, push, byte)
+}
+
+/// Model RustExpr for appending the bytes of a byte-array (or slice) to the output buffer of an encoder.
+pub fn extend_bytes(output: RustExpr, bytes: RustExpr) -> RustExpr {
+    call!(output, extend_from_slice, (bytes.borrow_of()))
+}
+
+/// Model RustExpr for the current length of the output buffer of an encoder.
+pub fn output_len(output: RustExpr) -> RustExpr {
+    call!(output, len)
+}
+
+/// Model RustExpr for the absolute offset of the next byte to be written by an encoder.
+pub fn output_offset(output: RustExpr) -> RustExpr {
+    call!(output, offset)
+}
+
+/// Model RustExpr for checking the length of a sequence encoded by `Format::RepeatCount` (Try-call).
+pub fn try_check_length(expected: RustExpr, found: RustExpr) -> RustExpr {
+    try_call!(fn check_length, expected, found)
+}
+
+/// Model RustExpr for post-encoding padding of `Format::Slice` contents written since `start` (Try-call).
+pub fn try_close_encoded_slice(output: RustExpr, start: RustExpr, sz: RustExpr) -> RustExpr {
+    try_call!(fn close_slice, output, start, sz)
+}
+
+/// Model RustExpr for handling `Format::Align(n)` when encoding.
+pub fn pad_align(output: RustExpr, n: usize) -> RustExpr {
+    call!(fn pad_align, output, RustExpr::num_lit(n))
+}
+
 // !SECTION
 
 // SECTION - boilerplate patterns based on incidental implementation details of codegen process
@@ -391,6 +523,17 @@ pub fn match_case_err_bind(lab: &'static str) -> MatchCaseLHS {
     ))
 }
 
+/// Match case for `Err(<lab>)`, guarded on the error not being recoverable (see `ParseError::is_recoverable`).
+pub fn match_case_err_unrecoverable(lab: &'static str) -> MatchCaseLHS {
+    MatchCaseLHS::WithGuard(
+        RustPattern::Variant(
+            Constructor::Simple(lbl("Err")),
+            Box::new(RustPattern::CatchAll(Some(lbl(lab)))),
+        ),
+        call!(RustExpr::local(lab), is_recoverable).negate(),
+    )
+}
+
 /// RustExpr for `Parser::new(<buffer>)`
 ///
 /// `buffer` should already be appropriately coerced into `&[u8]` (not `Vec<u8>` or `&Vec<u8>`).
@@ -411,20 +554,35 @@ pub fn let_mut_parser_new(ident: &'static str, buffer: RustExpr) -> RustStmt {
     RustStmt::Let(Mut::Mutable, lbl(ident), None, parser_new(buffer))
 }
 
-/// RustExpr for `Parser::from(<view>)`
-pub fn parser_from(view: RustExpr) -> RustExpr {
-    RustExpr::FunctionCall(
-        Box::new(RustExpr::Entity(RustEntity::Scoped(
-            vec![lbl("Parser")],
-            lbl("from"),
-        ))),
-        vec![view],
-    )
+/// Model RustExpr for `<parent>.with_sub_parser(<buffer>, |<ident>| <body>)?`, which runs `body` over a parser
+/// for `buffer` (bound to `ident`) that shares the resource limits of `parent`.
+///
+/// The argument `buffer` should already be appropriately coerced into `&[u8]` (not `Vec<u8>` or `&Vec<u8>`).
+pub fn try_with_sub_parser(
+    parent: RustExpr,
+    buffer: RustExpr,
+    ident: &'static str,
+    body: RustExpr,
+) -> RustExpr {
+    let closure = RustClosure::new_transform(ident, None, body);
+    try_call!(parent, with_sub_parser, buffer, RustExpr::Closure(closure))
 }
 
-/// RustStmt for `let mut <ident> = Parser::from(<view>)`.
-pub fn let_mut_parser_from(ident: &'static str, view: RustExpr) -> RustStmt {
-    RustStmt::Let(Mut::Mutable, lbl(ident), None, parser_from(view))
+/// Model RustExpr for `<parent>.with_sub_parser_from(<view>, |<ident>| <body>)?`, which runs `body` over a parser
+/// for `view` (bound to `ident`) that shares the resource limits of `parent`.
+pub fn try_with_sub_parser_from(
+    parent: RustExpr,
+    view: RustExpr,
+    ident: &'static str,
+    body: RustExpr,
+) -> RustExpr {
+    let closure = RustClosure::new_transform(ident, None, body);
+    try_call!(
+        parent,
+        with_sub_parser_from,
+        view,
+        RustExpr::Closure(closure)
+    )
 }
 
 pub fn reify_view(view_raw: RustExpr) -> RustExpr {
@@ -437,6 +595,21 @@ pub fn view_offset(view: RustExpr, offset: RustExpr) -> RustExpr {
     view.call_method_with("offset", [offset]).wrap_try()
 }
 
+/// RustExpr for `<parser>.view_at(<target>)?`, the View starting at absolute-offset `target`.
+pub fn try_view_at(parser: RustExpr, target: RustExpr) -> RustExpr {
+    try_call!(parser, view_at, target)
+}
+
+/// RustExpr for `Lazy::new(<view>, <offset>, <args>)`, deferring a parse at `offset` into `view`.
+pub fn lazy_new(view: RustExpr, offset: RustExpr, args: RustExpr) -> RustExpr {
+    RustExpr::scoped(["Lazy"], "new").call_with([view, offset, args])
+}
+
+/// RustExpr for `<lazy>.with_len(<len>)`, confining a deferred parse to a slice of `len` bytes.
+pub fn lazy_with_len(lazy: RustExpr, len: RustExpr) -> RustExpr {
+    call!(lazy, with_len, len)
+}
+
 // !SECTION
 
 // SECTION - helper functions for various kinds of error-handling patterns
@@ -504,6 +677,16 @@ pub fn let_mut_vec_new(ident: &'static str) -> RustStmt {
     RustStmt::Let(Mut::Mutable, lbl(ident), None, vec_new())
 }
 
+/// RustStmt for `let mut <ident> = <parser>.byte_run()`
+pub fn let_mut_byte_run(ident: &'static str, parser: RustExpr) -> RustStmt {
+    RustStmt::Let(Mut::Mutable, lbl(ident), None, call!(parser, byte_run))
+}
+
+/// RustExpr for `<run>.into_slice()`
+pub fn byte_run_slice(run: RustExpr) -> RustExpr {
+    call!(run, into_slice)
+}
+
 /// RustStmt for `let mut <ident> = Vec::new()`
 pub fn let_mut_sig_vec_new(ident: &'static str, seq_ty: RustType) -> RustStmt {
     RustStmt::Let(Mut::Mutable, lbl(ident), Some(seq_ty), vec_new())
@@ -147,6 +147,8 @@ impl CanOptimize for RustType {
             RustType::ReadArray(..) => 1,
             // in actuality, ViewObject has many more niches, but we can't predictably calculate them without locking in the backing-type implementation
             RustType::ViewObject(..) => 1,
+            // as with ViewObject, which it embeds
+            RustType::Lazy(..) => 1,
         }
     }
 }
@@ -183,6 +185,17 @@ impl MemSize for RustType {
                 let sz_start_offs = std::mem::size_of::<usize>();
                 sz_buffer + sz_start_offs
             }
+            RustType::Lazy(..) => {
+                // FIXME - this excludes the size of the args, which depend on the decoder of the deferred type
+                let sz_view = {
+                    let sz_buffer = std::mem::size_of::<&[u8]>();
+                    let sz_start_offs = std::mem::size_of::<usize>();
+                    sz_buffer + sz_start_offs
+                };
+                let sz_offset = std::mem::size_of::<usize>();
+                let sz_len = std::mem::size_of::<Option<usize>>();
+                sz_view + sz_offset + sz_len
+            }
         }
     }
 
@@ -200,7 +213,7 @@ impl MemSize for RustType {
             RustType::Verbatim(..) => {
                 unreachable!("unexpected RustType::Verbatim in structural type")
             }
-            RustType::ReadArray(..) | RustType::ViewObject(..) => {
+            RustType::ReadArray(..) | RustType::ViewObject(..) | RustType::Lazy(..) => {
                 // FIXME - this is subject to external implementation details
                 std::mem::align_of::<usize>()
             }
@@ -218,6 +231,7 @@ impl CopyEligible for RustType {
             }
             RustType::ReadArray(..) => READ_ARRAY_IS_COPY,
             RustType::ViewObject(..) => VIEW_OBJECT_IS_COPY,
+            RustType::Lazy(..) => false,
         }
     }
 }
@@ -269,14 +283,16 @@ impl CopyEligible for AtomType {
 }
 
 macro_rules! one_to_one {
-    ( size $self:expr , $( $variant:ident => $ty:ty ),+ $(,)? ) => {
+    ( size $self:expr , $( $variant:ident => $ty:ty ),+ $(,)? $( ; $( $other:pat => $val:expr ),+ $(,)? )? ) => {
         match $self {
             $( Self::$variant => size_of::<$ty>(), )+
+            $( $( $other => $val, )+ )?
         }
     };
-    ( align $self:expr , $( $variant:ident => $ty:ty ),+ $(,)? ) => {
+    ( align $self:expr , $( $variant:ident => $ty:ty ),+ $(,)? $( ; $( $other:pat => $val:expr ),+ $(,)? )? ) => {
         match $self {
             $( Self::$variant => align_of::<$ty>(), )+
+            $( $( $other => $val, )+ )?
         }
     }
 }
@@ -297,6 +313,7 @@ impl CanOptimize for PrimType {
             PrimType::Char => const { u32::MAX as usize - UTF16_SCALAR_MAX },
             PrimType::Usize => 0,
             PrimType::Unsigned(ut) => ut.niches(()),
+            PrimType::Float(_) | PrimType::Fixed(_) => 0,
         }
     }
 }
@@ -311,7 +328,10 @@ impl MemSize for PrimType {
             U64 => u64,
             Bool => bool,
             Char => char,
-            Usize => usize
+            Usize => usize,
+            F32 => f32,
+            F64 => f64;
+            PrimType::Fixed(fx) => fx.width() as usize / 8
         }
     }
 
@@ -325,6 +345,9 @@ impl MemSize for PrimType {
             Bool => bool,
             Char => char,
             Usize => usize,
+            F32 => f32,
+            F64 => f64;
+            PrimType::Fixed(fx) => fx.width() as usize / 8,
         }
     }
 }
@@ -334,7 +357,12 @@ impl CopyEligible for PrimType {
         // NOTE - as implemented, all PrimTypes are copy, but we don't want to hardcode this and forget if we add non-Copy primtypes later on
         match self {
             PrimType::Unsigned(ut) => ut.copy_hint(()),
-            PrimType::Usize | PrimType::Unit | PrimType::Bool | PrimType::Char => true,
+            PrimType::Usize
+            | PrimType::Unit
+            | PrimType::Bool
+            | PrimType::Char
+            | PrimType::Float(_)
+            | PrimType::Fixed(_) => true,
         }
     }
 }
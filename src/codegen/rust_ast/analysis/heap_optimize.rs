@@ -159,6 +159,7 @@ impl HeapOptimize for RustType {
         match self {
             RustType::ReadArray(..) => (HeapAction::Noop, mk_layout(self, context)),
             RustType::ViewObject(..) => (HeapAction::Noop, mk_layout(self, context)),
+            RustType::Lazy(..) => (HeapAction::Noop, mk_layout(self, context)),
             RustType::Atom(at) => at.heap_hint(strategy, context),
             RustType::AnonTuple(ts) => {
                 if strategy.is_never() {
@@ -106,6 +106,10 @@ impl ReadWidth for RustType {
                 // NOTE - even though ReadArray is a zero-width parse, we reject it because it doesn't meet our expected shape
                 ValueWidth::Variable { is_dynamic: false }
             }
+            RustType::Lazy(..) => {
+                // NOTE - a lazy parse reads nothing in place, but it is anything but a primitive parse
+                ValueWidth::Variable { is_dynamic: false }
+            }
             RustType::Verbatim(..) => unreachable!("unexpected Verbatim in structural type"),
         }
     }
@@ -130,6 +134,7 @@ impl ReadWidth for PrimType {
             PrimType::Bool => ValueWidth::VAR,
             PrimType::Char => ValueWidth::VAR,
             PrimType::Usize => ValueWidth::VAR,
+            PrimType::Float(_) | PrimType::Fixed(_) => ValueWidth::VAR,
         }
     }
 }
@@ -12,8 +12,9 @@ use crate::numeric::elaborator::PrimInt as ExtPrimInt;
 use crate::codegen::model::{DEFAULT_LT, READ_ARRAY_IS_COPY, VIEW_OBJECT_IS_COPY};
 use crate::output::{Fragment, FragmentBuilder};
 
+use crate::marker::Endian;
 use crate::precedence::{Precedence, cond_paren};
-use crate::{BaseKind, BaseType, IntoLabel, Label, ValueType};
+use crate::{BaseKind, BaseType, FixedType, IntoLabel, Label, ValueType};
 
 /// Enum-type (currently degenerate) for specifying the visibility of a top-level item
 #[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
@@ -210,6 +211,10 @@ impl ToFragment for RustImportItems {
 /// Top-level declared item (e.g. struct definitions and functions)
 pub(crate) struct RustItem {
     vis: Visibility,
+    /// Visibility of each field, if the declaration is a struct definition
+    field_vis: Visibility,
+    /// Cargo feature gating the `serde` derives of a type definition, whose fields and variants are annotated accordingly
+    serde_feature: Option<Label>,
     attrs: Vec<RustAttr>,
     doc_comment: Option<RustDocComment>,
     decl: RustDecl,
@@ -324,6 +329,8 @@ impl RustItem {
         Self {
             attrs,
             vis,
+            field_vis: Visibility::Implicit,
+            serde_feature: None,
             doc_comment: None,
             decl,
         }
@@ -343,11 +350,30 @@ impl RustItem {
     ///
     /// For more fine-control over the traits that are derived, use [`pub_decl_with_traits`](Self::pub_decl_with_traits).
     #[inline]
-    #[allow(dead_code)]
     pub fn pub_decl(decl: RustDecl) -> Self {
         Self::pub_decl_with_traits(decl, TraitSet::default())
     }
 
+    /// Appends an extra attribute to the declaration.
+    pub fn with_attr(mut self, attr: RustAttr) -> Self {
+        self.attrs.push(attr);
+        self
+    }
+
+    /// Overrides the visibility of each field of a struct definition (has no effect on other declarations).
+    pub fn with_field_vis(mut self, field_vis: Visibility) -> Self {
+        self.field_vis = field_vis;
+        self
+    }
+
+    /// Attaches feature-gated `serde` derives to a type definition (has no effect on other declarations).
+    ///
+    /// Fields and variants that cannot be derived as-is are annotated as per [`RustType::serde_field_attr`].
+    pub fn with_serde(mut self, feature: Label, traits: DeclDerives) -> Self {
+        self.serde_feature = Some(feature.clone());
+        self.with_attr(RustAttr::FeatureDeriveTraits(feature, traits))
+    }
+
     pub fn with_comment<Text: IntoLabel>(
         mut self,
         comment: impl IntoIterator<Item = Text>,
@@ -372,29 +398,46 @@ impl ToFragment for RustItem {
         for attr in self.attrs.iter() {
             builder.push(attr.to_fragment().cat_break());
         }
-        builder
-            .finalize()
-            .cat(self.vis.add_vis(self.decl.to_fragment()))
+        builder.finalize().cat(
+            self.vis.add_vis(
+                self.decl
+                    .to_fragment_with_fields(self.field_vis, self.serde_feature.as_ref()),
+            ),
+        )
     }
 }
 
 type TraitName = Label;
 
+/// Contents of the `serde(..)` attribute for fields and variants that are left out of `Deserialize`.
+const SERDE_SKIP: &str = "skip_deserializing";
+
 #[derive(Debug, Clone)]
 pub enum RustAttr {
     DeriveTraits(DeclDerives),
+    /// Derive-traits that are only attached when the named cargo feature is enabled
+    FeatureDeriveTraits(Label, DeclDerives),
 }
 
 impl ToFragment for RustAttr {
     fn to_fragment(&self) -> Fragment {
         match self {
             RustAttr::DeriveTraits(derives) => derives.to_fragment(),
+            RustAttr::FeatureDeriveTraits(feature, DeclDerives(traits)) => {
+                if traits.is_empty() {
+                    Fragment::Empty
+                } else {
+                    Fragment::string(format!("#[cfg_attr(feature = \"{feature}\", derive"))
+                        .cat(ToFragment::paren_list(traits))
+                        .cat(Fragment::string(")]"))
+                }
+            }
         }
     }
 }
 
 #[derive(Clone, Debug)]
-pub(crate) struct DeclDerives(Vec<TraitName>);
+pub(crate) struct DeclDerives(pub(crate) Vec<TraitName>);
 
 impl ToFragment for DeclDerives {
     fn to_fragment(&self) -> Fragment {
@@ -483,6 +526,18 @@ impl RustDecl {
 
 impl ToFragment for RustDecl {
     fn to_fragment(&self) -> Fragment {
+        self.to_fragment_with_fields(Visibility::Implicit, None)
+    }
+}
+
+impl RustDecl {
+    /// Variant of [`to_fragment`](ToFragment::to_fragment) that renders the fields of a struct definition with visibility `field_vis`,
+    /// and the fields and variants of a type definition with whatever `serde` attributes they need if `serde_feature` is given.
+    fn to_fragment_with_fields(
+        &self,
+        field_vis: Visibility,
+        serde_feature: Option<&Label>,
+    ) -> Fragment {
         match self {
             RustDecl::TypeDef(name, RustTypeDecl { def, lt }) => {
                 let identifier = if let Some(lt) = lt {
@@ -496,7 +551,10 @@ impl ToFragment for RustDecl {
                 let keyword = Fragment::string(def.keyword_for());
                 keyword
                     .intervene(Fragment::Char(' '), identifier)
-                    .intervene(Fragment::Char(' '), def.to_fragment())
+                    .intervene(
+                        Fragment::Char(' '),
+                        def.to_fragment_with_fields(field_vis, serde_feature),
+                    )
             }
             RustDecl::Function(fn_def) => fn_def.to_fragment(),
             RustDecl::TraitImpl(trait_impl) => trait_impl.to_fragment(),
@@ -671,14 +729,22 @@ impl RustTypeDef {
         }
     }
 
-    pub fn to_fragment(&self) -> Fragment {
+    /// Renders the body of the type-definition, with each field of a struct definition given visibility `field_vis`,
+    /// and any `serde` attributes gated under `serde_feature`.
+    fn to_fragment_with_fields(
+        &self,
+        field_vis: Visibility,
+        serde_feature: Option<&Label>,
+    ) -> Fragment {
         match self {
             RustTypeDef::Enum(vars) => {
-                let iter = vars.iter().map(RustVariant::to_fragment);
+                let iter = vars
+                    .iter()
+                    .map(|var| var.to_fragment_with_serde(serde_feature));
                 let inner = Fragment::seq(iter, Some(Fragment::string(", ")));
                 inner.delimit(Fragment::string("{ "), Fragment::string(" }"))
             }
-            RustTypeDef::Struct(str) => str.to_fragment(),
+            RustTypeDef::Struct(str) => str.to_fragment_with_fields(field_vis, serde_feature),
         }
     }
 
@@ -701,6 +767,8 @@ pub(crate) enum RustType {
     Verbatim(Label, Option<Box<UseParams>>),
     ReadArray(RustLt, MarkerType),
     ViewObject(RustLt),
+    /// Deferred parse of the (`CommonObject`-implementing) type at an offset into a View
+    Lazy(RustLt, Box<RustType>),
 }
 
 impl RustType {
@@ -781,6 +849,7 @@ impl RustType {
             // FIXME - is this correct?
             RustType::ReadArray(..) => !READ_ARRAY_IS_COPY,
             RustType::ViewObject(..) => false,
+            RustType::Lazy(..) => false,
         }
     }
 
@@ -864,7 +933,43 @@ impl RustType {
             RustType::Atom(atom_type) => atom_type.lt_param(),
             RustType::AnonTuple(rust_types) => rust_types.iter().find_map(|t| t.lt_param()),
             RustType::Verbatim(_, rust_params) => rust_params.as_deref()?.lt_params.first(),
-            RustType::ReadArray(lt, _) | RustType::ViewObject(lt) => Some(lt),
+            RustType::ReadArray(lt, _) | RustType::ViewObject(lt) | RustType::Lazy(lt, _) => {
+                Some(lt)
+            }
+        }
+    }
+
+    /// Returns the contents of the `serde(..)` attribute that a field of this type needs, in order for its
+    /// containing type-definition to derive `Deserialize`.
+    ///
+    /// Views into the input (and anything holding one) cannot be deserialized, and are skipped in favor of their
+    /// (empty) default value; any other type that borrows from the input borrows from the deserializer instead.
+    pub(crate) fn serde_field_attr(&self) -> Option<&'static str> {
+        if self.holds_view() {
+            Some(SERDE_SKIP)
+        } else if self.lt_param().is_some() {
+            Some("borrow")
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if a value of this type directly holds a `View`, `ReadArray`, or `Lazy` into the input.
+    ///
+    /// Locally-defined types are not inspected, as they are responsible for their own fields.
+    fn holds_view(&self) -> bool {
+        match self {
+            RustType::Atom(AtomType::Comp(ct)) => match ct {
+                CompType::Vec(t)
+                | CompType::RawSlice(t)
+                | CompType::Option(t)
+                | CompType::Borrow(_, _, t) => t.holds_view(),
+                CompType::Result(t, u) => t.holds_view() || u.holds_view(),
+                CompType::PhantomData(_) => false,
+            },
+            RustType::Atom(_) | RustType::Verbatim(..) => false,
+            RustType::AnonTuple(ts) => ts.iter().any(RustType::holds_view),
+            RustType::ReadArray(..) | RustType::ViewObject(..) | RustType::Lazy(..) => true,
         }
     }
 
@@ -881,6 +986,10 @@ impl RustType {
                 }
             }
             RustType::ReadArray(lt, _) | RustType::ViewObject(lt) => *lt = new_lt,
+            RustType::Lazy(lt, t) => {
+                *lt = new_lt.clone();
+                t.alpha_convert_lifetime(new_lt);
+            }
         }
     }
 
@@ -895,7 +1004,8 @@ impl RustType {
             RustType::AnonTuple(..)
             | RustType::ReadArray(..)
             | RustType::Verbatim(..)
-            | RustType::ViewObject(..) => None,
+            | RustType::ViewObject(..)
+            | RustType::Lazy(..) => None,
         }
     }
 }
@@ -937,6 +1047,8 @@ impl RustType {
             RustType::Verbatim(..) => false,
             RustType::ReadArray(..) => READ_ARRAY_IS_COPY,
             RustType::ViewObject(..) => VIEW_OBJECT_IS_COPY,
+            // NOTE - the args of a lazy parse are not guaranteed to be `Copy`
+            RustType::Lazy(..) => false,
         }
     }
 }
@@ -969,6 +1081,13 @@ impl ToFragment for RustType {
                 // REVIEW[epic=hardcoded] - using crate::parser::view::View for View-Objects
                 Fragment::string("View").cat(params.to_fragment())
             }
+            RustType::Lazy(lt, t) => {
+                let params = RustParams {
+                    lt_params: vec![lt.clone()],
+                    ty_params: vec![t.as_ref().clone()],
+                };
+                Fragment::string("Lazy").cat(params.to_fragment())
+            }
         }
     }
 }
@@ -994,16 +1113,42 @@ impl RustStruct {
     }
 }
 
-impl ToFragment for RustStruct {
-    fn to_fragment(&self) -> Fragment {
+impl RustStruct {
+    fn to_fragment_with_fields(
+        &self,
+        field_vis: Visibility,
+        serde_feature: Option<&Label>,
+    ) -> Fragment {
         match self {
             RustStruct::Record(flds) => {
-                <(Label, RustType)>::block_sep(flds.iter(), Fragment::Char(','))
+                let lines = flds.iter().map(|fld| {
+                    serde_field_attr(serde_feature, &fld.1)
+                        .cat(field_vis.add_vis(fld.to_fragment()))
+                });
+                Fragment::seq(lines, Some(Fragment::string(",\n")))
+                    .delimit(Fragment::string("{\n"), Fragment::string("\n}"))
             }
         }
     }
 }
 
+impl ToFragment for RustStruct {
+    fn to_fragment(&self) -> Fragment {
+        self.to_fragment_with_fields(Visibility::Implicit, None)
+    }
+}
+
+/// Renders the `serde` attribute (gated under `serde_feature`, if given) that a field or tuple-variant element of type `rt` needs,
+/// followed by a space.
+fn serde_field_attr(serde_feature: Option<&Label>, rt: &RustType) -> Fragment {
+    match serde_feature.zip(rt.serde_field_attr()) {
+        Some((feature, attr)) => Fragment::string(format!(
+            "#[cfg_attr(feature = \"{feature}\", serde({attr}))] "
+        )),
+        None => Fragment::Empty,
+    }
+}
+
 impl ToFragment for Label {
     /// Special-case for sanitization of labels-as-identifiers rather than a direct identity-function.
     fn to_fragment(&self) -> Fragment {
@@ -1101,17 +1246,46 @@ impl RustVariant {
     }
 }
 
-impl ToFragment for RustVariant {
-    fn to_fragment(&self) -> Fragment {
+impl RustVariant {
+    /// Variant of [`to_fragment`](ToFragment::to_fragment) that annotates the elements of a tuple-variant with
+    /// whatever `serde` attributes they need, if `serde_feature` is given.
+    ///
+    /// As `serde` cannot skip individual elements of a tuple-variant, a variant with any element that has to be skipped
+    /// is skipped in its entirety.
+    fn to_fragment_with_serde(&self, serde_feature: Option<&Label>) -> Fragment {
         match self {
             RustVariant::Unit(lab) => lab.to_fragment(),
             RustVariant::Tuple(lab, args) => {
-                lab.to_fragment().cat(RustType::paren_list(args.iter()))
+                let skip = args
+                    .iter()
+                    .any(|arg| arg.serde_field_attr() == Some(SERDE_SKIP));
+                if skip {
+                    let skip_attr = serde_feature.map_or(Fragment::Empty, |feature| {
+                        Fragment::string(format!(
+                            "#[cfg_attr(feature = \"{feature}\", serde({SERDE_SKIP}))] "
+                        ))
+                    });
+                    skip_attr.cat(lab.to_fragment().cat(RustType::paren_list(args.iter())))
+                } else {
+                    let elems = args
+                        .iter()
+                        .map(|arg| serde_field_attr(serde_feature, arg).cat(arg.to_fragment()));
+                    lab.to_fragment().cat(
+                        Fragment::seq(elems, Some(Fragment::string(", ")))
+                            .delimit(Fragment::Char('('), Fragment::Char(')')),
+                    )
+                }
             }
         }
     }
 }
 
+impl ToFragment for RustVariant {
+    fn to_fragment(&self) -> Fragment {
+        self.to_fragment_with_serde(None)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) enum AtomType<T = Box<RustType>, U = T>
 where
@@ -1201,16 +1375,51 @@ pub(crate) enum MarkerType {
     U16Be,
     U32Be,
     U64Be,
+    I8,
+    I16Be,
+    I32Be,
+    I64Be,
+    U16Le,
+    U32Le,
+    U64Le,
+    I16Le,
+    I32Le,
+    I64Le,
 }
 
 impl MarkerType {
     #[expect(unused)]
     pub const fn get_fixed_size(self) -> usize {
         match self {
-            MarkerType::U8 => size_of::<u8>(),
-            MarkerType::U16Be => size_of::<u16>(),
-            MarkerType::U32Be => size_of::<u32>(),
-            MarkerType::U64Be => size_of::<u64>(),
+            MarkerType::U8 | MarkerType::I8 => size_of::<u8>(),
+            MarkerType::U16Be | MarkerType::I16Be | MarkerType::U16Le | MarkerType::I16Le => {
+                size_of::<u16>()
+            }
+            MarkerType::U32Be | MarkerType::I32Be | MarkerType::U32Le | MarkerType::I32Le => {
+                size_of::<u32>()
+            }
+            MarkerType::U64Be | MarkerType::I64Be | MarkerType::U64Le | MarkerType::I64Le => {
+                size_of::<u64>()
+            }
+        }
+    }
+
+    /// Returns the marker-type of the same width and signedness as `self`, in the given byte-order.
+    pub const fn with_endian(self, endian: Endian) -> Self {
+        match (self, endian) {
+            (MarkerType::U16Be | MarkerType::U16Le, Endian::Be) => MarkerType::U16Be,
+            (MarkerType::U32Be | MarkerType::U32Le, Endian::Be) => MarkerType::U32Be,
+            (MarkerType::U64Be | MarkerType::U64Le, Endian::Be) => MarkerType::U64Be,
+            (MarkerType::I16Be | MarkerType::I16Le, Endian::Be) => MarkerType::I16Be,
+            (MarkerType::I32Be | MarkerType::I32Le, Endian::Be) => MarkerType::I32Be,
+            (MarkerType::I64Be | MarkerType::I64Le, Endian::Be) => MarkerType::I64Be,
+            (MarkerType::U16Be | MarkerType::U16Le, Endian::Le) => MarkerType::U16Le,
+            (MarkerType::U32Be | MarkerType::U32Le, Endian::Le) => MarkerType::U32Le,
+            (MarkerType::U64Be | MarkerType::U64Le, Endian::Le) => MarkerType::U64Le,
+            (MarkerType::I16Be | MarkerType::I16Le, Endian::Le) => MarkerType::I16Le,
+            (MarkerType::I32Be | MarkerType::I32Le, Endian::Le) => MarkerType::I32Le,
+            (MarkerType::I64Be | MarkerType::I64Le, Endian::Le) => MarkerType::I64Le,
+            (MarkerType::U8 | MarkerType::I8, _) => self,
         }
     }
 }
@@ -1222,6 +1431,10 @@ impl From<BaseKind> for MarkerType {
             BaseKind::U16 => MarkerType::U16Be,
             BaseKind::U32 => MarkerType::U32Be,
             BaseKind::U64 => MarkerType::U64Be,
+            BaseKind::I8 => MarkerType::I8,
+            BaseKind::I16 => MarkerType::I16Be,
+            BaseKind::I32 => MarkerType::I32Be,
+            BaseKind::I64 => MarkerType::I64Be,
         }
     }
 }
@@ -1233,12 +1446,22 @@ impl ToFragment for MarkerType {
             MarkerType::U16Be => Fragment::string("U16Be"),
             MarkerType::U32Be => Fragment::string("U32Be"),
             MarkerType::U64Be => Fragment::string("U64Be"),
+            MarkerType::I8 => Fragment::string("I8"),
+            MarkerType::I16Be => Fragment::string("I16Be"),
+            MarkerType::I32Be => Fragment::string("I32Be"),
+            MarkerType::I64Be => Fragment::string("I64Be"),
+            MarkerType::U16Le => Fragment::string("U16Le"),
+            MarkerType::U32Le => Fragment::string("U32Le"),
+            MarkerType::U64Le => Fragment::string("U64Le"),
+            MarkerType::I16Le => Fragment::string("I16Le"),
+            MarkerType::I32Le => Fragment::string("I32Le"),
+            MarkerType::I64Le => Fragment::string("I64Le"),
         }
     }
 }
 
 #[derive(Debug)]
-pub struct InvalidMarkerTypeError(PrimType);
+pub struct InvalidMarkerTypeError(RustType);
 
 impl std::fmt::Display for InvalidMarkerTypeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -1254,7 +1477,30 @@ impl TryFrom<PrimType> for MarkerType {
     fn try_from(pt: PrimType) -> Result<Self, Self::Error> {
         match pt {
             PrimType::Unsigned(mu) => Ok(mu.into()),
-            _ => Err(InvalidMarkerTypeError(pt)),
+            _ => Err(InvalidMarkerTypeError(pt.into())),
+        }
+    }
+}
+
+impl TryFrom<&RustType> for MarkerType {
+    type Error = InvalidMarkerTypeError;
+
+    fn try_from(rt: &RustType) -> Result<Self, Self::Error> {
+        match rt {
+            RustType::Atom(AtomType::Prim(pt)) => MarkerType::try_from(*pt),
+            RustType::Atom(AtomType::Signed(ms)) => Ok((*ms).into()),
+            _ => Err(InvalidMarkerTypeError(rt.clone())),
+        }
+    }
+}
+
+impl From<MachineSint> for MarkerType {
+    fn from(ms: MachineSint) -> Self {
+        match ms {
+            MachineSint::I8 => MarkerType::I8,
+            MachineSint::I16 => MarkerType::I16Be,
+            MachineSint::I32 => MarkerType::I32Be,
+            MachineSint::I64 => MarkerType::I64Be,
         }
     }
 }
@@ -1289,6 +1535,21 @@ impl MachineUint {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MachineFloat {
+    F32,
+    F64,
+}
+
+impl MachineFloat {
+    pub const fn to_static_str(self) -> &'static str {
+        match self {
+            MachineFloat::F32 => "f32",
+            MachineFloat::F64 => "f64",
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, PartialOrd, Ord, Hash)]
 pub(crate) enum PrimType {
     Unit,
@@ -1296,6 +1557,9 @@ pub(crate) enum PrimType {
     Char,
     Unsigned(MachineUint),
     Usize,
+    Float(MachineFloat),
+    /// Fixed-point type, rendered as the corresponding alias from `fixed::types`
+    Fixed(FixedType),
 }
 
 impl PrimType {
@@ -1303,6 +1567,9 @@ impl PrimType {
     pub const U16: PrimType = PrimType::Unsigned(MachineUint::U16);
     pub const U32: PrimType = PrimType::Unsigned(MachineUint::U32);
     pub const U64: PrimType = PrimType::Unsigned(MachineUint::U64);
+
+    pub const F32: PrimType = PrimType::Float(MachineFloat::F32);
+    pub const F64: PrimType = PrimType::Float(MachineFloat::F64);
 }
 
 impl PrimType {
@@ -1313,6 +1580,8 @@ impl PrimType {
     fn compare_width(pt0: PrimType, pt1: PrimType) -> Option<Ordering> {
         match (pt0, pt1) {
             (PrimType::Unit, _) | (_, PrimType::Unit) => None,
+            (PrimType::Float(_) | PrimType::Fixed(_), _)
+            | (_, PrimType::Float(_) | PrimType::Fixed(_)) => None,
             (PrimType::Char, _) | (_, PrimType::Char) => None,
             (PrimType::Bool, _) | (_, PrimType::Bool) => None,
             (PrimType::U8, PrimType::U8) => Some(Ordering::Equal),
@@ -1340,6 +1609,9 @@ impl From<BaseType> for PrimType {
             BaseType::U32 => PrimType::U32,
             BaseType::U64 => PrimType::U64,
             BaseType::Char => PrimType::Char,
+            BaseType::F32 => PrimType::Float(MachineFloat::F32),
+            BaseType::F64 => PrimType::Float(MachineFloat::F64),
+            BaseType::Fixed(fx) => PrimType::Fixed(fx),
         }
     }
 }
@@ -1352,6 +1624,8 @@ impl ToFragment for PrimType {
             PrimType::Char => "char",
             PrimType::Usize => "usize",
             PrimType::Unsigned(mu) => mu.to_static_str(),
+            PrimType::Float(mf) => mf.to_static_str(),
+            PrimType::Fixed(fx) => return Fragment::string(format!("fixed::types::{fx}")),
         })
     }
 }
@@ -1567,6 +1841,15 @@ impl From<MachineSint> for RustType {
     }
 }
 
+impl<X: Copy> From<BaseKind<X>> for RustType {
+    fn from(value: BaseKind<X>) -> Self {
+        match value.signed_type() {
+            Some(s) => RustType::from(MachineSint::from(s)),
+            None => RustType::from(PrimType::from(value.raw_base_type())),
+        }
+    }
+}
+
 impl From<ExtIntType> for RustType {
     fn from(value: ExtIntType) -> Self {
         Self::from(AtomType::from(value))
@@ -1590,12 +1873,7 @@ impl TryFrom<ValueType> for RustType {
             ValueType::ViewObj => Ok(RustType::ViewObject(RustLt::Parametric(Label::Borrowed(
                 DEFAULT_LT,
             )))),
-            ValueType::Base(BaseType::Bool) => Ok(PrimType::Bool.into()),
-            ValueType::Base(BaseType::Char) => Ok(PrimType::Char.into()),
-            ValueType::U8 => Ok(PrimType::U8.into()),
-            ValueType::U16 => Ok(PrimType::U16.into()),
-            ValueType::U32 => Ok(PrimType::U32.into()),
-            ValueType::U64 => Ok(PrimType::U64.into()),
+            ValueType::Base(b) => Ok(PrimType::from(b).into()),
             ValueType::Signed(s) => Ok(MachineSint::from(s).into()),
             ValueType::NumericHole => Err(value),
             ValueType::Tuple(mut vs) => {
@@ -2213,8 +2491,20 @@ impl RustExpr {
         self.call_with(None)
     }
 
+    /// Returns the boolean negation of `self`, flipping (in)equality tests and eliding double-negation where possible.
     pub fn negate(self) -> Self {
-        RustExpr::Operation(RustOp::PrefixOp(PrefixOperator::BoolNot, Box::new(self)))
+        match self {
+            RustExpr::Operation(RustOp::InfixOp(InfixOperator::Eq, lhs, rhs)) => {
+                RustExpr::Operation(RustOp::InfixOp(InfixOperator::Neq, lhs, rhs))
+            }
+            RustExpr::Operation(RustOp::InfixOp(InfixOperator::Neq, lhs, rhs)) => {
+                RustExpr::Operation(RustOp::InfixOp(InfixOperator::Eq, lhs, rhs))
+            }
+            RustExpr::Operation(RustOp::PrefixOp(PrefixOperator::BoolNot, inner)) => *inner,
+            other => {
+                RustExpr::Operation(RustOp::PrefixOp(PrefixOperator::BoolNot, Box::new(other)))
+            }
+        }
     }
 
     #[expect(dead_code)]
@@ -3725,10 +4015,13 @@ pub(crate) enum RustPattern {
     TupleLiteral(Vec<RustPattern>),
     ArrayLiteral(Vec<RustPattern>),
     Option(Option<Box<RustPattern>>),
-    Fill,                                   // `..`
-    CatchAll(Option<Label>),                // Wildcard when None, otherwise a variable-binding
-    BindRef(Label),                         // "x" => `ref x`
+    Fill,                                           // `..`
+    CatchAll(Option<Label>), // Wildcard when None, otherwise a variable-binding
+    BindRef(Label),          // "x" => `ref x`
     Variant(Constructor, Box<RustPattern>), // FIXME - need to attach enum scope
+    UnitVariant(Constructor), // `T::V` for variants without positional arguments
+    Struct(Constructor, Vec<(Label, RustPattern)>), // `T { a: p, .. }`, always partial
+    Or(Vec<RustPattern>),    // `p | q`
 }
 
 #[derive(Debug, Clone)]
@@ -3776,6 +4069,9 @@ impl ToFragment for RustPattern {
                             .delimit(Fragment::Char('('), Fragment::Char(')')),
                     )
             }
+            RustPattern::UnitVariant(constr) => {
+                RustExpr::Entity(RustEntity::from(constr.clone())).to_fragment()
+            }
             RustPattern::Fill => Fragment::String("..".into()),
             RustPattern::CatchAll(None) => Fragment::Char('_'),
             RustPattern::CatchAll(Some(lab)) => lab.to_fragment(),
@@ -3785,6 +4081,27 @@ impl ToFragment for RustPattern {
                 pat.to_fragment()
                     .delimit(Fragment::Char('('), Fragment::Char(')')),
             ),
+            RustPattern::Struct(constr, fields) => {
+                RustExpr::Entity(RustEntity::from(constr.clone()))
+                    .to_fragment()
+                    .cat(
+                        Fragment::seq(
+                            fields
+                                .iter()
+                                .map(|(lab, pat)| {
+                                    lab.to_fragment()
+                                        .intervene(Fragment::string(": "), pat.to_fragment())
+                                })
+                                .chain(std::iter::once(Fragment::string(".."))),
+                            Some(Fragment::string(", ")),
+                        )
+                        .delimit(Fragment::string(" { "), Fragment::string(" }")),
+                    )
+            }
+            RustPattern::Or(alts) => Fragment::seq(
+                alts.iter().map(RustPattern::to_fragment),
+                Some(Fragment::string(" | ")),
+            ),
         }
     }
 }
@@ -4009,6 +4326,21 @@ mod test {
         );
         expect_fragment(&re, "this.append(&mut other)")
     }
+
+    #[test]
+    fn negate_expr() {
+        let eq = RustExpr::infix(
+            RustExpr::local("x"),
+            InfixOperator::Eq,
+            RustExpr::num_lit(0usize),
+        );
+        expect_fragment(&eq.clone().negate(), "x != 0");
+        expect_fragment(&eq.negate().negate(), "x == 0");
+
+        let call = RustExpr::local("is_empty").call();
+        expect_fragment(&call.clone().negate(), "!(is_empty())");
+        expect_fragment(&call.negate().negate(), "is_empty()");
+    }
 }
 
 pub mod short_circuit {
@@ -4594,6 +4926,7 @@ pub mod var_container {
                 RustPattern::Fill => false,
                 RustPattern::Option(None) => false,
                 RustPattern::CatchAll(None) => false,
+                RustPattern::UnitVariant(..) => false,
 
                 // nested patterns
                 RustPattern::TupleLiteral(pats) | RustPattern::ArrayLiteral(pats) => {
@@ -4602,6 +4935,9 @@ pub mod var_container {
                 RustPattern::Option(Some(pat)) | RustPattern::Variant(.., pat) => {
                     pat.binds_var(var)
                 }
+                RustPattern::Struct(_, fields) => fields.iter().any(|(_, pat)| pat.binds_var(var)),
+                // NOTE - every alternative binds the same variables
+                RustPattern::Or(alts) => alts.iter().any(|pat| pat.binds_var(var)),
 
                 // binding patterns
                 RustPattern::BindRef(lab) | RustPattern::CatchAll(Some(lab)) => {
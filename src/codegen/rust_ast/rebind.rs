@@ -343,6 +343,14 @@ impl Rebindable for RustPattern {
                 con.rebind(table);
                 pat.rebind(table);
             }
+            RustPattern::UnitVariant(con) => con.rebind(table),
+            RustPattern::Struct(con, fields) => {
+                con.rebind(table);
+                for (_, pat) in fields.iter_mut() {
+                    pat.rebind(table);
+                }
+            }
+            RustPattern::Or(alts) => alts.rebind(table),
         }
     }
 }
@@ -410,6 +418,7 @@ impl Rebindable for RustType {
             RustType::AnonTuple(args) => args.iter_mut().for_each(|arg| arg.rebind(table)),
             // NOTE: provided ReadArray only holds MarkerType, it doesn't need any recursion
             RustType::ReadArray(..) | RustType::Verbatim(..) | RustType::ViewObject(..) => (),
+            RustType::Lazy(_, t) => t.rebind(table),
         }
     }
 }
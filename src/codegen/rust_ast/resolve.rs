@@ -284,6 +284,15 @@ fn solve_type(ty: &RustType, ctx: &SourceContext<'_>) -> Solution {
                         is_ref: false,
                     }
                 }
+                // NOTE - borrowed slices are owned as-is, as dereferencing them would yield an unsized type
+                CompType::Borrow(_, Mut::Immutable, t)
+                    if matches!(**t, RustType::Atom(AtomType::Comp(CompType::RawSlice(..)))) =>
+                {
+                    Solution {
+                        is_copy: true,
+                        is_ref: false,
+                    }
+                }
                 CompType::Borrow(.., t) => {
                     let Solution { is_copy, .. } = solve_type(t, ctx);
                     Solution {
@@ -316,6 +325,10 @@ fn solve_type(ty: &RustType, ctx: &SourceContext<'_>) -> Solution {
             is_copy: VIEW_OBJECT_IS_COPY,
             is_ref: false,
         },
+        RustType::Lazy(..) => Solution {
+            is_copy: false,
+            is_ref: false,
+        },
         RustType::Verbatim(..) => unreachable!("unsolvable verbatim type: {ty:?}"),
     }
 }
@@ -71,6 +71,7 @@ impl TypedDecoder<GenType> {
                 NumType::I(m_sint) => Some(Cow::Owned(GenType::Inline(RustType::from(*m_sint)))),
             },
             TypedDecoder::Call(t, ..)
+            | TypedDecoder::Native(t, ..)
             | TypedDecoder::Variant(t, ..)
             | TypedDecoder::Parallel(t, ..)
             | TypedDecoder::Branch(t, ..)
@@ -106,6 +107,7 @@ impl TypedDecoder<GenType> {
             | TypedDecoder::MonadSeq(t, ..)
             | TypedDecoder::Hint(t, ..)
             | TypedDecoder::Permit(t, ..)
+            | TypedDecoder::Resync(t, ..)
             | TypedDecoder::LiftedOption(t, ..)
             | TypedDecoder::AccumUntil(t, ..) => Some(Cow::Borrowed(t)),
             #[cfg(feature = "format_enforce")]
@@ -215,6 +217,7 @@ pub(crate) enum TypedDecoder<TypeRep> {
         Box<TypedDecoderExt<TypeRep>>,
     ),
     SkipRemainder,
+    Native(TypeRep, Label, Vec<TypedExpr<TypeRep>>),
     DecodeBytes(
         TypeRep,
         Box<TypedExpr<TypeRep>>,
@@ -262,17 +265,24 @@ pub(crate) enum TypedDecoder<TypeRep> {
         Box<TypedDecoderExt<TypeRep>>,
         Box<TypedExpr<TypeRep>>,
     ),
+    Resync(TypeRep, Box<TypedDecoderExt<TypeRep>>, Vec<ByteSet>),
 }
 
 #[derive(Clone, Debug)]
 pub(crate) struct TypedProgram<TypeRep> {
     pub decoders: Vec<(TypedDecoderExt<TypeRep>, TypeRep)>,
+    /// Name of the format each decoder was compiled from (if any), for reporting where a parse error occurred
+    pub format_names: Vec<Option<Label>>,
 }
 
 impl TypedProgram<GenType> {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         let decoders = Vec::new();
-        TypedProgram { decoders }
+        let format_names = Vec::new();
+        TypedProgram {
+            decoders,
+            format_names,
+        }
     }
 }
 
@@ -309,14 +319,14 @@ impl<'a> GTCompiler<'a> {
     pub(crate) fn compile_program(
         module: &FormatModule,
         format: &GTFormat,
-        extra: impl IntoIterator<Item = &'a GTFormat>,
+        extra: impl IntoIterator<Item = &'a (Label, GTFormat)>,
     ) -> AResult<TypedProgram<GenType>> {
         let mut compiler = GTCompiler::new(module);
 
-        compiler.compile_local_root(format, false)?;
+        compiler.compile_local_root(format, None, false)?;
 
-        for extra_f in extra {
-            compiler.compile_local_root(extra_f, true)?;
+        for (name, extra_f) in extra {
+            compiler.compile_local_root(extra_f, Some(name.clone()), true)?;
         }
 
         Ok(compiler.program)
@@ -325,7 +335,12 @@ impl<'a> GTCompiler<'a> {
     /// Given a `TypedFormat` that is either an overall root or the local root of
     /// a disconnected tree within a Format-forest, performs compilation over the
     /// format in question as well as all of its directly reachable descendants.
-    fn compile_local_root(&mut self, format: &'a GTFormat, is_extra: bool) -> AResult<()> {
+    fn compile_local_root(
+        &mut self,
+        format: &'a GTFormat,
+        name: Option<Label>,
+        is_extra: bool,
+    ) -> AResult<()> {
         let t = match format.get_type() {
             None => unreachable!("cannot compile program from local-root format with Void type"),
             Some(t) => t.into_owned(),
@@ -336,7 +351,7 @@ impl<'a> GTCompiler<'a> {
             return Ok(());
         }
 
-        self.queue_compile(t, format, None, Rc::new(Next::Empty));
+        self.queue_compile(t, name, format, None, Rc::new(Next::Empty));
         while let Some((f, args, next, n)) = self.compile_queue.pop() {
             let d = self.compile_gt_format(f, args, next)?;
             self.program.decoders[n].0 = d;
@@ -347,12 +362,14 @@ impl<'a> GTCompiler<'a> {
     fn queue_compile(
         &mut self,
         t: GenType,
+        name: Option<Label>,
         f: &'a GTFormat,
         args: Option<Vec<(Label, GenType)>>,
         next: Rc<Next<'a>>,
     ) -> usize {
         let n = self.program.decoders.len();
         self.program.decoders.push((TypedDecoder::Fail.into(), t));
+        self.program.format_names.push(name);
         self.compile_queue.push((f, args, next, n));
         n
     }
@@ -405,7 +422,9 @@ impl<'a> GTCompiler<'a> {
                 let n = if let Some(n) = self.decoder_map.get(&(*level, next.clone())) {
                     *n
                 } else {
-                    let n = self.queue_compile(gt.clone(), deref, sig_args, next.clone());
+                    let name = Label::from(self.module.get_name(*level).to_string());
+                    let n =
+                        self.queue_compile(gt.clone(), Some(name), deref, sig_args, next.clone());
                     self.decoder_map.insert((*level, next.clone()), n);
                     n
                 };
@@ -428,6 +447,9 @@ impl<'a> GTCompiler<'a> {
             TypedFormat::Fail => Ok(TypedDecoder::Fail),
             TypedFormat::EndOfInput => Ok(TypedDecoder::EndOfInput),
             TypedFormat::SkipRemainder => Ok(TypedDecoder::SkipRemainder),
+            TypedFormat::Native(gt, _index, path, args) => {
+                Ok(TypedDecoder::Native(gt.clone(), path.clone(), args.clone()))
+            }
             TypedFormat::Align(n) => Ok(TypedDecoder::Align(*n)),
             TypedFormat::Byte(bs) => Ok(TypedDecoder::Byte(*bs)),
             TypedFormat::Variant(gt, label, f) => {
@@ -767,6 +789,10 @@ impl<'a> GTCompiler<'a> {
                 let da = Box::new(self.compile_gt_format(inner, None, next)?);
                 Ok(TypedDecoder::Permit(gt.clone(), da, expr.clone()))
             }
+            TypedFormat::Resync(gt, inner, pattern) => {
+                let da = Box::new(self.compile_gt_format(inner, None, next)?);
+                Ok(TypedDecoder::Resync(gt.clone(), da, pattern.clone()))
+            }
         }?;
         Ok(TypedDecoderExt::new(dec, args))
     }
@@ -0,0 +1,848 @@
+//! Generation of encoder functions, which write the values produced by generated decoders back into bytes.
+//!
+//! Encoders are derived from the same [`TypedDecoder`] program as the decoders themselves, and exist only
+//! for those decoders whose structure permits the original bytes to be reconstructed from the decoded value
+//! (e.g. every ephemeral binding must either be unused by later parses, or be recoverable from a persisted field).
+use crate::byte_set::ByteSet;
+use crate::codegen::rust_ast::short_circuit::{EvalPurity, ShortCircuitExt};
+use crate::codegen::rust_ast::var_container::VarContainer;
+use crate::{BaseKind, CommonOp, Endian, Expr, Label, Pattern, StyleHint};
+
+use super::ixlabel::IxLabel;
+use super::model;
+use super::rust_ast::*;
+use super::typed_decoder::{GTDecoder, GTDecoderExt, TypedDecoder};
+use super::typed_format::{GenType, TypedExpr, TypedPattern};
+use super::{
+    ByteCriterion, ProdCtxt, Refutability, ToAst, embed_expr_nat, embed_expr_owned, embed_pattern,
+    get_trace, refutability_check,
+};
+
+pub(crate) fn encoder_fname(ixlabel: IxLabel) -> Label {
+    Label::from(format!("Encoder{}", ixlabel.to_usize()))
+}
+
+#[derive(Clone, Debug)]
+pub struct EncoderFn {
+    ixlabel: IxLabel,
+    body: Vec<RustStmt>,
+    extra_args: Option<Vec<(Label, GenType)>>,
+    value_type: RustType,
+}
+
+impl ToAst for EncoderFn {
+    type AstElem = RustFn;
+
+    fn to_ast(&self, _ctxt: ProdCtxt<'_>) -> RustFn {
+        let name = encoder_fname(self.ixlabel);
+        // NOTE - the lifetime of a value that is itself a borrow (e.g. `&'input [u8]`) can be elided
+        let value_type = match &self.value_type {
+            RustType::Atom(AtomType::Comp(CompType::Borrow(Some(_), m, inner)))
+                if inner.lt_param().is_none() =>
+            {
+                RustType::borrow_of(None, *m, (**inner).clone())
+            }
+            other => other.clone(),
+        };
+        let this_lt = value_type.lt_param();
+        let params = this_lt.map(|lt| DefParams::from_lt(lt.as_ref().clone()));
+        let sig = {
+            let output = (
+                Label::Borrowed(model::ENCODE_OUTPUT),
+                RustType::borrow_of(None, Mut::Mutable, RustType::imported("UnparseBuffer")),
+            );
+            let value = (
+                Label::Borrowed(model::ENCODE_VALUE),
+                RustType::borrow_of(None, Mut::Immutable, value_type),
+            );
+            let extra = self.extra_args.iter().flatten().map(|(lab, gt)| {
+                // NOTE - unused arguments are elided to avoid warnings in the generated code
+                let lab = if self.body.contains_var_ref(lab) {
+                    lab.clone()
+                } else {
+                    Label::Borrowed("_")
+                };
+                (
+                    lab,
+                    RustType::selective_borrow(None, Mut::Immutable, gt.to_rust_type()),
+                )
+            });
+            let args = [output, value].into_iter().chain(extra).collect();
+            FnSig::new(
+                args,
+                Some(RustType::result_of(
+                    RustType::UNIT,
+                    RustType::imported("UnparseError"),
+                )),
+            )
+        };
+        let body = self
+            .body
+            .iter()
+            .cloned()
+            .chain(std::iter::once(RustStmt::Return(
+                ReturnKind::Implicit,
+                RustExpr::UNIT.wrap_ok(Some("UResult")),
+            )))
+            .collect();
+        RustFn::new(name, params, sig, body)
+    }
+}
+
+/// Compiles an encoder for each decoder in `decoders` (by index) whose structure permits it.
+///
+/// As encoders of one decoder may call the encoders of others, support is determined as a fixed point,
+/// where any encoder that calls an unsupported encoder is itself unsupported.
+pub(crate) fn compile_encoders(
+    defined_types: &[RustTypeDecl],
+    decoders: &[(GTDecoderExt, GenType)],
+) -> Vec<Option<EncoderFn>> {
+    let mut supported = vec![true; decoders.len()];
+    loop {
+        let mut encoders = Vec::with_capacity(decoders.len());
+        let mut changed = false;
+        for (ix, (dec_ext, t)) in decoders.iter().enumerate() {
+            if !supported[ix] {
+                encoders.push(None);
+                continue;
+            }
+            let mut encoder_gen = EncoderGen {
+                defined_types,
+                supported: &supported,
+            };
+            let args = dec_ext.get_args();
+            let has_views = args
+                .iter()
+                .flatten()
+                .any(|(_, gt)| matches!(gt, GenType::Inline(RustType::ViewObject(..))));
+            let body = if has_views {
+                None
+            } else {
+                encoder_gen.encode(dec_ext.get_dec(), &Operand::Ref(value_expr()))
+            };
+            match body {
+                Some(body) => encoders.push(Some(EncoderFn {
+                    ixlabel: IxLabel::from(ix),
+                    body,
+                    extra_args: args.clone(),
+                    value_type: t.to_rust_type(),
+                })),
+                None => {
+                    changed = true;
+                    encoders.push(None);
+                }
+            }
+        }
+        for (ix, enc) in encoders.iter().enumerate() {
+            if enc.is_none() {
+                supported[ix] = false;
+            }
+        }
+        if !changed {
+            return encoders;
+        }
+    }
+}
+
+fn output_expr() -> RustExpr {
+    RustExpr::local(model::ENCODE_OUTPUT)
+}
+
+fn value_expr() -> RustExpr {
+    RustExpr::local(model::ENCODE_VALUE)
+}
+
+/// AST-level handle for the value being encoded
+#[derive(Clone, Debug)]
+enum Operand {
+    /// Expression of type `&T`
+    Ref(RustExpr),
+    /// Place-expression of type `T`
+    Place(RustExpr),
+}
+
+impl Operand {
+    /// Returns an expression that can be used as a method-call receiver or for projections (through auto-deref).
+    fn recv(&self) -> RustExpr {
+        match self {
+            Operand::Ref(expr) | Operand::Place(expr) => expr.clone(),
+        }
+    }
+
+    /// Returns an expression of type `&T`
+    fn borrowed(&self) -> RustExpr {
+        match self {
+            Operand::Ref(expr) => expr.clone(),
+            Operand::Place(expr) => expr.clone().borrow_of(),
+        }
+    }
+
+    /// Returns an expression of type `T`, copying or cloning as necessary.
+    fn owned(&self, t: &GenType) -> RustExpr {
+        match self {
+            Operand::Ref(expr) => RustExpr::Owned(OwnedRustExpr {
+                expr: Box::new(expr.clone()),
+                kind: if t.is_copy() {
+                    OwnedKind::Deref
+                } else {
+                    OwnedKind::Cloned
+                },
+            }),
+            Operand::Place(expr) => expr.clone().owned(t.to_rust_type()),
+        }
+    }
+
+    fn field(&self, name: &Label) -> Operand {
+        Operand::Place(self.recv().field(name.clone()))
+    }
+
+    fn at_pos(&self, n: usize) -> Operand {
+        Operand::Place(self.recv().at_pos(n))
+    }
+
+    fn index(&self, ix: usize) -> Operand {
+        Operand::Place(self.recv().index(RustExpr::num_lit(ix)))
+    }
+}
+
+struct EncoderGen<'a> {
+    defined_types: &'a [RustTypeDecl],
+    supported: &'a [bool],
+}
+
+type Stmts = Vec<RustStmt>;
+
+impl<'a> EncoderGen<'a> {
+    /// Embeds an expression that the encoding depends on, provided it does not contain an early return of a `ParseError`.
+    fn embed(&self, expr: &TypedExpr<GenType>, owned: bool) -> Option<RustExpr> {
+        let ret = if owned {
+            embed_expr_owned(expr)
+        } else {
+            embed_expr_nat(expr)
+        };
+        (ret.check_eval_purity() != EvalPurity::Return).then_some(ret)
+    }
+
+    fn enum_variants(&self, gt: &GenType) -> Option<(Label, &'a [RustVariant])> {
+        let (ix, lab, _) = gt.try_as_adhoc()?;
+        match &self.defined_types[ix].def {
+            RustTypeDef::Enum(vars) => Some((lab.clone(), vars.as_slice())),
+            RustTypeDef::Struct(_) => None,
+        }
+    }
+
+    /// Returns a block of statements that writes the bytes of `value` (as parsed by `dec`) to the output buffer,
+    /// or `None` if the decoder does not permit such a reconstruction.
+    fn encode(&mut self, dec: &GTDecoder, value: &Operand) -> Option<Stmts> {
+        match dec {
+            TypedDecoder::Byte(bs) => Some(self.encode_byte(bs, value)),
+            TypedDecoder::Call(_, ix, (args, views)) => {
+                if !self.supported[*ix] || !views.is_empty() {
+                    return None;
+                }
+                let mut call_args = vec![output_expr(), value.borrowed()];
+                for (_, x) in args.iter() {
+                    let t = x.get_type()?;
+                    let arg = if t.to_rust_type().should_borrow_for_arg() {
+                        RustExpr::borrow_of(self.embed(x, false)?)
+                    } else {
+                        self.embed(x, true)?
+                    };
+                    call_args.push(arg);
+                }
+                let call = RustExpr::local(encoder_fname(IxLabel::from(*ix))).call_with(call_args);
+                Some(vec![RustStmt::Expr(call.wrap_try())])
+            }
+            TypedDecoder::EndOfInput
+            | TypedDecoder::SkipRemainder
+            | TypedDecoder::Phantom
+            | TypedDecoder::Pos(..)
+            | TypedDecoder::Peek(..)
+            | TypedDecoder::PeekNot(..)
+            | TypedDecoder::Compute(..)
+            | TypedDecoder::LiftedOption(_, None) => Some(Vec::new()),
+            TypedDecoder::Align(n) => {
+                Some(vec![RustStmt::Expr(model::pad_align(output_expr(), *n))])
+            }
+            TypedDecoder::Hint(_, StyleHint::Common(CommonOp::EndianParse(kind)), _) => {
+                let method = match kind {
+                    BaseKind::U16Ext(Endian::Le)
+                    | BaseKind::U32Ext(Endian::Le)
+                    | BaseKind::U64Ext(Endian::Le)
+                    | BaseKind::I16Ext(Endian::Le)
+                    | BaseKind::I32Ext(Endian::Le)
+                    | BaseKind::I64Ext(Endian::Le) => "to_le_bytes",
+                    _ => "to_be_bytes",
+                };
+                Some(vec![RustStmt::Expr(model::extend_bytes(
+                    output_expr(),
+                    value.recv().call_method(method),
+                ))])
+            }
+            TypedDecoder::Hint(_, _, inner) | TypedDecoder::Where(_, inner, _) => {
+                self.encode(inner.get_dec(), value)
+            }
+            #[cfg(feature = "format_enforce")]
+            TypedDecoder::Enforce(_, inner) => self.encode(inner.get_dec(), value),
+            TypedDecoder::Tuple(_, elts) => {
+                let mut stmts = Vec::new();
+                for (ix, elt) in elts.iter().enumerate() {
+                    stmts.extend(self.encode(elt.get_dec(), &value.at_pos(ix))?);
+                }
+                Some(stmts)
+            }
+            TypedDecoder::Sequence(_, elts) => {
+                let mut stmts = vec![RustStmt::Expr(model::try_check_length(
+                    RustExpr::num_lit(elts.len()),
+                    value.recv().call_method("len"),
+                ))];
+                for (ix, elt) in elts.iter().enumerate() {
+                    stmts.extend(self.encode(elt.get_dec(), &value.index(ix))?);
+                }
+                Some(stmts)
+            }
+            TypedDecoder::Repeat0While(_, _, single)
+            | TypedDecoder::Repeat1Until(_, _, single)
+            | TypedDecoder::RepeatBetween(_, _, _, _, single)
+            | TypedDecoder::RepeatUntilLast(_, _, single)
+            | TypedDecoder::RepeatUntilSeq(_, _, single) => self.encode_each(single, value),
+            TypedDecoder::RepeatCount(_, count, single) => {
+                let check = RustStmt::Expr(model::try_check_length(
+                    self.embed(count, false)?.cast_as_usize(),
+                    value.recv().call_method("len"),
+                ));
+                let mut stmts = vec![check];
+                stmts.extend(self.encode_each(single, value)?);
+                Some(stmts)
+            }
+            TypedDecoder::Slice(_, width, inner) => {
+                let inner = self.encode(inner.get_dec(), value)?;
+                self.encode_slice(width, inner)
+            }
+            TypedDecoder::Maybe(_, cond, inner) => {
+                let cond = self.embed(cond, false)?;
+                // NOTE - the presence of the value must agree with the condition under which it is parsed
+                let refute_if = |cond: RustExpr| {
+                    RustStmt::Expr(RustExpr::Control(Box::new(RustControl::If(
+                        Box::new(cond),
+                        vec![RustStmt::Return(
+                            ReturnKind::Keyword,
+                            model::err_unparse_fallthrough(get_trace(&())),
+                        )],
+                        None,
+                    ))))
+                };
+                let mut some_stmts = vec![refute_if(cond.clone().negate())];
+                some_stmts.extend(self.encode(
+                    inner.get_dec(),
+                    &Operand::Ref(RustExpr::local(model::ENCODE_INNER)),
+                )?);
+                let some_pat = RustPattern::Option(Some(Box::new(RustPattern::CatchAll(Some(
+                    Label::Borrowed(model::ENCODE_INNER),
+                )))));
+                let cases = vec![
+                    arm(some_pat, some_stmts),
+                    arm(RustPattern::Option(None), vec![refute_if(cond)]),
+                ];
+                Some(vec![match_stmt(
+                    value.borrowed(),
+                    RustMatchBody::Irrefutable(cases),
+                )])
+            }
+            TypedDecoder::Variant(gt, ..) => self.encode_variants(gt, [dec], value),
+            TypedDecoder::Parallel(gt, alts) | TypedDecoder::Branch(gt, _, alts) => {
+                self.encode_alts(gt, alts, value)
+            }
+            TypedDecoder::Let(_, name, expr, inner) => {
+                let mut stmts = self.encode(inner.get_dec(), value)?;
+                if stmts.contains_var_ref(name) {
+                    let rhs = self.embed(expr, true)?;
+                    stmts.insert(0, RustStmt::assign(name.clone(), rhs));
+                }
+                Some(stmts)
+            }
+            TypedDecoder::LetFormat(_, f0, name, f) => {
+                let rest = self.encode(f.get_dec(), value)?;
+                let source = binding_source(f.get_dec(), name, value);
+                let needed = mentions(f.get_dec(), name);
+                self.encode_step(f0.get_dec(), Some(name), needed, source, rest)
+            }
+            TypedDecoder::MonadSeq(_, f0, f) => {
+                let rest = self.encode(f.get_dec(), value)?;
+                self.encode_step(f0.get_dec(), None, false, None, rest)
+            }
+            TypedDecoder::Match(_, scrutinee, cases) => {
+                let scrutinee_type = scrutinee.get_type()?.into_owned();
+                let scrutinized = self.embed(scrutinee, false)?;
+                let head = match &scrutinee_type {
+                    GenType::Inline(RustType::Atom(AtomType::Comp(CompType::Vec(..)))) => {
+                        scrutinized.vec_as_slice()
+                    }
+                    _ => scrutinized,
+                };
+                let mut arms = Vec::with_capacity(cases.len());
+                for (pat, dec) in cases.iter() {
+                    let body = self.encode(dec.get_dec(), value)?;
+                    match pat {
+                        TypedPattern::Guard(inner, guard) => {
+                            let guard = self.embed(guard, false)?;
+                            arms.push(guarded_arm(embed_pattern(inner), guard, body));
+                        }
+                        _ => arms.push(arm(embed_pattern(pat), body)),
+                    }
+                }
+                let body = match refutability_check(&scrutinee_type, cases) {
+                    Refutability::Irrefutable => return Some(irrefutable_match(head, arms)),
+                    Refutability::Refutable | Refutability::Indeterminate => {
+                        RustMatchBody::Refutable(
+                            arms,
+                            RustCatchAll::ReturnErrorValue {
+                                value: Box::new(model::err_unparse_fallthrough(get_trace(&()))),
+                            },
+                        )
+                    }
+                };
+                Some(vec![match_stmt(head, body)])
+            }
+            TypedDecoder::Fail
+            | TypedDecoder::Bits(..)
+            | TypedDecoder::WithRelativeOffset(..)
+            | TypedDecoder::Map(..)
+            | TypedDecoder::Dynamic(..)
+            | TypedDecoder::Apply(..)
+            | TypedDecoder::Native(..)
+            | TypedDecoder::ForEach(..)
+            | TypedDecoder::DecodeBytes(..)
+            | TypedDecoder::AccumUntil(..)
+            | TypedDecoder::LiftedOption(_, Some(_))
+            | TypedDecoder::LetView(..)
+            | TypedDecoder::CaptureBytes(..)
+            | TypedDecoder::ParseFromView(..)
+            | TypedDecoder::ReadArray(..)
+            | TypedDecoder::ReifyView(..)
+            | TypedDecoder::Permit(..)
+            | TypedDecoder::Resync(..) => None,
+        }
+    }
+
+    fn encode_byte(&mut self, bs: &ByteSet, value: &Operand) -> Stmts {
+        let b = RustExpr::local(model::ENCODE_BYTE);
+        let push = RustStmt::Expr(model::push_byte(output_expr(), b.clone()));
+        let bc = ByteCriterion::from(bs);
+        if bc.is_always_true() {
+            return vec![RustStmt::Expr(model::push_byte(
+                output_expr(),
+                value.owned(&GenType::from(PrimType::U8)),
+            ))];
+        }
+        let bind = RustStmt::assign(
+            model::ENCODE_BYTE,
+            value.owned(&GenType::from(PrimType::U8)),
+        );
+        let check = RustExpr::Control(Box::new(RustControl::If(
+            Box::new(bc.as_predicate(b.clone())),
+            vec![push],
+            Some(vec![RustStmt::Return(
+                ReturnKind::Keyword,
+                model::err_excluded_byte(b, get_trace(&())),
+            )]),
+        )));
+        vec![RustStmt::Expr(RustExpr::BlockScope(
+            vec![bind],
+            Box::new(check),
+        ))]
+    }
+
+    /// Encodes each element of the sequence `value` using `single`.
+    fn encode_each(&mut self, single: &GTDecoderExt, value: &Operand) -> Option<Stmts> {
+        let body = self.encode(
+            single.get_dec(),
+            &Operand::Ref(RustExpr::local(model::ENCODE_ELEM)),
+        )?;
+        let elem = if body.contains_var_ref(model::ENCODE_ELEM) {
+            Label::Borrowed(model::ENCODE_ELEM)
+        } else {
+            Label::Borrowed("_")
+        };
+        Some(vec![RustStmt::Expr(RustExpr::Control(Box::new(
+            RustControl::ForIter(elem, Box::new(value.recv().call_method("iter")), body),
+        )))])
+    }
+
+    fn encode_slice(&mut self, width: &TypedExpr<GenType>, inner: Stmts) -> Option<Stmts> {
+        let width = self.embed(width, false)?.cast_as_usize();
+        let mut stmts = vec![RustStmt::assign(
+            model::ENCODE_SLICE_START,
+            model::output_len(output_expr()),
+        )];
+        stmts.extend(inner);
+        let close = model::try_close_encoded_slice(
+            output_expr(),
+            RustExpr::local(model::ENCODE_SLICE_START),
+            width,
+        );
+        Some(vec![RustStmt::Expr(RustExpr::BlockScope(
+            stmts,
+            Box::new(close),
+        ))])
+    }
+
+    /// Encodes a value produced by one of several alternative decoders (e.g. a union).
+    fn encode_alts(
+        &mut self,
+        gt: &GenType,
+        alts: &[GTDecoderExt],
+        value: &Operand,
+    ) -> Option<Stmts> {
+        match alts {
+            [] => None,
+            [alt] => self.encode(alt.get_dec(), value),
+            _ if alts
+                .iter()
+                .all(|alt| matches!(alt.get_dec(), TypedDecoder::Variant(..))) =>
+            {
+                self.encode_variants(gt, alts.iter().map(GTDecoderExt::get_dec), value)
+            }
+            _ => {
+                let mut merged = ByteSet::empty();
+                for alt in alts.iter() {
+                    match alt.get_dec() {
+                        TypedDecoder::Byte(bs) => merged = merged.union(bs),
+                        _ => return None,
+                    }
+                }
+                Some(self.encode_byte(&merged, value))
+            }
+        }
+    }
+
+    /// Encodes an enum-typed value, where each of `decs` is a `TypedDecoder::Variant` of the enum.
+    ///
+    /// If several decoders produce the same variant, only the first is used.
+    fn encode_variants<'d>(
+        &mut self,
+        gt: &GenType,
+        decs: impl IntoIterator<Item = &'d GTDecoder>,
+        value: &Operand,
+    ) -> Option<Stmts> {
+        let (type_name, vars) = self.enum_variants(gt)?;
+        let mut seen = Vec::<&Label>::new();
+        let mut arms = Vec::new();
+        for dec in decs {
+            let TypedDecoder::Variant(_, name, inner) = dec else {
+                return None;
+            };
+            if seen.contains(&name) {
+                continue;
+            }
+            seen.push(name);
+            let constr = Constructor::Compound(type_name.clone(), name.clone());
+            match vars.iter().find(|var| var.get_label() == name)? {
+                RustVariant::Unit(_) => {
+                    let body = synthesize(inner.get_dec())?;
+                    arms.push(arm(RustPattern::UnitVariant(constr), body));
+                }
+                RustVariant::Tuple(_, types) if types.len() == 1 => {
+                    let body = self.encode(
+                        inner.get_dec(),
+                        &Operand::Ref(RustExpr::local(model::ENCODE_INNER)),
+                    )?;
+                    let binding = RustPattern::CatchAll(Some(Label::Borrowed(model::ENCODE_INNER)));
+                    arms.push(arm(RustPattern::Variant(constr, Box::new(binding)), body));
+                }
+                RustVariant::Tuple(..) => return None,
+            }
+        }
+        let body = if arms.len() < vars.len() {
+            RustMatchBody::Refutable(
+                arms,
+                RustCatchAll::ReturnErrorValue {
+                    value: Box::new(model::err_unparse_fallthrough(get_trace(&()))),
+                },
+            )
+        } else {
+            RustMatchBody::Irrefutable(arms)
+        };
+        Some(vec![match_stmt(value.borrowed(), body)])
+    }
+
+    /// Prepends the encoding of the let-bound (or sequenced) decoder `dec` to the encoding `rest` of the remainder of the chain.
+    ///
+    /// When `source` is provided, it is the persisted copy of the value bound to `name` within the overall value.
+    /// Otherwise, `needed` indicates whether the remainder of the chain depends on the bound value in any way.
+    fn encode_step(
+        &mut self,
+        dec: &GTDecoder,
+        name: Option<&Label>,
+        needed: bool,
+        source: Option<Operand>,
+        rest: Stmts,
+    ) -> Option<Stmts> {
+        let is_used = name.is_some_and(|name| rest.contains_var_ref(name));
+        let mut stmts = Vec::new();
+        match source {
+            Some(source) => {
+                stmts.extend(self.encode(dec, &source)?);
+                if is_used {
+                    let t = dec.get_type()?;
+                    stmts.push(RustStmt::assign(name?.clone(), source.owned(&t)));
+                }
+            }
+            None if !needed => stmts.extend(synthesize(dec)?),
+            None => match dec {
+                TypedDecoder::Compute(_, expr) => {
+                    if is_used {
+                        stmts.push(RustStmt::assign(name?.clone(), self.embed(expr, true)?));
+                    }
+                }
+                TypedDecoder::Pos(nt) => {
+                    if is_used {
+                        let offset = model::output_offset(output_expr());
+                        stmts.push(RustStmt::assign(
+                            name?.clone(),
+                            RustExpr::Operation(RustOp::AsCast(
+                                Box::new(offset),
+                                RustType::from(*nt),
+                            )),
+                        ));
+                    }
+                }
+                // NOTE - the value is needed by later parses but is not retained, so the encoding cannot be reconstructed
+                _ => return None,
+            },
+        }
+        stmts.extend(rest);
+        Some(stmts)
+    }
+}
+
+/// Returns `true` if the decoder `dec` might depend on the value bound to `name` in an enclosing scope.
+///
+/// Errs on the side of caution, returning `true` for any construct that is not modeled in detail.
+fn mentions(dec: &GTDecoder, name: &Label) -> bool {
+    let expr_mentions = |expr: &TypedExpr<GenType>| Expr::from(expr.clone()).is_shadowed_by(name);
+    match dec {
+        TypedDecoder::Fail
+        | TypedDecoder::EndOfInput
+        | TypedDecoder::Align(_)
+        | TypedDecoder::Byte(_)
+        | TypedDecoder::Pos(_)
+        | TypedDecoder::SkipRemainder
+        | TypedDecoder::Phantom
+        | TypedDecoder::Apply(..)
+        | TypedDecoder::LiftedOption(_, None) => false,
+        TypedDecoder::Call(_, _, (args, views)) => {
+            !views.is_empty() || args.iter().any(|(_, arg)| expr_mentions(arg))
+        }
+        TypedDecoder::Variant(_, _, inner)
+        | TypedDecoder::Repeat0While(_, _, inner)
+        | TypedDecoder::Repeat1Until(_, _, inner)
+        | TypedDecoder::Peek(_, inner)
+        | TypedDecoder::PeekNot(_, inner)
+        | TypedDecoder::Bits(_, inner)
+        | TypedDecoder::Hint(_, _, inner)
+        | TypedDecoder::LiftedOption(_, Some(inner)) => mentions(inner.get_dec(), name),
+        #[cfg(feature = "format_enforce")]
+        TypedDecoder::Enforce(_, inner) => mentions(inner.get_dec(), name),
+        TypedDecoder::Parallel(_, alts)
+        | TypedDecoder::Branch(_, _, alts)
+        | TypedDecoder::Tuple(_, alts)
+        | TypedDecoder::Sequence(_, alts) => alts.iter().any(|alt| mentions(alt.get_dec(), name)),
+        TypedDecoder::RepeatCount(_, expr, inner)
+        | TypedDecoder::RepeatUntilLast(_, expr, inner)
+        | TypedDecoder::RepeatUntilSeq(_, expr, inner)
+        | TypedDecoder::Slice(_, expr, inner)
+        | TypedDecoder::Map(_, inner, expr)
+        | TypedDecoder::Maybe(_, expr, inner)
+        | TypedDecoder::DecodeBytes(_, expr, inner) => {
+            expr_mentions(expr) || mentions(inner.get_dec(), name)
+        }
+        TypedDecoder::RepeatBetween(_, _, lo, hi, inner) => {
+            expr_mentions(lo) || expr_mentions(hi) || mentions(inner.get_dec(), name)
+        }
+        TypedDecoder::WithRelativeOffset(_, base, offset, inner) => {
+            expr_mentions(base) || expr_mentions(offset) || mentions(inner.get_dec(), name)
+        }
+        TypedDecoder::Where(_, inner, cond) => {
+            expr_mentions(cond.as_ref()) || mentions(inner.get_dec(), name)
+        }
+        TypedDecoder::Compute(_, expr) => expr_mentions(expr),
+        TypedDecoder::Native(_, _, args) => args.iter().any(expr_mentions),
+        TypedDecoder::Let(_, inner_name, expr, inner) => {
+            expr_mentions(expr) || (inner_name != name && mentions(inner.get_dec(), name))
+        }
+        TypedDecoder::ForEach(_, expr, inner_name, inner) => {
+            expr_mentions(expr) || (inner_name != name && mentions(inner.get_dec(), name))
+        }
+        TypedDecoder::LetFormat(_, f0, inner_name, f) => {
+            mentions(f0.get_dec(), name) || (inner_name != name && mentions(f.get_dec(), name))
+        }
+        TypedDecoder::MonadSeq(_, f0, f) => {
+            mentions(f0.get_dec(), name) || mentions(f.get_dec(), name)
+        }
+        TypedDecoder::Match(_, scrutinee, cases) => {
+            expr_mentions(scrutinee)
+                || cases.iter().any(|(pat, case)| {
+                    let pat = Pattern::from(pat.clone());
+                    (!pat.shadows(name) && mentions(case.get_dec(), name))
+                        || pat.guard_mentions(name)
+                })
+        }
+        _ => true,
+    }
+}
+
+/// Locates the persisted copy of the value bound to `name` within the value produced by the chain `dec`,
+/// if it is (directly) the value itself or a field of the record it constructs.
+fn binding_source(dec: &GTDecoder, name: &Label, value: &Operand) -> Option<Operand> {
+    match dec {
+        TypedDecoder::LetFormat(_, _, inner_name, _) if inner_name == name => None,
+        TypedDecoder::Let(_, inner_name, ..) if inner_name == name => None,
+        TypedDecoder::LetFormat(.., inner)
+        | TypedDecoder::MonadSeq(_, _, inner)
+        | TypedDecoder::Let(.., inner)
+        | TypedDecoder::Hint(_, _, inner)
+        | TypedDecoder::Where(_, inner, _) => binding_source(inner.get_dec(), name, value),
+        TypedDecoder::Compute(_, expr) => match expr.as_ref() {
+            TypedExpr::Var(_, var) if var == name => Some(value.clone()),
+            TypedExpr::Record(_, fields) => fields.iter().find_map(|(lab, fld)| match fld {
+                TypedExpr::Var(_, var) if var == name => Some(value.field(lab)),
+                _ => None,
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns a block of statements that writes a canonical (minimal) encoding for `dec`, for use in
+/// place of values that are parsed but not retained.
+fn synthesize(dec: &GTDecoder) -> Option<Stmts> {
+    match dec {
+        TypedDecoder::Byte(bs) => {
+            let byte = bs.min_elem()?;
+            Some(vec![RustStmt::Expr(model::push_byte(
+                output_expr(),
+                RustExpr::u8lit(byte),
+            ))])
+        }
+        TypedDecoder::Tuple(_, elts) | TypedDecoder::Sequence(_, elts) => {
+            let mut stmts = Vec::new();
+            for elt in elts.iter() {
+                stmts.extend(synthesize(elt.get_dec())?);
+            }
+            Some(stmts)
+        }
+        TypedDecoder::MonadSeq(_, f0, f) => {
+            let mut stmts = synthesize(f0.get_dec())?;
+            stmts.extend(synthesize(f.get_dec())?);
+            Some(stmts)
+        }
+        TypedDecoder::Repeat0While(..)
+        | TypedDecoder::EndOfInput
+        | TypedDecoder::SkipRemainder
+        | TypedDecoder::Phantom
+        | TypedDecoder::Pos(..)
+        | TypedDecoder::Peek(..)
+        | TypedDecoder::PeekNot(..)
+        | TypedDecoder::Compute(..) => Some(Vec::new()),
+        TypedDecoder::Align(n) => Some(vec![RustStmt::Expr(model::pad_align(output_expr(), *n))]),
+        TypedDecoder::Repeat1Until(_, _, inner)
+        | TypedDecoder::Hint(_, _, inner)
+        | TypedDecoder::Where(_, inner, _)
+        | TypedDecoder::Map(_, inner, _)
+        | TypedDecoder::Variant(_, _, inner) => synthesize(inner.get_dec()),
+        TypedDecoder::Parallel(_, alts) | TypedDecoder::Branch(_, _, alts) => {
+            synthesize(alts.first()?.get_dec())
+        }
+        _ => None,
+    }
+}
+
+fn arm(pat: RustPattern, body: Stmts) -> RustMatchCase {
+    (
+        MatchCaseLHS::Pattern(prune_bindings(pat, &|lab| body.contains_var_ref(lab))),
+        body,
+    )
+}
+
+/// Like [`arm`], but with a guard (which may also refer to the bindings of `pat`).
+fn guarded_arm(pat: RustPattern, guard: RustExpr, body: Stmts) -> RustMatchCase {
+    let is_used = |lab: &Label| body.contains_var_ref(lab) || guard.contains_var_ref(lab);
+    let pat = prune_bindings(pat, &is_used);
+    (MatchCaseLHS::WithGuard(pat, guard), body)
+}
+
+/// Constructs an irrefutable match, replacing it with simpler control flow when it is either a trivial catch-all
+/// or an exhaustive case-split on a boolean.
+fn irrefutable_match(head: RustExpr, mut arms: Vec<RustMatchCase>) -> Stmts {
+    let bool_lit = |arm: &RustMatchCase| match arm.0 {
+        MatchCaseLHS::Pattern(RustPattern::PrimLiteral(RustPrimLit::Boolean(b))) => Some(b),
+        _ => None,
+    };
+    match arms.as_slice() {
+        [(MatchCaseLHS::Pattern(RustPattern::CatchAll(None)), _)] => arms.pop().unwrap().1,
+        [a0, a1] if bool_lit(a0).is_some() && bool_lit(a1) == bool_lit(a0).map(|b| !b) => {
+            let first_is_true = bool_lit(a0) == Some(true);
+            let (b_last, b_first) = (arms.pop().unwrap().1, arms.pop().unwrap().1);
+            let (b_then, b_else) = if first_is_true {
+                (b_first, b_last)
+            } else {
+                (b_last, b_first)
+            };
+            let stmt = match (b_then.is_empty(), b_else.is_empty()) {
+                (true, true) => return Vec::new(),
+                (false, true) => RustControl::If(Box::new(head), b_then, None),
+                (true, false) => RustControl::If(Box::new(head.negate()), b_else, None),
+                (false, false) => RustControl::If(Box::new(head), b_then, Some(b_else)),
+            };
+            vec![RustStmt::Expr(RustExpr::Control(Box::new(stmt)))]
+        }
+        _ => vec![match_stmt(head, RustMatchBody::Irrefutable(arms))],
+    }
+}
+
+fn match_stmt(head: RustExpr, body: RustMatchBody) -> RustStmt {
+    RustStmt::Expr(RustExpr::Control(Box::new(RustControl::Match(
+        Box::new(head),
+        body,
+    ))))
+}
+
+/// Replaces any variable-bindings in `pat` that are not `is_used` with wildcards, to avoid unused-variable warnings.
+fn prune_bindings(pat: RustPattern, is_used: &impl Fn(&Label) -> bool) -> RustPattern {
+    match pat {
+        RustPattern::CatchAll(Some(lab)) | RustPattern::BindRef(lab) if !is_used(&lab) => {
+            RustPattern::CatchAll(None)
+        }
+        RustPattern::TupleLiteral(pats) => RustPattern::TupleLiteral(
+            pats.into_iter()
+                .map(|pat| prune_bindings(pat, is_used))
+                .collect(),
+        ),
+        RustPattern::ArrayLiteral(pats) => RustPattern::ArrayLiteral(
+            pats.into_iter()
+                .map(|pat| prune_bindings(pat, is_used))
+                .collect(),
+        ),
+        RustPattern::Option(Some(pat)) => {
+            RustPattern::Option(Some(Box::new(prune_bindings(*pat, is_used))))
+        }
+        RustPattern::Variant(constr, pat) => {
+            RustPattern::Variant(constr, Box::new(prune_bindings(*pat, is_used)))
+        }
+        RustPattern::Struct(constr, fields) => RustPattern::Struct(
+            constr,
+            fields
+                .into_iter()
+                .map(|(lab, pat)| (lab, prune_bindings(pat, is_used)))
+                .collect(),
+        ),
+        RustPattern::Or(alts) => RustPattern::Or(
+            alts.into_iter()
+                .map(|pat| prune_bindings(pat, is_used))
+                .collect(),
+        ),
+        other => other,
+    }
+}
@@ -12,7 +12,7 @@ use crate::codegen::rust_ast::{RustLt, RustParams, UseParams};
 use crate::numeric::core::Bounds as NumBounds;
 use crate::numeric::elaborator::TypedExpr as TypedNumExpr;
 use crate::validation::TypedCondition;
-use crate::{Arith, BaseKind, Endian, IntRel, Label, StyleHint, TypeHint, UnaryOp};
+use crate::{Arith, BaseKind, Endian, FixedType, IntRel, Label, StyleHint, TypeHint, UnaryOp};
 
 pub(crate) mod variables;
 
@@ -46,6 +46,11 @@ impl GenType {
     //     }
     // }
 
+    /// Returns `true` if `self` is the type of a deferred parse (i.e. `Lazy<'input, T>`).
+    pub(crate) fn is_lazy(&self) -> bool {
+        matches!(self, GenType::Inline(RustType::Lazy(..)))
+    }
+
     /// Attempt to extract the type-index and corresponding name (`Label`) from `self`.
     ///
     /// Returns `None` if the type in question is not itself a concrete definition (`GenType::Def`)
@@ -103,6 +108,10 @@ impl<TypeRep> std::hash::Hash for TypedFormat<TypeRep> {
             | TypedFormat::Pos(_)
             | TypedFormat::Fail
             | TypedFormat::EndOfInput => {}
+            TypedFormat::Native(_, index, _, args) => {
+                index.hash(state);
+                args.hash(state);
+            }
             TypedFormat::DecodeBytes(_, expr, inner) => {
                 expr.hash(state);
                 inner.hash(state);
@@ -212,6 +221,10 @@ impl<TypeRep> std::hash::Hash for TypedFormat<TypeRep> {
             TypedFormat::Enforce(_, f) => {
                 f.hash(state);
             }
+            TypedFormat::Resync(_, f, pattern) => {
+                f.hash(state);
+                pattern.hash(state);
+            }
             TypedFormat::Permit(_, f, dft) => {
                 f.hash(state);
                 dft.hash(state);
@@ -294,6 +307,8 @@ pub enum TypedFormat<TypeRep> {
     Apply(TypeRep, Label, Rc<TypedDynFormat<TypeRep>>),
     Pos(TypeRep),
     SkipRemainder,
+    /// Native format of the given index, whose generated-code implementation is at the given path
+    Native(TypeRep, usize, Label, Vec<TypedExpr<TypeRep>>),
     DecodeBytes(TypeRep, Box<TypedExpr<TypeRep>>, Box<TypedFormat<TypeRep>>),
     ParseFromView(TypeRep, TypedViewExpr<TypeRep>, Box<TypedFormat<TypeRep>>),
     LetFormat(
@@ -323,6 +338,7 @@ pub enum TypedFormat<TypeRep> {
     #[cfg(feature = "format_enforce")]
     Enforce(TypeRep, Box<TypedFormat<TypeRep>>),
     Permit(TypeRep, Box<TypedFormat<TypeRep>>, Box<TypedExpr<TypeRep>>),
+    Resync(TypeRep, Box<TypedFormat<TypeRep>>, Vec<ByteSet>),
 }
 
 impl TypedFormat<GenType> {
@@ -340,6 +356,8 @@ impl TypedFormat<GenType> {
             | TypedFormat::ParseFromView(_, _, _)
             | TypedFormat::Fail => Bounds::exact(0),
 
+            TypedFormat::Native(..) => Bounds::any(),
+
             TypedFormat::Peek(_, inner) | TypedFormat::PeekNot(_, inner) => {
                 inner.lookahead_bounds()
             }
@@ -401,9 +419,9 @@ impl TypedFormat<GenType> {
             ),
             #[cfg(feature = "format_enforce")]
             TypedFormat::Enforce(.., inner) => inner.lookahead_bounds(),
-            TypedFormat::Permit(.., inner, _) | TypedFormat::Hint(.., inner) => {
-                inner.lookahead_bounds()
-            }
+            TypedFormat::Permit(.., inner, _)
+            | TypedFormat::Resync(_, inner, _)
+            | TypedFormat::Hint(.., inner) => inner.lookahead_bounds(),
             TypedFormat::LiftedOption(_, f) => f
                 .as_ref()
                 .map_or(Bounds::exact(0), |f| f.lookahead_bounds()),
@@ -426,6 +444,8 @@ impl TypedFormat<GenType> {
             | TypedFormat::Pos(_)
             | TypedFormat::Fail => Bounds::exact(0),
 
+            TypedFormat::Native(..) => Bounds::any(),
+
             TypedFormat::Align(n) => Bounds::new(0, n - 1),
             TypedFormat::Byte(_) => Bounds::exact(1),
             TypedFormat::Variant(_, _, f) => f.match_bounds(),
@@ -484,6 +504,7 @@ impl TypedFormat<GenType> {
             TypedFormat::Permit(.., inner, _) | TypedFormat::Hint(.., inner) => {
                 inner.match_bounds()
             }
+            TypedFormat::Resync(_, inner, _) => Bounds::at_least(inner.match_bounds().min.min(1)),
             TypedFormat::LiftedOption(_, f) => {
                 f.as_ref().map_or(Bounds::exact(0), |f| f.match_bounds())
             }
@@ -519,10 +540,12 @@ impl TypedFormat<GenType> {
             TypedFormat::Byte(_) => Some(Cow::Owned(GenType::from(PrimType::U8))),
             // REVIEW - forcing Pos to be a U64-valued format
             TypedFormat::Pos(gt)
+            | TypedFormat::Native(gt, ..)
             | TypedFormat::LetFormat(gt, ..)
             | TypedFormat::MonadSeq(gt, ..)
             | TypedFormat::Hint(gt, ..)
             | TypedFormat::Permit(gt, ..)
+            | TypedFormat::Resync(gt, ..)
             | TypedFormat::DecodeBytes(gt, ..)
             | TypedFormat::ParseFromView(gt, ..)
             | TypedFormat::FormatCall(gt, ..)
@@ -685,6 +708,9 @@ where
     AsU32(Box<TypedExpr<TypeRep, VarId>>),
     AsU64(Box<TypedExpr<TypeRep, VarId>>),
     AsChar(Box<TypedExpr<TypeRep, VarId>>),
+    F32FromBits(Box<TypedExpr<TypeRep, VarId>>),
+    F64FromBits(Box<TypedExpr<TypeRep, VarId>>),
+    FixedFromBits(FixedType, Box<TypedExpr<TypeRep, VarId>>),
 
     U16Be(Box<TypedExpr<TypeRep, VarId>>),
     U16Le(Box<TypedExpr<TypeRep, VarId>>),
@@ -737,6 +763,29 @@ where
         Box<TypedExpr<TypeRep, VarId>>,
         Box<TypedExpr<TypeRep, VarId>>,
     ),
+    Filter(
+        TypeRep,
+        Box<TypedExpr<TypeRep, VarId>>,
+        Box<TypedExpr<TypeRep, VarId>>,
+    ),
+    Zip(
+        TypeRep,
+        Box<TypedExpr<TypeRep, VarId>>,
+        Box<TypedExpr<TypeRep, VarId>>,
+    ),
+    Reverse(TypeRep, Box<TypedExpr<TypeRep, VarId>>),
+    Any(
+        Box<TypedExpr<TypeRep, VarId>>,
+        Box<TypedExpr<TypeRep, VarId>>,
+    ),
+    All(
+        Box<TypedExpr<TypeRep, VarId>>,
+        Box<TypedExpr<TypeRep, VarId>>,
+    ),
+    IsSortedBy(
+        Box<TypedExpr<TypeRep, VarId>>,
+        Box<TypedExpr<TypeRep, VarId>>,
+    ),
     FlatMapList(
         TypeRep,
         Box<TypedExpr<TypeRep, VarId>>,
@@ -766,6 +815,14 @@ where
         Box<TypedExpr<TypeRep, VarId>>,
         Box<TypedExpr<TypeRep, VarId>>,
     ),
+    /// Call of the function at the given index (with the given name) of the module
+    Call(
+        TypeRep,
+        usize,
+        Label,
+        TypeHint,
+        Vec<TypedExpr<TypeRep, VarId>>,
+    ),
 }
 
 impl<TypeRep> std::hash::Hash for TypedExpr<TypeRep> {
@@ -828,6 +885,8 @@ impl<TypeRep> std::hash::Hash for TypedExpr<TypeRep> {
             | TypedExpr::AsU32(inner)
             | TypedExpr::AsU64(inner)
             | TypedExpr::AsChar(inner)
+            | TypedExpr::F32FromBits(inner)
+            | TypedExpr::F64FromBits(inner)
             | TypedExpr::U16Be(inner)
             | TypedExpr::U16Le(inner)
             | TypedExpr::U32Be(inner)
@@ -835,6 +894,10 @@ impl<TypeRep> std::hash::Hash for TypedExpr<TypeRep> {
             | TypedExpr::U64Be(inner)
             | TypedExpr::U64Le(inner)
             | TypedExpr::SeqLength(inner) => inner.hash(state),
+            TypedExpr::FixedFromBits(fx, inner) => {
+                fx.hash(state);
+                inner.hash(state);
+            }
             TypedExpr::SeqIx(_, sq, ix) => {
                 sq.hash(state);
                 ix.hash(state);
@@ -844,10 +907,19 @@ impl<TypeRep> std::hash::Hash for TypedExpr<TypeRep> {
                 start.hash(state);
                 len.hash(state);
             }
-            TypedExpr::FlatMap(_, f, sq) => {
+            TypedExpr::FlatMap(_, f, sq)
+            | TypedExpr::Filter(_, f, sq)
+            | TypedExpr::Any(f, sq)
+            | TypedExpr::All(f, sq)
+            | TypedExpr::IsSortedBy(f, sq) => {
                 f.hash(state);
                 sq.hash(state);
             }
+            TypedExpr::Zip(_, lhs, rhs) => {
+                lhs.hash(state);
+                rhs.hash(state);
+            }
+            TypedExpr::Reverse(_, sq) => sq.hash(state),
             TypedExpr::FlatMapAccum(_, f, acc, _vt, seq) => {
                 f.hash(state);
                 acc.hash(state);
@@ -881,6 +953,10 @@ impl<TypeRep> std::hash::Hash for TypedExpr<TypeRep> {
                 lhs.hash(state);
                 rhs.hash(state);
             }
+            TypedExpr::Call(_, index, _, _, args) => {
+                index.hash(state);
+                args.hash(state);
+            }
         }
     }
 }
@@ -907,7 +983,10 @@ impl TypedExpr<GenType> {
         match self {
             TypedExpr::Lambda(..) => None,
 
-            TypedExpr::Bool(_) => Some(Cow::Owned(GenType::from(PrimType::Bool))),
+            TypedExpr::Bool(_)
+            | TypedExpr::Any(..)
+            | TypedExpr::All(..)
+            | TypedExpr::IsSortedBy(..) => Some(Cow::Owned(GenType::from(PrimType::Bool))),
             TypedExpr::AsU8(_) | TypedExpr::U8(_) => Some(Cow::Owned(GenType::from(PrimType::U8))),
             TypedExpr::U16Le(_) | TypedExpr::U16Be(_) | TypedExpr::AsU16(_) | TypedExpr::U16(_) => {
                 Some(Cow::Owned(GenType::from(PrimType::U16)))
@@ -922,6 +1001,11 @@ impl TypedExpr<GenType> {
                 Some(Cow::Owned(GenType::from(PrimType::U64)))
             }
             TypedExpr::AsChar(_) => Some(Cow::Owned(GenType::from(PrimType::Char))),
+            TypedExpr::F32FromBits(_) => Some(Cow::Owned(GenType::from(PrimType::F32))),
+            TypedExpr::F64FromBits(_) => Some(Cow::Owned(GenType::from(PrimType::F64))),
+            TypedExpr::FixedFromBits(fx, _) => {
+                Some(Cow::Owned(GenType::from(PrimType::Fixed(*fx))))
+            }
 
             TypedExpr::Numeric(gt, ..)
             | TypedExpr::Var(gt, ..)
@@ -940,10 +1024,14 @@ impl TypedExpr<GenType> {
             | TypedExpr::SubSeq(gt, ..)
             | TypedExpr::SubSeqInflate(gt, ..)
             | TypedExpr::Append(gt, ..)
+            | TypedExpr::Call(gt, ..)
             | TypedExpr::FlatMap(gt, ..)
             | TypedExpr::FlatMapAccum(gt, ..)
             | TypedExpr::LeftFold(gt, ..)
             | TypedExpr::FindByKey(gt, ..)
+            | TypedExpr::Filter(gt, ..)
+            | TypedExpr::Zip(gt, ..)
+            | TypedExpr::Reverse(gt, ..)
             | TypedExpr::FlatMapList(gt, ..)
             | TypedExpr::LiftOption(gt, ..)
             | TypedExpr::Dup(gt, ..)
@@ -970,6 +1058,19 @@ pub enum TypedPattern<TypeRep> {
     Variant(TypeRep, Label, Box<TypedPattern<TypeRep>>),
     Seq(TypeRep, Vec<TypedPattern<TypeRep>>),
     Option(TypeRep, Option<Box<TypedPattern<TypeRep>>>),
+    Record(TypeRep, Vec<(Label, TypedPattern<TypeRep>)>),
+    Or(TypeRep, Vec<TypedPattern<TypeRep>>),
+    Guard(Box<TypedPattern<TypeRep>>, Box<TypedExpr<TypeRep>>),
+}
+
+impl<TypeRep> TypedPattern<TypeRep> {
+    /// Splits the guard, if any, off the outermost layer of this pattern.
+    pub(crate) fn split_guard(&self) -> (&Self, Option<&TypedExpr<TypeRep>>) {
+        match self {
+            TypedPattern::Guard(pat, guard) => (pat, Some(guard)),
+            _ => (self, None),
+        }
+    }
 }
 
 impl TypedPattern<GenType> {
@@ -990,7 +1091,10 @@ impl TypedPattern<GenType> {
             | TypedPattern::ZConst(gt, ..)
             | TypedPattern::ZRange(gt, ..)
             | TypedPattern::Variant(gt, ..)
-            | TypedPattern::Seq(gt, ..) => Cow::Borrowed(gt),
+            | TypedPattern::Seq(gt, ..)
+            | TypedPattern::Record(gt, ..)
+            | TypedPattern::Or(gt, ..) => Cow::Borrowed(gt),
+            TypedPattern::Guard(inner, _) => inner.get_type(),
         }
     }
 }
@@ -1017,6 +1121,12 @@ impl<TypeRep> std::hash::Hash for TypedPattern<TypeRep> {
             }
             TypedPattern::Seq(_, elts) => elts.hash(state),
             TypedPattern::Option(_, opt) => opt.hash(state),
+            TypedPattern::Record(_, fields) => fields.hash(state),
+            TypedPattern::Or(_, alts) => alts.hash(state),
+            TypedPattern::Guard(inner, guard) => {
+                inner.hash(state);
+                guard.hash(state);
+            }
         }
     }
 }
@@ -1117,6 +1227,9 @@ mod __impls {
                 TypedExpr::AsU32(x) => Expr::AsU32(rebox(x)),
                 TypedExpr::AsU64(x) => Expr::AsU64(rebox(x)),
                 TypedExpr::AsChar(x) => Expr::AsChar(rebox(x)),
+                TypedExpr::F32FromBits(x) => Expr::F32FromBits(rebox(x)),
+                TypedExpr::F64FromBits(x) => Expr::F64FromBits(rebox(x)),
+                TypedExpr::FixedFromBits(fx, x) => Expr::FixedFromBits(fx, rebox(x)),
                 TypedExpr::U16Be(x) => Expr::U16Be(rebox(x)),
                 TypedExpr::U16Le(x) => Expr::U16Le(rebox(x)),
                 TypedExpr::U32Be(x) => Expr::U32Be(rebox(x)),
@@ -1141,11 +1254,18 @@ mod __impls {
                 TypedExpr::FindByKey(_, is_sorted, lambda, key, seq) => {
                     Expr::FindByKey(is_sorted, rebox(lambda), rebox(key), rebox(seq))
                 }
+                TypedExpr::Filter(_, lambda, seq) => Expr::Filter(rebox(lambda), rebox(seq)),
+                TypedExpr::Zip(_, lhs, rhs) => Expr::Zip(rebox(lhs), rebox(rhs)),
+                TypedExpr::Reverse(_, seq) => Expr::Reverse(rebox(seq)),
+                TypedExpr::Any(lambda, seq) => Expr::Any(rebox(lambda), rebox(seq)),
+                TypedExpr::All(lambda, seq) => Expr::All(rebox(lambda), rebox(seq)),
+                TypedExpr::IsSortedBy(lambda, seq) => Expr::IsSortedBy(rebox(lambda), rebox(seq)),
                 TypedExpr::FlatMapList(_, lambda, vt, seq) => {
                     Expr::FlatMapList(rebox(lambda), vt, rebox(seq))
                 }
                 TypedExpr::Dup(_, count, x) => Expr::Dup(rebox(count), rebox(x)),
                 TypedExpr::Append(_, lhs, rhs) => Expr::Append(rebox(lhs), rebox(rhs)),
+                TypedExpr::Call(_, index, _, ret, args) => Expr::Call(index, ret, revec(args)),
                 TypedExpr::EnumFromTo(_, start, stop) => {
                     Expr::EnumFromTo(rebox(start), rebox(stop))
                 }
@@ -1177,6 +1297,9 @@ mod __impls {
                 }
                 TypedFormat::SkipRemainder => Format::SkipRemainder,
                 TypedFormat::Pos(_) => Format::Pos,
+                TypedFormat::Native(_, index, _, args) => {
+                    Format::Native(index, args.into_iter().map(Expr::from).collect())
+                }
                 TypedFormat::Fail => Format::Fail,
                 TypedFormat::EndOfInput => Format::EndOfInput,
                 TypedFormat::Align(n) => Format::Align(n),
@@ -1251,6 +1374,7 @@ mod __impls {
                 #[cfg(feature = "format_enforce")]
                 TypedFormat::Enforce(_, inner) => Format::Enforce(rebox(inner)),
                 TypedFormat::Permit(_, inner, dft) => Format::Permit(rebox(inner), rebox(dft)),
+                TypedFormat::Resync(_, inner, pattern) => Format::Resync(rebox(inner), pattern),
             }
         }
     }
@@ -1294,6 +1418,9 @@ mod __impls {
                 TypedPattern::Seq(_, elts) => Pattern::Seq(revec(elts)),
                 TypedPattern::Option(_, Some(inner)) => Pattern::Option(Some(rebox(inner))),
                 TypedPattern::Option(_, None) => Pattern::Option(None),
+                TypedPattern::Record(_, fields) => Pattern::Record(revec_pair(fields)),
+                TypedPattern::Or(_, alts) => Pattern::Or(revec(alts)),
+                TypedPattern::Guard(inner, guard) => Pattern::Guard(rebox(inner), rebox(guard)),
             }
         }
     }
@@ -4,16 +4,19 @@ use std::rc::Rc;
 
 use anyhow::{Result as AResult, anyhow};
 
-use crate::byte_set::ByteSet;
-use crate::error::{DecodeErrorKind, DecodeResult, EDecodeResult};
+use crate::byte_set::{ByteSet, resync_offset};
+use crate::error::{DecodeErrorKind, DecodeResult, EDecodeResult, EvalError, EvalResult};
+use crate::generic;
+use crate::limits::{Budget, LimitExceeded, Limits};
+use crate::native::NativeDecodeFn;
 use crate::read::ReadCtxt;
 use crate::try_with;
 use crate::util::WithErr;
 use crate::util::{ErrTrace as _, downgrade_error_with};
 use crate::validation::Condition;
 use crate::{
-    BaseKind, DynFormat, Endian, Expr, Format, FormatModule, Label, MatchTree, MaybeTyped, Next,
-    Pattern, TypeHint, TypeScope, ValueType, ViewExpr, ViewFormat,
+    BaseKind, DynFormat, Endian, Expr, ExprFunction, Format, FormatModule, Label, MatchTree,
+    MaybeTyped, Next, Pattern, TypeHint, TypeScope, ValueType, ViewExpr, ViewFormat,
 };
 
 pub mod seq_kind;
@@ -63,237 +66,166 @@ pub mod value;
 pub use value::Value;
 
 impl Expr {
-    pub fn eval<'a>(&'a self, scope: &'a Scope<'a>) -> Cow<'a, Value> {
-        match self {
+    /// Evaluates `self` within `scope`.
+    ///
+    /// Returns an error if the evaluation fails due to the values involved (e.g. on integer overflow), and panics if
+    /// it fails due to the expression being ill-typed.
+    pub fn eval<'a>(&'a self, scope: &'a Scope<'a>) -> EvalResult<Cow<'a, Value>> {
+        let value = match self {
             Expr::Var(name) => Cow::Borrowed(scope.get_value_by_name(name).unwrap()),
             Expr::Bool(b) => Cow::Owned(Value::Bool(*b)),
             Expr::U8(i) => Cow::Owned(Value::U8(*i)),
             Expr::U16(i) => Cow::Owned(Value::U16(*i)),
             Expr::U32(i) => Cow::Owned(Value::U32(*i)),
             Expr::U64(i) => Cow::Owned(Value::U64(*i)),
-            Expr::Numeric(n) => {
-                match n.eval(scope) {
-                    Ok(v) => Cow::Owned(v.into()),
-                    // REVIEW[epic=embedded-num] - we probably want a more sensible outcome than panic
-                    Err(e) => panic!("{e}"),
-                }
-            }
+            Expr::Numeric(n) => match n.eval(scope) {
+                Ok(v) => Cow::Owned(v.into()),
+                Err(e) => return Err(EvalError::Numeric(e.to_string())),
+            },
             Expr::Tuple(exprs) => Cow::Owned(Value::Tuple(
-                exprs.iter().map(|expr| expr.eval_value(scope)).collect(),
+                exprs
+                    .iter()
+                    .map(|expr| expr.eval_value(scope))
+                    .collect::<EvalResult<_>>()?,
             )),
-            Expr::TupleProj(head, index) => cow_map(head.eval(scope), |v| {
+            Expr::TupleProj(head, index) => cow_map(head.eval(scope)?, |v| {
                 v.coerce_mapped_value().tuple_proj(*index)
             }),
-            Expr::Record(fields) => {
-                Cow::Owned(Value::record(fields.iter().map(|(label, expr)| {
-                    (label.clone(), expr.eval(scope).into_owned())
-                })))
-            }
-            Expr::RecordProj(head, label) => cow_map(head.eval(scope), |v| {
+            Expr::Record(fields) => Cow::Owned(Value::record(
+                fields
+                    .iter()
+                    .map(|(label, expr)| Ok((label.clone(), expr.eval(scope)?.into_owned())))
+                    .collect::<EvalResult<Vec<_>>>()?,
+            )),
+            Expr::RecordProj(head, label) => cow_map(head.eval(scope)?, |v| {
                 v.coerce_mapped_value().record_proj(label.as_ref())
             }),
             Expr::Variant(label, expr) => {
-                Cow::Owned(Value::variant(label.clone(), expr.eval_value(scope)))
+                Cow::Owned(Value::variant(label.clone(), expr.eval_value(scope)?))
             }
             Expr::Seq(exprs) => Cow::Owned(Value::Seq(
-                exprs.iter().map(|expr| expr.eval_value(scope)).collect(),
+                exprs
+                    .iter()
+                    .map(|expr| expr.eval_value(scope))
+                    .collect::<EvalResult<SeqKind<_>>>()?,
             )),
             Expr::Match(head, branches) => {
-                let head = head.eval(scope);
+                let head = head.eval(scope)?;
                 for (pattern, expr) in branches {
-                    if let Some(pattern_scope) = head.matches(scope, pattern) {
-                        let value = expr.eval_value(&Scope::Multi(&pattern_scope));
-                        return Cow::Owned(value);
+                    if let Some(pattern_scope) = head.matches(scope, pattern)? {
+                        let value = expr.eval_value(&Scope::Multi(&pattern_scope))?;
+                        return Ok(Cow::Owned(value));
                     }
                 }
-                panic!("non-exhaustive patterns");
+                return Err(EvalError::NoMatch);
             }
             Expr::Destructure(head, pat, expr) => {
-                let head = head.eval(scope);
-                if let Some(pattern_scope) = head.matches(scope, pat) {
-                    let value = expr.eval_value(&Scope::Multi(&pattern_scope));
-                    Cow::Owned(value)
-                } else {
-                    panic!("refutable pattern failed to match: {pat:?} :~ {head:?}");
+                let head = head.eval(scope)?;
+                match head.matches(scope, pat)? {
+                    Some(pattern_scope) => {
+                        Cow::Owned(expr.eval_value(&Scope::Multi(&pattern_scope))?)
+                    }
+                    None => return Err(EvalError::NoMatch),
                 }
             }
             Expr::Lambda(_, _) => panic!("cannot eval lambda"),
 
             Expr::IntRel(rel, x, y) => Cow::Owned({
-                let left = x.eval_value(scope);
-                let right = y.eval_value(scope);
+                let left = x.eval_value(scope)?;
+                let right = y.eval_value(scope)?;
                 Value::int_rel(*rel, left, right)
             }),
             Expr::Arith(op, x, y) => Cow::Owned({
-                let left = x.eval_value(scope);
-                let right = y.eval_value(scope);
-                Value::arith(*op, left, right)
+                let left = x.eval_value(scope)?;
+                let right = y.eval_value(scope)?;
+                Value::arith(*op, left, right)?
             }),
             Expr::Unary(op, x) => Cow::Owned({
-                let value = x.eval_value(scope);
-                Value::unary(*op, value)
-            }),
-
-            // FIXME - extract common logic for As-expr on Value instead of separate impl for decoder and loc_decoder
-            Expr::AsU8(x) => {
-                Cow::Owned(match x.eval_value(scope) {
-                    Value::U8(x) => Value::U8(x),
-                    Value::U16(x) => Value::U8(u8::try_from(x).unwrap_or_else(|err| {
-                        panic!("cannot perform AsU8 cast on u16 {x}: {err}")
-                    })),
-                    Value::U32(x) => Value::U8(u8::try_from(x).unwrap_or_else(|err| {
-                        panic!("cannot perform AsU8 cast on u32 {x}: {err}")
-                    })),
-                    Value::U64(x) => Value::U8(u8::try_from(x).unwrap_or_else(|err| {
-                        panic!("cannot perform AsU8 cast on u64 {x}: {err}")
-                    })),
-                    Value::Usize(x) => Value::U8(u8::try_from(x).unwrap_or_else(|err| {
-                        panic!("cannot perform AsU8 cast on usize {x}: {err}")
-                    })),
-                    x => panic!("cannot convert {x:?} to U8"),
-                })
-            }
-            Expr::AsU16(x) => Cow::Owned(match x.eval_value(scope) {
-                Value::U8(x) => Value::U16(u16::from(x)),
-                Value::U16(x) => Value::U16(x),
-                Value::U32(x) => Value::U16(u16::try_from(x).unwrap()),
-                Value::U64(x) => Value::U16(u16::try_from(x).unwrap()),
-                Value::Usize(x) => Value::U16(u16::try_from(x).unwrap()),
-                x => panic!("cannot convert {x:?} to U16"),
-            }),
-            Expr::AsU32(x) => Cow::Owned(match x.eval_value(scope) {
-                Value::U8(x) => Value::U32(u32::from(x)),
-                Value::U16(x) => Value::U32(u32::from(x)),
-                Value::U32(x) => Value::U32(x),
-                Value::U64(x) => Value::U32(u32::try_from(x).unwrap()),
-                Value::Usize(x) => Value::U32(u32::try_from(x).unwrap()),
-                x => panic!("cannot convert {x:?} to U32"),
-            }),
-            Expr::AsU64(x) => Cow::Owned(match x.eval_value(scope) {
-                Value::U8(x) => Value::U64(u64::from(x)),
-                Value::U16(x) => Value::U64(u64::from(x)),
-                Value::U32(x) => Value::U64(u64::from(x)),
-                Value::U64(x) => Value::U64(x),
-                Value::Usize(x) => Value::U64(u64::try_from(x).unwrap()),
-                x => panic!("cannot convert {x:?} to U64"),
+                let value = x.eval_value(scope)?;
+                Value::unary(*op, value)?
             }),
 
-            Expr::U16Be(bytes) => match bytes.eval_value(scope).unwrap_tuple().as_slice() {
-                [Value::U8(hi), Value::U8(lo)] => {
-                    Cow::Owned(Value::U16(u16::from_be_bytes([*hi, *lo])))
-                }
-                _ => panic!("U16Be: expected (U8, U8)"),
-            },
-            Expr::U16Le(bytes) => match bytes.eval_value(scope).unwrap_tuple().as_slice() {
-                [Value::U8(lo), Value::U8(hi)] => {
-                    Cow::Owned(Value::U16(u16::from_le_bytes([*lo, *hi])))
-                }
-                _ => panic!("U16Le: expected (U8, U8)"),
-            },
-            Expr::U32Be(bytes) => match bytes.eval_value(scope).unwrap_tuple().as_slice() {
-                [Value::U8(a), Value::U8(b), Value::U8(c), Value::U8(d)] => {
-                    Cow::Owned(Value::U32(u32::from_be_bytes([*a, *b, *c, *d])))
-                }
-                _ => panic!("U32Be: expected (U8, U8, U8, U8)"),
-            },
-            Expr::U32Le(bytes) => match bytes.eval_value(scope).unwrap_tuple().as_slice() {
-                [Value::U8(a), Value::U8(b), Value::U8(c), Value::U8(d)] => {
-                    Cow::Owned(Value::U32(u32::from_le_bytes([*a, *b, *c, *d])))
-                }
-                _ => panic!("U32Le: expected (U8, U8, U8, U8)"),
-            },
-            Expr::U64Be(bytes) => match bytes.eval_value(scope).unwrap_tuple().as_slice() {
-                [
-                    Value::U8(a),
-                    Value::U8(b),
-                    Value::U8(c),
-                    Value::U8(d),
-                    Value::U8(e),
-                    Value::U8(f),
-                    Value::U8(g),
-                    Value::U8(h),
-                ] => Cow::Owned(Value::U64(u64::from_be_bytes([
-                    *a, *b, *c, *d, *e, *f, *g, *h,
-                ]))),
-                _ => panic!("U32Be: expected (U8, U8, U8, U8, U8, U8, U8, U8)"),
-            },
-            Expr::U64Le(bytes) => match bytes.eval_value(scope).unwrap_tuple().as_slice() {
-                [
-                    Value::U8(a),
-                    Value::U8(b),
-                    Value::U8(c),
-                    Value::U8(d),
-                    Value::U8(e),
-                    Value::U8(f),
-                    Value::U8(g),
-                    Value::U8(h),
-                ] => Cow::Owned(Value::U64(u64::from_le_bytes([
-                    *a, *b, *c, *d, *e, *f, *g, *h,
-                ]))),
-                _ => panic!("U32Le: expected (U8, U8, U8, U8, U8, U8, U8, U8)"),
-            },
-            Expr::AsChar(bytes) => Cow::Owned(match bytes.eval_value(scope) {
-                Value::U8(x) => Value::Char(char::from(x)),
-                Value::U16(x) => {
-                    Value::Char(char::from_u32(x as u32).unwrap_or(char::REPLACEMENT_CHARACTER))
-                }
-                Value::U32(x) => {
-                    Value::Char(char::from_u32(x).unwrap_or(char::REPLACEMENT_CHARACTER))
-                }
-                Value::U64(x) => Value::Char(
-                    char::from_u32(u32::try_from(x).unwrap())
-                        .unwrap_or(char::REPLACEMENT_CHARACTER),
-                ),
-                Value::Usize(x) => Value::Char(
-                    char::from_u32(u32::try_from(x).unwrap())
-                        .unwrap_or(char::REPLACEMENT_CHARACTER),
-                ),
-                _ => panic!("AsChar: expected U8, U16, U32, or U64"),
-            }),
-            Expr::SeqLength(seq) => match seq.eval(scope).coerce_mapped_value().get_sequence() {
+            // FIXME - loc_decoder still has its own copy of the As-expr logic held by Value
+            Expr::AsU8(x) => Cow::Owned(x.eval_value(scope)?.cast_u8()),
+            Expr::AsU16(x) => Cow::Owned(x.eval_value(scope)?.cast_u16()),
+            Expr::AsU32(x) => Cow::Owned(x.eval_value(scope)?.cast_u32()),
+            Expr::AsU64(x) => Cow::Owned(x.eval_value(scope)?.cast_u64()),
+
+            Expr::U16Be(bytes) => Cow::Owned(bytes.eval_value(scope)?.u16_be()),
+            Expr::U16Le(bytes) => Cow::Owned(bytes.eval_value(scope)?.u16_le()),
+            Expr::U32Be(bytes) => Cow::Owned(bytes.eval_value(scope)?.u32_be()),
+            Expr::U32Le(bytes) => Cow::Owned(bytes.eval_value(scope)?.u32_le()),
+            Expr::U64Be(bytes) => Cow::Owned(bytes.eval_value(scope)?.u64_be()),
+            Expr::U64Le(bytes) => Cow::Owned(bytes.eval_value(scope)?.u64_le()),
+            Expr::AsChar(bytes) => Cow::Owned(bytes.eval_value(scope)?.cast_char()),
+            Expr::F32FromBits(bits) | Expr::F64FromBits(bits) => {
+                Cow::Owned(Value::float_from_bits(bits.eval_value(scope)?))
+            }
+            Expr::FixedFromBits(fx, bits) => {
+                Cow::Owned(Value::fixed_from_bits(*fx, bits.eval_value(scope)?))
+            }
+            Expr::SeqLength(seq) => match seq.eval(scope)?.coerce_mapped_value().get_sequence() {
                 Some(values) => {
                     let len = values.len();
                     Cow::Owned(Value::U32(len as u32))
                 }
                 _ => panic!("SeqLength: expected Seq"),
             },
-            Expr::SeqIx(seq, index) => cow_remap(seq.eval(scope), |v| {
-                match v.coerce_mapped_value().get_sequence() {
-                    Some(values) => match values {
-                        ValueSeq::ValueSeq(values) => {
-                            let index = index.eval_value(scope).unwrap_usize();
-                            Cow::Borrowed(&values[index])
-                        }
-                        ValueSeq::IntRange(mut range) => {
-                            let index = index.eval_value(scope).unwrap_usize();
-                            Cow::Owned(Value::from(range.nth(index).unwrap()))
-                        }
-                    },
+            Expr::SeqIx(seq, index) => {
+                let index = index.eval_value(scope)?.unwrap_usize();
+                let seq = seq.eval(scope)?;
+                let len = match seq.coerce_mapped_value().get_sequence() {
+                    Some(values) => values.len(),
                     _ => panic!("SeqIx: expected Seq (or RangeFromTo)"),
+                };
+                if index >= len {
+                    return Err(EvalError::OutOfBounds { index, len });
                 }
-            }),
+                cow_remap(seq, |v| match v.coerce_mapped_value().get_sequence() {
+                    Some(ValueSeq::ValueSeq(values)) => Cow::Borrowed(&values[index]),
+                    Some(ValueSeq::IntRange(mut range)) => {
+                        Cow::Owned(Value::from(range.nth(index).unwrap()))
+                    }
+                    _ => unreachable!(),
+                })
+            }
             Expr::SubSeq(seq, start, length) => {
-                match seq.eval(scope).coerce_mapped_value().get_sequence() {
-                    Some(values) => match values {
-                        ValueSeq::ValueSeq(values) => {
-                            let start = start.eval_value(scope).unwrap_usize();
-                            let length = length.eval_value(scope).unwrap_usize();
-                            Cow::Owned(Value::Seq(values.sub_seq(start, length)))
+                match seq.eval(scope)?.coerce_mapped_value().get_sequence() {
+                    Some(values) => {
+                        let start = start.eval_value(scope)?.unwrap_usize();
+                        let length = length.eval_value(scope)?.unwrap_usize();
+                        let len = values.len();
+                        match start.checked_add(length) {
+                            Some(end) if end <= len => {}
+                            _ => {
+                                return Err(EvalError::OutOfBounds {
+                                    index: start.saturating_add(length),
+                                    len,
+                                });
+                            }
                         }
-                        ValueSeq::IntRange(range) => {
-                            let start = start.eval_value(scope).unwrap_usize();
-                            let length = length.eval_value(scope).unwrap_usize();
-                            Cow::Owned(Value::EnumFromTo(sub_range(range, start, length)))
+                        match values {
+                            ValueSeq::ValueSeq(values) => {
+                                Cow::Owned(Value::Seq(values.sub_seq(start, length)))
+                            }
+                            ValueSeq::IntRange(range) => {
+                                Cow::Owned(Value::EnumFromTo(sub_range(range, start, length)))
+                            }
                         }
-                    },
+                    }
                     _ => panic!("SubSeq: expected Seq"),
                 }
             }
             Expr::SubSeqInflate(seq, start, length) => {
-                match seq.eval(scope).coerce_mapped_value().get_sequence() {
+                match seq.eval(scope)?.coerce_mapped_value().get_sequence() {
                     Some(values) => {
-                        let start = start.eval_value(scope).unwrap_usize();
-                        let length = length.eval_value(scope).unwrap_usize();
+                        let start = start.eval_value(scope)?.unwrap_usize();
+                        let length = length.eval_value(scope)?.unwrap_usize();
+                        let len = values.len();
+                        // NOTE - elements past the end of `seq` repeat those already copied, so the copy must begin within it
+                        if length > 0 && start >= len {
+                            return Err(EvalError::OutOfBounds { index: start, len });
+                        }
                         let mut vs = Vec::new();
                         match values {
                             ValueSeq::ValueSeq(vs0) => {
@@ -324,27 +256,42 @@ impl Expr {
                 }
             }
             Expr::Append(seq0, seq1) => {
-                match seq0.eval(scope).coerce_mapped_value().get_sequence() {
-                    Some(val_seq0) => match seq1.eval(scope).coerce_mapped_value().get_sequence() {
-                        Some(val_seq1) => {
-                            if val_seq0.is_empty() {
-                                return Cow::Owned(seq1.eval(scope).coerce_mapped_value().clone());
-                            } else if val_seq1.is_empty() {
-                                return Cow::Owned(seq0.eval(scope).coerce_mapped_value().clone());
+                match seq0.eval(scope)?.coerce_mapped_value().get_sequence() {
+                    Some(val_seq0) => {
+                        match seq1.eval(scope)?.coerce_mapped_value().get_sequence() {
+                            Some(val_seq1) => {
+                                if val_seq0.is_empty() {
+                                    return Ok(Cow::Owned(
+                                        seq1.eval(scope)?.coerce_mapped_value().clone(),
+                                    ));
+                                } else if val_seq1.is_empty() {
+                                    return Ok(Cow::Owned(
+                                        seq0.eval(scope)?.coerce_mapped_value().clone(),
+                                    ));
+                                }
+                                Cow::Owned(Value::Seq(val_seq0.append(val_seq1)))
                             }
-                            Cow::Owned(Value::Seq(val_seq0.append(val_seq1)))
+                            _ => unreachable!("Append: expected Seq in (lhs)"),
                         }
-                        _ => unreachable!("Append: expected Seq in (lhs)"),
-                    },
+                    }
                     _ => unreachable!("Append: expected Seq in (lhs)"),
                 }
             }
+            Expr::Call(index, _, args) => {
+                let root = scope.root();
+                let func = root.get_function(*index);
+                let mut new_scope = MultiScope::with_capacity(root, args.len());
+                for ((name, _), arg) in func.params.iter().zip(args) {
+                    new_scope.push_owned(name.clone(), arg.eval_value(scope)?);
+                }
+                Cow::Owned(func.body().eval_value(&Scope::Multi(&new_scope))?)
+            }
             Expr::FlatMap(expr, seq) => {
-                match seq.eval(scope).coerce_mapped_value().get_sequence() {
+                match seq.eval(scope)?.coerce_mapped_value().get_sequence() {
                     Some(values) => {
                         let mut vs = Vec::new();
                         for v in values {
-                            match expr.eval_lambda(scope, &v) {
+                            match expr.eval_lambda(scope, &v)? {
                                 Value::Seq(vn) => {
                                     vs.extend(vn);
                                 }
@@ -361,12 +308,12 @@ impl Expr {
                     _ => panic!("FlatMap: expected Seq"),
                 }
             }
-            Expr::FlatMapAccum(expr, accum, _accum_type, seq) => match seq.eval_value(scope) {
+            Expr::FlatMapAccum(expr, accum, _accum_type, seq) => match seq.eval_value(scope)? {
                 Value::Seq(values) => {
-                    let mut accum = accum.eval_value(scope);
+                    let mut accum = accum.eval_value(scope)?;
                     let mut vs = Vec::new();
                     for v in values {
-                        let ret = expr.eval_lambda(scope, &Value::Tuple(vec![accum, v]));
+                        let ret = expr.eval_lambda(scope, &Value::Tuple(vec![accum, v]))?;
                         accum = match extract_pair(ret.unwrap_tuple()) {
                             (accum, Value::Seq(vn)) => {
                                 vs.extend(vn);
@@ -379,45 +326,121 @@ impl Expr {
                 }
                 _ => panic!("FlatMapAccum: expected Seq"),
             },
-            Expr::LeftFold(expr, accum, _accum_type, seq) => match seq.eval_value(scope) {
+            Expr::LeftFold(expr, accum, _accum_type, seq) => match seq.eval_value(scope)? {
                 Value::Seq(values) => {
-                    let mut accum = accum.eval_value(scope);
+                    let mut accum = accum.eval_value(scope)?;
                     for v in values {
-                        let tmp = expr.eval_lambda(scope, &Value::Tuple(vec![accum, v]));
+                        let tmp = expr.eval_lambda(scope, &Value::Tuple(vec![accum, v]))?;
                         accum = tmp
                     }
                     Cow::Owned(accum)
                 }
                 _ => panic!("LeftFold: expected Seq"),
             },
-            Expr::FindByKey(is_sorted, f_get_key, query_key, seq) => match seq.eval_value(scope) {
+            Expr::FindByKey(is_sorted, f_get_key, query_key, seq) => match seq.eval_value(scope)? {
                 Value::Seq(values) => {
-                    let query = query_key.eval_value(scope);
-                    let eval = |lambda: &Expr, arg: &Value| lambda.eval_lambda(scope, arg);
-                    if *is_sorted {
-                        match search::find_index_by_key_sorted(f_get_key, &query, &values, eval) {
-                            Some(ix) => {
-                                Cow::Owned(Value::Option(Some(Box::new(values[ix].clone()))))
-                            }
-                            None => Cow::Owned(Value::Option(None)),
-                        }
+                    let query = query_key.eval_value(scope)?;
+                    let get_key = |arg: &Value| f_get_key.eval_lambda(scope, arg);
+                    let found = if *is_sorted {
+                        search::find_index_by_key_sorted(&query, &values, get_key)?
                     } else {
-                        match search::find_index_by_key_unsorted(f_get_key, &query, &values, eval) {
-                            Some(ix) => {
-                                Cow::Owned(Value::Option(Some(Box::new(values[ix].clone()))))
+                        search::find_index_by_key_unsorted(&query, &values, get_key)?
+                    };
+                    match found {
+                        Some(ix) => Cow::Owned(Value::Option(Some(Box::new(values[ix].clone())))),
+                        None => Cow::Owned(Value::Option(None)),
+                    }
+                }
+                _ => panic!("FindByKey: expected Seq"),
+            },
+            Expr::Filter(expr, seq) => {
+                match seq.eval(scope)?.coerce_mapped_value().get_sequence() {
+                    Some(values) => {
+                        let mut vs = Vec::new();
+                        for v in values {
+                            if expr.eval_lambda(scope, &v)?.unwrap_bool() {
+                                vs.push(v.into_owned());
                             }
-                            None => Cow::Owned(Value::Option(None)),
                         }
+                        Cow::Owned(Value::Seq(vs.into()))
                     }
+                    _ => panic!("Filter: expected Seq"),
                 }
-                _ => panic!("FindByKey: expected Seq"),
+            }
+            Expr::Zip(seq0, seq1) => match (
+                seq0.eval(scope)?.coerce_mapped_value().get_sequence(),
+                seq1.eval(scope)?.coerce_mapped_value().get_sequence(),
+            ) {
+                (Some(values0), Some(values1)) => {
+                    let vs: Vec<Value> = values0
+                        .into_iter()
+                        .zip(values1)
+                        .map(|(v0, v1)| Value::Tuple(vec![v0.into_owned(), v1.into_owned()]))
+                        .collect();
+                    Cow::Owned(Value::Seq(vs.into()))
+                }
+                _ => panic!("Zip: expected Seq"),
+            },
+            Expr::Reverse(seq) => match seq.eval(scope)?.coerce_mapped_value().get_sequence() {
+                Some(values) => {
+                    let mut vs: Vec<Value> = values.into_iter().map(Cow::into_owned).collect();
+                    vs.reverse();
+                    Cow::Owned(Value::Seq(vs.into()))
+                }
+                _ => panic!("Reverse: expected Seq"),
             },
-            Expr::FlatMapList(expr, _ret_type, seq) => match seq.eval_value(scope) {
+            Expr::Any(expr, seq) => match seq.eval(scope)?.coerce_mapped_value().get_sequence() {
+                Some(values) => {
+                    let mut any = false;
+                    for v in values {
+                        if expr.eval_lambda(scope, &v)?.unwrap_bool() {
+                            any = true;
+                            break;
+                        }
+                    }
+                    Cow::Owned(Value::Bool(any))
+                }
+                _ => panic!("Any: expected Seq"),
+            },
+            Expr::All(expr, seq) => match seq.eval(scope)?.coerce_mapped_value().get_sequence() {
+                Some(values) => {
+                    let mut all = true;
+                    for v in values {
+                        if !expr.eval_lambda(scope, &v)?.unwrap_bool() {
+                            all = false;
+                            break;
+                        }
+                    }
+                    Cow::Owned(Value::Bool(all))
+                }
+                _ => panic!("All: expected Seq"),
+            },
+            Expr::IsSortedBy(expr, seq) => {
+                match seq.eval(scope)?.coerce_mapped_value().get_sequence() {
+                    Some(values) => {
+                        let vs: Vec<Cow<'_, Value>> = values.into_iter().collect();
+                        let mut is_sorted = true;
+                        for pair in vs.windows(2) {
+                            let arg = Value::Tuple(vec![
+                                pair[0].clone().into_owned(),
+                                pair[1].clone().into_owned(),
+                            ]);
+                            if !expr.eval_lambda(scope, &arg)?.unwrap_bool() {
+                                is_sorted = false;
+                                break;
+                            }
+                        }
+                        Cow::Owned(Value::Bool(is_sorted))
+                    }
+                    _ => panic!("IsSortedBy: expected Seq"),
+                }
+            }
+            Expr::FlatMapList(expr, _ret_type, seq) => match seq.eval_value(scope)? {
                 Value::Seq(values) => {
                     let mut vs = Vec::new();
                     for v in values {
                         let arg = Value::Tuple(vec![Value::Seq(SeqKind::Strict(vs)), v]);
-                        if let Value::Seq(vn) = expr.eval_lambda(scope, &arg) {
+                        if let Value::Seq(vn) = expr.eval_lambda(scope, &arg)? {
                             vs = match arg {
                                 Value::Tuple(mut args) => match args.remove(0) {
                                     Value::Seq(vs) => vs.into_vec(),
@@ -435,34 +458,35 @@ impl Expr {
                 _ => panic!("FlatMapList: expected Seq"),
             },
             Expr::Dup(count, expr) => {
-                let count = count.eval_value(scope).unwrap_usize();
-                let v = expr.eval_value(scope);
+                let count = count.eval_value(scope)?.unwrap_usize();
+                let v = expr.eval_value(scope)?;
                 Cow::Owned(Value::Seq(SeqKind::Dup(count, Box::new(v))))
             }
             Expr::EnumFromTo(start, stop) => {
-                let start = start.eval_value(scope).unwrap_usize();
-                let stop = stop.eval_value(scope).unwrap_usize();
+                let start = start.eval_value(scope)?.unwrap_usize();
+                let stop = stop.eval_value(scope)?.unwrap_usize();
                 Cow::Owned(Value::EnumFromTo(start..stop))
             }
             Expr::LiftOption(opt) => match opt {
-                Some(expr) => Cow::Owned(Value::Option(Some(Box::new(expr.eval_value(scope))))),
+                Some(expr) => Cow::Owned(Value::Option(Some(Box::new(expr.eval_value(scope)?)))),
                 None => Cow::Owned(Value::Option(None)),
             },
-        }
+        };
+        Ok(value)
     }
 
-    fn eval_value_ref<'a, 'b: 'a>(&'b self, scope: &'a Scope<'a>) -> Cow<'a, Value> {
-        match self.eval(scope) {
+    fn eval_value_ref<'a, 'b: 'a>(&'b self, scope: &'a Scope<'a>) -> EvalResult<Cow<'a, Value>> {
+        Ok(match self.eval(scope)? {
             Cow::Borrowed(value) => Cow::Borrowed(value.coerce_mapped_value()),
             Cow::Owned(v) => Cow::Owned(v.extract_mapped_value()),
-        }
+        })
     }
 
-    pub fn eval_value<'a>(&self, scope: &'a Scope<'a>) -> Value {
-        self.eval_value_ref(scope).into_owned()
+    pub fn eval_value<'a>(&self, scope: &'a Scope<'a>) -> EvalResult<Value> {
+        Ok(self.eval_value_ref(scope)?.into_owned())
     }
 
-    fn eval_lambda<'a>(&self, scope: &'a Scope<'a>, arg: &Value) -> Value {
+    pub(crate) fn eval_lambda<'a>(&self, scope: &'a Scope<'a>, arg: &Value) -> EvalResult<Value> {
         match self {
             Expr::Lambda(name, expr) => {
                 let child_scope = SingleScope::new(scope, name, arg);
@@ -473,7 +497,12 @@ impl Expr {
     }
 }
 
+pub mod memo;
 pub(crate) mod search;
+pub mod stream;
+pub mod vm;
+
+use memo::{Memo, MemoStats};
 
 /// Decoders with a fixed amount of lookahead
 #[derive(Clone, Debug)]
@@ -511,6 +540,7 @@ pub enum Decoder {
     RepeatBetween(MatchTree, Box<Expr>, Box<Expr>, Box<Decoder>),
     ForEach(Box<Expr>, Label, Box<Decoder>),
     SkipRemainder,
+    Native(NativeDecodeFn, Vec<Expr>),
     DecodeBytes(Box<Expr>, Box<Decoder>),
     ParseFromView(ViewExpr, Box<Decoder>),
     LetFormat(Box<Decoder>, Label, Box<Decoder>),
@@ -525,27 +555,167 @@ pub enum Decoder {
     #[cfg(feature = "format_enforce")]
     Enforce(Box<Decoder>),
     Permit(Box<Decoder>, Box<Expr>),
+    Resync(Box<Decoder>, Vec<ByteSet>),
 }
 
 #[derive(Clone, Debug)]
 pub struct Program {
     pub decoders: Vec<(Decoder, ValueType)>,
+    /// Name of the format each decoder was compiled from (if any), for reporting where a decode error occurred
+    pub format_names: Vec<Option<Label>>,
+    /// Functions of the module, callable from the expressions of every decoder
+    pub functions: Vec<ExprFunction>,
+    /// Limits each run is subject to
+    pub(crate) limits: Limits,
+    /// Whether each run memoizes the outcomes of the format calls it makes
+    pub(crate) memoize: bool,
 }
 
 impl Program {
     pub(crate) fn new() -> Self {
         let decoders = Vec::new();
-        Program { decoders }
+        let format_names = Vec::new();
+        let functions = Vec::new();
+        let limits = Limits::default();
+        let memoize = false;
+        Program {
+            decoders,
+            format_names,
+            functions,
+            limits,
+            memoize,
+        }
+    }
+
+    /// Returns `self` with each subsequent [`Program::run`] subject to `limits`.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.set_limits(limits);
+        self
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    /// Returns `self` with each subsequent run memoizing the outcome of every format call, so that
+    /// repeated attempts of the same call at the same position (as under `Format::UnionNondet`) are only
+    /// decoded once.
+    pub fn with_memoization(mut self, enabled: bool) -> Self {
+        self.set_memoization(enabled);
+        self
+    }
+
+    pub fn set_memoization(&mut self, enabled: bool) {
+        self.memoize = enabled;
     }
 
     pub fn run<'input>(&self, input: ReadCtxt<'input>) -> DecodeResult<(Value, ReadCtxt<'input>)> {
-        Ok(self.decoders[0]
+        self.run_decoder(&self.decoders[0].0, input)
+    }
+
+    /// Runs `d`, one of the decoders of `self`, over `input` as though it were the top-level decoder.
+    pub(crate) fn run_decoder<'input>(
+        &self,
+        d: &Decoder,
+        input: ReadCtxt<'input>,
+    ) -> DecodeResult<(Value, ReadCtxt<'input>)> {
+        let run = RunState::new(self);
+        Ok(
+            d.parse(self, &run, &Scope::Functions(&self.functions), input)?
+                .extract_warn(),
+        )
+    }
+
+    /// Runs `self` over `input` as [`Program::run`] does, also returning the memoization statistics of the run.
+    pub fn run_with_stats<'input>(
+        &self,
+        input: ReadCtxt<'input>,
+    ) -> (DecodeResult<(Value, ReadCtxt<'input>)>, MemoStats) {
+        let run = RunState::new(self);
+        let res = self.decoders[0]
             .0
-            .parse(self, &Scope::Empty, input)?
-            .extract_warn())
+            .parse(self, &run, &Scope::Functions(&self.functions), input)
+            .map(WithErr::extract_warn);
+        (res, run.memo.stats())
+    }
+}
+
+/// State of a single run of a [`Program`]: the resources it has consumed so far, and the outcomes of the
+/// format calls it has made (if memoization is enabled).
+///
+/// A fresh `RunState` is made for every run, so none of it is shared between runs, or between threads.
+#[derive(Debug)]
+pub struct RunState {
+    pub(crate) budget: Budget,
+    pub(crate) memo: Memo,
+}
+
+impl RunState {
+    pub fn new(program: &Program) -> RunState {
+        RunState {
+            budget: Budget::new(program.limits),
+            memo: Memo::new(program.memoize),
+        }
     }
 }
 
+/// Charges `n` sequence elements, about to be accumulated at `input`, against the budget of the current run.
+fn tally_elements(budget: &Budget, n: usize, input: ReadCtxt<'_>) -> DecodeResult<()> {
+    within_budget(budget.tally_elements(n), input)
+}
+
+/// Elements accumulated by a repetition, kept as the bytes themselves when every element is decoded by
+/// [`Decoder::Byte`], rather than as a `Vec<Value>` that is only compacted once complete.
+pub(crate) enum Accum {
+    Values(Vec<Value>),
+    Bytes(Vec<u8>),
+}
+
+impl Accum {
+    fn new(elem: &Decoder, capacity: usize) -> Self {
+        Self::with_capacity(matches!(elem, Decoder::Byte(_)), capacity)
+    }
+
+    pub(crate) fn with_capacity(bytes: bool, capacity: usize) -> Self {
+        if bytes {
+            Accum::Bytes(Vec::with_capacity(capacity))
+        } else {
+            Accum::Values(Vec::with_capacity(capacity))
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Accum::Values(vs) => vs.len(),
+            Accum::Bytes(bs) => bs.len(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, v: Value) {
+        match (self, v) {
+            (Accum::Values(vs), v) => vs.push(v),
+            (Accum::Bytes(bs), Value::U8(b)) => bs.push(b),
+            (Accum::Bytes(_), other) => unreachable!("Decoder::Byte yielded non-byte {other:?}"),
+        }
+    }
+
+    pub(crate) fn into_value(self) -> Value {
+        match self {
+            Accum::Values(vs) => Value::Seq(vs.into()),
+            Accum::Bytes(bs) if bs.is_empty() => Value::Seq(SeqKind::Strict(Vec::new())),
+            Accum::Bytes(bs) => Value::Seq(SeqKind::Bytes(bs.into())),
+        }
+    }
+}
+
+fn within_budget(res: Result<(), LimitExceeded>, input: ReadCtxt<'_>) -> DecodeResult<()> {
+    res.map_err(|exceeded| DecodeErrorKind::limit_exceeded(exceeded, input.offset).into())
+}
+
 pub struct Compiler<'a> {
     module: &'a FormatModule,
     program: Program,
@@ -567,12 +737,21 @@ impl<'a> Compiler<'a> {
     }
 
     pub fn compile_program(module: &FormatModule, format: &Format) -> AResult<Program> {
+        let (module, format) = generic::monomorphize(module, format);
+        let (module, format) = (module.as_ref(), format.as_ref());
         let mut compiler = Compiler::new(module);
+        if let Some(func) = module.functions().iter().find(|func| func.body.is_none()) {
+            return Err(anyhow!(
+                "function `{}` is declared but never defined",
+                func.name
+            ));
+        }
+        compiler.program.functions = module.functions().to_vec();
         // type
         let scope = TypeScope::new();
         let t = module.infer_format_type(&scope, format)?;
         // decoder
-        compiler.queue_compile(t, format, Rc::new(Next::Empty));
+        compiler.queue_compile(t, None, format, Rc::new(Next::Empty));
         while let Some((f, next, n)) = compiler.compile_queue.pop() {
             let d = compiler.compile_format(f, next)?;
             compiler.program.decoders[n].0 = d;
@@ -580,9 +759,16 @@ impl<'a> Compiler<'a> {
         Ok(compiler.program)
     }
 
-    fn queue_compile(&mut self, t: ValueType, f: &'a Format, next: Rc<Next<'a>>) -> usize {
+    fn queue_compile(
+        &mut self,
+        t: ValueType,
+        name: Option<Label>,
+        f: &'a Format,
+        next: Rc<Next<'a>>,
+    ) -> usize {
         let n = self.program.decoders.len();
         self.program.decoders.push((Decoder::Fail, t));
+        self.program.format_names.push(name);
         self.compile_queue.push((f, next, n));
         n
     }
@@ -614,7 +800,8 @@ impl<'a> Compiler<'a> {
                     *n
                 } else {
                     let t = self.module.get_format_type(*level).clone();
-                    let n = self.queue_compile(t, f, next.clone());
+                    let name = Label::from(self.module.get_name(*level).to_string());
+                    let n = self.queue_compile(t, Some(name), f, next.clone());
                     self.decoder_map.insert((*level, next.clone()), n);
                     n
                 };
@@ -630,6 +817,9 @@ impl<'a> Compiler<'a> {
                 }
                 Ok(Decoder::Call(n, args, views))
             }
+            Format::GenericVar(..) => Err(anyhow!(
+                "generic formats must be monomorphized before compilation"
+            )),
             Format::Phantom(_inner) => Ok(Decoder::Phantom),
             Format::Fail => Ok(Decoder::Fail),
             Format::DecodeBytes(expr, inner) => {
@@ -649,6 +839,10 @@ impl<'a> Compiler<'a> {
             Format::Align(n) => Ok(Decoder::Align(*n)),
             Format::Byte(bs) => Ok(Decoder::Byte(*bs)),
             Format::SkipRemainder => Ok(Decoder::SkipRemainder),
+            Format::Native(index, args) => Ok(Decoder::Native(
+                self.module.get_native(*index).decode,
+                args.clone(),
+            )),
             Format::Variant(label, f) => {
                 let d = self.compile_format(f, next.clone())?;
                 Ok(Decoder::Variant(label.clone(), Box::new(d)))
@@ -854,6 +1048,10 @@ impl<'a> Compiler<'a> {
                 let da = Box::new(self.compile_format(a, next.clone())?);
                 Ok(Decoder::Permit(da, expr.clone()))
             }
+            Format::Resync(a, pattern) => {
+                let da = Box::new(self.compile_format(a, next.clone())?);
+                Ok(Decoder::Resync(da, pattern.clone()))
+            }
             Format::Compute(expr) => Ok(Decoder::Compute(expr.clone())),
             Format::Let(name, expr, a) => {
                 let da = Box::new(self.compile_format(a, next.clone())?);
@@ -916,6 +1114,8 @@ pub enum ScopeEntry<Value: Clone> {
 
 pub enum Scope<'a> {
     Empty,
+    /// Outermost scope of a run, binding no variables but providing the functions of the module to calls
+    Functions(&'a [ExprFunction]),
     Multi(&'a MultiScope<'a>),
     Single(SingleScope<'a>),
     Decoder(DecoderScope<'a>),
@@ -968,7 +1168,9 @@ impl std::error::Error for UnknownVarError {}
 impl<'a> Scope<'a> {
     pub(crate) fn get_value_by_name(&self, name: &str) -> Result<&Value, UnknownVarError> {
         match self {
-            Scope::Empty => Err(UnknownVarError(Label::Owned(name.to_string()))),
+            Scope::Empty | Scope::Functions(_) => {
+                Err(UnknownVarError(Label::Owned(name.to_string())))
+            }
             Scope::Multi(multi) => multi.get_value_by_name(name),
             Scope::Single(single) => single.get_value_by_name(name),
             Scope::Decoder(decoder) => decoder.parent.get_value_by_name(name),
@@ -978,7 +1180,7 @@ impl<'a> Scope<'a> {
 
     fn get_decoder_by_name(&self, name: &str) -> &Decoder {
         match self {
-            Scope::Empty => panic!("decoder not found: {name}"),
+            Scope::Empty | Scope::Functions(_) => panic!("decoder not found: {name}"),
             Scope::Multi(multi) => multi.parent.get_decoder_by_name(name),
             Scope::Single(single) => single.parent.get_decoder_by_name(name),
             Scope::Decoder(decoder) => decoder.get_decoder_by_name(name),
@@ -988,7 +1190,7 @@ impl<'a> Scope<'a> {
 
     fn get_view_by_name(&self, name: &str) -> View<'a> {
         match self {
-            Scope::Empty => panic!("view not found: {name}"),
+            Scope::Empty | Scope::Functions(_) => panic!("view not found: {name}"),
             Scope::Multi(multi) => multi.get_view_by_name(name),
             Scope::Single(single) => single.parent.get_view_by_name(name),
             Scope::Decoder(decoder) => decoder.parent.get_view_by_name(name),
@@ -996,9 +1198,27 @@ impl<'a> Scope<'a> {
         }
     }
 
+    /// Returns the outermost scope enclosing this one, within which the functions of the module are called.
+    pub(crate) fn root(&self) -> &Scope<'a> {
+        match self {
+            Scope::Empty | Scope::Functions(_) => self,
+            Scope::Multi(multi) => multi.parent.root(),
+            Scope::Single(single) => single.parent.root(),
+            Scope::Decoder(decoder) => decoder.parent.root(),
+            Scope::View(view) => view.parent.root(),
+        }
+    }
+
+    fn get_function(&self, index: usize) -> &ExprFunction {
+        match self.root() {
+            Scope::Functions(functions) => &functions[index],
+            _ => panic!("function not found: {index}"),
+        }
+    }
+
     pub fn get_bindings(&self, bindings: &mut Vec<(Label, ScopeEntry<Value>)>) {
         match self {
-            Scope::Empty => {}
+            Scope::Empty | Scope::Functions(_) => {}
             Scope::Multi(multi) => multi.get_bindings(bindings),
             Scope::Single(single) => single.get_bindings(bindings),
             Scope::Decoder(decoder) => decoder.get_bindings(bindings),
@@ -1159,72 +1379,109 @@ impl Decoder {
     pub fn parse<'input>(
         &self,
         program: &Program,
+        run: &RunState,
         scope: &Scope<'_>,
         input: ReadCtxt<'input>,
     ) -> EDecodeResult<(Value, ReadCtxt<'input>)> {
+        within_budget(run.budget.step(), input)?;
         match self {
             Decoder::Call(n, es, vs) => {
-                let mut new_scope = MultiScope::with_capacity(&Scope::Empty, es.len());
-                for (name, e) in es {
-                    let v = e.eval_value(scope);
+                let args: Vec<Value> = es
+                    .iter()
+                    .map(|(_, e)| e.eval_value(scope))
+                    .collect::<EvalResult<_>>()?;
+                let views = vs
+                    .iter()
+                    .map(|(_, v)| Self::eval_view_expr(scope, v))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let key = run.memo.key(*n, args.iter(), views.iter().copied(), input);
+                if let Some((res, usage)) =
+                    key.as_ref().and_then(|k| run.memo.recall_value(k, input))
+                {
+                    within_budget(run.budget.replay(usage), input)?;
+                    return res;
+                }
+                let before = run.budget.usage();
+                let mut new_scope = MultiScope::with_capacity(scope.root(), es.len());
+                for ((name, _), v) in es.iter().zip(args) {
                     new_scope.push_owned(name.clone(), v);
                 }
-                for (name, v) in vs {
-                    let vv = Self::eval_view_expr(scope, v)?;
+                for ((name, _), vv) in vs.iter().zip(views) {
                     new_scope.push_view(name.clone(), vv);
                 }
-                Ok(try_with! (
-                program.decoders[*n]
-                    .0
-                    .parse(program, &Scope::Multi(&new_scope), input)
-                => ("parse@Call", *n)
-                ))
+                within_budget(run.budget.enter(), input)?;
+                let res =
+                    program.decoders[*n]
+                        .0
+                        .parse(program, run, &Scope::Multi(&new_scope), input);
+                run.budget.leave();
+                let res = match (res, &program.format_names[*n]) {
+                    (Err(e), Some(name)) => Err(e.within(name.clone())),
+                    (res, _) => res,
+                };
+                if let Some(key) = key {
+                    let usage = run.budget.usage().since(before);
+                    run.memo.record_value(key, &res, usage, input);
+                }
+                res
             }
             Decoder::Phantom => Ok(WithErr::new((Value::PhantomData, input))),
             Decoder::Fail => Err(DecodeErrorKind::<Value>::fail(scope, input).into()),
             Decoder::Pos => {
-                let pos = input.offset as u64;
+                let pos = input.position() as u64;
                 Ok(WithErr::new((Value::U64(pos), input)))
             }
             Decoder::SkipRemainder => {
                 let input = input.skip_remainder();
                 Ok(WithErr::new((Value::UNIT, input)))
             }
+            Decoder::Native(decode, args) => {
+                let args: Vec<Value> = args
+                    .iter()
+                    .map(|arg| arg.eval_value(scope))
+                    .collect::<EvalResult<_>>()?;
+                let offset = input.offset;
+                let (value, len) = decode(input.remaining(), &args)
+                    .map_err(|error| DecodeErrorKind::native(error, offset))?;
+                let (_, input) = input
+                    .split_at(len)
+                    .ok_or(DecodeErrorKind::overrun(len, offset))?;
+                Ok(WithErr::new((value, input)))
+            }
             Decoder::EndOfInput => match input.read_byte() {
                 None => Ok(WithErr::new((Value::UNIT, input))),
                 Some((b, _)) => Err(DecodeErrorKind::trailing(b, input.offset).into()),
             },
             Decoder::Align(n) => {
-                let skip = (n - (input.offset % n)) % n;
+                let skip = (n - (input.position() % n)) % n;
                 let (_, input) = input
                     .split_at(skip)
                     .ok_or(DecodeErrorKind::overrun(skip, input.offset))?;
                 Ok(WithErr::new((Value::UNIT, input)))
             }
             Decoder::Byte(bs) => {
-                let (b, input) = input
-                    .read_byte()
-                    .ok_or(DecodeErrorKind::overbyte(input.offset))?;
+                let offset = input.offset;
+                let (b, input) = input.read_byte().ok_or(DecodeErrorKind::overbyte(offset))?;
                 if bs.contains(b) {
                     Ok(WithErr::new((Value::U8(b), input)))
                 } else {
-                    Err(DecodeErrorKind::unexpected(b, *bs, input.offset).into())
+                    Err(DecodeErrorKind::unexpected(b, *bs, offset).into())
                 }
             }
             Decoder::Variant(label, d) => Ok(d
-                .parse(program, scope, input)?
+                .parse(program, run, scope, input)?
                 .map(move |(v, input)| (Value::Variant(label.clone(), Box::new(v)), input))),
             Decoder::Branch(tree, branches) => {
                 let index = tree.matches(input).ok_or(DecodeErrorKind::NoValidBranch {
                     offset: input.offset,
                 })?;
                 let d = &branches[index];
-                Ok(d.parse(program, scope, input)?
+                Ok(d.parse(program, run, scope, input)?
                     .map(|(v, input)| (Value::Branch(index, Box::new(v)), input)))
             }
             Decoder::Parallel(branches) => {
                 for (index, d) in branches.iter().enumerate() {
-                    let res = d.parse(program, scope, input);
+                    let res = d.parse(program, run, scope, input);
                     if let Ok(p) = res {
                         return Ok(p.map(|(v, input)| (Value::Branch(index, Box::new(v)), input)));
                     }
@@ -1236,7 +1493,7 @@ impl Decoder {
                 fields.iter().enumerate(),
                 |(mut v, input), (ix, f)| {
                     Ok(
-                        try_with!(f.parse(program, scope, input) => ("Tuple", ix)).map(
+                        try_with!(f.parse(program, run, scope, input) => ("Tuple", ix)).map(
                             move |(vf, new_input)| {
                                 v.push(vf);
                                 (v, new_input)
@@ -1251,27 +1508,27 @@ impl Decoder {
                 decs.iter(),
                 |(mut v, input), f| {
                     Ok(
-                        try_with!(f.parse(program, scope, input) => ("Sequence", v.len())).map(
-                            move |(vf, new_input)| {
+                        try_with!(f.parse(program, run, scope, input) => ("Sequence", v.len()))
+                            .map(move |(vf, new_input)| {
                                 v.push(vf);
                                 (v, new_input)
-                            },
-                        ),
+                            }),
                     )
                 },
             )?
-            .map(|(v, input)| (Value::Seq(SeqKind::Strict(v)), input))),
+            .map(|(v, input)| (Value::Seq(v.into()), input))),
             Decoder::While(tree, a) => {
                 let mut input = input;
-                let mut res = WithErr::new((Vec::new(), input));
+                let mut res = WithErr::new((Accum::new(a, 0), input));
                 while try_with!(tree.matches(input).ok_or(DecodeErrorKind::NoValidBranch {
                     offset: input.offset,
                 }) => ("While(matchtree)", res.as_ref().0.len()))
                     == 0
                 {
                     res = res.join(|(mut v, input)| {
+                        tally_elements(&run.budget, 1, input)?;
                         Ok(
-                            try_with!(a.parse(program, scope, input) => ("While(parse)", v.len()))
+                            try_with!(a.parse(program, run, scope, input) => ("While(parse)", v.len()))
                                 .map(|(va, next_input)| {
                                     v.push(va);
                                     (v, next_input)
@@ -1280,14 +1537,15 @@ impl Decoder {
                     })?;
                     input = res.as_ref().1;
                 }
-                Ok(res.map(|(v, input)| (Value::Seq(v.into()), input)))
+                Ok(res.map(|(v, input)| (v.into_value(), input)))
             }
             Decoder::Until(tree, a) => {
-                let mut res = WithErr::new((Vec::new(), input));
+                let mut res = WithErr::new((Accum::new(a, 0), input));
                 loop {
                     res = res.join(|(mut v, input)| {
+                        tally_elements(&run.budget, 1, input)?;
                         Ok(
-                            try_with!(a.parse(program, scope, input) => ("Until(parse)", v.len()))
+                            try_with!(a.parse(program, run, scope, input) => ("Until(parse)", v.len()))
                                 .map(|(va, next_input)| {
                                     v.push(va);
                                     (v, next_input)
@@ -1303,19 +1561,13 @@ impl Decoder {
                         break;
                     }
                 }
-                Ok(res.map(|(v, input)| (Value::Seq(v.into()), input)))
+                Ok(res.map(|(v, input)| (v.into_value(), input)))
             }
             Decoder::DecodeBytes(bytes, a) => {
-                let bytes = {
-                    let raw = bytes.eval_value(scope);
-                    let seq_vals = raw.get_sequence().expect("bad type for DecodeBytes input");
-                    seq_vals
-                        .into_iter()
-                        .map(|v| v.get_as_u8())
-                        .collect::<Vec<u8>>()
-                };
+                let raw = bytes.eval_value(scope)?;
+                let bytes = raw.get_bytes();
                 let new_input = ReadCtxt::new(&bytes);
-                try_with!(a.parse(program, scope, new_input) => ("DecodeBytes", bytes.len())).join(
+                try_with!(run.memo.suspend(|| a.parse(program, run, scope, new_input)) => ("DecodeBytes", bytes.len())).join(
                     |(va, rem_input)| {
                         Ok(match rem_input.read_byte() {
                             Some((b, _)) => {
@@ -1334,47 +1586,51 @@ impl Decoder {
             }
             Decoder::ParseFromView(v_expr, a) => {
                 let view_window = Self::eval_view_expr(scope, v_expr)?;
-                Ok(try_with!(a.parse(program, scope, view_window) => ("ParseFromView", format!("{:?}", v_expr)))
+                Ok(try_with!(a.parse(program, run, scope, view_window) => ("ParseFromView", format!("{:?}", v_expr)))
                     .map(|(va, _)| (va, input)))
             }
-            Decoder::LetFormat(da, name, db) => {
-                try_with!(da.parse(program, scope, input) => ("LetFormat(lhs)", name.to_string()))
-                    .join(|(va, input)| {
-                        let new_scope = Scope::Single(SingleScope::new(scope, name, &va));
-                        db.parse(program, &new_scope, input)
-                    })
-            }
+            Decoder::LetFormat(da, name, db) => da
+                .parse(program, run, scope, input)
+                .map_err(|e| e.within(name.clone()))?
+                .join(|(va, input)| {
+                    let new_scope = Scope::Single(SingleScope::new(scope, name, &va));
+                    db.parse(program, run, &new_scope, input)
+                }),
             Decoder::MonadSeq(da, db) => {
-                try_with!(da.parse(program, scope, input) => "MonadSeq(lhs)").join(|(_, input)| {
-                    Ok(try_with!(db.parse(program, scope, input) => "MonadSeq(rhs)"))
-                })
+                try_with!(da.parse(program, run, scope, input) => "MonadSeq(lhs)").join(
+                    |(_, input)| {
+                        Ok(try_with!(db.parse(program, run, scope, input) => "MonadSeq(rhs)"))
+                    },
+                )
             }
             Decoder::ForEach(expr, lbl, a) => {
                 // we need val because it would otherwise be a dropped temporary binding
-                let val = expr.eval_value(scope);
+                let val = expr.eval_value(scope)?;
                 let seq = val.get_sequence().expect("bad type for ForEach input");
+                tally_elements(&run.budget, seq.len(), input)?;
                 Ok(WithErr::fold(
-                    (Vec::with_capacity(seq.len()), input),
+                    (Accum::new(a, seq.len()), input),
                     seq,
                     |(mut v, input), e| {
                         let new_scope = Scope::Single(SingleScope::new(scope, lbl, &e));
-                        Ok(try_with!(a.parse(program, &new_scope, input) => ("ForEach", v.len(), format!("{e:?}")))
+                        Ok(try_with!(a.parse(program, run, &new_scope, input) => ("ForEach", v.len(), format!("{e:?}")))
                             .map(|(va, next_input)| {
                                 v.push(va);
                                 (v, next_input)
                             }))
                     },
                 )?
-                .map(|(v, input)| (Value::Seq(v.into()), input)))
+                .map(|(v, input)| (v.into_value(), input)))
             }
             Decoder::RepeatCount(expr, a) => {
-                let count = expr.eval_value(scope).unwrap_usize();
+                let count = expr.eval_value(scope)?.unwrap_usize();
+                tally_elements(&run.budget, count, input)?;
                 Ok(WithErr::fold(
-                    (Vec::with_capacity(count), input),
+                    (Accum::new(a, count), input),
                     0..count,
                     |(mut v, input), _| {
                         Ok(
-                            try_with!(a.parse(program, scope, input) => ("RepeatCount", v.len()))
+                            try_with!(a.parse(program, run, scope, input) => ("RepeatCount", v.len()))
                                 .map(|(va, next_input)| {
                                     v.push(va);
                                     (v, next_input)
@@ -1382,16 +1638,16 @@ impl Decoder {
                         )
                     },
                 )?
-                .map(|(v, input)| (Value::Seq(v.into()), input)))
+                .map(|(v, input)| (v.into_value(), input)))
             }
             Decoder::RepeatBetween(reps_left_tree, min, max, a) => {
-                let min = min.eval_value(scope).unwrap_usize();
-                let max = max.eval_value(scope).unwrap_usize();
-                let mut res = WithErr::new((Vec::new(), input));
+                let min = min.eval_value(scope)?.unwrap_usize();
+                let max = max.eval_value(scope)?.unwrap_usize();
+                let mut res = WithErr::new((Accum::new(a, 0), input));
                 loop {
-                    let v = &res.as_ref().0;
+                    let (v, input) = res.as_ref();
                     // REVIEW - does the order of the conditions in the OR matter?
-                    if reps_left_tree.matches(input).ok_or(
+                    if reps_left_tree.matches(*input).ok_or(
                         DecodeErrorKind::NoValidBranch {
                             offset: input.offset,
                         }
@@ -1405,8 +1661,9 @@ impl Decoder {
                         break;
                     }
                     res = res.join(|(mut v, input)| {
+                        tally_elements(&run.budget, 1, input)?;
                         Ok(
-                            try_with!(a.parse(program, scope, input) => ("RepeatBetween", v.len()))
+                            try_with!(a.parse(program, run, scope, input) => ("RepeatBetween", v.len()))
                                 .map(|(va, next_input)| {
                                     v.push(va);
                                     (v, next_input)
@@ -1414,12 +1671,12 @@ impl Decoder {
                         )
                     })?;
                 }
-                Ok(res.map(|(v, input)| (Value::Seq(v.into()), input)))
+                Ok(res.map(|(v, input)| (v.into_value(), input)))
             }
             Decoder::Maybe(expr, a) => {
-                let is_present = expr.eval_value(scope).unwrap_bool();
+                let is_present = expr.eval_value(scope)?.unwrap_bool();
                 if is_present {
-                    Ok(try_with!(a.parse(program, scope, input) => "Maybe")
+                    Ok(try_with!(a.parse(program, run, scope, input) => "Maybe")
                         .map(|(val, input)| (Value::Option(Some(Box::new(val))), input)))
                 } else {
                     Ok(WithErr::new((Value::Option(None), input)))
@@ -1427,60 +1684,62 @@ impl Decoder {
             }
             Decoder::RepeatUntilLast(expr, a) => {
                 // third value is "done" flag
-                let mut res = WithErr::new((Vec::new(), input, false));
+                let mut res = WithErr::new((Accum::new(a, 0), input, false));
                 loop {
                     res = res.join(|(mut v, input, _done)| {
-                        Ok(try_with!(a.parse(program, scope, input) => ("RepeatUntilLast", v.len())).map(|(va, next_input)| {
-                            let done = expr.eval_lambda(scope, &va).unwrap_bool();
-                            v.push(va);
-                            (v, next_input, done)
-                        }))
+                        tally_elements(&run.budget, 1, input)?;
+                        try_with!(a.parse(program, run, scope, input) => ("RepeatUntilLast", v.len()))
+                            .try_map(|(va, next_input)| {
+                                let done = expr.eval_lambda(scope, &va)?.unwrap_bool();
+                                v.push(va);
+                                Ok((v, next_input, done))
+                            })
                     })?;
                     break_if_done!(res => (v, input));
                 }
-                Ok(res.map(|(v, input, _)| (Value::Seq(v.into()), input)))
+                Ok(res.map(|(v, input, _)| (v.into_value(), input)))
             }
             Decoder::RepeatUntilSeq(expr, a) => {
                 // third value is "done" flag
                 let mut res = WithErr::new((Vec::new(), input, false));
                 loop {
                     res = res.join(|(mut v, input, _done)| {
-                        Ok(
-                            try_with!(a.parse(program, scope, input) => ("RepeatUntilSeq", format!("len={}", v.len()), format!("{a:?}")))
-                                .map(|(va, next_input)| {
-                                    v.push(va);
-                                    let vs = Value::Seq(v.into());
-                                    let done = expr.eval_lambda(scope, &vs).unwrap_bool();
-                                    let v = match vs {
-                                        Value::Seq(v) => v.into_vec(),
-                                        _ => unreachable!(),
-                                    };
-                                    (v, next_input, done)
-                                }),
-                        )
+                        tally_elements(&run.budget, 1, input)?;
+                        try_with!(a.parse(program, run, scope, input) => ("RepeatUntilSeq", format!("len={}", v.len()), format!("{a:?}")))
+                            .try_map(|(va, next_input)| {
+                                v.push(va);
+                                let vs = Value::Seq(v.into());
+                                let done = expr.eval_lambda(scope, &vs)?.unwrap_bool();
+                                let v = match vs {
+                                    Value::Seq(v) => v.into_vec(),
+                                    _ => unreachable!(),
+                                };
+                                Ok((v, next_input, done))
+                            })
                     })?;
                     break_if_done!(res => (v, input));
                 }
                 Ok(res.map(|(v, input, _)| (Value::Seq(v.into()), input)))
             }
             Decoder::AccumUntil(f_done, f_update, init, _vt, a) => {
-                let accum = init.eval_value(scope);
+                let accum = init.eval_value(scope)?;
                 let mut res = WithErr::new((Vec::new(), accum, input, false));
                 loop {
                     res = res.join(|(mut v, accum, input, _done)| {
                         let done_arg =
                             Value::Tuple(vec![accum.clone(), Value::Seq(v.clone().into())]);
-                        let is_done = f_done.eval_lambda(scope, &done_arg).unwrap_bool();
+                        let is_done = f_done.eval_lambda(scope, &done_arg)?.unwrap_bool();
                         if is_done {
                             return Ok(WithErr::new((v, accum, input, true)));
                         }
-                        Ok(try_with!(a.parse(program, scope, input) => ("AccumUntil", format!("len={}", v.len()), format!("accum={:?}", accum)))
-                            .map(|(next_elem, next_input)| {
+                        tally_elements(&run.budget, 1, input)?;
+                        try_with!(a.parse(program, run, scope, input) => ("AccumUntil", format!("len={}", v.len()), format!("accum={:?}", accum)))
+                            .try_map(|(next_elem, next_input)| {
                                 v.push(next_elem.clone());
                                 let update_arg = Value::Tuple(vec![accum.clone(), next_elem]);
-                                let next_accum = f_update.eval_lambda(scope, &update_arg);
-                                (v, next_accum, next_input, false)
-                            }))
+                                let next_accum = f_update.eval_lambda(scope, &update_arg)?;
+                                Ok((v, next_accum, next_input, false))
+                            })
                     })?;
                     break_if_done!(res => (v, accum, input));
                 }
@@ -1488,9 +1747,11 @@ impl Decoder {
                     (Value::Tuple(vec![accum, Value::Seq(v.into())]), input)
                 }))
             }
-            Decoder::Peek(a) => Ok(a.parse(program, scope, input)?.map(|(v, _)| (v, input))),
+            Decoder::Peek(a) => Ok(a
+                .parse(program, run, scope, input)?
+                .map(|(v, _)| (v, input))),
             Decoder::PeekNot(a) => {
-                if a.parse(program, scope, input).is_ok() {
+                if a.parse(program, run, scope, input).is_ok() {
                     Err(DecodeErrorKind::<Value>::fail(scope, input)
                         .with_trace(("PeekNot", format!("{:?}", a))))
                 } else {
@@ -1498,12 +1759,12 @@ impl Decoder {
                 }
             }
             Decoder::Slice(expr, a) => {
-                let size = expr.eval_value(scope).unwrap_usize();
+                let size = expr.eval_value(scope)?.unwrap_usize();
                 let (slice, input) = input.split_at(size).ok_or(
                     DecodeErrorKind::overrun(size, input.offset)
                         .with_trace(("Slice(create)", format!("{:?}->{size}", expr))),
                 )?;
-                Ok(try_with!(a.parse(program, scope, slice) => ("Slice(parse)", format!("{:?}", a))).map(|(v, _)| (v, input)))
+                Ok(try_with!(a.parse(program, run, scope, slice) => ("Slice(parse)", format!("{:?}", a))).map(|(v, _)| (v, input)))
             }
             Decoder::Bits(a) => {
                 // FIXME - copying the entire buffer as bits feels inefficient, we should measure performance and see if there is a better alternative
@@ -1513,7 +1774,7 @@ impl Decoder {
                         bits.push((b & (1 << i)) >> i);
                     }
                 }
-                try_with!(a.parse(program, scope, ReadCtxt::new(&bits)) => ("Bits(parse)", format!("{a:?}")))
+                try_with!(run.memo.suspend(|| a.parse(program, run, scope, ReadCtxt::new(&bits))) => ("Bits(parse)", format!("{a:?}")))
                     .join(|(v, bits)| {
                         let bytes_remain = bits.remaining().len() >> 3;
                         let bytes_read = input.remaining().len() - bytes_remain;
@@ -1524,35 +1785,37 @@ impl Decoder {
                     })
             }
             Decoder::WithRelativeOffset(base_addr, expr, a) => {
-                let base = base_addr.eval_value(scope).unwrap_usize();
-                let offset = expr.eval_value(scope).unwrap_usize();
+                let base = base_addr.eval_value(scope)?.unwrap_usize();
+                let offset = expr.eval_value(scope)?.unwrap_usize();
                 let abs_offset = base + offset;
+                within_budget(run.budget.hop(), input)?;
                 let seek_input = input.seek_to(abs_offset).ok_or(
                     DecodeErrorKind::bad_seek(abs_offset, input.input.len())
                         .with_trace("WithRelativeOffset(seek)"),
                 )?;
-                Ok(try_with!(a.parse(program, scope, seek_input) => ("WithRelativeOffset(parse)", format!("{a:?}")))
+                Ok(try_with!(a.parse(program, run, scope, seek_input) => ("WithRelativeOffset(parse)", format!("{a:?}")))
                     .map(|(v, _)| (v, input)))
             }
-            Decoder::Map(d, expr) => Ok(
-                try_with!(d.parse(program, scope, input) => ("Map(parse)", format!("{d:?}"))).map(
-                    |(orig, input)| {
-                        let v = expr.eval_lambda(scope, &orig);
-                        (Value::Mapped(Box::new(orig), Box::new(v)), input)
-                    },
-                ),
-            ),
+            Decoder::Map(d, expr) => {
+                try_with!(d.parse(program, run, scope, input) => ("Map(parse)", format!("{d:?}")))
+                    .try_map(|(orig, input)| {
+                        let v = expr.eval_lambda(scope, &orig)?;
+                        Ok((Value::Mapped(Box::new(orig), Box::new(v)), input))
+                    })
+            }
             Decoder::Where(d, cond) => {
-                try_with!(d.parse(program, scope, input) => ("Where(parse)", format!("{d:?}")))
+                let offset = input.offset;
+                try_with!(d.parse(program, run, scope, input) => ("Where(parse)", format!("{d:?}")))
                     .join(|(v, input)| {
                         let Condition { expr, severity } = cond;
-                        match expr.eval_lambda(scope, &v).unwrap_bool() {
+                        match expr.eval_lambda(scope, &v)?.unwrap_bool() {
                             true => Ok(WithErr::new((v, input))),
                             false => {
                                 let err = DecodeErrorKind::bad_where(
                                     scope,
                                     expr.clone(),
                                     Box::new(v.clone()),
+                                    offset,
                                 )
                                 .into();
                                 if severity.is_strict() {
@@ -1565,28 +1828,28 @@ impl Decoder {
                     })
             }
             Decoder::Compute(expr) => {
-                let v = expr.eval_value(scope);
+                let v = expr.eval_value(scope)?;
                 Ok(WithErr::new((v, input)))
             }
             Decoder::Let(name, expr, d) => {
-                let v = expr.eval_value(scope);
+                let v = expr.eval_value(scope)?;
                 let let_scope = SingleScope::new(scope, name, &v);
                 Ok(
-                    try_with!(d.parse(program, &Scope::Single(let_scope), input) => ("Let(parse)", format!("{} := {:?} <- {:?}", name, v, expr), format!("{d:?}"))),
+                    try_with!(d.parse(program, run, &Scope::Single(let_scope), input) => ("Let(parse)", format!("{} := {:?} <- {:?}", name, v, expr), format!("{d:?}"))),
                 )
             }
             Decoder::LetView(name, d) => {
                 let view = input;
                 let let_scope = ViewScope::new(scope, name, view);
                 Ok(
-                    try_with!(d.parse(program, &Scope::View(let_scope), input) => ("LetView(parse)", name.to_string(), format!("{d:?}"))),
+                    try_with!(d.parse(program, run, &Scope::View(let_scope), input) => ("LetView(parse)", name.to_string(), format!("{d:?}"))),
                 )
             }
             Decoder::Match(head, branches) => {
-                let head = head.eval(scope);
+                let head = head.eval(scope)?;
                 for (index, (pattern, decoder)) in branches.iter().enumerate() {
-                    if let Some(pattern_scope) = head.matches(scope, pattern) {
-                        return Ok(try_with!(decoder.parse(program, &Scope::Multi(&pattern_scope), input)
+                    if let Some(pattern_scope) = head.matches(scope, pattern)? {
+                        return Ok(try_with!(decoder.parse(program, run, &Scope::Multi(&pattern_scope), input)
                         => ("Match(parse)", format!("[{}]: {:?} => {:?}", index, pattern, decoder))
                         )
                             .map(|(v, input)| (Value::Branch(index, Box::new(v)), input)));
@@ -1599,42 +1862,30 @@ impl Decoder {
                 );
             }
             Decoder::Dynamic(name, DynFormat::Huffman(lengths_expr, opt_values_expr), d) => {
-                let lengths_val = lengths_expr.eval(scope);
-                let lengths = value_to_vec_usize(lengths_val.as_ref());
-                let lengths = match opt_values_expr {
-                    None => lengths,
-                    Some(e) => {
-                        let values = value_to_vec_usize(e.eval(scope).as_ref());
-                        let mut new_lengths = [0].repeat(values.len());
-                        for i in 0..lengths.len() {
-                            new_lengths[values[i]] = lengths[i];
-                        }
-                        new_lengths
-                    }
-                };
+                let lengths = huffman_lengths(scope, lengths_expr, opt_values_expr.as_deref())?;
                 let f = make_huffman_codes(&lengths);
                 let dyn_d = Compiler::compile_one(&f).unwrap();
                 let child_scope = DecoderScope::new(scope, name, dyn_d);
                 Ok(
-                    try_with!(d.parse(program, &Scope::Decoder(child_scope), input) => ("Dynamic(parse)", name.to_string(), format!("{d:?}"))),
+                    try_with!(d.parse(program, run, &Scope::Decoder(child_scope), input) => ("Dynamic(parse)", name.to_string(), format!("{d:?}"))),
                 )
             }
             Decoder::Apply(name) => {
                 let d = scope.get_decoder_by_name(name);
                 Ok(
-                    try_with!(d.parse(program, scope, input) => ("Apply(parse)", name.to_string(), format!("{d:?}"))),
+                    try_with!(d.parse(program, run, scope, input) => ("Apply(parse)", name.to_string(), format!("{d:?}"))),
                 )
             }
             Decoder::LiftedOption(None) => Ok(WithErr::new((Value::Option(None), input))),
             Decoder::LiftedOption(Some(dec)) => Ok(try_with!(dec
-                .parse(program, scope, input) => ("LiftedOption(parse)", format!("{dec:?}")))
+                .parse(program, run, scope, input) => ("LiftedOption(parse)", format!("{dec:?}")))
             .map(|(v, input)| (Value::Option(Some(Box::new(v))), input))),
             Decoder::CaptureBytes(v_expr, len) => {
-                let len = len.eval_value(scope).unwrap_usize();
+                let len = len.eval_value(scope)?.unwrap_usize();
 
                 let view_window = Self::eval_view_expr(scope, v_expr)?;
 
-                // accumulate `len` bytes into a Vec<Value>
+                // accumulate `len` bytes into a Vec<u8>
                 let mut accum = Vec::with_capacity(len);
                 let mut buf = view_window;
                 for _ in 0..len {
@@ -1645,18 +1896,20 @@ impl Decoder {
                             format!("view: {v_expr:?}"),
                         )));
                     };
-                    accum.push(Value::U8(byte));
+                    accum.push(byte);
                     buf = new_buf;
                 }
 
                 // return the accumulated bytes, along with the original input
-                Ok(WithErr::new((Value::Seq(SeqKind::Strict(accum)), input)))
+                Ok(WithErr::new((
+                    Value::Seq(SeqKind::Bytes(accum.into())),
+                    input,
+                )))
             }
             Decoder::ReadArray(v_expr, len, kind) => {
-                let len = len.eval_value(scope).unwrap_usize();
+                let len = len.eval_value(scope)?.unwrap_usize();
                 let view_window = Self::eval_view_expr(scope, v_expr)?;
 
-                // REVIEW - hardcoded big-endianness
                 let mut accum = Vec::with_capacity(len);
                 let mut buf = view_window;
                 for ix in 0..len {
@@ -1665,7 +1918,7 @@ impl Decoder {
                     buf = new_buf;
                 }
 
-                Ok(WithErr::new((Value::Seq(SeqKind::Strict(accum)), input)))
+                Ok(WithErr::new((Value::Seq(accum.into()), input)))
             }
             Decoder::ReifyView(v_expr) => {
                 let view = try_with!(Self::eval_view_expr(scope, v_expr) => ("ReifyView", format!("view: {v_expr:?}")));
@@ -1678,7 +1931,7 @@ impl Decoder {
             }
             #[cfg(feature = "format_enforce")]
             Decoder::Enforce(a) => {
-                let res = a.parse(program, scope, input)?;
+                let res = a.parse(program, run, scope, input)?;
                 match res.into_strict() {
                     Ok((v, input)) => Ok(WithErr::new((v, input))),
                     Err(mut errs) => {
@@ -1687,16 +1940,29 @@ impl Decoder {
                     }
                 }
             }
-            Decoder::Permit(a, expr) => Ok(downgrade_error_with(
-                a.parse(program, scope, input)
-                    .map(|ok| ok.map(|(v, input)| (Value::Permit(Ok(Box::new(v))), input))),
-                || {
-                    (
-                        Value::Permit(Err(Some(Box::new(expr.eval_value(scope))))),
-                        input,
-                    )
-                },
-            )),
+            Decoder::Permit(a, expr) => {
+                let res = a
+                    .parse(program, run, scope, input)
+                    .map(|ok| ok.map(|(v, input)| (Value::Permit(Ok(Box::new(v))), input)));
+                let fallback = match res {
+                    Ok(_) => None,
+                    Err(_) => Some(Box::new(expr.eval_value(scope)?)),
+                };
+                Ok(downgrade_error_with(res, || {
+                    (Value::Permit(Err(fallback)), input)
+                }))
+            }
+            Decoder::Resync(a, pattern) => match a.parse(program, run, scope, input) {
+                Ok(res) => Ok(res.map(|(v, input)| (Value::variant("ok", v), input))),
+                Err(e) if !e.is_recoverable() => Err(e),
+                Err(e) => {
+                    log::warn!("resynchronizing after error: {e}");
+                    let data = input.remaining();
+                    let len = resync_offset(data, pattern);
+                    let (_, rest) = input.split_at(len).unwrap();
+                    Ok(WithErr::new((Value::resync_skipped(e, &data[..len]), rest)))
+                }
+            },
         }
     }
 
@@ -1710,7 +1976,10 @@ impl Decoder {
                 Ok(view)
             }
             ViewExpr::Offset(base, offset) => {
-                let offset = offset.eval_value(scope).unwrap_usize();
+                let offset = offset
+                    .eval_value(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_usize();
                 let base_view = Self::eval_view_expr(scope, base)?;
                 let Some((_, view_window)) = base_view.split_at(offset) else {
                     return Err(DecodeErrorKind::overrun(offset, base_view.offset));
@@ -1725,35 +1994,75 @@ fn read_base(
     buf: ReadCtxt<'_>,
     kind: BaseKind<Endian>,
 ) -> Result<(Value, ReadCtxt<'_>), DecodeErrorKind> {
-    match kind {
-        BaseKind::U8 => {
-            let Some((byte, new_buf)) = buf.read_byte() else {
-                return Err(DecodeErrorKind::overbyte(buf.offset));
+    match read_base_value(buf, kind) {
+        Some(ret) => Ok(ret),
+        None if kind.size() == 1 => Err(DecodeErrorKind::overbyte(buf.offset)),
+        None => Err(DecodeErrorKind::overrun(kind.size(), buf.offset)),
+    }
+}
+
+/// Reads a single machine-integer of the given kind and endianness from `buf`.
+///
+/// Signed kinds are read as their unsigned counterparts and reinterpreted bitwise,
+/// yielding a `Value::Numeric` with the appropriate signed representation.
+///
+/// Returns `None` if there are not enough bytes remaining in `buf`.
+pub(crate) fn read_base_value(
+    buf: ReadCtxt<'_>,
+    kind: BaseKind<Endian>,
+) -> Option<(Value, ReadCtxt<'_>)> {
+    use crate::numeric::core::TypedConst;
+    use crate::valuetype::SignedIntType;
+
+    fn signed(n: impl Into<num_bigint::BigInt>, s: SignedIntType) -> Value {
+        Value::Numeric(Rc::new(TypedConst(n.into(), s.into())))
+    }
+
+    macro_rules! read_endian {
+        ($int:ty, $endian:expr) => {{
+            let (raw, new_buf) = buf.read_array::<{ std::mem::size_of::<$int>() }>()?;
+            let val = match $endian {
+                Endian::Be => <$int>::from_be_bytes(raw),
+                Endian::Le => <$int>::from_le_bytes(raw),
             };
-            Ok((Value::U8(byte), new_buf))
+            (val, new_buf)
+        }};
+    }
+
+    Some(match kind {
+        BaseKind::U8 => {
+            let (byte, new_buf) = buf.read_byte()?;
+            (Value::U8(byte), new_buf)
         }
-        BaseKind::U16BE => {
-            let Some((val, new_buf)) = buf.read_u16be() else {
-                return Err(DecodeErrorKind::overrun(kind.size(), buf.offset));
-            };
-            Ok((Value::U16(val), new_buf))
+        BaseKind::I8 => {
+            let (byte, new_buf) = buf.read_byte()?;
+            (signed(byte as i8, SignedIntType::I8), new_buf)
         }
-        BaseKind::U32BE => {
-            let Some((val, new_buf)) = buf.read_u32be() else {
-                return Err(DecodeErrorKind::overrun(kind.size(), buf.offset));
-            };
-            Ok((Value::U32(val), new_buf))
+        BaseKind::U16Ext(e) => {
+            let (val, new_buf) = read_endian!(u16, e);
+            (Value::U16(val), new_buf)
         }
-        BaseKind::U64BE => {
-            let Some((val, new_buf)) = buf.read_u64be() else {
-                return Err(DecodeErrorKind::overrun(kind.size(), buf.offset));
-            };
-            Ok((Value::U64(val), new_buf))
+        BaseKind::U32Ext(e) => {
+            let (val, new_buf) = read_endian!(u32, e);
+            (Value::U32(val), new_buf)
         }
-        BaseKind::U16LE | BaseKind::U32LE | BaseKind::U64LE => {
-            unimplemented!("little-endian read-base parses not yet implemented")
+        BaseKind::U64Ext(e) => {
+            let (val, new_buf) = read_endian!(u64, e);
+            (Value::U64(val), new_buf)
         }
-    }
+        BaseKind::I16Ext(e) => {
+            let (val, new_buf) = read_endian!(i16, e);
+            (signed(val, SignedIntType::I16), new_buf)
+        }
+        BaseKind::I32Ext(e) => {
+            let (val, new_buf) = read_endian!(i32, e);
+            (signed(val, SignedIntType::I32), new_buf)
+        }
+        BaseKind::I64Ext(e) => {
+            let (val, new_buf) = read_endian!(i64, e);
+            (signed(val, SignedIntType::I64), new_buf)
+        }
+    })
 }
 
 fn value_to_vec_usize(v: &Value) -> Vec<usize> {
@@ -1769,7 +2078,39 @@ fn value_to_vec_usize(v: &Value) -> Vec<usize> {
         .collect::<Vec<usize>>()
 }
 
-fn make_huffman_codes(lengths: &[usize]) -> Format {
+/// Evaluates the code-length table of a `DynFormat::Huffman`, permuting it according to the
+/// optional symbol-order table if one is given.
+pub(crate) fn huffman_lengths(
+    scope: &Scope<'_>,
+    lengths_expr: &Expr,
+    opt_values_expr: Option<&Expr>,
+) -> EvalResult<Vec<usize>> {
+    let lengths_val = lengths_expr.eval(scope)?;
+    let values_val = opt_values_expr.map(|e| e.eval(scope)).transpose()?;
+    Ok(huffman_code_lengths(
+        lengths_val.as_ref(),
+        values_val.as_deref(),
+    ))
+}
+
+/// Permutes an already-evaluated code-length table according to the optional symbol-order table,
+/// as in [`huffman_lengths`].
+pub(crate) fn huffman_code_lengths(lengths: &Value, values: Option<&Value>) -> Vec<usize> {
+    let lengths = value_to_vec_usize(lengths);
+    match values {
+        None => lengths,
+        Some(values) => {
+            let values = value_to_vec_usize(values);
+            let mut new_lengths = [0].repeat(values.len());
+            for i in 0..lengths.len() {
+                new_lengths[values[i]] = lengths[i];
+            }
+            new_lengths
+        }
+    }
+}
+
+pub(crate) fn make_huffman_codes(lengths: &[usize]) -> Format {
     let max_length = *lengths.iter().max().unwrap();
     let mut bl_count = [0].repeat(max_length + 1);
 
@@ -1862,7 +2203,12 @@ mod tests {
     fn accepts(d: &Decoder, input: &[u8], tail: &[u8], expect: Value) {
         let program = Program::new();
         let (val, remain) = d
-            .parse(&program, &Scope::Empty, ReadCtxt::new(input))
+            .parse(
+                &program,
+                &RunState::new(&program),
+                &Scope::Empty,
+                ReadCtxt::new(input),
+            )
             .unwrap()
             .into_inner();
         assert_eq!(val, expect);
@@ -1872,8 +2218,13 @@ mod tests {
     fn rejects(d: &Decoder, input: &[u8]) {
         let program = Program::new();
         assert!(
-            d.parse(&program, &Scope::Empty, ReadCtxt::new(input))
-                .is_err()
+            d.parse(
+                &program,
+                &RunState::new(&program),
+                &Scope::Empty,
+                ReadCtxt::new(input)
+            )
+            .is_err()
         );
     }
 
@@ -1917,6 +2268,125 @@ mod tests {
         rejects(&d, &[]);
     }
 
+    #[test]
+    fn compile_repeat_bytes() {
+        let parse = |f: &Format, input: &[u8]| {
+            let d = Compiler::compile_one(f).unwrap();
+            let program = Program::new();
+            d.parse(
+                &program,
+                &RunState::new(&program),
+                &Scope::Empty,
+                ReadCtxt::new(input),
+            )
+            .unwrap()
+            .into_inner()
+            .0
+        };
+        let fs = [
+            repeat(not_byte(0x00)),
+            repeat_count(Expr::U8(3), ANY_BYTE),
+            repeat_until_last(lambda("b", expr_eq(var("b"), Expr::U8(3))), ANY_BYTE),
+        ];
+        for f in &fs {
+            match parse(f, &[1, 2, 3, 0]) {
+                Value::Seq(SeqKind::Bytes(bs)) => assert_eq!(&*bs, &[1, 2, 3]),
+                other => panic!("expected compact bytes, found {other:?}"),
+            }
+        }
+        // non-byte elements are still accumulated as values
+        let v = parse(&repeat_count(Expr::U8(2), tuple([ANY_BYTE])), &[1, 2]);
+        assert!(matches!(v, Value::Seq(SeqKind::Strict(_))));
+    }
+
+    #[test]
+    fn read_base_signed() {
+        use crate::numeric::core::TypedConst;
+        use crate::valuetype::SignedIntType;
+
+        fn signed(n: i64, s: SignedIntType) -> Value {
+            Value::Numeric(Rc::new(TypedConst(n.into(), s.into())))
+        }
+
+        let input = [0xFF, 0xFE, 0x00, 0x01];
+        let read = |kind| read_base_value(ReadCtxt::new(&input), kind).map(|(v, _)| v);
+        assert_eq!(read(BaseKind::I8), Some(signed(-1, SignedIntType::I8)));
+        assert_eq!(read(BaseKind::I16BE), Some(signed(-2, SignedIntType::I16)));
+        assert_eq!(
+            read(BaseKind::I16LE),
+            Some(signed(-257, SignedIntType::I16))
+        );
+        assert_eq!(read(BaseKind::U16LE), Some(Value::U16(0xFEFF)));
+        assert_eq!(
+            read(BaseKind::I32BE),
+            Some(signed(-131071, SignedIntType::I32))
+        );
+        assert_eq!(
+            read(BaseKind::I32LE),
+            Some(signed(0x0100FEFF, SignedIntType::I32))
+        );
+        assert_eq!(read(BaseKind::I64BE), None);
+    }
+
+    #[test]
+    fn compile_signed_helpers() {
+        let f = tuple([i16be(), i16le()]);
+        let d = Compiler::compile_one(&f).unwrap();
+        let program = Program::new();
+        let (val, remain) = d
+            .parse(
+                &program,
+                &RunState::new(&program),
+                &Scope::Empty,
+                ReadCtxt::new(&[0xFF, 0xFE, 0xFE, 0xFF, 0x00]),
+            )
+            .unwrap()
+            .into_inner();
+        let Value::Tuple(vs) = val else {
+            panic!("expected tuple, found {val:?}")
+        };
+        assert_eq!(vs.len(), 2);
+        assert_eq!(vs[0], vs[1]);
+        assert_eq!(remain.remaining(), &[0x00]);
+    }
+
+    #[test]
+    fn compile_float_and_fixed() {
+        use crate::FixedType;
+
+        let f = tuple([
+            f32be(),
+            f64le(),
+            fixed_be(FixedType::I16F16),
+            fixed_le(FixedType::I2F14),
+        ]);
+        let d = Compiler::compile_one(&f).unwrap();
+        let mut input = Vec::new();
+        input.extend(1.5f32.to_be_bytes());
+        input.extend((-0.25f64).to_le_bytes());
+        input.extend([0xFF, 0xFE, 0x80, 0x00]); // -1.5 in 16.16
+        input.extend([0x00, 0x40]); // 1.0 in 2.14
+        let program = Program::new();
+        let (val, remain) = d
+            .parse(
+                &program,
+                &RunState::new(&program),
+                &Scope::Empty,
+                ReadCtxt::new(&input),
+            )
+            .unwrap()
+            .into_inner();
+        assert!(remain.remaining().is_empty());
+        let Value::Tuple(vs) = val else {
+            panic!("expected tuple, found {val:?}")
+        };
+        let shown = vs
+            .iter()
+            .map(|v| v.coerce_mapped_value().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(shown, ["1.5", "-0.25", "-1.5", "1.0"]);
+    }
+
     #[test]
     fn compile_alt() {
         let f = alts::<&str>([]);
@@ -2756,6 +3226,97 @@ mod tests {
         accepts(&d, DATA, &[], Value::Branch(0, Box::new(Value::Bool(true))));
     }
 
+    fn record_or_guard_format() -> Format {
+        let kind = |a: u8, b: u8| pat_or([Pattern::U8(a), Pattern::U8(b)]);
+        chain(
+            record([("kind", u8()), ("len", u8())]),
+            "hdr",
+            fmt_match(
+                var("hdr"),
+                [
+                    (
+                        pat_guard(
+                            pat_record([("kind", kind(1, 2)), ("len", bind("n"))]),
+                            expr_gt(var("n"), Expr::U8(4)),
+                        ),
+                        compute(var("n")),
+                    ),
+                    (pat_record([("kind", kind(1, 2))]), compute(Expr::U8(0))),
+                    (
+                        pat_or([
+                            pat_record([("kind", Pattern::U8(3)), ("len", bind("n"))]),
+                            pat_record([("kind", Pattern::U8(4)), ("len", bind("n"))]),
+                        ]),
+                        compute(var("n")),
+                    ),
+                    (Pattern::Wildcard, compute(Expr::U8(255))),
+                ],
+            ),
+        )
+    }
+
+    #[test]
+    fn test_record_or_guard_patterns() {
+        let d = Compiler::compile_one(&record_or_guard_format()).unwrap();
+        let branch = |ix: usize, n: u8| Value::Branch(ix, Box::new(Value::U8(n)));
+
+        accepts(&d, &[1, 5], &[], branch(0, 5));
+        accepts(&d, &[2, 3], &[], branch(1, 0));
+        accepts(&d, &[4, 7], &[], branch(2, 7));
+        accepts(&d, &[9, 9], &[], branch(3, 255));
+    }
+
+    #[test]
+    fn test_record_or_guard_patterns_with_loc() {
+        let program =
+            Compiler::compile_program(&FormatModule::new(), &record_or_guard_format()).unwrap();
+        let branch = |ix: usize, n: u8| Value::Branch(ix, Box::new(Value::U8(n)));
+        for (input, expected) in [
+            ([1, 5], branch(0, 5)),
+            ([2, 3], branch(1, 0)),
+            ([4, 7], branch(2, 7)),
+            ([9, 9], branch(3, 255)),
+        ] {
+            let (p_value, _) = program.run_with_loc(ReadCtxt::new(&input)).unwrap();
+            assert_eq!(p_value.clone_into_value(), expected, "input: {input:?}");
+        }
+
+        // the same arms, as an expression
+        let f = chain(
+            record([("kind", u8()), ("len", u8())]),
+            "hdr",
+            compute(expr_match(
+                var("hdr"),
+                [
+                    (
+                        pat_guard(
+                            pat_record([("kind", pat_or([Pattern::U8(1), Pattern::U8(2)]))]),
+                            expr_gt(record_proj(var("hdr"), "len"), Expr::U8(4)),
+                        ),
+                        Expr::U8(1),
+                    ),
+                    (
+                        pat_or([
+                            pat_record([("kind", Pattern::U8(3)), ("len", bind("n"))]),
+                            pat_record([("kind", Pattern::U8(4)), ("len", bind("n"))]),
+                        ]),
+                        var("n"),
+                    ),
+                    (Pattern::Wildcard, Expr::U8(255)),
+                ],
+            )),
+        );
+        let program = Compiler::compile_program(&FormatModule::new(), &f).unwrap();
+        for (input, expected) in [([1, 5], 1), ([1, 3], 255), ([4, 7], 7)] {
+            let (p_value, _) = program.run_with_loc(ReadCtxt::new(&input)).unwrap();
+            assert_eq!(
+                p_value.clone_into_value(),
+                Value::U8(expected),
+                "input: {input:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_bits() {
         let byte = 0b1101_0110;
@@ -2776,6 +3337,321 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_repeat_between_lookahead() {
+        let f = tuple([
+            repeat_between(Expr::U32(0), Expr::U32(3), is_byte(1)),
+            is_byte(2),
+        ]);
+        let program = Compiler::compile_program(&FormatModule::new(), &f).unwrap();
+        let expected = Value::Tuple(vec![Value::Seq(vec![Value::U8(1)].into()), Value::U8(2)]);
+        let (value, _) = program.run(ReadCtxt::new(&[1, 2])).unwrap();
+        assert_eq!(value, expected);
+        let (p_value, _) = program.run_with_loc(ReadCtxt::new(&[1, 2])).unwrap();
+        assert_eq!(p_value.clone_into_value(), expected);
+        let vm = vm::VmProgram::compile(&program).unwrap();
+        let (vm_value, _) = vm.run(ReadCtxt::new(&[1, 2])).unwrap();
+        assert_eq!(vm_value, expected);
+    }
+
+    #[test]
+    fn test_error_path() {
+        let mut module = FormatModule::new();
+        let flags = module.define_format(
+            "test.flags",
+            record([("header", is_byte(0xFF)), ("flags", byte_in(0x00..=0x3F))]),
+        );
+        let main = module.define_format(
+            "test.main",
+            record([("tag", ANY_BYTE), ("simple", flags.call())]),
+        );
+        let program = Compiler::compile_program(&module, &main.call()).unwrap();
+        let err = program.run(ReadCtxt::new(&[0x00, 0xFF, 0x40])).unwrap_err();
+        assert_eq!(err.offset(), Some(2));
+        assert_eq!(
+            err.path().map(|frame| frame.as_ref()).collect::<Vec<_>>(),
+            ["test.main", "simple", "test.flags", "flags"]
+        );
+        assert_eq!(
+            err.to_string(),
+            "test.main > simple > test.flags > flags @ 0x2: expected ⟦0,63⟧ (found 0x40)"
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        use crate::limits::Limit;
+
+        fn exceeded(err: crate::error::DecodeError) -> LimitExceeded {
+            match *err.err {
+                DecodeErrorKind::LimitExceeded { exceeded, .. } => exceeded,
+                other => panic!("expected LimitExceeded, found {other:?}"),
+            }
+        }
+
+        let mut module = FormatModule::new();
+        let mut nested = module.define_format("test.nest0", ANY_BYTE);
+        for i in 1..5 {
+            nested = module.define_format(format!("test.nest{i}"), nested.call());
+        }
+        let program = Compiler::compile_program(&module, &nested.call()).unwrap();
+        assert!(program.run(ReadCtxt::new(&[0])).is_ok());
+        let program = program.with_limits(Limits::default().max_depth(3));
+        let err = program.run(ReadCtxt::new(&[0])).unwrap_err();
+        assert_eq!(
+            exceeded(err),
+            LimitExceeded {
+                limit: Limit::Depth,
+                max: 3
+            }
+        );
+
+        let huge = repeat_count(Expr::U32(u32::MAX), compute(Expr::U8(0)));
+        let program = Compiler::compile_program(&FormatModule::new(), &huge)
+            .unwrap()
+            .with_limits(Limits::default().max_seq_elements(1000));
+        let err = program.run(ReadCtxt::new(&[])).unwrap_err();
+        assert_eq!(exceeded(err).limit, Limit::SeqElements);
+
+        let program = Compiler::compile_program(&FormatModule::new(), &repeat(ANY_BYTE))
+            .unwrap()
+            .with_limits(Limits::default().max_steps(50));
+        assert!(program.run(ReadCtxt::new(&[0; 10])).is_ok());
+        let err = program.run(ReadCtxt::new(&[0; 100])).unwrap_err();
+        assert_eq!(exceeded(err).limit, Limit::Steps);
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        use crate::Arith;
+        use crate::error::EvalError;
+
+        fn eval_error(err: crate::error::DecodeError) -> EvalError {
+            match *err.err {
+                DecodeErrorKind::Eval { error } => error,
+                other => panic!("expected Eval, found {other:?}"),
+            }
+        }
+
+        let f = chain(ANY_BYTE, "n", compute(sub(var("n"), Expr::U8(2))));
+        let program = Compiler::compile_program(&FormatModule::new(), &f).unwrap();
+        assert_eq!(program.run(ReadCtxt::new(&[5])).unwrap().0, Value::U8(3));
+        let err = program.run(ReadCtxt::new(&[1])).unwrap_err();
+        assert_eq!(eval_error(err), EvalError::Overflow { op: Arith::Sub });
+
+        let f = chain(
+            repeat_count(Expr::U8(2), ANY_BYTE),
+            "xs",
+            compute(index_unchecked(var("xs"), Expr::U32(2))),
+        );
+        let program = Compiler::compile_program(&FormatModule::new(), &f).unwrap();
+        let err = program.run(ReadCtxt::new(&[1, 2])).unwrap_err();
+        assert_eq!(eval_error(err), EvalError::OutOfBounds { index: 2, len: 2 });
+    }
+
+    #[test]
+    fn test_native() {
+        use crate::BaseType;
+        use crate::native::{NativeError, NativeFormat};
+
+        fn take(bytes: &[u8], args: &[Value]) -> Result<(Value, usize), NativeError> {
+            let [Value::U8(n)] = args else {
+                return Err(NativeError::new("expected a single U8 argument"));
+            };
+            let n = usize::from(*n);
+            let data = bytes
+                .get(..n)
+                .ok_or_else(|| NativeError::new("not enough bytes"))?;
+            let data: Vec<Value> = data.iter().copied().map(Value::U8).collect();
+            Ok((Value::Seq(data.into()), n))
+        }
+
+        let mut module = FormatModule::new();
+        let native = module.define_native_format(
+            "test.take",
+            vec![(Label::Borrowed("n"), ValueType::Base(BaseType::U8))],
+            NativeFormat::new(
+                ValueType::Seq(Box::new(ValueType::Base(BaseType::U8))),
+                take,
+                "natives::take",
+            ),
+        );
+        let f = record([
+            ("len", ANY_BYTE),
+            ("data", native.call_args(vec![var("len")])),
+            ("tail", ANY_BYTE),
+        ]);
+        let program = Compiler::compile_program(&module, &f).unwrap();
+        let (value, rest) = program.run(ReadCtxt::new(&[2, 0xA, 0xB, 0xC])).unwrap();
+        assert_eq!(
+            value,
+            Value::record([
+                ("len", Value::U8(2)),
+                (
+                    "data",
+                    Value::Seq(vec![Value::U8(0xA), Value::U8(0xB)].into())
+                ),
+                ("tail", Value::U8(0xC)),
+            ])
+        );
+        assert!(rest.remaining().is_empty());
+
+        let err = program.run(ReadCtxt::new(&[5, 1])).unwrap_err();
+        match *err.err {
+            DecodeErrorKind::Native { error, offset: 1 } => {
+                assert_eq!(error, NativeError::new("not enough bytes"))
+            }
+            other => panic!("expected native error at offset 1, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resync() {
+        let chunk = record([
+            ("marker", is_byte(0xFF)),
+            ("tag", byte_in(0x01..=0x7F)),
+            ("payload", ANY_BYTE),
+        ]);
+        let f = repeat(resync(chunk, [ByteSet::singleton(0xFF)]));
+        let module = FormatModule::new();
+        let program = Compiler::compile_program(&module, &f).unwrap();
+        let input = [0xFF, 0x01, 0xAA, 0xFF, 0x80, 0xBB, 0xFF, 0x02, 0xCC];
+        let (value, rest) = program.run(ReadCtxt::new(&input)).unwrap();
+        assert!(rest.remaining().is_empty());
+        let chunk_value = |tag, payload| {
+            Value::variant(
+                "ok",
+                Value::record([
+                    ("marker", Value::U8(0xFF)),
+                    ("tag", Value::U8(tag)),
+                    ("payload", Value::U8(payload)),
+                ]),
+            )
+        };
+        let Value::Seq(items) = value else {
+            panic!("expected sequence, found {value:?}")
+        };
+        let items = items.into_vec();
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0], chunk_value(0x01, 0xAA));
+        assert_eq!(items[2], chunk_value(0x02, 0xCC));
+        match &items[1] {
+            Value::Variant(label, skipped) if label == "skipped" => {
+                let Value::Seq(data) = skipped.record_proj("data") else {
+                    panic!("expected skipped bytes, found {skipped:?}")
+                };
+                assert_eq!(data.len(), 3);
+                assert!(
+                    matches!(skipped.record_proj("error"), Value::Seq(error) if !error.is_empty())
+                );
+            }
+            other => panic!("expected skipped region, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_resync_propagates_limits() {
+        use crate::limits::Limit;
+
+        let f = resync(repeat(ANY_BYTE), [ByteSet::singleton(0xFF)]);
+        let program = Compiler::compile_program(&FormatModule::new(), &f)
+            .unwrap()
+            .with_limits(Limits::default().max_steps(50));
+        let err = program.run(ReadCtxt::new(&[0; 100])).unwrap_err();
+        assert!(!err.is_recoverable());
+        assert!(matches!(
+            *err.err,
+            DecodeErrorKind::LimitExceeded { exceeded, .. } if exceeded.limit == Limit::Steps
+        ));
+
+        let vm = vm::VmProgram::compile(&program).unwrap();
+        let err = vm.run(ReadCtxt::new(&[0; 100])).unwrap_err();
+        assert!(!err.is_recoverable());
+    }
+
+    #[test]
+    fn test_resync_length_delimited() {
+        let chunk = record([("marker", is_byte(0xFF)), ("payload", ANY_BYTE)]);
+        let f = repeat(record([
+            ("len", ANY_BYTE),
+            ("chunk", slice(var("len"), resync(chunk, []))),
+        ]));
+        let program = Compiler::compile_program(&FormatModule::new(), &f).unwrap();
+        let input = [2, 0xFF, 0xAA, 3, 0x00, 0x01, 0x02, 2, 0xFF, 0xBB];
+        let (value, rest) = program.run(ReadCtxt::new(&input)).unwrap();
+        assert!(rest.remaining().is_empty());
+        let Value::Seq(items) = value else {
+            panic!("expected sequence, found {value:?}")
+        };
+        let items = items.into_vec();
+        assert_eq!(items.len(), 3);
+        match items[1].record_proj("chunk") {
+            Value::Variant(label, skipped) if label == "skipped" => {
+                assert!(matches!(skipped.record_proj("data"), Value::Seq(data) if data.len() == 3));
+            }
+            other => panic!("expected skipped region, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_memoization() {
+        let mut module = FormatModule::new();
+        let body = module.define_format("test.body", repeat_count(Expr::U8(3), is_byte(0xAA)));
+        let f = alts_nondet([
+            ("one", tuple([body.call(), is_byte(0x01)])),
+            ("two", tuple([body.call(), is_byte(0x02)])),
+            ("three", tuple([body.call(), is_byte(0x03)])),
+        ]);
+        let input = [0xAA, 0xAA, 0xAA, 0x03];
+        let program = Compiler::compile_program(&module, &f).unwrap();
+        let (res, stats) = program.run_with_stats(ReadCtxt::new(&input));
+        let (expected, _) = res.unwrap();
+        assert_eq!(stats, MemoStats::default());
+
+        let program = program.with_memoization(true);
+        let (res, stats) = program.run_with_stats(ReadCtxt::new(&input));
+        let (value, rest) = res.unwrap();
+        assert_eq!(value, expected);
+        assert!(rest.remaining().is_empty());
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 1, 1));
+        // every run starts afresh
+        assert_eq!(program.run_with_stats(ReadCtxt::new(&input)).1, stats);
+
+        let (res, stats) = program.run_with_loc_stats(ReadCtxt::new(&input));
+        assert_eq!(res.unwrap().0.clone_into_value(), expected);
+        assert_eq!(stats.hits, 2);
+
+        // reused outcomes are charged against the limits as if they had been decoded over again
+        let min_steps = |program: &Program| {
+            (1..)
+                .find(|&n| {
+                    let program = program.clone().with_limits(Limits::default().max_steps(n));
+                    program.run(ReadCtxt::new(&input)).is_ok()
+                })
+                .unwrap()
+        };
+        assert_eq!(
+            min_steps(&program),
+            min_steps(&program.clone().with_memoization(false))
+        );
+        let limits = Limits::default().max_seq_elements(8);
+        assert!(
+            program
+                .clone()
+                .with_limits(limits)
+                .run(ReadCtxt::new(&input))
+                .is_err()
+        );
+        let limits = Limits::default().max_seq_elements(9);
+        assert!(
+            program
+                .clone()
+                .with_limits(limits)
+                .run(ReadCtxt::new(&input))
+                .is_ok()
+        );
+    }
+
     /// Regression test for the `Next`-context bug in `compile_format` for `RepeatCount`
     /// (and analogous repeat-like formats).
     ///
@@ -2816,4 +3692,148 @@ mod tests {
 
         accepts(&d, data, &[], expected);
     }
+
+    #[test]
+    fn test_seq_combinators() {
+        let xs = || var("xs");
+        let is_odd = |x| expr_eq(rem(x, Expr::U8(2)), Expr::U8(1));
+        let ascending = lambda(
+            "p",
+            expr_lte(tuple_proj(var("p"), 0), tuple_proj(var("p"), 1)),
+        );
+        let f = record([
+            ("xs", repeat_count(Expr::U32(4), ANY_BYTE)),
+            (
+                "out",
+                compute(Expr::Tuple(vec![
+                    seq_filter(lambda("x", is_odd(var("x"))), xs()),
+                    seq_zip(xs(), seq_reverse(xs())),
+                    seq_any(is_odd, xs()),
+                    seq_all(is_odd, xs()),
+                    is_sorted_by(ascending.clone(), xs()),
+                    is_sorted_by(ascending, singleton(Expr::U8(7))),
+                ])),
+            ),
+        ]);
+        let module = FormatModule::new();
+        let program = Compiler::compile_program(&module, &f).unwrap();
+        let (value, _) = program.run(ReadCtxt::new(&[1, 2, 2, 5])).unwrap();
+        let bytes = |bs: &[u8]| Value::Seq(bs.iter().copied().map(Value::U8).collect());
+        let pair = |x, y| Value::Tuple(vec![Value::U8(x), Value::U8(y)]);
+        let expected = Value::Tuple(vec![
+            bytes(&[1, 5]),
+            Value::Seq(SeqKind::Strict(vec![
+                pair(1, 5),
+                pair(2, 2),
+                pair(2, 2),
+                pair(5, 1),
+            ])),
+            Value::Bool(true),
+            Value::Bool(false),
+            Value::Bool(true),
+            Value::Bool(true),
+        ]);
+        assert_eq!(value.record_proj("out"), &expected);
+
+        let (p_value, _) = program.run_with_loc(ReadCtxt::new(&[1, 2, 2, 5])).unwrap();
+        assert_eq!(p_value.clone_into_value().record_proj("out"), &expected);
+
+        let vm = vm::VmProgram::compile(&program).unwrap();
+        let (vm_value, _) = vm.run(ReadCtxt::new(&[1, 2, 2, 5])).unwrap();
+        assert_eq!(vm_value.record_proj("out"), &expected);
+
+        let (value, _) = program.run(ReadCtxt::new(&[3, 2, 7, 9])).unwrap();
+        let Value::Tuple(out) = value.record_proj("out") else {
+            panic!("expected tuple, found {value:?}")
+        };
+        assert_eq!(out[4], Value::Bool(false));
+    }
+
+    #[test]
+    fn test_recursive_function() {
+        let mut module = FormatModule::new();
+        // sum(xs) = if xs == [] then 0 else xs[0] + sum(xs[1..])
+        let sum = module.declare_function(
+            "sum",
+            vec![("xs".into(), ValueType::Seq(Box::new(ValueType::U8)))],
+            ValueType::U32,
+        );
+        let xs = || var("xs");
+        module.define_function_body(
+            &sum,
+            expr_if_else(
+                expr_eq(seq_length(xs()), Expr::U32(0)),
+                Expr::U32(0),
+                add(
+                    as_u32(index_unchecked(xs(), Expr::U32(0))),
+                    sum.call(vec![sub_seq(xs(), Expr::U32(1), pred(seq_length(xs())))]),
+                ),
+            ),
+        );
+        let f = record([
+            ("xs", repeat_count(Expr::U32(4), ANY_BYTE)),
+            ("total", compute(sum.call(vec![var("xs")]))),
+        ]);
+        let program = Compiler::compile_program(&module, &f).unwrap();
+        let input = [1, 2, 3, 250];
+        let (value, _) = program.run(ReadCtxt::new(&input)).unwrap();
+        assert_eq!(value.record_proj("total"), &Value::U32(256));
+
+        let (p_value, _) = program.run_with_loc(ReadCtxt::new(&input)).unwrap();
+        assert_eq!(
+            p_value.clone_into_value().record_proj("total"),
+            &Value::U32(256)
+        );
+
+        let vm = vm::VmProgram::compile(&program).unwrap();
+        let (vm_value, _) = vm.run(ReadCtxt::new(&input)).unwrap();
+        assert_eq!(vm_value.record_proj("total"), &Value::U32(256));
+    }
+
+    #[test]
+    fn test_generic_format() {
+        let mut module = FormatModule::new();
+        let pair = module.define_format("test.pair", tuple([ANY_BYTE, ANY_BYTE]));
+        let prefixed = module.define_generic_format(
+            "test.prefixed",
+            vec![Label::Borrowed("elem")],
+            record([
+                ("len", ANY_BYTE),
+                (
+                    "items",
+                    repeat_count(var("len"), Format::Apply(Label::Borrowed("elem"))),
+                ),
+            ]),
+        );
+        let f = tuple([
+            prefixed.apply(vec![ANY_BYTE]),
+            prefixed.apply(vec![pair.call()]),
+        ]);
+        let input = [2, 1, 2, 1, 3, 4];
+        let prefixed_value = |len: u8, items: Vec<Value>| {
+            Value::record([
+                ("len", Value::U8(len)),
+                ("items", Value::Seq(SeqKind::Strict(items))),
+            ])
+        };
+        let expected = Value::Tuple(vec![
+            prefixed_value(2, vec![Value::U8(1), Value::U8(2)]),
+            prefixed_value(1, vec![Value::Tuple(vec![Value::U8(3), Value::U8(4)])]),
+        ]);
+        let program = Compiler::compile_program(&module, &f).unwrap();
+        let (value, _) = program.run(ReadCtxt::new(&input)).unwrap();
+        assert_eq!(value, expected);
+
+        let (p_value, _) = program.run_with_loc(ReadCtxt::new(&input)).unwrap();
+        assert_eq!(p_value.clone_into_value(), expected);
+
+        let vm = vm::VmProgram::compile(&program).unwrap();
+        let (vm_value, _) = vm.run(ReadCtxt::new(&input)).unwrap();
+        assert_eq!(vm_value, expected);
+
+        let bytes = crate::encoder::Encoder::new(&module)
+            .encode(&f, &expected)
+            .unwrap();
+        assert_eq!(bytes, input);
+    }
 }
@@ -0,0 +1,216 @@
+//! Packrat-style memoization of format calls, for the interpreter.
+//!
+//! Under a [`Format::UnionNondet`](crate::Format::UnionNondet), the same format is liable to be attempted at
+//! the same position once for every enclosing branch that reaches it. When enabled (see
+//! [`Program::with_memoization`](super::Program::with_memoization)), the outcome of each `Decoder::Call` is
+//! recorded against the decoder it invokes, the arguments and views it is passed, and the (possibly
+//! slice-limited) context it reads from, so that repeated attempts reuse it rather than decoding the same
+//! bytes over again.
+//!
+//! The table belongs to a single run (see [`RunState`](super::RunState)), so outcomes are never shared
+//! between runs. Reusing an outcome charges the [`Budget`](crate::limits::Budget) of the run with everything
+//! the original decode consumed, so that memoization never lets a run exceed its limits unnoticed. Calls made
+//! while decoding the output of a `DecodeBytes` (or the bits of a `Bits`) are always evaluated afresh, as
+//! those are not positions within the buffer being run over.
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use crate::error::{DecodeError, DecodeErrorKind, EDecodeResult, ELocDecodeResult};
+use crate::limits::Usage;
+use crate::loc_decoder::ParsedValue;
+use crate::read::ReadCtxt;
+use crate::util::EResult;
+
+use super::Value;
+
+/// Counts of the memoized calls performed over a run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoStats {
+    /// Calls whose outcome was reused from an earlier attempt
+    pub hits: usize,
+    /// Calls that had to be decoded, for lack of an earlier attempt
+    pub misses: usize,
+    /// Number of distinct outcomes recorded
+    pub entries: usize,
+}
+
+impl std::fmt::Display for MemoStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses ({} entries)",
+            self.hits, self.misses, self.entries
+        )
+    }
+}
+
+/// Bounds of a `ReadCtxt` over the buffer being run over.
+///
+/// Every context over that buffer starts where it does (slices only shorten it), so its bounds identify it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Extent {
+    offset: usize,
+    end: usize,
+}
+
+impl Extent {
+    fn of(ctxt: ReadCtxt<'_>) -> Extent {
+        Extent {
+            offset: ctxt.offset,
+            end: ctxt.input.len(),
+        }
+    }
+}
+
+/// Everything the outcome of a `Decoder::Call` depends on.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct MemoKey {
+    decoder: usize,
+    input: Extent,
+    args: Vec<Value>,
+    views: Vec<Extent>,
+}
+
+/// Recorded outcomes, each with the resources the decode that produced it consumed.
+type Table<T, E> = RefCell<HashMap<MemoKey, (EResult<(T, usize), E>, Usage)>>;
+
+/// A recorded outcome, resumed from the context of the call reusing it, with the resources to charge for it.
+type Recalled<'input, T, E> = (EResult<(T, ReadCtxt<'input>), E>, Usage);
+
+#[derive(Debug, Default)]
+pub(crate) struct Memo {
+    enabled: bool,
+    /// Number of enclosing decodes of a buffer other than the one being run over
+    suspended: Cell<usize>,
+    values: Table<Value, DecodeError>,
+    parsed: Table<ParsedValue, DecodeErrorKind<ParsedValue>>,
+    hits: Cell<usize>,
+    misses: Cell<usize>,
+}
+
+impl Memo {
+    pub(crate) fn new(enabled: bool) -> Memo {
+        Memo {
+            enabled,
+            ..Memo::default()
+        }
+    }
+
+    pub(crate) fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits.get(),
+            misses: self.misses.get(),
+            entries: self.values.borrow().len() + self.parsed.borrow().len(),
+        }
+    }
+
+    /// Evaluates `f`, which decodes a buffer other than the one being run over, without memoizing any of the
+    /// calls it makes.
+    pub(crate) fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.suspended.set(self.suspended.get() + 1);
+        let res = f();
+        self.suspended.set(self.suspended.get() - 1);
+        res
+    }
+
+    /// Returns the key under which to memoize a call of `decoder` over `input`, or `None` if the call
+    /// should not be memoized.
+    pub(crate) fn key<'a>(
+        &self,
+        decoder: usize,
+        args: impl IntoIterator<Item = &'a Value>,
+        views: impl IntoIterator<Item = ReadCtxt<'a>>,
+        input: ReadCtxt<'_>,
+    ) -> Option<MemoKey> {
+        if !self.enabled || self.suspended.get() > 0 {
+            return None;
+        }
+        Some(MemoKey {
+            decoder,
+            input: Extent::of(input),
+            args: args.into_iter().cloned().collect(),
+            views: views.into_iter().map(Extent::of).collect(),
+        })
+    }
+
+    /// Returns the recorded outcome of the call identified by `key`, along with the resources that must be
+    /// charged for reusing it.
+    pub(crate) fn recall_value<'input>(
+        &self,
+        key: &MemoKey,
+        input: ReadCtxt<'input>,
+    ) -> Option<(EDecodeResult<(Value, ReadCtxt<'input>)>, Usage)> {
+        self.recall(&self.values, key, input)
+    }
+
+    pub(crate) fn record_value(
+        &self,
+        key: MemoKey,
+        res: &EDecodeResult<(Value, ReadCtxt<'_>)>,
+        usage: Usage,
+        input: ReadCtxt<'_>,
+    ) {
+        // NOTE - whether a limit is exceeded depends on the state of the whole run, not just on the call
+        if let Err(e) = res
+            && matches!(*e.err, DecodeErrorKind::LimitExceeded { .. })
+        {
+            return;
+        }
+        self.record(&self.values, key, res, usage, input)
+    }
+
+    pub(crate) fn recall_parsed<'input>(
+        &self,
+        key: &MemoKey,
+        input: ReadCtxt<'input>,
+    ) -> Option<(ELocDecodeResult<(ParsedValue, ReadCtxt<'input>)>, Usage)> {
+        self.recall(&self.parsed, key, input)
+    }
+
+    pub(crate) fn record_parsed(
+        &self,
+        key: MemoKey,
+        res: &ELocDecodeResult<(ParsedValue, ReadCtxt<'_>)>,
+        usage: Usage,
+        input: ReadCtxt<'_>,
+    ) {
+        self.record(&self.parsed, key, res, usage, input)
+    }
+
+    fn recall<'input, T: Clone, E: Clone>(
+        &self,
+        table: &Table<T, E>,
+        key: &MemoKey,
+        input: ReadCtxt<'input>,
+    ) -> Option<Recalled<'input, T, E>> {
+        let Some((res, usage)) = table.borrow().get(key).cloned() else {
+            self.misses.set(self.misses.get() + 1);
+            return None;
+        };
+        self.hits.set(self.hits.get() + 1);
+        let res = res.map(|ok| ok.map(|(v, end)| (v, input.seek_to(end).unwrap())));
+        Some((res, usage))
+    }
+
+    fn record<T: Clone, E: Clone>(
+        &self,
+        table: &Table<T, E>,
+        key: MemoKey,
+        res: &EResult<(T, ReadCtxt<'_>), E>,
+        usage: Usage,
+        input: ReadCtxt<'_>,
+    ) {
+        let entry = match res {
+            Ok(ok) => {
+                let (_, rest) = ok.as_ref();
+                // a call ends in the same context it began in, but we only rely on this after checking
+                if rest.input.len() != input.input.len() {
+                    return;
+                }
+                Ok(ok.clone().map(|(v, rest)| (v, rest.offset)))
+            }
+            Err(e) => Err(e.clone()),
+        };
+        table.borrow_mut().insert(key, (entry, usage));
+    }
+}
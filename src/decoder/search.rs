@@ -1,5 +1,7 @@
-use crate::Expr;
-use crate::decoder::{Value, seq_kind::SeqKind};
+use crate::decoder::{
+    Value,
+    seq_kind::{Element, SeqKind},
+};
 
 /// Helper trait to apply find_index_by_key_sorted to ParsedValue and Value generically
 pub(crate) trait AsKey {
@@ -46,26 +48,37 @@ impl<T> KeyCache<T> {
         Self(store)
     }
 
-    pub(crate) fn get_or_init(&self, index: usize, f: impl FnOnce() -> T) -> &T {
-        self.0[index].get_or_init(f)
+    pub(crate) fn get_or_try_init<E>(
+        &self,
+        index: usize,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<&T, E> {
+        let cell = &self.0[index];
+        if let Some(value) = cell.get() {
+            return Ok(value);
+        }
+        let value = f()?;
+        Ok(cell.get_or_init(|| value))
     }
 }
 
-pub(crate) fn find_index_by_key_sorted<'a, V, V0, Eval>(
-    f_get_key: &Expr,
+/// Binary-searches `values` (sorted by `get_key`) for an element whose key is `query`, returning its index if found.
+///
+/// Returns the first error produced by `get_key`, if any.
+pub(crate) fn find_index_by_key_sorted<V, V0, E, GetKey>(
     query: &V,
     values: &SeqKind<V0>,
-    evaluate: Eval,
-) -> Option<usize>
+    get_key: GetKey,
+) -> Result<Option<usize>, E>
 where
-    Eval: 'a + Fn(&Expr, &V0) -> V,
+    GetKey: Fn(&V0) -> Result<V, E>,
     V: AsKey,
-    V0: Clone,
+    V0: Element,
 {
     use std::cmp::Ordering;
     // If values is empty, search is trivial
     if values.is_empty() {
-        return None;
+        return Ok(None);
     }
 
     let len = values.len();
@@ -73,34 +86,34 @@ where
     // cache to store keys we have seen after computing them once
     let cache = KeyCache::<V>::new(len);
     // helper closure to keep code below lightweight and more implementation-agnostic
-    let get_key_at_index = |ix: usize| cache.get_or_init(ix, || evaluate(f_get_key, &values[ix]));
+    let get_key_at_index = |ix: usize| cache.get_or_try_init(ix, || get_key(&values[ix]));
 
     // check boundaries, once only, at very start
-    let lower_bound = get_key_at_index(0);
+    let lower_bound = get_key_at_index(0)?;
 
     // don't bother evaluating upper_bound if query <= lower-bound
     match query.compare_as_key(lower_bound) {
-        Ordering::Less => return None,
-        Ordering::Equal => return Some(0),
+        Ordering::Less => return Ok(None),
+        Ordering::Equal => return Ok(Some(0)),
         Ordering::Greater => {
             // skip computing 'upper bound' on singleton list
             if len <= 1 {
-                return None;
+                return Ok(None);
             }
         }
     }
 
     // safe because values cannot be empty
     let last_ix = len - 1;
-    let upper_bound = get_key_at_index(last_ix);
+    let upper_bound = get_key_at_index(last_ix)?;
 
     match query.compare_as_key(upper_bound) {
-        Ordering::Greater => return None,
-        Ordering::Equal => return Some(last_ix),
+        Ordering::Greater => return Ok(None),
+        Ordering::Equal => return Ok(Some(last_ix)),
         Ordering::Less => {
             // skip entire loop when there are no middle values
             if len <= 2 {
-                return None;
+                return Ok(None);
             }
         }
     }
@@ -116,32 +129,34 @@ where
         // condition
         let mid = (lower_bound_ix + upper_bound_ix) / 2;
 
-        let mid_key = get_key_at_index(mid);
+        let mid_key = get_key_at_index(mid)?;
         match query.compare_as_key(mid_key) {
             Ordering::Less => upper_bound_ix = mid - 1,
-            Ordering::Equal => return Some(mid),
+            Ordering::Equal => return Ok(Some(mid)),
             Ordering::Greater => lower_bound_ix = mid + 1,
         }
     }
-    None
+    Ok(None)
 }
 
-pub(crate) fn find_index_by_key_unsorted<'a, V, V0, Eval>(
-    f_get_key: &'a Expr,
+/// Linearly searches `values` for the first element whose key (according to `get_key`) is `query`, returning its index if found.
+///
+/// Returns the first error produced by `get_key`, if any.
+pub(crate) fn find_index_by_key_unsorted<V, V0, E, GetKey>(
     query: &V,
     values: &SeqKind<V0>,
-    evaluate: Eval,
-) -> Option<usize>
+    get_key: GetKey,
+) -> Result<Option<usize>, E>
 where
-    Eval: 'a + Fn(&Expr, &V0) -> V,
+    GetKey: Fn(&V0) -> Result<V, E>,
     V: AsKey,
-    V0: Clone,
+    V0: Element,
 {
     for (ix, v) in values.iter().enumerate() {
-        let key = evaluate(f_get_key, v);
+        let key = get_key(v)?;
         if query.eq_key(&key) {
-            return Some(ix);
+            return Ok(Some(ix));
         }
     }
-    None
+    Ok(None)
 }
@@ -1,15 +1,37 @@
-use std::{borrow::Cow, fmt::Debug, ops::Index};
+use std::{
+    borrow::Cow,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    ops::Index,
+    rc::Rc,
+};
 
-use serde::Serialize;
+use serde::{Serialize, Serializer, ser::SerializeStruct};
 
 /// Represents a sequence of values, which is either explicitly constructed
 /// or yielded through an array-generator term (i.e. [`crate::Expr::Dup`]).
-#[derive(Clone, PartialEq, Debug, Serialize, Hash, Eq)]
-#[serde(tag = "tag", content = "data")]
+///
+/// Sequences consisting solely of bytes are stored compactly (see [`SeqKind::Bytes`]) whenever they are
+/// constructed through `From<Vec<T>>` or `FromIterator<T>`, or decoded by repeating a single-byte format
+/// (in which case they are accumulated as bytes from the outset); this is not observable through any of the
+/// methods below, or through equality, hashing and serialization, all of which treat such a sequence as
+/// the equivalent `Strict` one.
+#[derive(Clone, Debug)]
 // NOTE - T must be clone in order for `Dup` to be well-founded, as non-Clone values cannot be duped
 pub enum SeqKind<T: Clone> {
     Strict(Vec<T>),
     Dup(usize, Box<T>),
+    /// A sequence of elements that each represent a byte, stored as the bytes themselves
+    Bytes(Rc<[u8]>),
+}
+
+/// Element types of a [`SeqKind`], which may represent bytes that can be stored compactly.
+pub trait Element: Clone + 'static {
+    /// Returns a reference to the element representing the byte `b`.
+    fn byte(b: u8) -> &'static Self;
+
+    /// Returns the byte `self` represents, if it can be stored compactly without loss of information.
+    fn to_byte(&self) -> Option<u8>;
 }
 
 /// Abstraction over Value-like constructs that can be iterated over
@@ -17,12 +39,12 @@ pub enum SeqKind<T: Clone> {
 ///
 /// Used to allow for `IntRange` to be represented without allocating a `Vec` of `Value`/`ParsedValue`.
 #[derive(Debug, Clone, PartialEq)]
-pub enum ValueSeq<'a, V: Clone = super::Value> {
+pub enum ValueSeq<'a, V: Element = super::Value> {
     ValueSeq(&'a SeqKind<V>),
     IntRange(std::ops::Range<usize>),
 }
 
-impl<'a, V: Clone> ValueSeq<'a, V> {
+impl<'a, V: Element> ValueSeq<'a, V> {
     pub fn len(&self) -> usize {
         match self {
             ValueSeq::ValueSeq(vs) => vs.len(),
@@ -60,14 +82,14 @@ pub(crate) fn sub_range(
     range.start + start..range.start + start + len
 }
 
-pub enum ValueIter<'a, V: Clone = super::Value> {
+pub enum ValueIter<'a, V: Element = super::Value> {
     ValueIter(Iter<'a, V>),
     IntRange(std::ops::Range<usize>),
 }
 
 impl<'a, V> Iterator for ValueIter<'a, V>
 where
-    V: Clone + From<usize>,
+    V: Element + From<usize>,
 {
     type Item = Cow<'a, V>;
 
@@ -79,7 +101,7 @@ where
     }
 }
 
-impl<'a, V: Clone> IntoIterator for ValueSeq<'a, V>
+impl<'a, V: Element> IntoIterator for ValueSeq<'a, V>
 where
     V: From<usize>,
 {
@@ -95,7 +117,7 @@ where
     }
 }
 
-impl<T: Clone> SeqKind<T> {
+impl<T: Element> SeqKind<T> {
     /// Constructs an empty (strict) `SeqKind` value.
     pub const fn new() -> Self {
         SeqKind::Strict(Vec::new())
@@ -105,6 +127,7 @@ impl<T: Clone> SeqKind<T> {
         match self {
             SeqKind::Strict(vs) => vs.len(),
             SeqKind::Dup(n, _) => *n,
+            SeqKind::Bytes(bs) => bs.len(),
         }
     }
 
@@ -112,11 +135,20 @@ impl<T: Clone> SeqKind<T> {
         matches!(self, SeqKind::Strict(_))
     }
 
+    /// Returns the bytes of the sequence, if it is stored compactly.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            SeqKind::Bytes(bs) => Some(bs),
+            _ => None,
+        }
+    }
+
     /// Returns `true` if the sequence contains no elements.
     pub fn is_empty(&self) -> bool {
         match self {
             SeqKind::Strict(vs) => vs.is_empty(),
             SeqKind::Dup(n, _) => *n == 0,
+            SeqKind::Bytes(bs) => bs.is_empty(),
         }
     }
 
@@ -125,6 +157,7 @@ impl<T: Clone> SeqKind<T> {
         match self {
             SeqKind::Strict(vs) => vs,
             SeqKind::Dup(n, v) => vec![*v; n],
+            SeqKind::Bytes(bs) => bs.iter().map(|b| T::byte(*b).clone()).collect(),
         }
     }
 
@@ -134,6 +167,7 @@ impl<T: Clone> SeqKind<T> {
         match self {
             SeqKind::Strict(vs) => vs.get(ix),
             SeqKind::Dup(n, v) => (ix < *n).then_some(&**v),
+            SeqKind::Bytes(bs) => bs.get(ix).map(|b| T::byte(*b)),
         }
     }
 
@@ -156,14 +190,12 @@ impl<T: Clone> SeqKind<T> {
                     )
                 }
             }
+            SeqKind::Bytes(bs) => SeqKind::Bytes(Rc::from(&bs[start..][..len])),
         }
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &T> {
-        match self {
-            SeqKind::Strict(vs) => Iter::Strict(vs.iter()),
-            SeqKind::Dup(n, v) => Iter::Dup(std::iter::repeat_n(Box::as_ref(v), *n)),
-        }
+        self.into_iter()
     }
 
     pub fn append(self, other: SeqKind<T>) -> SeqKind<T> {
@@ -171,17 +203,65 @@ impl<T: Clone> SeqKind<T> {
             other
         } else if other.is_empty() {
             self
+        } else if let (SeqKind::Bytes(bs0), SeqKind::Bytes(bs1)) = (&self, &other) {
+            SeqKind::Bytes(bs0.iter().chain(bs1.iter()).copied().collect())
         } else {
             // REVIEW - there may be minor optimizations we could leverage, but they would be very marginal yield
             let mut seq0 = self.into_vec();
             let mut seq1 = other.into_vec();
             seq0.append(&mut seq1);
-            SeqKind::Strict(seq0)
+            SeqKind::from(seq0)
         }
     }
 }
 
-impl<T: Clone> Index<usize> for SeqKind<T> {
+impl<T: Element + PartialEq> PartialEq for SeqKind<T> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SeqKind::Bytes(bs0), SeqKind::Bytes(bs1)) => bs0 == bs1,
+            _ => self.len() == other.len() && self.iter().eq(other.iter()),
+        }
+    }
+}
+
+impl<T: Element + Eq> Eq for SeqKind<T> {}
+
+impl<T: Element + Hash> Hash for SeqKind<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for v in self.iter() {
+            v.hash(state);
+        }
+    }
+}
+
+/// Serializes in the form derived for `Strict` and `Dup`, with `Bytes` serialized as the equivalent `Strict`.
+impl<T: Element + Serialize> Serialize for SeqKind<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Elements<'a, T: Clone>(&'a SeqKind<T>);
+
+        impl<T: Element + Serialize> Serialize for Elements<'_, T> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_seq(self.0.iter())
+            }
+        }
+
+        let mut state = serializer.serialize_struct("SeqKind", 2)?;
+        match self {
+            SeqKind::Dup(n, v) => {
+                state.serialize_field("tag", "Dup")?;
+                state.serialize_field("data", &(n, v))?;
+            }
+            SeqKind::Strict(_) | SeqKind::Bytes(_) => {
+                state.serialize_field("tag", "Strict")?;
+                state.serialize_field("data", &Elements(self))?;
+            }
+        }
+        state.end()
+    }
+}
+
+impl<T: Element> Index<usize> for SeqKind<T> {
     type Output = T;
 
     fn index(&self, index: usize) -> &Self::Output {
@@ -193,15 +273,17 @@ impl<T: Clone> Index<usize> for SeqKind<T> {
 pub enum Iter<'a, T> {
     Strict(std::slice::Iter<'a, T>),
     Dup(std::iter::RepeatN<&'a T>),
+    Bytes(std::slice::Iter<'a, u8>),
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
+impl<'a, T: Element> Iterator for Iter<'a, T> {
     type Item = &'a T;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             Iter::Strict(it) => it.next(),
             Iter::Dup(it) => it.next(),
+            Iter::Bytes(it) => it.next().map(|b| T::byte(*b)),
         }
     }
 }
@@ -210,20 +292,22 @@ impl<'a, T> Iterator for Iter<'a, T> {
 pub enum IntoIter<T: Clone> {
     Strict(std::vec::IntoIter<T>),
     Dup(std::iter::RepeatN<T>),
+    Bytes(Rc<[u8]>, std::ops::Range<usize>),
 }
 
-impl<T: Clone> Iterator for IntoIter<T> {
+impl<T: Element> Iterator for IntoIter<T> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             IntoIter::Strict(it) => it.next(),
             IntoIter::Dup(it) => it.next(),
+            IntoIter::Bytes(bs, range) => range.next().map(|ix| T::byte(bs[ix]).clone()),
         }
     }
 }
 
-impl<T: Clone> IntoIterator for SeqKind<T> {
+impl<T: Element> IntoIterator for SeqKind<T> {
     type Item = T;
 
     type IntoIter = IntoIter<T>;
@@ -232,11 +316,15 @@ impl<T: Clone> IntoIterator for SeqKind<T> {
         match self {
             SeqKind::Strict(vs) => IntoIter::Strict(vs.into_iter()),
             SeqKind::Dup(n, v) => IntoIter::Dup(std::iter::repeat_n(*v, n)),
+            SeqKind::Bytes(bs) => {
+                let range = 0..bs.len();
+                IntoIter::Bytes(bs, range)
+            }
         }
     }
 }
 
-impl<'a, T: Clone> IntoIterator for &'a SeqKind<T> {
+impl<'a, T: Element> IntoIterator for &'a SeqKind<T> {
     type Item = &'a T;
 
     type IntoIter = Iter<'a, T>;
@@ -245,18 +333,28 @@ impl<'a, T: Clone> IntoIterator for &'a SeqKind<T> {
         match self {
             SeqKind::Strict(vs) => Iter::Strict(vs.iter()),
             SeqKind::Dup(n, v) => Iter::Dup(std::iter::repeat_n(&**v, *n)),
+            SeqKind::Bytes(bs) => Iter::Bytes(bs.iter()),
         }
     }
 }
 
-impl<T: Clone> FromIterator<T> for SeqKind<T> {
+impl<T: Element> FromIterator<T> for SeqKind<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        SeqKind::Strict(Vec::from_iter(iter))
+        SeqKind::from(Vec::from_iter(iter))
     }
 }
 
-impl<T: Clone> From<Vec<T>> for SeqKind<T> {
+/// Stores `v` compactly if it is a non-empty sequence of bytes, or as a `Strict` sequence otherwise.
+impl<T: Element> From<Vec<T>> for SeqKind<T> {
     fn from(v: Vec<T>) -> Self {
-        SeqKind::Strict(v)
+        if v.is_empty() {
+            return SeqKind::Strict(v);
+        }
+        if v.iter().all(|x| x.to_byte().is_some()) {
+            // NOTE - collecting from the exact-size iterator allocates the bytes only once
+            SeqKind::Bytes(v.iter().map(|x| x.to_byte().unwrap()).collect())
+        } else {
+            SeqKind::Strict(v)
+        }
     }
 }
@@ -0,0 +1,624 @@
+//! Incremental decoding of successive values from an [`std::io::Read`] source.
+//!
+//! The interpreter itself only ever sees a contiguous buffer; [`StreamDecoder`] keeps that buffer as small as
+//! the current value allows by decoding optimistically against whatever has been read so far, and only pulling
+//! in more of the stream (and re-running the decode) when the outcome depended on the end of the buffered bytes.
+//! This covers the lookahead needed by `MatchTree` branches and repetitions, as well as `Peek` and `Slice`,
+//! without any of them needing to know they are reading from a stream.
+//!
+//! When the top-level format is a repetition (`Format::Repeat` or `Format::Repeat1`, possibly through named
+//! formats taking no arguments), its elements are decoded and yielded one at a time: the bytes of each element
+//! are dropped as soon as it has been decoded, and only the element being decoded (or the lookahead deciding
+//! whether another follows) is ever re-run against a longer buffer. Otherwise, each value of the top-level format
+//! is buffered in its entirety (along with any lookahead past it), so it should be self-delimiting with values of
+//! bounded size, such as length-prefixed records. Either way, a value that is still being decoded once the buffer
+//! has reached its maximum size (see [`StreamDecoder::max_buffer`]) is reported as [`StreamError::BufferLimit`]
+//! rather than buffering the remainder of the stream.
+//!
+//! Every value (or element) is decoded in a run of its own, so the [`Limits`](crate::limits::Limits) of the program
+//! apply to each one separately.
+//!
+//! Formats that address the input out of order (`WithRelativeOffset`, and anything involving views) cannot be
+//! decoded this way and are rejected up front.
+//!
+//! Only the interpreter can decode from a stream: generated decoders (and [`VmProgram`](super::vm::VmProgram))
+//! run over a complete buffer, and streaming them is out of scope.
+use std::collections::HashSet;
+use std::io::Read;
+
+use super::{Decoder, Program, Value};
+use crate::error::{DecodeError, DecodeErrorKind, DecodeResult};
+use crate::read::{ReadCtxt, StreamWindow};
+use crate::{Format, FormatModule, Label, MatchTree};
+
+/// Default minimum number of bytes requested from the underlying reader whenever the buffer needs to grow.
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Default maximum number of bytes buffered while decoding a single value.
+const DEFAULT_MAX_BUFFER: usize = 64 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum StreamError {
+    /// The underlying reader failed.
+    Io(std::io::Error),
+    /// The program requires random access to its input, via the named construct.
+    RandomAccess(&'static str),
+    /// Decoding the value starting at absolute offset `start` failed.
+    ///
+    /// Any offsets reported within `error` are relative to `start`.
+    Decode { start: usize, error: DecodeError },
+    /// The value starting at absolute offset `start` was decoded without consuming any input, so decoding
+    /// another would never make progress.
+    NoProgress { start: usize },
+    /// The value starting at absolute offset `start` could not be decoded within the maximum of `max` buffered bytes.
+    BufferLimit { start: usize, max: usize },
+}
+
+impl std::fmt::Display for StreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::Io(err) => write!(f, "error reading stream: {err}"),
+            StreamError::RandomAccess(what) => {
+                write!(
+                    f,
+                    "format cannot be streamed: {what} requires random access to the input"
+                )
+            }
+            StreamError::Decode { start, error } => {
+                write!(f, "in value starting at {start:#X}: {error}")
+            }
+            StreamError::NoProgress { start } => {
+                write!(f, "value at {start:#X} consumed no input")
+            }
+            StreamError::BufferLimit { start, max } => {
+                write!(
+                    f,
+                    "value at {start:#X} does not fit in {max} buffered bytes"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamError::Io(err) => Some(err),
+            StreamError::Decode { error, .. } => Some(error),
+            StreamError::RandomAccess(_)
+            | StreamError::NoProgress { .. }
+            | StreamError::BufferLimit { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for StreamError {
+    fn from(err: std::io::Error) -> Self {
+        StreamError::Io(err)
+    }
+}
+
+/// Decodes successive values of a [`Program`]'s top-level format (or of its elements, if it is a repetition) from
+/// a reader, retaining only the bytes of the value currently being decoded (and any lookahead past it).
+pub struct StreamDecoder<'p, R> {
+    program: &'p Program,
+    /// Top-level repetition whose elements are yielded, if any
+    elements: Option<Elements<'p>>,
+    reader: R,
+    buffer: Vec<u8>,
+    /// Absolute stream offset of `buffer[0]`
+    base: usize,
+    at_eof: bool,
+    chunk_size: usize,
+    max_buffer: usize,
+    /// Largest number of bytes buffered at any one time
+    peak: usize,
+}
+
+impl<'p, R: Read> StreamDecoder<'p, R> {
+    /// Constructs a `StreamDecoder` over `reader`, or returns [`StreamError::RandomAccess`] if `program`
+    /// cannot be decoded without random access to its input.
+    pub fn new(program: &'p Program, reader: R) -> Result<Self, StreamError> {
+        if let Some(what) = program.random_access() {
+            return Err(StreamError::RandomAccess(what));
+        }
+        Ok(StreamDecoder {
+            program,
+            elements: Elements::of(program),
+            reader,
+            buffer: Vec::new(),
+            base: 0,
+            at_eof: false,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            max_buffer: DEFAULT_MAX_BUFFER,
+            peak: 0,
+        })
+    }
+
+    /// Sets the minimum number of bytes to request from the reader whenever more input is needed.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Sets the maximum number of bytes to buffer while decoding a single value (64 MiB by default).
+    pub fn max_buffer(mut self, max_buffer: usize) -> Self {
+        self.max_buffer = max_buffer.max(1);
+        self
+    }
+
+    /// Returns the absolute offset in the stream at which the next value will be decoded.
+    pub fn offset(&self) -> usize {
+        self.base
+    }
+
+    /// Returns the number of bytes currently buffered.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the largest number of bytes that have been buffered at any one time.
+    pub fn peak_buffered(&self) -> usize {
+        self.peak
+    }
+
+    /// Returns `true` if the top-level format is a repetition whose elements are yielded one at a time.
+    pub fn yields_elements(&self) -> bool {
+        self.elements.is_some()
+    }
+
+    /// Decodes the next value (or element) from the stream, returning `Ok(None)` once the stream has been
+    /// exhausted.
+    pub fn decode_next(&mut self) -> Result<Option<Value>, StreamError> {
+        if self.buffer.is_empty() {
+            self.fill(Ord::min(self.chunk_size, self.max_buffer))?;
+            if self.buffer.is_empty() {
+                return Ok(None);
+            }
+        }
+        let Some(elements) = self.elements else {
+            return self.decode(&self.program.decoders[0].0, &[]);
+        };
+        let another = match elements.kind {
+            // the first element of a `Repeat1` is decoded unconditionally
+            RepeatKind::Until if elements.first => true,
+            kind => {
+                let start = self.base;
+                let Some(index) = self.with_lookahead(|input| elements.tree.matches(input))? else {
+                    let error = DecodeErrorKind::NoValidBranch { offset: 0 }.into();
+                    return Err(StreamError::Decode { start, error });
+                };
+                // `While` repeats on the first branch of its tree, `Until` stops on it
+                (index == 0) == (kind == RepeatKind::While)
+            }
+        };
+        if !another {
+            if self.buffer.is_empty() {
+                return Ok(None);
+            }
+            let start = self.base;
+            match elements.kind {
+                // a fresh repetition would end just the same, without consuming any input
+                RepeatKind::While => return Err(StreamError::NoProgress { start }),
+                // a fresh repetition begins with an element of its own
+                RepeatKind::Until => {}
+            }
+        }
+        if let Some(elements) = &mut self.elements {
+            elements.first = false;
+        }
+        self.decode(elements.element, elements.path)
+    }
+
+    /// Decodes a single value of `d`, one of the decoders of the program, from the start of the buffer,
+    /// attributing any error to the named formats in `path` (innermost first).
+    fn decode(&mut self, d: &'p Decoder, path: &[Label]) -> Result<Option<Value>, StreamError> {
+        let program = self.program;
+        let start = self.base;
+        let res = self.with_lookahead(|input| {
+            let (value, rest) = program.run_decoder(d, input)?;
+            DecodeResult::Ok((value, rest.offset))
+        })?;
+        match res {
+            Ok((_, 0)) => Err(StreamError::NoProgress { start }),
+            Ok((value, consumed)) => {
+                self.buffer.drain(..consumed);
+                self.base += consumed;
+                Ok(Some(value))
+            }
+            Err(error) => {
+                let error = path
+                    .iter()
+                    .fold(error, |error, name| error.within(name.clone()));
+                Err(StreamError::Decode { start, error })
+            }
+        }
+    }
+
+    /// Evaluates `f` over the buffered bytes, pulling in more of the stream (and evaluating it again) for as
+    /// long as its outcome depended on the end of the buffer.
+    fn with_lookahead<T>(&mut self, f: impl Fn(ReadCtxt<'_>) -> T) -> Result<T, StreamError> {
+        loop {
+            let window = StreamWindow::new(self.base, self.buffer.len());
+            let res = f(ReadCtxt::streaming(&self.buffer, &window));
+            if !window.is_exhausted() || self.at_eof {
+                return Ok(res);
+            }
+            if self.buffer.len() >= self.max_buffer {
+                return Err(StreamError::BufferLimit {
+                    start: self.base,
+                    max: self.max_buffer,
+                });
+            }
+            // double the buffer so that re-decoding stays linear overall
+            let n = Ord::max(self.buffer.len(), self.chunk_size);
+            self.fill(Ord::min(n, self.max_buffer - self.buffer.len()))?;
+        }
+    }
+
+    /// Appends up to `n` more bytes from the reader to the buffer.
+    fn fill(&mut self, n: usize) -> std::io::Result<()> {
+        let got = (&mut self.reader)
+            .take(n as u64)
+            .read_to_end(&mut self.buffer)?;
+        if got < n {
+            self.at_eof = true;
+        }
+        self.peak = self.peak.max(self.buffer.len());
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for StreamDecoder<'_, R> {
+    type Item = Result<Value, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.decode_next().transpose()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RepeatKind {
+    /// `Decoder::While`, as compiled from `Format::Repeat`
+    While,
+    /// `Decoder::Until`, as compiled from `Format::Repeat1`
+    Until,
+}
+
+/// A repetition at the top level of a program, whose elements are decoded one at a time.
+#[derive(Clone, Copy)]
+struct Elements<'p> {
+    kind: RepeatKind,
+    tree: &'p MatchTree,
+    element: &'p Decoder,
+    /// Named formats called on the way to the repetition, innermost first
+    path: &'p [Label],
+    /// Whether the next element is the first of a `Repeat1`
+    first: bool,
+}
+
+impl<'p> Elements<'p> {
+    fn of(program: &'p Program) -> Option<Elements<'p>> {
+        let mut d = &program.decoders[0].0;
+        let mut path = None;
+        // NOTE - bounded in case of a cycle of calls that never reaches a repetition
+        for _ in 0..program.decoders.len() {
+            match d {
+                Decoder::Call(n, args, views) if args.is_empty() && views.is_empty() => {
+                    // only the innermost name is reported, as for an error within the top-level value
+                    path = program.format_names[*n].as_ref().map(std::slice::from_ref);
+                    d = &program.decoders[*n].0;
+                }
+                Decoder::While(tree, element) | Decoder::Until(tree, element) => {
+                    let kind = match d {
+                        Decoder::While(..) => RepeatKind::While,
+                        _ => RepeatKind::Until,
+                    };
+                    return Some(Elements {
+                        kind,
+                        tree,
+                        element,
+                        path: path.unwrap_or_default(),
+                        first: true,
+                    });
+                }
+                _ => return None,
+            }
+        }
+        None
+    }
+}
+
+/// Returns the format of the values a [`StreamDecoder`] yields for a program compiled from `format`: that of
+/// its elements if it is a repetition (see [`StreamDecoder::yields_elements`]), or `format` itself otherwise.
+pub fn streamed_format<'a>(module: &'a FormatModule, format: &'a Format) -> &'a Format {
+    let mut f = format;
+    for _ in 0..=module.formats.len() {
+        match f {
+            Format::ItemVar(level, args, views) if args.is_empty() && views.is_empty() => {
+                f = module.get_format(*level);
+            }
+            Format::Hint(_, a) => f = a,
+            Format::Repeat(a) | Format::Repeat1(a) => return a,
+            _ => break,
+        }
+    }
+    format
+}
+
+impl Program {
+    /// Returns the name of the first construct found that would require random access to the input,
+    /// or `None` if the program can be decoded from a stream.
+    ///
+    /// Decoders that only run over buffers of their own (i.e. under `Bits` or `DecodeBytes`) are not
+    /// considered.
+    pub fn random_access(&self) -> Option<&'static str> {
+        let mut visited = HashSet::new();
+        random_access(self, &self.decoders[0].0, &mut visited)
+    }
+}
+
+fn random_access(
+    program: &Program,
+    d: &Decoder,
+    visited: &mut HashSet<usize>,
+) -> Option<&'static str> {
+    match d {
+        Decoder::Call(n, _, views) => {
+            if !views.is_empty() {
+                return Some("view argument");
+            }
+            if visited.insert(*n) {
+                random_access(program, &program.decoders[*n].0, visited)
+            } else {
+                None
+            }
+        }
+        Decoder::WithRelativeOffset(..) => Some("WithRelativeOffset"),
+        Decoder::LetView(..) => Some("LetView"),
+        Decoder::ParseFromView(..) => Some("ParseFromView"),
+        Decoder::CaptureBytes(..) => Some("CaptureBytes"),
+        Decoder::ReadArray(..) => Some("ReadArray"),
+        Decoder::ReifyView(..) => Some("ReifyView"),
+        Decoder::Pos
+        | Decoder::Fail
+        | Decoder::EndOfInput
+        | Decoder::Align(_)
+        | Decoder::Byte(_)
+        | Decoder::Compute(_)
+        | Decoder::Apply(_)
+        | Decoder::SkipRemainder
+        | Decoder::Native(..)
+        | Decoder::LiftedOption(None)
+        | Decoder::Phantom
+        | Decoder::Bits(_)
+        | Decoder::DecodeBytes(..) => None,
+        Decoder::Parallel(ds)
+        | Decoder::Branch(_, ds)
+        | Decoder::Tuple(ds)
+        | Decoder::Sequence(ds) => ds.iter().find_map(|d| random_access(program, d, visited)),
+        Decoder::Match(_, branches) => branches
+            .iter()
+            .find_map(|(_, d)| random_access(program, d, visited)),
+        Decoder::LetFormat(d0, _, d1) | Decoder::MonadSeq(d0, d1) => {
+            random_access(program, d0, visited).or_else(|| random_access(program, d1, visited))
+        }
+        Decoder::Variant(_, d)
+        | Decoder::While(_, d)
+        | Decoder::Until(_, d)
+        | Decoder::RepeatCount(_, d)
+        | Decoder::RepeatUntilLast(_, d)
+        | Decoder::RepeatUntilSeq(_, d)
+        | Decoder::Maybe(_, d)
+        | Decoder::Peek(d)
+        | Decoder::PeekNot(d)
+        | Decoder::Slice(_, d)
+        | Decoder::Map(d, _)
+        | Decoder::Where(d, _)
+        | Decoder::Let(_, _, d)
+        | Decoder::Dynamic(_, _, d)
+        | Decoder::RepeatBetween(_, _, _, d)
+        | Decoder::ForEach(_, _, d)
+        | Decoder::AccumUntil(_, _, _, _, d)
+        | Decoder::LiftedOption(Some(d))
+        | Decoder::Permit(d, _)
+        | Decoder::Resync(d, _) => random_access(program, d, visited),
+        #[cfg(feature = "format_enforce")]
+        Decoder::Enforce(d) => random_access(program, d, visited),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::Compiler;
+    use crate::helper::*;
+    use crate::{Expr, Format, FormatModule};
+
+    fn chunk_module() -> (FormatModule, Format) {
+        let mut module = FormatModule::new();
+        // a length-prefixed chunk, whose payload is a run of 0xAA followed by a run of 0x01
+        let chunk = module.define_format(
+            "test.chunk",
+            record([
+                ("len", ANY_BYTE),
+                (
+                    "data",
+                    slice(
+                        var("len"),
+                        record([
+                            ("run", repeat(is_byte(0xAA))),
+                            ("rest", repeat(is_byte(0x01))),
+                        ]),
+                    ),
+                ),
+                ("pos", Format::Pos),
+            ]),
+        );
+        (module, chunk.call())
+    }
+
+    #[test]
+    fn stream_matches_whole_decode() {
+        let (module, format) = chunk_module();
+        let program = Compiler::compile_program(&module, &format).unwrap();
+        let mut input = Vec::new();
+        for len in [3u8, 0, 200, 5].repeat(20) {
+            input.push(len);
+            input.extend(std::iter::repeat_n(0xAA, len as usize / 2));
+            input.extend(std::iter::repeat_n(0x01, len as usize - len as usize / 2));
+        }
+
+        let mut expected = Vec::new();
+        let mut ctxt = ReadCtxt::new(&input);
+        while ctxt.offset < input.len() {
+            let (value, rest) = program.run(ctxt).unwrap();
+            expected.push(value);
+            ctxt = rest;
+        }
+
+        let mut stream = StreamDecoder::new(&program, &input[..])
+            .unwrap()
+            .chunk_size(1);
+        let mut actual = Vec::new();
+        while let Some(value) = stream.decode_next().unwrap() {
+            assert!(stream.buffered() < 512);
+            actual.push(value);
+        }
+        assert_eq!(stream.offset(), input.len());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn stream_peak_buffer_is_bounded_by_value_size() {
+        let (module, format) = chunk_module();
+        let program = Compiler::compile_program(&module, &format).unwrap();
+        let mut input = Vec::new();
+        for len in (0..=255u8).cycle().take(4096) {
+            input.push(len);
+            input.extend(std::iter::repeat_n(0xAA, len as usize / 2));
+            input.extend(std::iter::repeat_n(0x01, len as usize - len as usize / 2));
+        }
+        assert!(input.len() > 512 * 1024);
+
+        let mut stream = StreamDecoder::new(&program, &input[..])
+            .unwrap()
+            .chunk_size(16);
+        let mut count = 0;
+        while stream.decode_next().unwrap().is_some() {
+            count += 1;
+        }
+        assert_eq!(count, 4096);
+        assert_eq!(stream.offset(), input.len());
+        // a value spans at most 256 bytes, plus a byte of lookahead, rounded up by the doubling of the buffer
+        assert!(stream.peak_buffered() <= 1024);
+    }
+
+    #[test]
+    fn stream_reports_buffer_limit() {
+        let format = record([("bytes", repeat(ANY_BYTE))]);
+        let program = Compiler::compile_program(&FormatModule::new(), &format).unwrap();
+        let input = vec![0; 64 * 1024];
+        let mut stream = StreamDecoder::new(&program, &input[..])
+            .unwrap()
+            .chunk_size(16)
+            .max_buffer(1000);
+        match stream.next() {
+            Some(Err(StreamError::BufferLimit {
+                start: 0,
+                max: 1000,
+            })) => {}
+            other => panic!("expected buffer limit error at offset 0, found {other:?}"),
+        }
+        assert_eq!(stream.peak_buffered(), 1000);
+    }
+
+    #[test]
+    fn stream_yields_elements_of_repetition() {
+        let (mut module, chunk) = chunk_module();
+        let chunks = module.define_format("test.chunks", repeat(chunk.clone()));
+        let mut input = Vec::new();
+        for len in (0..=255u8).cycle().take(4096) {
+            input.push(len);
+            input.extend(std::iter::repeat_n(0xAA, len as usize / 2));
+            input.extend(std::iter::repeat_n(0x01, len as usize - len as usize / 2));
+        }
+
+        for format in [chunks.call(), repeat1(chunk.clone())] {
+            assert_eq!(streamed_format(&module, &format), &chunk);
+            let program = Compiler::compile_program(&module, &format).unwrap();
+            let (whole, _) = program.run(ReadCtxt::new(&input)).unwrap();
+            let expected = whole
+                .get_sequence()
+                .unwrap()
+                .into_iter()
+                .collect::<Vec<_>>();
+
+            let mut stream = StreamDecoder::new(&program, &input[..])
+                .unwrap()
+                .chunk_size(16);
+            assert!(stream.yields_elements());
+            let mut actual = Vec::new();
+            while let Some(value) = stream.decode_next().unwrap() {
+                actual.push(value);
+            }
+            assert_eq!(stream.offset(), input.len());
+            assert_eq!(actual.len(), 4096);
+            assert!(actual.iter().eq(expected.iter().map(|v| v.as_ref())));
+            // only a single element (and the lookahead past it) is ever buffered
+            assert!(stream.peak_buffered() <= 1024);
+        }
+    }
+
+    #[test]
+    fn stream_repeated_bytes_without_buffering() {
+        let program = Compiler::compile_program(&FormatModule::new(), &repeat(ANY_BYTE)).unwrap();
+        let input = vec![0; 64 * 1024];
+        let mut stream = StreamDecoder::new(&program, &input[..])
+            .unwrap()
+            .chunk_size(16)
+            .max_buffer(1000);
+        assert_eq!(stream.by_ref().map(Result::unwrap).count(), input.len());
+        assert!(stream.peak_buffered() <= 32);
+    }
+
+    #[test]
+    fn stream_reports_repetition_without_progress() {
+        let program =
+            Compiler::compile_program(&FormatModule::new(), &repeat(is_byte(0x00))).unwrap();
+        let mut stream = StreamDecoder::new(&program, &[0x00, 0x00, 0x01][..]).unwrap();
+        assert_eq!(stream.next().unwrap().unwrap(), Value::U8(0));
+        assert_eq!(stream.next().unwrap().unwrap(), Value::U8(0));
+        assert!(matches!(
+            stream.next(),
+            Some(Err(StreamError::NoProgress { start: 2 }))
+        ));
+    }
+
+    #[test]
+    fn stream_reports_truncated_value() {
+        let (module, format) = chunk_module();
+        let program = Compiler::compile_program(&module, &format).unwrap();
+        let input = [1, 0xAA, 4, 0xAA];
+        let mut stream = StreamDecoder::new(&program, &input[..]).unwrap();
+        assert!(stream.next().unwrap().is_ok());
+        match stream.next() {
+            Some(Err(StreamError::Decode { start: 2, .. })) => {}
+            other => panic!("expected decode error at offset 2, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn stream_rejects_random_access() {
+        let format = Format::WithRelativeOffset(
+            Box::new(Expr::U32(0)),
+            Box::new(Expr::U32(4)),
+            Box::new(ANY_BYTE),
+        );
+        let program = Compiler::compile_program(&FormatModule::new(), &format).unwrap();
+        assert!(matches!(
+            StreamDecoder::new(&program, std::io::empty()),
+            Err(StreamError::RandomAccess("WithRelativeOffset"))
+        ));
+    }
+}
@@ -3,12 +3,13 @@ use std::rc::Rc;
 use num_bigint::BigInt;
 use serde::Serialize;
 
+use crate::error::{EvalError, EvalResult};
 use crate::numeric::core::{TypedConst, Value as NumValue};
-use crate::{Arith, IntRel, IntoLabel, Label, Pattern, UnaryOp};
+use crate::{Arith, Expr, FixedType, IntRel, IntoLabel, Label, Pattern, UnaryOp};
 
 use super::{
     MultiScope, Scope,
-    seq_kind::{SeqKind, ValueSeq},
+    seq_kind::{Element, SeqKind, ValueSeq},
 };
 
 impl From<NumValue> for Value {
@@ -18,6 +19,90 @@ impl From<NumValue> for Value {
     }
 }
 
+/// IEEE-754 floating-point value, held by its bit-pattern so that [`Value`] can remain `Eq + Hash`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum FloatValue {
+    F32(u32),
+    F64(u64),
+}
+
+impl FloatValue {
+    /// Returns the (lossless) `f64` representation of the float.
+    pub fn to_f64(self) -> f64 {
+        match self {
+            FloatValue::F32(bits) => f64::from(f32::from_bits(bits)),
+            FloatValue::F64(bits) => f64::from_bits(bits),
+        }
+    }
+}
+
+impl std::fmt::Display for FloatValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            FloatValue::F32(bits) => write!(f, "{:?}", f32::from_bits(bits)),
+            FloatValue::F64(bits) => write!(f, "{:?}", f64::from_bits(bits)),
+        }
+    }
+}
+
+impl Serialize for FloatValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match *self {
+            FloatValue::F32(bits) => serializer.serialize_f32(f32::from_bits(bits)),
+            FloatValue::F64(bits) => serializer.serialize_f64(f64::from_bits(bits)),
+        }
+    }
+}
+
+/// Fixed-point value, held as the raw bits of its underlying machine-integer representation.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct FixedValue {
+    ty: FixedType,
+    bits: u64,
+}
+
+impl FixedValue {
+    /// Constructs a `FixedValue` of type `ty` from the low `ty.width()` bits of `bits`.
+    pub fn from_bits(ty: FixedType, bits: u64) -> Self {
+        let mask = u64::MAX >> (64 - ty.width() as u32);
+        FixedValue {
+            ty,
+            bits: bits & mask,
+        }
+    }
+
+    pub fn fixed_type(&self) -> FixedType {
+        self.ty
+    }
+
+    /// Returns the raw (unsigned) bits of the representation.
+    pub fn to_bits(&self) -> u64 {
+        self.bits
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.ty.to_f64(self.bits)
+    }
+}
+
+impl std::fmt::Display for FixedValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.to_f64())
+    }
+}
+
+impl Serialize for FixedValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize)]
 #[serde(tag = "tag", content = "data")]
 pub enum Value {
@@ -28,6 +113,8 @@ pub enum Value {
     U64(u64),
     Char(char),
     Usize(usize),
+    Float(FloatValue),
+    Fixed(FixedValue),
     // TODO[epic=embedded-num] - implement proper support for Numeric
     Numeric(Rc<TypedConst>),
     View {
@@ -78,6 +165,33 @@ impl From<usize> for Value {
     }
 }
 
+/// Table of the values of every byte, from which `SeqKind::Bytes` lends its elements.
+///
+/// As a `const` rather than a `static`, the table need not be `Sync`; its promoted reference is nonetheless `'static`.
+const BYTE_VALUES: &[Value; 256] = &{
+    let mut values = [const { Value::U8(0) }; 256];
+    let mut b = 1;
+    while b < 256 {
+        // NOTE - the placeholder being replaced owns nothing, but has a destructor that cannot run at compile-time
+        std::mem::forget(std::mem::replace(&mut values[b], Value::U8(b as u8)));
+        b += 1;
+    }
+    values
+};
+
+impl Element for Value {
+    fn byte(b: u8) -> &'static Value {
+        &BYTE_VALUES[usize::from(b)]
+    }
+
+    fn to_byte(&self) -> Option<u8> {
+        match self {
+            Value::U8(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
 const MAX_SEQ_LEN: usize = 64;
 
 impl std::fmt::Display for Value {
@@ -90,6 +204,8 @@ impl std::fmt::Display for Value {
             Value::U64(i) => write!(f, "{i}"),
             Value::Char(c) => write!(f, "{c:?}"),
             Value::Usize(i) => write!(f, "{i}"),
+            Value::Float(x) => write!(f, "{x}"),
+            Value::Fixed(x) => write!(f, "{x}"),
             Value::Numeric(n) => write!(f, "{n}"),
             Value::View { offset } => write!(f, "View[+{offset}]"),
             Value::EnumFromTo(r) => write!(f, "{r:?}"),
@@ -123,7 +239,7 @@ impl std::fmt::Display for Value {
             }
             Value::Seq(s_kind) => match s_kind {
                 SeqKind::Dup(n, v) => write!(f, "[{v}; {n}]"),
-                SeqKind::Strict(vs) => {
+                vs @ (SeqKind::Strict(_) | SeqKind::Bytes(_)) => {
                     if vs.len() > MAX_SEQ_LEN {
                         write!(f, "[...; {}]", vs.len())
                     } else {
@@ -177,8 +293,8 @@ impl Value {
         &'a self,
         scope: &mut MultiScope<'a>,
         pattern: &Pattern,
-    ) -> bool {
-        match pattern {
+    ) -> EvalResult<bool> {
+        let is_match = match pattern {
             Pattern::Binding(name) => {
                 scope.push(name.clone(), self);
                 true
@@ -236,8 +352,8 @@ impl Value {
             Pattern::Tuple(ps) => match self {
                 Value::Tuple(vs) if ps.len() == vs.len() => {
                     for (p, v) in Iterator::zip(ps.iter(), vs.iter()) {
-                        if !v.matches_inner(scope, p) {
-                            return false;
+                        if !v.matches_inner(scope, p)? {
+                            return Ok(false);
                         }
                     }
                     true
@@ -247,8 +363,8 @@ impl Value {
             Pattern::Seq(ps) => match self {
                 Value::Seq(vs) if ps.len() == vs.len() => {
                     for (p, v) in Iterator::zip(ps.iter(), vs.iter()) {
-                        if !v.matches_inner(scope, p) {
-                            return false;
+                        if !v.matches_inner(scope, p)? {
+                            return Ok(false);
                         }
                     }
                     true
@@ -256,15 +372,70 @@ impl Value {
                 _ => false,
             },
             Pattern::Variant(label0, p) => match self {
-                Value::Variant(label1, v) if label0 == label1 => v.matches_inner(scope, p),
+                Value::Variant(label1, v) if label0 == label1 => v.matches_inner(scope, p)?,
                 _ => false,
             },
             Pattern::Option(None) => matches!(self, Value::Option(None)),
             Pattern::Option(Some(p)) => match self {
-                Value::Option(Some(v)) => v.matches_inner(scope, p),
+                Value::Option(Some(v)) => v.matches_inner(scope, p)?,
                 _ => false,
             },
+            Pattern::Record(pfields) => match self {
+                Value::Record(fields) => {
+                    for (label, p) in pfields.iter() {
+                        // NOTE - record fields are often the result of a parse, and so may be wrapped in `Branch` or `Mapped`
+                        let v = match fields.iter().find(|(l, _)| l == label) {
+                            Some((_, v)) => v.coerce_nominal_value(),
+                            None => panic!("{label} not found in record"),
+                        };
+                        if !v.matches_inner(scope, p)? {
+                            return Ok(false);
+                        }
+                    }
+                    true
+                }
+                _ => false,
+            },
+            Pattern::Or(alts) => {
+                for p in alts.iter() {
+                    if self.matches_alternative(scope, p, None)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            // NOTE - as with Rust match-guards, the guard is checked against each matching alternative in turn
+            Pattern::Guard(p, guard) if p.has_or() => {
+                for alt in p.alternatives().iter() {
+                    if self.matches_alternative(scope, alt, Some(guard))? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            Pattern::Guard(p, guard) => self.matches_alternative(scope, p, Some(guard))?,
+        };
+        Ok(is_match)
+    }
+
+    /// Attempts to match `self` against `pattern` and then check `guard`, if any, discarding any
+    /// bindings made to `scope` on failure.
+    fn matches_alternative<'a>(
+        &'a self,
+        scope: &mut MultiScope<'a>,
+        pattern: &Pattern,
+        guard: Option<&Expr>,
+    ) -> EvalResult<bool> {
+        let mark = scope.entries.len();
+        let is_match = self.matches_inner(scope, pattern)?
+            && match guard {
+                None => true,
+                Some(guard) => guard.eval_value(&Scope::Multi(scope))?.unwrap_bool(),
+            };
+        if !is_match {
+            scope.entries.truncate(mark);
         }
+        Ok(is_match)
     }
 
     /// Given a `Value` and a `Pattern` to attempt to match it to, returns `Some(new_scope)` if
@@ -272,16 +443,19 @@ impl Value {
     /// `scope` that contains all new bindings produced through the pattern-match), and `None`
     /// if it does not match the pattern in question.
     ///
+    /// Returns an error if the evaluation of a guard of the pattern fails.
+    ///
     /// Used for `Decoder::Match`, `Decoder::Destructure`, and evaluation of `Expr::Match`.
     pub(crate) fn matches<'a>(
         &'a self,
         scope: &'a Scope<'a>,
         pattern: &Pattern,
-    ) -> Option<MultiScope<'a>> {
+    ) -> EvalResult<Option<MultiScope<'a>>> {
         let mut pattern_scope = MultiScope::new(scope);
-        self.coerce_nominal_value()
-            .matches_inner(&mut pattern_scope, pattern)
-            .then_some(pattern_scope)
+        let is_match = self
+            .coerce_nominal_value()
+            .matches_inner(&mut pattern_scope, pattern)?;
+        Ok(is_match.then_some(pattern_scope))
     }
 
     /// Higher-level version of `coerce_mapped_value` that also unwraps any `Value::Permit(Err)`
@@ -326,6 +500,19 @@ impl Value {
         }
     }
 
+    /// Returns the bytes of a sequence of `U8` values, without copying them if they are stored compactly.
+    ///
+    /// Panics if `self` is not such a sequence.
+    pub(crate) fn get_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        if let Value::Seq(seq) = self
+            && let Some(bytes) = seq.as_bytes()
+        {
+            return std::borrow::Cow::Borrowed(bytes);
+        }
+        let seq_vals = self.get_sequence().expect("bad type for DecodeBytes input");
+        std::borrow::Cow::Owned(seq_vals.into_iter().map(|v| v.get_as_u8()).collect())
+    }
+
     pub(crate) fn is_boolean(&self) -> bool {
         matches!(self.coerce_mapped_value(), Value::Bool(_))
     }
@@ -347,6 +534,18 @@ impl Value {
         Value::Variant(label.into(), value.into())
     }
 
+    /// Value of a [`Format::Resync`](crate::Format::Resync) that failed with `error`, after which `data` was skipped over.
+    pub fn resync_skipped(error: impl std::fmt::Display, data: &[u8]) -> Value {
+        let bytes = |bytes: &[u8]| Value::Seq(SeqKind::Bytes(Rc::from(bytes)));
+        Value::variant(
+            "skipped",
+            Value::record([
+                ("error", bytes(error.to_string().as_bytes())),
+                ("data", bytes(data)),
+            ]),
+        )
+    }
+
     /// Unwraps any compatible numeric-typed `Value` and returns the contained number as a `usize`.
     ///
     /// # Panics
@@ -378,6 +577,35 @@ impl Value {
         }
     }
 
+    /// Reinterprets a `Value::U32` or `Value::U64` as the IEEE-754 float with the same bit-pattern.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not `U32` or `U64`.
+    pub(crate) fn float_from_bits(self) -> Value {
+        match self {
+            Value::U32(x) => Value::Float(FloatValue::F32(x)),
+            Value::U64(x) => Value::Float(FloatValue::F64(x)),
+            other => panic!("expected U32 or U64 for bitwise float-cast, found {other:?}"),
+        }
+    }
+
+    /// Reinterprets an unsigned machine-integer value as a fixed-point value of type `fx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is not an unsigned machine-integer.
+    pub(crate) fn fixed_from_bits(fx: FixedType, bits: Value) -> Value {
+        let raw = match bits {
+            Value::U8(x) => u64::from(x),
+            Value::U16(x) => u64::from(x),
+            Value::U32(x) => u64::from(x),
+            Value::U64(x) => x,
+            other => panic!("FixedFromBits: expected U8, U16, U32, or U64, found {other:?}"),
+        };
+        Value::Fixed(FixedValue::from_bits(fx, raw))
+    }
+
     pub(crate) fn unwrap_tuple(self) -> Vec<Value> {
         match self {
             Value::Tuple(values) => values,
@@ -393,6 +621,118 @@ impl Value {
     }
 }
 
+/// Integer conversions and byte-assemblies, as performed by `Expr::AsU8`, `Expr::U16Be` and the like.
+impl Value {
+    pub(crate) fn cast_u8(self) -> Value {
+        match self {
+            Value::U8(x) => Value::U8(x),
+            Value::U16(x) => Value::U8(
+                u8::try_from(x)
+                    .unwrap_or_else(|err| panic!("cannot perform AsU8 cast on u16 {x}: {err}")),
+            ),
+            Value::U32(x) => Value::U8(
+                u8::try_from(x)
+                    .unwrap_or_else(|err| panic!("cannot perform AsU8 cast on u32 {x}: {err}")),
+            ),
+            Value::U64(x) => Value::U8(
+                u8::try_from(x)
+                    .unwrap_or_else(|err| panic!("cannot perform AsU8 cast on u64 {x}: {err}")),
+            ),
+            Value::Usize(x) => Value::U8(
+                u8::try_from(x)
+                    .unwrap_or_else(|err| panic!("cannot perform AsU8 cast on usize {x}: {err}")),
+            ),
+            x => panic!("cannot convert {x:?} to U8"),
+        }
+    }
+
+    pub(crate) fn cast_u16(self) -> Value {
+        match self {
+            Value::U8(x) => Value::U16(u16::from(x)),
+            Value::U16(x) => Value::U16(x),
+            Value::U32(x) => Value::U16(u16::try_from(x).unwrap()),
+            Value::U64(x) => Value::U16(u16::try_from(x).unwrap()),
+            Value::Usize(x) => Value::U16(u16::try_from(x).unwrap()),
+            x => panic!("cannot convert {x:?} to U16"),
+        }
+    }
+
+    pub(crate) fn cast_u32(self) -> Value {
+        match self {
+            Value::U8(x) => Value::U32(u32::from(x)),
+            Value::U16(x) => Value::U32(u32::from(x)),
+            Value::U32(x) => Value::U32(x),
+            Value::U64(x) => Value::U32(u32::try_from(x).unwrap()),
+            Value::Usize(x) => Value::U32(u32::try_from(x).unwrap()),
+            x => panic!("cannot convert {x:?} to U32"),
+        }
+    }
+
+    pub(crate) fn cast_u64(self) -> Value {
+        match self {
+            Value::U8(x) => Value::U64(u64::from(x)),
+            Value::U16(x) => Value::U64(u64::from(x)),
+            Value::U32(x) => Value::U64(u64::from(x)),
+            Value::U64(x) => Value::U64(x),
+            Value::Usize(x) => Value::U64(u64::try_from(x).unwrap()),
+            x => panic!("cannot convert {x:?} to U64"),
+        }
+    }
+
+    pub(crate) fn cast_char(self) -> Value {
+        let code = match self {
+            Value::U8(x) => return Value::Char(char::from(x)),
+            Value::U16(x) => u32::from(x),
+            Value::U32(x) => x,
+            Value::U64(x) => u32::try_from(x).unwrap(),
+            Value::Usize(x) => u32::try_from(x).unwrap(),
+            _ => panic!("AsChar: expected U8, U16, U32, or U64"),
+        };
+        Value::Char(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Unwraps a tuple of `N` bytes, or panics with a message attributing the failure to `op`.
+    fn unwrap_bytes<const N: usize>(self, op: &str) -> [u8; N] {
+        let elems = self.unwrap_tuple();
+        let mut bytes = [0; N];
+        if elems.len() == N {
+            for (byte, elem) in bytes.iter_mut().zip(elems) {
+                match elem {
+                    Value::U8(b) => *byte = b,
+                    _ => panic!("{op}: expected {N} bytes"),
+                }
+            }
+            bytes
+        } else {
+            panic!("{op}: expected {N} bytes")
+        }
+    }
+
+    pub(crate) fn u16_be(self) -> Value {
+        Value::U16(u16::from_be_bytes(self.unwrap_bytes("U16Be")))
+    }
+
+    pub(crate) fn u16_le(self) -> Value {
+        Value::U16(u16::from_le_bytes(self.unwrap_bytes("U16Le")))
+    }
+
+    pub(crate) fn u32_be(self) -> Value {
+        Value::U32(u32::from_be_bytes(self.unwrap_bytes("U32Be")))
+    }
+
+    pub(crate) fn u32_le(self) -> Value {
+        Value::U32(u32::from_le_bytes(self.unwrap_bytes("U32Le")))
+    }
+
+    pub(crate) fn u64_be(self) -> Value {
+        Value::U64(u64::from_be_bytes(self.unwrap_bytes("U64Be")))
+    }
+
+    pub(crate) fn u64_le(self) -> Value {
+        Value::U64(u64::from_le_bytes(self.unwrap_bytes("U64Le")))
+    }
+}
+
 fn __rel<T>(rel: IntRel, left: T, right: T) -> bool
 where
     T: Eq + Ord,
@@ -407,7 +747,7 @@ where
     }
 }
 
-fn __arith<T>(arith: Arith, left: T, right: T) -> T
+fn __arith<T>(arith: Arith, left: T, right: T) -> EvalResult<T>
 where
     T: num_traits::CheckedAdd,
     T: num_traits::CheckedSub,
@@ -420,49 +760,34 @@ where
     T: std::ops::BitOr<Output = T>,
     T: std::ops::BitAnd<Output = T>,
 {
-    match arith {
-        Arith::Add => left
-            .checked_add(&right)
-            .unwrap_or_else(|| panic!("integer overflow")),
-        Arith::Sub => left
-            .checked_sub(&right)
-            .unwrap_or_else(|| panic!("integer overflow")),
-        Arith::Mul => left
-            .checked_mul(&right)
-            .unwrap_or_else(|| panic!("integer overflow")),
-        Arith::Div => left
-            .checked_div(&right)
-            .unwrap_or_else(|| panic!("integer overflow")),
-        Arith::Rem => left
-            .checked_rem(&right)
-            .unwrap_or_else(|| panic!("integer overflow")),
-        Arith::Shl => left << right.as_(),
-        Arith::Shr => left >> right.as_(),
-        Arith::BitOr => left | right,
-        Arith::BitAnd => left & right,
+    let res = match arith {
+        Arith::Add => left.checked_add(&right),
+        Arith::Sub => left.checked_sub(&right),
+        Arith::Mul => left.checked_mul(&right),
+        Arith::Div => left.checked_div(&right),
+        Arith::Rem => left.checked_rem(&right),
+        Arith::Shl => left.checked_shl(right.as_()),
+        Arith::Shr => left.checked_shr(right.as_()),
+        Arith::BitOr => Some(left | right),
+        Arith::BitAnd => Some(left & right),
         Arith::BoolOr | Arith::BoolAnd => unreachable!("bool ops should be handled separately"),
-    }
+    };
+    res.ok_or(EvalError::Overflow { op: arith })
 }
 
-fn __unary<T>(op: UnaryOp, value: T) -> T
+fn __unary<T>(op: UnaryOp, value: T) -> EvalResult<T>
 where
     T: num_traits::CheckedAdd,
     T: num_traits::CheckedSub,
     T: num_traits::One,
 {
     match op {
-        UnaryOp::IntPred => value.checked_sub(&T::one()).unwrap_or_else(|| {
-            panic!(
-                "__unary::<{}>@IntPred: integer underflow",
-                std::any::type_name::<T>()
-            )
-        }),
-        UnaryOp::IntSucc => value.checked_add(&T::one()).unwrap_or_else(|| {
-            panic!(
-                "__unary::<{}>@IntSucc: integer overflow",
-                std::any::type_name::<T>()
-            )
-        }),
+        UnaryOp::IntPred => value
+            .checked_sub(&T::one())
+            .ok_or(EvalError::Overflow { op: Arith::Sub }),
+        UnaryOp::IntSucc => value
+            .checked_add(&T::one())
+            .ok_or(EvalError::Overflow { op: Arith::Add }),
         UnaryOp::BoolNot => unreachable!("bool ops should be handled separately"),
     }
 }
@@ -538,12 +863,15 @@ impl Value {
         }
     }
 
-    pub fn arith(arith: Arith, left: Value, right: Value) -> Value {
+    /// Applies the arithmetic operation `arith` to `left` and `right`.
+    ///
+    /// Returns an error if the operation overflows, and panics if the operands are not of the same type.
+    pub fn arith(arith: Arith, left: Value, right: Value) -> EvalResult<Value> {
         if matches!(arith, Arith::BoolOr | Arith::BoolAnd) {
             match (left, right) {
                 (Value::Bool(l), Value::Bool(r)) => match arith {
-                    Arith::BoolOr => Value::Bool(l || r),
-                    Arith::BoolAnd => Value::Bool(l && r),
+                    Arith::BoolOr => Ok(Value::Bool(l || r)),
+                    Arith::BoolAnd => Ok(Value::Bool(l && r)),
                     _ => unreachable!(),
                 },
                 (left, right) => {
@@ -554,11 +882,11 @@ impl Value {
             }
         } else {
             match (left, right) {
-                (Value::U8(l), Value::U8(r)) => Value::U8(__arith(arith, l, r)),
-                (Value::U16(l), Value::U16(r)) => Value::U16(__arith(arith, l, r)),
-                (Value::U32(l), Value::U32(r)) => Value::U32(__arith(arith, l, r)),
-                (Value::U64(l), Value::U64(r)) => Value::U64(__arith(arith, l, r)),
-                (Value::Usize(l), Value::Usize(r)) => Value::Usize(__arith(arith, l, r)),
+                (Value::U8(l), Value::U8(r)) => __arith(arith, l, r).map(Value::U8),
+                (Value::U16(l), Value::U16(r)) => __arith(arith, l, r).map(Value::U16),
+                (Value::U32(l), Value::U32(r)) => __arith(arith, l, r).map(Value::U32),
+                (Value::U64(l), Value::U64(r)) => __arith(arith, l, r).map(Value::U64),
+                (Value::Usize(l), Value::Usize(r)) => __arith(arith, l, r).map(Value::Usize),
                 (Value::Numeric(_l), Value::Numeric(_r)) => {
                     panic!(
                         "raw arithmetic on numerics should be done in numeric model, or with Expr-level casts beforehand"
@@ -571,18 +899,21 @@ impl Value {
         }
     }
 
-    pub fn unary(op: UnaryOp, value: Value) -> Value {
+    /// Applies the unary operation `op` to `value`.
+    ///
+    /// Returns an error if the operation overflows, and panics if the operand is not of a suitable type.
+    pub fn unary(op: UnaryOp, value: Value) -> EvalResult<Value> {
         match op {
             UnaryOp::BoolNot => match value {
-                Value::Bool(b) => Value::Bool(!b),
+                Value::Bool(b) => Ok(Value::Bool(!b)),
                 _ => panic!("cannot apply bool-not to non-boolean operand (`{value:?}`)"),
             },
             op => match value {
-                Value::U8(i) => Value::U8(__unary(op, i)),
-                Value::U16(i) => Value::U16(__unary(op, i)),
-                Value::U32(i) => Value::U32(__unary(op, i)),
-                Value::U64(i) => Value::U64(__unary(op, i)),
-                Value::Usize(i) => Value::Usize(__unary(op, i)),
+                Value::U8(i) => __unary(op, i).map(Value::U8),
+                Value::U16(i) => __unary(op, i).map(Value::U16),
+                Value::U32(i) => __unary(op, i).map(Value::U32),
+                Value::U64(i) => __unary(op, i).map(Value::U64),
+                Value::Usize(i) => __unary(op, i).map(Value::Usize),
                 Value::Numeric(_i) => {
                     panic!("top-level unary operations should not be performed on raw-numeric");
                 }
@@ -810,4 +1141,70 @@ mod tests {
     fn fully_unsupported_combination_panics() {
         Value::int_rel(IntRel::Eq, Value::Bool(true), Value::U8(1));
     }
+
+    // ---- compact byte-sequences ----
+
+    fn strict_bytes(bytes: &[u8]) -> Value {
+        Value::Seq(SeqKind::Strict(
+            bytes.iter().copied().map(Value::U8).collect(),
+        ))
+    }
+
+    #[test]
+    fn byte_seqs_are_compact() {
+        let Value::Seq(seq) = Value::Seq(vec![Value::U8(1), Value::U8(2)].into()) else {
+            unreachable!()
+        };
+        assert_eq!(seq.as_bytes(), Some(&[1, 2][..]));
+        let Value::Seq(seq) = Value::Seq(vec![Value::U8(1), Value::U16(2)].into()) else {
+            unreachable!()
+        };
+        assert!(seq.is_strict());
+    }
+
+    #[test]
+    fn byte_values_table() {
+        for b in 0..=u8::MAX {
+            assert_eq!(Value::byte(b), &Value::U8(b));
+            assert_eq!(Value::byte(b).to_byte(), Some(b));
+        }
+        // the table is usable from any thread, without requiring `Value: Sync`
+        let b = std::thread::spawn(|| Value::byte(0xA5).to_byte())
+            .join()
+            .unwrap();
+        assert_eq!(b, Some(0xA5));
+    }
+
+    #[test]
+    fn byte_seqs_are_transparent() {
+        use std::hash::{BuildHasher, RandomState};
+
+        let bytes = [0x00, 0x7f, 0xff];
+        let strict = strict_bytes(&bytes);
+        let compact = Value::Seq(SeqKind::Bytes(Rc::from(&bytes[..])));
+        assert_eq!(strict, compact);
+        let hasher = RandomState::new();
+        assert_eq!(hasher.hash_one(&strict), hasher.hash_one(&compact));
+        assert_eq!(strict.to_string(), compact.to_string());
+        assert_eq!(
+            serde_json::to_string(&strict).unwrap(),
+            serde_json::to_string(&compact).unwrap()
+        );
+        assert_eq!(compact.get_bytes().as_ref(), &bytes);
+
+        let (Value::Seq(strict), Value::Seq(compact)) = (strict, compact) else {
+            unreachable!()
+        };
+        assert_eq!(compact[1], Value::U8(0x7f));
+        assert_eq!(compact.sub_seq(1, 2), strict.sub_seq(1, 2));
+        assert_eq!(
+            compact.clone().append(compact.clone()),
+            strict.clone().append(strict.clone())
+        );
+        assert_eq!(
+            compact.clone().append(strict.clone()).as_bytes(),
+            Some(&[0x00, 0x7f, 0xff, 0x00, 0x7f, 0xff][..])
+        );
+        assert_eq!(compact.into_iter().collect::<Vec<_>>(), strict.into_vec());
+    }
 }
@@ -0,0 +1,2460 @@
+//! Bytecode backend for the interpreter.
+//!
+//! [`Decoder::parse`] walks the decoder tree directly, looking up every variable by name through a chain of
+//! [`Scope`]s and cloning intermediate values as it goes. A [`VmProgram`] instead compiles each decoder of a
+//! [`Program`] ahead of time into flat blocks of [`Op`]s, in which every variable, view and dynamic format is
+//! resolved to a numbered slot of the frame it is bound in, and evaluates them on a stack machine that shares
+//! bound values (and projections thereof) by reference-count rather than copying them.
+//!
+//! The values produced are identical to those of [`Program::run`], as are the errors reported on failure,
+//! save for the diagnostic traces the tree-walker attaches to them. Limits are enforced as they are by the
+//! tree-walker, except that the number of steps charged for a given decode may differ slightly; format calls
+//! are never memoized.
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::{Result as AResult, anyhow};
+
+use crate::byte_set::{ByteSet, resync_offset};
+use crate::error::{DecodeError, DecodeErrorKind, DecodeResult, EvalError, EvalResult};
+use crate::limits::{Budget, Limits};
+use crate::native::NativeDecodeFn;
+use crate::numeric::core::Expr as NumExpr;
+use crate::read::ReadCtxt;
+use crate::scope::EvalScope;
+use crate::validation::{Condition, Severity};
+use crate::{
+    Arith, BaseKind, DynFormat, Endian, Expr, ExprFunction, FixedType, IntRel, Label, MatchTree,
+    Pattern, UnaryOp, ViewExpr,
+};
+
+use super::seq_kind::{SeqKind, ValueSeq, sub_range};
+use super::{
+    Accum, Compiler, Decoder, MultiScope, Program, Scope, UnknownVarError, Value, extract_pair,
+    huffman_code_lengths, make_huffman_codes, read_base, search, within_budget,
+};
+
+/// A [`Program`] compiled to bytecode.
+#[derive(Debug)]
+pub struct VmProgram {
+    funcs: Vec<Func>,
+    /// Bytecode of each function of the module
+    fns: Vec<FnCode>,
+    format_names: Vec<Option<Label>>,
+    limits: Limits,
+}
+
+impl VmProgram {
+    /// Compiles every decoder reachable from the entry-point of `program`, subject to the same limits.
+    pub fn compile(program: &Program) -> AResult<VmProgram> {
+        let mut funcs: Vec<Option<Func>> = program.decoders.iter().map(|_| None).collect();
+        // the parameter names of each decoder are only known from the calls made to it
+        let mut queue = vec![(0, Vec::new(), Vec::new())];
+        while let Some((ix, args, views)) = queue.pop() {
+            if funcs[ix].is_some() {
+                continue;
+            }
+            let mut calls = Vec::new();
+            let func = FuncCompiler::compile(&program.decoders[ix].0, &args, &views, &mut calls)
+                .map_err(|e| match &program.format_names[ix] {
+                    Some(name) => e.context(format!("compiling format `{name}`")),
+                    None => e,
+                })?;
+            funcs[ix] = Some(func);
+            queue.extend(calls);
+        }
+        let fns = program
+            .functions
+            .iter()
+            .map(|func| {
+                FuncCompiler::compile_function(func)
+                    .map_err(|e| e.context(format!("compiling function `{}`", func.name)))
+            })
+            .collect::<AResult<_>>()?;
+        Ok(VmProgram {
+            // decoders that are never called are left empty
+            funcs: funcs.into_iter().map(Option::unwrap_or_default).collect(),
+            fns,
+            format_names: program.format_names.clone(),
+            limits: program.limits(),
+        })
+    }
+
+    /// Returns `self` with each subsequent [`VmProgram::run`] subject to `limits`.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.set_limits(limits);
+        self
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    pub fn run<'input>(&self, input: ReadCtxt<'input>) -> DecodeResult<(Value, ReadCtxt<'input>)> {
+        let main = &self.funcs[0];
+        let mut machine = Machine {
+            program: self,
+            stack: Vec::new(),
+            frame: Frame::new(main),
+            warnings: Vec::new(),
+            budget: Budget::new(self.limits),
+        };
+        let mut views = vec![input; main.n_views];
+        let input = machine.exec(&main.code, input, &mut views)?;
+        for err in machine.warnings.iter() {
+            log::warn!("[non-fatal error]: {err}");
+        }
+        Ok((machine.pop().into_value(), input))
+    }
+}
+
+/// Bytecode of a single decoder, along with the number of slots of each kind its frame requires.
+#[derive(Debug, Default)]
+struct Func {
+    code: Block,
+    n_slots: usize,
+    n_views: usize,
+    n_dyns: usize,
+}
+
+/// Bytecode of a function of the module, evaluated within a frame of its own whose leading slots hold its
+/// arguments.
+#[derive(Debug)]
+struct FnCode {
+    body: Code,
+    n_slots: usize,
+}
+
+type Block = Box<[Op]>;
+
+type Code = Box<[Instr]>;
+
+/// Slot of each variable bound by a pattern, along with the path to the part of the matched value it is bound to.
+type Binds = Box<[(usize, Rc<[Proj]>)]>;
+
+/// Decoding instruction.
+///
+/// Each complete decoder leaves exactly one value on the operand stack on success, and consumes the values
+/// computed for it by the instructions preceding it (in the order they were pushed).
+#[derive(Debug)]
+enum Op {
+    Eval(Instr),
+    /// Pops a value and binds it to a slot
+    Store(usize),
+    /// Binds the current input to a view slot
+    StoreView(usize),
+    Pop,
+    Call {
+        func: usize,
+        n_args: usize,
+        views: Box<[ViewCode]>,
+    },
+    Pos,
+    Fail,
+    EndOfInput,
+    SkipRemainder,
+    Phantom,
+    Align(usize),
+    Byte(ByteSet),
+    Branch(MatchTree, Box<[Block]>),
+    Parallel(Box<[Block]>),
+    While(MatchTree, Block),
+    Until(MatchTree, Block),
+    RepeatCount(Block),
+    RepeatBetween(MatchTree, Block),
+    RepeatUntilLast(Lambda, Block),
+    RepeatUntilSeq(Lambda, Block),
+    AccumUntil {
+        done: Lambda,
+        update: Lambda,
+        body: Block,
+    },
+    ForEach(usize, Block),
+    Maybe(Block),
+    Peek(Block),
+    PeekNot(Block),
+    Slice(Block),
+    Bits(Block),
+    WithRelativeOffset(Block),
+    DecodeBytes(Block),
+    ParseFromView(ViewCode, Block),
+    Map(Lambda),
+    Where {
+        body: Block,
+        cond: Lambda,
+        expr: Box<Expr>,
+        severity: Severity,
+    },
+    Match(Box<[Arm<Block>]>),
+    LetFormat {
+        body: Block,
+        slot: usize,
+        name: Label,
+    },
+    /// Pops the code-length table (preceded by the symbol-order table, if permuted) of a Huffman code and
+    /// binds its decoder to a slot
+    Dynamic {
+        slot: usize,
+        permuted: bool,
+    },
+    Apply(usize),
+    Native(NativeDecodeFn, usize),
+    CaptureBytes(ViewCode),
+    ReadArray(ViewCode, BaseKind<Endian>),
+    ReifyView(ViewCode),
+    #[cfg(feature = "format_enforce")]
+    Enforce(Block),
+    Permit(Block, Code),
+    Resync(Block, Vec<ByteSet>),
+}
+
+/// Expression-evaluation instruction.
+#[derive(Debug)]
+enum Instr {
+    Const(Value),
+    Load(usize),
+    /// Moves the value bound to a slot onto the stack, unbinding it (for the last use of the binding)
+    Take(usize),
+    /// Loads a projection of the value bound to a slot, without copying it
+    LoadPath(usize, Rc<[Proj]>),
+    /// Loads a projection of the value bound to a slot as `LoadPath` does, followed by a `Coerce`
+    LoadValue(usize, Rc<[Proj]>),
+    /// Unwraps any `Mapped`, `Branch` or `Permit(Ok)` wrapper around the value on top of the stack
+    Coerce,
+    Tuple(usize),
+    Record(Box<[Label]>),
+    Variant(Label),
+    Seq(usize),
+    Some,
+    Proj(Proj),
+    Numeric(Box<NumExpr>, Box<[(Label, usize)]>),
+    IntRel(IntRel),
+    Arith(Arith),
+    Unary(UnaryOp),
+    Cast(Cast),
+    SeqLength,
+    SeqIx,
+    SubSeq,
+    SubSeqInflate,
+    Append,
+    Dup,
+    EnumFromTo,
+    FlatMap(Lambda),
+    FlatMapAccum(Lambda),
+    FlatMapList(Lambda),
+    LeftFold(Lambda),
+    FindByKey(bool, Lambda),
+    Filter(Lambda),
+    Zip,
+    Reverse,
+    Any(Lambda),
+    All(Lambda),
+    IsSortedBy(Lambda),
+    /// Calls the function at the given index with the given number of arguments, the last of which is on top of the stack
+    Call(usize, usize),
+    Match(Box<[Arm<Code>]>),
+    Destructure(Box<Arm<Code>>),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Cast {
+    AsU8,
+    AsU16,
+    AsU32,
+    AsU64,
+    AsChar,
+    U16Be,
+    U16Le,
+    U32Be,
+    U32Le,
+    U64Be,
+    U64Le,
+    FloatFromBits,
+    FixedFromBits(FixedType),
+}
+
+impl Cast {
+    fn apply(self, v: Value) -> Value {
+        match self {
+            Cast::AsU8 => v.cast_u8(),
+            Cast::AsU16 => v.cast_u16(),
+            Cast::AsU32 => v.cast_u32(),
+            Cast::AsU64 => v.cast_u64(),
+            Cast::AsChar => v.cast_char(),
+            Cast::U16Be => v.u16_be(),
+            Cast::U16Le => v.u16_le(),
+            Cast::U32Be => v.u32_be(),
+            Cast::U32Le => v.u32_le(),
+            Cast::U64Be => v.u64_be(),
+            Cast::U64Le => v.u64_le(),
+            Cast::FloatFromBits => v.float_from_bits(),
+            Cast::FixedFromBits(fx) => Value::fixed_from_bits(fx, v),
+        }
+    }
+}
+
+/// Single step of a projection into a value, as performed by `Expr::TupleProj`, `Expr::RecordProj` and
+/// `Expr::SeqIx`, or by the binding of a pattern variable.
+#[derive(Clone, Debug)]
+enum Proj {
+    Tuple(usize),
+    Record(Label),
+    Index(usize),
+    /// Unwraps the value as `Value::matches` does before matching it against a pattern
+    Nominal,
+    /// Element of a tuple or sequence, as bound by a pattern
+    Element(usize),
+    /// Contents of a variant or `Some`, as bound by a pattern
+    Inner,
+}
+
+impl Proj {
+    fn apply<'a>(&self, v: &'a Value) -> &'a Value {
+        match self {
+            Proj::Tuple(ix) => v.coerce_mapped_value().tuple_proj(*ix),
+            Proj::Record(label) => v.coerce_mapped_value().record_proj(label.as_ref()),
+            Proj::Index(ix) => match v.coerce_mapped_value() {
+                Value::Seq(values) => &values[*ix],
+                other => panic!("SeqIx: expected Seq, found {other:?}"),
+            },
+            Proj::Nominal => v.coerce_nominal_value(),
+            Proj::Element(ix) => match v {
+                Value::Tuple(values) => &values[*ix],
+                Value::Seq(values) => &values[*ix],
+                other => unreachable!("pattern-matched tuple or sequence, found {other:?}"),
+            },
+            Proj::Inner => match v {
+                Value::Variant(_, inner) | Value::Option(Some(inner)) => inner,
+                other => unreachable!("pattern-matched variant or option, found {other:?}"),
+            },
+        }
+    }
+
+    /// Like `apply`, but moving the projected value out of `v` where possible.
+    fn take(&self, v: Value) -> Value {
+        match (self, v.extract_mapped_value()) {
+            (Proj::Tuple(ix), Value::Tuple(mut values)) if *ix < values.len() => {
+                values.swap_remove(*ix)
+            }
+            (Proj::Record(label), Value::Record(fields)) => {
+                match fields.into_iter().find(|(l, _)| l == label) {
+                    Some((_, v)) => v,
+                    None => panic!("{label} not found in record"),
+                }
+            }
+            (Proj::Index(ix), Value::Seq(SeqKind::Strict(mut values))) if *ix < values.len() => {
+                values.swap_remove(*ix)
+            }
+            (proj, v) => proj.apply(&v).clone(),
+        }
+    }
+}
+
+/// Parameter and body of an `Expr::Lambda`.
+#[derive(Debug)]
+struct Lambda {
+    param: usize,
+    binding: Binding,
+    /// End of the range of slots (beginning with `param`) bound within the body
+    end: usize,
+    body: Code,
+}
+
+/// How a [`Lambda`] binds its argument.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Binding {
+    /// The argument is never read, and so is not bound at all
+    Unused,
+    /// The argument is bound to the parameter slot
+    Whole,
+    /// The lambda immediately destructures its argument as a pair, whose components are bound to the
+    /// parameter slot and the slot following it instead
+    Pair,
+}
+
+/// Branch of a `Match` (or `Destructure`), along with the slot of each variable bound by its pattern and
+/// the path to the part of the matched value it is bound to.
+#[derive(Debug)]
+struct Arm<B> {
+    pattern: Pattern,
+    /// Or-free alternatives of the (unguarded) pattern, each with the bindings it makes; every alternative
+    /// binds the same set of slots
+    alts: Box<[(Pattern, Binds)]>,
+    guard: Option<Code>,
+    body: B,
+}
+
+/// View slot along with the offset-expressions to apply to it, innermost first.
+#[derive(Debug)]
+struct ViewCode {
+    slot: usize,
+    offsets: Box<[Code]>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Slot {
+    Value(usize),
+    View(usize),
+    Decoder(usize),
+}
+
+struct FuncCompiler<'a> {
+    scope: Vec<(Label, Slot)>,
+    /// Slots bound by the chain of `Let`s whose body is the decoder being compiled, none of which is read
+    /// once it completes
+    tail: Vec<usize>,
+    n_slots: usize,
+    n_views: usize,
+    n_dyns: usize,
+    calls: &'a mut Vec<(usize, Vec<Label>, Vec<Label>)>,
+}
+
+impl<'a> FuncCompiler<'a> {
+    /// Compiles `d`, with the given parameters, recording the decoders it calls (along with the names they
+    /// bind their parameters to) in `calls`.
+    fn compile(
+        d: &Decoder,
+        args: &[Label],
+        views: &[Label],
+        calls: &'a mut Vec<(usize, Vec<Label>, Vec<Label>)>,
+    ) -> AResult<Func> {
+        let mut compiler = FuncCompiler {
+            scope: Vec::new(),
+            tail: Vec::new(),
+            n_slots: 0,
+            n_views: 0,
+            n_dyns: 0,
+            calls,
+        };
+        for name in args {
+            compiler.bind_value(name);
+        }
+        for name in views {
+            let slot = compiler.n_views;
+            compiler.n_views += 1;
+            compiler.scope.push((name.clone(), Slot::View(slot)));
+        }
+        let code = compiler.block(d)?;
+        Ok(Func {
+            code,
+            n_slots: compiler.n_slots,
+            n_views: compiler.n_views,
+            n_dyns: compiler.n_dyns,
+        })
+    }
+
+    /// Compiles the body of `func`, whose parameters are bound to the leading slots of its frame.
+    fn compile_function(func: &ExprFunction) -> AResult<FnCode> {
+        let mut calls = Vec::new();
+        let mut compiler = FuncCompiler {
+            scope: Vec::new(),
+            tail: Vec::new(),
+            n_slots: 0,
+            n_views: 0,
+            n_dyns: 0,
+            calls: &mut calls,
+        };
+        for (name, _) in func.params.iter() {
+            compiler.bind_value(name);
+        }
+        let mut code = Vec::new();
+        compiler.value(func.body(), &mut code)?;
+        Ok(FnCode {
+            body: code.into_boxed_slice(),
+            n_slots: compiler.n_slots,
+        })
+    }
+
+    fn bind_value(&mut self, name: &Label) -> usize {
+        let slot = self.n_slots;
+        self.n_slots += 1;
+        self.scope.push((name.clone(), Slot::Value(slot)));
+        slot
+    }
+
+    fn lookup(&self, name: &str) -> AResult<Slot> {
+        self.scope
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, slot)| *slot)
+            .ok_or_else(|| anyhow!("unbound variable `{name}`"))
+    }
+
+    fn lookup_value(&self, name: &str) -> AResult<usize> {
+        match self.lookup(name)? {
+            Slot::Value(slot) => Ok(slot),
+            _ => Err(anyhow!("`{name}` is not bound to a value")),
+        }
+    }
+
+    fn block(&mut self, d: &Decoder) -> AResult<Block> {
+        let mut ops = Vec::new();
+        self.decoder(d, &mut ops)?;
+        Ok(ops.into_boxed_slice())
+    }
+
+    /// Compiles `d` within the scope of a single new value-binding, returning the block and the slot.
+    fn block_with(&mut self, name: &Label, d: &Decoder) -> AResult<(usize, Block)> {
+        let mark = self.scope.len();
+        let slot = self.bind_value(name);
+        let block = self.block(d);
+        self.scope.truncate(mark);
+        Ok((slot, block?))
+    }
+
+    fn blocks(&mut self, ds: &[Decoder]) -> AResult<Box<[Block]>> {
+        ds.iter().map(|d| self.block(d)).collect()
+    }
+
+    /// Appends the instructions evaluating `expr` to a (coerced) value.
+    fn eval(&mut self, expr: &Expr, ops: &mut Vec<Op>) -> AResult<()> {
+        let mut code = Vec::new();
+        self.value(expr, &mut code)?;
+        ops.extend(code.into_iter().map(Op::Eval));
+        Ok(())
+    }
+
+    fn decoder(&mut self, d: &Decoder, ops: &mut Vec<Op>) -> AResult<()> {
+        let tail = std::mem::take(&mut self.tail);
+        match d {
+            Decoder::Call(n, args, views) => {
+                for (_, arg) in args {
+                    self.eval(arg, ops)?;
+                }
+                self.calls.push((
+                    *n,
+                    args.iter().map(|(name, _)| name.clone()).collect(),
+                    views.iter().map(|(name, _)| name.clone()).collect(),
+                ));
+                ops.push(Op::Call {
+                    func: *n,
+                    n_args: args.len(),
+                    views: views
+                        .iter()
+                        .map(|(_, v)| self.view(v))
+                        .collect::<AResult<_>>()?,
+                });
+            }
+            Decoder::Pos => ops.push(Op::Pos),
+            Decoder::Fail => ops.push(Op::Fail),
+            Decoder::EndOfInput => ops.push(Op::EndOfInput),
+            Decoder::SkipRemainder => ops.push(Op::SkipRemainder),
+            Decoder::Phantom => ops.push(Op::Phantom),
+            Decoder::Align(n) => ops.push(Op::Align(*n)),
+            Decoder::Byte(bs) => ops.push(Op::Byte(*bs)),
+            Decoder::Variant(label, d) => {
+                self.decoder(d, ops)?;
+                ops.push(Op::Eval(Instr::Variant(label.clone())));
+            }
+            Decoder::Branch(tree, ds) => ops.push(Op::Branch(tree.clone(), self.blocks(ds)?)),
+            Decoder::Parallel(ds) => ops.push(Op::Parallel(self.blocks(ds)?)),
+            Decoder::Tuple(ds) => {
+                for d in ds {
+                    self.decoder(d, ops)?;
+                }
+                ops.push(Op::Eval(Instr::Tuple(ds.len())));
+            }
+            Decoder::Sequence(ds) => {
+                for d in ds {
+                    self.decoder(d, ops)?;
+                }
+                ops.push(Op::Eval(Instr::Seq(ds.len())));
+            }
+            Decoder::While(tree, a) => ops.push(Op::While(tree.clone(), self.block(a)?)),
+            Decoder::Until(tree, a) => ops.push(Op::Until(tree.clone(), self.block(a)?)),
+            Decoder::RepeatCount(count, a) => {
+                self.eval(count, ops)?;
+                ops.push(Op::RepeatCount(self.block(a)?));
+            }
+            Decoder::RepeatBetween(tree, min, max, a) => {
+                self.eval(min, ops)?;
+                self.eval(max, ops)?;
+                ops.push(Op::RepeatBetween(tree.clone(), self.block(a)?));
+            }
+            Decoder::RepeatUntilLast(f, a) => {
+                ops.push(Op::RepeatUntilLast(self.lambda(f)?, self.block(a)?));
+            }
+            Decoder::RepeatUntilSeq(f, a) => {
+                ops.push(Op::RepeatUntilSeq(self.lambda(f)?, self.block(a)?));
+            }
+            Decoder::AccumUntil(f_done, f_update, init, _, a) => {
+                self.eval(init, ops)?;
+                ops.push(Op::AccumUntil {
+                    done: self.lambda(f_done)?,
+                    update: self.lambda(f_update)?,
+                    body: self.block(a)?,
+                });
+            }
+            Decoder::ForEach(seq, name, a) => {
+                self.eval(seq, ops)?;
+                let (slot, body) = self.block_with(name, a)?;
+                ops.push(Op::ForEach(slot, body));
+            }
+            Decoder::Maybe(cond, a) => {
+                self.eval(cond, ops)?;
+                ops.push(Op::Maybe(self.block(a)?));
+            }
+            Decoder::Peek(a) => ops.push(Op::Peek(self.block(a)?)),
+            Decoder::PeekNot(a) => ops.push(Op::PeekNot(self.block(a)?)),
+            Decoder::Slice(size, a) => {
+                self.eval(size, ops)?;
+                ops.push(Op::Slice(self.block(a)?));
+            }
+            Decoder::Bits(a) => ops.push(Op::Bits(self.block(a)?)),
+            Decoder::WithRelativeOffset(base, offset, a) => {
+                self.eval(base, ops)?;
+                self.eval(offset, ops)?;
+                ops.push(Op::WithRelativeOffset(self.block(a)?));
+            }
+            Decoder::DecodeBytes(bytes, a) => {
+                self.eval(bytes, ops)?;
+                ops.push(Op::DecodeBytes(self.block(a)?));
+            }
+            Decoder::ParseFromView(view, a) => {
+                ops.push(Op::ParseFromView(self.view(view)?, self.block(a)?));
+            }
+            Decoder::Map(d, f) => {
+                self.decoder(d, ops)?;
+                ops.push(Op::Map(self.lambda(f)?));
+            }
+            Decoder::Where(d, Condition { expr, severity }) => ops.push(Op::Where {
+                body: self.block(d)?,
+                cond: self.lambda(expr)?,
+                expr: expr.clone(),
+                severity: *severity,
+            }),
+            Decoder::Compute(expr) if !tail.is_empty() => match self.moved_fields(expr, &tail) {
+                Some((labels, slots)) => {
+                    ops.extend(slots.into_iter().map(|slot| Op::Eval(Instr::Take(slot))));
+                    ops.push(Op::Eval(Instr::Record(labels)));
+                }
+                None => self.eval(expr, ops)?,
+            },
+            Decoder::Compute(expr) => self.eval(expr, ops)?,
+            Decoder::Let(name, expr, d) => {
+                self.eval(expr, ops)?;
+                let mark = self.scope.len();
+                let slot = self.bind_value(name);
+                ops.push(Op::Store(slot));
+                self.tail = tail;
+                self.tail.push(slot);
+                let res = self.decoder(d, ops);
+                self.scope.truncate(mark);
+                res?;
+            }
+            Decoder::LetView(name, d) => {
+                let slot = self.n_views;
+                self.n_views += 1;
+                ops.push(Op::StoreView(slot));
+                let mark = self.scope.len();
+                self.scope.push((name.clone(), Slot::View(slot)));
+                let res = self.decoder(d, ops);
+                self.scope.truncate(mark);
+                res?;
+            }
+            Decoder::LetFormat(da, name, db) => {
+                let body = self.block(da)?;
+                let mark = self.scope.len();
+                let slot = self.bind_value(name);
+                ops.push(Op::LetFormat {
+                    body,
+                    slot,
+                    name: name.clone(),
+                });
+                self.tail = tail;
+                self.tail.push(slot);
+                let res = self.decoder(db, ops);
+                self.scope.truncate(mark);
+                res?;
+            }
+            Decoder::MonadSeq(da, db) => {
+                self.decoder(da, ops)?;
+                ops.push(Op::Pop);
+                self.tail = tail;
+                self.decoder(db, ops)?;
+            }
+            Decoder::Match(head, branches) => {
+                let mut code = Vec::new();
+                self.expr(head, &mut code)?;
+                ops.extend(code.into_iter().map(Op::Eval));
+                let arms = branches
+                    .iter()
+                    .map(|(pattern, d)| self.arm(pattern, |this| this.block(d)))
+                    .collect::<AResult<_>>()?;
+                ops.push(Op::Match(arms));
+            }
+            Decoder::Dynamic(name, DynFormat::Huffman(lengths, values), d) => {
+                let mut code = Vec::new();
+                self.expr(lengths, &mut code)?;
+                if let Some(values) = values {
+                    self.expr(values, &mut code)?;
+                }
+                ops.extend(code.into_iter().map(Op::Eval));
+                let slot = self.n_dyns;
+                self.n_dyns += 1;
+                ops.push(Op::Dynamic {
+                    slot,
+                    permuted: values.is_some(),
+                });
+                let mark = self.scope.len();
+                self.scope.push((name.clone(), Slot::Decoder(slot)));
+                let res = self.decoder(d, ops);
+                self.scope.truncate(mark);
+                res?;
+            }
+            Decoder::Apply(name) => match self.lookup(name)? {
+                Slot::Decoder(slot) => ops.push(Op::Apply(slot)),
+                _ => return Err(anyhow!("`{name}` is not bound to a format")),
+            },
+            Decoder::Native(decode, args) => {
+                for arg in args {
+                    self.eval(arg, ops)?;
+                }
+                ops.push(Op::Native(*decode, args.len()));
+            }
+            Decoder::LiftedOption(None) => {
+                ops.push(Op::Eval(Instr::Const(Value::Option(None))));
+            }
+            Decoder::LiftedOption(Some(d)) => {
+                self.decoder(d, ops)?;
+                ops.push(Op::Eval(Instr::Some));
+            }
+            Decoder::CaptureBytes(view, len) => {
+                self.eval(len, ops)?;
+                ops.push(Op::CaptureBytes(self.view(view)?));
+            }
+            Decoder::ReadArray(view, len, kind) => {
+                self.eval(len, ops)?;
+                ops.push(Op::ReadArray(self.view(view)?, *kind));
+            }
+            Decoder::ReifyView(view) => ops.push(Op::ReifyView(self.view(view)?)),
+            #[cfg(feature = "format_enforce")]
+            Decoder::Enforce(a) => ops.push(Op::Enforce(self.block(a)?)),
+            Decoder::Permit(a, expr) => {
+                let mut code = Vec::new();
+                self.value(expr, &mut code)?;
+                ops.push(Op::Permit(self.block(a)?, code.into_boxed_slice()));
+            }
+            Decoder::Resync(a, pattern) => ops.push(Op::Resync(self.block(a)?, pattern.clone())),
+        }
+        Ok(())
+    }
+
+    /// If `expr` is a record whose fields are distinct variables bound in `tail`, as at the end of a chain of
+    /// `Let`s, returns its labels and the slots its fields can be moved out of rather than copied.
+    fn moved_fields(&self, expr: &Expr, tail: &[usize]) -> Option<(Box<[Label]>, Vec<usize>)> {
+        let Expr::Record(fields) = expr else {
+            return None;
+        };
+        let mut slots = Vec::with_capacity(fields.len());
+        for (_, expr) in fields {
+            let Expr::Var(name) = expr else {
+                return None;
+            };
+            let slot = self.lookup_value(name).ok()?;
+            if !tail.contains(&slot) || slots.contains(&slot) {
+                return None;
+            }
+            slots.push(slot);
+        }
+        Some((
+            fields.iter().map(|(label, _)| label.clone()).collect(),
+            slots,
+        ))
+    }
+
+    fn view(&mut self, view: &ViewExpr) -> AResult<ViewCode> {
+        match view {
+            ViewExpr::Var(name) => match self.lookup(name)? {
+                Slot::View(slot) => Ok(ViewCode {
+                    slot,
+                    offsets: Box::new([]),
+                }),
+                _ => Err(anyhow!("`{name}` is not bound to a view")),
+            },
+            ViewExpr::Offset(base, offset) => {
+                let base = self.view(base)?;
+                let mut code = Vec::new();
+                self.value(offset, &mut code)?;
+                let mut offsets = base.offsets.into_vec();
+                offsets.push(code.into_boxed_slice());
+                Ok(ViewCode {
+                    slot: base.slot,
+                    offsets: offsets.into_boxed_slice(),
+                })
+            }
+        }
+    }
+
+    fn lambda(&mut self, expr: &Expr) -> AResult<Lambda> {
+        let Expr::Lambda(name, body) = expr else {
+            return Err(anyhow!("expected lambda, found {expr:?}"));
+        };
+        let mark = self.scope.len();
+        let (param, binding, body) = match &**body {
+            Expr::Destructure(head, Pattern::Tuple(ps), inner)
+                if matches!(&**head, Expr::Var(v) if v == name) && !inner.is_shadowed_by(name) =>
+            {
+                match ps.as_slice() {
+                    [Pattern::Binding(first), Pattern::Binding(second)] => {
+                        let param = self.bind_value(first);
+                        self.bind_value(second);
+                        (param, Binding::Pair, &**inner)
+                    }
+                    _ => (self.bind_value(name), Binding::Whole, &**body),
+                }
+            }
+            _ if !body.is_shadowed_by(name) => (self.n_slots, Binding::Unused, &**body),
+            _ => (self.bind_value(name), Binding::Whole, &**body),
+        };
+        let mut code = Vec::new();
+        let res = self.value(body, &mut code);
+        self.scope.truncate(mark);
+        res?;
+        Ok(Lambda {
+            param,
+            binding,
+            end: self.n_slots,
+            body: code.into_boxed_slice(),
+        })
+    }
+
+    fn arm<B>(
+        &mut self,
+        pattern: &Pattern,
+        body: impl FnOnce(&mut Self) -> AResult<B>,
+    ) -> AResult<Arm<B>> {
+        let (unguarded, guard) = pattern.split_guard();
+        let patterns = if unguarded.has_or() {
+            unguarded.alternatives()
+        } else {
+            vec![unguarded.clone()]
+        };
+        let mark = self.scope.len();
+        let mut slots: Vec<(Label, usize)> = Vec::new();
+        let mut alts = Vec::with_capacity(patterns.len());
+        for alt in patterns {
+            let mut bindings = Vec::new();
+            pattern_bindings(&alt, &mut vec![Proj::Nominal], &mut bindings);
+            let binds = bindings
+                .into_iter()
+                .map(|(name, path)| {
+                    let slot = match slots.iter().find(|(n, _)| n == name) {
+                        Some((_, slot)) => *slot,
+                        None => {
+                            let slot = self.bind_value(name);
+                            slots.push((name.clone(), slot));
+                            slot
+                        }
+                    };
+                    (slot, path.into())
+                })
+                .collect();
+            alts.push((alt, binds));
+        }
+        let guard = guard.map(|guard| self.code(guard)).transpose();
+        let body = body(self);
+        self.scope.truncate(mark);
+        Ok(Arm {
+            pattern: pattern.clone(),
+            alts: alts.into_boxed_slice(),
+            guard: guard?,
+            body: body?,
+        })
+    }
+
+    /// Appends the instructions evaluating `expr` to a value, as with `Expr::eval_value`.
+    fn value(&mut self, expr: &Expr, code: &mut Vec<Instr>) -> AResult<()> {
+        if let Some((slot, path)) = self.path(expr)? {
+            code.push(Instr::LoadValue(slot, path.into()));
+            return Ok(());
+        }
+        self.expr(expr, code)?;
+        if matches!(
+            expr,
+            Expr::TupleProj(..) | Expr::RecordProj(..) | Expr::SeqIx(..)
+        ) {
+            code.push(Instr::Coerce);
+        }
+        Ok(())
+    }
+
+    /// Appends the instructions evaluating `expr`, as with `Expr::eval`.
+    fn expr(&mut self, expr: &Expr, code: &mut Vec<Instr>) -> AResult<()> {
+        match expr {
+            Expr::Var(name) => code.push(Instr::Load(self.lookup_value(name)?)),
+            Expr::Bool(b) => code.push(Instr::Const(Value::Bool(*b))),
+            Expr::U8(i) => code.push(Instr::Const(Value::U8(*i))),
+            Expr::U16(i) => code.push(Instr::Const(Value::U16(*i))),
+            Expr::U32(i) => code.push(Instr::Const(Value::U32(*i))),
+            Expr::U64(i) => code.push(Instr::Const(Value::U64(*i))),
+            Expr::Numeric(n) => {
+                let vars = n
+                    .iter_vars()
+                    .map(|name| Ok((Label::from(name.to_string()), self.lookup_value(name)?)))
+                    .collect::<AResult<_>>()?;
+                code.push(Instr::Numeric(n.clone(), vars));
+            }
+            Expr::Tuple(exprs) => {
+                for expr in exprs {
+                    self.value(expr, code)?;
+                }
+                code.push(Instr::Tuple(exprs.len()));
+            }
+            Expr::TupleProj(head, index) => match self.path(head)? {
+                Some((slot, mut path)) => {
+                    path.push(Proj::Tuple(*index));
+                    code.push(Instr::LoadPath(slot, path.into()));
+                }
+                None => {
+                    self.expr(head, code)?;
+                    code.push(Instr::Proj(Proj::Tuple(*index)));
+                }
+            },
+            Expr::Record(fields) => {
+                for (_, expr) in fields {
+                    self.expr(expr, code)?;
+                }
+                code.push(Instr::Record(
+                    fields.iter().map(|(label, _)| label.clone()).collect(),
+                ));
+            }
+            Expr::RecordProj(head, label) => match self.path(head)? {
+                Some((slot, mut path)) => {
+                    path.push(Proj::Record(label.clone()));
+                    code.push(Instr::LoadPath(slot, path.into()));
+                }
+                None => {
+                    self.expr(head, code)?;
+                    code.push(Instr::Proj(Proj::Record(label.clone())));
+                }
+            },
+            Expr::Variant(label, expr) => {
+                self.value(expr, code)?;
+                code.push(Instr::Variant(label.clone()));
+            }
+            Expr::Seq(exprs) => {
+                for expr in exprs {
+                    self.value(expr, code)?;
+                }
+                code.push(Instr::Seq(exprs.len()));
+            }
+            Expr::Match(head, branches) => {
+                self.expr(head, code)?;
+                let arms = branches
+                    .iter()
+                    .map(|(pattern, expr)| self.arm(pattern, |this| this.code(expr)))
+                    .collect::<AResult<_>>()?;
+                code.push(Instr::Match(arms));
+            }
+            Expr::Destructure(head, pattern, expr) => {
+                self.expr(head, code)?;
+                let arm = self.arm(pattern, |this| this.code(expr))?;
+                code.push(Instr::Destructure(Box::new(arm)));
+            }
+            Expr::Lambda(..) => return Err(anyhow!("cannot eval lambda")),
+            Expr::IntRel(rel, x, y) => {
+                self.value(x, code)?;
+                self.value(y, code)?;
+                code.push(Instr::IntRel(*rel));
+            }
+            Expr::Arith(op, x, y) => {
+                self.value(x, code)?;
+                self.value(y, code)?;
+                code.push(Instr::Arith(*op));
+            }
+            Expr::Unary(op, x) => {
+                self.value(x, code)?;
+                code.push(Instr::Unary(*op));
+            }
+            Expr::AsU8(x) => self.cast(Cast::AsU8, x, code)?,
+            Expr::AsU16(x) => self.cast(Cast::AsU16, x, code)?,
+            Expr::AsU32(x) => self.cast(Cast::AsU32, x, code)?,
+            Expr::AsU64(x) => self.cast(Cast::AsU64, x, code)?,
+            Expr::AsChar(x) => self.cast(Cast::AsChar, x, code)?,
+            Expr::U16Be(x) => self.cast(Cast::U16Be, x, code)?,
+            Expr::U16Le(x) => self.cast(Cast::U16Le, x, code)?,
+            Expr::U32Be(x) => self.cast(Cast::U32Be, x, code)?,
+            Expr::U32Le(x) => self.cast(Cast::U32Le, x, code)?,
+            Expr::U64Be(x) => self.cast(Cast::U64Be, x, code)?,
+            Expr::U64Le(x) => self.cast(Cast::U64Le, x, code)?,
+            Expr::F32FromBits(x) | Expr::F64FromBits(x) => {
+                self.cast(Cast::FloatFromBits, x, code)?
+            }
+            Expr::FixedFromBits(fx, x) => self.cast(Cast::FixedFromBits(*fx), x, code)?,
+            Expr::SeqLength(seq) => {
+                self.expr(seq, code)?;
+                code.push(Instr::SeqLength);
+            }
+            Expr::SeqIx(seq, index) => {
+                self.expr(seq, code)?;
+                self.value(index, code)?;
+                code.push(Instr::SeqIx);
+            }
+            Expr::SubSeq(seq, start, length) => {
+                self.expr(seq, code)?;
+                self.value(start, code)?;
+                self.value(length, code)?;
+                code.push(Instr::SubSeq);
+            }
+            Expr::SubSeqInflate(seq, start, length) => {
+                self.expr(seq, code)?;
+                self.value(start, code)?;
+                self.value(length, code)?;
+                code.push(Instr::SubSeqInflate);
+            }
+            Expr::Append(seq0, seq1) => {
+                self.expr(seq0, code)?;
+                self.expr(seq1, code)?;
+                code.push(Instr::Append);
+            }
+            Expr::Call(index, _, args) => {
+                for arg in args {
+                    self.value(arg, code)?;
+                }
+                code.push(Instr::Call(*index, args.len()));
+            }
+            Expr::FlatMap(f, seq) => {
+                self.expr(seq, code)?;
+                code.push(Instr::FlatMap(self.lambda(f)?));
+            }
+            Expr::FlatMapAccum(f, accum, _, seq) => {
+                self.value(accum, code)?;
+                self.value(seq, code)?;
+                code.push(Instr::FlatMapAccum(self.lambda(f)?));
+            }
+            Expr::FlatMapList(f, _, seq) => {
+                self.value(seq, code)?;
+                code.push(Instr::FlatMapList(self.lambda(f)?));
+            }
+            Expr::LeftFold(f, accum, _, seq) => {
+                self.value(accum, code)?;
+                self.value(seq, code)?;
+                code.push(Instr::LeftFold(self.lambda(f)?));
+            }
+            Expr::FindByKey(is_sorted, f, query, seq) => {
+                self.value(query, code)?;
+                self.value(seq, code)?;
+                code.push(Instr::FindByKey(*is_sorted, self.lambda(f)?));
+            }
+            Expr::Filter(f, seq) => {
+                self.expr(seq, code)?;
+                code.push(Instr::Filter(self.lambda(f)?));
+            }
+            Expr::Zip(seq0, seq1) => {
+                self.expr(seq0, code)?;
+                self.expr(seq1, code)?;
+                code.push(Instr::Zip);
+            }
+            Expr::Reverse(seq) => {
+                self.expr(seq, code)?;
+                code.push(Instr::Reverse);
+            }
+            Expr::Any(f, seq) => {
+                self.expr(seq, code)?;
+                code.push(Instr::Any(self.lambda(f)?));
+            }
+            Expr::All(f, seq) => {
+                self.expr(seq, code)?;
+                code.push(Instr::All(self.lambda(f)?));
+            }
+            Expr::IsSortedBy(f, seq) => {
+                self.expr(seq, code)?;
+                code.push(Instr::IsSortedBy(self.lambda(f)?));
+            }
+            Expr::Dup(count, expr) => {
+                self.value(count, code)?;
+                self.value(expr, code)?;
+                code.push(Instr::Dup);
+            }
+            Expr::EnumFromTo(start, stop) => {
+                self.value(start, code)?;
+                self.value(stop, code)?;
+                code.push(Instr::EnumFromTo);
+            }
+            Expr::LiftOption(None) => code.push(Instr::Const(Value::Option(None))),
+            Expr::LiftOption(Some(expr)) => {
+                self.value(expr, code)?;
+                code.push(Instr::Some);
+            }
+        }
+        Ok(())
+    }
+
+    fn code(&mut self, expr: &Expr) -> AResult<Code> {
+        let mut code = Vec::new();
+        self.value(expr, &mut code)?;
+        Ok(code.into_boxed_slice())
+    }
+
+    fn cast(&mut self, cast: Cast, x: &Expr, code: &mut Vec<Instr>) -> AResult<()> {
+        self.value(x, code)?;
+        code.push(Instr::Cast(cast));
+        Ok(())
+    }
+
+    /// If `expr` is a chain of tuple- and record-projections out of a variable, returns the slot of the
+    /// variable and the projections to apply to it.
+    fn path(&self, expr: &Expr) -> AResult<Option<(usize, Vec<Proj>)>> {
+        match expr {
+            Expr::Var(name) => Ok(Some((self.lookup_value(name)?, Vec::new()))),
+            Expr::TupleProj(head, index) => Ok(self.path(head)?.map(|(slot, mut path)| {
+                path.push(Proj::Tuple(*index));
+                (slot, path)
+            })),
+            Expr::RecordProj(head, label) => Ok(self.path(head)?.map(|(slot, mut path)| {
+                path.push(Proj::Record(label.clone()));
+                (slot, path)
+            })),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Returns `true` if `v` matches the or-free, unguarded `pattern`, as `Value::matches_inner` does, without
+/// collecting the bindings it makes (which `Arm` resolves ahead of time).
+fn is_match(v: &Value, pattern: &Pattern) -> EvalResult<bool> {
+    match (pattern, v) {
+        (Pattern::Binding(_) | Pattern::Wildcard, _) => Ok(true),
+        (Pattern::Tuple(ps), Value::Tuple(vs)) if ps.len() == vs.len() => {
+            all_match(Iterator::zip(vs.iter(), ps.iter()))
+        }
+        (Pattern::Seq(ps), Value::Seq(vs)) if ps.len() == vs.len() => {
+            all_match(Iterator::zip(vs.iter(), ps.iter()))
+        }
+        (Pattern::Variant(label0, p), Value::Variant(label1, v)) if label0 == label1 => {
+            is_match(v, p)
+        }
+        (Pattern::Option(Some(p)), Value::Option(Some(v))) => is_match(v, p),
+        (Pattern::Record(pfields), Value::Record(fields)) => {
+            for (label, p) in pfields.iter() {
+                let v = match fields.iter().find(|(l, _)| l == label) {
+                    Some((_, v)) => v.coerce_nominal_value(),
+                    None => panic!("{label} not found in record"),
+                };
+                if !is_match(v, p)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        (Pattern::Tuple(_) | Pattern::Seq(_) | Pattern::Variant(..) | Pattern::Record(_), _) => {
+            Ok(false)
+        }
+        // the remaining patterns bind nothing
+        _ => v.matches_inner(&mut MultiScope::new(&Scope::Empty), pattern),
+    }
+}
+
+fn all_match<'a>(pairs: impl Iterator<Item = (&'a Value, &'a Pattern)>) -> EvalResult<bool> {
+    for (v, p) in pairs {
+        if !is_match(v, p)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Collects the names bound by `pattern` along with the path to the part of the matched value each is bound
+/// to, in the order `Value::matches` binds them.
+fn pattern_bindings<'a>(
+    pattern: &'a Pattern,
+    path: &mut Vec<Proj>,
+    bindings: &mut Vec<(&'a Label, Vec<Proj>)>,
+) {
+    match pattern {
+        Pattern::Binding(name) => bindings.push((name, path.clone())),
+        Pattern::Tuple(ps) | Pattern::Seq(ps) => {
+            for (ix, p) in ps.iter().enumerate() {
+                path.push(Proj::Element(ix));
+                pattern_bindings(p, path, bindings);
+                path.pop();
+            }
+        }
+        Pattern::Variant(_, p) | Pattern::Option(Some(p)) => {
+            path.push(Proj::Inner);
+            pattern_bindings(p, path, bindings);
+            path.pop();
+        }
+        Pattern::Record(fields) => {
+            for (label, p) in fields {
+                path.extend([Proj::Record(label.clone()), Proj::Nominal]);
+                pattern_bindings(p, path, bindings);
+                path.truncate(path.len() - 2);
+            }
+        }
+        Pattern::Or(..) | Pattern::Guard(..) => {
+            unreachable!("or-patterns and guards are expanded before collecting bindings")
+        }
+        _ => {}
+    }
+}
+
+/// Value held on the operand stack, which may be shared with (a part of) a bound value rather than owned.
+#[derive(Clone, Debug)]
+enum Operand {
+    Owned(Value),
+    Shared(Rc<Value>),
+    Projected(Rc<Value>, Rc<[Proj]>),
+    /// Element of a shared sequence, as `Projected` with a single `Proj::Index`
+    Element(Rc<Value>, usize),
+}
+
+impl Operand {
+    fn get(&self) -> &Value {
+        match self {
+            Operand::Owned(v) => v,
+            Operand::Shared(v) => v,
+            Operand::Projected(v, path) => path.iter().fold(&**v, |v, proj| proj.apply(v)),
+            Operand::Element(v, ix) => Proj::Index(*ix).apply(v),
+        }
+    }
+
+    fn into_value(self) -> Value {
+        match self {
+            Operand::Owned(v) => v,
+            Operand::Shared(v) => Rc::unwrap_or_clone(v),
+            Operand::Projected(..) | Operand::Element(..) => self.get().clone(),
+        }
+    }
+
+    /// Moves an owned value behind a reference-count (unless it is flat), so that it can be copied cheaply.
+    fn share(self) -> Operand {
+        match self {
+            Operand::Owned(v) if !is_flat(&v) => Operand::Shared(Rc::new(v)),
+            other => other,
+        }
+    }
+
+    fn coerce(self) -> Operand {
+        match self {
+            Operand::Owned(v) => Operand::Owned(v.extract_mapped_value()),
+            shared => match shared.get() {
+                Value::Mapped(..) | Value::Branch(..) | Value::Permit(Ok(_)) => {
+                    Operand::Owned(shared.get().coerce_mapped_value().clone())
+                }
+                _ => shared,
+            },
+        }
+    }
+
+    fn project(self, proj: Proj) -> Operand {
+        match self {
+            Operand::Owned(v) => Operand::Owned(proj.take(v)),
+            shared => {
+                let target = proj.apply(shared.get());
+                if is_flat(target) {
+                    return Operand::Owned(target.clone());
+                }
+                match (shared, proj) {
+                    (Operand::Shared(v), Proj::Index(ix)) => Operand::Element(v, ix),
+                    (shared, proj) => shared.project_path(&Rc::from([proj])),
+                }
+            }
+        }
+    }
+
+    fn project_path(self, path: &Rc<[Proj]>) -> Operand {
+        if let Operand::Owned(v) = self {
+            return Operand::Owned(path.iter().fold(v, |v, proj| proj.take(v)));
+        }
+        let target = path.iter().fold(self.get(), |v, proj| proj.apply(v));
+        if is_flat(target) {
+            return Operand::Owned(target.clone());
+        }
+        match self {
+            Operand::Owned(_) => unreachable!(),
+            Operand::Shared(v) => Operand::Projected(v, path.clone()),
+            Operand::Projected(v, path0) => {
+                Operand::Projected(v, path0.iter().chain(path.iter()).cloned().collect())
+            }
+            Operand::Element(v, ix) => Operand::Projected(
+                v,
+                std::iter::once(Proj::Index(ix))
+                    .chain(path.iter().cloned())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Bindings of the decoder (or dynamic format) being executed.
+///
+/// Values are only ever bound in shared (or flat) form, so that loading them is cheap.
+#[derive(Default)]
+struct Frame {
+    slots: Vec<Option<Operand>>,
+    dyns: Vec<Option<Rc<Func>>>,
+}
+
+impl Frame {
+    fn new(func: &Func) -> Frame {
+        Frame {
+            slots: vec![None; func.n_slots],
+            dyns: vec![None; func.n_dyns],
+        }
+    }
+}
+
+/// Resolves the numeric variables of an `Expr::Numeric` to the slots they are bound to.
+struct SlotScope<'a> {
+    vars: &'a [(Label, usize)],
+    slots: &'a [Option<Operand>],
+}
+
+impl<'a> EvalScope<'a> for SlotScope<'a> {
+    type Output = &'a Value;
+    type Error = UnknownVarError;
+
+    fn lookup_var(&'a self, name: &str) -> Result<&'a Value, UnknownVarError> {
+        self.vars
+            .iter()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, slot)| self.slots[*slot].as_ref().map(Operand::get))
+            .ok_or_else(|| UnknownVarError(Label::from(name.to_string())))
+    }
+}
+
+struct Machine<'p> {
+    program: &'p VmProgram,
+    stack: Vec<Operand>,
+    frame: Frame,
+    /// Non-fatal errors encountered so far, discarded along with any branch that fails
+    warnings: Vec<DecodeError>,
+    /// Resource usage of the run, against the limits of the program
+    budget: Budget,
+}
+
+impl Machine<'_> {
+    fn push(&mut self, v: Value) {
+        self.stack.push(Operand::Owned(v));
+    }
+
+    fn pop(&mut self) -> Operand {
+        self.stack.pop().expect("operand stack underflow")
+    }
+
+    fn pop_value(&mut self) -> Value {
+        self.pop().into_value()
+    }
+
+    fn pop_values(&mut self, n: usize) -> Vec<Value> {
+        let base = self.stack.len() - n;
+        self.stack.drain(base..).map(Operand::into_value).collect()
+    }
+
+    fn wrap(&mut self, f: impl FnOnce(Value) -> Value) {
+        let v = self.pop_value();
+        self.push(f(v));
+    }
+
+    fn mark(&self) -> (usize, usize) {
+        (self.stack.len(), self.warnings.len())
+    }
+
+    fn backtrack(&mut self, (height, n_warnings): (usize, usize)) {
+        self.stack.truncate(height);
+        self.warnings.truncate(n_warnings);
+    }
+
+    fn tally_elements(&self, n: usize, input: ReadCtxt<'_>) -> DecodeResult<()> {
+        within_budget(self.budget.tally_elements(n), input)
+    }
+
+    /// Reads `count` bytes from `bs`, as `count` steps of `Op::Byte` would.
+    fn read_bytes<'i>(
+        &mut self,
+        bs: &ByteSet,
+        count: usize,
+        mut input: ReadCtxt<'i>,
+    ) -> DecodeResult<ReadCtxt<'i>> {
+        let mut v = Accum::with_capacity(true, count);
+        for _ in 0..count {
+            within_budget(self.budget.step(), input)?;
+            let offset = input.offset;
+            let (b, rest) = input.read_byte().ok_or(DecodeErrorKind::overbyte(offset))?;
+            if !bs.contains(b) {
+                return Err(DecodeErrorKind::unexpected(b, *bs, offset).into());
+            }
+            v.push(Value::U8(b));
+            input = rest;
+        }
+        self.push(v.into_value());
+        Ok(input)
+    }
+
+    fn bind(&mut self, slot: usize, v: Operand) {
+        self.frame.slots[slot] = Some(v.share());
+    }
+
+    /// Applies `f` to `arg`, releasing every binding made in the process.
+    fn call(&mut self, f: &Lambda, arg: Operand) -> EvalResult<Value> {
+        match f.binding {
+            Binding::Unused => return self.eval_body(f),
+            Binding::Whole => {
+                self.bind(f.param, arg);
+                return self.eval_body(f);
+            }
+            Binding::Pair => {}
+        }
+        match arg {
+            Operand::Owned(Value::Tuple(vs)) if vs.len() == 2 => {
+                let [first, second] = <[Value; 2]>::try_from(vs).unwrap();
+                self.call_pair(f, Operand::Owned(first), Operand::Owned(second))
+            }
+            arg => {
+                if !matches!(arg.get().coerce_nominal_value(), Value::Tuple(vs) if vs.len() == 2) {
+                    return Err(EvalError::NoMatch);
+                }
+                let arg = arg.share();
+                let first = arg
+                    .clone()
+                    .project_path(&Rc::from([Proj::Nominal, Proj::Element(0)]));
+                let second = arg.project_path(&Rc::from([Proj::Nominal, Proj::Element(1)]));
+                self.call_pair(f, first, second)
+            }
+        }
+    }
+
+    /// Applies `f` to the pair of `first` and `second`, without constructing it unless `f` requires it whole.
+    fn call_pair(&mut self, f: &Lambda, first: Operand, second: Operand) -> EvalResult<Value> {
+        if f.binding != Binding::Pair {
+            let arg = Value::Tuple(vec![first.into_value(), second.into_value()]);
+            return self.call(f, Operand::Owned(arg));
+        }
+        self.bind(f.param, first);
+        self.bind(f.param + 1, second);
+        self.eval_body(f)
+    }
+
+    fn eval_body(&mut self, f: &Lambda) -> EvalResult<Value> {
+        self.eval(&f.body)?;
+        let ret = self.pop_value();
+        self.frame.slots[f.param..f.end].fill(None);
+        Ok(ret)
+    }
+
+    /// Applies `f` to `arg`, returning the result along with `arg` itself.
+    fn apply(&mut self, f: &Lambda, arg: Value) -> EvalResult<(Value, Value)> {
+        if f.binding == Binding::Unused {
+            return Ok((self.eval_body(f)?, arg));
+        }
+        let arg = Rc::new(arg);
+        let ret = self.call(f, Operand::Shared(arg.clone()))?;
+        Ok((ret, Rc::unwrap_or_clone(arg)))
+    }
+
+    /// Binds the variables of the pattern of `arm` to the corresponding parts of `head`, returning `false` if
+    /// no alternative of it both matches and satisfies its guard.
+    ///
+    /// `head` is only moved behind a reference-count once some part of it is to be bound.
+    fn matches<B>(&mut self, head: &mut Operand, arm: &Arm<B>) -> EvalResult<bool> {
+        for (pattern, binds) in arm.alts.iter() {
+            if !is_match(head.get().coerce_nominal_value(), pattern)? {
+                continue;
+            }
+            if !binds.is_empty() {
+                *head = std::mem::replace(head, Operand::Owned(Value::UNIT)).share();
+            }
+            for (slot, path) in binds {
+                self.bind(*slot, head.clone().project_path(path));
+            }
+            match &arm.guard {
+                None => return Ok(true),
+                Some(guard) => {
+                    self.eval(guard)?;
+                    if self.pop_value().unwrap_bool() {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn view<'i>(
+        &mut self,
+        view: &ViewCode,
+        views: &[ReadCtxt<'i>],
+    ) -> Result<ReadCtxt<'i>, DecodeErrorKind> {
+        let mut ctxt = views[view.slot];
+        for offset in view.offsets.iter() {
+            self.eval(offset).map_err(DecodeErrorKind::eval)?;
+            let offset = self.pop_value().unwrap_usize();
+            let Some((_, window)) = ctxt.split_at(offset) else {
+                return Err(DecodeErrorKind::overrun(offset, ctxt.offset));
+            };
+            ctxt = window;
+        }
+        Ok(ctxt)
+    }
+
+    fn exec<'i>(
+        &mut self,
+        code: &[Op],
+        mut input: ReadCtxt<'i>,
+        views: &mut [ReadCtxt<'i>],
+    ) -> DecodeResult<ReadCtxt<'i>> {
+        for op in code {
+            input = self.step(op, input, views)?;
+        }
+        Ok(input)
+    }
+
+    fn step<'i>(
+        &mut self,
+        op: &Op,
+        input: ReadCtxt<'i>,
+        views: &mut [ReadCtxt<'i>],
+    ) -> DecodeResult<ReadCtxt<'i>> {
+        match op {
+            Op::Eval(instr) => {
+                self.eval_instr(instr)?;
+                return Ok(input);
+            }
+            Op::Store(slot) => {
+                let v = self.pop();
+                self.bind(*slot, v);
+                return Ok(input);
+            }
+            Op::StoreView(slot) => {
+                views[*slot] = input;
+                return Ok(input);
+            }
+            Op::Pop => {
+                self.pop();
+                return Ok(input);
+            }
+            _ => within_budget(self.budget.step(), input)?,
+        }
+        match op {
+            Op::Eval(_) | Op::Store(_) | Op::StoreView(_) | Op::Pop => unreachable!(),
+            Op::Call {
+                func,
+                n_args,
+                views: view_args,
+            } => {
+                let program = self.program;
+                let callee = &program.funcs[*func];
+                let mut frame = Frame::new(callee);
+                let base = self.stack.len() - n_args;
+                for (slot, arg) in self.stack.drain(base..).enumerate() {
+                    frame.slots[slot] = Some(arg.share());
+                }
+                let mut callee_views = vec![input; callee.n_views];
+                for (ix, view) in view_args.iter().enumerate() {
+                    callee_views[ix] = self.view(view, views)?;
+                }
+                within_budget(self.budget.enter(), input)?;
+                let caller = std::mem::replace(&mut self.frame, frame);
+                let res = self.exec(&callee.code, input, &mut callee_views);
+                self.frame = caller;
+                self.budget.leave();
+                match (res, &program.format_names[*func]) {
+                    (Err(e), Some(name)) => Err(e.within(name.clone())),
+                    (res, _) => res,
+                }
+            }
+            Op::Pos => {
+                self.push(Value::U64(input.position() as u64));
+                Ok(input)
+            }
+            Op::Fail => Err(DecodeErrorKind::<Value>::fail(&Scope::Empty, input).into()),
+            Op::EndOfInput => match input.read_byte() {
+                None => {
+                    self.push(Value::UNIT);
+                    Ok(input)
+                }
+                Some((b, _)) => Err(DecodeErrorKind::trailing(b, input.offset).into()),
+            },
+            Op::SkipRemainder => {
+                self.push(Value::UNIT);
+                Ok(input.skip_remainder())
+            }
+            Op::Phantom => {
+                self.push(Value::PhantomData);
+                Ok(input)
+            }
+            Op::Align(n) => {
+                let skip = (n - (input.position() % n)) % n;
+                let (_, input) = input
+                    .split_at(skip)
+                    .ok_or(DecodeErrorKind::overrun(skip, input.offset))?;
+                self.push(Value::UNIT);
+                Ok(input)
+            }
+            Op::Byte(bs) => {
+                let offset = input.offset;
+                let (b, input) = input.read_byte().ok_or(DecodeErrorKind::overbyte(offset))?;
+                if bs.contains(b) {
+                    self.push(Value::U8(b));
+                    Ok(input)
+                } else {
+                    Err(DecodeErrorKind::unexpected(b, *bs, offset).into())
+                }
+            }
+            Op::Branch(tree, branches) => {
+                let index = tree.matches(input).ok_or(DecodeErrorKind::NoValidBranch {
+                    offset: input.offset,
+                })?;
+                let input = self.exec(&branches[index], input, views)?;
+                self.wrap(|v| Value::Branch(index, Box::new(v)));
+                Ok(input)
+            }
+            Op::Parallel(branches) => {
+                let mark = self.mark();
+                for (index, branch) in branches.iter().enumerate() {
+                    match self.exec(branch, input, views) {
+                        Ok(input) => {
+                            self.wrap(|v| Value::Branch(index, Box::new(v)));
+                            return Ok(input);
+                        }
+                        Err(_) => self.backtrack(mark),
+                    }
+                }
+                Err(DecodeErrorKind::<Value>::fail(&Scope::Empty, input)
+                    .with_trace("no valid branch"))
+            }
+            Op::While(tree, body) => {
+                let mut input = input;
+                let mut v = Accum::with_capacity(is_byte(body), 0);
+                while tree.matches(input).ok_or(DecodeErrorKind::NoValidBranch {
+                    offset: input.offset,
+                })? == 0
+                {
+                    self.tally_elements(1, input)?;
+                    input = self.exec(body, input, views)?;
+                    v.push(self.pop_value());
+                }
+                self.push(v.into_value());
+                Ok(input)
+            }
+            Op::Until(tree, body) => {
+                let mut input = input;
+                let mut v = Accum::with_capacity(is_byte(body), 0);
+                loop {
+                    self.tally_elements(1, input)?;
+                    input = self.exec(body, input, views)?;
+                    v.push(self.pop_value());
+                    if tree.matches(input).ok_or(DecodeErrorKind::NoValidBranch {
+                        offset: input.offset,
+                    })? == 0
+                    {
+                        break;
+                    }
+                }
+                self.push(v.into_value());
+                Ok(input)
+            }
+            Op::RepeatCount(body) => {
+                let count = self.pop_value().unwrap_usize();
+                self.tally_elements(count, input)?;
+                if let [Op::Byte(bs)] = &body[..] {
+                    return self.read_bytes(bs, count, input);
+                }
+                let mut input = input;
+                let mut v = Accum::with_capacity(false, count);
+                for _ in 0..count {
+                    input = self.exec(body, input, views)?;
+                    v.push(self.pop_value());
+                }
+                self.push(v.into_value());
+                Ok(input)
+            }
+            Op::RepeatBetween(tree, body) => {
+                let max = self.pop_value().unwrap_usize();
+                let min = self.pop_value().unwrap_usize();
+                let mut input = input;
+                let mut v = Accum::with_capacity(is_byte(body), 0);
+                loop {
+                    if tree.matches(input).ok_or(DecodeErrorKind::NoValidBranch {
+                        offset: input.offset,
+                    })? == 0
+                        || v.len() == max
+                    {
+                        if v.len() < min {
+                            unreachable!("incoherent bounds for RepeatBetween(_, {min}, {max}, _)");
+                        }
+                        break;
+                    }
+                    self.tally_elements(1, input)?;
+                    input = self.exec(body, input, views)?;
+                    v.push(self.pop_value());
+                }
+                self.push(v.into_value());
+                Ok(input)
+            }
+            Op::RepeatUntilLast(f, body) => {
+                let mut input = input;
+                let mut v = Accum::with_capacity(is_byte(body), 0);
+                loop {
+                    self.tally_elements(1, input)?;
+                    input = self.exec(body, input, views)?;
+                    let va = self.pop_value();
+                    let (done, va) = self.apply(f, va)?;
+                    v.push(va);
+                    if done.unwrap_bool() {
+                        break;
+                    }
+                }
+                self.push(v.into_value());
+                Ok(input)
+            }
+            Op::RepeatUntilSeq(f, body) => {
+                let mut input = input;
+                let mut v = Vec::new();
+                loop {
+                    self.tally_elements(1, input)?;
+                    input = self.exec(body, input, views)?;
+                    v.push(self.pop_value());
+                    let (done, vs) = self.apply(f, Value::Seq(v.into()))?;
+                    v = match vs {
+                        Value::Seq(v) => v.into_vec(),
+                        _ => unreachable!(),
+                    };
+                    if done.unwrap_bool() {
+                        break;
+                    }
+                }
+                self.push(Value::Seq(v.into()));
+                Ok(input)
+            }
+            Op::AccumUntil { done, update, body } => {
+                let mut input = input;
+                let mut accum = self.pop_value();
+                let mut v = Vec::new();
+                loop {
+                    let done_arg = Value::Tuple(vec![accum, Value::Seq(v.into())]);
+                    let (is_done, done_arg) = self.apply(done, done_arg)?;
+                    let (next_accum, vs) = extract_pair(done_arg.unwrap_tuple());
+                    accum = next_accum;
+                    v = match vs {
+                        Value::Seq(v) => v.into_vec(),
+                        _ => unreachable!(),
+                    };
+                    if is_done.unwrap_bool() {
+                        break;
+                    }
+                    self.tally_elements(1, input)?;
+                    input = self.exec(body, input, views)?;
+                    let next_elem = self.pop_value();
+                    let (next_accum, update_arg) =
+                        self.apply(update, Value::Tuple(vec![accum, next_elem]))?;
+                    let (_, next_elem) = extract_pair(update_arg.unwrap_tuple());
+                    v.push(next_elem);
+                    accum = next_accum;
+                }
+                self.push(Value::Tuple(vec![accum, Value::Seq(v.into())]));
+                Ok(input)
+            }
+            Op::ForEach(slot, body) => {
+                let seq = self.pop().share();
+                let len = match seq.get().get_sequence() {
+                    Some(values) => values.len(),
+                    None => panic!("bad type for ForEach input"),
+                };
+                self.tally_elements(len, input)?;
+                let mut input = input;
+                let mut v = Accum::with_capacity(is_byte(body), len);
+                for ix in 0..len {
+                    self.bind(*slot, element(&seq, ix));
+                    input = self.exec(body, input, views)?;
+                    v.push(self.pop_value());
+                }
+                self.push(v.into_value());
+                Ok(input)
+            }
+            Op::Maybe(body) => {
+                if self.pop_value().unwrap_bool() {
+                    let input = self.exec(body, input, views)?;
+                    self.wrap(|v| Value::Option(Some(Box::new(v))));
+                    Ok(input)
+                } else {
+                    self.push(Value::Option(None));
+                    Ok(input)
+                }
+            }
+            Op::Peek(body) => {
+                self.exec(body, input, views)?;
+                Ok(input)
+            }
+            Op::PeekNot(body) => {
+                let mark = self.mark();
+                let res = self.exec(body, input, views);
+                self.backtrack(mark);
+                if res.is_ok() {
+                    Err(DecodeErrorKind::<Value>::fail(&Scope::Empty, input).with_trace("PeekNot"))
+                } else {
+                    self.push(Value::UNIT);
+                    Ok(input)
+                }
+            }
+            Op::Slice(body) => {
+                let size = self.pop_value().unwrap_usize();
+                let (slice, input) = input
+                    .split_at(size)
+                    .ok_or(DecodeErrorKind::overrun(size, input.offset))?;
+                self.exec(body, slice, views)?;
+                Ok(input)
+            }
+            Op::Bits(body) => {
+                let mut bits = Vec::with_capacity(input.remaining().len() * 8);
+                for b in input.remaining() {
+                    for i in 0..8 {
+                        bits.push((b & (1 << i)) >> i);
+                    }
+                }
+                let mut bit_views = views.to_vec();
+                let bits = self.exec(body, ReadCtxt::new(&bits), &mut bit_views)?;
+                let bytes_remain = bits.remaining().len() >> 3;
+                let bytes_read = input.remaining().len() - bytes_remain;
+                let (_, input) = input
+                    .split_at(bytes_read)
+                    .ok_or(DecodeErrorKind::overrun(bytes_read, input.offset))?;
+                Ok(input)
+            }
+            Op::WithRelativeOffset(body) => {
+                let offset = self.pop_value().unwrap_usize();
+                let base = self.pop_value().unwrap_usize();
+                let abs_offset = base + offset;
+                within_budget(self.budget.hop(), input)?;
+                let seek_input = input
+                    .seek_to(abs_offset)
+                    .ok_or(DecodeErrorKind::bad_seek(abs_offset, input.input.len()))?;
+                self.exec(body, seek_input, views)?;
+                Ok(input)
+            }
+            Op::DecodeBytes(body) => {
+                let raw = self.pop_value();
+                let bytes = raw.get_bytes();
+                let mut inner_views = views.to_vec();
+                let rem_input = self.exec(body, ReadCtxt::new(&bytes), &mut inner_views)?;
+                if let Some((b, _)) = rem_input.read_byte() {
+                    self.warnings.push(
+                        DecodeErrorKind::Trailing {
+                            byte: b,
+                            offset: rem_input.offset,
+                        }
+                        .into(),
+                    );
+                }
+                Ok(input)
+            }
+            Op::ParseFromView(view, body) => {
+                let view_window = self.view(view, views)?;
+                self.exec(body, view_window, views)?;
+                Ok(input)
+            }
+            Op::Map(f) => {
+                let orig = self.pop_value();
+                let (v, orig) = self.apply(f, orig)?;
+                self.push(Value::Mapped(Box::new(orig), Box::new(v)));
+                Ok(input)
+            }
+            Op::Where {
+                body,
+                cond,
+                expr,
+                severity,
+            } => {
+                let offset = input.offset;
+                let input = self.exec(body, input, views)?;
+                let v = self.pop_value();
+                let (holds, v) = self.apply(cond, v)?;
+                if !holds.unwrap_bool() {
+                    let err = DecodeErrorKind::bad_where(
+                        &Scope::Empty,
+                        expr.clone(),
+                        Box::new(v.clone()),
+                        offset,
+                    )
+                    .into();
+                    if severity.is_strict() {
+                        return Err(err);
+                    }
+                    self.warnings.push(err);
+                }
+                self.push(v);
+                Ok(input)
+            }
+            Op::Match(arms) => {
+                let mut head = self.pop();
+                for (index, arm) in arms.iter().enumerate() {
+                    if self.matches(&mut head, arm)? {
+                        let input = self.exec(&arm.body, input, views)?;
+                        self.wrap(|v| Value::Branch(index, Box::new(v)));
+                        return Ok(input);
+                    }
+                }
+                panic!(
+                    "non-exhaustive patterns: {:?} not in {:#?}",
+                    head.get(),
+                    arms.iter().map(|arm| &arm.pattern).collect::<Vec<_>>()
+                );
+            }
+            Op::LetFormat { body, slot, name } => {
+                let input = self
+                    .exec(body, input, views)
+                    .map_err(|e| e.within(name.clone()))?;
+                let v = self.pop();
+                self.bind(*slot, v);
+                Ok(input)
+            }
+            Op::Dynamic { slot, permuted } => {
+                let values = permuted.then(|| self.pop_value());
+                let lengths = self.pop_value();
+                let lengths = huffman_code_lengths(&lengths, values.as_ref());
+                let f = make_huffman_codes(&lengths);
+                let d = Compiler::compile_one(&f).unwrap();
+                let func = FuncCompiler::compile(&d, &[], &[], &mut Vec::new()).unwrap();
+                self.frame.dyns[*slot] = Some(Rc::new(func));
+                Ok(input)
+            }
+            Op::Apply(slot) => {
+                let func = self.frame.dyns[*slot].clone().expect("unbound format");
+                let caller = std::mem::replace(&mut self.frame, Frame::new(&func));
+                let res = self.exec(&func.code, input, &mut vec![input; func.n_views]);
+                self.frame = caller;
+                res
+            }
+            Op::Native(decode, n_args) => {
+                let args = self.pop_values(*n_args);
+                let offset = input.offset;
+                let (value, len) = decode(input.remaining(), &args)
+                    .map_err(|error| DecodeErrorKind::native(error, offset))?;
+                let (_, input) = input
+                    .split_at(len)
+                    .ok_or(DecodeErrorKind::overrun(len, offset))?;
+                self.push(value);
+                Ok(input)
+            }
+            Op::CaptureBytes(view) => {
+                let len = self.pop_value().unwrap_usize();
+                let mut buf = self.view(view, views)?;
+                let mut accum = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let Some((byte, new_buf)) = buf.read_byte() else {
+                        return Err(DecodeErrorKind::overbyte(buf.offset).into());
+                    };
+                    accum.push(byte);
+                    buf = new_buf;
+                }
+                self.push(Value::Seq(SeqKind::Bytes(accum.into())));
+                Ok(input)
+            }
+            Op::ReadArray(view, kind) => {
+                let len = self.pop_value().unwrap_usize();
+                let mut buf = self.view(view, views)?;
+                let mut accum = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let (val, new_buf) = read_base(buf, *kind)?;
+                    accum.push(val);
+                    buf = new_buf;
+                }
+                self.push(Value::Seq(accum.into()));
+                Ok(input)
+            }
+            Op::ReifyView(view) => {
+                let view = self.view(view, views)?;
+                self.push(Value::View {
+                    offset: view.offset,
+                });
+                Ok(input)
+            }
+            #[cfg(feature = "format_enforce")]
+            Op::Enforce(body) => {
+                let n_warnings = self.warnings.len();
+                let input = self.exec(body, input, views)?;
+                if self.warnings.len() > n_warnings {
+                    let e = self.warnings.swap_remove(n_warnings);
+                    self.warnings.truncate(n_warnings);
+                    return Err(e);
+                }
+                Ok(input)
+            }
+            Op::Permit(body, fallback) => {
+                let mark = self.mark();
+                match self.exec(body, input, views) {
+                    Ok(input) => {
+                        self.wrap(|v| Value::Permit(Ok(Box::new(v))));
+                        Ok(input)
+                    }
+                    Err(e) => {
+                        log::error!("downgraded error: {e}");
+                        self.backtrack(mark);
+                        self.eval(fallback)?;
+                        self.wrap(|v| Value::Permit(Err(Some(Box::new(v)))));
+                        self.warnings.push(e);
+                        Ok(input)
+                    }
+                }
+            }
+            Op::Resync(body, pattern) => {
+                let mark = self.mark();
+                match self.exec(body, input, views) {
+                    Ok(input) => {
+                        self.wrap(|v| Value::variant("ok", v));
+                        Ok(input)
+                    }
+                    Err(e) if !e.is_recoverable() => Err(e),
+                    Err(e) => {
+                        log::warn!("resynchronizing after error: {e}");
+                        self.backtrack(mark);
+                        let data = input.remaining();
+                        let len = resync_offset(data, pattern);
+                        let (_, rest) = input.split_at(len).unwrap();
+                        self.push(Value::resync_skipped(e, &data[..len]));
+                        Ok(rest)
+                    }
+                }
+            }
+        }
+    }
+
+    fn eval(&mut self, code: &[Instr]) -> EvalResult<()> {
+        for instr in code {
+            self.eval_instr(instr)?;
+        }
+        Ok(())
+    }
+
+    fn eval_instr(&mut self, instr: &Instr) -> EvalResult<()> {
+        match instr {
+            Instr::Const(v) => self.push(v.clone()),
+            Instr::Load(slot) => {
+                let v = self.frame.slots[*slot].clone().expect("unbound variable");
+                self.stack.push(v);
+            }
+            Instr::Take(slot) => {
+                let v = self.frame.slots[*slot].take().expect("unbound variable");
+                self.stack.push(v);
+            }
+            Instr::LoadPath(slot, path) => {
+                let v = self.frame.slots[*slot].clone().expect("unbound variable");
+                self.stack.push(v.project_path(path));
+            }
+            Instr::LoadValue(slot, path) => {
+                let v = self.frame.slots[*slot].as_ref().expect("unbound variable");
+                let target = path.iter().fold(v.get(), |v, proj| proj.apply(v));
+                let v = match target.coerce_mapped_value() {
+                    // skip the projection of the shared value where its coercion can simply be copied
+                    target if is_flat(target) => Operand::Owned(target.clone()),
+                    _ => v.clone().project_path(path).coerce(),
+                };
+                self.stack.push(v);
+            }
+            Instr::Coerce => {
+                let v = self.pop();
+                self.stack.push(v.coerce());
+            }
+            Instr::Tuple(n) => {
+                let vs = self.pop_values(*n);
+                self.push(Value::Tuple(vs));
+            }
+            Instr::Record(labels) => {
+                let base = self.stack.len() - labels.len();
+                let fields = self.stack.drain(base..).map(Operand::into_value);
+                let v = Value::Record(labels.iter().cloned().zip(fields).collect());
+                self.push(v);
+            }
+            Instr::Variant(label) => self.wrap(|v| Value::variant(label.clone(), v)),
+            Instr::Seq(n) => {
+                let vs = self.pop_values(*n);
+                self.push(Value::Seq(vs.into()));
+            }
+            Instr::Some => self.wrap(|v| Value::Option(Some(Box::new(v)))),
+            Instr::Proj(proj) => {
+                let v = self.pop();
+                self.stack.push(v.project(proj.clone()));
+            }
+            Instr::Numeric(n, vars) => {
+                let scope = SlotScope {
+                    vars,
+                    slots: &self.frame.slots,
+                };
+                let v = match n.eval(&scope) {
+                    Ok(v) => v.into(),
+                    Err(e) => return Err(EvalError::Numeric(e.to_string())),
+                };
+                self.push(v);
+            }
+            Instr::IntRel(rel) => {
+                let right = self.pop_value();
+                let left = self.pop_value();
+                self.push(Value::int_rel(*rel, left, right));
+            }
+            Instr::Arith(op) => {
+                let right = self.pop_value();
+                let left = self.pop_value();
+                self.push(Value::arith(*op, left, right)?);
+            }
+            Instr::Unary(op) => {
+                let v = self.pop_value();
+                self.push(Value::unary(*op, v)?);
+            }
+            Instr::Cast(cast) => self.wrap(|v| cast.apply(v)),
+            Instr::SeqLength => {
+                let seq = self.pop();
+                let len = match seq.get().coerce_mapped_value().get_sequence() {
+                    Some(values) => values.len(),
+                    _ => panic!("SeqLength: expected Seq"),
+                };
+                self.push(Value::U32(len as u32));
+            }
+            Instr::SeqIx => {
+                let index = self.pop_value().unwrap_usize();
+                let seq = self.pop();
+                let len = match seq.get().coerce_mapped_value().get_sequence() {
+                    Some(values) => values.len(),
+                    None => panic!("SeqIx: expected Seq (or RangeFromTo)"),
+                };
+                if index >= len {
+                    return Err(EvalError::OutOfBounds { index, len });
+                }
+                let elem = match seq.get().coerce_mapped_value() {
+                    Value::Seq(_) => seq.project(Proj::Index(index)),
+                    Value::EnumFromTo(range) => {
+                        Operand::Owned(Value::from(range.clone().nth(index).unwrap()))
+                    }
+                    _ => unreachable!(),
+                };
+                self.stack.push(elem);
+            }
+            Instr::SubSeq => {
+                let length = self.pop_value().unwrap_usize();
+                let start = self.pop_value().unwrap_usize();
+                let seq = self.pop();
+                let len = match seq.get().coerce_mapped_value().get_sequence() {
+                    Some(values) => values.len(),
+                    None => panic!("SubSeq: expected Seq"),
+                };
+                match start.checked_add(length) {
+                    Some(end) if end <= len => {}
+                    _ => {
+                        return Err(EvalError::OutOfBounds {
+                            index: start.saturating_add(length),
+                            len,
+                        });
+                    }
+                }
+                let v = match seq.get().coerce_mapped_value().get_sequence() {
+                    Some(ValueSeq::ValueSeq(values)) => Value::Seq(values.sub_seq(start, length)),
+                    Some(ValueSeq::IntRange(range)) => {
+                        Value::EnumFromTo(sub_range(range, start, length))
+                    }
+                    _ => panic!("SubSeq: expected Seq"),
+                };
+                self.push(v);
+            }
+            Instr::SubSeqInflate => {
+                let length = self.pop_value().unwrap_usize();
+                let start = self.pop_value().unwrap_usize();
+                let seq = self.pop();
+                let len = match seq.get().coerce_mapped_value().get_sequence() {
+                    Some(values) => values.len(),
+                    None => panic!("SubSeqInflate: expected Seq"),
+                };
+                if length > 0 && start >= len {
+                    return Err(EvalError::OutOfBounds { index: start, len });
+                }
+                let mut vs = Vec::new();
+                match seq.get().coerce_mapped_value().get_sequence() {
+                    Some(ValueSeq::ValueSeq(vs0)) => {
+                        for i in 0..length {
+                            if i + start < vs0.len() {
+                                vs.push(vs0[i + start].clone());
+                            } else {
+                                vs.push(vs[i + start - vs0.len()].clone());
+                            }
+                        }
+                    }
+                    Some(ValueSeq::IntRange(range)) => {
+                        let len = range.len();
+                        let mut iter = range.skip(start);
+                        for i in 0..length {
+                            if let Some(val) = iter.next() {
+                                vs.push(val.into());
+                            } else {
+                                vs.push(vs[i + start - len].clone());
+                            }
+                        }
+                    }
+                    _ => panic!("SubSeqInflate: expected Seq"),
+                }
+                self.push(Value::Seq(vs.into()));
+            }
+            Instr::Append => {
+                let seq1 = self.pop();
+                let seq0 = self.pop();
+                let v = match (
+                    seq0.get().coerce_mapped_value().get_sequence(),
+                    seq1.get().coerce_mapped_value().get_sequence(),
+                ) {
+                    (Some(val_seq0), Some(_)) if val_seq0.is_empty() => seq1.coerce().into_value(),
+                    (Some(_), Some(val_seq1)) if val_seq1.is_empty() => seq0.coerce().into_value(),
+                    (Some(val_seq0), Some(val_seq1)) => Value::Seq(val_seq0.append(val_seq1)),
+                    _ => unreachable!("Append: expected Seq in (lhs)"),
+                };
+                self.push(v);
+            }
+            Instr::Call(index, n_args) => {
+                let func = &self.program.fns[*index];
+                let base = self.stack.len() - n_args;
+                let mut slots = vec![None; func.n_slots];
+                for (slot, arg) in Iterator::zip(slots.iter_mut(), self.stack.drain(base..)) {
+                    *slot = Some(arg.share());
+                }
+                let frame = Frame {
+                    slots,
+                    dyns: Vec::new(),
+                };
+                let caller = std::mem::replace(&mut self.frame, frame);
+                let res = self.eval(&func.body);
+                self.frame = caller;
+                res?;
+            }
+            Instr::Dup => {
+                let v = self.pop_value();
+                let count = self.pop_value().unwrap_usize();
+                self.push(Value::Seq(SeqKind::Dup(count, Box::new(v))));
+            }
+            Instr::EnumFromTo => {
+                let stop = self.pop_value().unwrap_usize();
+                let start = self.pop_value().unwrap_usize();
+                self.push(Value::EnumFromTo(start..stop));
+            }
+            Instr::FlatMap(f) => {
+                let seq = self.pop().coerce().share();
+                let len = match seq.get().get_sequence() {
+                    Some(values) => values.len(),
+                    None => panic!("FlatMap: expected Seq"),
+                };
+                let mut vs = Vec::new();
+                for ix in 0..len {
+                    match self.call(f, element(&seq, ix))? {
+                        Value::Seq(vn) => vs.extend(vn),
+                        Value::EnumFromTo(range) => vs.extend(range.map(Value::from)),
+                        _ => panic!("FlatMap: expected Seq (or EnumFromTo)"),
+                    }
+                }
+                self.push(Value::Seq(vs.into()));
+            }
+            Instr::FlatMapAccum(f) => {
+                let seq = self.pop().share();
+                let Value::Seq(values) = seq.get() else {
+                    panic!("FlatMapAccum: expected Seq");
+                };
+                let len = values.len();
+                let mut accum = self.pop_value();
+                let mut vs = Vec::new();
+                for ix in 0..len {
+                    let ret = self.call_pair(f, Operand::Owned(accum), element(&seq, ix))?;
+                    accum = match extract_pair(ret.unwrap_tuple()) {
+                        (accum, Value::Seq(vn)) => {
+                            vs.extend(vn);
+                            accum
+                        }
+                        _ => panic!("FlatMapAccum: expected two values"),
+                    };
+                }
+                self.push(Value::Seq(vs.into()));
+            }
+            Instr::FlatMapList(f) => {
+                let seq = self.pop().share();
+                let Value::Seq(values) = seq.get() else {
+                    panic!("FlatMapList: expected Seq");
+                };
+                let len = values.len();
+                let mut vs = Vec::new();
+                for ix in 0..len {
+                    // the output so far is only lent to `f`, and so can be reclaimed without copying it
+                    let buffer = Rc::new(Value::Seq(SeqKind::Strict(vs)));
+                    let ret =
+                        self.call_pair(f, Operand::Shared(buffer.clone()), element(&seq, ix))?;
+                    let Value::Seq(vn) = ret else {
+                        panic!("FlatMapList: expected Seq");
+                    };
+                    vs = match Rc::unwrap_or_clone(buffer) {
+                        Value::Seq(vs) => vs.into_vec(),
+                        _ => unreachable!(),
+                    };
+                    vs.extend(vn);
+                }
+                self.push(Value::Seq(vs.into()));
+            }
+            Instr::LeftFold(f) => {
+                let seq = self.pop().share();
+                let Value::Seq(values) = seq.get() else {
+                    panic!("LeftFold: expected Seq");
+                };
+                let len = values.len();
+                let mut accum = self.pop_value();
+                for ix in 0..len {
+                    accum = self.call_pair(f, Operand::Owned(accum), element(&seq, ix))?;
+                }
+                self.push(accum);
+            }
+            Instr::FindByKey(is_sorted, f) => {
+                let Value::Seq(values) = self.pop_value() else {
+                    panic!("FindByKey: expected Seq");
+                };
+                let query = self.pop_value();
+                let ix = {
+                    let this = RefCell::new(&mut *self);
+                    let get_key = |v: &Value| Ok(this.borrow_mut().apply(f, v.clone())?.0);
+                    if *is_sorted {
+                        search::find_index_by_key_sorted(&query, &values, get_key)?
+                    } else {
+                        search::find_index_by_key_unsorted(&query, &values, get_key)?
+                    }
+                };
+                let found = ix.map(|ix| Box::new(values[ix].clone()));
+                self.push(Value::Option(found));
+            }
+            Instr::Filter(f) => {
+                let seq = self.pop().coerce().share();
+                let len = match seq.get().get_sequence() {
+                    Some(values) => values.len(),
+                    None => panic!("Filter: expected Seq"),
+                };
+                let mut vs = Vec::new();
+                for ix in 0..len {
+                    let elem = element(&seq, ix);
+                    if self.call(f, elem.clone())?.unwrap_bool() {
+                        vs.push(elem.into_value());
+                    }
+                }
+                self.push(Value::Seq(vs.into()));
+            }
+            Instr::Zip => {
+                let seq1 = self.pop();
+                let seq0 = self.pop();
+                let vs: Vec<Value> = match (
+                    seq0.get().coerce_mapped_value().get_sequence(),
+                    seq1.get().coerce_mapped_value().get_sequence(),
+                ) {
+                    (Some(values0), Some(values1)) => values0
+                        .into_iter()
+                        .zip(values1)
+                        .map(|(v0, v1)| Value::Tuple(vec![v0.into_owned(), v1.into_owned()]))
+                        .collect(),
+                    _ => panic!("Zip: expected Seq"),
+                };
+                self.push(Value::Seq(vs.into()));
+            }
+            Instr::Reverse => {
+                let seq = self.pop();
+                let mut vs: Vec<Value> = match seq.get().coerce_mapped_value().get_sequence() {
+                    Some(values) => values.into_iter().map(|v| v.into_owned()).collect(),
+                    None => panic!("Reverse: expected Seq"),
+                };
+                vs.reverse();
+                self.push(Value::Seq(vs.into()));
+            }
+            Instr::Any(f) => {
+                let seq = self.pop().coerce().share();
+                let len = match seq.get().get_sequence() {
+                    Some(values) => values.len(),
+                    None => panic!("Any: expected Seq"),
+                };
+                let mut any = false;
+                for ix in 0..len {
+                    if self.call(f, element(&seq, ix))?.unwrap_bool() {
+                        any = true;
+                        break;
+                    }
+                }
+                self.push(Value::Bool(any));
+            }
+            Instr::All(f) => {
+                let seq = self.pop().coerce().share();
+                let len = match seq.get().get_sequence() {
+                    Some(values) => values.len(),
+                    None => panic!("All: expected Seq"),
+                };
+                let mut all = true;
+                for ix in 0..len {
+                    if !self.call(f, element(&seq, ix))?.unwrap_bool() {
+                        all = false;
+                        break;
+                    }
+                }
+                self.push(Value::Bool(all));
+            }
+            Instr::IsSortedBy(f) => {
+                let seq = self.pop().coerce().share();
+                let len = match seq.get().get_sequence() {
+                    Some(values) => values.len(),
+                    None => panic!("IsSortedBy: expected Seq"),
+                };
+                let mut is_sorted = true;
+                for ix in 1..len {
+                    let (first, second) = (element(&seq, ix - 1), element(&seq, ix));
+                    if !self.call_pair(f, first, second)?.unwrap_bool() {
+                        is_sorted = false;
+                        break;
+                    }
+                }
+                self.push(Value::Bool(is_sorted));
+            }
+            Instr::Match(arms) => {
+                let mut head = self.pop();
+                for arm in arms.iter() {
+                    if self.matches(&mut head, arm)? {
+                        return self.eval(&arm.body);
+                    }
+                }
+                return Err(EvalError::NoMatch);
+            }
+            Instr::Destructure(arm) => {
+                let mut head = self.pop();
+                if !self.matches(&mut head, arm)? {
+                    return Err(EvalError::NoMatch);
+                }
+                self.eval(&arm.body)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns `true` if `v` can be copied without allocating.
+fn is_flat(v: &Value) -> bool {
+    match v {
+        Value::Bool(_)
+        | Value::U8(_)
+        | Value::U16(_)
+        | Value::U32(_)
+        | Value::U64(_)
+        | Value::Char(_)
+        | Value::Usize(_)
+        | Value::Float(_)
+        | Value::Fixed(_)
+        | Value::Numeric(_)
+        | Value::View { .. }
+        | Value::PhantomData
+        | Value::EnumFromTo(_)
+        | Value::Option(None) => true,
+        Value::Tuple(vs) => vs.is_empty(),
+        _ => false,
+    }
+}
+
+/// Returns the element of the (coerced) sequence `seq` at `ix`, sharing it if possible.
+fn element(seq: &Operand, ix: usize) -> Operand {
+    match seq.get() {
+        Value::Seq(_) => seq.clone().project(Proj::Index(ix)),
+        Value::EnumFromTo(range) => Operand::Owned(Value::from(range.start + ix)),
+        other => unreachable!("expected sequence, found {other:?}"),
+    }
+}
+
+/// Returns `true` if `body` decodes a single byte, so that its repetitions can accumulate the bytes themselves.
+fn is_byte(body: &[Op]) -> bool {
+    matches!(body, [Op::Byte(_)])
+}
@@ -0,0 +1,1150 @@
+//! Serialization of decoded values back into bytes, using the same [`Format`] they were decoded from.
+//!
+//! The encoder walks a `Format` alongside a [`Value`] and emits the bytes that would decode to that
+//! value. Only the invertible subset of formats is supported: anything whose value does not retain
+//! enough information to reproduce its bytes (such as `Format::Map` over a value that has lost its
+//! preimage) yields an [`EncodeError`] rather than a guess.
+//!
+//! Values produced by the decoder are always sufficient in this respect, as they keep the preimage of every
+//! `Map` (`Value::Mapped`) and the index of every union-branch (`Value::Branch`). Derived fields
+//! (`Format::Compute`) are recomputed and checked against the value rather than encoded.
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::ops::Range;
+
+mod chain;
+
+use crate::decoder::{
+    MultiScope, Scope, SeqKind, SingleScope, Value, huffman_lengths, make_huffman_codes,
+};
+use crate::error::{EncodeError, EncodeResult, EvalResult};
+use crate::{
+    Arith, BaseKind, CommonOp, DynFormat, Endian, Expr, Format, FormatModule, FormatRef, Label,
+    StyleHint, ViewExpr, ViewFormat,
+};
+
+/// Reconstructs a complete value of a format from a partial one (see [`Encoder::with_completion`]).
+type Completion<'a> = Box<dyn Fn(&Value) -> Option<Value> + 'a>;
+
+/// Encoder for values decoded from formats within a given [`FormatModule`].
+pub struct Encoder<'a> {
+    module: &'a FormatModule,
+    completions: Vec<(usize, Completion<'a>)>,
+}
+
+/// Bindings that live outside of the value-scope: views (as absolute offsets) and dynamic formats.
+pub(crate) enum Env<'a> {
+    /// View-arguments of the top-level format or of the current `ItemVar` invocation
+    Call(Vec<(Label, usize)>),
+    View(&'a Env<'a>, &'a str, usize),
+    Dynamic(&'a Env<'a>, &'a str, Format),
+    Pending(&'a Env<'a>, &'a Pending<'a>),
+}
+
+/// Integer binding that is only ever used as the size of later slices, and so is solved for by encoding them.
+///
+/// Its bytes are written as a placeholder, and patched once the size of a slice it measures is known.
+pub(crate) struct Pending<'a> {
+    name: Label,
+    value: Cell<Option<u64>>,
+    /// Binding of the calling format that was passed in verbatim as the argument `name`
+    source: Option<&'a Pending<'a>>,
+}
+
+impl<'a> Pending<'a> {
+    pub(crate) fn new(name: Label, source: Option<&'a Pending<'a>>) -> Pending<'a> {
+        let value = Cell::new(source.and_then(|p| p.value.get()));
+        Pending {
+            name,
+            value,
+            source,
+        }
+    }
+
+    pub(crate) fn value(&self) -> Option<u64> {
+        self.value.get()
+    }
+
+    fn resolve(&self, n: u64) -> EncodeResult<()> {
+        match self.value.get() {
+            Some(prior) if prior != n => Err(EncodeError::BadLength {
+                expected: prior as usize,
+                found: n as usize,
+            }),
+            Some(_) => Ok(()),
+            None => {
+                self.value.set(Some(n));
+                match self.source {
+                    Some(source) => source.resolve(n),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Env<'a> {
+    pub(crate) const EMPTY: Env<'static> = Env::Call(Vec::new());
+
+    pub(crate) fn get_view_by_name(&self, name: &str) -> usize {
+        match self {
+            Env::Call(views) => match views.iter().rev().find(|(n, _)| n == name) {
+                Some((_, offset)) => *offset,
+                None => panic!("view not found: {name}"),
+            },
+            Env::View(_, n, offset) if *n == name => *offset,
+            Env::View(parent, ..) | Env::Dynamic(parent, ..) | Env::Pending(parent, _) => {
+                parent.get_view_by_name(name)
+            }
+        }
+    }
+
+    /// Evaluates a view-expression to the absolute offset it refers to.
+    pub(crate) fn eval_view_expr(&self, scope: &Scope<'_>, v_expr: &ViewExpr) -> EvalResult<usize> {
+        match v_expr {
+            ViewExpr::Var(name) => Ok(self.get_view_by_name(name)),
+            ViewExpr::Offset(base, offset) => {
+                Ok(self.eval_view_expr(scope, base)? + offset.eval_value(scope)?.unwrap_usize())
+            }
+        }
+    }
+
+    pub(crate) fn get_format_by_name(&self, name: &str) -> &Format {
+        match self {
+            Env::Call(_) => panic!("dynamic format not found: {name}"),
+            Env::Dynamic(_, n, format) if *n == name => format,
+            Env::View(parent, ..) | Env::Dynamic(parent, ..) | Env::Pending(parent, _) => {
+                parent.get_format_by_name(name)
+            }
+        }
+    }
+
+    /// Returns the pending binding that `expr` refers to, if any.
+    pub(crate) fn get_pending(&self, expr: &Expr) -> Option<&Pending<'_>> {
+        match self {
+            Env::Call(_) => None,
+            Env::Pending(_, pending) if expr.is_shadowed_by(&pending.name) => Some(pending),
+            Env::View(parent, ..) | Env::Dynamic(parent, ..) | Env::Pending(parent, _) => {
+                parent.get_pending(expr)
+            }
+        }
+    }
+}
+
+/// Byte-buffer being written to, starting at an absolute offset of `base`.
+///
+/// Bytes that are not determined by the value (alignment and slice padding) are marked as `soft`, and
+/// may be overwritten by the encodings of out-of-line formats (e.g. `WithRelativeOffset`), which are
+/// collected as `patches` to be written at their own absolute offsets once encoding is complete.
+pub(crate) struct Sink {
+    base: usize,
+    bytes: Vec<u8>,
+    soft: Vec<Range<usize>>,
+    patches: Vec<Sink>,
+}
+
+impl Sink {
+    pub(crate) fn new(base: usize) -> Sink {
+        Sink {
+            base,
+            bytes: Vec::new(),
+            soft: Vec::new(),
+            patches: Vec::new(),
+        }
+    }
+
+    /// Absolute offset of the next byte to be written
+    pub(crate) fn offset(&self) -> usize {
+        self.base + self.bytes.len()
+    }
+
+    pub(crate) fn push(&mut self, byte: u8) {
+        self.bytes.push(byte);
+    }
+
+    pub(crate) fn pad(&mut self, len: usize) {
+        let start = self.offset();
+        self.bytes.resize(self.bytes.len() + len, 0);
+        self.soft.push(start..start + len);
+    }
+
+    /// Appends the contents of `other`, which must start where `self` currently ends.
+    pub(crate) fn append(&mut self, other: Sink) {
+        debug_assert_eq!(other.base, self.offset());
+        self.bytes.extend(other.bytes);
+        self.soft.extend(other.soft);
+        self.patches.extend(other.patches);
+    }
+
+    /// Number of bytes written in-line, excluding any patches
+    pub(crate) fn written(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub(crate) fn add_patch(&mut self, patch: Sink) {
+        self.patches.push(patch);
+    }
+
+    /// Packs the contents of `bits` (one bit per byte, least-significant bit first) into bytes and appends them.
+    ///
+    /// Fails if `bits` has any patches, as these have no meaningful position within the bitstream.
+    pub(crate) fn append_bits(&mut self, bits: Sink) -> EncodeResult<()> {
+        if !bits.patches.is_empty() {
+            return Err(EncodeError::NotInvertible {
+                construct: "Bits",
+                reason: "out-of-line formats within a bitstream are not supported",
+            });
+        }
+        for chunk in bits.bytes.chunks(8) {
+            let byte = chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |acc, (i, bit)| acc | ((bit & 1) << i));
+            self.push(byte);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> EncodeResult<Vec<u8>> {
+        let mut image = Image {
+            bytes: Vec::new(),
+            fixed: Vec::new(),
+        };
+        image.write(self)?;
+        Ok(image.bytes)
+    }
+}
+
+/// Flattened output of a [`Sink`] and all of its patches.
+struct Image {
+    bytes: Vec<u8>,
+    fixed: Vec<bool>,
+}
+
+impl Image {
+    fn write(&mut self, sink: Sink) -> EncodeResult<()> {
+        let end = sink.offset();
+        if self.bytes.len() < end {
+            self.bytes.resize(end, 0);
+            self.fixed.resize(end, false);
+        }
+        let mut soft = sink.soft.iter().peekable();
+        for (ix, byte) in sink.bytes.into_iter().enumerate() {
+            let offset = sink.base + ix;
+            while soft.next_if(|range| range.end <= offset).is_some() {}
+            if soft.peek().is_some_and(|range| range.contains(&offset)) {
+                continue;
+            }
+            if self.fixed[offset] && self.bytes[offset] != byte {
+                return Err(EncodeError::Conflict { offset });
+            }
+            self.bytes[offset] = byte;
+            self.fixed[offset] = true;
+        }
+        for patch in sink.patches {
+            self.write(patch)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Encoder<'a> {
+    pub fn new(module: &'a FormatModule) -> Encoder<'a> {
+        Encoder {
+            module,
+            completions: Vec::new(),
+        }
+    }
+
+    /// Registers a fallback for values of `format` that cannot be encoded as they are.
+    ///
+    /// Values that are reconstructed from the parts of them that another format depends on (e.g. the
+    /// inflated contents of a compressed stream) may not determine the bytes of their format. If such a
+    /// value fails to encode, `complete` is given the chance to provide a complete value to encode instead.
+    pub fn with_completion(
+        mut self,
+        format: FormatRef,
+        complete: impl Fn(&Value) -> Option<Value> + 'a,
+    ) -> Encoder<'a> {
+        self.completions
+            .push((format.get_level(), Box::new(complete)));
+        self
+    }
+
+    /// Encodes `value` according to `format`, returning bytes that decode back to `value`.
+    pub fn encode(&self, format: &Format, value: &Value) -> EncodeResult<Vec<u8>> {
+        let mut sink = Sink::new(0);
+        self.encode_format(
+            format,
+            value,
+            &Scope::Functions(self.module.functions()),
+            &Env::EMPTY,
+            &mut sink,
+        )?;
+        sink.finish()
+    }
+
+    fn encode_format(
+        &self,
+        format: &Format,
+        value: &Value,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> EncodeResult<()> {
+        match format {
+            Format::ItemVar(level, arg_exprs, arg_views) => {
+                let arg_names = self.module.get_args(*level);
+                let mut new_scope = MultiScope::with_capacity(scope.root(), arg_names.len());
+                for ((name, _), expr) in Iterator::zip(arg_names.iter(), arg_exprs.iter()) {
+                    new_scope.push_owned(name.clone(), expr.eval_value(scope)?);
+                }
+                let view_names = self.module.get_view_args(*level);
+                let call_env = Env::Call(
+                    Iterator::zip(view_names.iter(), arg_views.iter())
+                        .map(|(name, v_expr)| {
+                            Ok((name.clone(), env.eval_view_expr(scope, v_expr)?))
+                        })
+                        .collect::<EvalResult<_>>()?,
+                );
+                let pending = Iterator::zip(arg_names.iter(), arg_exprs.iter())
+                    .filter_map(|((name, _), expr)| {
+                        let arg @ Expr::Var(_) = strip_casts(expr) else {
+                            return None;
+                        };
+                        let source = env.get_pending(arg)?;
+                        Some(Pending::new(name.clone(), Some(source)))
+                    })
+                    .collect::<Vec<_>>();
+                let format = self.module.get_format(*level);
+                let new_scope = Scope::Multi(&new_scope);
+                let Some((_, complete)) = self.completions.iter().find(|(l, _)| l == level) else {
+                    return self
+                        .encode_pending(format, value, &new_scope, &call_env, &pending, sink);
+                };
+                let mut item_sink = Sink::new(sink.offset());
+                match self.encode_pending(
+                    format,
+                    value,
+                    &new_scope,
+                    &call_env,
+                    &pending,
+                    &mut item_sink,
+                ) {
+                    Ok(()) => {
+                        sink.append(item_sink);
+                        Ok(())
+                    }
+                    Err(err) => {
+                        let completed = complete(value).ok_or(err)?;
+                        self.encode_pending(
+                            format, &completed, &new_scope, &call_env, &pending, sink,
+                        )
+                    }
+                }
+            }
+            Format::GenericVar(index, formats, arg_exprs, arg_views) => {
+                let generic = self.module.get_generic(*index);
+                let mut new_scope = MultiScope::with_capacity(scope.root(), generic.args.len());
+                for ((name, _), expr) in Iterator::zip(generic.args.iter(), arg_exprs.iter()) {
+                    new_scope.push_owned(name.clone(), expr.eval_value(scope)?);
+                }
+                let call_env = Env::Call(
+                    Iterator::zip(generic.views.iter(), arg_views.iter())
+                        .map(|(name, v_expr)| {
+                            Ok((name.clone(), env.eval_view_expr(scope, v_expr)?))
+                        })
+                        .collect::<EvalResult<_>>()?,
+                );
+                self.encode_format(
+                    &generic.instantiate(formats),
+                    value,
+                    &Scope::Multi(&new_scope),
+                    &call_env,
+                    sink,
+                )
+            }
+            Format::Fail => Err(EncodeError::Fail),
+            Format::EndOfInput | Format::SkipRemainder | Format::Pos | Format::Phantom(_) => Ok(()),
+            Format::Peek(_) | Format::PeekNot(_) | Format::DecodeBytes(..) => Ok(()),
+            Format::Native(..) => Err(EncodeError::NotInvertible {
+                construct: "Native",
+                reason: "native formats are only implemented for decoding",
+            }),
+            Format::Align(n) => {
+                sink.pad((n - (sink.offset() % n)) % n);
+                Ok(())
+            }
+            Format::Byte(bs) => match value {
+                Value::U8(b) if bs.contains(*b) => {
+                    sink.push(*b);
+                    Ok(())
+                }
+                Value::U8(b) => Err(EncodeError::Unexpected {
+                    found: *b,
+                    expected: *bs,
+                }),
+                other => Err(mismatch("U8", other)),
+            },
+            Format::Variant(label, f) => match value {
+                Value::Variant(l, v) if l == label => self.encode_format(f, v, scope, env, sink),
+                other => Err(mismatch("Variant", other)),
+            },
+            Format::Union(branches) | Format::UnionNondet(branches) => match value {
+                Value::Branch(index, v) => match branches.get(*index) {
+                    Some(f) => self.encode_format(f, v, scope, env, sink),
+                    None => Err(mismatch("Branch", value)),
+                },
+                // NOTE - values that were not produced by the decoder are encoded with the first branch that accepts them
+                _ => {
+                    for f in branches {
+                        let mut branch_sink = Sink::new(sink.offset());
+                        if self
+                            .encode_format(f, value, scope, env, &mut branch_sink)
+                            .is_ok()
+                        {
+                            sink.append(branch_sink);
+                            return Ok(());
+                        }
+                    }
+                    Err(EncodeError::NoValidBranch {
+                        value: Box::new(value.clone()),
+                    })
+                }
+            },
+            Format::Tuple(fields) => match value {
+                Value::Tuple(vs) if vs.len() == fields.len() => {
+                    for (f, v) in Iterator::zip(fields.iter(), vs.iter()) {
+                        self.encode_format(f, v, scope, env, sink)?;
+                    }
+                    Ok(())
+                }
+                other => Err(mismatch("Tuple", other)),
+            },
+            Format::Sequence(formats) => {
+                let seq = get_seq(value)?;
+                check_length(formats.len(), seq.len())?;
+                for (f, v) in Iterator::zip(formats.iter(), seq) {
+                    self.encode_format(f, v, scope, env, sink)?;
+                }
+                Ok(())
+            }
+            Format::Repeat(a) | Format::RepeatUntilLast(_, a) | Format::RepeatUntilSeq(_, a) => {
+                self.encode_seq(a, get_seq(value)?, scope, env, sink)
+            }
+            Format::Repeat1(a) => {
+                let seq = get_seq(value)?;
+                if seq.is_empty() {
+                    return Err(EncodeError::BadLength {
+                        expected: 1,
+                        found: 0,
+                    });
+                }
+                self.encode_seq(a, seq, scope, env, sink)
+            }
+            Format::RepeatCount(expr, a) => {
+                let seq = get_seq(value)?;
+                check_length(expr.eval_value(scope)?.unwrap_usize(), seq.len())?;
+                self.encode_seq(a, seq, scope, env, sink)
+            }
+            Format::RepeatBetween(min, max, a) => {
+                let seq = get_seq(value)?;
+                let min = min.eval_value(scope)?.unwrap_usize();
+                let max = max.eval_value(scope)?.unwrap_usize();
+                if seq.len() < min {
+                    check_length(min, seq.len())?;
+                } else if seq.len() > max {
+                    check_length(max, seq.len())?;
+                }
+                self.encode_seq(a, seq, scope, env, sink)
+            }
+            Format::AccumUntil(.., a) => match value {
+                Value::Tuple(vs) if vs.len() == 2 => {
+                    self.encode_seq(a, get_seq(&vs[1])?, scope, env, sink)
+                }
+                other => Err(mismatch("Tuple", other)),
+            },
+            Format::ForEach(expr, lbl, a) => {
+                let seq = get_seq(value)?;
+                let val = expr.eval_value(scope)?;
+                let items = val.get_sequence().expect("bad type for ForEach input");
+                check_length(items.len(), seq.len())?;
+                for (item, v) in Iterator::zip(items.into_iter(), seq) {
+                    let new_scope = Scope::Single(SingleScope::new(scope, lbl, &item));
+                    self.encode_format(a, v, &new_scope, env, sink)?;
+                }
+                Ok(())
+            }
+            Format::Maybe(expr, a) => {
+                let is_present = expr.eval_value(scope)?.unwrap_bool();
+                match value {
+                    Value::Option(Some(v)) if is_present => {
+                        self.encode_format(a, v, scope, env, sink)
+                    }
+                    Value::Option(None) if !is_present => Ok(()),
+                    other => Err(mismatch(if is_present { "Some" } else { "None" }, other)),
+                }
+            }
+            Format::Slice(expr, a) => {
+                let mut slice_sink = Sink::new(sink.offset());
+                self.encode_format(a, value, scope, env, &mut slice_sink)?;
+                let written = slice_sink.written();
+                let size = match env.get_pending(expr) {
+                    Some(pending) => match pending.value() {
+                        Some(n) => eval_size(expr, &pending.name, n, scope),
+                        None => {
+                            let n = invert_size(expr, &pending.name, written as u64, scope)
+                                .ok_or(EncodeError::NotInvertible {
+                                    construct: "Slice",
+                                    reason: "length cannot be solved for from the size of the slice",
+                                })?;
+                            pending.resolve(n)?;
+                            Some(written)
+                        }
+                    }
+                    .ok_or(EncodeError::NotInvertible {
+                        construct: "Slice",
+                        reason: "length cannot be evaluated from its placeholder",
+                    })?,
+                    None => expr.eval_value(scope)?.unwrap_usize(),
+                };
+                if written > size {
+                    return Err(EncodeError::SliceOverflow { size, written });
+                }
+                slice_sink.pad(size - written);
+                sink.append(slice_sink);
+                Ok(())
+            }
+            Format::Bits(a) => {
+                let mut bit_sink = Sink::new(0);
+                self.encode_format(a, value, scope, env, &mut bit_sink)?;
+                sink.append_bits(bit_sink)
+            }
+            Format::WithRelativeOffset(base_addr, expr, a) => {
+                let base = base_addr.eval_value(scope)?.unwrap_usize();
+                let offset = expr.eval_value(scope)?.unwrap_usize();
+                self.encode_patch(base + offset, a, value, scope, env, sink)
+            }
+            Format::Map(a, _expr) => match value {
+                Value::Mapped(orig, _) => self.encode_format(a, orig, scope, env, sink),
+                _ => Err(EncodeError::NotInvertible {
+                    construct: "Map",
+                    reason: "value does not retain the preimage of the mapping",
+                }),
+            },
+            Format::Where(a, cond) => {
+                self.encode_format(a, value, scope, env, sink)?;
+                if cond.is_required() && !cond.as_ref().eval_lambda(scope, value)?.unwrap_bool() {
+                    return Err(EncodeError::BadWhere {
+                        assertion: Box::new(cond.as_ref().clone()),
+                        exception: Box::new(value.clone()),
+                    });
+                }
+                Ok(())
+            }
+            Format::Compute(expr) => {
+                let computed = expr.eval_value(scope)?;
+                if !same_value(&computed, value) {
+                    return Err(EncodeError::BadCompute {
+                        expr: expr.clone(),
+                        computed: Box::new(computed),
+                        found: Box::new(value.clone()),
+                    });
+                }
+                Ok(())
+            }
+            Format::Let(name, expr, a) => {
+                let v = expr.eval_value(scope)?;
+                let let_scope = Scope::Single(SingleScope::new(scope, name, &v));
+                self.encode_format(a, value, &let_scope, env, sink)
+            }
+            Format::Match(head, branches) => {
+                let head = head.eval(scope)?;
+                let (index, v) = match value {
+                    Value::Branch(index, v) if *index < branches.len() => (Some(*index), &**v),
+                    _ => (None, value),
+                };
+                for (ix, (pattern, f)) in branches.iter().enumerate() {
+                    if index.is_some_and(|index| index != ix) {
+                        continue;
+                    }
+                    if let Some(pattern_scope) = head.matches(scope, pattern)? {
+                        return self.encode_format(f, v, &Scope::Multi(&pattern_scope), env, sink);
+                    }
+                }
+                Err(EncodeError::NoValidBranch {
+                    value: Box::new(value.clone()),
+                })
+            }
+            Format::Dynamic(name, DynFormat::Huffman(lengths_expr, opt_values_expr), a) => {
+                let lengths = huffman_lengths(scope, lengths_expr, opt_values_expr.as_deref())?;
+                let dyn_env = Env::Dynamic(env, name, make_huffman_codes(&lengths));
+                self.encode_format(a, value, scope, &dyn_env, sink)
+            }
+            Format::Apply(name) => {
+                self.encode_format(env.get_format_by_name(name), value, scope, env, sink)
+            }
+            Format::LetFormat(..) | Format::MonadSeq(..) => {
+                self.encode_chain(format, value, scope, env, sink)
+            }
+            Format::Hint(StyleHint::Common(CommonOp::EndianParse(kind)), a) => match value {
+                Value::Mapped(..) => self.encode_format(a, value, scope, env, sink),
+                _ => encode_base(*kind, value, sink),
+            },
+            Format::Hint(_, a) => self.encode_format(a, value, scope, env, sink),
+            Format::LiftedOption(None) => match value {
+                Value::Option(None) => Ok(()),
+                other => Err(mismatch("None", other)),
+            },
+            Format::LiftedOption(Some(a)) => match value {
+                Value::Option(Some(v)) => self.encode_format(a, v, scope, env, sink),
+                other => Err(mismatch("Some", other)),
+            },
+            Format::LetView(name, a) => {
+                let view_env = Env::View(env, name, sink.offset());
+                self.encode_format(a, value, scope, &view_env, sink)
+            }
+            Format::WithView(v_expr, vf) => {
+                let offset = env.eval_view_expr(scope, v_expr)?;
+                match vf {
+                    ViewFormat::CaptureBytes(_) => {
+                        let mut patch = Sink::new(offset);
+                        for v in get_seq(value)? {
+                            match v {
+                                Value::U8(b) => patch.push(*b),
+                                other => return Err(mismatch("U8", other)),
+                            }
+                        }
+                        sink.add_patch(patch);
+                        Ok(())
+                    }
+                    ViewFormat::ReadArray(_, kind) => {
+                        let mut patch = Sink::new(offset);
+                        for v in get_seq(value)? {
+                            encode_base(*kind, v, &mut patch)?;
+                        }
+                        sink.add_patch(patch);
+                        Ok(())
+                    }
+                    ViewFormat::ReifyView => Ok(()),
+                }
+            }
+            Format::ParseFromView(v_expr, a) => {
+                let offset = env.eval_view_expr(scope, v_expr)?;
+                self.encode_patch(offset, a, value, scope, env, sink)
+            }
+            #[cfg(feature = "format_enforce")]
+            Format::Enforce(a) => self.encode_format(a, value, scope, env, sink),
+            Format::Permit(a, _) => match value {
+                Value::Permit(Ok(v)) => self.encode_format(a, v, scope, env, sink),
+                Value::Permit(Err(_)) => Err(EncodeError::NotInvertible {
+                    construct: "Permit",
+                    reason: "value is a fallback for a failed parse",
+                }),
+                other => self.encode_format(a, other, scope, env, sink),
+            },
+            Format::Resync(a, _) => match value {
+                Value::Variant(label, v) if label == "ok" => {
+                    self.encode_format(a, v, scope, env, sink)
+                }
+                // NOTE - skipped regions are written back verbatim
+                Value::Variant(label, v) if label == "skipped" => {
+                    for b in get_seq(v.record_proj("data"))?.iter() {
+                        match b {
+                            Value::U8(b) => sink.push(*b),
+                            other => return Err(mismatch("U8", other)),
+                        }
+                    }
+                    Ok(())
+                }
+                other => Err(mismatch("Variant", other)),
+            },
+        }
+    }
+
+    fn encode_seq<'v>(
+        &self,
+        format: &Format,
+        seq: impl IntoIterator<Item = &'v Value>,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> EncodeResult<()> {
+        for v in seq {
+            self.encode_format(format, v, scope, env, sink)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes `format` in an environment extended with each of the pending bindings in `pending`.
+    fn encode_pending(
+        &self,
+        format: &Format,
+        value: &Value,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        pending: &[Pending<'_>],
+        sink: &mut Sink,
+    ) -> EncodeResult<()> {
+        match pending.split_first() {
+            None => self.encode_format(format, value, scope, env, sink),
+            Some((first, rest)) => {
+                self.encode_pending(format, value, scope, &Env::Pending(env, first), rest, sink)
+            }
+        }
+    }
+
+    /// Encodes a format whose bytes are located at the absolute `offset`, independently of the current position.
+    fn encode_patch(
+        &self,
+        offset: usize,
+        format: &Format,
+        value: &Value,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> EncodeResult<()> {
+        let mut patch = Sink::new(offset);
+        self.encode_format(format, value, scope, env, &mut patch)?;
+        sink.add_patch(patch);
+        Ok(())
+    }
+
+    /// Constructs a value that `format` can encode without any information beyond the current scope,
+    /// for fields whose values are discarded after parsing (e.g. padding, magic numbers and assertions).
+    ///
+    /// Any byte that is not uniquely determined by the format is chosen to be the least that the format accepts.
+    fn synthesize(&self, format: &Format, scope: &Scope<'_>, offset: usize) -> Option<Value> {
+        match format {
+            Format::ItemVar(level, arg_exprs, arg_views) if arg_views.is_empty() => {
+                let arg_names = self.module.get_args(*level);
+                let mut new_scope = MultiScope::with_capacity(scope.root(), arg_names.len());
+                for ((name, _), expr) in Iterator::zip(arg_names.iter(), arg_exprs.iter()) {
+                    new_scope.push_owned(name.clone(), expr.eval_value(scope).ok()?);
+                }
+                self.synthesize(
+                    self.module.get_format(*level),
+                    &Scope::Multi(&new_scope),
+                    offset,
+                )
+            }
+            Format::GenericVar(index, formats, arg_exprs, arg_views) if arg_views.is_empty() => {
+                let generic = self.module.get_generic(*index);
+                let mut new_scope = MultiScope::with_capacity(scope.root(), generic.args.len());
+                for ((name, _), expr) in Iterator::zip(generic.args.iter(), arg_exprs.iter()) {
+                    new_scope.push_owned(name.clone(), expr.eval_value(scope).ok()?);
+                }
+                self.synthesize(
+                    &generic.instantiate(formats),
+                    &Scope::Multi(&new_scope),
+                    offset,
+                )
+            }
+            Format::EndOfInput
+            | Format::SkipRemainder
+            | Format::Align(_)
+            | Format::PeekNot(_)
+            | Format::Peek(_) => Some(Value::UNIT),
+            Format::Phantom(_) => Some(Value::PhantomData),
+            Format::Pos => Some(Value::U64(offset as u64)),
+            Format::Byte(bs) => bs.min_elem().map(Value::U8),
+            Format::Variant(label, f) => Some(Value::Variant(
+                label.clone(),
+                Box::new(self.synthesize(f, scope, offset)?),
+            )),
+            Format::Union(branches) | Format::UnionNondet(branches) => {
+                branches.iter().enumerate().find_map(|(index, f)| {
+                    let v = self.synthesize(f, scope, offset)?;
+                    Some(Value::Branch(index, Box::new(v)))
+                })
+            }
+            Format::Tuple(fields) => Some(Value::Tuple(
+                fields
+                    .iter()
+                    .map(|f| self.synthesize(f, scope, offset))
+                    .collect::<Option<_>>()?,
+            )),
+            Format::Sequence(formats) => Some(Value::Seq(
+                formats
+                    .iter()
+                    .map(|f| self.synthesize(f, scope, offset))
+                    .collect::<Option<_>>()?,
+            )),
+            Format::Repeat(_) => Some(Value::Seq(SeqKind::new())),
+            Format::Repeat1(f) => Some(Value::Seq(SeqKind::Strict(vec![
+                self.synthesize(f, scope, offset)?,
+            ]))),
+            Format::RepeatCount(expr, f) => {
+                let count = expr.eval_value(scope).ok()?.unwrap_usize();
+                let v = self.synthesize(f, scope, offset)?;
+                Some(Value::Seq(SeqKind::Strict(vec![v; count])))
+            }
+            Format::Slice(_, f) | Format::Bits(f) | Format::Hint(_, f) | Format::Where(f, _) => {
+                self.synthesize(f, scope, offset)
+            }
+            Format::Map(f, expr) => {
+                let orig = self.synthesize(f, scope, offset)?;
+                let v = expr.eval_lambda(scope, &orig).ok()?;
+                Some(Value::Mapped(Box::new(orig), Box::new(v)))
+            }
+            Format::Compute(expr) => Some(expr.eval_value(scope).ok()?),
+            Format::Maybe(expr, f) => match expr.eval_value(scope).ok()?.unwrap_bool() {
+                true => Some(Value::Option(Some(Box::new(
+                    self.synthesize(f, scope, offset)?,
+                )))),
+                false => Some(Value::Option(None)),
+            },
+            Format::Let(name, expr, f) => {
+                let v = expr.eval_value(scope).ok()?;
+                self.synthesize(f, &Scope::Single(SingleScope::new(scope, name, &v)), offset)
+            }
+            // NOTE - the offset of each format in the chain is only approximate, as nothing has been written yet
+            Format::LetFormat(first, name, rest) => {
+                let v = self.synthesize(first, scope, offset)?;
+                self.synthesize(
+                    rest,
+                    &Scope::Single(SingleScope::new(scope, name, &v)),
+                    offset,
+                )
+            }
+            Format::MonadSeq(first, rest) => {
+                self.synthesize(first, scope, offset)?;
+                self.synthesize(rest, scope, offset)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Attempts to find the value bound to `name` within the value of a format that is evaluated in the scope of that binding.
+///
+/// This succeeds whenever the binding is retained verbatim by the value, as is the case for the persisted fields of a record.
+fn recover_binding<'v>(format: &Format, value: &'v Value, name: &str) -> Option<Cow<'v, Value>> {
+    match format {
+        Format::Compute(expr) => recover_from_expr(expr, value, name),
+        Format::LetFormat(_, inner_name, f) | Format::Let(inner_name, _, f) => {
+            if inner_name == name {
+                None
+            } else {
+                recover_binding(f, value, name)
+            }
+        }
+        Format::MonadSeq(_, f) | Format::Hint(_, f) | Format::Where(f, _) => {
+            recover_binding(f, value, name)
+        }
+        Format::Map(f, _) => match value {
+            Value::Mapped(orig, _) => recover_binding(f, orig, name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn recover_from_expr<'v>(expr: &Expr, value: &'v Value, name: &str) -> Option<Cow<'v, Value>> {
+    match (expr, value) {
+        (Expr::Var(var_name), _) if var_name == name => Some(Cow::Borrowed(value)),
+        (Expr::Record(fields), Value::Record(field_values)) => {
+            fields.iter().find_map(|(label, field_expr)| {
+                let (_, v) = field_values.iter().find(|(l, _)| l == label)?;
+                recover_from_expr(field_expr, v, name)
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Strips any integer casts from `expr`, which do not change the value of a slice length that fits within them.
+fn strip_casts(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::AsU8(inner) | Expr::AsU16(inner) | Expr::AsU32(inner) | Expr::AsU64(inner) => {
+            strip_casts(inner)
+        }
+        _ => expr,
+    }
+}
+
+/// Returns `true` if a slice-length `expr` can be solved for `name` by [`invert_size`].
+fn is_size_of(expr: &Expr, name: &str) -> bool {
+    match strip_casts(expr) {
+        Expr::Var(v) => v == name,
+        Expr::Arith(Arith::Add | Arith::Sub | Arith::Mul, lhs, rhs) => {
+            match (lhs.is_shadowed_by(name), rhs.is_shadowed_by(name)) {
+                (true, false) => is_size_of(lhs, name),
+                (false, true) => is_size_of(rhs, name),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Finds the value of `name` for which the slice-length `expr` evaluates to `size`.
+fn invert_size(expr: &Expr, name: &str, size: u64, scope: &Scope<'_>) -> Option<u64> {
+    match strip_casts(expr) {
+        Expr::Var(v) if v == name => Some(size),
+        Expr::Arith(op, lhs, rhs) => {
+            let known = |x: &Expr| num_value(&x.eval_value(scope).ok()?);
+            match (op, lhs.is_shadowed_by(name)) {
+                (Arith::Add, true) => invert_size(lhs, name, size.checked_sub(known(rhs)?)?, scope),
+                (Arith::Add, false) => {
+                    invert_size(rhs, name, size.checked_sub(known(lhs)?)?, scope)
+                }
+                (Arith::Sub, true) => invert_size(lhs, name, size.checked_add(known(rhs)?)?, scope),
+                (Arith::Sub, false) => {
+                    invert_size(rhs, name, known(lhs)?.checked_sub(size)?, scope)
+                }
+                (Arith::Mul, is_lhs) => {
+                    let (unknown, k) = if is_lhs {
+                        (lhs, known(rhs)?)
+                    } else {
+                        (rhs, known(lhs)?)
+                    };
+                    (k != 0 && size.is_multiple_of(k)).then_some(())?;
+                    invert_size(unknown, name, size / k, scope)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Evaluates the slice-length `expr` for a value of `n` for `name`.
+fn eval_size(expr: &Expr, name: &str, n: u64, scope: &Scope<'_>) -> Option<usize> {
+    let size = match strip_casts(expr) {
+        Expr::Var(v) if v == name => n,
+        Expr::Arith(op, lhs, rhs) => {
+            let operand = |x: &Expr| match x.is_shadowed_by(name) {
+                true => eval_size(x, name, n, scope).map(|size| size as u64),
+                false => num_value(&x.eval_value(scope).ok()?),
+            };
+            let (lhs, rhs) = (operand(lhs)?, operand(rhs)?);
+            match op {
+                Arith::Add => lhs.checked_add(rhs)?,
+                Arith::Sub => lhs.checked_sub(rhs)?,
+                Arith::Mul => lhs.checked_mul(rhs)?,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    usize::try_from(size).ok()
+}
+
+/// Writes a numeric value as a machine integer of the given kind.
+fn encode_base(kind: BaseKind<Endian>, value: &Value, sink: &mut Sink) -> EncodeResult<()> {
+    let bits = num_value(value).ok_or_else(|| mismatch(kind.name(), value))?;
+    let size = kind.size();
+    let be_bytes = &bits.to_be_bytes()[8 - size..];
+    let is_le = matches!(
+        kind,
+        BaseKind::U16Ext(Endian::Le)
+            | BaseKind::U32Ext(Endian::Le)
+            | BaseKind::U64Ext(Endian::Le)
+            | BaseKind::I16Ext(Endian::Le)
+            | BaseKind::I32Ext(Endian::Le)
+            | BaseKind::I64Ext(Endian::Le)
+    );
+    if is_le {
+        be_bytes.iter().rev().for_each(|b| sink.push(*b));
+    } else {
+        be_bytes.iter().for_each(|b| sink.push(*b));
+    }
+    Ok(())
+}
+
+/// Returns `true` if `computed` is equal to `value`, or to its image if it is a mapped value.
+fn same_value(computed: &Value, value: &Value) -> bool {
+    computed == value || computed == value.coerce_mapped_value()
+}
+
+/// Interprets a numeric value as a 64-bit pattern, with negative values in two's complement.
+fn num_value(value: &Value) -> Option<u64> {
+    match value.coerce_mapped_value() {
+        Value::U8(n) => Some(u64::from(*n)),
+        Value::U16(n) => Some(u64::from(*n)),
+        Value::U32(n) => Some(u64::from(*n)),
+        Value::U64(n) => Some(*n),
+        Value::Usize(n) => Some(*n as u64),
+        Value::Numeric(tc) => {
+            let raw = tc.as_raw_value();
+            u64::try_from(raw)
+                .ok()
+                .or_else(|| i64::try_from(raw).ok().map(|n| n as u64))
+        }
+        _ => None,
+    }
+}
+
+fn get_seq(value: &Value) -> EncodeResult<&SeqKind<Value>> {
+    match value {
+        Value::Seq(seq) => Ok(seq),
+        other => Err(mismatch("Seq", other)),
+    }
+}
+
+fn check_length(expected: usize, found: usize) -> EncodeResult<()> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(EncodeError::BadLength { expected, found })
+    }
+}
+
+fn mismatch(expected: &'static str, found: &Value) -> EncodeError {
+    EncodeError::Mismatch {
+        expected,
+        found: Box::new(found.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decoder::Compiler;
+    use crate::helper::*;
+    use crate::read::ReadCtxt;
+    use crate::{BaseType, ValueType};
+
+    /// Test-helper function for encoder tests.
+    ///
+    /// Decodes `input` according to `format`, and checks that encoding the resulting value yields `expect`.
+    fn roundtrips(format: Format, input: &[u8], expect: &[u8]) {
+        let module = FormatModule::new();
+        let program = Compiler::compile_program(&module, &format).unwrap();
+        let (value, _) = program.run(ReadCtxt::new(input)).unwrap();
+        let bytes = Encoder::new(&module).encode(&format, &value).unwrap();
+        assert_eq!(bytes, expect);
+    }
+
+    #[test]
+    fn test_encode_slice_overflow() {
+        let format = record([
+            ("len", u8()),
+            ("data", slice(var("len"), repeat(is_byte(0x2a)))),
+        ]);
+        let value = Value::record([
+            ("len", Value::U8(0)),
+            (
+                "data",
+                Value::Seq(SeqKind::Strict(vec![Value::U8(0x2a); 3])),
+            ),
+        ]);
+        let module = FormatModule::new();
+        let result = Encoder::new(&module).encode(&format, &value);
+        assert!(matches!(
+            result,
+            Err(EncodeError::SliceOverflow {
+                size: 0,
+                written: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn test_encode_packed_bits() {
+        use BitFieldKind::*;
+        let format = bit_fields_u8([
+            FlagBit("flag"),
+            Reserved {
+                bit_width: 3,
+                check_zero: false,
+            },
+            BitsField {
+                field_name: "nibble",
+                bit_width: 4,
+            },
+        ]);
+        roundtrips(format, &[0x8a], &[0x8a]);
+    }
+
+    #[test]
+    fn test_encode_ephemeral_length() {
+        let format = record_auto([
+            ("_len", u8()),
+            ("data", slice(var("_len"), repeat(is_byte(0x2a)))),
+        ]);
+        roundtrips(format, &[3, 0x2a, 0x2a, 0x2a], &[3, 0x2a, 0x2a, 0x2a]);
+    }
+
+    #[test]
+    fn test_encode_search_exceeded() {
+        let module = FormatModule::new();
+
+        // a 16-bit domain is small enough to search exhaustively
+        let narrow = record_auto([
+            ("_x", u16be()),
+            ("y", compute(rem(var("_x"), Expr::U16(7)))),
+        ]);
+        let value = Value::record([("y", Value::U16(3))]);
+        let bytes = Encoder::new(&module).encode(&narrow, &value).unwrap();
+        let program = Compiler::compile_program(&module, &narrow).unwrap();
+        let (decoded, _) = program.run(ReadCtxt::new(&bytes)).unwrap();
+        assert_eq!(decoded, value);
+
+        let wide = record_auto([
+            ("_x", u32be()),
+            ("y", compute(rem(var("_x"), Expr::U32(7)))),
+        ]);
+        let value = Value::record([("y", Value::U32(3))]);
+        let result = Encoder::new(&module).encode(&wide, &value);
+        assert!(matches!(
+            result,
+            Err(EncodeError::SearchExceeded { ref name, limit }) if name == "_x" && limit == chain::MAX_DOMAIN
+        ));
+    }
+
+    #[test]
+    fn test_encode_backpatched_length() {
+        let mut module = FormatModule::new();
+        let payload = module.define_format_args(
+            "test.payload",
+            vec![(Label::Borrowed("len"), ValueType::Base(BaseType::U16))],
+            slice(sub(var("len"), Expr::U16(2)), repeat(is_byte(0x2a))),
+        );
+        let format = record_auto([
+            ("_len", u16be()),
+            ("data", payload.call_args(vec![var("_len")])),
+        ]);
+        let input = [0, 5, 0x2a, 0x2a, 0x2a];
+        let program = Compiler::compile_program(&module, &format).unwrap();
+        let (value, _) = program.run(ReadCtxt::new(&input)).unwrap();
+        let bytes = Encoder::new(&module).encode(&format, &value).unwrap();
+        assert_eq!(bytes, input);
+    }
+
+    #[test]
+    fn test_encode_flat_mapped_runs() {
+        let run = record([
+            ("_len", u8()),
+            ("_char", u8()),
+            ("buf", repeat_count(var("_len"), compute(var("_char")))),
+        ]);
+        let format = record_auto([
+            ("_runs", repeat(run)),
+            (
+                "data",
+                compute(flat_map(
+                    lambda("run", record_proj(var("run"), "buf")),
+                    var("_runs"),
+                )),
+            ),
+        ]);
+        let input = [3, b'a', 1, b'b', 2, b'c'];
+        roundtrips(format, &input, &input);
+    }
+
+    #[test]
+    fn test_encode_map_without_preimage() {
+        let format = map(u8(), lambda("x", Expr::Var("x".into())));
+        let module = FormatModule::new();
+        let result = Encoder::new(&module).encode(&format, &Value::U8(1));
+        assert!(matches!(
+            result,
+            Err(EncodeError::NotInvertible {
+                construct: "Map",
+                ..
+            })
+        ));
+    }
+}
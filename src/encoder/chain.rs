@@ -0,0 +1,1138 @@
+//! Encoding of monadic chains (`LetFormat`/`MonadSeq`), including records.
+//!
+//! The value of a chain is the value of its final format, so any binding that the final format does not
+//! retain verbatim (e.g. the ephemeral fields of a record) has to be reconstructed before the chain can be
+//! encoded. This is done by solving for the missing bindings against the facts that the value does record:
+//! the results of later `Compute` fields, the lengths of later sequences and slices, the presence of
+//! later optional fields, and the bytes that later `DecodeBytes` formats were decoded from. Bindings that
+//! these facts only partially determine (e.g. records of which a single field is used) are reconstructed
+//! as partial values, whose missing parts are synthesized when they are encoded.
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use super::{Encoder, Env, Pending, Sink, is_size_of, mismatch, num_value, same_value};
+use crate::decoder::{MultiScope, Scope, SeqKind, SingleScope, Value, read_base_value};
+use crate::error::{EncodeError, EncodeResult, EvalResult};
+use crate::numeric::core::Expr as NumExpr;
+use crate::read::ReadCtxt;
+use crate::{Arith, BaseKind, CommonOp, Endian, Expr, Format, Label, StyleHint};
+
+/// Upper bound on the number of candidate values that will be searched for a single missing binding
+pub(super) const MAX_DOMAIN: usize = 1 << 16;
+
+/// Upper bound on the depth of format calls that a slice length will be traced through
+const MAX_CALL_DEPTH: usize = 8;
+
+/// Candidate values for a missing binding, as enumerated by [`Encoder::domain`]
+enum Domain {
+    /// Every value that the format of the binding can produce
+    Finite(Vec<Value>),
+    /// The format can produce more than `MAX_DOMAIN` values
+    Exceeded,
+    /// The values of the format cannot be enumerated
+    Unknown,
+}
+
+impl Domain {
+    fn values(&self) -> Option<&[Value]> {
+        match self {
+            Domain::Finite(values) => Some(values),
+            Domain::Exceeded | Domain::Unknown => None,
+        }
+    }
+
+    fn map(self, f: impl FnMut(Value) -> Value) -> Domain {
+        match self {
+            Domain::Finite(values) => Domain::Finite(values.into_iter().map(f).collect()),
+            other => other,
+        }
+    }
+
+    /// As [`Domain::map`], but any value that `f` fails to evaluate for makes the domain `Unknown`.
+    fn try_map(self, f: impl FnMut(Value) -> EvalResult<Value>) -> Domain {
+        match self {
+            Domain::Finite(values) => match values.into_iter().map(f).collect() {
+                Ok(values) => Domain::Finite(values),
+                Err(_) => Domain::Unknown,
+            },
+            other => other,
+        }
+    }
+}
+
+/// Single element of a flattened chain, binding the value of `format` to `name` (if any)
+struct Step<'f> {
+    name: Option<&'f Label>,
+    format: &'f Format,
+}
+
+/// Fact about the value of a chain, in the form of an expression and the value it must evaluate to
+struct Constraint<'a> {
+    expr: &'a Expr,
+    target: Cow<'a, Value>,
+}
+
+impl<'a> Encoder<'a> {
+    pub(super) fn encode_chain(
+        &self,
+        format: &Format,
+        value: &Value,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> EncodeResult<()> {
+        let mut steps: Vec<Step<'_>> = Vec::new();
+        let mut terminal = format;
+        loop {
+            match terminal {
+                // NOTE - a binding that shadows an earlier one starts a new chain, so that names are unique within each
+                Format::LetFormat(_, name, _)
+                    if steps.iter().any(|step| step.name == Some(name)) =>
+                {
+                    break;
+                }
+                Format::LetFormat(first, name, rest) => {
+                    steps.push(Step {
+                        name: Some(name),
+                        format: first,
+                    });
+                    terminal = rest;
+                }
+                Format::MonadSeq(first, rest) => {
+                    steps.push(Step {
+                        name: None,
+                        format: first,
+                    });
+                    terminal = rest;
+                }
+                _ => break,
+            }
+        }
+
+        let mut bindings = steps
+            .iter()
+            .map(|step| super::recover_binding(terminal, value, step.name?))
+            .collect::<Vec<_>>();
+
+        let is_unknown = |(step, binding): (&Step<'_>, &Option<Cow<'_, Value>>)| {
+            step.name.is_some() && binding.is_none() && !is_derived(step.format)
+        };
+        let deferred = if Iterator::zip(steps.iter(), bindings.iter()).any(is_unknown) {
+            self.solve_chain(&steps, &mut bindings, terminal, value, scope, env)?
+        } else {
+            vec![false; steps.len()]
+        };
+        self.encode_steps(
+            &steps, &bindings, &deferred, terminal, value, scope, env, sink,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn encode_steps(
+        &self,
+        steps: &[Step<'_>],
+        bindings: &[Option<Cow<'_, Value>>],
+        deferred: &[bool],
+        terminal: &Format,
+        value: &Value,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> EncodeResult<()> {
+        let (Some((step, steps)), Some((binding, bindings)), Some((is_deferred, deferred))) = (
+            steps.split_first(),
+            bindings.split_first(),
+            deferred.split_first(),
+        ) else {
+            return self.encode_terminal(terminal, value, scope, env, sink);
+        };
+        if *is_deferred {
+            return self.encode_deferred(
+                step, steps, bindings, deferred, terminal, value, scope, env, sink,
+            );
+        }
+        let v = match binding {
+            Some(v) => Cow::Borrowed(v.as_ref()),
+            None => Cow::Owned(
+                self.synthesize(step.format, scope, sink.offset())
+                    .ok_or_else(|| match step.name {
+                        Some(name) => EncodeError::Unrecoverable { name: name.clone() },
+                        None => EncodeError::NotInvertible {
+                            construct: "MonadSeq",
+                            reason: "discarded value cannot be reconstructed",
+                        },
+                    })?,
+            ),
+        };
+        self.encode_format(step.format, &v, scope, env, sink)?;
+        match step.name {
+            Some(name) => {
+                let new_scope = Scope::Single(SingleScope::new(scope, name, &v));
+                self.encode_steps(
+                    steps, bindings, deferred, terminal, value, &new_scope, env, sink,
+                )
+            }
+            None => self.encode_steps(steps, bindings, deferred, terminal, value, scope, env, sink),
+        }
+    }
+
+    /// Encodes the final format of a chain, which for a partial value need only agree with the fields it retains.
+    fn encode_terminal(
+        &self,
+        terminal: &Format,
+        value: &Value,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> EncodeResult<()> {
+        if !is_partial(terminal, value) {
+            return self.encode_format(terminal, value, scope, env, sink);
+        }
+        let (Format::Compute(expr), Value::Record(field_values)) = (terminal, value) else {
+            unreachable!("partial value of a non-record");
+        };
+        let computed = expr.eval_value(scope)?;
+        let agrees = field_values
+            .iter()
+            .all(|(label, v)| same_value(computed.record_proj(label), v));
+        if !agrees {
+            return Err(EncodeError::BadCompute {
+                expr: expr.clone(),
+                computed: Box::new(computed),
+                found: Box::new(value.clone()),
+            });
+        }
+        Ok(())
+    }
+
+    /// Encodes a step whose binding is only used as the size of later slices, by writing a placeholder
+    /// and patching it with the length that is solved for when those slices are encoded.
+    #[allow(clippy::too_many_arguments)]
+    fn encode_deferred(
+        &self,
+        step: &Step<'_>,
+        steps: &[Step<'_>],
+        bindings: &[Option<Cow<'_, Value>>],
+        deferred: &[bool],
+        terminal: &Format,
+        value: &Value,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> EncodeResult<()> {
+        let name = step.name.expect("deferred step has no binding");
+        let kind = base_kind(step.format).expect("deferred step is not an integer");
+        let offset = sink.offset();
+        let unrecoverable = || EncodeError::Unrecoverable { name: name.clone() };
+        let placeholder = self
+            .synthesize(step.format, scope, offset)
+            .ok_or_else(unrecoverable)?;
+        sink.pad(kind.size());
+
+        let pending = Pending::new(name.clone(), None);
+        let new_scope = Scope::Single(SingleScope::new(scope, name, &placeholder));
+        let pending_env = Env::Pending(env, &pending);
+        self.encode_steps(
+            steps,
+            bindings,
+            deferred,
+            terminal,
+            value,
+            &new_scope,
+            &pending_env,
+            sink,
+        )?;
+
+        let n = pending.value().ok_or_else(unrecoverable)?;
+        let v = base_value(kind, n).ok_or_else(|| mismatch(kind.name(), &Value::U64(n)))?;
+        let mut patch = Sink::new(offset);
+        self.encode_format(step.format, &v, scope, env, &mut patch)?;
+        sink.add_patch(patch);
+        Ok(())
+    }
+
+    /// Fills in the bindings of `steps` that could not be recovered directly from the value of the chain,
+    /// returning an error if any such binding is used by the chain but cannot be determined.
+    fn solve_chain<'v>(
+        &self,
+        steps: &[Step<'_>],
+        bindings: &mut [Option<Cow<'v, Value>>],
+        terminal: &'v Format,
+        value: &'v Value,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+    ) -> EncodeResult<Vec<bool>> {
+        let mut solutions: Vec<Option<Value>> = vec![None; steps.len()];
+        let unknowns = (0..steps.len())
+            .filter(|ix| steps[*ix].name.is_some() && bindings[*ix].is_none())
+            .collect::<Vec<_>>();
+
+        let constraints = {
+            let lookahead = lookahead_scope(scope, steps, bindings, &solutions);
+            let lookahead = Scope::Multi(&lookahead);
+            let mut constraints = Vec::new();
+            for (step, binding) in Iterator::zip(steps.iter(), bindings.iter()) {
+                if let Some(v) = binding {
+                    self.collect_constraints(step.format, v, &lookahead, env, &mut constraints);
+                }
+            }
+            if let Format::Compute(expr) = terminal {
+                match (expr.as_ref(), value) {
+                    (Expr::Record(fields), Value::Record(field_values)) => {
+                        for (label, expr) in fields.iter() {
+                            let Some((_, v)) = field_values.iter().find(|(l, _)| l == label) else {
+                                continue;
+                            };
+                            if !matches!(expr, Expr::Var(_)) {
+                                constraints.push(Constraint {
+                                    expr,
+                                    target: Cow::Borrowed(v),
+                                });
+                            }
+                        }
+                    }
+                    (Expr::Var(_), _) => {}
+                    (expr, value) => constraints.push(Constraint {
+                        expr,
+                        target: Cow::Borrowed(value),
+                    }),
+                }
+            } else {
+                self.collect_constraints(terminal, value, &lookahead, env, &mut constraints);
+            }
+            constraints
+        };
+
+        let domains = {
+            let lookahead = lookahead_scope(scope, steps, bindings, &solutions);
+            let lookahead = Scope::Multi(&lookahead);
+            unknowns
+                .iter()
+                .map(|ix| {
+                    let format = steps[*ix].format;
+                    if unknowns
+                        .iter()
+                        .any(|other| mentions(format, steps[*other].name.unwrap()))
+                    {
+                        Domain::Unknown
+                    } else {
+                        self.domain(format, &lookahead)
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+
+        loop {
+            let mut assignments: Vec<(usize, Value)> = Vec::new();
+            {
+                let lookahead = lookahead_scope(scope, steps, bindings, &solutions);
+                let lookahead = Scope::Multi(&lookahead);
+                let unsolved = unknowns
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, ix)| solutions[**ix].is_none())
+                    .map(|(u, ix)| (u, *ix, steps[*ix].name.unwrap()))
+                    .collect::<Vec<_>>();
+                if unsolved.is_empty() {
+                    break;
+                }
+                let solver = Solver {
+                    encoder: self,
+                    env,
+                    scope: &lookahead,
+                    unsolved: &unsolved,
+                    domains: &domains,
+                    steps,
+                };
+
+                // derived bindings, whose values are computed from those of other bindings
+                for (_, ix, _) in unsolved.iter() {
+                    if let Format::Compute(expr) = steps[*ix].format
+                        && !solver.mentions_unsolved(expr)
+                    {
+                        assignments.push((*ix, expr.eval_value(&lookahead)?));
+                    }
+                }
+
+                // direct inversion of constraints
+                if assignments.is_empty() {
+                    for constraint in constraints.iter() {
+                        if !solver.mentions_unsolved(constraint.expr) {
+                            continue;
+                        }
+                        let Some(solved) = solver.invert(constraint.expr, &constraint.target)
+                        else {
+                            continue;
+                        };
+                        for (name, target) in solved {
+                            let Some((u, ix, _)) = unsolved.iter().find(|(.., n)| *n == &name)
+                            else {
+                                continue;
+                            };
+                            if assignments.iter().any(|(other, _)| other == ix) {
+                                continue;
+                            }
+                            if let Some(v) = solver.value_for(*u, *ix, &target) {
+                                assignments.push((*ix, v));
+                            }
+                        }
+                    }
+                }
+
+                // exhaustive search over the candidate values of a single binding
+                if assignments.is_empty() {
+                    for (u, ix, name) in unsolved.iter() {
+                        let Some(domain) = domains[*u].values() else {
+                            continue;
+                        };
+                        let relevant = constraints
+                            .iter()
+                            .filter(|c| {
+                                c.expr.is_shadowed_by(name)
+                                    && unsolved.iter().all(|(.., other)| {
+                                        other == name || !c.expr.is_shadowed_by(other)
+                                    })
+                            })
+                            .collect::<Vec<_>>();
+                        if relevant.is_empty() {
+                            continue;
+                        }
+                        let found = domain.iter().find(|candidate| {
+                            let mut candidate_scope = MultiScope::with_capacity(&lookahead, 1);
+                            candidate_scope.push((*name).clone(), candidate);
+                            let candidate_scope = Scope::Multi(&candidate_scope);
+                            relevant.iter().all(|c| {
+                                c.expr
+                                    .eval_value(&candidate_scope)
+                                    .is_ok_and(|v| same_value(&v, &c.target))
+                            })
+                        });
+                        if let Some(v) = found {
+                            assignments.push((*ix, v.clone()));
+                            break;
+                        }
+                    }
+                }
+            }
+            if assignments.is_empty() {
+                break;
+            }
+            for (ix, v) in assignments {
+                solutions[ix] = Some(v);
+            }
+        }
+
+        // NOTE - a binding whose search was cut short may still have been recoverable, so we report it separately
+        let exceeded = Iterator::zip(unknowns.iter(), domains.iter())
+            .map(|(ix, domain)| {
+                let name = steps[*ix].name.unwrap();
+                matches!(domain, Domain::Exceeded)
+                    && constraints.iter().any(|c| c.expr.is_shadowed_by(name))
+            })
+            .collect::<Vec<_>>();
+        drop(constraints);
+
+        let is_partial = is_partial(terminal, value);
+        let mut deferred = vec![false; steps.len()];
+        for (u, ix) in unknowns.into_iter().enumerate() {
+            match solutions[ix].take() {
+                Some(v) => bindings[ix] = Some(Cow::Owned(v)),
+                None if is_derived(steps[ix].format) => {}
+                None => {
+                    let name = steps[ix].name.unwrap();
+                    let is_used = steps[ix + 1..]
+                        .iter()
+                        .any(|step| mentions(step.format, name))
+                        || terminal_mentions(terminal, value, name);
+                    if !is_used {
+                        continue;
+                    }
+                    let is_size_only = base_kind(steps[ix].format).is_some()
+                        && steps[ix + 1..]
+                            .iter()
+                            .map(|step| step.format)
+                            .chain(std::iter::once(terminal))
+                            .all(|f| self.only_sizes(f, name, 0));
+                    if is_size_only {
+                        deferred[ix] = true;
+                        continue;
+                    }
+                    // NOTE - the fields missing from a partial value are unconstrained, so any value will do
+                    if is_partial {
+                        continue;
+                    }
+                    if exceeded[u] {
+                        return Err(EncodeError::SearchExceeded {
+                            name: name.clone(),
+                            limit: MAX_DOMAIN,
+                        });
+                    }
+                    return Err(EncodeError::Unrecoverable { name: name.clone() });
+                }
+            }
+        }
+        Ok(deferred)
+    }
+
+    /// Collects the constraints that a known value `value` of `format` places on the bindings it depends on.
+    fn collect_constraints<'c>(
+        &self,
+        format: &'c Format,
+        value: &'c Value,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        constraints: &mut Vec<Constraint<'c>>,
+    ) {
+        match format {
+            Format::Compute(expr) => constraints.push(Constraint {
+                expr,
+                target: Cow::Borrowed(value),
+            }),
+            Format::Hint(_, f) | Format::Where(f, _) => {
+                self.collect_constraints(f, value, scope, env, constraints)
+            }
+            Format::Map(f, _) => {
+                if let Value::Mapped(orig, _) = value {
+                    self.collect_constraints(f, orig, scope, env, constraints)
+                }
+            }
+            Format::RepeatCount(expr, f) => {
+                if let Value::Seq(seq) = value {
+                    constraints.push(Constraint {
+                        expr,
+                        target: Cow::Owned(Value::Usize(seq.len())),
+                    });
+                    for v in seq.iter() {
+                        self.collect_constraints(f, v, scope, env, constraints);
+                    }
+                }
+            }
+            Format::Repeat(f)
+            | Format::Repeat1(f)
+            | Format::RepeatBetween(.., f)
+            | Format::RepeatUntilLast(_, f)
+            | Format::RepeatUntilSeq(_, f) => {
+                if let Value::Seq(seq) = value {
+                    for v in seq.iter() {
+                        self.collect_constraints(f, v, scope, env, constraints);
+                    }
+                }
+            }
+            Format::DecodeBytes(expr, f) => {
+                let mut bytes_sink = Sink::new(0);
+                if self
+                    .encode_format(f, value, scope, env, &mut bytes_sink)
+                    .is_ok()
+                    && let Ok(bytes) = bytes_sink.finish()
+                {
+                    constraints.push(Constraint {
+                        expr,
+                        target: Cow::Owned(Value::Seq(SeqKind::Bytes(Rc::from(bytes)))),
+                    })
+                }
+            }
+            Format::ForEach(expr, lbl, f) => {
+                // NOTE - the constraints on each element are gathered into a single constraint on the whole sequence
+                let Value::Seq(seq) = value else {
+                    return;
+                };
+                let mut items = Vec::with_capacity(seq.len());
+                for v in seq.iter() {
+                    let mut item_constraints = Vec::new();
+                    self.collect_constraints(f, v, scope, env, &mut item_constraints);
+                    let Some(item) = item_constraints
+                        .iter()
+                        .find_map(|c| self.unapply(c.expr, lbl, &c.target, None, scope, env))
+                    else {
+                        return;
+                    };
+                    items.push(item);
+                }
+                constraints.push(Constraint {
+                    expr,
+                    target: Cow::Owned(Value::Seq(SeqKind::from(items))),
+                })
+            }
+            Format::Maybe(expr, _) => {
+                if let Value::Option(opt) = value {
+                    constraints.push(Constraint {
+                        expr,
+                        target: Cow::Owned(Value::Bool(opt.is_some())),
+                    })
+                }
+            }
+            Format::Slice(expr, f) => {
+                // NOTE - the length of a slice is only needed when it is not already known
+                if matches!(expr.as_ref(), Expr::U8(_) | Expr::U16(_) | Expr::U32(_)) {
+                    return;
+                }
+                let mut slice_sink = Sink::new(0);
+                if self
+                    .encode_format(f, value, scope, env, &mut slice_sink)
+                    .is_ok()
+                {
+                    constraints.push(Constraint {
+                        expr,
+                        target: Cow::Owned(Value::Usize(slice_sink.written())),
+                    })
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Constructs a value for `name` on which `expr` evaluates to `target`, for expressions that only
+    /// select or rearrange parts of that value. Any part of the value that `expr` does not depend on is
+    /// left out, so that records may be partial.
+    ///
+    /// For flat-maps, `format` is the format that produces the value of `name`, whose elements are used to
+    /// decide where to split the target.
+    fn unapply(
+        &self,
+        expr: &Expr,
+        name: &str,
+        target: &Value,
+        format: Option<&Format>,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+    ) -> Option<Value> {
+        match expr {
+            Expr::Var(v) if v == name => Some(target.clone()),
+            Expr::RecordProj(head, label) => {
+                let record = Value::Record(vec![(label.clone(), target.clone())]);
+                self.unapply(head, name, &record, format, scope, env)
+            }
+            Expr::FlatMap(f, seq) => {
+                let Expr::Lambda(elem_name, body) = f.as_ref() else {
+                    return None;
+                };
+                let elem_format = repeat_elem(format?)?;
+                let Value::Seq(target_seq) = target else {
+                    return None;
+                };
+                let preimage = |start: usize, len: usize| {
+                    let chunk = Value::Seq(target_seq.sub_seq(start, len));
+                    let elem = self.unapply(body, elem_name, &chunk, None, scope, env)?;
+                    let mut scratch = Sink::new(0);
+                    self.encode_format(elem_format, &elem, scope, env, &mut scratch)
+                        .is_ok()
+                        .then_some(elem)
+                };
+                // NOTE - each element takes the longest run of the target that it accepts
+                let mut elems = Vec::new();
+                let mut start = 0;
+                while start < target_seq.len() {
+                    let remaining = target_seq.len() - start;
+                    if let Some(elem) = preimage(start, remaining) {
+                        elems.push(elem);
+                        break;
+                    }
+                    let mut best = (0, None);
+                    let (mut lo, mut hi) = (1, remaining - 1);
+                    while lo <= hi {
+                        let mid = lo + (hi - lo) / 2;
+                        match preimage(start, mid) {
+                            Some(elem) => {
+                                best = (mid, Some(elem));
+                                lo = mid + 1;
+                            }
+                            None => hi = mid - 1,
+                        }
+                    }
+                    let (len, elem) = best;
+                    elems.push(elem?);
+                    start += len;
+                }
+                let elems = Value::Seq(SeqKind::from(elems));
+                self.unapply(seq, name, &elems, format, scope, env)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if every use of `name` within `format` is as the length of a slice that it can be solved
+    /// for from, either directly or as a verbatim argument to a format that does the same.
+    fn only_sizes(&self, format: &Format, name: &str, depth: usize) -> bool {
+        match format {
+            Format::Slice(expr, f) => {
+                (!expr.is_shadowed_by(name) || is_size_of(expr, name))
+                    && self.only_sizes(f, name, depth)
+            }
+            Format::ItemVar(level, args, _) => {
+                let params = self.module.get_args(*level);
+                Iterator::zip(params.iter(), args.iter()).all(|((param, _), expr)| {
+                    !expr.is_shadowed_by(name)
+                        || (depth < MAX_CALL_DEPTH
+                            && matches!(super::strip_casts(expr), Expr::Var(v) if v == name)
+                            && self.only_sizes(self.module.get_format(*level), param, depth + 1))
+                })
+            }
+            Format::Variant(_, f)
+            | Format::Repeat(f)
+            | Format::Repeat1(f)
+            | Format::Bits(f)
+            | Format::Hint(_, f)
+            | Format::LiftedOption(Some(f)) => self.only_sizes(f, name, depth),
+            Format::Union(fs)
+            | Format::UnionNondet(fs)
+            | Format::Tuple(fs)
+            | Format::Sequence(fs) => fs.iter().all(|f| self.only_sizes(f, name, depth)),
+            // NOTE - a shadowing binding would make the pending binding ambiguous within its scope
+            Format::LetFormat(first, lbl, second) => {
+                self.only_sizes(first, name, depth)
+                    && if lbl == name {
+                        !mentions(second, name)
+                    } else {
+                        self.only_sizes(second, name, depth)
+                    }
+            }
+            Format::MonadSeq(first, second) => {
+                self.only_sizes(first, name, depth) && self.only_sizes(second, name, depth)
+            }
+            _ => !mentions(format, name),
+        }
+    }
+
+    /// Enumerates every value that `format` can produce, provided it has no more than `MAX_DOMAIN` of them.
+    fn domain(&self, format: &Format, scope: &Scope<'_>) -> Domain {
+        match format {
+            Format::Byte(bs) => Domain::Finite(bs.iter().map(Value::U8).collect()),
+            Format::Hint(StyleHint::Common(CommonOp::EndianParse(kind)), _) if kind.size() <= 2 => {
+                let values = (0..1usize << (8 * kind.size()))
+                    .map(|n| {
+                        let bytes = n.to_le_bytes();
+                        let (v, _) = read_base_value(ReadCtxt::new(&bytes[..kind.size()]), *kind)
+                            .expect("insufficient bytes for domain element");
+                        v
+                    })
+                    .collect();
+                Domain::Finite(values)
+            }
+            Format::Hint(StyleHint::Common(CommonOp::EndianParse(_)), _) => Domain::Exceeded,
+            Format::Hint(_, f) => self.domain(f, scope),
+            Format::Map(f, expr) => self.domain(f, scope).try_map(|orig| {
+                let v = expr.eval_lambda(scope, &orig)?;
+                Ok(Value::Mapped(Box::new(orig), Box::new(v)))
+            }),
+            Format::Where(f, cond) => match self.domain(f, scope) {
+                Domain::Finite(values) => Domain::Finite(
+                    values
+                        .into_iter()
+                        .filter(|v| {
+                            cond.as_ref()
+                                .eval_lambda(scope, v)
+                                .is_ok_and(|b| b.unwrap_bool())
+                        })
+                        .collect(),
+                ),
+                other => other,
+            },
+            Format::ItemVar(level, arg_exprs, arg_views) if arg_views.is_empty() => {
+                let arg_names = self.module.get_args(*level);
+                let mut new_scope = MultiScope::with_capacity(scope.root(), arg_names.len());
+                for ((name, _), expr) in Iterator::zip(arg_names.iter(), arg_exprs.iter()) {
+                    let Ok(v) = expr.eval_value(scope) else {
+                        return Domain::Unknown;
+                    };
+                    new_scope.push_owned(name.clone(), v);
+                }
+                self.domain(self.module.get_format(*level), &Scope::Multi(&new_scope))
+            }
+            Format::GenericVar(index, formats, arg_exprs, arg_views) if arg_views.is_empty() => {
+                let generic = self.module.get_generic(*index);
+                let mut new_scope = MultiScope::with_capacity(scope.root(), generic.args.len());
+                for ((name, _), expr) in Iterator::zip(generic.args.iter(), arg_exprs.iter()) {
+                    let Ok(v) = expr.eval_value(scope) else {
+                        return Domain::Unknown;
+                    };
+                    new_scope.push_owned(name.clone(), v);
+                }
+                self.domain(&generic.instantiate(formats), &Scope::Multi(&new_scope))
+            }
+            Format::Variant(label, f) => self
+                .domain(f, scope)
+                .map(|v| Value::Variant(label.clone(), Box::new(v))),
+            Format::Union(branches) => {
+                let mut values = Vec::new();
+                for (index, f) in branches.iter().enumerate() {
+                    match self.domain(f, scope) {
+                        Domain::Finite(branch_values) => values.extend(
+                            branch_values
+                                .into_iter()
+                                .map(|v| Value::Branch(index, Box::new(v))),
+                        ),
+                        other => return other,
+                    }
+                    if values.len() > MAX_DOMAIN {
+                        return Domain::Exceeded;
+                    }
+                }
+                Domain::Finite(values)
+            }
+            _ => Domain::Unknown,
+        }
+    }
+}
+
+/// Returns `true` if `value` is a record that lacks some of the fields of the record that `terminal` computes,
+/// as is the case for values reconstructed from the parts of them that another format depends on.
+fn is_partial(terminal: &Format, value: &Value) -> bool {
+    match (terminal, value) {
+        (Format::Compute(expr), Value::Record(field_values)) => match expr.as_ref() {
+            Expr::Record(fields) => {
+                let has_field = |label: &Label| fields.iter().any(|(l, _)| l == label);
+                field_values.len() < fields.len()
+                    && field_values.iter().all(|(label, _)| has_field(label))
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Returns `true` if the final format of a chain depends on `name` for any part of `value` that it has to reproduce.
+fn terminal_mentions(terminal: &Format, value: &Value, name: &str) -> bool {
+    if !is_partial(terminal, value) {
+        return mentions(terminal, name);
+    }
+    let (Format::Compute(expr), Value::Record(field_values)) = (terminal, value) else {
+        unreachable!("partial value of a non-record");
+    };
+    let Expr::Record(fields) = expr.as_ref() else {
+        unreachable!("partial value of a non-record");
+    };
+    fields.iter().any(|(label, field_expr)| {
+        field_values.iter().any(|(l, _)| l == label) && field_expr.is_shadowed_by(name)
+    })
+}
+
+/// Returns the format of each element of a repetition.
+fn repeat_elem(format: &Format) -> Option<&Format> {
+    match format {
+        Format::Repeat(f)
+        | Format::Repeat1(f)
+        | Format::RepeatCount(_, f)
+        | Format::RepeatBetween(.., f)
+        | Format::RepeatUntilLast(_, f)
+        | Format::RepeatUntilSeq(_, f) => Some(f),
+        Format::Hint(_, f) | Format::Where(f, _) => repeat_elem(f),
+        _ => None,
+    }
+}
+
+/// Returns `true` for formats whose values are determined entirely by the scope they are evaluated in.
+fn is_derived(format: &Format) -> bool {
+    matches!(format, Format::Compute(_) | Format::Pos)
+}
+
+/// Constructs a scope extending `scope` with every binding of the chain that is known so far.
+fn lookahead_scope<'a>(
+    scope: &'a Scope<'a>,
+    steps: &[Step<'_>],
+    bindings: &'a [Option<Cow<'_, Value>>],
+    solutions: &'a [Option<Value>],
+) -> MultiScope<'a> {
+    let mut lookahead = MultiScope::with_capacity(scope, steps.len());
+    for (ix, step) in steps.iter().enumerate() {
+        let Some(name) = step.name else {
+            continue;
+        };
+        if let Some(v) = &bindings[ix] {
+            lookahead.push(name.clone(), v.as_ref());
+        } else if let Some(v) = &solutions[ix] {
+            lookahead.push(name.clone(), v);
+        }
+    }
+    lookahead
+}
+
+/// Context for inverting constraint expressions with respect to the bindings that are not yet known.
+struct Solver<'s, 'f> {
+    encoder: &'s Encoder<'s>,
+    env: &'s Env<'s>,
+    scope: &'s Scope<'s>,
+    /// Index into `domains`, index into `steps`, and name of each unsolved binding
+    unsolved: &'s [(usize, usize, &'f Label)],
+    domains: &'s [Domain],
+    steps: &'s [Step<'f>],
+}
+
+impl Solver<'_, '_> {
+    fn mentions_unsolved(&self, expr: &Expr) -> bool {
+        self.unsolved
+            .iter()
+            .any(|(.., name)| expr.is_shadowed_by(name))
+    }
+
+    /// Finds values for the unsolved bindings in `expr` that make it evaluate to `target`, if this can be
+    /// done by running each operation within `expr` backwards.
+    fn invert(&self, expr: &Expr, target: &Value) -> Option<Vec<(Label, Value)>> {
+        if !self.mentions_unsolved(expr) {
+            return same_value(&expr.eval_value(self.scope).ok()?, target).then(Vec::new);
+        }
+        match expr {
+            Expr::Var(name) => Some(vec![(name.clone(), target.clone())]),
+            Expr::AsU8(inner) | Expr::AsU16(inner) | Expr::AsU32(inner) | Expr::AsU64(inner) => {
+                self.invert(inner, &Value::U64(num_value(target)?))
+            }
+            Expr::Numeric(num) => match num.as_ref() {
+                NumExpr::NumVar(name) => Some(vec![(name.clone(), Value::U64(num_value(target)?))]),
+                NumExpr::Cast(_, inner) => match inner.as_ref() {
+                    NumExpr::NumVar(name) => {
+                        Some(vec![(name.clone(), Value::U64(num_value(target)?))])
+                    }
+                    _ => None,
+                },
+                _ => None,
+            },
+            Expr::Arith(op, lhs, rhs) => {
+                let t = num_value(target)?;
+                let known = |x: &Expr| {
+                    (!self.mentions_unsolved(x))
+                        .then(|| num_value(&x.eval_value(self.scope).ok()?))
+                        .flatten()
+                };
+                match (op, known(lhs), known(rhs)) {
+                    (Arith::Shl, None, Some(k)) => {
+                        let k = u32::try_from(k).ok()?;
+                        if k >= 64 || t & ((1 << k) - 1) != 0 {
+                            return None;
+                        }
+                        self.invert(lhs, &Value::U64(t >> k))
+                    }
+                    (Arith::Add, None, Some(k)) => self.invert(lhs, &Value::U64(t.checked_sub(k)?)),
+                    (Arith::Add, Some(k), None) => self.invert(rhs, &Value::U64(t.checked_sub(k)?)),
+                    (Arith::Sub, None, Some(k)) => self.invert(lhs, &Value::U64(t.checked_add(k)?)),
+                    (Arith::Sub, Some(k), None) => self.invert(rhs, &Value::U64(k.checked_sub(t)?)),
+                    (Arith::BitOr, None, Some(k)) if self.support(lhs) & k == 0 => {
+                        (t & k == k).then_some(())?;
+                        self.invert(lhs, &Value::U64(t & !k))
+                    }
+                    (Arith::BitOr, Some(k), None) if self.support(rhs) & k == 0 => {
+                        (t & k == k).then_some(())?;
+                        self.invert(rhs, &Value::U64(t & !k))
+                    }
+                    (Arith::BitOr, None, None) => {
+                        let (lo, hi) = (self.support(lhs), self.support(rhs));
+                        if lo & hi != 0 || t & !(lo | hi) != 0 {
+                            return None;
+                        }
+                        let mut solved = self.invert(lhs, &Value::U64(t & lo))?;
+                        solved.extend(self.invert(rhs, &Value::U64(t & hi))?);
+                        Some(solved)
+                    }
+                    _ => None,
+                }
+            }
+            Expr::RecordProj(..) | Expr::FlatMap(..) => {
+                let mut mentioned = self
+                    .unsolved
+                    .iter()
+                    .filter(|(.., name)| expr.is_shadowed_by(name));
+                let (_, ix, name) = mentioned.next()?;
+                if mentioned.next().is_some() {
+                    return None;
+                }
+                let format = self.steps[*ix].format;
+                let v =
+                    self.encoder
+                        .unapply(expr, name, target, Some(format), self.scope, self.env)?;
+                Some(vec![((*name).clone(), v)])
+            }
+            _ => None,
+        }
+    }
+
+    /// Computes a mask of every bit that may be set in the value of `expr`.
+    fn support(&self, expr: &Expr) -> u64 {
+        if !self.mentions_unsolved(expr) {
+            return expr
+                .eval_value(self.scope)
+                .ok()
+                .and_then(|v| num_value(&v))
+                .unwrap_or(u64::MAX);
+        }
+        match expr {
+            Expr::Var(name) => {
+                let Some((u, ix, _)) = self.unsolved.iter().find(|(.., n)| *n == name) else {
+                    return u64::MAX;
+                };
+                match self.domains[*u].values() {
+                    Some(domain) => domain
+                        .iter()
+                        .map(|v| num_value(v).unwrap_or(u64::MAX))
+                        .fold(0, |acc, n| acc | n),
+                    None => match base_kind(self.steps[*ix].format) {
+                        Some(kind) if kind.size() < 8 => (1 << (8 * kind.size())) - 1,
+                        _ => u64::MAX,
+                    },
+                }
+            }
+            Expr::AsU8(inner) => self.support(inner) & 0xff,
+            Expr::AsU16(inner) => self.support(inner) & 0xffff,
+            Expr::AsU32(inner) => self.support(inner) & 0xffff_ffff,
+            Expr::AsU64(inner) => self.support(inner),
+            Expr::Arith(op, lhs, rhs) => {
+                let (lo, hi) = (self.support(lhs), self.support(rhs));
+                match op {
+                    Arith::BitOr => lo | hi,
+                    Arith::BitAnd => lo & hi,
+                    Arith::Shl if !self.mentions_unsolved(rhs) => {
+                        let shifted = lo.checked_shl(hi as u32).unwrap_or(0);
+                        if shifted >> (hi as u32).min(63) == lo {
+                            shifted
+                        } else {
+                            u64::MAX
+                        }
+                    }
+                    Arith::Shr if !self.mentions_unsolved(rhs) => {
+                        lo.checked_shr(hi as u32).unwrap_or(0)
+                    }
+                    _ => u64::MAX,
+                }
+            }
+            _ => u64::MAX,
+        }
+    }
+
+    /// Reconstructs the value of `format` whose image under any mappings within it is `target`.
+    fn preimage(&self, format: &Format, target: &Value) -> Option<Value> {
+        match format {
+            Format::Map(f, lambda) => {
+                let Expr::Lambda(name, body) = lambda.as_ref() else {
+                    return None;
+                };
+                let orig =
+                    self.encoder
+                        .unapply(body, name, target, Some(f), self.scope, self.env)?;
+                Some(Value::Mapped(Box::new(orig), Box::new(target.clone())))
+            }
+            Format::Hint(_, f) | Format::Where(f, _) => self.preimage(f, target),
+            _ => Some(target.clone()),
+        }
+    }
+
+    /// Converts the solution `target` for the binding at index `ix` into a value its format can encode.
+    fn value_for(&self, u: usize, ix: usize, target: &Value) -> Option<Value> {
+        let Some(n) = num_value(target) else {
+            return self.preimage(self.steps[ix].format, target);
+        };
+        if let Some(domain) = self.domains[u].values() {
+            return domain.iter().find(|v| num_value(v) == Some(n)).cloned();
+        }
+        base_value(base_kind(self.steps[ix].format)?, n)
+    }
+}
+
+/// Reads back the 64-bit pattern `n` as a value of the given machine-integer kind, if it fits within it.
+fn base_value(kind: BaseKind<Endian>, n: u64) -> Option<Value> {
+    let size = kind.size();
+    if size < 8 && n >> (8 * size) != 0 && !is_sign_extended(n, size) {
+        return None;
+    }
+    let bytes = match kind {
+        BaseKind::U8 | BaseKind::I8 => n.to_le_bytes(),
+        BaseKind::U16Ext(Endian::Le)
+        | BaseKind::U32Ext(Endian::Le)
+        | BaseKind::U64Ext(Endian::Le)
+        | BaseKind::I16Ext(Endian::Le)
+        | BaseKind::I32Ext(Endian::Le)
+        | BaseKind::I64Ext(Endian::Le) => n.to_le_bytes(),
+        _ => {
+            let mut bytes = [0; 8];
+            bytes[..size].copy_from_slice(&n.to_be_bytes()[8 - size..]);
+            bytes
+        }
+    };
+    let (v, _) = read_base_value(ReadCtxt::new(&bytes[..size]), kind)?;
+    Some(v)
+}
+
+/// Returns `true` if `n` is the sign-extension of a two's-complement integer `size` bytes wide.
+fn is_sign_extended(n: u64, size: usize) -> bool {
+    (n as i64) >> (8 * size - 1) == -1
+}
+
+/// Determines the machine-integer kind of a format, if it is a (possibly constrained) integer parse.
+fn base_kind(format: &Format) -> Option<BaseKind<Endian>> {
+    match format {
+        Format::Hint(StyleHint::Common(CommonOp::EndianParse(kind)), _) => Some(*kind),
+        Format::Hint(_, f) | Format::Where(f, _) => base_kind(f),
+        _ => None,
+    }
+}
+
+/// Returns `true` if `format` may depend on a binding of `name` in the scope it is evaluated in.
+fn mentions(format: &Format, name: &str) -> bool {
+    match format {
+        // NOTE - the format arguments of a generic format only refer to its own parameters
+        Format::ItemVar(_, args, _)
+        | Format::GenericVar(_, _, args, _)
+        | Format::Native(_, args) => args.iter().any(|e| e.is_shadowed_by(name)),
+        Format::Fail
+        | Format::EndOfInput
+        | Format::Align(_)
+        | Format::Byte(_)
+        | Format::Pos
+        | Format::SkipRemainder
+        | Format::Apply(_)
+        | Format::LiftedOption(None) => false,
+        Format::Variant(_, f)
+        | Format::Repeat(f)
+        | Format::Repeat1(f)
+        | Format::Peek(f)
+        | Format::PeekNot(f)
+        | Format::Bits(f)
+        | Format::Hint(_, f)
+        | Format::LiftedOption(Some(f))
+        | Format::Phantom(f)
+        | Format::Resync(f, _)
+        | Format::LetView(_, f) => mentions(f, name),
+        #[cfg(feature = "format_enforce")]
+        Format::Enforce(f) => mentions(f, name),
+        Format::Union(fs) | Format::UnionNondet(fs) | Format::Tuple(fs) | Format::Sequence(fs) => {
+            fs.iter().any(|f| mentions(f, name))
+        }
+        Format::RepeatCount(e, f)
+        | Format::RepeatUntilLast(e, f)
+        | Format::RepeatUntilSeq(e, f)
+        | Format::Maybe(e, f)
+        | Format::Slice(e, f)
+        | Format::Map(f, e)
+        | Format::DecodeBytes(e, f)
+        | Format::Permit(f, e) => e.is_shadowed_by(name) || mentions(f, name),
+        Format::RepeatBetween(min, max, f) => {
+            min.is_shadowed_by(name) || max.is_shadowed_by(name) || mentions(f, name)
+        }
+        Format::AccumUntil(done, update, init, _, f) => {
+            done.is_shadowed_by(name)
+                || update.is_shadowed_by(name)
+                || init.is_shadowed_by(name)
+                || mentions(f, name)
+        }
+        Format::ForEach(e, lbl, f) => e.is_shadowed_by(name) || (lbl != name && mentions(f, name)),
+        Format::WithRelativeOffset(base, offset, f) => {
+            base.is_shadowed_by(name) || offset.is_shadowed_by(name) || mentions(f, name)
+        }
+        Format::Where(f, cond) => mentions(f, name) || cond.as_ref().is_shadowed_by(name),
+        Format::Compute(e) => e.is_shadowed_by(name),
+        Format::Let(lbl, e, f) => e.is_shadowed_by(name) || (lbl != name && mentions(f, name)),
+        Format::Match(head, branches) => {
+            head.is_shadowed_by(name)
+                || branches.iter().any(|(pat, f)| {
+                    (!pat.shadows(name) && mentions(f, name)) || pat.guard_mentions(name)
+                })
+        }
+        Format::Dynamic(lbl, crate::DynFormat::Huffman(lengths, values), f) => {
+            lengths.is_shadowed_by(name)
+                || values.as_ref().is_some_and(|v| v.is_shadowed_by(name))
+                || (lbl != name && mentions(f, name))
+        }
+        Format::LetFormat(first, lbl, second) => {
+            mentions(first, name) || (lbl != name && mentions(second, name))
+        }
+        Format::MonadSeq(first, second) => mentions(first, name) || mentions(second, name),
+        // NOTE - view-expressions may embed offset-expressions, so we err on the side of caution
+        Format::WithView(..) | Format::ParseFromView(..) => true,
+    }
+}
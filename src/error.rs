@@ -1,9 +1,41 @@
 use crate::byte_set::ByteSet;
 use crate::decoder::{Scope, ScopeEntry, Value};
+use crate::limits::LimitExceeded;
 use crate::loc_decoder::{LocScope, ParsedValue};
+use crate::native::NativeError;
 use crate::read::ReadCtxt;
 use crate::util::EResult;
-use crate::{Expr, Label};
+use crate::{Arith, Expr, Label};
+
+pub type EvalResult<T> = Result<T, EvalError>;
+
+/// Errors that can occur when evaluating an [`Expr`], due to the values it is evaluated over.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EvalError {
+    /// An arithmetic operation overflowed the type of its operands, or divided by zero
+    Overflow { op: Arith },
+    /// A sequence was indexed, or sliced, beyond its end
+    OutOfBounds { index: usize, len: usize },
+    /// No branch of a match-expression, or the pattern of a destructuring, accepts the value
+    NoMatch,
+    /// Evaluation of an embedded numeric expression failed
+    Numeric(String),
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overflow { op } => write!(f, "integer overflow in {op:?}"),
+            Self::OutOfBounds { index, len } => {
+                write!(f, "index {index} out of bounds of sequence of length {len}")
+            }
+            Self::NoMatch => write!(f, "no pattern matches the value"),
+            Self::Numeric(msg) => write!(f, "numeric evaluation failed: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
 
 pub type DecodeResult<T> = Result<T, DecodeError>;
 pub type EDecodeResult<T> = EResult<T, DecodeError>;
@@ -14,9 +46,62 @@ pub type ELocDecodeResult<T> = EResult<T, DecodeErrorKind<crate::loc_decoder::Pa
 #[derive(Debug)]
 pub struct DecodeError<V: Clone = Value> {
     pub err: Box<DecodeErrorKind<V>>,
+    /// Names of the formats, and labels of the record fields, being decoded when the error occurred (innermost first)
+    pub path: Vec<Label>,
     pub _trace: Vec<Box<dyn std::fmt::Debug + Sync + Send + 'static>>,
 }
 
+impl<V: Clone> DecodeError<V> {
+    /// Records that the error occurred while decoding `frame` (a format name or record field label), which
+    /// encloses any frames recorded so far.
+    pub fn within(mut self, frame: impl Into<Label>) -> Self {
+        self.path.push(frame.into());
+        self
+    }
+
+    /// Returns an iterator over the format names and field labels leading up to the error, outermost first.
+    pub fn path(&self) -> impl DoubleEndedIterator<Item = &Label> {
+        self.path.iter().rev()
+    }
+
+    /// Returns the absolute offset at which the error occurred, if known.
+    pub fn offset(&self) -> Option<usize> {
+        self.err.offset()
+    }
+
+    /// Returns `true` if the error is a failure to parse the input, which a recovery construct such as
+    /// [`Format::Resync`](crate::Format::Resync) may skip over, rather than a resource limit being exceeded.
+    pub fn is_recoverable(&self) -> bool {
+        self.err.is_recoverable()
+    }
+}
+
+/// Placeholder for a trace item of a cloned `DecodeError`, which renders identically to the original.
+struct TraceText(String);
+
+impl std::fmt::Debug for TraceText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<V: Clone> Clone for DecodeError<V> {
+    fn clone(&self) -> Self {
+        DecodeError {
+            err: self.err.clone(),
+            path: self.path.clone(),
+            _trace: self
+                ._trace
+                .iter()
+                .map(|item| {
+                    Box::new(TraceText(format!("{item:?}")))
+                        as Box<dyn std::fmt::Debug + Sync + Send>
+                })
+                .collect(),
+        }
+    }
+}
+
 impl<V: Clone + std::fmt::Debug> crate::util::ErrTrace for DecodeError<V> {
     fn with_trace<T>(mut self, ctxt: T) -> Self
     where
@@ -31,22 +116,47 @@ impl<V: Clone + std::fmt::Debug> From<DecodeErrorKind<V>> for DecodeError<V> {
     fn from(err: DecodeErrorKind<V>) -> Self {
         DecodeError {
             err: Box::new(err),
+            path: Vec::new(),
             _trace: Vec::new(),
         }
     }
 }
 
+impl<V: Clone + std::fmt::Debug> From<EvalError> for DecodeError<V> {
+    fn from(error: EvalError) -> Self {
+        DecodeErrorKind::eval(error).into()
+    }
+}
+
 impl<V: Clone + std::fmt::Debug> std::fmt::Display for DecodeError<V> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self._trace.is_empty() {
-            write!(f, "{}", self.err)
+        if self.path.is_empty() {
+            write!(f, "{}", self.err)?;
         } else {
-            writeln!(f, "{} (", self.err)?;
+            for (ix, frame) in self.path().enumerate() {
+                if ix > 0 {
+                    f.write_str(" > ")?;
+                }
+                f.write_str(frame)?;
+            }
+            if let Some(offset) = self.offset() {
+                write!(f, " @ {offset:#X}")?;
+            }
+            match self.err.as_ref() {
+                DecodeErrorKind::Unexpected {
+                    found, expected, ..
+                } => write!(f, ": expected {expected} (found {found:#04X})")?,
+                other => write!(f, ": {other}")?,
+            }
+        }
+        if !self._trace.is_empty() {
+            writeln!(f, " (")?;
             for item in self._trace.iter() {
                 writeln!(f, "\t{item:?}")?;
             }
-            write!(f, ")")
+            write!(f, ")")?;
         }
+        Ok(())
     }
 }
 
@@ -56,7 +166,7 @@ impl<V: Clone + std::fmt::Debug + 'static> std::error::Error for DecodeError<V>
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum DecodeErrorKind<V: Clone = Value> {
     Fail {
         bindings: Vec<(Label, ScopeEntry<V>)>,
@@ -67,6 +177,7 @@ pub enum DecodeErrorKind<V: Clone = Value> {
         bindings: Vec<(Label, ScopeEntry<V>)>,
         assertion: Box<Expr>,
         exception: Box<V>,
+        offset: usize,
     },
     Trailing {
         byte: u8,
@@ -91,6 +202,17 @@ pub enum DecodeErrorKind<V: Clone = Value> {
     NoValidBranch {
         offset: usize,
     },
+    LimitExceeded {
+        exceeded: LimitExceeded,
+        offset: usize,
+    },
+    Native {
+        error: NativeError,
+        offset: usize,
+    },
+    Eval {
+        error: EvalError,
+    },
 }
 
 impl<V: Clone> DecodeErrorKind<V> {
@@ -100,6 +222,7 @@ impl<V: Clone> DecodeErrorKind<V> {
     {
         DecodeError {
             err: Box::new(self),
+            path: Vec::new(),
             _trace: vec![Box::new(trace)],
         }
     }
@@ -122,6 +245,7 @@ impl<V: std::fmt::Debug + Clone> std::fmt::Display for DecodeErrorKind<V> {
                 bindings,
                 assertion,
                 exception,
+                ..
             } => {
                 write!(
                     f,
@@ -171,6 +295,13 @@ impl<V: std::fmt::Debug + Clone> std::fmt::Display for DecodeErrorKind<V> {
                     "no valid branch found for content starting at offset {offset}"
                 )
             }
+            Self::LimitExceeded { exceeded, offset } => {
+                write!(f, "{exceeded} (offset = {offset})")
+            }
+            Self::Native { error, offset } => {
+                write!(f, "{error} (offset = {offset})")
+            }
+            Self::Eval { error } => write!(f, "{error}"),
         }
     }
 }
@@ -194,6 +325,7 @@ impl DecodeErrorKind<Value> {
         scope: &Scope<'_>,
         assertion: Box<Expr>,
         exception: Box<Value>,
+        offset: usize,
     ) -> DecodeErrorKind<Value> {
         let mut bindings = Vec::new();
         scope.get_bindings(&mut bindings);
@@ -201,6 +333,7 @@ impl DecodeErrorKind<Value> {
             bindings,
             assertion,
             exception,
+            offset,
         }
     }
 }
@@ -222,6 +355,7 @@ impl DecodeErrorKind<ParsedValue> {
         scope: &LocScope<'_>,
         assertion: Box<Expr>,
         exception: Box<ParsedValue>,
+        offset: usize,
     ) -> DecodeErrorKind<ParsedValue> {
         let mut bindings = Vec::new();
         scope.get_bindings(&mut bindings);
@@ -229,11 +363,28 @@ impl DecodeErrorKind<ParsedValue> {
             bindings,
             assertion,
             exception,
+            offset,
         }
     }
 }
 
 impl<V: Clone> DecodeErrorKind<V> {
+    /// Returns the absolute offset at which the error occurred, if it is associated with one.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::Fail { offset, .. }
+            | Self::BadWhere { offset, .. }
+            | Self::Trailing { offset, .. }
+            | Self::Overrun { offset, .. }
+            | Self::Overbyte { offset }
+            | Self::Unexpected { offset, .. }
+            | Self::NoValidBranch { offset }
+            | Self::LimitExceeded { offset, .. }
+            | Self::Native { offset, .. } => Some(*offset),
+            Self::SeekPastEnd { .. } | Self::Eval { .. } => None,
+        }
+    }
+
     pub fn trailing(byte: u8, offset: usize) -> Self {
         Self::Trailing { byte, offset }
     }
@@ -263,4 +414,272 @@ impl<V: Clone> DecodeErrorKind<V> {
             offset,
         }
     }
+
+    pub fn limit_exceeded(exceeded: LimitExceeded, offset: usize) -> Self {
+        Self::LimitExceeded { exceeded, offset }
+    }
+
+    pub fn native(error: NativeError, offset: usize) -> Self {
+        Self::Native { error, offset }
+    }
+
+    pub fn eval(error: EvalError) -> Self {
+        Self::Eval { error }
+    }
+
+    /// Returns `true` unless the error is a resource limit being exceeded (see [`DecodeError::is_recoverable`]).
+    pub fn is_recoverable(&self) -> bool {
+        !matches!(self, Self::LimitExceeded { .. })
+    }
 }
+
+pub type EncodeResult<T> = Result<T, EncodeError>;
+
+/// Errors that can occur when encoding a [`Value`] back into bytes via [`crate::encoder::Encoder`].
+#[derive(Debug)]
+pub enum EncodeError {
+    /// The format cannot be run backwards to reproduce the bytes of the given value
+    NotInvertible {
+        construct: &'static str,
+        reason: &'static str,
+    },
+    /// The shape of the value does not correspond to the format being encoded
+    Mismatch {
+        expected: &'static str,
+        found: Box<Value>,
+    },
+    /// The value to be encoded by `Format::Byte` lies outside of its `ByteSet`
+    Unexpected { found: u8, expected: ByteSet },
+    /// A computed value does not agree with the one recorded in the value being encoded
+    BadCompute {
+        expr: Box<Expr>,
+        computed: Box<Value>,
+        found: Box<Value>,
+    },
+    /// The number of elements in a sequence disagrees with the count the format demands
+    BadLength { expected: usize, found: usize },
+    /// The encoded contents of a `Format::Slice` do not fit within its size
+    SliceOverflow { size: usize, written: usize },
+    /// The value being encoded violates the assertion of a `Format::Where`
+    BadWhere {
+        assertion: Box<Expr>,
+        exception: Box<Value>,
+    },
+    /// A binding is consumed by the format but not retained by the value, and cannot be recovered
+    Unrecoverable { name: Label },
+    /// A binding could only be recovered by searching through more than `limit` candidate values
+    SearchExceeded { name: Label, limit: usize },
+    /// Two separate encodings disagree on the byte at a given absolute offset
+    Conflict { offset: usize },
+    /// No branch of a union or match can encode the given value
+    NoValidBranch { value: Box<Value> },
+    /// Encountered `Format::Fail`
+    Fail,
+    /// Evaluation of an expression failed
+    Eval(EvalError),
+}
+
+impl From<EvalError> for EncodeError {
+    fn from(err: EvalError) -> Self {
+        EncodeError::Eval(err)
+    }
+}
+
+impl std::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotInvertible { construct, reason } => {
+                write!(f, "cannot encode Format::{construct}: {reason}")
+            }
+            Self::Mismatch { expected, found } => {
+                write!(f, "expected {expected} value, found `{found:?}`")
+            }
+            Self::Unexpected { found, expected } => {
+                write!(
+                    f,
+                    "byte `{found:02x}` is not in the expected set {expected:?}"
+                )
+            }
+            Self::BadCompute {
+                expr,
+                computed,
+                found,
+            } => {
+                write!(
+                    f,
+                    "computed value `{computed:?}` of `{expr:?}` does not match encoded value `{found:?}`"
+                )
+            }
+            Self::BadLength { expected, found } => {
+                write!(
+                    f,
+                    "sequence has {found} elements where the format requires {expected}"
+                )
+            }
+            Self::SliceOverflow { size, written } => {
+                write!(
+                    f,
+                    "encoding {written} bytes would overflow a slice of {size} bytes"
+                )
+            }
+            Self::BadWhere {
+                assertion,
+                exception,
+            } => {
+                write!(
+                    f,
+                    "assertion `{assertion:?}` does not hold for value `{exception:?}`"
+                )
+            }
+            Self::Unrecoverable { name } => {
+                write!(
+                    f,
+                    "binding `{name}` is not retained by the value and cannot be recovered"
+                )
+            }
+            Self::SearchExceeded { name, limit } => {
+                write!(
+                    f,
+                    "binding `{name}` is not retained by the value, and has more than {limit} candidate values to search"
+                )
+            }
+            Self::Conflict { offset } => {
+                write!(f, "conflicting bytes encoded at offset {offset}")
+            }
+            Self::NoValidBranch { value } => {
+                write!(f, "no branch can encode value `{value:?}`")
+            }
+            Self::Fail => write!(f, "cannot encode Format::Fail"),
+            Self::Eval(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+pub type GenerateResult<T> = Result<T, GenerateError>;
+
+/// Errors that can occur when generating inputs via [`crate::generate::Generator`].
+#[derive(Debug)]
+pub enum GenerateError {
+    /// The format contains a construct that the generator cannot produce input for
+    Unsupported { construct: &'static str },
+    /// The input would exceed the configured maximum size
+    OverBudget { max_size: usize },
+    /// The format-invocations are nested beyond the configured maximum depth
+    TooDeep { max_depth: usize },
+    /// Encountered `Format::Fail`, or a byte-set or match with no possible values
+    Fail,
+    /// No candidate value satisfying the assertion of a `Format::Where` was found
+    Unsatisfied { assertion: Box<Expr> },
+    /// The byte at the given offset was fixed by a lookahead that the current format does not accept
+    Lookahead { offset: usize },
+    /// The generated bytes could not be laid out (e.g. due to overlapping out-of-line formats)
+    Layout(EncodeError),
+    /// Evaluation of an expression failed for the values generated so far
+    Eval(EvalError),
+    /// The generated input was rejected by the decoder
+    Rejected(Box<DecodeError>),
+    /// The first union of the root format has no branch with the given index
+    NoSuchBranch { index: usize },
+    /// The input generated for one branch of a union was decoded as another
+    WrongBranch { expected: usize, found: usize },
+    /// Every attempt at generating an input failed, with the given error for the last one
+    Exhausted {
+        attempts: usize,
+        last: Box<GenerateError>,
+    },
+}
+
+impl From<EncodeError> for GenerateError {
+    fn from(err: EncodeError) -> Self {
+        GenerateError::Layout(err)
+    }
+}
+
+impl From<EvalError> for GenerateError {
+    fn from(err: EvalError) -> Self {
+        GenerateError::Eval(err)
+    }
+}
+
+impl std::fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported { construct } => {
+                write!(f, "cannot generate input for Format::{construct}")
+            }
+            Self::OverBudget { max_size } => {
+                write!(f, "input would exceed the size limit of {max_size} bytes")
+            }
+            Self::TooDeep { max_depth } => {
+                write!(f, "format invocations nested beyond depth {max_depth}")
+            }
+            Self::Fail => write!(f, "cannot generate input for Format::Fail"),
+            Self::Unsatisfied { assertion } => {
+                write!(f, "no value found satisfying assertion `{assertion:?}`")
+            }
+            Self::Lookahead { offset } => {
+                write!(
+                    f,
+                    "byte at offset {offset} conflicts with an earlier lookahead"
+                )
+            }
+            Self::Layout(err) => write!(f, "cannot lay out generated input: {err}"),
+            Self::Eval(err) => write!(f, "evaluation failed: {err}"),
+            Self::Rejected(err) => write!(f, "generated input was rejected: {err}"),
+            Self::NoSuchBranch { index } => write!(f, "union has no branch {index}"),
+            Self::WrongBranch { expected, found } => {
+                write!(
+                    f,
+                    "input generated for branch {expected} was decoded as branch {found}"
+                )
+            }
+            Self::Exhausted { attempts, last } => {
+                write!(
+                    f,
+                    "no valid input found in {attempts} attempts (last: {last})"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Layout(err) => Some(err),
+            Self::Eval(err) => Some(err),
+            Self::Rejected(err) => Some(err.as_ref()),
+            Self::Exhausted { last, .. } => Some(last.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+pub type CodeGenResult<T> = Result<T, CodeGenError>;
+
+/// Errors that can occur when generating Rust code via [`crate::codegen::generate_code_with`].
+#[derive(Debug)]
+pub enum CodeGenError {
+    /// No format with the given name was found for one of the specified roots
+    UnknownRoot { name: Label },
+    /// No format with the given name was found for one of the specified lazy formats
+    UnknownLazy { name: Label },
+    /// The named lazy format cannot be deferred, for the given reason
+    UnsupportedLazy { name: Label, reason: &'static str },
+}
+
+impl std::fmt::Display for CodeGenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownRoot { name } => write!(f, "unknown root format `{name}`"),
+            Self::UnknownLazy { name } => write!(f, "unknown lazy format `{name}`"),
+            Self::UnsupportedLazy { name, reason } => {
+                write!(f, "lazy format `{name}` {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodeGenError {}
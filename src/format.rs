@@ -59,6 +59,8 @@ use crate::validation::Condition;
 pub enum Format {
     /// Reference to a top-level item
     ItemVar(usize, Vec<Expr>, Vec<ViewExpr>), // FIXME - do the exprs here need type(+) info?
+    /// Reference to a generic top-level item, instantiated with the given format arguments (which may only refer to its parameters)
+    GenericVar(usize, Vec<Format>, Vec<Expr>, Vec<ViewExpr>),
     /// A format that never matches
     Fail,
     /// Matches if the end of the input has been reached
@@ -125,6 +127,8 @@ pub enum Format {
     Pos,
     /// Skip the remainder of the stream, up until the end of input or the last available byte within a Slice
     SkipRemainder,
+    /// Decode a value using the `FormatModule`'s native (externally-implemented) format with the given index, passing it the given arguments
+    Native(usize, Vec<Expr>),
     /// Given an expression corresponding to a byte-sequence, decode it again using the provided Format. This can be used to reparse the initial decode of formats that output Vec<u8> or similar
     DecodeBytes(Box<Expr>, Box<Format>),
     /// Process one format, bind the result to a label, and process a second format, discarding the result of the first
@@ -150,6 +154,11 @@ pub enum Format {
     Enforce(Box<Format>),
     /// Parse a format but downgrade any errors it produces to warnings, evaluating the provided expr to produce a fallback value if it has already failed
     Permit(Box<Format>, Box<Expr>),
+    /// Parse a format, but on failure skip ahead to the next occurrence of a byte-pattern (or the end of the current slice), yielding
+    /// `ok(value)` on success and `skipped({ error, data })` otherwise, where `error` is the (UTF-8) error message and `data` the skipped bytes
+    ///
+    /// Exceeded resource limits are not parse failures, and are propagated rather than skipped over.
+    Resync(Box<Format>, Vec<ByteSet>),
 }
 
 impl Format {
@@ -262,9 +271,14 @@ impl Format {
     pub(crate) fn match_bounds(&self, module: &FormatModule) -> Bounds {
         match self {
             Format::ItemVar(level, _args, _views) => module.get_format(*level).match_bounds(module),
+            Format::GenericVar(index, formats, ..) => module
+                .get_generic(*index)
+                .instantiate(formats)
+                .match_bounds(module),
             Format::Fail => Bounds::exact(0),
             Format::EndOfInput => Bounds::exact(0),
             Format::SkipRemainder => Bounds::any(),
+            Format::Native(..) => Bounds::any(),
             Format::Align(0) => unreachable!("illegal Format::Align modulus (== 0)"),
             Format::Align(n) => Bounds::new(0, n - 1),
             Format::Byte(_) => Bounds::exact(1),
@@ -313,6 +327,8 @@ impl Format {
                 first.match_bounds(module) + second.match_bounds(module)
             }
             Format::Permit(inner, ..) | Format::Hint(.., inner) => inner.match_bounds(module),
+            // NOTE - a skipped region is only empty at the end of the current slice, but may run all the way to it
+            Format::Resync(f, _) => Bounds::at_least(f.match_bounds(module).min.min(1)),
             #[cfg(feature = "format_enforce")]
             Format::Enforce(inner) => inner.match_bounds(module),
             Format::LiftedOption(opt) => match opt {
@@ -344,10 +360,16 @@ impl Format {
             Format::ItemVar(level, _args, _views) => {
                 module.get_format(*level).lookahead_bounds(module)
             }
+            Format::GenericVar(index, formats, ..) => module
+                .get_generic(*index)
+                .instantiate(formats)
+                .lookahead_bounds(module),
             Format::Fail => Bounds::exact(0),
             Format::EndOfInput => Bounds::exact(0),
             // NOTE - for PeekNot purposes it is not fully clear how to treat SkipRemainder, but we want to mirror the behavior of `Repeat(Byte)`
             Format::SkipRemainder => Bounds::any(),
+            // NOTE - native formats may read any amount of the remaining input
+            Format::Native(..) => Bounds::any(),
             Format::Align(0) => unreachable!("illegal Format::Align modulus (== 0)"),
             Format::Align(n) => Bounds::new(0, n - 1),
             Format::Byte(_) => Bounds::exact(1),
@@ -396,7 +418,7 @@ impl Format {
                 f0.lookahead_bounds(module),
                 f0.match_bounds(module) + f.lookahead_bounds(module),
             ),
-            Format::Permit(f, _expr) => f.lookahead_bounds(module),
+            Format::Permit(f, _) | Format::Resync(f, _) => f.lookahead_bounds(module),
             #[cfg(feature = "format_enforce")]
             Format::Enforce(f) => f.lookahead_bounds(module),
             Format::Hint(_, f) => f.lookahead_bounds(module),
@@ -435,10 +457,15 @@ impl Format {
             Format::ItemVar(level, _args, _views) => {
                 module.get_format(*level).depends_on_next(module)
             }
+            Format::GenericVar(index, formats, ..) => module
+                .get_generic(*index)
+                .instantiate(formats)
+                .depends_on_next(module),
             Format::Fail => false,
             Format::EndOfInput => false,
             // NOTE - compiling SkipRemainder doesn't depend on the next format because the next format can only ever match the empty byte string at that point
             Format::SkipRemainder => false,
+            Format::Native(..) => false,
             Format::Align(..) => false,
             Format::Byte(..) => false,
             Format::WithView(..) => false,
@@ -474,7 +501,9 @@ impl Format {
             }
             #[cfg(feature = "format_enforce")]
             Format::Enforce(f) => f.depends_on_next(module),
-            Format::Permit(f, _) | Format::Hint(_, f) => f.depends_on_next(module),
+            Format::Permit(f, _) | Format::Resync(f, _) | Format::Hint(_, f) => {
+                f.depends_on_next(module)
+            }
             Format::LiftedOption(opt) => opt.as_ref().is_some_and(|f| f.depends_on_next(module)),
             Format::Phantom(_) => false,
         }
@@ -534,3 +563,153 @@ impl Format {
         matches!(self, Format::Hint(StyleHint::Record { .. }, _))
     }
 }
+
+impl Format {
+    /// Returns `true` if the format decodes a single byte as-is (i.e. it is a `Byte`, possibly under hints or
+    /// `ItemVar` references).
+    pub(crate) fn is_plain_byte(&self, module: &FormatModule) -> bool {
+        match self {
+            Format::Byte(bs) => !bs.is_empty(),
+            Format::Hint(_, f) => f.is_plain_byte(module),
+            Format::ItemVar(level, ..) => module.get_format(*level).is_plain_byte(module),
+            _ => false,
+        }
+    }
+
+    /// Returns the formats nested immediately within this one, not including those it refers to through `ItemVar`.
+    pub(crate) fn subformats(&self) -> Vec<&Format> {
+        match self {
+            Format::ItemVar(..)
+            | Format::Fail
+            | Format::EndOfInput
+            | Format::Align(_)
+            | Format::Byte(_)
+            | Format::Compute(_)
+            | Format::Apply(_)
+            | Format::Pos
+            | Format::SkipRemainder
+            | Format::Native(..)
+            | Format::WithView(..)
+            | Format::LiftedOption(None) => Vec::new(),
+            Format::Union(fs)
+            | Format::UnionNondet(fs)
+            | Format::Tuple(fs)
+            | Format::Sequence(fs)
+            | Format::GenericVar(_, fs, ..) => fs.iter().collect(),
+            Format::Match(_, branches) => branches.iter().map(|(_, f)| f).collect(),
+            Format::LetFormat(f0, _, f1) | Format::MonadSeq(f0, f1) => vec![f0, f1],
+            Format::Variant(_, f)
+            | Format::Repeat(f)
+            | Format::Repeat1(f)
+            | Format::RepeatCount(_, f)
+            | Format::RepeatBetween(_, _, f)
+            | Format::RepeatUntilLast(_, f)
+            | Format::RepeatUntilSeq(_, f)
+            | Format::AccumUntil(.., f)
+            | Format::ForEach(_, _, f)
+            | Format::Maybe(_, f)
+            | Format::Peek(f)
+            | Format::PeekNot(f)
+            | Format::Slice(_, f)
+            | Format::Bits(f)
+            | Format::WithRelativeOffset(_, _, f)
+            | Format::Map(f, _)
+            | Format::Where(f, _)
+            | Format::Let(_, _, f)
+            | Format::Dynamic(_, _, f)
+            | Format::DecodeBytes(_, f)
+            | Format::Hint(_, f)
+            | Format::LiftedOption(Some(f))
+            | Format::LetView(_, f)
+            | Format::ParseFromView(_, f)
+            | Format::Phantom(f)
+            | Format::Permit(f, _)
+            | Format::Resync(f, _) => vec![f],
+            #[cfg(feature = "format_enforce")]
+            Format::Enforce(f) => vec![f],
+        }
+    }
+
+    /// Mutable counterpart of [`Format::subformats`].
+    pub(crate) fn subformats_mut(&mut self) -> Vec<&mut Format> {
+        match self {
+            Format::ItemVar(..)
+            | Format::Fail
+            | Format::EndOfInput
+            | Format::Align(_)
+            | Format::Byte(_)
+            | Format::Compute(_)
+            | Format::Apply(_)
+            | Format::Pos
+            | Format::SkipRemainder
+            | Format::Native(..)
+            | Format::WithView(..)
+            | Format::LiftedOption(None) => Vec::new(),
+            Format::Union(fs)
+            | Format::UnionNondet(fs)
+            | Format::Tuple(fs)
+            | Format::Sequence(fs)
+            | Format::GenericVar(_, fs, ..) => fs.iter_mut().collect(),
+            Format::Match(_, branches) => branches.iter_mut().map(|(_, f)| f).collect(),
+            Format::LetFormat(f0, _, f1) | Format::MonadSeq(f0, f1) => vec![f0, f1],
+            Format::Variant(_, f)
+            | Format::Repeat(f)
+            | Format::Repeat1(f)
+            | Format::RepeatCount(_, f)
+            | Format::RepeatBetween(_, _, f)
+            | Format::RepeatUntilLast(_, f)
+            | Format::RepeatUntilSeq(_, f)
+            | Format::AccumUntil(.., f)
+            | Format::ForEach(_, _, f)
+            | Format::Maybe(_, f)
+            | Format::Peek(f)
+            | Format::PeekNot(f)
+            | Format::Slice(_, f)
+            | Format::Bits(f)
+            | Format::WithRelativeOffset(_, _, f)
+            | Format::Map(f, _)
+            | Format::Where(f, _)
+            | Format::Let(_, _, f)
+            | Format::Dynamic(_, _, f)
+            | Format::DecodeBytes(_, f)
+            | Format::Hint(_, f)
+            | Format::LiftedOption(Some(f))
+            | Format::LetView(_, f)
+            | Format::ParseFromView(_, f)
+            | Format::Phantom(f)
+            | Format::Permit(f, _)
+            | Format::Resync(f, _) => vec![f],
+            #[cfg(feature = "format_enforce")]
+            Format::Enforce(f) => vec![f],
+        }
+    }
+
+    /// Returns the levels referred to through `ItemVar` within this format, not including those referred to in turn by
+    /// the formats at those levels.
+    pub(crate) fn referenced_levels(&self) -> Vec<usize> {
+        let mut levels = Vec::new();
+        let mut stack = vec![self];
+        while let Some(f) = stack.pop() {
+            if let Format::ItemVar(level, ..) = f {
+                levels.push(*level);
+            }
+            stack.extend(f.subformats());
+        }
+        levels
+    }
+
+    /// Returns the level of the format parsed by this one at its current position, if it does nothing else besides
+    /// (optionally) confining that parse to a slice.
+    ///
+    /// Such formats are the ones that may be deferred at an offset, as lazy parses in generated code.
+    pub(crate) fn deferrable_level(&self) -> Option<usize> {
+        match self {
+            Format::ItemVar(level, ..) => Some(*level),
+            Format::Slice(_, f) => match f.as_ref() {
+                Format::ItemVar(level, ..) => Some(*level),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
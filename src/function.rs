@@ -0,0 +1,519 @@
+//! Named, typed functions over values, defined within a [`FormatModule`](crate::FormatModule) and invoked
+//! from any expression of the module through [`Expr::Call`].
+//!
+//! Functions are pure, and may call themselves (or one another) recursively. So that evaluating a call
+//! always terminates, every cycle of calls must be well-founded: each recursive function is assigned a
+//! parameter of unsigned or sequence type as its measure, and every call within a cycle must pass, as the
+//! measure of the callee, either the measure of the caller itself or something syntactically smaller than it
+//! (`x - k` or `pred(x)` for an unsigned `x` and a literal `k >= 1`, or `sub_seq(x, k, _)` for a sequence `x`),
+//! with no cycle consisting only of the former.
+//!
+//! - The interpreters evaluate the body of the function in a scope that binds only its parameters.
+//! - Generated code declares a single Rust function for each, called wherever the function is.
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::{Arith, BaseType, Expr, Label, TypeHint, UnaryOp, ValueType};
+
+/// A function defined within a [`FormatModule`](crate::FormatModule).
+#[derive(Clone, Debug, Serialize)]
+pub struct ExprFunction {
+    pub name: Label,
+    pub params: Vec<(Label, ValueType)>,
+    pub ret: ValueType,
+    /// Body of the function, in a scope binding only `params`; `None` if only declared so far
+    pub(crate) body: Option<Expr>,
+}
+
+impl ExprFunction {
+    /// Returns the body of the function.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the function has been declared but never defined.
+    pub fn body(&self) -> &Expr {
+        match &self.body {
+            Some(body) => body,
+            None => panic!("function `{}` is declared but never defined", self.name),
+        }
+    }
+}
+
+/// Reference to a function defined within a [`FormatModule`](crate::FormatModule), from which calls to it are
+/// constructed.
+#[derive(Clone, Debug)]
+pub struct FunctionRef {
+    index: usize,
+    ret: TypeHint,
+}
+
+impl FunctionRef {
+    pub(crate) fn new(index: usize, ret: ValueType) -> Self {
+        FunctionRef {
+            index,
+            ret: ret.into(),
+        }
+    }
+
+    pub fn get_index(&self) -> usize {
+        self.index
+    }
+
+    pub fn call(&self, args: Vec<Expr>) -> Expr {
+        Expr::Call(self.index, self.ret.clone(), args)
+    }
+}
+
+/// Error reported for a cycle of calls that cannot be shown to terminate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonTerminating {
+    /// Names of the functions in the cycle, in order of definition
+    pub functions: Vec<Label>,
+}
+
+impl fmt::Display for NonTerminating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = self
+            .functions
+            .iter()
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(
+            f,
+            "cannot show that recursion through {names} terminates: no parameter decreases along every cycle of calls"
+        )
+    }
+}
+
+impl std::error::Error for NonTerminating {}
+
+/// Size of an argument of a call, relative to the parameters of the calling function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Size {
+    /// The parameter itself
+    Same(usize),
+    /// Strictly smaller than the parameter
+    Smaller(usize),
+    Unknown,
+}
+
+struct Call {
+    callee: usize,
+    args: Vec<Size>,
+}
+
+/// Checks that every cycle of calls among the (defined) `functions` terminates.
+pub(crate) fn check_termination(functions: &[ExprFunction]) -> Result<(), NonTerminating> {
+    let calls: Vec<Vec<Call>> = functions
+        .iter()
+        .map(|func| {
+            let mut calls = Vec::new();
+            if let Some(body) = &func.body {
+                collect_calls(body, &func.params, &mut Vec::new(), &mut calls);
+            }
+            calls
+        })
+        .collect();
+    let reaches = reachability(&calls);
+    let mut checked = vec![false; functions.len()];
+    for ix in 0..functions.len() {
+        if checked[ix] || !reaches[ix][ix] {
+            continue;
+        }
+        let cycle: Vec<usize> = (0..functions.len())
+            .filter(|&other| reaches[ix][other] && reaches[other][ix])
+            .collect();
+        for &member in cycle.iter() {
+            checked[member] = true;
+        }
+        if !has_measure(functions, &calls, &cycle) {
+            return Err(NonTerminating {
+                functions: cycle.iter().map(|&f| functions[f].name.clone()).collect(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the transitive closure of the call graph.
+fn reachability(calls: &[Vec<Call>]) -> Vec<Vec<bool>> {
+    let n = calls.len();
+    let mut reaches = vec![vec![false; n]; n];
+    for (from, row) in reaches.iter_mut().enumerate() {
+        let mut stack: Vec<usize> = calls[from].iter().map(|call| call.callee).collect();
+        while let Some(to) = stack.pop() {
+            if !row[to] {
+                row[to] = true;
+                stack.extend(calls[to].iter().map(|call| call.callee));
+            }
+        }
+    }
+    reaches
+}
+
+/// Returns `true` if some assignment of measures to the functions of `cycle` shows every cycle of calls
+/// among them to terminate.
+fn has_measure(functions: &[ExprFunction], calls: &[Vec<Call>], cycle: &[usize]) -> bool {
+    let candidates: Vec<Vec<usize>> = cycle
+        .iter()
+        .map(|&f| {
+            let params = &functions[f].params;
+            (0..params.len())
+                .filter(|&ix| is_well_founded(&params[ix].1))
+                .collect()
+        })
+        .collect();
+    if candidates.iter().any(Vec::is_empty) {
+        return false;
+    }
+    // odometer over every combination of candidate measures
+    let mut choice = vec![0; cycle.len()];
+    loop {
+        let measure = |f: usize| {
+            let pos = cycle.iter().position(|&g| g == f).unwrap();
+            candidates[pos][choice[pos]]
+        };
+        if is_measure(calls, cycle, measure) {
+            return true;
+        }
+        let mut pos = 0;
+        loop {
+            if pos == cycle.len() {
+                return false;
+            }
+            choice[pos] += 1;
+            if choice[pos] < candidates[pos].len() {
+                break;
+            }
+            choice[pos] = 0;
+            pos += 1;
+        }
+    }
+}
+
+/// Returns `true` if no call within `cycle` increases the `measure` of its callee beyond that of its caller,
+/// and no cycle of calls preserves it throughout.
+fn is_measure(calls: &[Vec<Call>], cycle: &[usize], measure: impl Fn(usize) -> usize) -> bool {
+    let mut preserving: Vec<(usize, usize)> = Vec::new();
+    for &caller in cycle {
+        for call in calls[caller].iter() {
+            if !cycle.contains(&call.callee) {
+                continue;
+            }
+            match call.args.get(measure(call.callee)) {
+                Some(Size::Smaller(ix)) if *ix == measure(caller) => {}
+                Some(Size::Same(ix)) if *ix == measure(caller) => {
+                    preserving.push((caller, call.callee))
+                }
+                _ => return false,
+            }
+        }
+    }
+    // repeatedly discard the calls out of functions with no preserving call into them
+    loop {
+        let before = preserving.len();
+        let targets: Vec<usize> = preserving.iter().map(|&(_, to)| to).collect();
+        preserving.retain(|(from, _)| targets.contains(from));
+        if preserving.is_empty() {
+            return true;
+        } else if preserving.len() == before {
+            return false;
+        }
+    }
+}
+
+fn is_well_founded(t: &ValueType) -> bool {
+    matches!(
+        t,
+        ValueType::Base(BaseType::U8 | BaseType::U16 | BaseType::U32 | BaseType::U64)
+            | ValueType::Seq(_)
+    )
+}
+
+/// Returns the size of `arg` relative to the parameters `params`, none of whose names in `bound` are
+/// in scope.
+fn arg_size(arg: &Expr, params: &[(Label, ValueType)], bound: &[Label]) -> Size {
+    let param_index = |x: &Expr| match x {
+        Expr::Var(name) if !bound.contains(name) => params.iter().position(|(p, _)| p == name),
+        _ => None,
+    };
+    let is_positive = |x: &Expr| match x {
+        Expr::U8(n) => *n > 0,
+        Expr::U16(n) => *n > 0,
+        Expr::U32(n) => *n > 0,
+        Expr::U64(n) => *n > 0,
+        _ => false,
+    };
+    match arg {
+        Expr::Var(_) => param_index(arg).map_or(Size::Unknown, Size::Same),
+        Expr::Arith(Arith::Sub, x, k) if is_positive(k) => {
+            param_index(x).map_or(Size::Unknown, Size::Smaller)
+        }
+        Expr::Unary(UnaryOp::IntPred, x) => param_index(x).map_or(Size::Unknown, Size::Smaller),
+        Expr::SubSeq(x, start, _) if is_positive(start) => {
+            param_index(x).map_or(Size::Unknown, Size::Smaller)
+        }
+        _ => Size::Unknown,
+    }
+}
+
+/// Collects every call made within `expr`, where the names in `bound` are bound locally (rather than to
+/// the parameters `params`).
+fn collect_calls(
+    expr: &Expr,
+    params: &[(Label, ValueType)],
+    bound: &mut Vec<Label>,
+    calls: &mut Vec<Call>,
+) {
+    let mut visit = |x: &Expr, bound: &mut Vec<Label>| collect_calls(x, params, bound, calls);
+    match expr {
+        Expr::Var(_)
+        | Expr::Bool(_)
+        | Expr::U8(_)
+        | Expr::U16(_)
+        | Expr::U32(_)
+        | Expr::U64(_)
+        | Expr::Numeric(_) => {}
+        Expr::Call(callee, _, args) => {
+            for arg in args {
+                visit(arg, bound);
+            }
+            let args = args
+                .iter()
+                .map(|arg| arg_size(arg, params, bound))
+                .collect();
+            calls.push(Call {
+                callee: *callee,
+                args,
+            });
+        }
+        Expr::Lambda(name, body) => {
+            bound.push(name.clone());
+            visit(body, bound);
+            bound.pop();
+        }
+        Expr::Record(fields) => {
+            // every field is evaluated in the enclosing scope, so field names bind nothing
+            for (_, x) in fields {
+                visit(x, bound);
+            }
+        }
+        Expr::Match(head, arms) => {
+            visit(head, bound);
+            for (pattern, body) in arms {
+                let mark = bound.len();
+                bound.extend(pattern.bound_names().into_iter().cloned());
+                if let (_, Some(guard)) = pattern.split_guard() {
+                    visit(guard, bound);
+                }
+                visit(body, bound);
+                bound.truncate(mark);
+            }
+        }
+        Expr::Destructure(head, pattern, body) => {
+            visit(head, bound);
+            let mark = bound.len();
+            bound.extend(pattern.bound_names().into_iter().cloned());
+            if let (_, Some(guard)) = pattern.split_guard() {
+                visit(guard, bound);
+            }
+            visit(body, bound);
+            bound.truncate(mark);
+        }
+        Expr::Tuple(xs) | Expr::Seq(xs) => {
+            for x in xs {
+                visit(x, bound);
+            }
+        }
+        Expr::TupleProj(x, _)
+        | Expr::RecordProj(x, _)
+        | Expr::Variant(_, x)
+        | Expr::Unary(_, x)
+        | Expr::AsU8(x)
+        | Expr::AsU16(x)
+        | Expr::AsU32(x)
+        | Expr::AsU64(x)
+        | Expr::AsChar(x)
+        | Expr::F32FromBits(x)
+        | Expr::F64FromBits(x)
+        | Expr::FixedFromBits(_, x)
+        | Expr::U16Be(x)
+        | Expr::U16Le(x)
+        | Expr::U32Be(x)
+        | Expr::U32Le(x)
+        | Expr::U64Be(x)
+        | Expr::U64Le(x)
+        | Expr::SeqLength(x)
+        | Expr::Reverse(x) => visit(x, bound),
+        Expr::IntRel(_, x, y)
+        | Expr::Arith(_, x, y)
+        | Expr::SeqIx(x, y)
+        | Expr::FlatMap(x, y)
+        | Expr::FlatMapList(x, _, y)
+        | Expr::Filter(x, y)
+        | Expr::Zip(x, y)
+        | Expr::Any(x, y)
+        | Expr::All(x, y)
+        | Expr::IsSortedBy(x, y)
+        | Expr::EnumFromTo(x, y)
+        | Expr::Dup(x, y)
+        | Expr::Append(x, y) => {
+            visit(x, bound);
+            visit(y, bound);
+        }
+        Expr::SubSeq(x, y, z)
+        | Expr::SubSeqInflate(x, y, z)
+        | Expr::FlatMapAccum(x, y, _, z)
+        | Expr::FindByKey(_, x, y, z)
+        | Expr::LeftFold(x, y, _, z) => {
+            visit(x, bound);
+            visit(y, bound);
+            visit(z, bound);
+        }
+        Expr::LiftOption(opt) => {
+            if let Some(x) = opt {
+                visit(x, bound);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pattern;
+    use crate::helper::*;
+
+    fn function(name: &'static str, params: Vec<(Label, ValueType)>, body: Expr) -> ExprFunction {
+        ExprFunction {
+            name: Label::Borrowed(name),
+            params,
+            ret: ValueType::U32,
+            body: Some(body),
+        }
+    }
+
+    fn call(index: usize, args: Vec<Expr>) -> Expr {
+        FunctionRef::new(index, ValueType::U32).call(args)
+    }
+
+    fn names(result: Result<(), NonTerminating>) -> Vec<Label> {
+        result.err().map_or(Vec::new(), |e| e.functions)
+    }
+
+    #[test]
+    fn test_structural_recursion() {
+        // sum(n) = if n == 0 then 0 else n + sum(n - 1)
+        let sum = function(
+            "sum",
+            vec![("n".into(), ValueType::U32)],
+            expr_if_else(
+                expr_eq(var("n"), Expr::U32(0)),
+                Expr::U32(0),
+                add(var("n"), call(0, vec![sub(var("n"), Expr::U32(1))])),
+            ),
+        );
+        assert!(check_termination(&[sum]).is_ok());
+        // len(xs) = if xs == [] then 0 else 1 + len(xs[1..])
+        let len = function(
+            "len",
+            vec![("xs".into(), ValueType::Seq(Box::new(ValueType::U8)))],
+            expr_match(
+                seq_length(var("xs")),
+                vec![
+                    (Pattern::U32(0), Expr::U32(0)),
+                    (
+                        Pattern::Wildcard,
+                        succ(call(
+                            0,
+                            vec![sub_seq(
+                                var("xs"),
+                                Expr::U32(1),
+                                pred(seq_length(var("xs"))),
+                            )],
+                        )),
+                    ),
+                ],
+            ),
+        );
+        assert!(check_termination(&[len]).is_ok());
+        // f(n) = { n: n, rest: f(n - 1) }, where the field `n` does not shadow the parameter
+        let fields = function(
+            "fields",
+            vec![("n".into(), ValueType::U32)],
+            Expr::Record(vec![
+                ("n".into(), var("n")),
+                ("rest".into(), call(0, vec![sub(var("n"), Expr::U32(1))])),
+            ]),
+        );
+        assert!(check_termination(&[fields]).is_ok());
+    }
+
+    #[test]
+    fn test_non_terminating() {
+        let forever = function(
+            "forever",
+            vec![("n".into(), ValueType::U32)],
+            call(0, vec![var("n")]),
+        );
+        assert_eq!(names(check_termination(&[forever])), vec!["forever"]);
+        // the decreasing argument is a local binding, rather than the parameter
+        let shadowed = function(
+            "shadowed",
+            vec![("n".into(), ValueType::U32)],
+            expr_match(
+                var("n"),
+                vec![(
+                    Pattern::binding("n"),
+                    call(0, vec![add(sub(var("n"), Expr::U32(1)), Expr::U32(1))]),
+                )],
+            ),
+        );
+        assert_eq!(names(check_termination(&[shadowed])), vec!["shadowed"]);
+    }
+
+    #[test]
+    fn test_mutual_recursion() {
+        // even(n) = n == 0 || odd(n - 1); odd(n) = n != 0 && even(n - 1)
+        let even = function(
+            "even",
+            vec![("n".into(), ValueType::U32)],
+            or(
+                expr_eq(var("n"), Expr::U32(0)),
+                call(1, vec![sub(var("n"), Expr::U32(1))]),
+            ),
+        );
+        let odd = function(
+            "odd",
+            vec![("m".into(), ValueType::U32)],
+            and(
+                expr_ne(var("m"), Expr::U32(0)),
+                call(0, vec![sub(var("m"), Expr::U32(1))]),
+            ),
+        );
+        assert!(check_termination(&[even.clone(), odd]).is_ok());
+        // a call that preserves the measure is fine, so long as the cycle decreases it elsewhere
+        let relay = function(
+            "relay",
+            vec![("m".into(), ValueType::U32)],
+            call(0, vec![var("m")]),
+        );
+        assert!(check_termination(&[even.clone(), relay]).is_ok());
+        let echo = function(
+            "echo",
+            vec![("m".into(), ValueType::U32)],
+            call(0, vec![var("m")]),
+        );
+        let stall = function(
+            "stall",
+            vec![("n".into(), ValueType::U32)],
+            call(1, vec![var("n")]),
+        );
+        assert_eq!(
+            names(check_termination(&[stall, echo])),
+            vec!["stall", "echo"]
+        );
+    }
+}
@@ -0,0 +1,799 @@
+//! Generation of random inputs that are accepted by a given [`Format`].
+//!
+//! The generator walks a `Format` in the same order as the decoder, choosing bytes, repetition counts and
+//! union branches at random (from a seeded RNG, for reproducibility) while keeping track of the values the
+//! decoder would bind along the way, so that dependent formats (e.g. counts, slice lengths and offsets)
+//! are generated consistently with the bytes that precede them.
+//!
+//! Constraints that cannot be solved directly, such as `Format::Where` assertions and the lookahead
+//! decisions of unions and repeats, are handled by rejection: each candidate is checked by decoding it
+//! with the interpreter ([`crate::decoder::Program::run`]), and discarded if it fails to parse.
+use std::collections::BTreeMap;
+
+use anyhow::Result as AResult;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::byte_set::ByteSet;
+use crate::decoder::{
+    Compiler, MultiScope, Program, Scope, SeqKind, SingleScope, Value, huffman_lengths,
+    make_huffman_codes,
+};
+use crate::encoder::{Env, Sink};
+use crate::error::{EncodeError, EvalResult, GenerateError, GenerateResult};
+use crate::read::ReadCtxt;
+use crate::{BaseKind, CommonOp, DynFormat, Endian, Format, FormatModule, StyleHint, ViewFormat};
+
+/// Number of candidate values generated for a `Format::Where` before giving up on the assertion
+const WHERE_RETRIES: usize = 16;
+
+/// Probability of restricting a multi-byte integer to the range of a single byte, rather than choosing it uniformly.
+///
+/// As many such integers are lengths, counts and offsets, this keeps the size of generated inputs manageable.
+const SMALL_INT_BIAS: f64 = 0.75;
+
+/// Parameters controlling the size and reproducibility of generated inputs.
+#[derive(Clone, Copy, Debug)]
+pub struct GenConfig {
+    /// Seed for the random number generator
+    pub seed: u64,
+    /// Maximum length (in bytes) of any generated input
+    pub max_size: usize,
+    /// Maximum nesting depth of format-invocations (`Format::ItemVar`), to bound recursive formats
+    pub max_depth: usize,
+    /// Number of candidates to generate before giving up
+    pub max_attempts: usize,
+}
+
+impl Default for GenConfig {
+    fn default() -> Self {
+        GenConfig {
+            seed: 0,
+            max_size: 1 << 16,
+            max_depth: 64,
+            max_attempts: 64,
+        }
+    }
+}
+
+/// Generator of random byte strings accepted by a root [`Format`] within a [`FormatModule`].
+pub struct Generator<'a> {
+    module: &'a FormatModule,
+    format: &'a Format,
+    program: Program,
+    config: GenConfig,
+    rng: ChaCha8Rng,
+    /// When set, every choice is made to produce as little output as possible
+    minimal: bool,
+    /// Upper bound on the offset of any byte written to the current stream
+    limit: usize,
+    depth: usize,
+    state: State,
+    /// Offset and format of the union at which the branch was pinned, during the current attempt
+    pinned_union: Option<(usize, Format)>,
+    /// Decoder for the most recent `pinned_union`, used to check that it is parsed as the pinned branch
+    union_program: Option<(Format, Program)>,
+}
+
+/// Choices made by earlier parts of a candidate that constrain the remainder of its generation.
+#[derive(Clone, Default)]
+struct State {
+    /// Bytes at absolute offsets whose values were fixed in advance by a `Format::Peek`
+    forced: BTreeMap<usize, u8>,
+    /// Branch to take at the first union encountered, if any
+    pinned: Option<usize>,
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(module: &'a FormatModule, format: &'a Format, config: GenConfig) -> AResult<Self> {
+        let program = Compiler::compile_program(module, format)?;
+        Ok(Generator {
+            module,
+            format,
+            program,
+            config,
+            rng: ChaCha8Rng::seed_from_u64(config.seed),
+            minimal: false,
+            limit: config.max_size,
+            depth: 0,
+            state: State::default(),
+            pinned_union: None,
+            union_program: None,
+        })
+    }
+
+    /// Generates a random input accepted by the root format.
+    pub fn generate(&mut self) -> GenerateResult<Vec<u8>> {
+        let mut last = GenerateError::Fail;
+        for _ in 0..self.config.max_attempts {
+            match self.attempt(None, false) {
+                Ok(bytes) => return Ok(bytes),
+                Err(err) => last = err,
+            }
+        }
+        Err(GenerateError::Exhausted {
+            attempts: self.config.max_attempts,
+            last: Box::new(last),
+        })
+    }
+
+    /// Generates an input accepted by the root format that takes branch `index` of the first union it
+    /// encounters, returning the smallest such input found within the configured number of attempts.
+    pub fn generate_branch(&mut self, index: usize) -> GenerateResult<Vec<u8>> {
+        let mut best: Option<Vec<u8>> = None;
+        let mut last = GenerateError::Fail;
+        for attempt in 0..self.config.max_attempts {
+            match self.attempt(Some(index), attempt == 0) {
+                Ok(bytes) => {
+                    if best.as_ref().is_none_or(|best| bytes.len() < best.len()) {
+                        best = Some(bytes);
+                    }
+                }
+                Err(err @ GenerateError::NoSuchBranch { .. }) => return Err(err),
+                Err(err) => last = err,
+            }
+        }
+        best.ok_or_else(|| GenerateError::Exhausted {
+            attempts: self.config.max_attempts,
+            last: Box::new(last),
+        })
+    }
+
+    /// Generates a minimal example for each branch of the first union encountered by the root format.
+    pub fn branch_examples(&mut self) -> Vec<GenerateResult<Vec<u8>>> {
+        let mut examples = Vec::new();
+        for index in 0.. {
+            match self.generate_branch(index) {
+                Err(GenerateError::NoSuchBranch { .. }) => break,
+                res => examples.push(res),
+            }
+        }
+        examples
+    }
+
+    fn attempt(&mut self, pinned: Option<usize>, minimal: bool) -> GenerateResult<Vec<u8>> {
+        self.minimal = minimal;
+        self.limit = self.config.max_size;
+        self.depth = 0;
+        self.state = State {
+            forced: BTreeMap::new(),
+            pinned,
+        };
+        self.pinned_union = None;
+        let mut sink = Sink::new(0);
+        self.gen_format(
+            self.format,
+            &Scope::Functions(self.module.functions()),
+            &Env::EMPTY,
+            &mut sink,
+        )?;
+        let bytes = sink.finish()?;
+        if let Err(err) = self.program.run(ReadCtxt::new(&bytes)) {
+            return Err(GenerateError::Rejected(Box::new(err)));
+        }
+        if let (Some(index), Some((offset, union))) = (pinned, self.pinned_union.take()) {
+            self.check_pinned(index, offset, union, &bytes)?;
+        }
+        Ok(bytes)
+    }
+
+    /// Generates bytes for `format` into `sink`, returning the value the decoder would produce for them.
+    fn gen_format(
+        &mut self,
+        format: &Format,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> GenerateResult<Value> {
+        match format {
+            Format::ItemVar(level, arg_exprs, arg_views) => {
+                if self.depth >= self.config.max_depth {
+                    return Err(GenerateError::TooDeep {
+                        max_depth: self.config.max_depth,
+                    });
+                }
+                let arg_names = self.module.get_args(*level);
+                let mut new_scope = MultiScope::with_capacity(scope.root(), arg_names.len());
+                for ((name, _), expr) in Iterator::zip(arg_names.iter(), arg_exprs.iter()) {
+                    new_scope.push_owned(name.clone(), expr.eval_value(scope)?);
+                }
+                let view_names = self.module.get_view_args(*level);
+                let call_env = Env::Call(
+                    Iterator::zip(view_names.iter(), arg_views.iter())
+                        .map(|(name, v_expr)| {
+                            Ok((name.clone(), env.eval_view_expr(scope, v_expr)?))
+                        })
+                        .collect::<EvalResult<_>>()?,
+                );
+                self.depth += 1;
+                let res = self.gen_format(
+                    self.module.get_format(*level),
+                    &Scope::Multi(&new_scope),
+                    &call_env,
+                    sink,
+                );
+                self.depth -= 1;
+                res
+            }
+            Format::GenericVar(index, formats, arg_exprs, arg_views) => {
+                let generic = self.module.get_generic(*index);
+                let mut new_scope = MultiScope::with_capacity(scope.root(), generic.args.len());
+                for ((name, _), expr) in Iterator::zip(generic.args.iter(), arg_exprs.iter()) {
+                    new_scope.push_owned(name.clone(), expr.eval_value(scope)?);
+                }
+                let call_env = Env::Call(
+                    Iterator::zip(generic.views.iter(), arg_views.iter())
+                        .map(|(name, v_expr)| {
+                            Ok((name.clone(), env.eval_view_expr(scope, v_expr)?))
+                        })
+                        .collect::<EvalResult<_>>()?,
+                );
+                self.gen_format(
+                    &generic.instantiate(formats),
+                    &Scope::Multi(&new_scope),
+                    &call_env,
+                    sink,
+                )
+            }
+            Format::Fail => Err(GenerateError::Fail),
+            Format::EndOfInput | Format::SkipRemainder => Ok(Value::UNIT),
+            Format::Native(..) => Err(GenerateError::Unsupported {
+                construct: "Native",
+            }),
+            Format::Align(n) => {
+                sink.pad((n - (sink.offset() % n)) % n);
+                Ok(Value::UNIT)
+            }
+            Format::Byte(bs) => Ok(Value::U8(self.gen_byte(*bs, sink)?)),
+            Format::Variant(label, f) => {
+                let v = self.gen_format(f, scope, env, sink)?;
+                Ok(Value::Variant(label.clone(), Box::new(v)))
+            }
+            Format::Union(branches) | Format::UnionNondet(branches) => {
+                let order = match self.state.pinned.take() {
+                    Some(index) if index < branches.len() => {
+                        self.pinned_union = Some((sink.offset(), format.clone()));
+                        vec![index]
+                    }
+                    Some(index) => return Err(GenerateError::NoSuchBranch { index }),
+                    None => self.order(branches.len()),
+                };
+                let mut last = GenerateError::Fail;
+                for index in order {
+                    match self.try_gen(&branches[index], scope, env, sink) {
+                        Ok(v) => return Ok(Value::Branch(index, Box::new(v))),
+                        Err(err) => last = err,
+                    }
+                }
+                Err(last)
+            }
+            Format::Tuple(fields) => {
+                let mut vs = Vec::with_capacity(fields.len());
+                for f in fields {
+                    vs.push(self.gen_format(f, scope, env, sink)?);
+                }
+                Ok(Value::Tuple(vs))
+            }
+            Format::Sequence(formats) => {
+                let mut vs = Vec::with_capacity(formats.len());
+                for f in formats {
+                    vs.push(self.gen_format(f, scope, env, sink)?);
+                }
+                Ok(Value::Seq(SeqKind::Strict(vs)))
+            }
+            Format::Repeat(a) => {
+                let count = self.gen_count(0, None);
+                self.gen_seq(a, 0, count, scope, env, sink)
+            }
+            Format::Repeat1(a) => {
+                let count = self.gen_count(1, None);
+                self.gen_seq(a, 1, count, scope, env, sink)
+            }
+            Format::RepeatCount(expr, a) => {
+                let count = expr.eval_value(scope)?.unwrap_usize();
+                self.gen_seq(a, count, count, scope, env, sink)
+            }
+            Format::RepeatBetween(min, max, a) => {
+                let min = min.eval_value(scope)?.unwrap_usize();
+                let max = max.eval_value(scope)?.unwrap_usize();
+                let count = self.gen_count(min, Some(max));
+                self.gen_seq(a, min, count, scope, env, sink)
+            }
+            Format::RepeatUntilLast(expr, a) => {
+                let mut vs = Vec::new();
+                loop {
+                    let v = self.gen_format(a, scope, env, sink)?;
+                    let done = expr.eval_lambda(scope, &v)?.unwrap_bool();
+                    vs.push(v);
+                    if done {
+                        break Ok(Value::Seq(vs.into()));
+                    }
+                }
+            }
+            Format::RepeatUntilSeq(expr, a) => {
+                let mut vs = Vec::new();
+                loop {
+                    vs.push(self.gen_format(a, scope, env, sink)?);
+                    let seq = Value::Seq(vs.into());
+                    let done = expr.eval_lambda(scope, &seq)?.unwrap_bool();
+                    match seq {
+                        Value::Seq(seq) if done => break Ok(Value::Seq(seq)),
+                        Value::Seq(seq) => vs = seq.into_vec(),
+                        _ => unreachable!(),
+                    }
+                }
+            }
+            Format::AccumUntil(f_done, f_update, init, _vt, a) => {
+                let mut accum = init.eval_value(scope)?;
+                let mut vs = Vec::new();
+                loop {
+                    let done_arg = Value::Tuple(vec![accum.clone(), Value::Seq(vs.clone().into())]);
+                    if f_done.eval_lambda(scope, &done_arg)?.unwrap_bool() {
+                        break Ok(Value::Tuple(vec![accum, Value::Seq(vs.into())]));
+                    }
+                    let v = self.gen_format(a, scope, env, sink)?;
+                    vs.push(v.clone());
+                    accum = f_update.eval_lambda(scope, &Value::Tuple(vec![accum, v]))?;
+                }
+            }
+            Format::ForEach(expr, lbl, a) => {
+                let val = expr.eval_value(scope)?;
+                let items = val.get_sequence().expect("bad type for ForEach input");
+                let mut vs = Vec::with_capacity(items.len());
+                for item in items {
+                    let new_scope = Scope::Single(SingleScope::new(scope, lbl, &item));
+                    vs.push(self.gen_format(a, &new_scope, env, sink)?);
+                }
+                Ok(Value::Seq(vs.into()))
+            }
+            Format::Maybe(expr, a) => {
+                if expr.eval_value(scope)?.unwrap_bool() {
+                    let v = self.gen_format(a, scope, env, sink)?;
+                    Ok(Value::Option(Some(Box::new(v))))
+                } else {
+                    Ok(Value::Option(None))
+                }
+            }
+            Format::Peek(a) => {
+                // NOTE - the bytes of the lookahead are fixed in advance, to be reproduced by whatever follows
+                let start = sink.offset();
+                let mut lookahead = Sink::new(start);
+                let v = self.gen_format(a, scope, env, &mut lookahead)?;
+                for (ix, byte) in lookahead.finish()?.into_iter().enumerate().skip(start) {
+                    match self.state.forced.insert(ix, byte) {
+                        Some(prior) if prior != byte => {
+                            return Err(GenerateError::Lookahead { offset: ix });
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(v)
+            }
+            // NOTE - negative lookahead is left to be enforced by rejection
+            Format::PeekNot(_) => Ok(Value::UNIT),
+            Format::Slice(expr, a) => {
+                let size = expr.eval_value(scope)?.unwrap_usize();
+                self.check_limit(sink.offset().saturating_add(size))?;
+                let mut slice_sink = Sink::new(sink.offset());
+                let limit = self.limit;
+                self.limit = sink.offset() + size;
+                let res = self.gen_format(a, scope, env, &mut slice_sink);
+                self.limit = limit;
+                let v = res?;
+                let written = slice_sink.written();
+                if written > size {
+                    return Err(EncodeError::SliceOverflow { size, written }.into());
+                }
+                slice_sink.pad(size - written);
+                sink.append(slice_sink);
+                Ok(v)
+            }
+            Format::Bits(a) => {
+                let forced = std::mem::take(&mut self.state.forced);
+                let limit = self.limit;
+                self.limit = limit.saturating_mul(8);
+                let mut bit_sink = Sink::new(0);
+                let res = self.gen_format(a, scope, env, &mut bit_sink);
+                self.state.forced = forced;
+                self.limit = limit;
+                let v = res?;
+                sink.append_bits(bit_sink)?;
+                Ok(v)
+            }
+            Format::WithRelativeOffset(base_addr, expr, a) => {
+                let base = base_addr.eval_value(scope)?.unwrap_usize();
+                let offset = expr.eval_value(scope)?.unwrap_usize();
+                self.gen_patch(base.saturating_add(offset), a, scope, env, sink)
+            }
+            Format::Map(a, expr) => {
+                let orig = self.gen_format(a, scope, env, sink)?;
+                let v = expr.eval_lambda(scope, &orig)?;
+                Ok(Value::Mapped(Box::new(orig), Box::new(v)))
+            }
+            Format::Where(a, cond) => {
+                // NOTE - assertions on single bytes are solved directly, by narrowing the set of permitted bytes
+                if let Some(bs) = self.byte_set(a) {
+                    let bs = ByteSet::from_iter(bs.iter().filter(|b| {
+                        cond.as_ref()
+                            .eval_lambda(scope, &Value::U8(*b))
+                            .is_ok_and(|v| v.unwrap_bool())
+                    }));
+                    return Ok(Value::U8(self.gen_byte(bs, sink)?));
+                }
+                let minimal = self.minimal;
+                let mut res = Err(GenerateError::Unsatisfied {
+                    assertion: Box::new(cond.as_ref().clone()),
+                });
+                for retry in 0..WHERE_RETRIES {
+                    // NOTE - minimal choices are deterministic, so there is no point in repeating them
+                    self.minimal = minimal && retry == 0;
+                    let mut where_sink = Sink::new(sink.offset());
+                    let saved = self.state.clone();
+                    match self.gen_format(a, scope, env, &mut where_sink) {
+                        Ok(v)
+                            if cond.as_ref().eval_lambda(scope, &v)?.unwrap_bool()
+                                || (!cond.is_required() && retry + 1 == WHERE_RETRIES) =>
+                        {
+                            sink.append(where_sink);
+                            res = Ok(v);
+                            break;
+                        }
+                        Ok(_) => self.state = saved,
+                        Err(err) => {
+                            self.state = saved;
+                            res = Err(err);
+                        }
+                    }
+                }
+                self.minimal = minimal;
+                res
+            }
+            Format::Compute(expr) => Ok(expr.eval_value(scope)?),
+            Format::Let(name, expr, a) => {
+                let v = expr.eval_value(scope)?;
+                let let_scope = Scope::Single(SingleScope::new(scope, name, &v));
+                self.gen_format(a, &let_scope, env, sink)
+            }
+            Format::Match(head, branches) => {
+                let head = head.eval(scope)?;
+                for (index, (pattern, f)) in branches.iter().enumerate() {
+                    if let Some(pattern_scope) = head.matches(scope, pattern)? {
+                        let v = self.gen_format(f, &Scope::Multi(&pattern_scope), env, sink)?;
+                        return Ok(Value::Branch(index, Box::new(v)));
+                    }
+                }
+                Err(GenerateError::Fail)
+            }
+            Format::Dynamic(name, DynFormat::Huffman(lengths_expr, opt_values_expr), a) => {
+                let lengths = huffman_lengths(scope, lengths_expr, opt_values_expr.as_deref())?;
+                let dyn_env = Env::Dynamic(env, name, make_huffman_codes(&lengths));
+                self.gen_format(a, scope, &dyn_env, sink)
+            }
+            Format::Apply(name) => self.gen_format(env.get_format_by_name(name), scope, env, sink),
+            Format::Pos => Ok(Value::U64(sink.offset() as u64)),
+            Format::DecodeBytes(..) => Err(GenerateError::Unsupported {
+                construct: "DecodeBytes",
+            }),
+            Format::LetFormat(a, name, b) => {
+                let va = self.gen_format(a, scope, env, sink)?;
+                let new_scope = Scope::Single(SingleScope::new(scope, name, &va));
+                self.gen_format(b, &new_scope, env, sink)
+            }
+            Format::MonadSeq(a, b) => {
+                self.gen_format(a, scope, env, sink)?;
+                self.gen_format(b, scope, env, sink)
+            }
+            Format::Hint(StyleHint::Common(CommonOp::EndianParse(kind)), a) => {
+                // NOTE - multi-byte integers are often lengths or offsets, so we favor those that fit within one byte
+                if self.minimal || self.rng.random_bool(SMALL_INT_BIAS) {
+                    let start = sink.offset();
+                    let high_bytes = match kind {
+                        BaseKind::U16Ext(Endian::Le)
+                        | BaseKind::U32Ext(Endian::Le)
+                        | BaseKind::U64Ext(Endian::Le)
+                        | BaseKind::I16Ext(Endian::Le)
+                        | BaseKind::I32Ext(Endian::Le)
+                        | BaseKind::I64Ext(Endian::Le) => start + 1..start + kind.size(),
+                        _ => start..start + kind.size() - 1,
+                    };
+                    for offset in high_bytes {
+                        self.state.forced.entry(offset).or_insert(0);
+                    }
+                }
+                self.gen_format(a, scope, env, sink)
+            }
+            Format::Hint(_, a) => self.gen_format(a, scope, env, sink),
+            Format::LiftedOption(None) => Ok(Value::Option(None)),
+            Format::LiftedOption(Some(a)) => {
+                let v = self.gen_format(a, scope, env, sink)?;
+                Ok(Value::Option(Some(Box::new(v))))
+            }
+            Format::LetView(name, a) => {
+                let view_env = Env::View(env, name, sink.offset());
+                self.gen_format(a, scope, &view_env, sink)
+            }
+            Format::WithView(v_expr, vf) => match vf {
+                ViewFormat::ReifyView => Ok(Value::View {
+                    offset: env.eval_view_expr(scope, v_expr)?,
+                }),
+                // REVIEW - the viewed bytes may not have been generated yet, so we cannot know their values
+                ViewFormat::CaptureBytes(_) | ViewFormat::ReadArray(..) => {
+                    Err(GenerateError::Unsupported {
+                        construct: "WithView",
+                    })
+                }
+            },
+            Format::ParseFromView(v_expr, a) => {
+                let offset = env.eval_view_expr(scope, v_expr)?;
+                self.gen_patch(offset, a, scope, env, sink)
+            }
+            Format::Phantom(_) => Ok(Value::PhantomData),
+            #[cfg(feature = "format_enforce")]
+            Format::Enforce(a) => self.gen_format(a, scope, env, sink),
+            Format::Permit(a, _) => {
+                let v = self.gen_format(a, scope, env, sink)?;
+                Ok(Value::Permit(Ok(Box::new(v))))
+            }
+            Format::Resync(a, _) => {
+                let v = self.gen_format(a, scope, env, sink)?;
+                Ok(Value::variant("ok", v))
+            }
+        }
+    }
+
+    /// Checks that the pinned union at `offset` within `bytes` is decoded as branch `index`, as the decoder of the
+    /// root format may have taken a different branch (e.g. an earlier branch of a `Format::UnionNondet` that also
+    /// accepts the input).
+    ///
+    /// Unions that depend on bindings from their context cannot be decoded in isolation, and are not checked.
+    fn check_pinned(
+        &mut self,
+        index: usize,
+        offset: usize,
+        union: Format,
+        bytes: &[u8],
+    ) -> GenerateResult<()> {
+        if self.union_program.as_ref().is_none_or(|(f, _)| *f != union) {
+            let Ok(program) = Compiler::compile_program(self.module, &union) else {
+                return Ok(());
+            };
+            self.union_program = Some((union, program));
+        }
+        let (_, program) = self.union_program.as_ref().unwrap();
+        let (_, input) = ReadCtxt::new(bytes).split_at(offset).unwrap();
+        match program.run(input) {
+            Ok((Value::Branch(found, _), _)) if found != index => Err(GenerateError::WrongBranch {
+                expected: index,
+                found,
+            }),
+            Ok(_) => Ok(()),
+            Err(err) => Err(GenerateError::Rejected(Box::new(err))),
+        }
+    }
+
+    /// Generates `format` into a separate sink, which is only appended to `sink` if generation succeeds.
+    fn try_gen(
+        &mut self,
+        format: &Format,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> GenerateResult<Value> {
+        let mut sub_sink = Sink::new(sink.offset());
+        let saved = self.state.clone();
+        match self.gen_format(format, scope, env, &mut sub_sink) {
+            Ok(v) => {
+                sink.append(sub_sink);
+                Ok(v)
+            }
+            Err(err) => {
+                self.state = saved;
+                Err(err)
+            }
+        }
+    }
+
+    /// Generates a sequence of `count` elements, of which any beyond the first `min` are dropped if they
+    /// would exceed the size limit.
+    fn gen_seq(
+        &mut self,
+        format: &Format,
+        min: usize,
+        count: usize,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> GenerateResult<Value> {
+        let mut vs = Vec::with_capacity(count.min(self.limit));
+        for ix in 0..count {
+            if ix < min {
+                vs.push(self.gen_format(format, scope, env, sink)?);
+                continue;
+            }
+            match self.try_gen(format, scope, env, sink) {
+                Ok(v) => vs.push(v),
+                Err(GenerateError::OverBudget { .. }) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(Value::Seq(vs.into()))
+    }
+
+    /// Generates a format whose bytes are located at the absolute `offset`, independently of the current position.
+    fn gen_patch(
+        &mut self,
+        offset: usize,
+        format: &Format,
+        scope: &Scope<'_>,
+        env: &Env<'_>,
+        sink: &mut Sink,
+    ) -> GenerateResult<Value> {
+        self.check_limit(offset)?;
+        let mut patch = Sink::new(offset);
+        let v = self.gen_format(format, scope, env, &mut patch)?;
+        sink.add_patch(patch);
+        Ok(v)
+    }
+
+    /// Returns the set of bytes accepted by `format`, if it parses exactly one byte and returns it verbatim.
+    fn byte_set(&self, format: &Format) -> Option<ByteSet> {
+        match format {
+            Format::Byte(bs) => Some(*bs),
+            Format::Hint(_, a) => self.byte_set(a),
+            Format::ItemVar(level, args, views) if args.is_empty() && views.is_empty() => {
+                self.byte_set(self.module.get_format(*level))
+            }
+            _ => None,
+        }
+    }
+
+    fn gen_byte(&mut self, bs: ByteSet, sink: &mut Sink) -> GenerateResult<u8> {
+        let offset = sink.offset();
+        self.check_limit(offset + 1)?;
+        let byte = match self.state.forced.get(&offset) {
+            Some(&byte) if bs.contains(byte) => byte,
+            Some(_) => return Err(GenerateError::Lookahead { offset }),
+            None if self.minimal => bs.min_elem().ok_or(GenerateError::Fail)?,
+            None => match bs.len() {
+                0 => return Err(GenerateError::Fail),
+                n => bs.iter().nth(self.rng.random_range(0..n as usize)).unwrap(),
+            },
+        };
+        sink.push(byte);
+        Ok(byte)
+    }
+
+    /// Chooses a number of repetitions of at least `min` (and at most `max`, if specified).
+    ///
+    /// Counts beyond the minimum follow a geometric distribution, favoring shorter sequences.
+    fn gen_count(&mut self, min: usize, max: Option<usize>) -> usize {
+        let mut count = min;
+        if !self.minimal {
+            while max.is_none_or(|max| count < max) && self.rng.random_bool(0.5) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Returns the order in which the branches of a union are to be attempted.
+    fn order(&mut self, len: usize) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..len).collect();
+        if !self.minimal {
+            order.shuffle(&mut self.rng);
+        }
+        order
+    }
+
+    fn check_limit(&self, end: usize) -> GenerateResult<()> {
+        if end > self.limit {
+            return Err(GenerateError::OverBudget {
+                max_size: self.config.max_size,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Expr;
+    use crate::decoder::Compiler;
+    use crate::helper::*;
+
+    fn config(seed: u64) -> GenConfig {
+        GenConfig {
+            seed,
+            max_size: 256,
+            ..GenConfig::default()
+        }
+    }
+
+    fn decodes(format: &Format, input: &[u8]) -> Value {
+        let module = FormatModule::new();
+        let program = Compiler::compile_program(&module, format).unwrap();
+        program.run(ReadCtxt::new(input)).unwrap().0
+    }
+
+    #[test]
+    fn test_generate_length_prefixed() {
+        let format = record([
+            ("len", u16be()),
+            ("data", slice(var("len"), repeat(is_byte(0x2a)))),
+            ("end", Format::EndOfInput),
+        ]);
+        let module = FormatModule::new();
+        for seed in 0..16 {
+            let bytes = Generator::new(&module, &format, config(seed))
+                .unwrap()
+                .generate()
+                .unwrap();
+            let len = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+            assert_eq!(bytes.len(), len + 2);
+            decodes(&format, &bytes);
+        }
+    }
+
+    #[test]
+    fn test_generate_reproducible() {
+        let format = repeat(record([("tag", byte_in(b'a'..=b'z')), ("value", u8())]));
+        let module = FormatModule::new();
+        let run = |seed| {
+            let mut generator = Generator::new(&module, &format, config(seed)).unwrap();
+            (0..8)
+                .map(|_| generator.generate().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn test_generate_where_byte() {
+        let format = record([
+            ("version", where_between_u8(u8(), 3, 5)),
+            ("end", Format::EndOfInput),
+        ]);
+        let module = FormatModule::new();
+        let mut generator = Generator::new(&module, &format, config(0)).unwrap();
+        for _ in 0..16 {
+            let bytes = generator.generate().unwrap();
+            assert!(matches!(bytes.as_slice(), [3..=5]));
+        }
+    }
+
+    #[test]
+    fn test_generate_over_budget() {
+        let format = repeat_count(Expr::U32(300), u8());
+        let module = FormatModule::new();
+        let mut generator = Generator::new(&module, &format, config(0)).unwrap();
+        assert!(matches!(
+            generator.generate(),
+            Err(GenerateError::Exhausted { last, .. }) if matches!(*last, GenerateError::OverBudget { max_size: 256 })
+        ));
+    }
+
+    #[test]
+    fn test_branch_examples() {
+        let format = record([
+            (
+                "data",
+                alts([
+                    ("short", is_byte(0x01)),
+                    ("long", tuple([is_byte(0x02), repeat1(u8())])),
+                ]),
+            ),
+            ("end", Format::EndOfInput),
+        ]);
+        let module = FormatModule::new();
+        let mut generator = Generator::new(&module, &format, config(0)).unwrap();
+        let examples = generator
+            .branch_examples()
+            .into_iter()
+            .collect::<GenerateResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(examples, vec![vec![0x01], vec![0x02, 0x00]]);
+    }
+}
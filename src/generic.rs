@@ -0,0 +1,290 @@
+//! Generic formats, defined within a [`FormatModule`] over format-kinded parameters in addition to the usual
+//! value and view parameters, and invoked through [`Format::GenericVar`].
+//!
+//! Within the body of a generic format, each format parameter is referred to by name through [`Format::Apply`].
+//! The format arguments of an invocation are substituted into the body of the generic format, so they may refer to
+//! its value and view parameters (which is how an argument such as `glyph_table(nglyphs)` is passed along with the
+//! value it depends on), but not to any other variable or view bound by the invoking format; an invocation whose
+//! format arguments refer to anything else is rejected when its type is inferred.
+//!
+//! Generic formats are never processed as such: both the decoder [`Compiler`](crate::decoder::Compiler) and
+//! code generation first [`monomorphize`] the module, defining a plain format for every distinct
+//! instantiation and replacing each invocation with an [`ItemVar`](Format::ItemVar) that refers to it.
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::{Expr, Format, FormatModule, Label, ValueType, ViewExpr};
+
+/// A format defined within a [`FormatModule`] over one or more format parameters.
+#[derive(Clone, Debug, Serialize)]
+pub struct GenericFormat {
+    pub name: Label,
+    /// Names of the format parameters
+    pub params: Vec<Label>,
+    pub args: Vec<(Label, ValueType)>,
+    pub views: Vec<Label>,
+    pub body: Format,
+}
+
+impl GenericFormat {
+    /// Returns the body of the generic format with each of its format parameters replaced by the corresponding
+    /// element of `formats`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the number of `formats` does not match the number of parameters.
+    pub fn instantiate(&self, formats: &[Format]) -> Format {
+        assert_eq!(
+            self.params.len(),
+            formats.len(),
+            "generic format `{}` expects {} format arguments, found {}",
+            self.name,
+            self.params.len(),
+            formats.len()
+        );
+        let mut body = self.body.clone();
+        let mut bound = Vec::from_iter(Iterator::zip(self.params.iter(), formats.iter()));
+        substitute(&mut body, &mut bound);
+        body
+    }
+}
+
+/// Replaces every [`Format::Apply`] of a name in `bound` (other than those shadowed by an inner
+/// [`Format::Dynamic`]) with the format it is bound to.
+fn substitute(format: &mut Format, bound: &mut Vec<(&Label, &Format)>) {
+    match format {
+        Format::Apply(name) => {
+            if let Some((_, arg)) = bound.iter().rev().find(|(param, _)| *param == name) {
+                *format = (*arg).clone();
+            }
+        }
+        Format::Dynamic(name, _, inner) => {
+            let shadowed = bound
+                .iter()
+                .position(|(param, _)| *param == name)
+                .map(|ix| bound.remove(ix));
+            substitute(inner, bound);
+            bound.extend(shadowed);
+        }
+        _ => {
+            for f in format.subformats_mut() {
+                substitute(f, bound);
+            }
+        }
+    }
+}
+
+/// Reference to a generic format defined within a [`FormatModule`], from which invocations of it are constructed.
+#[derive(Copy, Clone)]
+pub struct GenericRef(pub(crate) usize);
+
+impl GenericRef {
+    pub fn get_index(&self) -> usize {
+        self.0
+    }
+
+    pub fn apply(&self, formats: Vec<Format>) -> Format {
+        Format::GenericVar(self.0, formats, Vec::new(), Vec::new())
+    }
+
+    pub fn apply_args(&self, formats: Vec<Format>, args: Vec<Expr>) -> Format {
+        Format::GenericVar(self.0, formats, args, Vec::new())
+    }
+
+    pub fn apply_views(&self, formats: Vec<Format>, views: Vec<ViewExpr>) -> Format {
+        Format::GenericVar(self.0, formats, Vec::new(), views)
+    }
+
+    pub fn apply_args_views(
+        &self,
+        formats: Vec<Format>,
+        args: Vec<Expr>,
+        views: Vec<ViewExpr>,
+    ) -> Format {
+        Format::GenericVar(self.0, formats, args, views)
+    }
+}
+
+/// Returns a copy of `module` and `format` in which every invocation of a generic format has been replaced by an
+/// invocation of a plain format defined for its instantiation (borrowing both as-is if `module` has no generic
+/// formats).
+///
+/// Each distinct instantiation is defined only once, named after the generic format and its format arguments
+/// (e.g. `list.byte`).
+pub fn monomorphize<'a>(
+    module: &'a FormatModule,
+    format: &'a Format,
+) -> (Cow<'a, FormatModule>, Cow<'a, Format>) {
+    if module.generics().is_empty() {
+        return (Cow::Borrowed(module), Cow::Borrowed(format));
+    }
+    let mut mono = Monomorphizer {
+        module: module.clone(),
+        instances: HashMap::new(),
+    };
+    for level in 0..module.formats.len() {
+        let mut body = mono.module.formats[level].clone();
+        mono.rewrite(&mut body);
+        mono.module.formats[level] = body;
+    }
+    let mut format = format.clone();
+    mono.rewrite(&mut format);
+    (Cow::Owned(mono.module), Cow::Owned(format))
+}
+
+struct Monomorphizer {
+    module: FormatModule,
+    /// Level of the format defined for each instantiation so far
+    instances: HashMap<(usize, Vec<Format>), usize>,
+}
+
+impl Monomorphizer {
+    /// Replaces every `GenericVar` within `format` with an `ItemVar` invoking its instantiation.
+    fn rewrite(&mut self, format: &mut Format) {
+        for f in format.subformats_mut() {
+            self.rewrite(f);
+        }
+        if let Format::GenericVar(index, formats, args, views) = format {
+            let level = self.instance(*index, std::mem::take(formats));
+            *format = Format::ItemVar(level, std::mem::take(args), std::mem::take(views));
+        }
+    }
+
+    /// Returns the level of the instantiation of the generic format `index` over `formats` (which contain no
+    /// `GenericVar`), defining it if need be.
+    fn instance(&mut self, index: usize, formats: Vec<Format>) -> usize {
+        if let Some(level) = self.instances.get(&(index, formats.clone())) {
+            return *level;
+        }
+        let generic = self.module.get_generic(index).clone();
+        // NOTE - generic formats can only invoke those defined before them, so this recursion is bounded
+        let mut body = generic.instantiate(&formats);
+        self.rewrite(&mut body);
+        let name = self.instance_name(&generic.name, &formats);
+        let level = self
+            .module
+            .define_format_args_views(name, generic.args, generic.views, body)
+            .get_level();
+        self.instances.insert((index, formats), level);
+        level
+    }
+
+    fn instance_name(&self, name: &Label, formats: &[Format]) -> Label {
+        let describe = |f: &Format| match f {
+            Format::ItemVar(level, ..) => self.module.get_name(*level).to_string(),
+            Format::Byte(bs) if bs.is_full() => String::from("byte"),
+            _ => String::from("anon"),
+        };
+        let args = formats.iter().map(describe).collect::<Vec<_>>().join(".");
+        let base = format!("{name}.{args}");
+        let mut candidate = base.clone();
+        let mut n = 1;
+        while self.module.find_format(&candidate).is_some() {
+            n += 1;
+            candidate = format!("{base}.{n}");
+        }
+        Label::from(candidate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::*;
+
+    #[test]
+    fn test_substitute_shadowed() {
+        let generic = GenericFormat {
+            name: Label::Borrowed("pair"),
+            params: vec![Label::Borrowed("x")],
+            args: Vec::new(),
+            views: Vec::new(),
+            body: tuple([
+                Format::Apply(Label::Borrowed("x")),
+                Format::Dynamic(
+                    Label::Borrowed("x"),
+                    crate::DynFormat::Huffman(Box::new(var("lengths")), None),
+                    Box::new(Format::Apply(Label::Borrowed("x"))),
+                ),
+            ]),
+        };
+        let Format::Tuple(fs) = generic.instantiate(&[is_byte(7)]) else {
+            panic!("expected tuple")
+        };
+        assert_eq!(fs[0], is_byte(7));
+        assert!(
+            matches!(&fs[1], Format::Dynamic(_, _, inner) if matches!(**inner, Format::Apply(_)))
+        );
+    }
+
+    #[test]
+    fn test_instances_shared() {
+        let mut module = FormatModule::new();
+        let list = module.define_generic_format_args(
+            "list",
+            vec![Label::Borrowed("elem")],
+            vec![(Label::Borrowed("count"), ValueType::U8)],
+            repeat_count(var("count"), Format::Apply(Label::Borrowed("elem"))),
+        );
+        let bytes = list.apply_args(vec![ANY_BYTE], vec![Expr::U8(2)]);
+        let f = tuple([bytes.clone(), bytes]);
+        let (mono, f) = monomorphize(&module, &f);
+        let Format::Tuple(fs) = f.as_ref() else {
+            panic!("expected tuple")
+        };
+        assert_eq!(fs[0], fs[1]);
+        let Format::ItemVar(level, ..) = fs[0] else {
+            panic!("expected ItemVar, found {:?}", fs[0])
+        };
+        assert_eq!(mono.get_name(level), "list.byte");
+        assert_eq!(
+            mono.get_format_type(level),
+            &ValueType::Seq(Box::new(ValueType::U8))
+        );
+    }
+
+    #[test]
+    fn test_format_arg_uses_param() {
+        let mut module = FormatModule::new();
+        let list = module.define_generic_format_args(
+            "list",
+            vec![Label::Borrowed("elem")],
+            vec![(Label::Borrowed("count"), ValueType::U8)],
+            repeat_count(var("count"), Format::Apply(Label::Borrowed("elem"))),
+        );
+        // a square of `count` rows of `count` bytes
+        let square = list.apply_args(
+            vec![repeat_count(var("count"), ANY_BYTE)],
+            vec![Expr::U8(2)],
+        );
+        let (mono, f) = monomorphize(&module, &square);
+        let Format::ItemVar(level, ..) = f.as_ref() else {
+            panic!("expected ItemVar, found {f:?}")
+        };
+        assert_eq!(mono.get_name(*level), "list.anon");
+        assert_eq!(
+            mono.get_format_type(*level),
+            &ValueType::Seq(Box::new(ValueType::Seq(Box::new(ValueType::U8))))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "refers to more than its parameters")]
+    fn test_open_format_arg() {
+        let mut module = FormatModule::new();
+        let list = module.define_generic_format_args(
+            "list",
+            vec![Label::Borrowed("elem")],
+            vec![(Label::Borrowed("count"), ValueType::U8)],
+            repeat_count(var("count"), Format::Apply(Label::Borrowed("elem"))),
+        );
+        // `len` is bound by the invoking format, and so is not in scope within `list`
+        module.define_format_args(
+            "outer",
+            vec![(Label::Borrowed("len"), ValueType::U8)],
+            list.apply_args(vec![repeat_count(var("len"), ANY_BYTE)], vec![Expr::U8(2)]),
+        );
+    }
+}
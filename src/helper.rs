@@ -6,7 +6,7 @@ use crate::byte_set::ByteSet;
 pub use crate::marker::BaseKind;
 use crate::validation::{Condition, Severity};
 use crate::{
-    Arith, BaseType, Expr, Format, IntRel, IntoLabel, Label, OwnedRecordFormat, Pattern,
+    Arith, Expr, FixedType, Format, IntRel, IntoLabel, Label, OwnedRecordFormat, Pattern,
     RecordBuilder, StyleHint, TypeHint, UnaryOp, ValueType, ViewExpr, ViewFormat,
 };
 use crate::{Endian, bounds::Bounds};
@@ -745,6 +745,21 @@ pub fn as_char(x: Expr) -> Expr {
     Expr::AsChar(Box::new(x))
 }
 
+/// Reinterprets the bits of a `U32`-typed expression as an `F32`.
+pub fn f32_from_bits(x: Expr) -> Expr {
+    Expr::F32FromBits(Box::new(x))
+}
+
+/// Reinterprets the bits of a `U64`-typed expression as an `F64`.
+pub fn f64_from_bits(x: Expr) -> Expr {
+    Expr::F64FromBits(Box::new(x))
+}
+
+/// Reinterprets the bits of an unsigned expression, of the same width as `fx`, as a fixed-point value.
+pub fn fixed_from_bits(fx: FixedType, x: Expr) -> Expr {
+    Expr::FixedFromBits(fx, Box::new(x))
+}
+
 pub fn pred(x: Expr) -> Expr {
     Expr::Unary(UnaryOp::IntPred, Box::new(x))
 }
@@ -891,6 +906,30 @@ pub fn dup(count: Expr, expr: Expr) -> Expr {
     Expr::Dup(Box::new(count), Box::new(expr))
 }
 
+/// Helper-function for [`Expr::Filter`].
+///
+/// `f` must be a lambda from the element-type of `seq` to `Bool`.
+pub fn seq_filter(f: Expr, seq: Expr) -> Expr {
+    Expr::Filter(Box::new(f), Box::new(seq))
+}
+
+/// Helper-function for [`Expr::Zip`].
+pub fn seq_zip(lhs: Expr, rhs: Expr) -> Expr {
+    Expr::Zip(Box::new(lhs), Box::new(rhs))
+}
+
+/// Helper-function for [`Expr::Reverse`].
+pub fn seq_reverse(seq: Expr) -> Expr {
+    Expr::Reverse(Box::new(seq))
+}
+
+/// Helper-function for [`Expr::IsSortedBy`].
+///
+/// `f` must be a lambda over a pair `(prev, next)` of adjacent elements of `seq`, returning `Bool`.
+pub fn is_sorted_by(f: Expr, seq: Expr) -> Expr {
+    Expr::IsSortedBy(Box::new(f), Box::new(seq))
+}
+
 /// Composed `Format::Where` and `Expr::Lambda` taking a raw format, an arbitrary name for the lambda expression head, and the lambda body as an Expr.
 pub fn where_lambda(raw: Format, name: impl IntoLabel, body: Expr) -> Format {
     Format::Where(Box::new(raw), Condition::from_lambda(lambda(name, body)))
@@ -1125,6 +1164,21 @@ pub fn pat_some(pat: Pattern) -> Pattern {
     Pattern::Option(Some(Box::new(pat)))
 }
 
+/// Helper for constructing a partial record-pattern `{ label: pat, .. }` within the Pattern model-language.
+pub fn pat_record<Name: IntoLabel>(fields: impl IntoIterator<Item = (Name, Pattern)>) -> Pattern {
+    Pattern::record(fields)
+}
+
+/// Helper for constructing an or-pattern `pat0 | pat1 | ...` within the Pattern model-language.
+pub fn pat_or(alts: impl IntoIterator<Item = Pattern>) -> Pattern {
+    Pattern::Or(alts.into_iter().collect())
+}
+
+/// Helper for constructing a match-arm pattern `pat if cond` within the Pattern model-language.
+pub fn pat_guard(pat: Pattern, cond: Expr) -> Pattern {
+    pat.guarded(cond)
+}
+
 /// Helper for constructing `fmt -> Option::Some` within the Format model-language.
 pub fn fmt_some(f: Format) -> Format {
     Format::LiftedOption(Some(Box::new(f)))
@@ -1469,12 +1523,17 @@ pub fn seq_any<F>(f: F, seq: Expr) -> Expr
 where
     F: FnOnce(Expr) -> Expr,
 {
-    left_fold(
-        lambda_tuple(["any", "x"], or(var("any"), f(var("x")))),
-        Expr::Bool(false),
-        ValueType::Base(BaseType::Bool),
-        seq,
-    )
+    Expr::Any(Box::new(lambda("x", f(var("x")))), Box::new(seq))
+}
+
+/// Given a sequence `seq` of type `Seq(T)`, return an expression of type `Bool`
+/// that is `true` if every element of `seq` yields `true` when `f` is called over it
+/// (including when the sequence is empty), and `false` otherwise.
+pub fn seq_all<F>(f: F, seq: Expr) -> Expr
+where
+    F: FnOnce(Expr) -> Expr,
+{
+    Expr::All(Box::new(lambda("x", f(var("x")))), Box::new(seq))
 }
 
 /// Analogue of [`std::option::Option::map_or`] expressed within the Expr model.
@@ -1827,31 +1886,98 @@ pub mod base {
         u64le, U64LE, 8, U64Le;
     }
 
-    // TODO[epic=signed-parse] - add stylehint support for signed-parse operations
-    /// Parses a u8 value and performs a bitwise cast to i8.
-    pub fn i8() -> Format {
-        map_numeric(u8(), |v| num::cast_bitwise(MachineRep::I8, v))
+    macro_rules! signed {
+        ( $( $fname:ident, $kind_endian:ident, $raw:ident, $rep:ident );* $(;)? ) => {
+            $(
+                #[doc = concat!("Stand-in for `BaseKind::", stringify!($kind_endian), "`")]
+                #[doc = ""]
+                #[doc = concat!("Parses a raw value as in [`", stringify!($raw), "`] and performs a bitwise cast to ", stringify!($rep), ".")]
+                pub fn $fname() -> Format {
+                    Format::Hint(
+                        StyleHint::Common(CommonOp::EndianParse(BaseKind::$kind_endian)),
+                        Box::new(map_numeric($raw(), |v| num::cast_bitwise(MachineRep::$rep, v))),
+                    )
+                }
+            )*
+        };
     }
 
-    // TODO[epic=signed-parse] - add stylehint support for signed-parse operations
-    /// Parses a big-endian u16 value and performs a bitwise cast to i16.
-    pub fn i16be() -> Format {
-        map_numeric(u16be(), |v| num::cast_bitwise(MachineRep::I16, v))
+    signed! {
+        i8, I8, u8, I8;
+        i16be, I16BE, u16be, I16;
+        i16le, I16LE, u16le, I16;
+        i32be, I32BE, u32be, I32;
+        i32le, I32LE, u32le, I32;
+        i64be, I64BE, u64be, I64;
+        i64le, I64LE, u64le, I64;
+    }
+
+    macro_rules! float {
+        ( $( $fname:ident, $raw:ident, $op:ident );* $(;)? ) => {
+            $(
+                #[doc = concat!("Parses a raw value as in [`", stringify!($raw), "`] and reinterprets its bits as an IEEE-754 float.")]
+                pub fn $fname() -> Format {
+                    map($raw(), lambda("bits", Expr::$op(Box::new(var("bits")))))
+                }
+            )*
+        };
     }
 
-    // TODO[epic=signed-parse] - add stylehint support for signed-parse operations
-    /// Parses a big-endian u32 value and performs a bitwise cast to i32.
-    pub fn i32be() -> Format {
-        map_numeric(u32be(), |v| num::cast_bitwise(MachineRep::I32, v))
+    float! {
+        f32be, u32be, F32FromBits;
+        f32le, u32le, F32FromBits;
+        f64be, u64be, F64FromBits;
+        f64le, u64le, F64FromBits;
     }
 
-    // TODO[epic=signed-parse] - add stylehint support for signed-parse operations
-    /// Parses a big-endian u64 value and performs a bitwise cast to i64.
-    pub fn i64be() -> Format {
-        map_numeric(u64be(), |v| num::cast_bitwise(MachineRep::I64, v))
+    /// Parses a big-endian fixed-point value of the given type.
+    pub fn fixed_be(fx: FixedType) -> Format {
+        fixed_endian(fx, Endian::Be)
+    }
+
+    /// Parses a little-endian fixed-point value of the given type.
+    pub fn fixed_le(fx: FixedType) -> Format {
+        fixed_endian(fx, Endian::Le)
+    }
+
+    fn fixed_endian(fx: FixedType, endian: Endian) -> Format {
+        let raw = match fx.width() {
+            8 => BaseKind::U8,
+            16 => BaseKind::U16Ext(endian),
+            32 => BaseKind::U32Ext(endian),
+            64 => BaseKind::U64Ext(endian),
+            _ => unreachable!("bad fixed-point width {}", fx.width()),
+        };
+        map(
+            base_kind(raw),
+            lambda("bits", fixed_from_bits(fx, var("bits"))),
+        )
+    }
+
+    /// Returns the canonical parse-directive for the given [`BaseKind`] and endianness.
+    pub fn base_kind(kind: BaseKind<Endian>) -> Format {
+        match kind {
+            BaseKind::U8 => u8(),
+            BaseKind::U16BE => u16be(),
+            BaseKind::U16LE => u16le(),
+            BaseKind::U32BE => u32be(),
+            BaseKind::U32LE => u32le(),
+            BaseKind::U64BE => u64be(),
+            BaseKind::U64LE => u64le(),
+            BaseKind::I8 => i8(),
+            BaseKind::I16BE => i16be(),
+            BaseKind::I16LE => i16le(),
+            BaseKind::I32BE => i32be(),
+            BaseKind::I32LE => i32le(),
+            BaseKind::I64BE => i64be(),
+            BaseKind::I64LE => i64le(),
+        }
     }
 }
-pub use base::{bit, i8, i16be, i32be, i64be, u8, u16be, u16le, u32be, u32le, u64be, u64le};
+pub use base::{
+    bit, f32be, f32le, f64be, f64le, fixed_be, fixed_le, i8, i16be, i16le, i32be, i32le, i64be,
+    i64le, u8, u16be, u16le, u32be, u32le, u64be, u64le,
+};
 
 pub mod ascii {
     use super::{mk_ascii_string, *};
@@ -2090,6 +2216,17 @@ pub fn permit(format: Format, if_error: Expr) -> Format {
     Format::Permit(Box::new(format), Box::new(if_error))
 }
 
+/// Helper for [`Format::Resync`], which recovers from errors in the interior format by skipping ahead to the
+/// next occurrence of `pattern`, or to the end of the current slice if `pattern` is empty or never occurs.
+///
+/// The resulting value is `ok(value)` if `format` was parsed successfully, and `skipped({ error, data })` otherwise.
+///
+/// For length-delimited elements, use an empty `pattern` within the [`slice`] of the element, so that
+/// recovery skips to the end of the element.
+pub fn resync(format: Format, pattern: impl IntoIterator<Item = ByteSet>) -> Format {
+    Format::Resync(Box::new(format), pattern.into_iter().collect())
+}
+
 /// Helper for [`Format::Enforce`], which upgrades warnings to errors in the interior format.
 #[cfg(feature = "format_enforce")]
 pub fn enforce(format: Format) -> Format {
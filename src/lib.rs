@@ -12,6 +12,7 @@ use serde::Serialize;
 
 use crate::bounds::Bounds;
 use crate::byte_set::ByteSet;
+use crate::native::NativeFormat;
 use crate::numeric::core::{Expr as NumExpr, VOID};
 use crate::read::ReadCtxt;
 
@@ -20,15 +21,24 @@ pub mod bounds;
 pub mod byte_set;
 pub mod codegen;
 pub mod decoder;
+pub mod encoder;
 
 pub mod dep_ref;
 pub use dep_ref::DepFormat;
 
 pub mod error;
+pub mod function;
+pub use function::{ExprFunction, FunctionRef};
+pub mod generate;
+pub mod generic;
+pub use generic::{GenericFormat, GenericRef};
 pub mod helper;
+pub mod limits;
 pub mod loc_decoder;
 pub mod marker;
+pub mod match_check;
 pub use marker::{BaseKind, Endian};
+pub mod native;
 pub mod numeric;
 pub mod output;
 pub mod parser;
@@ -63,7 +73,7 @@ pub enum ValueKind<T> {
 }
 
 pub(crate) mod valuetype;
-pub use valuetype::{BaseType, TypeHint, ValueType};
+pub use valuetype::{BaseType, FixedType, SignedIntType, TypeHint, ValueType};
 
 fn mk_value_expr(vt: &ValueType) -> Option<Expr> {
     match vt {
@@ -76,6 +86,12 @@ fn mk_value_expr(vt: &ValueType) -> Option<Expr> {
             BaseType::U32 => Expr::U32(0),
             BaseType::U64 => Expr::U64(0),
             BaseType::Char => Expr::AsChar(Box::new(Expr::U32(0))),
+            BaseType::F32 => Expr::F32FromBits(Box::new(Expr::U32(0))),
+            BaseType::F64 => Expr::F64FromBits(Box::new(Expr::U64(0))),
+            BaseType::Fixed(fx) => Expr::FixedFromBits(
+                *fx,
+                Box::new(mk_value_expr(&ValueType::Base(fx.raw_base_type()))?),
+            ),
         }),
         ValueType::NumericHole => Some(Expr::Numeric(Box::new(NumExpr::Const(
             numeric::TypedConst::from_u8(0),
@@ -173,6 +189,12 @@ pub enum Expr {
     AsU32(Box<Expr>),
     AsU64(Box<Expr>),
     AsChar(Box<Expr>),
+    /// F32FromBits :: U32 -> F32 (bitwise reinterpretation as an IEEE-754 float)
+    F32FromBits(Box<Expr>),
+    /// F64FromBits :: U64 -> F64 (bitwise reinterpretation as an IEEE-754 float)
+    F64FromBits(Box<Expr>),
+    /// FixedFromBits :: U{width} -> Fixed (bitwise reinterpretation as the given fixed-point type)
+    FixedFromBits(FixedType, Box<Expr>),
 
     U16Be(Box<Expr>),
     U16Le(Box<Expr>),
@@ -196,6 +218,18 @@ pub enum Expr {
     FlatMapList(Box<Expr>, TypeHint, Box<Expr>),
     /// FindByKey :: (is-sorted) -> (T -> K) -> K -> \[T\] ->  Option(T) (is-sorted indicates whether the values are sorted w.r.t. the keying function in question; only simple types can be keys for now)
     FindByKey(bool, Box<Expr>, Box<Expr>, Box<Expr>),
+    /// Filter :: (T -> Bool) -> \[T\] -> \[T\] (keeps the elements satisfying the predicate, in order)
+    Filter(Box<Expr>, Box<Expr>),
+    /// Zip :: \[T\] -> \[U\] -> \[(T, U)\] (truncated to the length of the shorter sequence)
+    Zip(Box<Expr>, Box<Expr>),
+    /// Reverse :: \[T\] -> \[T\]
+    Reverse(Box<Expr>),
+    /// Any :: (T -> Bool) -> \[T\] -> Bool (false for the empty sequence)
+    Any(Box<Expr>, Box<Expr>),
+    /// All :: (T -> Bool) -> \[T\] -> Bool (true for the empty sequence)
+    All(Box<Expr>, Box<Expr>),
+    /// IsSortedBy :: ((T, T) -> Bool) -> \[T\] -> Bool (conjunction of the predicate over each pair of adjacent elements)
+    IsSortedBy(Box<Expr>, Box<Expr>),
 
     /// EnumFromTo :: (Num, Num) -> \[Num\]
     EnumFromTo(Box<Expr>, Box<Expr>),
@@ -208,6 +242,9 @@ pub enum Expr {
     LiftOption(Option<Box<Expr>>),
     /// [T] -> [T] -> [T] (append operation)
     Append(Box<Expr>, Box<Expr>),
+
+    /// Call :: (function index) -> TypeRep Ret -> (args...) -> Ret (call of a function defined in the `FormatModule`)
+    Call(usize, TypeHint, Vec<Expr>),
 }
 
 impl Expr {
@@ -232,7 +269,7 @@ impl Expr {
     // FIXME: is this still an inherent method, or should we have a UD -> TC phase and use get_type_info instead?
     fn infer_type(&self, scope: &TypeScope<'_, ValueType>) -> AResult<ValueType> {
         match self {
-            Expr::Var(name) => match scope.get_type_by_name(name) {
+            Expr::Var(name) => match scope.get_type_by_name(name)? {
                 ValueKind::Value(t) => Ok(t.clone()),
                 ValueKind::View => Err(anyhow!(
                     "expected ValueKind::Value, found ValueKind::View for var {name}"
@@ -363,6 +400,22 @@ impl Expr {
                 t if t.is_numeric() => Ok(ValueType::Base(BaseType::Char)),
                 x => Err(anyhow!("unsound type cast AsChar(_ : {x:?})")),
             },
+            Expr::F32FromBits(x) => match x.infer_type(scope)? {
+                ValueType::U32 => Ok(ValueType::F32),
+                x => Err(anyhow!("unsound bitwise cast F32FromBits(_ : {x:?})")),
+            },
+            Expr::F64FromBits(x) => match x.infer_type(scope)? {
+                ValueType::U64 => Ok(ValueType::F64),
+                x => Err(anyhow!("unsound bitwise cast F64FromBits(_ : {x:?})")),
+            },
+            Expr::FixedFromBits(fx, x) => match x.infer_type(scope)? {
+                ValueType::Base(b) if b == fx.raw_base_type() => {
+                    Ok(ValueType::Base(BaseType::Fixed(*fx)))
+                }
+                x => Err(anyhow!(
+                    "unsound bitwise cast FixedFromBits({fx}, _ : {x:?})"
+                )),
+            },
             Expr::U16Be(bytes) => {
                 let _t = bytes.infer_type(scope)?;
                 match _t.try_as_tuple_type() {
@@ -554,6 +607,56 @@ impl Expr {
                 },
                 other => Err(anyhow!("FindByKey: Expected Lambda, found {other:?}")),
             },
+            Expr::Filter(expr, seq) | Expr::Any(expr, seq) | Expr::All(expr, seq) => {
+                let op = match self {
+                    Expr::Filter(..) => "Filter",
+                    Expr::Any(..) => "Any",
+                    _ => "All",
+                };
+                match expr.as_ref() {
+                    Expr::Lambda(name, expr) => match seq.infer_type(scope)? {
+                        ValueType::Seq(t) => {
+                            let mut child_scope = TypeScope::child(scope);
+                            child_scope.push(name.clone(), t.as_ref().clone());
+                            match expr.infer_type(&child_scope)? {
+                                ValueType::Base(BaseType::Bool) => match self {
+                                    Expr::Filter(..) => Ok(ValueType::Seq(t)),
+                                    _ => Ok(ValueType::Base(BaseType::Bool)),
+                                },
+                                other => Err(anyhow!("{op}: expected Bool, found {other:?}")),
+                            }
+                        }
+                        other => Err(anyhow!("{op}: expected Seq, found {other:?}")),
+                    },
+                    other => Err(anyhow!("{op}: expected Lambda, found {other:?}")),
+                }
+            }
+            Expr::Zip(lhs, rhs) => match (lhs.infer_type(scope)?, rhs.infer_type(scope)?) {
+                (ValueType::Seq(t1), ValueType::Seq(t2)) => {
+                    Ok(ValueType::Seq(Box::new(ValueType::Tuple(vec![*t1, *t2]))))
+                }
+                (lhs_type, rhs_type) => Err(anyhow!(
+                    "Zip: lhs and rhs must be Seq: {lhs_type:?}, {rhs_type:?} !~ Seq(_)"
+                )),
+            },
+            Expr::Reverse(seq) => match seq.infer_type(scope)? {
+                ValueType::Seq(t) => Ok(ValueType::Seq(t)),
+                other => Err(anyhow!("Reverse: expected Seq, found {other:?}")),
+            },
+            Expr::IsSortedBy(expr, seq) => match expr.as_ref() {
+                Expr::Lambda(name, expr) => match seq.infer_type(scope)? {
+                    ValueType::Seq(t) => {
+                        let mut child_scope = TypeScope::child(scope);
+                        child_scope.push(name.clone(), ValueType::Tuple(vec![(*t).clone(), *t]));
+                        match expr.infer_type(&child_scope)? {
+                            ValueType::Base(BaseType::Bool) => Ok(ValueType::Base(BaseType::Bool)),
+                            other => Err(anyhow!("IsSortedBy: expected Bool, found {other:?}")),
+                        }
+                    }
+                    other => Err(anyhow!("IsSortedBy: expected Seq, found {other:?}")),
+                },
+                other => Err(anyhow!("IsSortedBy: expected Lambda, found {other:?}")),
+            },
             Expr::FlatMapList(expr, ret_type, seq) => match expr.as_ref() {
                 Expr::Lambda(name, expr) => match seq.infer_type(scope)? {
                     ValueType::Seq(t) => {
@@ -622,6 +725,13 @@ impl Expr {
                     )),
                 }
             }
+            // NOTE - the arguments are checked against the parameters of the function by the typechecker, which can see the module
+            Expr::Call(_, ret, args) => {
+                for arg in args {
+                    arg.infer_type(scope)?;
+                }
+                Ok(ret.as_ref().clone())
+            }
         }
     }
 
@@ -664,18 +774,23 @@ impl Expr {
             Expr::Seq(elts) => elts.iter().any(|x| x.is_shadowed_by(name)),
             Expr::Match(head, arms) => {
                 head.is_shadowed_by(name)
-                    || arms
-                        .iter()
-                        .any(|(pat, x)| !pat.shadows(name) && x.is_shadowed_by(name))
+                    || arms.iter().any(|(pat, x)| {
+                        (!pat.shadows(name) && x.is_shadowed_by(name)) || pat.guard_mentions(name)
+                    })
             }
             Expr::Destructure(head, pattern, body) => {
-                head.is_shadowed_by(name) || (!pattern.shadows(name) && body.is_shadowed_by(name))
+                head.is_shadowed_by(name)
+                    || (!pattern.shadows(name) && body.is_shadowed_by(name))
+                    || pattern.guard_mentions(name)
             }
             Expr::AsU8(x)
             | Expr::AsU16(x)
             | Expr::AsU32(x)
             | Expr::AsU64(x)
             | Expr::AsChar(x)
+            | Expr::F32FromBits(x)
+            | Expr::F64FromBits(x)
+            | Expr::FixedFromBits(_, x)
             | Expr::U16Be(x)
             | Expr::U16Le(x)
             | Expr::U32Be(x)
@@ -697,7 +812,14 @@ impl Expr {
             Expr::FindByKey(_, k, v, s) => {
                 k.is_shadowed_by(name) || v.is_shadowed_by(name) || s.is_shadowed_by(name)
             }
+            Expr::Filter(f, x)
+            | Expr::Any(f, x)
+            | Expr::All(f, x)
+            | Expr::IsSortedBy(f, x)
+            | Expr::Zip(f, x) => f.is_shadowed_by(name) || x.is_shadowed_by(name),
+            Expr::Reverse(x) => x.is_shadowed_by(name),
             Expr::LiftOption(opt_x) => opt_x.as_ref().is_some_and(|x| x.is_shadowed_by(name)),
+            Expr::Call(_, _, args) => args.iter().any(|x| x.is_shadowed_by(name)),
         }
     }
 
@@ -804,7 +926,7 @@ impl ViewExpr {
 
     pub fn check_type(&self, scope: &TypeScope<'_, ValueType>) -> AResult<()> {
         match self {
-            ViewExpr::Var(ident) => match scope.get_type_by_name(ident) {
+            ViewExpr::Var(ident) => match scope.get_type_by_name(ident)? {
                 ValueKind::View => Ok(()),
                 ValueKind::Value(_t) => Err(anyhow!("expected View, found Value({_t:?}")),
                 ValueKind::Format(_t) => Err(anyhow!("expected View, found FOrmat({_t:?}")),
@@ -824,7 +946,7 @@ impl ViewExpr {
 
     pub fn check_type_ext(&self, scope: &TypeScope<'_, alt::ValueTypeExt>) -> AResult<()> {
         match self {
-            ViewExpr::Var(ident) => match scope.get_type_by_name(ident) {
+            ViewExpr::Var(ident) => match scope.get_type_by_name(ident)? {
                 ValueKind::View => Ok(()),
                 ValueKind::Value(_t) => Err(anyhow!("expected View, found Value({_t:?}")),
                 ValueKind::Format(_t) => Err(anyhow!("expected View, found FOrmat({_t:?}")),
@@ -848,7 +970,7 @@ impl ViewExpr {
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 #[serde(tag = "tag", content = "args")]
 pub enum CommonOp {
-    // FIXME[epic=signed-parse] - add in expressivity for signed-integer parsing as commonop
+    /// Machine-integer parse of the given kind (signed or unsigned) and endianness
     EndianParse(BaseKind<Endian>),
 }
 
@@ -903,13 +1025,16 @@ impl FormatRef {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct FormatModule {
     names: Vec<Label>,
     args: Vec<Vec<(Label, ValueType)>>,
     views: IxHeap<Vec<Label>>,
     formats: Vec<Format>,
     format_types: Vec<ValueType>,
+    natives: Vec<NativeFormat>,
+    functions: Vec<ExprFunction>,
+    generics: Vec<GenericFormat>,
 }
 
 impl FormatModule {
@@ -920,6 +1045,9 @@ impl FormatModule {
             views: IxHeap::new(),
             formats: Vec::new(),
             format_types: Vec::new(),
+            natives: Vec::new(),
+            functions: Vec::new(),
+            generics: Vec::new(),
         }
     }
 
@@ -972,10 +1100,197 @@ impl FormatModule {
         FormatRef(level)
     }
 
+    /// Defines a format over the format parameters `params` (see [`generic`]), each of which is referred to
+    /// within `format` through [`Format::Apply`].
+    pub fn define_generic_format(
+        &mut self,
+        name: impl IntoLabel,
+        params: Vec<Label>,
+        format: Format,
+    ) -> GenericRef {
+        self.define_generic_format_args_views(name, params, vec![], vec![], format)
+    }
+
+    pub fn define_generic_format_args(
+        &mut self,
+        name: impl IntoLabel,
+        params: Vec<Label>,
+        args: Vec<(Label, ValueType)>,
+        format: Format,
+    ) -> GenericRef {
+        self.define_generic_format_args_views(name, params, args, vec![], format)
+    }
+
+    /// Defines a format over the format parameters `params`, as well as the value and view parameters `args`
+    /// and `views`.
+    ///
+    /// As the type of `format` depends on the format arguments it is instantiated with, it is only inferred
+    /// (and checked) at each invocation.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `format` invokes a generic format not defined before it.
+    pub fn define_generic_format_args_views(
+        &mut self,
+        name: impl IntoLabel,
+        params: Vec<Label>,
+        args: Vec<(Label, ValueType)>,
+        views: Vec<Label>,
+        format: Format,
+    ) -> GenericRef {
+        let name = name.into();
+        let index = self.generics.len();
+        let mut stack = vec![&format];
+        while let Some(f) = stack.pop() {
+            if let Format::GenericVar(other, ..) = f {
+                assert!(
+                    *other < index,
+                    "generic format `{name}` invokes generic format #{other}, which is not defined before it"
+                );
+            }
+            stack.extend(f.subformats());
+        }
+        self.generics.push(GenericFormat {
+            name,
+            params,
+            args,
+            views,
+            body: format,
+        });
+        GenericRef(index)
+    }
+
+    /// Defines a format implemented outside of the `Format` language (see [`native`]), which takes the
+    /// arguments `args` and produces values of type `native.value_type`.
+    pub fn define_native_format(
+        &mut self,
+        name: impl IntoLabel,
+        args: Vec<(Label, ValueType)>,
+        native: NativeFormat,
+    ) -> FormatRef {
+        let arg_exprs = args
+            .iter()
+            .map(|(arg_name, _)| Expr::Var(arg_name.clone()))
+            .collect();
+        let index = self.natives.len();
+        self.natives.push(native);
+        self.define_format_args(name, args, Format::Native(index, arg_exprs))
+    }
+
+    /// Defines a (non-recursive) function named `name`, evaluating `body` over the parameters `params`, to be
+    /// called through [`FunctionRef::call`].
+    pub fn define_function(
+        &mut self,
+        name: impl IntoLabel,
+        params: Vec<(Label, ValueType)>,
+        ret: ValueType,
+        body: Expr,
+    ) -> FunctionRef {
+        let func = self.declare_function(name, params, ret);
+        self.define_function_body(&func, body);
+        func
+    }
+
+    /// Declares a function named `name` over the parameters `params`, whose body is to be provided later by
+    /// [`define_function_body`](Self::define_function_body), so that it can call itself (or functions declared
+    /// after it) recursively.
+    pub fn declare_function(
+        &mut self,
+        name: impl IntoLabel,
+        params: Vec<(Label, ValueType)>,
+        ret: ValueType,
+    ) -> FunctionRef {
+        let name = name.into();
+        if self.find_function(&name).is_some() {
+            panic!("function `{name}` is already declared");
+        }
+        let index = self.functions.len();
+        self.functions.push(ExprFunction {
+            name,
+            params,
+            ret: ret.clone(),
+            body: None,
+        });
+        FunctionRef::new(index, ret)
+    }
+
+    /// Defines the body of the previously-declared function `func`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the function already has a body, if `body` is ill-typed or of a type other than the declared
+    /// return type, or if recursion through the function cannot be shown to terminate (see [`function`]).
+    pub fn define_function_body(&mut self, func: &FunctionRef, body: Expr) {
+        let index = func.get_index();
+        let function = &self.functions[index];
+        if function.body.is_some() {
+            panic!("function `{}` is already defined", function.name);
+        }
+        let mut scope = TypeScope::new();
+        for (param_name, param_type) in &function.params {
+            scope.push(param_name.clone(), param_type.clone());
+        }
+        match body
+            .infer_type(&scope)
+            .and_then(|t| Ok(function.ret.unify(&t)?))
+        {
+            Ok(_) => {}
+            Err(msg) => panic!("function `{}`: {msg}", function.name),
+        }
+        self.functions[index].body = Some(body);
+        if let Err(err) = function::check_termination(&self.functions) {
+            panic!("{err}");
+        }
+    }
+
+    /// Returns a reference to the function declared under the name `name`, if there is one.
+    pub fn find_function(&self, name: &str) -> Option<FunctionRef> {
+        self.functions
+            .iter()
+            .position(|func| func.name.as_ref() == name)
+            .map(|index| FunctionRef::new(index, self.functions[index].ret.clone()))
+    }
+
+    pub fn get_function(&self, index: usize) -> &ExprFunction {
+        &self.functions[index]
+    }
+
+    pub fn functions(&self) -> &[ExprFunction] {
+        &self.functions
+    }
+
+    pub fn get_native(&self, index: usize) -> &NativeFormat {
+        &self.natives[index]
+    }
+
+    pub fn get_generic(&self, index: usize) -> &GenericFormat {
+        &self.generics[index]
+    }
+
+    pub fn generics(&self) -> &[GenericFormat] {
+        &self.generics
+    }
+
     pub fn get_name(&self, level: usize) -> &str {
         &self.names[level]
     }
 
+    /// Returns a reference to the first format defined under the name `name`, if there is one.
+    pub fn find_format(&self, name: &str) -> Option<FormatRef> {
+        self.names
+            .iter()
+            .position(|other| other.as_ref() == name)
+            .map(FormatRef)
+    }
+
+    /// Returns a reference to the first generic format defined under the name `name`, if there is one.
+    pub fn find_generic(&self, name: &str) -> Option<GenericRef> {
+        self.generics
+            .iter()
+            .position(|other| other.name.as_ref() == name)
+            .map(GenericRef)
+    }
+
     /// Iterates through every format defined in this module, constructing an invocation for each
     /// with an appropriate array of arguments with the expected `ValueType`s.
     pub fn iter_formats(&self) -> impl Iterator<Item = (usize, Format)> + '_ {
@@ -1041,6 +1356,51 @@ impl FormatModule {
                 }
                 Ok(self.get_format_type(*level).clone())
             }
+            Format::GenericVar(index, formats, arg_exprs, arg_views) => {
+                let generic = self.get_generic(*index);
+                if generic.params.len() != formats.len() {
+                    return Err(anyhow!(
+                        "Expected {} format arguments, found {}",
+                        generic.params.len(),
+                        formats.len()
+                    ));
+                }
+                if generic.args.len() != arg_exprs.len() {
+                    return Err(anyhow!(
+                        "Expected {} arguments, found {}",
+                        generic.args.len(),
+                        arg_exprs.len()
+                    ));
+                }
+                if generic.views.len() != arg_views.len() {
+                    return Err(anyhow!(
+                        "Expected {} views, found {}",
+                        generic.views.len(),
+                        arg_views.len(),
+                    ));
+                }
+                let mut child_scope = TypeScope::new();
+                for ((name, arg_type), expr) in Iterator::zip(generic.args.iter(), arg_exprs.iter())
+                {
+                    let t = expr.infer_type(scope)?;
+                    let _t = arg_type.unify(&t)?;
+                    child_scope.push(name.clone(), arg_type.clone());
+                }
+                for name in generic.views.iter() {
+                    child_scope.push_view(name.clone());
+                }
+                // NOTE - format arguments are substituted into the body of the generic format, where only its own
+                // parameters are in scope, so they must type-check against those alone
+                for (ix, f) in formats.iter().enumerate() {
+                    self.infer_format_type(&child_scope, f).map_err(|e| {
+                        anyhow!(
+                            "format argument #{ix} of generic format `{}` refers to more than its parameters: {e}",
+                            generic.name
+                        )
+                    })?;
+                }
+                self.infer_format_type(&child_scope, &generic.instantiate(formats))
+            }
             Format::DecodeBytes(bytes, f) => {
                 let bytes_type = bytes.infer_type(scope)?;
                 match bytes_type {
@@ -1058,6 +1418,12 @@ impl FormatModule {
             }
             Format::Fail => Ok(ValueType::Empty),
             Format::SkipRemainder | Format::EndOfInput => Ok(ValueType::Tuple(vec![])),
+            Format::Native(index, args) => {
+                for arg in args {
+                    arg.infer_type(scope)?;
+                }
+                Ok(self.get_native(*index).value_type.clone())
+            }
             Format::Align(_n) => Ok(ValueType::Tuple(vec![])),
             Format::Byte(_bs) => Ok(ValueType::U8),
             Format::Variant(label, f) => Ok(ValueType::Union(BTreeMap::from([(
@@ -1256,6 +1622,7 @@ impl FormatModule {
                 let t = self.infer_format_type(scope, f)?;
                 Ok(t0.unify(&t)?)
             }
+            Format::Resync(f, _) => Ok(ValueType::resync(self.infer_format_type(scope, f)?)),
             Format::Match(head, branches) => {
                 if branches.is_empty() {
                     return Err(anyhow!("infer_format_type: empty Match"));
@@ -1295,7 +1662,7 @@ impl FormatModule {
                 child_scope.push_format(name.clone(), ValueType::U16);
                 self.infer_format_type(&child_scope, format)
             }
-            Format::Apply(name) => match scope.get_type_by_name(name) {
+            Format::Apply(name) => match scope.get_type_by_name(name)? {
                 ValueKind::Format(t) => Ok(t.clone()),
                 ValueKind::View => Err(anyhow!("Apply: expected format, found View")),
                 ValueKind::Value(t) => Err(anyhow!("Apply: expected format, found {t:?}")),
@@ -1342,9 +1709,7 @@ impl FormatModule {
                         }
                     }
                     // NOTE[epic=view-format] - in the current base-model design and implementation, ReadArray captures a `Seq<K>` where K is informed by `kind`
-                    Ok(ValueType::Seq(Box::new(ValueType::Base(BaseType::from(
-                        *kind,
-                    )))))
+                    Ok(ValueType::Seq(Box::new(ValueType::from(*kind))))
                 }
                 ViewFormat::ReifyView => {
                     view.check_type(scope)?;
@@ -1749,6 +2114,7 @@ impl<'a> MatchTreeStep<'a> {
             }
             TypedFormat::Phantom(..) => Self::accept(),
             TypedFormat::SkipRemainder => Self::accept(),
+            TypedFormat::Native(..) => Self::accept(),
             TypedFormat::Byte(bs) => Self::branch(*bs, next),
             TypedFormat::Variant(_, _label, f) => Self::from_gt_format(module, f, next.clone()),
             TypedFormat::Union(_, branches) | TypedFormat::UnionNondet(_, branches) => {
@@ -1957,7 +2323,9 @@ impl<'a> MatchTreeStep<'a> {
                 Self::from_gt_format(module, f0, next0)
             }
             TypedFormat::Hint(_, _hint, f) => Self::from_gt_format(module, f, next),
-            TypedFormat::Permit(_, f, _expr) => Self::from_gt_format(module, f, next),
+            TypedFormat::Permit(_, f, _) | TypedFormat::Resync(_, f, _) => {
+                Self::from_gt_format(module, f, next)
+            }
             #[cfg(feature = "format_enforce")]
             TypedFormat::Enforce(_, f) => Self::from_gt_format(module, f, next),
             TypedFormat::WithView(_, _ident, _vf) => Self::from_next(module, next),
@@ -1974,10 +2342,15 @@ impl<'a> MatchTreeStep<'a> {
             Format::ItemVar(level, _args, _views) => {
                 Self::from_format(module, module.get_format(*level), next)
             }
+            Format::GenericVar(..) => {
+                unreachable!("generic formats must be monomorphized before compilation")
+            }
             Format::Phantom(..) => Self::accept(),
             Format::Fail => Self::reject(),
             Format::EndOfInput => Self::accept(),
             Format::SkipRemainder => Self::accept(),
+            // NOTE - we cannot know what a native format will accept, so it is treated like SkipRemainder
+            Format::Native(..) => Self::accept(),
             Format::Align(n) => Self::from_align(module, next, *n),
             Format::DecodeBytes(_bytes, _f) => Self::from_next(module, next),
             Format::ParseFromView(_view, _f) => Self::from_next(module, next),
@@ -2107,8 +2480,8 @@ impl<'a> MatchTreeStep<'a> {
                 // FIXME - does the construction of a view-binding affect our matchtree?
                 Self::from_format(module, f, next)
             }
-            // REVIEW - it isn't fully clear whether Permit should affect the matchtree in some way...
-            Format::Permit(f, _) => Self::from_format(module, f, next),
+            // REVIEW - it isn't fully clear whether Permit (or Resync) should affect the matchtree in some way...
+            Format::Permit(f, _) | Format::Resync(f, _) => Self::from_format(module, f, next),
             #[cfg(feature = "format_enforce")]
             Format::Enforce(f) => Self::from_format(module, f, next),
             Format::Match(_, branches) => {
@@ -2351,16 +2724,16 @@ impl<'a, T> TypeScope<'a, T> {
         self.types.push(ValueKind::View);
     }
 
-    fn get_type_by_name(&self, name: &str) -> &ValueKind<T> {
+    fn get_type_by_name(&self, name: &str) -> AResult<&ValueKind<T>> {
         for (i, n) in self.names.iter().enumerate().rev() {
             if n == name {
-                return &self.types[i];
+                return Ok(&self.types[i]);
             }
         }
         if let Some(scope) = self.parent {
             scope.get_type_by_name(name)
         } else {
-            panic!("variable not found: {name}");
+            Err(anyhow!("variable not found: {name}"))
         }
     }
 }
@@ -0,0 +1,208 @@
+//! Configurable bounds on the work performed by a single decode, for use on untrusted inputs.
+//!
+//! The same [`Limits`] are honored by both the interpreter ([`Program::run`](crate::decoder::Program::run))
+//! and generated decoders (via [`Parser::with_limits`](crate::parser::Parser::with_limits)), which track
+//! their usage against them in a [`Budget`].
+use std::cell::Cell;
+
+/// Upper bounds on the resources a decode may consume; `None` means unbounded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum total number of elements accumulated across all sequences
+    pub max_seq_elements: Option<usize>,
+    /// Maximum nesting depth of format calls
+    pub max_depth: Option<usize>,
+    /// Maximum number of `WithRelativeOffset` seeks
+    pub max_offset_hops: Option<usize>,
+    /// Maximum number of decoding steps (an implementation-defined unit of work, roughly proportional to
+    /// decoding time)
+    pub max_steps: Option<usize>,
+}
+
+impl Limits {
+    /// Limits that never restrict a decode.
+    pub const UNBOUNDED: Limits = Limits {
+        max_seq_elements: None,
+        max_depth: None,
+        max_offset_hops: None,
+        max_steps: None,
+    };
+
+    pub fn max_seq_elements(mut self, max: usize) -> Self {
+        self.max_seq_elements = Some(max);
+        self
+    }
+
+    pub fn max_depth(mut self, max: usize) -> Self {
+        self.max_depth = Some(max);
+        self
+    }
+
+    pub fn max_offset_hops(mut self, max: usize) -> Self {
+        self.max_offset_hops = Some(max);
+        self
+    }
+
+    pub fn max_steps(mut self, max: usize) -> Self {
+        self.max_steps = Some(max);
+        self
+    }
+}
+
+/// One of the bounds set by [`Limits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Limit {
+    SeqElements,
+    Depth,
+    OffsetHops,
+    Steps,
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::SeqElements => f.write_str("sequence elements"),
+            Limit::Depth => f.write_str("nested format calls"),
+            Limit::OffsetHops => f.write_str("offset hops"),
+            Limit::Steps => f.write_str("decoding steps"),
+        }
+    }
+}
+
+/// Error indicating that a decode would have exceeded one of its [`Limits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LimitExceeded {
+    pub limit: Limit,
+    pub max: usize,
+}
+
+impl std::fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "exceeded limit of {} {}", self.max, self.limit)
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// Resources consumed over (part of) a decode, as counted by a [`Budget`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub seq_elements: usize,
+    pub offset_hops: usize,
+    pub steps: usize,
+}
+
+impl Usage {
+    /// Returns the resources consumed between `earlier` and `self`.
+    pub fn since(self, earlier: Usage) -> Usage {
+        Usage {
+            seq_elements: self.seq_elements.saturating_sub(earlier.seq_elements),
+            offset_hops: self.offset_hops.saturating_sub(earlier.offset_hops),
+            steps: self.steps.saturating_sub(earlier.steps),
+        }
+    }
+}
+
+/// Running totals of the resources consumed by a decode, checked against its [`Limits`].
+///
+/// A `Budget` belongs to a single decode (a fresh one is made for every run of a program), so it is never
+/// shared between threads.
+#[derive(Clone, Debug, Default)]
+pub struct Budget {
+    limits: Limits,
+    seq_elements: Cell<usize>,
+    depth: Cell<usize>,
+    offset_hops: Cell<usize>,
+    steps: Cell<usize>,
+}
+
+impl Budget {
+    pub fn new(limits: Limits) -> Budget {
+        Budget {
+            limits,
+            ..Budget::default()
+        }
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    fn charge(
+        counter: &Cell<usize>,
+        max: Option<usize>,
+        limit: Limit,
+        n: usize,
+    ) -> Result<(), LimitExceeded> {
+        let total = counter.get().saturating_add(n);
+        match max {
+            Some(max) if total > max => Err(LimitExceeded { limit, max }),
+            _ => {
+                counter.set(total);
+                Ok(())
+            }
+        }
+    }
+
+    /// Records that `n` more sequence elements are being accumulated.
+    pub fn tally_elements(&self, n: usize) -> Result<(), LimitExceeded> {
+        Self::charge(
+            &self.seq_elements,
+            self.limits.max_seq_elements,
+            Limit::SeqElements,
+            n,
+        )
+    }
+
+    /// Records a single decoding step.
+    pub fn step(&self) -> Result<(), LimitExceeded> {
+        Self::charge(&self.steps, self.limits.max_steps, Limit::Steps, 1)
+    }
+
+    /// Records a `WithRelativeOffset` seek.
+    pub fn hop(&self) -> Result<(), LimitExceeded> {
+        Self::charge(
+            &self.offset_hops,
+            self.limits.max_offset_hops,
+            Limit::OffsetHops,
+            1,
+        )
+    }
+
+    /// Records entry into a nested format call, which must be balanced by a call to [`Budget::leave`].
+    pub fn enter(&self) -> Result<(), LimitExceeded> {
+        Self::charge(&self.depth, self.limits.max_depth, Limit::Depth, 1)
+    }
+
+    /// Records exit from a nested format call.
+    pub fn leave(&self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+
+    /// Returns the resources consumed so far.
+    pub fn usage(&self) -> Usage {
+        Usage {
+            seq_elements: self.seq_elements.get(),
+            offset_hops: self.offset_hops.get(),
+            steps: self.steps.get(),
+        }
+    }
+
+    /// Records `usage` over again, as when reusing the outcome of a decode that consumed it rather than
+    /// repeating the decode itself.
+    pub fn replay(&self, usage: Usage) -> Result<(), LimitExceeded> {
+        self.tally_elements(usage.seq_elements)?;
+        Self::charge(
+            &self.offset_hops,
+            self.limits.max_offset_hops,
+            Limit::OffsetHops,
+            usage.offset_hops,
+        )?;
+        Self::charge(
+            &self.steps,
+            self.limits.max_steps,
+            Limit::Steps,
+            usage.steps,
+        )
+    }
+}
@@ -4,21 +4,23 @@ use std::cmp::Ordering;
 use num_bigint::BigInt;
 use serde::Serialize;
 
-use crate::byte_set::ByteSet;
+use crate::byte_set::{ByteSet, resync_offset};
 use crate::decoder::View;
 use crate::decoder::break_if_done;
 use crate::decoder::{
-    Compiler, Decoder, Program, ScopeEntry, SeqKind, UnknownVarError, Value, ValueSeq, cow_map,
-    cow_remap, extract_pair,
+    Compiler, Decoder, Program, RunState, ScopeEntry, SeqKind, UnknownVarError, Value, ValueSeq,
+    cow_map, cow_remap, extract_pair,
+    memo::MemoStats,
     search::{find_index_by_key_sorted, find_index_by_key_unsorted},
-    seq_kind::sub_range,
+    seq_kind::{Element, sub_range},
 };
-use crate::error::{DecodeErrorKind, ELocDecodeResult, LocDecodeResult};
+use crate::error::{DecodeErrorKind, ELocDecodeResult, EvalError, EvalResult, LocDecodeResult};
+use crate::limits::Usage;
 use crate::read::ReadCtxt;
 use crate::util::WithErr;
 use crate::util::downgrade_error_with;
 use crate::validation::Condition;
-use crate::{BaseKind, DynFormat, Endian, Expr, Format, Label, Pattern, ViewExpr};
+use crate::{BaseKind, DynFormat, Endian, Expr, ExprFunction, Format, Label, Pattern, ViewExpr};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize)]
 pub enum ParseLoc {
@@ -280,6 +282,37 @@ impl ParsedValue {
     }
 }
 
+/// Table of the synthesized values of every byte, from which `SeqKind::Bytes` lends its elements.
+///
+/// As a `const` rather than a `static`, the table need not be `Sync`; its promoted reference is nonetheless `'static`.
+const BYTE_VALUES: &[ParsedValue; 256] = &{
+    const fn synthesized(b: u8) -> ParsedValue {
+        ParsedValue::Flat(Parsed {
+            loc: ParseLoc::Synthesized,
+            inner: Value::U8(b),
+        })
+    }
+    let mut values = [const { synthesized(0) }; 256];
+    let mut b = 1;
+    while b < 256 {
+        // NOTE - the placeholder being replaced owns nothing, but has a destructor that cannot run at compile-time
+        std::mem::forget(std::mem::replace(&mut values[b], synthesized(b as u8)));
+        b += 1;
+    }
+    values
+};
+
+/// Byte-sequences are never stored compactly, as that would lose the location of each byte.
+impl Element for ParsedValue {
+    fn byte(b: u8) -> &'static ParsedValue {
+        &BYTE_VALUES[usize::from(b)]
+    }
+
+    fn to_byte(&self) -> Option<u8> {
+        None
+    }
+}
+
 impl From<ParsedValue> for Value {
     fn from(value: ParsedValue) -> Self {
         match value {
@@ -334,10 +367,11 @@ impl ParsedValue {
                 Value::Tuple(Vec::from_iter(ts.inner.iter().cloned().map(Value::from)))
             }
             ParsedValue::Seq(seq) => match &seq.inner {
-                SeqKind::Strict(elts) => Value::Seq(SeqKind::Strict(Vec::from_iter(
-                    elts.iter().cloned().map(Value::from),
-                ))),
+                SeqKind::Strict(elts) => {
+                    Value::Seq(SeqKind::from_iter(elts.iter().cloned().map(Value::from)))
+                }
                 SeqKind::Dup(n, v) => Value::Seq(SeqKind::Dup(*n, Box::new(v.clone_into_value()))),
+                SeqKind::Bytes(bs) => Value::Seq(SeqKind::Bytes(bs.clone())),
             },
             ParsedValue::Record(fs) => Value::Record(Vec::from_iter(
                 fs.inner.iter().cloned().map(|(lab, f)| (lab, f.into())),
@@ -363,8 +397,12 @@ impl ParsedValue {
         }
     }
 
-    pub fn matches_inner(&self, scope: &mut LocMultiScope<'_>, pattern: &Pattern) -> bool {
-        match pattern {
+    pub fn matches_inner(
+        &self,
+        scope: &mut LocMultiScope<'_>,
+        pattern: &Pattern,
+    ) -> EvalResult<bool> {
+        let is_match = match pattern {
             Pattern::Binding(name) => {
                 scope.push(name.clone(), self.clone());
                 true
@@ -397,8 +435,8 @@ impl ParsedValue {
             Pattern::Tuple(ps) => match self {
                 ParsedValue::Tuple(vs) if ps.len() == vs.inner.len() => {
                     for (p, v) in Iterator::zip(ps.iter(), vs.inner.iter()) {
-                        if !v.matches_inner(scope, p) {
-                            return false;
+                        if !v.matches_inner(scope, p)? {
+                            return Ok(false);
                         }
                     }
                     true
@@ -408,8 +446,8 @@ impl ParsedValue {
             Pattern::Seq(ps) => match self {
                 ParsedValue::Seq(vs) if ps.len() == vs.inner.len() => {
                     for (p, v) in Iterator::zip(ps.iter(), vs.inner.iter()) {
-                        if !v.matches_inner(scope, p) {
-                            return false;
+                        if !v.matches_inner(scope, p)? {
+                            return Ok(false);
                         }
                     }
                     true
@@ -417,12 +455,12 @@ impl ParsedValue {
                 _ => false,
             },
             Pattern::Variant(label0, p) => match self {
-                ParsedValue::Variant(label1, v) if label0 == label1 => v.matches_inner(scope, p),
+                ParsedValue::Variant(label1, v) if label0 == label1 => v.matches_inner(scope, p)?,
                 _ => false,
             },
             Pattern::Option(None) => matches!(self, ParsedValue::Option(None)),
             Pattern::Option(Some(p)) => match self {
-                ParsedValue::Option(Some(v)) => v.matches_inner(scope, p),
+                ParsedValue::Option(Some(v)) => v.matches_inner(scope, p)?,
                 _ => false,
             },
             Pattern::Int(bounds) => match self {
@@ -460,7 +498,62 @@ impl ParsedValue {
                 },
                 _ => false,
             },
+            Pattern::Record(pfields) => match self {
+                ParsedValue::Record(Parsed { inner: fields, .. }) => {
+                    for (label, p) in pfields.iter() {
+                        let v = match fields.iter().find(|(l, _)| l == label) {
+                            Some((_, v)) => v.coerce_mapped_value(),
+                            None => panic!("{label} not found in record"),
+                        };
+                        if !v.matches_inner(scope, p)? {
+                            return Ok(false);
+                        }
+                    }
+                    true
+                }
+                _ => false,
+            },
+            Pattern::Or(alts) => {
+                for p in alts.iter() {
+                    if self.matches_alternative(scope, p, None)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            Pattern::Guard(p, guard) if p.has_or() => {
+                for alt in p.alternatives().iter() {
+                    if self.matches_alternative(scope, alt, Some(guard))? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            Pattern::Guard(p, guard) => self.matches_alternative(scope, p, Some(guard))?,
+        };
+        Ok(is_match)
+    }
+
+    /// Attempts to match `self` against `pattern` and then check `guard`, if any, discarding any
+    /// bindings made to `scope` on failure.
+    fn matches_alternative(
+        &self,
+        scope: &mut LocMultiScope<'_>,
+        pattern: &Pattern,
+        guard: Option<&Expr>,
+    ) -> EvalResult<bool> {
+        let mark = scope.entries.len();
+        let is_match = self.matches_inner(scope, pattern)?
+            && match guard {
+                None => true,
+                Some(guard) => guard
+                    .eval_value_with_loc(&LocScope::Multi(scope))?
+                    .unwrap_bool(),
+            };
+        if !is_match {
+            scope.entries.truncate(mark);
         }
+        Ok(is_match)
     }
 
     fn tuple_proj(&self, index: usize) -> &Self {
@@ -500,6 +593,8 @@ impl ParsedValue {
             | Value::Usize(_)
             | Value::EnumFromTo(_)
             | Value::PhantomData
+            | Value::Float(_)
+            | Value::Fixed(_)
             | Value::Char(_) => ParsedValue::Flat(Parsed {
                 loc: ParseLoc::Synthesized,
                 inner: expr_value,
@@ -577,17 +672,25 @@ impl ParsedValue {
         }
     }
 
-    fn matches<'a>(&self, scope: &'a LocScope<'a>, pattern: &Pattern) -> Option<LocMultiScope<'a>> {
+    fn matches<'a>(
+        &self,
+        scope: &'a LocScope<'a>,
+        pattern: &Pattern,
+    ) -> EvalResult<Option<LocMultiScope<'a>>> {
         let mut pattern_scope = LocMultiScope::new(scope);
-        self.coerce_mapped_value()
-            .matches_inner(&mut pattern_scope, pattern)
-            .then_some(pattern_scope)
+        let is_match = self
+            .coerce_mapped_value()
+            .matches_inner(&mut pattern_scope, pattern)?;
+        Ok(is_match.then_some(pattern_scope))
     }
 }
 
 impl Expr {
-    pub fn eval_with_loc<'a>(&'a self, scope: &'a LocScope<'a>) -> Cow<'a, ParsedValue> {
-        match self {
+    pub fn eval_with_loc<'a>(
+        &'a self,
+        scope: &'a LocScope<'a>,
+    ) -> EvalResult<Cow<'a, ParsedValue>> {
+        let value = match self {
             Expr::Var(name) => Cow::Borrowed(scope.get_value_by_name(name).unwrap()),
             Expr::Bool(b) => Cow::Owned(ParsedValue::from_evaluated(Value::Bool(*b))),
             Expr::U8(i) => Cow::Owned(ParsedValue::from_evaluated(Value::U8(*i))),
@@ -598,76 +701,79 @@ impl Expr {
                 let num_val = n.eval(scope);
                 match num_val {
                     Ok(v) => Cow::Owned(ParsedValue::from_evaluated(Value::from(v))),
-                    // REVIEW[epic=embedded-num] - we probably want a more sensible outcome than panic
-                    Err(e) => panic!("{e}"),
+                    Err(e) => return Err(EvalError::Numeric(e.to_string())),
                 }
             }
             Expr::Tuple(exprs) => Cow::Owned(ParsedValue::from_evaluated(Value::Tuple(
                 exprs
                     .iter()
                     .map(|expr| expr.eval_value_with_loc(scope))
-                    .collect(),
+                    .collect::<EvalResult<_>>()?,
             ))),
-            Expr::TupleProj(head, index) => cow_map(head.eval_with_loc(scope), |v| {
+            Expr::TupleProj(head, index) => cow_map(head.eval_with_loc(scope)?, |v| {
                 v.coerce_mapped_value().tuple_proj(*index)
             }),
             Expr::Record(fields) => Cow::Owned(ParsedValue::collect_fields(
                 fields
                     .iter()
-                    .map(|(label, expr)| (label.clone(), expr.eval_with_loc(scope).into_owned()))
-                    .collect(),
+                    .map(|(label, expr)| {
+                        Ok((label.clone(), expr.eval_with_loc(scope)?.into_owned()))
+                    })
+                    .collect::<EvalResult<_>>()?,
             )),
-            Expr::RecordProj(head, label) => cow_map(head.eval_with_loc(scope), |v| {
+            Expr::RecordProj(head, label) => cow_map(head.eval_with_loc(scope)?, |v| {
                 v.coerce_mapped_value().record_proj(label.as_ref())
             }),
             Expr::Variant(label, expr) => Cow::Owned(ParsedValue::from_evaluated(Value::variant(
                 label.clone(),
-                expr.eval_value_with_loc(scope),
+                expr.eval_value_with_loc(scope)?,
             ))),
             Expr::Seq(exprs) => Cow::Owned(ParsedValue::from_evaluated(Value::Seq(
                 exprs
                     .iter()
                     .map(|expr| expr.eval_value_with_loc(scope))
-                    .collect(),
+                    .collect::<EvalResult<_>>()?,
             ))),
             Expr::Match(head, branches) => {
-                let head = head.eval_with_loc(scope);
+                let head = head.eval_with_loc(scope)?;
                 for (pattern, expr) in branches {
-                    if let Some(pattern_scope) = head.matches(scope, pattern) {
-                        let value = expr.eval_value_with_loc(&LocScope::Multi(&pattern_scope));
-                        return Cow::Owned(ParsedValue::from_evaluated(value));
+                    if let Some(pattern_scope) = head.matches(scope, pattern)? {
+                        let value = expr.eval_value_with_loc(&LocScope::Multi(&pattern_scope))?;
+                        return Ok(Cow::Owned(ParsedValue::from_evaluated(value)));
                     }
                 }
-                panic!("non-exhaustive patterns");
+                return Err(EvalError::NoMatch);
             }
             Expr::Destructure(head, pattern, expr) => {
-                let head = head.eval_with_loc(scope);
-                if let Some(pattern_scope) = head.matches(scope, pattern) {
-                    let value = expr.eval_value_with_loc(&LocScope::Multi(&pattern_scope));
-                    return Cow::Owned(ParsedValue::from_evaluated(value));
+                let head = head.eval_with_loc(scope)?;
+                match head.matches(scope, pattern)? {
+                    Some(pattern_scope) => {
+                        let value = expr.eval_value_with_loc(&LocScope::Multi(&pattern_scope))?;
+                        Cow::Owned(ParsedValue::from_evaluated(value))
+                    }
+                    None => return Err(EvalError::NoMatch),
                 }
-                panic!("refuted pattern: {head:?} does not match {pattern:?}")
             }
             Expr::Lambda(_, _) => panic!("cannot eval lambda"),
 
             Expr::IntRel(rel, x, y) => Cow::Owned(ParsedValue::from_evaluated({
-                let left = x.eval_value_with_loc(scope);
-                let right = y.eval_value_with_loc(scope);
+                let left = x.eval_value_with_loc(scope)?;
+                let right = y.eval_value_with_loc(scope)?;
                 Value::int_rel(*rel, left, right)
             })),
             Expr::Arith(op, x, y) => Cow::Owned(ParsedValue::from_evaluated({
-                let left = x.eval_value_with_loc(scope);
-                let right = y.eval_value_with_loc(scope);
-                Value::arith(*op, left, right)
+                let left = x.eval_value_with_loc(scope)?;
+                let right = y.eval_value_with_loc(scope)?;
+                Value::arith(*op, left, right)?
             })),
             Expr::Unary(op, x) => Cow::Owned(ParsedValue::from_evaluated({
-                let value = x.eval_value_with_loc(scope);
-                Value::unary(*op, value)
+                let value = x.eval_value_with_loc(scope)?;
+                Value::unary(*op, value)?
             })),
 
             // FIXME - extract common logic for As-expr on Value instead of separate impl for decoder and loc_decoder
             Expr::AsU8(x) => Cow::Owned(ParsedValue::from_evaluated(
-                match x.eval_value_with_loc(scope) {
+                match x.eval_value_with_loc(scope)? {
                     Value::U8(x) => Value::U8(x),
                     Value::U16(x) => Value::U8(u8::try_from(x).unwrap_or_else(|err| {
                         panic!("cannot perform AsU8 cast on u16 {x}: {err}")
@@ -685,7 +791,7 @@ impl Expr {
                 },
             )),
             Expr::AsU16(x) => Cow::Owned(ParsedValue::from_evaluated(
-                match x.eval_value_with_loc(scope) {
+                match x.eval_value_with_loc(scope)? {
                     Value::U8(x) => Value::U16(u16::from(x)),
                     Value::U16(x) => Value::U16(x),
                     Value::U32(x) => Value::U16(u16::try_from(x).unwrap()),
@@ -695,7 +801,7 @@ impl Expr {
                 },
             )),
             Expr::AsU32(x) => Cow::Owned(ParsedValue::from_evaluated(
-                match x.eval_value_with_loc(scope) {
+                match x.eval_value_with_loc(scope)? {
                     Value::U8(x) => Value::U32(u32::from(x)),
                     Value::U16(x) => Value::U32(u32::from(x)),
                     Value::U32(x) => Value::U32(x),
@@ -705,7 +811,7 @@ impl Expr {
                 },
             )),
             Expr::AsU64(x) => Cow::Owned(ParsedValue::from_evaluated(
-                match x.eval_value_with_loc(scope) {
+                match x.eval_value_with_loc(scope)? {
                     Value::U8(x) => Value::U64(u64::from(x)),
                     Value::U16(x) => Value::U64(u64::from(x)),
                     Value::U32(x) => Value::U64(u64::from(x)),
@@ -716,7 +822,7 @@ impl Expr {
             )),
 
             Expr::U16Be(bytes) => {
-                match bytes.eval_value_with_loc(scope).unwrap_tuple().as_slice() {
+                match bytes.eval_value_with_loc(scope)?.unwrap_tuple().as_slice() {
                     [Value::U8(hi), Value::U8(lo)] => Cow::Owned(ParsedValue::from_evaluated(
                         Value::U16(u16::from_be_bytes([*hi, *lo])),
                     )),
@@ -724,7 +830,7 @@ impl Expr {
                 }
             }
             Expr::U16Le(bytes) => {
-                match bytes.eval_value_with_loc(scope).unwrap_tuple().as_slice() {
+                match bytes.eval_value_with_loc(scope)?.unwrap_tuple().as_slice() {
                     [Value::U8(lo), Value::U8(hi)] => Cow::Owned(ParsedValue::from_evaluated(
                         Value::U16(u16::from_le_bytes([*lo, *hi])),
                     )),
@@ -732,7 +838,7 @@ impl Expr {
                 }
             }
             Expr::U32Be(bytes) => {
-                match bytes.eval_value_with_loc(scope).unwrap_tuple().as_slice() {
+                match bytes.eval_value_with_loc(scope)?.unwrap_tuple().as_slice() {
                     [Value::U8(a), Value::U8(b), Value::U8(c), Value::U8(d)] => {
                         Cow::Owned(ParsedValue::from_evaluated(Value::U32(u32::from_be_bytes(
                             [*a, *b, *c, *d],
@@ -742,7 +848,7 @@ impl Expr {
                 }
             }
             Expr::U32Le(bytes) => {
-                match bytes.eval_value_with_loc(scope).unwrap_tuple().as_slice() {
+                match bytes.eval_value_with_loc(scope)?.unwrap_tuple().as_slice() {
                     [Value::U8(a), Value::U8(b), Value::U8(c), Value::U8(d)] => {
                         Cow::Owned(ParsedValue::from_evaluated(Value::U32(u32::from_le_bytes(
                             [*a, *b, *c, *d],
@@ -752,7 +858,7 @@ impl Expr {
                 }
             }
             Expr::U64Be(bytes) => {
-                match bytes.eval_value_with_loc(scope).unwrap_tuple().as_slice() {
+                match bytes.eval_value_with_loc(scope)?.unwrap_tuple().as_slice() {
                     [
                         Value::U8(a),
                         Value::U8(b),
@@ -769,7 +875,7 @@ impl Expr {
                 }
             }
             Expr::U64Le(bytes) => {
-                match bytes.eval_value_with_loc(scope).unwrap_tuple().as_slice() {
+                match bytes.eval_value_with_loc(scope)?.unwrap_tuple().as_slice() {
                     [
                         Value::U8(a),
                         Value::U8(b),
@@ -785,8 +891,16 @@ impl Expr {
                     _ => panic!("U32Le: expected (U8, U8, U8, U8, U8, U8, U8, U8)"),
                 }
             }
+            Expr::F32FromBits(bits) | Expr::F64FromBits(bits) => {
+                Cow::Owned(ParsedValue::from_evaluated(Value::float_from_bits(
+                    bits.eval_value_with_loc(scope)?,
+                )))
+            }
+            Expr::FixedFromBits(fx, bits) => Cow::Owned(ParsedValue::from_evaluated(
+                Value::fixed_from_bits(*fx, bits.eval_value_with_loc(scope)?),
+            )),
             Expr::AsChar(bytes) => Cow::Owned(ParsedValue::from_evaluated(
-                match bytes.eval_value_with_loc(scope) {
+                match bytes.eval_value_with_loc(scope)? {
                     Value::U8(x) => Value::Char(char::from(x)),
                     Value::U16(x) => {
                         Value::Char(char::from_u32(x as u32).unwrap_or(char::REPLACEMENT_CHARACTER))
@@ -802,7 +916,7 @@ impl Expr {
                 },
             )),
             Expr::SeqLength(seq) => match seq
-                .eval_with_loc(scope)
+                .eval_with_loc(scope)?
                 .coerce_mapped_value()
                 .get_sequence()
             {
@@ -812,31 +926,43 @@ impl Expr {
                 }
                 _ => panic!("SeqLength: expected Seq (or EnumFromTo)"),
             },
-            Expr::SeqIx(seq, index) => cow_remap(seq.eval_with_loc(scope), |v| {
-                match v.coerce_mapped_value().get_sequence() {
-                    Some(values) => {
-                        let index = index.eval_value_with_loc(scope).unwrap_usize();
-                        match values {
-                            ValueSeq::ValueSeq(values) => Cow::Borrowed(&values[index]),
-                            ValueSeq::IntRange(mut range) => {
-                                Cow::Owned(ParsedValue::from_evaluated(Value::Usize(
-                                    range.nth(index).unwrap(),
-                                )))
-                            }
-                        }
-                    }
+            Expr::SeqIx(seq, index) => {
+                let index = index.eval_value_with_loc(scope)?.unwrap_usize();
+                let seq = seq.eval_with_loc(scope)?;
+                let len = match seq.coerce_mapped_value().get_sequence() {
+                    Some(values) => values.len(),
                     _ => panic!("SeqIx: expected Seq (or EnumFromTo)"),
+                };
+                if index >= len {
+                    return Err(EvalError::OutOfBounds { index, len });
                 }
-            }),
+                cow_remap(seq, |v| match v.coerce_mapped_value().get_sequence() {
+                    Some(ValueSeq::ValueSeq(values)) => Cow::Borrowed(&values[index]),
+                    Some(ValueSeq::IntRange(mut range)) => Cow::Owned(ParsedValue::from_evaluated(
+                        Value::Usize(range.nth(index).unwrap()),
+                    )),
+                    _ => unreachable!(),
+                })
+            }
             Expr::SubSeq(seq, start, length) => {
                 match seq
-                    .eval_with_loc(scope)
+                    .eval_with_loc(scope)?
                     .coerce_mapped_value()
                     .get_sequence()
                 {
                     Some(values) => {
-                        let start = start.eval_value_with_loc(scope).unwrap_usize();
-                        let length = length.eval_value_with_loc(scope).unwrap_usize();
+                        let start = start.eval_value_with_loc(scope)?.unwrap_usize();
+                        let length = length.eval_value_with_loc(scope)?.unwrap_usize();
+                        let len = values.len();
+                        match start.checked_add(length) {
+                            Some(end) if end <= len => {}
+                            _ => {
+                                return Err(EvalError::OutOfBounds {
+                                    index: start.saturating_add(length),
+                                    len,
+                                });
+                            }
+                        }
                         match values {
                             ValueSeq::ValueSeq(values) => Cow::Owned(
                                 ParsedValue::from_evaluated_seq(values.sub_seq(start, length)),
@@ -851,13 +977,17 @@ impl Expr {
             }
             Expr::SubSeqInflate(seq, start, length) => {
                 match seq
-                    .eval_with_loc(scope)
+                    .eval_with_loc(scope)?
                     .coerce_mapped_value()
                     .get_sequence()
                 {
                     Some(values) => {
-                        let start = start.eval_value_with_loc(scope).unwrap_usize();
-                        let length = length.eval_value_with_loc(scope).unwrap_usize();
+                        let start = start.eval_value_with_loc(scope)?.unwrap_usize();
+                        let length = length.eval_value_with_loc(scope)?.unwrap_usize();
+                        let len = values.len();
+                        if length > 0 && start >= len {
+                            return Err(EvalError::OutOfBounds { index: start, len });
+                        }
                         let mut vs = Vec::new();
                         match values {
                             ValueSeq::ValueSeq(vs0) => {
@@ -888,18 +1018,18 @@ impl Expr {
                 }
             }
             Expr::Append(seq0, seq1) => {
-                let tmp0 = seq0.eval_with_loc(scope);
+                let tmp0 = seq0.eval_with_loc(scope)?;
                 let val0 = tmp0.coerce_mapped_value();
                 match val0.get_sequence() {
                     Some(val_seq0) => {
-                        let tmp1 = seq1.eval_with_loc(scope);
+                        let tmp1 = seq1.eval_with_loc(scope)?;
                         let val1 = tmp1.coerce_mapped_value();
                         match val1.get_sequence() {
                             Some(val_seq1) => {
                                 if val_seq0.is_empty() {
-                                    return Cow::Owned(val1.clone());
+                                    return Ok(Cow::Owned(val1.clone()));
                                 } else if val_seq1.is_empty() {
-                                    return Cow::Owned(val0.clone());
+                                    return Ok(Cow::Owned(val0.clone()));
                                 }
                                 Cow::Owned(ParsedValue::Seq(Parsed {
                                     loc: ParseLoc::Synthesized,
@@ -912,16 +1042,29 @@ impl Expr {
                     _ => unreachable!("Append: expected Seq in (lhs)"),
                 }
             }
+            Expr::Call(index, _, args) => {
+                let root = scope.root();
+                let func = root.get_function(*index);
+                let mut new_scope = LocMultiScope::with_capacity(root, args.len());
+                for ((name, _), arg) in func.params.iter().zip(args) {
+                    new_scope.push(name.clone(), arg.eval_with_loc(scope)?.into_owned());
+                }
+                Cow::Owned(
+                    func.body()
+                        .eval_with_loc(&LocScope::Multi(&new_scope))?
+                        .into_owned(),
+                )
+            }
             Expr::FlatMap(expr, seq) => {
                 match seq
-                    .eval_with_loc(scope)
+                    .eval_with_loc(scope)?
                     .coerce_mapped_value()
                     .get_sequence()
                 {
                     Some(values) => {
                         let mut vs: Vec<Value> = Vec::new();
                         for v in values {
-                            if let Value::Seq(vn) = expr.eval_lambda_with_loc(scope, &v) {
+                            if let Value::Seq(vn) = expr.eval_lambda_with_loc(scope, &v)? {
                                 vs.extend(vn);
                             } else {
                                 panic!("FlatMap: expected Seq");
@@ -933,12 +1076,12 @@ impl Expr {
                 }
             }
             Expr::FlatMapAccum(expr, accum, _accum_type, seq) => match seq
-                .eval_with_loc(scope)
+                .eval_with_loc(scope)?
                 .coerce_mapped_value()
                 .get_sequence()
             {
                 Some(values) => {
-                    let mut accum = accum.eval_value_with_loc(scope);
+                    let mut accum = accum.eval_value_with_loc(scope)?;
                     let mut vs = Vec::new();
                     for v in values {
                         let ret = expr.eval_lambda_with_loc(
@@ -947,7 +1090,7 @@ impl Expr {
                                 accum,
                                 v.clone_into_value(),
                             ])),
-                        );
+                        )?;
                         accum = match extract_pair(ret.unwrap_tuple()) {
                             (accum, Value::Seq(vn)) => {
                                 vs.extend(vn);
@@ -961,12 +1104,12 @@ impl Expr {
                 None => panic!("FlatMapAccum: expected Seq"),
             },
             Expr::LeftFold(expr, accum, _accum_type, seq) => match seq
-                .eval_with_loc(scope)
+                .eval_with_loc(scope)?
                 .coerce_mapped_value()
                 .get_sequence()
             {
                 Some(values) => {
-                    let mut accum = accum.eval_value_with_loc(scope);
+                    let mut accum = accum.eval_value_with_loc(scope)?;
                     for v in values {
                         let new_accum = expr.eval_lambda_with_loc(
                             scope,
@@ -974,7 +1117,7 @@ impl Expr {
                                 accum,
                                 v.clone_into_value(),
                             ])),
-                        );
+                        )?;
                         accum = new_accum;
                     }
                     Cow::Owned(ParsedValue::from_evaluated(accum))
@@ -983,16 +1126,15 @@ impl Expr {
             },
             Expr::FindByKey(is_sorted, f_get_key, query_key, seq) => {
                 match seq
-                    .eval_with_loc(scope)
+                    .eval_with_loc(scope)?
                     .coerce_mapped_value()
                     .get_sequence()
                 {
                     Some(ValueSeq::ValueSeq(values)) => {
-                        let eval =
-                            |lambda: &Expr, v: &ParsedValue| lambda.eval_lambda_with_loc(scope, v);
-                        let query = query_key.eval_value_with_loc(scope);
+                        let get_key = |v: &ParsedValue| f_get_key.eval_lambda_with_loc(scope, v);
+                        let query = query_key.eval_value_with_loc(scope)?;
                         if *is_sorted {
-                            match find_index_by_key_sorted(f_get_key, &query, values, eval) {
+                            match find_index_by_key_sorted(&query, values, get_key)? {
                                 Some(ix) => Cow::Owned(ParsedValue::Option(Some(Box::new(
                                     values[ix].clone(),
                                 )))),
@@ -1001,7 +1143,7 @@ impl Expr {
                                 }
                             }
                         } else {
-                            match find_index_by_key_unsorted(f_get_key, &query, values, eval) {
+                            match find_index_by_key_unsorted(&query, values, get_key)? {
                                 Some(ix) => Cow::Owned(ParsedValue::Option(Some(Box::new(
                                     values[ix].clone(),
                                 )))),
@@ -1017,15 +1159,126 @@ impl Expr {
                     None => panic!("FindByKey: expected Seq"),
                 }
             }
-            Expr::FlatMapList(expr, _ret_type, seq) => match seq.eval_value_with_loc(scope) {
+            Expr::Filter(expr, seq) => {
+                match seq
+                    .eval_with_loc(scope)?
+                    .coerce_mapped_value()
+                    .get_sequence()
+                {
+                    Some(values) => {
+                        let mut vs: Vec<ParsedValue> = Vec::new();
+                        for v in values {
+                            if expr.eval_lambda_with_loc(scope, &v)?.unwrap_bool() {
+                                vs.push(v.into_owned());
+                            }
+                        }
+                        Cow::Owned(ParsedValue::from_evaluated_seq(vs))
+                    }
+                    _ => panic!("Filter: expected Seq"),
+                }
+            }
+            Expr::Zip(seq0, seq1) => match (
+                seq0.eval_with_loc(scope)?
+                    .coerce_mapped_value()
+                    .get_sequence(),
+                seq1.eval_with_loc(scope)?
+                    .coerce_mapped_value()
+                    .get_sequence(),
+            ) {
+                (Some(values0), Some(values1)) => {
+                    let vs: Vec<ParsedValue> = values0
+                        .into_iter()
+                        .zip(values1)
+                        .map(|(v0, v1)| {
+                            ParsedValue::Tuple(Parsed {
+                                loc: ParseLoc::Synthesized,
+                                inner: vec![v0.into_owned(), v1.into_owned()],
+                            })
+                        })
+                        .collect();
+                    Cow::Owned(ParsedValue::from_evaluated_seq(vs))
+                }
+                _ => panic!("Zip: expected Seq"),
+            },
+            Expr::Reverse(seq) => match seq
+                .eval_with_loc(scope)?
+                .coerce_mapped_value()
+                .get_sequence()
+            {
+                Some(values) => {
+                    let mut vs: Vec<ParsedValue> =
+                        values.into_iter().map(Cow::into_owned).collect();
+                    vs.reverse();
+                    Cow::Owned(ParsedValue::from_evaluated_seq(vs))
+                }
+                _ => panic!("Reverse: expected Seq"),
+            },
+            Expr::Any(expr, seq) => match seq
+                .eval_with_loc(scope)?
+                .coerce_mapped_value()
+                .get_sequence()
+            {
+                Some(values) => {
+                    let mut any = false;
+                    for v in values {
+                        if expr.eval_lambda_with_loc(scope, &v)?.unwrap_bool() {
+                            any = true;
+                            break;
+                        }
+                    }
+                    Cow::Owned(ParsedValue::from_evaluated(Value::Bool(any)))
+                }
+                _ => panic!("Any: expected Seq"),
+            },
+            Expr::All(expr, seq) => match seq
+                .eval_with_loc(scope)?
+                .coerce_mapped_value()
+                .get_sequence()
+            {
+                Some(values) => {
+                    let mut all = true;
+                    for v in values {
+                        if !expr.eval_lambda_with_loc(scope, &v)?.unwrap_bool() {
+                            all = false;
+                            break;
+                        }
+                    }
+                    Cow::Owned(ParsedValue::from_evaluated(Value::Bool(all)))
+                }
+                _ => panic!("All: expected Seq"),
+            },
+            Expr::IsSortedBy(expr, seq) => match seq
+                .eval_with_loc(scope)?
+                .coerce_mapped_value()
+                .get_sequence()
+            {
+                Some(values) => {
+                    let vs: Vec<Cow<'_, ParsedValue>> = values.into_iter().collect();
+                    let mut is_sorted = true;
+                    for pair in vs.windows(2) {
+                        let arg = ParsedValue::Tuple(Parsed {
+                            loc: ParseLoc::Synthesized,
+                            inner: vec![pair[0].clone().into_owned(), pair[1].clone().into_owned()],
+                        });
+                        if !expr.eval_lambda_with_loc(scope, &arg)?.unwrap_bool() {
+                            is_sorted = false;
+                            break;
+                        }
+                    }
+                    Cow::Owned(ParsedValue::from_evaluated(Value::Bool(is_sorted)))
+                }
+                _ => panic!("IsSortedBy: expected Seq"),
+            },
+            Expr::FlatMapList(expr, _ret_type, seq) => match seq.eval_value_with_loc(scope)? {
                 Value::Seq(values) => {
                     let mut vs = Vec::new();
                     for v in values {
                         let arg = Value::Tuple(vec![Value::Seq(vs.into()), v]);
                         // TODO can we avoid cloning arg here?
-                        if let Value::Seq(vn) = expr
-                            .eval_lambda_with_loc(scope, &ParsedValue::from_evaluated(arg.clone()))
-                        {
+                        if let Value::Seq(vn) = expr.eval_lambda_with_loc(
+                            scope,
+                            &ParsedValue::from_evaluated(arg.clone()),
+                        )? {
                             vs = match arg {
                                 Value::Tuple(mut args) => match args.remove(0) {
                                     Value::Seq(vs) => vs.into_vec(),
@@ -1043,32 +1296,39 @@ impl Expr {
                 _ => panic!("FlatMapList: expected Seq"),
             },
             Expr::Dup(count, expr) => {
-                let count = count.eval_value_with_loc(scope).unwrap_usize();
-                let v = expr.eval_value_with_loc(scope);
+                let count = count.eval_value_with_loc(scope)?.unwrap_usize();
+                let v = expr.eval_value_with_loc(scope)?;
                 Cow::Owned(ParsedValue::from_evaluated(Value::Seq(SeqKind::Dup(
                     count,
                     Box::new(v),
                 ))))
             }
             Expr::EnumFromTo(start, stop) => {
-                let start = start.eval_value_with_loc(scope).unwrap_usize();
-                let stop = stop.eval_value_with_loc(scope).unwrap_usize();
+                let start = start.eval_value_with_loc(scope)?.unwrap_usize();
+                let stop = stop.eval_value_with_loc(scope)?.unwrap_usize();
                 Cow::Owned(ParsedValue::from_evaluated(Value::EnumFromTo(start..stop)))
             }
             Expr::LiftOption(opt) => Cow::Owned(ParsedValue::from_evaluated(Value::Option(
                 opt.as_ref()
-                    .map(|expr| Box::new(expr.eval_value_with_loc(scope))),
+                    .map(|expr| expr.eval_value_with_loc(scope).map(Box::new))
+                    .transpose()?,
             ))),
-        }
+        };
+        Ok(value)
     }
 
-    pub fn eval_value_with_loc<'a>(&self, scope: &'a LocScope<'a>) -> Value {
-        self.eval_with_loc(scope)
+    pub fn eval_value_with_loc<'a>(&self, scope: &'a LocScope<'a>) -> EvalResult<Value> {
+        Ok(self
+            .eval_with_loc(scope)?
             .coerce_mapped_value()
-            .clone_into_value()
+            .clone_into_value())
     }
 
-    fn eval_lambda_with_loc<'a>(&self, scope: &'a LocScope<'a>, arg: &ParsedValue) -> Value {
+    fn eval_lambda_with_loc<'a>(
+        &self,
+        scope: &'a LocScope<'a>,
+        arg: &ParsedValue,
+    ) -> EvalResult<Value> {
         match self {
             Expr::Lambda(name, expr) => {
                 let child_scope = LocSingleScope::new(scope, name, arg);
@@ -1084,10 +1344,21 @@ impl Program {
         &self,
         input: ReadCtxt<'input>,
     ) -> LocDecodeResult<(ParsedValue, ReadCtxt<'input>)> {
-        Ok(self.decoders[0]
+        self.run_with_loc_stats(input).0
+    }
+
+    /// Runs `self` over `input` as [`Program::run_with_loc`] does, also returning the memoization statistics
+    /// of the run.
+    pub fn run_with_loc_stats<'input>(
+        &self,
+        input: ReadCtxt<'input>,
+    ) -> (LocDecodeResult<(ParsedValue, ReadCtxt<'input>)>, MemoStats) {
+        let run = RunState::new(self);
+        let res = self.decoders[0]
             .0
-            .parse_with_loc(self, &LocScope::Empty, input)?
-            .extract_warn())
+            .parse_with_loc(self, &run, &LocScope::Functions(&self.functions), input)
+            .map(WithErr::extract_warn);
+        (res, run.memo.stats())
     }
 }
 
@@ -1095,6 +1366,8 @@ pub type LocScopeEntry = ScopeEntry<ParsedValue>;
 
 pub enum LocScope<'a> {
     Empty,
+    /// Outermost scope of a run, binding no variables but providing the functions of the module to calls
+    Functions(&'a [ExprFunction]),
     Multi(&'a LocMultiScope<'a>),
     Single(LocSingleScope<'a>),
     Decoder(LocDecoderScope<'a>),
@@ -1142,7 +1415,9 @@ pub struct LocViewScope<'a> {
 impl<'a> LocScope<'a> {
     pub(crate) fn get_value_by_name(&self, name: &str) -> Result<&ParsedValue, UnknownVarError> {
         match self {
-            LocScope::Empty => Err(UnknownVarError(Label::Owned(name.to_string()))),
+            LocScope::Empty | LocScope::Functions(_) => {
+                Err(UnknownVarError(Label::Owned(name.to_string())))
+            }
             LocScope::Multi(multi) => multi.get_value_by_name(name),
             LocScope::Single(single) => single.get_value_by_name(name),
             LocScope::Decoder(decoder) => decoder.parent.get_value_by_name(name),
@@ -1152,7 +1427,7 @@ impl<'a> LocScope<'a> {
 
     fn get_decoder_by_name(&self, name: &str) -> &Decoder {
         match self {
-            LocScope::Empty => panic!("decoder not found: {name}"),
+            LocScope::Empty | LocScope::Functions(_) => panic!("decoder not found: {name}"),
             LocScope::Multi(multi) => multi.parent.get_decoder_by_name(name),
             LocScope::Single(single) => single.parent.get_decoder_by_name(name),
             LocScope::Decoder(decoder) => decoder.get_decoder_by_name(name),
@@ -1162,7 +1437,7 @@ impl<'a> LocScope<'a> {
 
     fn get_view_by_name(&self, name: &str) -> View<'a> {
         match self {
-            LocScope::Empty => panic!("view not found: {name}"),
+            LocScope::Empty | LocScope::Functions(_) => panic!("view not found: {name}"),
             LocScope::Multi(multi) => multi.get_view_by_name(name),
             LocScope::Single(single) => single.parent.get_view_by_name(name),
             LocScope::Decoder(decoder) => decoder.parent.get_view_by_name(name),
@@ -1170,9 +1445,27 @@ impl<'a> LocScope<'a> {
         }
     }
 
+    /// Returns the outermost scope enclosing this one, within which the functions of the module are called.
+    fn root(&self) -> &LocScope<'a> {
+        match self {
+            LocScope::Empty | LocScope::Functions(_) => self,
+            LocScope::Multi(multi) => multi.parent.root(),
+            LocScope::Single(single) => single.parent.root(),
+            LocScope::Decoder(decoder) => decoder.parent.root(),
+            LocScope::View(view) => view.parent.root(),
+        }
+    }
+
+    fn get_function(&self, index: usize) -> &ExprFunction {
+        match self.root() {
+            LocScope::Functions(functions) => &functions[index],
+            _ => panic!("function not found: {index}"),
+        }
+    }
+
     pub fn get_bindings(&self, bindings: &mut Vec<(Label, LocScopeEntry)>) {
         match self {
-            LocScope::Empty => {}
+            LocScope::Empty | LocScope::Functions(_) => {}
             LocScope::Multi(multi) => multi.get_bindings(bindings),
             LocScope::Single(single) => single.get_bindings(bindings),
             LocScope::Decoder(decoder) => decoder.get_bindings(bindings),
@@ -1331,24 +1624,49 @@ impl Decoder {
     pub fn parse_with_loc<'input>(
         &self,
         program: &Program,
+        run: &RunState,
         scope: &LocScope<'_>,
         input: ReadCtxt<'input>,
     ) -> ELocDecodeResult<(ParsedValue, ReadCtxt<'input>)> {
         let start_offset = input.offset;
         match self {
             Decoder::Call(n, es, vs) => {
-                let mut new_scope = LocMultiScope::with_capacity(&LocScope::Empty, es.len());
+                let mut new_scope = LocMultiScope::with_capacity(scope.root(), es.len());
                 for (name, e) in es {
-                    let v = e.eval_with_loc(scope).as_ref().clone();
+                    let v = e
+                        .eval_with_loc(scope)
+                        .map_err(DecodeErrorKind::eval)?
+                        .as_ref()
+                        .clone();
                     new_scope.push(name.clone(), v);
                 }
+                let mut views = Vec::with_capacity(vs.len());
                 for (name, vv) in vs {
                     let v = Self::eval_view_expr_with_loc(scope, vv)?;
                     new_scope.push_view(name.clone(), v);
+                    views.push(v);
+                }
+                // NOTE - the locations of an argument may be carried into the result, so only argument-free calls are memoized
+                let key = if es.is_empty() {
+                    run.memo.key(*n, [], views, input)
+                } else {
+                    None
+                };
+                // NOTE - runs with locations are not subject to limits, so there is no usage to charge or replay
+                if let Some((res, _)) = key.as_ref().and_then(|k| run.memo.recall_parsed(k, input))
+                {
+                    return res;
                 }
-                program.decoders[*n]
-                    .0
-                    .parse_with_loc(program, &LocScope::Multi(&new_scope), input)
+                let res = program.decoders[*n].0.parse_with_loc(
+                    program,
+                    run,
+                    &LocScope::Multi(&new_scope),
+                    input,
+                );
+                if let Some(key) = key {
+                    run.memo.record_parsed(key, &res, Usage::default(), input);
+                }
+                res
             }
             Decoder::Phantom => Ok(WithErr::new((ParsedValue::new_phantom(), input))),
             Decoder::Fail => Err(DecodeErrorKind::<ParsedValue>::loc_fail(scope, input)),
@@ -1365,6 +1683,22 @@ impl Decoder {
                 let ret = ParsedValue::unit_spanning(start, input.offset - start);
                 Ok(WithErr::new((ret, input)))
             }
+            Decoder::Native(decode, args) => {
+                let args: Vec<Value> = args
+                    .iter()
+                    .map(|arg| Ok(arg.eval_with_loc(scope)?.into_cow_value().into_owned()))
+                    .collect::<EvalResult<_>>()
+                    .map_err(DecodeErrorKind::eval)?;
+                let (value, len) = decode(input.remaining(), &args)
+                    .map_err(|error| DecodeErrorKind::native(error, start_offset))?;
+                let (_, input) = input
+                    .split_at(len)
+                    .ok_or(DecodeErrorKind::overrun(len, start_offset))?;
+                Ok(WithErr::new((
+                    ParsedValue::new_flat(value, start_offset, len),
+                    input,
+                )))
+            }
             Decoder::EndOfInput => match input.read_byte() {
                 None => Ok(WithErr::new((ParsedValue::unit_at(start_offset), input))),
                 Some((b, _)) => Err(DecodeErrorKind::<ParsedValue>::trailing(b, input.offset)),
@@ -1393,19 +1727,19 @@ impl Decoder {
                 }
             }
             Decoder::Variant(label, d) => Ok(d
-                .parse_with_loc(program, scope, input)?
+                .parse_with_loc(program, run, scope, input)?
                 .map(move |(v, input)| (ParsedValue::Variant(label.clone(), Box::new(v)), input))),
             Decoder::Branch(tree, branches) => {
                 let index = tree.matches(input).ok_or(DecodeErrorKind::NoValidBranch {
                     offset: input.offset,
                 })?;
                 let d = &branches[index];
-                Ok(d.parse_with_loc(program, scope, input)?
+                Ok(d.parse_with_loc(program, run, scope, input)?
                     .map(|(v, input)| (ParsedValue::Branch(index, Box::new(v)), input)))
             }
             Decoder::Parallel(branches) => {
                 for (index, d) in branches.iter().enumerate() {
-                    let res = d.parse_with_loc(program, scope, input);
+                    let res = d.parse_with_loc(program, run, scope, input);
                     if let Ok(p) = res {
                         return Ok(
                             p.map(|(v, input)| (ParsedValue::Branch(index, Box::new(v)), input))
@@ -1418,11 +1752,12 @@ impl Decoder {
                 (Vec::with_capacity(fields.len()), input),
                 fields.iter(),
                 |(mut v, input), f| {
-                    Ok(f.parse_with_loc(program, scope, input)?
-                        .map(move |(vf, next_input)| {
+                    Ok(f.parse_with_loc(program, run, scope, input)?.map(
+                        move |(vf, next_input)| {
                             v.push(vf);
                             (v, next_input)
-                        }))
+                        },
+                    ))
                 },
             )?
             .map(|(v, input)| {
@@ -1433,11 +1768,12 @@ impl Decoder {
                 (Vec::with_capacity(decs.len()), input),
                 decs.iter(),
                 |(mut vs, input), f| {
-                    Ok(f.parse_with_loc(program, scope, input)?
-                        .map(move |(vf, next_input)| {
+                    Ok(f.parse_with_loc(program, run, scope, input)?.map(
+                        move |(vf, next_input)| {
                             vs.push(vf);
                             (vs, next_input)
-                        }))
+                        },
+                    ))
                 },
             )?
             .map(|(v, input)| {
@@ -1452,7 +1788,7 @@ impl Decoder {
                 })? == 0
                 {
                     res = res.join(|(mut v, input)| {
-                        Ok(a.parse_with_loc(program, scope, input)?
+                        Ok(a.parse_with_loc(program, run, scope, input)?
                             .map(|(va, next_input)| {
                                 v.push(va);
                                 (v, next_input)
@@ -1469,7 +1805,7 @@ impl Decoder {
                 let mut res = WithErr::new((Vec::new(), input));
                 loop {
                     res = res.join(|(mut v, input)| {
-                        Ok(a.parse_with_loc(program, scope, input)?
+                        Ok(a.parse_with_loc(program, run, scope, input)?
                             .map(|(va, next_input)| {
                                 v.push(va);
                                 (v, next_input)
@@ -1490,7 +1826,9 @@ impl Decoder {
             }
             Decoder::DecodeBytes(bytes, a) => {
                 let bytes = {
-                    let raw = bytes.eval_value_with_loc(scope);
+                    let raw = bytes
+                        .eval_value_with_loc(scope)
+                        .map_err(DecodeErrorKind::eval)?;
                     let seq_vals = raw.get_sequence().expect("bad type for DecodeBytes input");
                     seq_vals
                         .into_iter()
@@ -1498,7 +1836,8 @@ impl Decoder {
                         .collect::<Vec<u8>>()
                 };
                 let new_input = ReadCtxt::new(&bytes);
-                a.parse_with_loc(program, scope, new_input)?
+                run.memo
+                    .suspend(|| a.parse_with_loc(program, run, scope, new_input))?
                     .join(|(va, rem_input)| {
                         Ok(match rem_input.read_byte() {
                             Some((b, _)) => {
@@ -1515,32 +1854,32 @@ impl Decoder {
             }
             Decoder::ParseFromView(v_expr, a) => {
                 let view = Self::eval_view_expr_with_loc(scope, v_expr)?;
-                Ok(a.parse_with_loc(program, scope, view)?
+                Ok(a.parse_with_loc(program, run, scope, view)?
                     .map(|(va, _)| (va, input)))
             }
-            Decoder::LetFormat(da, name, db) => {
-                da.parse_with_loc(program, scope, input)?
-                    .join(|(va, input)| {
-                        let new_scope = LocScope::Single(LocSingleScope::new(scope, name, &va));
-                        db.parse_with_loc(program, &new_scope, input)
-                    })
-            }
+            Decoder::LetFormat(da, name, db) => da
+                .parse_with_loc(program, run, scope, input)?
+                .join(|(va, input)| {
+                    let new_scope = LocScope::Single(LocSingleScope::new(scope, name, &va));
+                    db.parse_with_loc(program, run, &new_scope, input)
+                }),
             Decoder::MonadSeq(da, db) => da
-                .parse_with_loc(program, scope, input)?
-                .join(|(_, input)| db.parse_with_loc(program, scope, input)),
+                .parse_with_loc(program, run, scope, input)?
+                .join(|(_, input)| db.parse_with_loc(program, run, scope, input)),
             Decoder::ForEach(expr, lbl, a) => {
-                let val = expr.eval_with_loc(scope);
+                let val = expr.eval_with_loc(scope).map_err(DecodeErrorKind::eval)?;
                 let seq = val.get_sequence().expect("bad type for ForEach input");
                 Ok(WithErr::fold(
                     (Vec::with_capacity(seq.len()), input),
                     seq,
                     |(mut v, input), e| {
                         let new_scope = LocScope::Single(LocSingleScope::new(scope, lbl, &e));
-                        Ok(a.parse_with_loc(program, &new_scope, input)?
-                            .map(|(va, next_input)| {
+                        Ok(a.parse_with_loc(program, run, &new_scope, input)?.map(
+                            |(va, next_input)| {
                                 v.push(va);
                                 (v, next_input)
-                            }))
+                            },
+                        ))
                     },
                 )?
                 .map(|(v, input)| {
@@ -1549,12 +1888,15 @@ impl Decoder {
                 }))
             }
             Decoder::RepeatCount(expr, a) => {
-                let count = expr.eval_value_with_loc(scope).unwrap_usize();
+                let count = expr
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_usize();
                 Ok(WithErr::fold(
                     (Vec::with_capacity(count), input),
                     0..count,
                     |(mut v, input), _| {
-                        Ok(a.parse_with_loc(program, scope, input)?
+                        Ok(a.parse_with_loc(program, run, scope, input)?
                             .map(|(va, next_input)| {
                                 v.push(va);
                                 (v, next_input)
@@ -1567,14 +1909,20 @@ impl Decoder {
                 }))
             }
             Decoder::RepeatBetween(reps_left_tree, min, max, a) => {
-                let min = min.eval_value_with_loc(scope).unwrap_usize();
-                let max = max.eval_value_with_loc(scope).unwrap_usize();
+                let min = min
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_usize();
+                let max = max
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_usize();
                 let mut res = WithErr::new((Vec::new(), input));
                 loop {
-                    let v = &res.as_ref().0;
+                    let (v, input) = res.as_ref();
                     // REVIEW - does the order of the conditions in the OR matter?
                     if reps_left_tree
-                        .matches(input)
+                        .matches(*input)
                         .ok_or(DecodeErrorKind::NoValidBranch {
                             offset: input.offset,
                         })?
@@ -1587,11 +1935,12 @@ impl Decoder {
                         break;
                     }
                     res = res.join(|(mut v, input)| {
-                        Ok(a.parse_with_loc(program, scope, input)?
-                            .map(move |(va, next_input)| {
+                        Ok(a.parse_with_loc(program, run, scope, input)?.map(
+                            move |(va, next_input)| {
                                 v.push(va);
                                 (v, next_input)
-                            }))
+                            },
+                        ))
                     })?;
                 }
                 Ok(res.map(|(v, input)| {
@@ -1600,9 +1949,12 @@ impl Decoder {
                 }))
             }
             Decoder::Maybe(expr, a) => {
-                let is_present = expr.eval_value_with_loc(scope).unwrap_bool();
+                let is_present = expr
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_bool();
                 if is_present {
-                    Ok(a.parse_with_loc(program, scope, input)?
+                    Ok(a.parse_with_loc(program, run, scope, input)?
                         .map(|(val, input)| (ParsedValue::Option(Some(Box::new(val))), input)))
                 } else {
                     Ok(WithErr::new((ParsedValue::Option(None), input)))
@@ -1613,12 +1965,16 @@ impl Decoder {
                 let mut res = WithErr::new((Vec::new(), input, false));
                 loop {
                     res = res.join(|(mut v, input, _done)| {
-                        Ok(a.parse_with_loc(program, scope, input)?
-                            .map(move |(va, next_input)| {
-                                let done = expr.eval_lambda_with_loc(scope, &va).unwrap_bool();
+                        a.parse_with_loc(program, run, scope, input)?.try_map(
+                            move |(va, next_input)| {
+                                let done = expr
+                                    .eval_lambda_with_loc(scope, &va)
+                                    .map_err(DecodeErrorKind::eval)?
+                                    .unwrap_bool();
                                 v.push(va);
-                                (v, next_input, done)
-                            }))
+                                Ok((v, next_input, done))
+                            },
+                        )
                     })?;
                     let done = res.as_ref().2;
                     if done {
@@ -1635,17 +1991,20 @@ impl Decoder {
                 let mut res = WithErr::new((Vec::new(), input, false));
                 loop {
                     res = res.join(|(mut v, input, _done)| {
-                        Ok(a.parse_with_loc(program, scope, input)?
-                            .map(|(va, next_input)| {
+                        a.parse_with_loc(program, run, scope, input)?
+                            .try_map(|(va, next_input)| {
                                 v.push(va);
                                 let vs = ParsedValue::from_evaluated_seq(v);
-                                let done = expr.eval_lambda_with_loc(scope, &vs).unwrap_bool();
+                                let done = expr
+                                    .eval_lambda_with_loc(scope, &vs)
+                                    .map_err(DecodeErrorKind::eval)?
+                                    .unwrap_bool();
                                 v = match vs {
                                     ParsedValue::Seq(v) => v.inner.into_vec(),
                                     _ => unreachable!(),
                                 };
-                                (v, next_input, done)
-                            }))
+                                Ok((v, next_input, done))
+                            })
                     })?;
                     break_if_done!(res => (v, input));
                 }
@@ -1654,7 +2013,9 @@ impl Decoder {
                     .map(|(v, input, _)| (ParsedValue::new_seq(v, start_offset, total_len), input)))
             }
             Decoder::AccumUntil(f_done, f_update, init, _vt, a) => {
-                let accum = init.eval_value_with_loc(scope);
+                let accum = init
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?;
                 let mut res = WithErr::new((Vec::new(), accum, input, false));
                 loop {
                     res = res.join(|(mut v, accum, input, _done)| {
@@ -1667,21 +2028,26 @@ impl Decoder {
                             )
                             .clone_into_value(),
                         ]));
-                        let is_done = f_done.eval_lambda_with_loc(scope, &done_arg).unwrap_bool();
+                        let is_done = f_done
+                            .eval_lambda_with_loc(scope, &done_arg)
+                            .map_err(DecodeErrorKind::eval)?
+                            .unwrap_bool();
                         if is_done {
                             return Ok(WithErr::new((v, accum, input, true)));
                         }
-                        Ok(a.parse_with_loc(program, scope, input)?.map(
+                        a.parse_with_loc(program, run, scope, input)?.try_map(
                             |(next_elem, next_input)| {
                                 v.push(next_elem.clone());
                                 let update_arg = ParsedValue::from_evaluated(Value::Tuple(vec![
                                     accum.clone(),
                                     next_elem.clone_into_value(),
                                 ]));
-                                let next_accum = f_update.eval_lambda_with_loc(scope, &update_arg);
-                                (v, next_accum, next_input, false)
+                                let next_accum = f_update
+                                    .eval_lambda_with_loc(scope, &update_arg)
+                                    .map_err(DecodeErrorKind::eval)?;
+                                Ok((v, next_accum, next_input, false))
                             },
-                        ))
+                        )
                     })?;
                     break_if_done!(res => (v, accum, input));
                 }
@@ -1702,21 +2068,24 @@ impl Decoder {
             }
 
             Decoder::Peek(a) => Ok(a
-                .parse_with_loc(program, scope, input)?
+                .parse_with_loc(program, run, scope, input)?
                 .map(|(v, _)| (v, input))),
             Decoder::PeekNot(a) => {
-                if a.parse_with_loc(program, scope, input).is_ok() {
+                if a.parse_with_loc(program, run, scope, input).is_ok() {
                     Err(DecodeErrorKind::loc_fail(scope, input))
                 } else {
                     Ok(WithErr::new((ParsedValue::unit_at(start_offset), input)))
                 }
             }
             Decoder::Slice(expr, a) => {
-                let size = expr.eval_value_with_loc(scope).unwrap_usize();
+                let size = expr
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_usize();
                 let (slice, input) = input
                     .split_at(size)
                     .ok_or(DecodeErrorKind::overrun(size, input.offset))?;
-                Ok(a.parse_with_loc(program, scope, slice)?
+                Ok(a.parse_with_loc(program, run, scope, slice)?
                     .map(|(v, _)| (v, input)))
             }
             Decoder::Bits(a) => {
@@ -1727,7 +2096,8 @@ impl Decoder {
                         bits.push((b & (1 << i)) >> i);
                     }
                 }
-                a.parse_with_loc(program, scope, ReadCtxt::new(&bits))?
+                run.memo
+                    .suspend(|| a.parse_with_loc(program, run, scope, ReadCtxt::new(&bits)))?
                     .join(|(v, bits)| {
                         let bytes_remain = bits.remaining().len() >> 3;
                         let bytes_read = input.remaining().len() - bytes_remain;
@@ -1738,63 +2108,85 @@ impl Decoder {
                     })
             }
             Decoder::WithRelativeOffset(base_addr, expr, a) => {
-                let base_addr = base_addr.eval_value_with_loc(scope).unwrap_usize();
-                let offset = expr.eval_value_with_loc(scope).unwrap_usize();
+                let base_addr = base_addr
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_usize();
+                let offset = expr
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_usize();
                 let abs_offset = base_addr + offset;
                 let seek_input = input
                     .seek_to(abs_offset)
                     .ok_or(DecodeErrorKind::bad_seek(abs_offset, input.input.len()))?;
-                Ok(a.parse_with_loc(program, scope, seek_input)?
+                Ok(a.parse_with_loc(program, run, scope, seek_input)?
                     .map(|(v, _)| (v, input)))
             }
             Decoder::Map(d, expr) => {
-                Ok(d.parse_with_loc(program, scope, input)?
-                    .map(|(orig, input)| {
-                        let v = expr.eval_lambda_with_loc(scope, &orig);
+                d.parse_with_loc(program, run, scope, input)?
+                    .try_map(|(orig, input)| {
+                        let v = expr
+                            .eval_lambda_with_loc(scope, &orig)
+                            .map_err(DecodeErrorKind::eval)?;
                         let image = ParsedValue::inherit(&orig, v);
-                        (ParsedValue::Mapped(Box::new(orig), Box::new(image)), input)
-                    }))
+                        Ok((ParsedValue::Mapped(Box::new(orig), Box::new(image)), input))
+                    })
             }
             Decoder::Where(d, cond) => {
-                d.parse_with_loc(program, scope, input)?.join(|(v, input)| {
-                    let Condition { expr, severity } = cond;
-                    match expr.eval_lambda_with_loc(scope, &v).unwrap_bool() {
-                        true => Ok(WithErr::new((v, input))),
-                        false => {
-                            let err = DecodeErrorKind::loc_bad_where(
-                                scope,
-                                expr.clone(),
-                                Box::new(v.clone()),
-                            );
-                            if severity.is_strict() {
-                                Err(err)
-                            } else {
-                                Ok(WithErr::with_err((v, input), err))
+                let offset = input.offset;
+                d.parse_with_loc(program, run, scope, input)?
+                    .join(|(v, input)| {
+                        let Condition { expr, severity } = cond;
+                        match expr
+                            .eval_lambda_with_loc(scope, &v)
+                            .map_err(DecodeErrorKind::eval)?
+                            .unwrap_bool()
+                        {
+                            true => Ok(WithErr::new((v, input))),
+                            false => {
+                                let err = DecodeErrorKind::loc_bad_where(
+                                    scope,
+                                    expr.clone(),
+                                    Box::new(v.clone()),
+                                    offset,
+                                );
+                                if severity.is_strict() {
+                                    Err(err)
+                                } else {
+                                    Ok(WithErr::with_err((v, input), err))
+                                }
                             }
                         }
-                    }
-                })
+                    })
             }
             Decoder::Compute(expr) => {
-                let v = expr.eval_with_loc(scope);
+                let v = expr.eval_with_loc(scope).map_err(DecodeErrorKind::eval)?;
                 Ok(WithErr::new((v.as_ref().clone(), input)))
             }
             Decoder::Let(name, expr, d) => {
-                let v = expr.eval_with_loc(scope).as_ref().clone();
+                let v = expr
+                    .eval_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .as_ref()
+                    .clone();
                 let let_scope = LocSingleScope::new(scope, name, &v);
-                d.parse_with_loc(program, &LocScope::Single(let_scope), input)
+                d.parse_with_loc(program, run, &LocScope::Single(let_scope), input)
             }
             Decoder::LetView(name, d) => {
                 let view = input;
                 let let_scope = LocViewScope::new(scope, name, view);
-                d.parse_with_loc(program, &LocScope::View(let_scope), input)
+                d.parse_with_loc(program, run, &LocScope::View(let_scope), input)
             }
             Decoder::Match(head, branches) => {
-                let head = head.eval_with_loc(scope);
+                let head = head.eval_with_loc(scope).map_err(DecodeErrorKind::eval)?;
                 for (index, (pattern, decoder)) in branches.iter().enumerate() {
-                    if let Some(pattern_scope) = head.matches(scope, pattern) {
+                    if let Some(pattern_scope) = head
+                        .matches(scope, pattern)
+                        .map_err(DecodeErrorKind::eval)?
+                    {
                         return Ok(decoder
-                            .parse_with_loc(program, &LocScope::Multi(&pattern_scope), input)?
+                            .parse_with_loc(program, run, &LocScope::Multi(&pattern_scope), input)?
                             .map(|(v, input)| (ParsedValue::Branch(index, Box::new(v)), input)));
                     }
                 }
@@ -1805,12 +2197,18 @@ impl Decoder {
                 );
             }
             Decoder::Dynamic(name, DynFormat::Huffman(lengths_expr, opt_values_expr), d) => {
-                let lengths_val = lengths_expr.eval_with_loc(scope);
+                let lengths_val = lengths_expr
+                    .eval_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?;
                 let lengths = value_to_vec_usize(lengths_val.as_ref());
                 let lengths = match opt_values_expr {
                     None => lengths,
                     Some(e) => {
-                        let values = value_to_vec_usize(e.eval_with_loc(scope).as_ref());
+                        let values = value_to_vec_usize(
+                            e.eval_with_loc(scope)
+                                .map_err(DecodeErrorKind::eval)?
+                                .as_ref(),
+                        );
                         let mut new_lengths = [0].repeat(values.len());
                         for i in 0..lengths.len() {
                             new_lengths[values[i]] = lengths[i];
@@ -1821,21 +2219,24 @@ impl Decoder {
                 let f = make_huffman_codes(&lengths);
                 let dyn_d = Compiler::compile_one(&f).unwrap();
                 let child_scope = LocDecoderScope::new(scope, name, dyn_d);
-                d.parse_with_loc(program, &LocScope::Decoder(child_scope), input)
+                d.parse_with_loc(program, run, &LocScope::Decoder(child_scope), input)
             }
             Decoder::Apply(name) => {
                 let d = scope.get_decoder_by_name(name);
-                d.parse_with_loc(program, scope, input)
+                d.parse_with_loc(program, run, scope, input)
             }
             Decoder::LiftedOption(None) => Ok(WithErr::new((
                 ParsedValue::from_evaluated(Value::Option(None)),
                 input,
             ))),
             Decoder::LiftedOption(Some(d)) => Ok(d
-                .parse_with_loc(program, scope, input)?
+                .parse_with_loc(program, run, scope, input)?
                 .map(|(v, input)| (ParsedValue::Option(Some(Box::new(v))), input))),
             Decoder::CaptureBytes(v_expr, len) => {
-                let len = len.eval_value_with_loc(scope).unwrap_usize();
+                let len = len
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_usize();
 
                 let view_window = Self::eval_view_expr_with_loc(scope, v_expr)?;
                 // accumulate `len` bytes into a Vec<Value>
@@ -1862,7 +2263,10 @@ impl Decoder {
                 )))
             }
             Decoder::ReadArray(v_expr, len, kind) => {
-                let len = len.eval_value_with_loc(scope).unwrap_usize();
+                let len = len
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_usize();
                 let view_window = Self::eval_view_expr_with_loc(scope, v_expr)?;
 
                 // REVIEW - hardcoded big-endianness
@@ -1886,22 +2290,46 @@ impl Decoder {
                 });
                 Ok(WithErr::new((v, input)))
             }
-            Decoder::Permit(inner, dft) => Ok(downgrade_error_with(
-                inner
-                    .parse_with_loc(program, scope, input)
-                    .map(|ok| ok.map(|(v, input)| (ParsedValue::Permit(Ok(Box::new(v))), input))),
-                || {
-                    (
-                        ParsedValue::Permit(Err(Some(Box::new(
-                            dft.eval_with_loc(scope).into_owned(),
-                        )))),
-                        input,
-                    )
-                },
-            )),
+            Decoder::Permit(inner, dft) => {
+                let res = inner
+                    .parse_with_loc(program, run, scope, input)
+                    .map(|ok| ok.map(|(v, input)| (ParsedValue::Permit(Ok(Box::new(v))), input)));
+                let fallback = match res {
+                    Ok(_) => None,
+                    Err(_) => Some(Box::new(
+                        dft.eval_with_loc(scope)
+                            .map_err(DecodeErrorKind::eval)?
+                            .into_owned(),
+                    )),
+                };
+                Ok(downgrade_error_with(res, || {
+                    (ParsedValue::Permit(Err(fallback)), input)
+                }))
+            }
+            Decoder::Resync(inner, pattern) => {
+                match inner.parse_with_loc(program, run, scope, input) {
+                    Ok(res) => Ok(res.map(|(v, input)| {
+                        (ParsedValue::wrap_variant(Label::Borrowed("ok"), v), input)
+                    })),
+                    Err(e) if !e.is_recoverable() => Err(e),
+                    Err(e) => {
+                        log::warn!("resynchronizing after error: {e}");
+                        let data = input.remaining();
+                        let len = resync_offset(data, pattern);
+                        let (_, rest) = input.split_at(len).unwrap();
+                        let mut skipped =
+                            ParsedValue::from_evaluated(Value::resync_skipped(e, &data[..len]));
+                        skipped.translate(ParseLoc::InBuffer {
+                            offset: start_offset,
+                            length: len,
+                        });
+                        Ok(WithErr::new((skipped, rest)))
+                    }
+                }
+            }
             #[cfg(feature = "format_enforce")]
             Decoder::Enforce(inner) => {
-                let res = inner.parse_with_loc(program, scope, input)?;
+                let res = inner.parse_with_loc(program, run, scope, input)?;
                 match res.into_strict() {
                     Ok((v, input)) => Ok(WithErr::new((v, input))),
                     Err(mut errs) => {
@@ -1924,7 +2352,10 @@ impl Decoder {
                 Ok(view)
             }
             ViewExpr::Offset(base, offset) => {
-                let offset = offset.eval_value_with_loc(scope).unwrap_usize();
+                let offset = offset
+                    .eval_value_with_loc(scope)
+                    .map_err(DecodeErrorKind::eval)?
+                    .unwrap_usize();
                 let base_view = Self::eval_view_expr_with_loc(scope, base)?;
                 let Some((_, view_window)) = base_view.split_at(offset) else {
                     return Err(DecodeErrorKind::overrun(offset, base_view.offset));
@@ -1939,34 +2370,12 @@ fn read_base(
     buf: ReadCtxt<'_>,
     kind: BaseKind<Endian>,
 ) -> Result<(ParsedValue, ReadCtxt<'_>), DecodeErrorKind<ParsedValue>> {
-    let (val, new_buf) = match kind {
-        BaseKind::U8 => {
-            let Some((byte, new_buf)) = buf.read_byte() else {
-                return Err(DecodeErrorKind::overbyte(buf.offset));
-            };
-            (Value::U8(byte), new_buf)
-        }
-        BaseKind::U16BE => {
-            let Some((val, new_buf)) = buf.read_u16be() else {
-                return Err(DecodeErrorKind::overrun(kind.size(), buf.offset));
-            };
-            (Value::U16(val), new_buf)
-        }
-        BaseKind::U32BE => {
-            let Some((val, new_buf)) = buf.read_u32be() else {
-                return Err(DecodeErrorKind::overrun(kind.size(), buf.offset));
-            };
-            (Value::U32(val), new_buf)
-        }
-        BaseKind::U64BE => {
-            let Some((val, new_buf)) = buf.read_u64be() else {
-                return Err(DecodeErrorKind::overrun(kind.size(), buf.offset));
-            };
-            (Value::U64(val), new_buf)
-        }
-        BaseKind::U16LE | BaseKind::U32LE | BaseKind::U64LE => {
-            unimplemented!("little-endian read-base parses not yet implemented")
-        }
+    let Some((val, new_buf)) = crate::decoder::read_base_value(buf, kind) else {
+        return Err(if kind.size() == 1 {
+            DecodeErrorKind::overbyte(buf.offset)
+        } else {
+            DecodeErrorKind::overrun(kind.size(), buf.offset)
+        });
     };
     Ok((ParsedValue::new_flat(val, buf.offset, kind.size()), new_buf))
 }
@@ -1,4 +1,4 @@
-use crate::valuetype::BaseType;
+use crate::valuetype::{BaseType, SignedIntType, ValueType};
 use serde::Serialize;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Default)]
@@ -33,12 +33,19 @@ impl Endian {
 
 /// Marker-type for various widths of machine-integer parse-directives,
 /// with support for generic decoration with either `()` or [`Endian`]
+///
+/// Signed variants are read as the unsigned integer of the same width and
+/// reinterpreted bitwise (two's complement).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
 pub enum BaseKind<X: Copy = ()> {
     U8,
     U16Ext(X),
     U32Ext(X),
     U64Ext(X),
+    I8,
+    I16Ext(X),
+    I32Ext(X),
+    I64Ext(X),
 }
 
 impl BaseKind {
@@ -53,6 +60,18 @@ impl BaseKind {
     pub const U16LE: BaseKind<Endian> = BaseKind::U16Ext(Endian::Le);
     pub const U32LE: BaseKind<Endian> = BaseKind::U32Ext(Endian::Le);
     pub const U64LE: BaseKind<Endian> = BaseKind::U64Ext(Endian::Le);
+
+    pub const I16: BaseKind<()> = BaseKind::I16Ext(());
+    pub const I32: BaseKind<()> = BaseKind::I32Ext(());
+    pub const I64: BaseKind<()> = BaseKind::I64Ext(());
+
+    pub const I16BE: BaseKind<Endian> = BaseKind::I16Ext(Endian::Be);
+    pub const I32BE: BaseKind<Endian> = BaseKind::I32Ext(Endian::Be);
+    pub const I64BE: BaseKind<Endian> = BaseKind::I64Ext(Endian::Be);
+
+    pub const I16LE: BaseKind<Endian> = BaseKind::I16Ext(Endian::Le);
+    pub const I32LE: BaseKind<Endian> = BaseKind::I32Ext(Endian::Le);
+    pub const I64LE: BaseKind<Endian> = BaseKind::I64Ext(Endian::Le);
 }
 
 impl BaseKind {
@@ -62,11 +81,28 @@ impl BaseKind {
             BaseKind::U16Ext(_) => "U16",
             BaseKind::U32Ext(_) => "U32",
             BaseKind::U64Ext(_) => "U64",
+            BaseKind::I8 => "I8",
+            BaseKind::I16Ext(_) => "I16",
+            BaseKind::I32Ext(_) => "I32",
+            BaseKind::I64Ext(_) => "I64",
         }
     }
 }
 
 impl BaseKind<Endian> {
+    /// Returns the byte-order of the base-kind, which is nominally [`Endian::Be`] for single-byte kinds.
+    pub const fn endian(&self) -> Endian {
+        match self {
+            BaseKind::U8 | BaseKind::I8 => Endian::Be,
+            BaseKind::U16Ext(e)
+            | BaseKind::U32Ext(e)
+            | BaseKind::U64Ext(e)
+            | BaseKind::I16Ext(e)
+            | BaseKind::I32Ext(e)
+            | BaseKind::I64Ext(e) => *e,
+        }
+    }
+
     pub const fn name(&self) -> &'static str {
         use Endian::*;
         match self {
@@ -79,6 +115,16 @@ impl BaseKind<Endian> {
             BaseKind::U16Ext(Le) => "U16Le",
             BaseKind::U32Ext(Le) => "U32Le",
             BaseKind::U64Ext(Le) => "U64Le",
+
+            BaseKind::I8 => "I8",
+
+            BaseKind::I16Ext(Be) => "I16Be",
+            BaseKind::I32Ext(Be) => "I32Be",
+            BaseKind::I64Ext(Be) => "I64Be",
+
+            BaseKind::I16Ext(Le) => "I16Le",
+            BaseKind::I32Ext(Le) => "I32Le",
+            BaseKind::I64Ext(Le) => "I64Le",
         }
     }
 }
@@ -93,21 +139,64 @@ impl<X: Copy> BaseKind<X> {
     /// Returns the size for the given base-kind in bytes.
     pub const fn size(&self) -> usize {
         match self {
-            BaseKind::U8 => std::mem::size_of::<u8>(),
-            BaseKind::U16Ext(..) => std::mem::size_of::<u16>(),
-            BaseKind::U32Ext(..) => std::mem::size_of::<u32>(),
-            BaseKind::U64Ext(..) => std::mem::size_of::<u64>(),
+            BaseKind::U8 | BaseKind::I8 => std::mem::size_of::<u8>(),
+            BaseKind::U16Ext(..) | BaseKind::I16Ext(..) => std::mem::size_of::<u16>(),
+            BaseKind::U32Ext(..) | BaseKind::I32Ext(..) => std::mem::size_of::<u32>(),
+            BaseKind::U64Ext(..) | BaseKind::I64Ext(..) => std::mem::size_of::<u64>(),
         }
     }
-}
 
-impl<X: Copy> From<BaseKind<X>> for BaseType {
-    fn from(value: BaseKind<X>) -> Self {
-        match value {
-            BaseKind::U8 => BaseType::U8,
+    /// Returns `true` if the base-kind is a signed (two's complement) integer.
+    pub const fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            BaseKind::I8 | BaseKind::I16Ext(..) | BaseKind::I32Ext(..) | BaseKind::I64Ext(..)
+        )
+    }
+
+    /// Returns the unsigned base-kind of the same width (and decoration) as `self`.
+    ///
+    /// This is the kind of the raw bytes that are read before any signed reinterpretation.
+    pub const fn to_unsigned(self) -> BaseKind<X> {
+        match self {
+            BaseKind::U8 | BaseKind::I8 => BaseKind::U8,
+            BaseKind::U16Ext(x) | BaseKind::I16Ext(x) => BaseKind::U16Ext(x),
+            BaseKind::U32Ext(x) | BaseKind::I32Ext(x) => BaseKind::U32Ext(x),
+            BaseKind::U64Ext(x) | BaseKind::I64Ext(x) => BaseKind::U64Ext(x),
+        }
+    }
+
+    /// Returns the signed integer type produced by `self`, or `None` if it is unsigned.
+    pub const fn signed_type(&self) -> Option<SignedIntType> {
+        match self {
+            BaseKind::I8 => Some(SignedIntType::I8),
+            BaseKind::I16Ext(..) => Some(SignedIntType::I16),
+            BaseKind::I32Ext(..) => Some(SignedIntType::I32),
+            BaseKind::I64Ext(..) => Some(SignedIntType::I64),
+            BaseKind::U8 | BaseKind::U16Ext(..) | BaseKind::U32Ext(..) | BaseKind::U64Ext(..) => {
+                None
+            }
+        }
+    }
+
+    /// Returns the unsigned [`BaseType`] of the raw value read by `self`.
+    ///
+    /// For signed kinds, this is the type of the bits prior to reinterpretation.
+    pub const fn raw_base_type(&self) -> BaseType {
+        match self.to_unsigned() {
             BaseKind::U16Ext(..) => BaseType::U16,
             BaseKind::U32Ext(..) => BaseType::U32,
             BaseKind::U64Ext(..) => BaseType::U64,
+            _ => BaseType::U8,
+        }
+    }
+}
+
+impl<X: Copy> From<BaseKind<X>> for ValueType {
+    fn from(value: BaseKind<X>) -> Self {
+        match value.signed_type() {
+            Some(s) => ValueType::Signed(s),
+            None => ValueType::Base(value.raw_base_type()),
         }
     }
 }
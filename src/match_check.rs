@@ -0,0 +1,1051 @@
+//! Static exhaustiveness and reachability checking for `Expr::Match` and `Format::Match`.
+//!
+//! Every match within a format definition is checked against the type of its head expression, as inferred
+//! within that definition, using the usefulness algorithm of Maranget's "Warnings for pattern matching"
+//! (2007): a match is non-exhaustive if a wildcard would still be useful after all of its (unguarded) arms,
+//! and an arm is unreachable if its pattern is useless with respect to the unguarded arms that precede it.
+//!
+//! The set of values a pattern can distinguish is derived from the head type: variant sets come from
+//! `ValueType::Union`, integer domains from the width of the type (with `Pattern::Int` and friends matching
+//! ranges within it), and uncovered integers are reported as sorted, merged ranges. Sequences and characters are
+//! treated as having infinitely many constructors, so matches over them are only exhaustive with a
+//! catch-all arm.
+use std::fmt;
+use std::rc::Rc;
+
+use crate::output::tree::TreePrinter;
+use crate::precedence::Precedence;
+use crate::{
+    BaseType, DynFormat, Expr, Format, FormatModule, Label, Pattern, SignedIntType, TypeScope,
+    ValueType, ViewExpr, ViewFormat,
+};
+
+/// Problem found with a match within a format definition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchDiagnostic {
+    /// Name of the format (or function) definition in which the match occurs
+    pub format: Label,
+    /// Head expression of the match, as rendered by the tree printer
+    pub head: String,
+    pub kind: MatchDiagnosticKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatchDiagnosticKind {
+    /// Some values of the head are not matched by any arm, as exemplified by `witness` (rendered as a pattern)
+    NonExhaustive { witness: String },
+    /// The arm at index `arm` can never be selected, as every value it matches is matched by an earlier arm
+    Unreachable { arm: usize },
+}
+
+impl fmt::Display for MatchDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            MatchDiagnosticKind::NonExhaustive { witness } => write!(
+                f,
+                "{}: non-exhaustive match on `{}`: `{witness}` not covered",
+                self.format, self.head
+            ),
+            MatchDiagnosticKind::Unreachable { arm } => write!(
+                f,
+                "{}: unreachable arm #{arm} in match on `{}`",
+                self.format, self.head
+            ),
+        }
+    }
+}
+
+/// Checks every match in every format (and function) definition of `module`, returning the problems found in order of
+/// definition.
+pub fn check_matches(module: &FormatModule) -> Vec<MatchDiagnostic> {
+    let mut checker = Checker {
+        module,
+        format: Label::Borrowed(""),
+        diagnostics: Vec::new(),
+    };
+    for level in 0..module.formats.len() {
+        checker.format = Label::from(module.get_name(level).to_owned());
+        let mut scope = TypeScope::new();
+        for (arg_name, arg_type) in module.get_args(level) {
+            scope.push(arg_name.clone(), arg_type.clone());
+        }
+        for view_name in module.get_view_args(level) {
+            scope.push_view(view_name.clone());
+        }
+        checker.check_format(&scope, module.get_format(level));
+    }
+    for func in module.functions() {
+        let Some(body) = &func.body else {
+            continue;
+        };
+        checker.format = func.name.clone();
+        let mut scope = TypeScope::new();
+        for (param_name, param_type) in &func.params {
+            scope.push(param_name.clone(), param_type.clone());
+        }
+        checker.check_expr(&scope, body);
+    }
+    checker.diagnostics
+}
+
+struct Checker<'a> {
+    module: &'a FormatModule,
+    /// Name of the format definition currently being checked
+    format: Label,
+    diagnostics: Vec<MatchDiagnostic>,
+}
+
+impl Checker<'_> {
+    fn check_format(&mut self, scope: &TypeScope<'_>, format: &Format) {
+        match format {
+            Format::ItemVar(_, args, views) => {
+                for arg in args {
+                    self.check_expr(scope, arg);
+                }
+                for view in views {
+                    self.check_view(scope, view);
+                }
+            }
+            Format::GenericVar(index, formats, args, views) => {
+                // NOTE - the format arguments are checked in the scope of the parameters of the generic format
+                let generic = self.module.get_generic(*index);
+                let mut child_scope = TypeScope::new();
+                for (name, arg_type) in &generic.args {
+                    child_scope.push(name.clone(), arg_type.clone());
+                }
+                for name in &generic.views {
+                    child_scope.push_view(name.clone());
+                }
+                for f in formats {
+                    self.check_format(&child_scope, f);
+                }
+                for arg in args {
+                    self.check_expr(scope, arg);
+                }
+                for view in views {
+                    self.check_view(scope, view);
+                }
+            }
+            Format::Fail
+            | Format::EndOfInput
+            | Format::Align(_)
+            | Format::Byte(_)
+            | Format::Apply(_)
+            | Format::Pos
+            | Format::SkipRemainder
+            | Format::LiftedOption(None) => {}
+            Format::Variant(_, f)
+            | Format::Repeat(f)
+            | Format::Repeat1(f)
+            | Format::Peek(f)
+            | Format::PeekNot(f)
+            | Format::Bits(f)
+            | Format::Hint(_, f)
+            | Format::Phantom(f)
+            | Format::Resync(f, _)
+            | Format::LiftedOption(Some(f)) => self.check_format(scope, f),
+            #[cfg(feature = "format_enforce")]
+            Format::Enforce(f) => self.check_format(scope, f),
+            Format::Union(branches)
+            | Format::UnionNondet(branches)
+            | Format::Tuple(branches)
+            | Format::Sequence(branches) => {
+                for f in branches {
+                    self.check_format(scope, f);
+                }
+            }
+            Format::RepeatCount(expr, f)
+            | Format::Maybe(expr, f)
+            | Format::Slice(expr, f)
+            | Format::DecodeBytes(expr, f) => {
+                self.check_expr(scope, expr);
+                self.check_format(scope, f);
+            }
+            Format::Permit(f, expr) => {
+                self.check_format(scope, f);
+                self.check_expr(scope, expr);
+            }
+            Format::RepeatBetween(min, max, f) | Format::WithRelativeOffset(min, max, f) => {
+                self.check_expr(scope, min);
+                self.check_expr(scope, max);
+                self.check_format(scope, f);
+            }
+            Format::RepeatUntilLast(lambda, f) => {
+                if let Ok(t) = self.module.infer_format_type(scope, f) {
+                    self.check_lambda(scope, lambda, t);
+                }
+                self.check_format(scope, f);
+            }
+            Format::RepeatUntilSeq(lambda, f) => {
+                if let Ok(t) = self.module.infer_format_type(scope, f) {
+                    self.check_lambda(scope, lambda, ValueType::Seq(Box::new(t)));
+                }
+                self.check_format(scope, f);
+            }
+            Format::AccumUntil(lambda_acc_seq, lambda_acc_val, init, vt, f) => {
+                self.check_expr(scope, init);
+                if let (Ok(t), Expr::Lambda(head, body)) = (
+                    self.module.infer_format_type(scope, f),
+                    lambda_acc_seq.as_ref(),
+                ) {
+                    let acc = vt.as_ref().clone();
+                    let mut child_scope = TypeScope::child(scope);
+                    child_scope.push(
+                        head.clone(),
+                        ValueType::Tuple(vec![acc.clone(), ValueType::Seq(Box::new(t.clone()))]),
+                    );
+                    self.check_expr(&child_scope, body);
+                    // NOTE - the second lambda is typed within the scope of the first (see `infer_format_type`)
+                    self.check_lambda(&child_scope, lambda_acc_val, ValueType::Tuple(vec![acc, t]));
+                }
+                self.check_format(scope, f);
+            }
+            Format::ForEach(expr, name, f) => {
+                self.check_expr(scope, expr);
+                if let Ok(ValueType::Seq(elem_t)) = expr.infer_type(scope) {
+                    let mut child_scope = TypeScope::child(scope);
+                    child_scope.push(name.clone(), *elem_t);
+                    self.check_format(&child_scope, f);
+                }
+            }
+            Format::Map(f, lambda) => {
+                self.check_format(scope, f);
+                if let Ok(t) = self.module.infer_format_type(scope, f) {
+                    self.check_lambda(scope, lambda, t);
+                }
+            }
+            Format::Where(f, cond) => {
+                self.check_format(scope, f);
+                if let Ok(t) = self.module.infer_format_type(scope, f) {
+                    self.check_lambda(scope, cond.as_ref(), t);
+                }
+            }
+            Format::Compute(expr) => self.check_expr(scope, expr),
+            Format::Let(name, expr, f) => {
+                self.check_expr(scope, expr);
+                if let Ok(t) = expr.infer_type(scope) {
+                    let mut child_scope = TypeScope::child(scope);
+                    child_scope.push(name.clone(), t);
+                    self.check_format(&child_scope, f);
+                }
+            }
+            Format::Match(head, branches) => {
+                self.check_expr(scope, head);
+                let Ok(head_type) = head.infer_type(scope) else {
+                    return;
+                };
+                self.check_arms(head, &head_type, branches.iter().map(|(pat, _)| pat));
+                let head_type = Rc::new(head_type);
+                for (pat, f) in branches {
+                    let mut child_scope = TypeScope::child(scope);
+                    pat.build_scope(&mut child_scope, head_type.clone());
+                    if let Some(guard) = pat.split_guard().1 {
+                        self.check_expr(&child_scope, guard);
+                    }
+                    self.check_format(&child_scope, f);
+                }
+            }
+            Format::Dynamic(name, DynFormat::Huffman(lengths, opt_values), f) => {
+                self.check_expr(scope, lengths);
+                if let Some(values) = opt_values {
+                    self.check_expr(scope, values);
+                }
+                let mut child_scope = TypeScope::child(scope);
+                child_scope.push_format(name.clone(), ValueType::U16);
+                self.check_format(&child_scope, f);
+            }
+            Format::Native(_, args) => {
+                for arg in args {
+                    self.check_expr(scope, arg);
+                }
+            }
+            Format::LetFormat(f0, name, f) => {
+                self.check_format(scope, f0);
+                if let Ok(t0) = self.module.infer_format_type(scope, f0) {
+                    let mut child_scope = TypeScope::child(scope);
+                    child_scope.push(name.clone(), t0);
+                    self.check_format(&child_scope, f);
+                }
+            }
+            Format::MonadSeq(f0, f) => {
+                self.check_format(scope, f0);
+                self.check_format(scope, f);
+            }
+            Format::LetView(name, f) => {
+                let mut child_scope = TypeScope::child(scope);
+                child_scope.push_view(name.clone());
+                self.check_format(&child_scope, f);
+            }
+            Format::WithView(view, view_format) => {
+                self.check_view(scope, view);
+                match view_format {
+                    ViewFormat::CaptureBytes(len) | ViewFormat::ReadArray(len, _) => {
+                        self.check_expr(scope, len)
+                    }
+                    ViewFormat::ReifyView => {}
+                }
+            }
+            Format::ParseFromView(view, f) => {
+                self.check_view(scope, view);
+                self.check_format(scope, f);
+            }
+        }
+    }
+
+    fn check_view(&mut self, scope: &TypeScope<'_>, view: &ViewExpr) {
+        match view {
+            ViewExpr::Var(_) => {}
+            ViewExpr::Offset(base, offset) => {
+                self.check_view(scope, base);
+                self.check_expr(scope, offset);
+            }
+        }
+    }
+
+    /// Checks the body of `lambda` with its parameter bound to `param_type`.
+    fn check_lambda(&mut self, scope: &TypeScope<'_>, lambda: &Expr, param_type: ValueType) {
+        match lambda {
+            Expr::Lambda(name, body) => {
+                let mut child_scope = TypeScope::child(scope);
+                child_scope.push(name.clone(), param_type);
+                self.check_expr(&child_scope, body);
+            }
+            other => self.check_expr(scope, other),
+        }
+    }
+
+    fn check_expr(&mut self, scope: &TypeScope<'_>, expr: &Expr) {
+        match expr {
+            Expr::Var(_)
+            | Expr::Bool(_)
+            | Expr::U8(_)
+            | Expr::U16(_)
+            | Expr::U32(_)
+            | Expr::U64(_)
+            | Expr::Numeric(_)
+            | Expr::LiftOption(None) => {}
+            Expr::Tuple(exprs) | Expr::Seq(exprs) | Expr::Call(_, _, exprs) => {
+                for x in exprs {
+                    self.check_expr(scope, x);
+                }
+            }
+            Expr::Record(fields) => {
+                for (_, x) in fields {
+                    self.check_expr(scope, x);
+                }
+            }
+            Expr::TupleProj(x, _)
+            | Expr::RecordProj(x, _)
+            | Expr::Variant(_, x)
+            | Expr::Unary(_, x)
+            | Expr::AsU8(x)
+            | Expr::AsU16(x)
+            | Expr::AsU32(x)
+            | Expr::AsU64(x)
+            | Expr::AsChar(x)
+            | Expr::F32FromBits(x)
+            | Expr::F64FromBits(x)
+            | Expr::FixedFromBits(_, x)
+            | Expr::U16Be(x)
+            | Expr::U16Le(x)
+            | Expr::U32Be(x)
+            | Expr::U32Le(x)
+            | Expr::U64Be(x)
+            | Expr::U64Le(x)
+            | Expr::SeqLength(x)
+            | Expr::Reverse(x)
+            | Expr::LiftOption(Some(x)) => self.check_expr(scope, x),
+            Expr::IntRel(_, x, y)
+            | Expr::Arith(_, x, y)
+            | Expr::SeqIx(x, y)
+            | Expr::EnumFromTo(x, y)
+            | Expr::Dup(x, y)
+            | Expr::Zip(x, y)
+            | Expr::Append(x, y) => {
+                self.check_expr(scope, x);
+                self.check_expr(scope, y);
+            }
+            Expr::SubSeq(x, y, z) | Expr::SubSeqInflate(x, y, z) => {
+                self.check_expr(scope, x);
+                self.check_expr(scope, y);
+                self.check_expr(scope, z);
+            }
+            Expr::Match(head, branches) => {
+                self.check_expr(scope, head);
+                let Ok(head_type) = head.infer_type(scope) else {
+                    return;
+                };
+                self.check_arms(head, &head_type, branches.iter().map(|(pat, _)| pat));
+                let head_type = Rc::new(head_type);
+                for (pat, x) in branches {
+                    let mut child_scope = TypeScope::child(scope);
+                    pat.build_scope(&mut child_scope, head_type.clone());
+                    if let Some(guard) = pat.split_guard().1 {
+                        self.check_expr(&child_scope, guard);
+                    }
+                    self.check_expr(&child_scope, x);
+                }
+            }
+            Expr::Destructure(head, pat, body) => {
+                self.check_expr(scope, head);
+                if let Ok(head_type) = head.infer_type(scope) {
+                    let mut child_scope = TypeScope::child(scope);
+                    pat.build_scope(&mut child_scope, Rc::new(head_type));
+                    self.check_expr(&child_scope, body);
+                }
+            }
+            Expr::Lambda(name, body) => {
+                // NOTE - lambdas in argument position are handled by their parent, so the parameter type is unknown here
+                let mut child_scope = TypeScope::child(scope);
+                child_scope.push(name.clone(), ValueType::Any);
+                self.check_expr(&child_scope, body);
+            }
+            Expr::FlatMap(lambda, seq)
+            | Expr::Filter(lambda, seq)
+            | Expr::Any(lambda, seq)
+            | Expr::All(lambda, seq)
+            | Expr::FindByKey(_, lambda, _, seq) => {
+                if let Expr::FindByKey(_, _, query, _) = expr {
+                    self.check_expr(scope, query);
+                }
+                self.check_expr(scope, seq);
+                if let Ok(ValueType::Seq(elem_t)) = seq.infer_type(scope) {
+                    self.check_lambda(scope, lambda, *elem_t);
+                }
+            }
+            Expr::IsSortedBy(lambda, seq) => {
+                self.check_expr(scope, seq);
+                if let Ok(ValueType::Seq(elem_t)) = seq.infer_type(scope) {
+                    let param_type = ValueType::Tuple(vec![(*elem_t).clone(), *elem_t]);
+                    self.check_lambda(scope, lambda, param_type);
+                }
+            }
+            Expr::FlatMapAccum(lambda, accum, accum_type, seq)
+            | Expr::LeftFold(lambda, accum, accum_type, seq) => {
+                self.check_expr(scope, accum);
+                self.check_expr(scope, seq);
+                if let Ok(ValueType::Seq(elem_t)) = seq.infer_type(scope) {
+                    let param_type = ValueType::Tuple(vec![accum_type.as_ref().clone(), *elem_t]);
+                    self.check_lambda(scope, lambda, param_type);
+                }
+            }
+            Expr::FlatMapList(lambda, ret_type, seq) => {
+                self.check_expr(scope, seq);
+                if let Ok(ValueType::Seq(elem_t)) = seq.infer_type(scope) {
+                    let param_type = ValueType::Tuple(vec![
+                        ValueType::Seq(Box::new(ret_type.as_ref().clone())),
+                        *elem_t,
+                    ]);
+                    self.check_lambda(scope, lambda, param_type);
+                }
+            }
+        }
+    }
+
+    /// Checks the arms of a match on `head`, whose patterns are given in order, for exhaustiveness and reachability.
+    fn check_arms<'p>(
+        &mut self,
+        head: &Expr,
+        head_type: &ValueType,
+        patterns: impl Iterator<Item = &'p Pattern>,
+    ) {
+        let tys = [head_type.clone()];
+        let mut rows: Vec<Vec<Pat>> = Vec::new();
+        for (arm, pat) in patterns.enumerate() {
+            let (pat, guard) = pat.split_guard();
+            let alts: Vec<Pat> = pat
+                .alternatives()
+                .iter()
+                .map(|alt| Pat::lower(alt, head_type))
+                .collect();
+            if !alts
+                .iter()
+                .any(|alt| useful(&rows, std::slice::from_ref(alt), &tys).is_some())
+            {
+                self.report(head, MatchDiagnosticKind::Unreachable { arm });
+            }
+            // NOTE - a guarded arm may fail to match any value, so it cannot cover anything for later arms
+            if guard.is_none() {
+                rows.extend(alts.into_iter().map(|alt| vec![alt]));
+            }
+        }
+        if let Some(witness) = useful(&rows, &[Pat::Wild], &tys) {
+            let witness = witness[0].to_string();
+            self.report(head, MatchDiagnosticKind::NonExhaustive { witness });
+        }
+    }
+
+    fn report(&mut self, head: &Expr, kind: MatchDiagnosticKind) {
+        self.diagnostics.push(MatchDiagnostic {
+            format: self.format.clone(),
+            head: TreePrinter::new(self.module)
+                .compile_expr(head, Precedence::TOP)
+                .to_string(),
+            kind,
+        });
+    }
+}
+
+/// Value-constructor distinguished by patterns.
+#[derive(Clone, Debug, PartialEq)]
+enum Ctor {
+    Bool(bool),
+    Variant(Label),
+    None,
+    Some,
+    Tuple(usize),
+    /// Record with the given fields, in the order they appear in its type
+    Record(Vec<Label>),
+    /// Sequence of the given length
+    Seq(usize),
+    /// Inclusive range of integers
+    Int(i128, i128),
+    Char(char),
+    /// Sorted, disjoint and non-adjacent inclusive ranges of integers, only ever produced as part of a witness
+    Ranges(Vec<(i128, i128)>),
+}
+
+/// Pattern over [`Ctor`]s, without bindings, or-patterns or guards.
+#[derive(Clone, Debug)]
+enum Pat {
+    Wild,
+    Ctor(Ctor, Vec<Pat>),
+}
+
+impl Pat {
+    /// Lowers an or-free, unguarded pattern matching values of type `t`.
+    fn lower(pattern: &Pattern, t: &ValueType) -> Pat {
+        let leaf = |ctor| Pat::Ctor(ctor, Vec::new());
+        let int = |n: u64| leaf(Ctor::Int(n.into(), n.into()));
+        match pattern {
+            Pattern::Binding(_) | Pattern::Wildcard => Pat::Wild,
+            Pattern::Bool(b) => leaf(Ctor::Bool(*b)),
+            Pattern::U8(n) => int(u64::from(*n)),
+            Pattern::U16(n) => int(u64::from(*n)),
+            Pattern::U32(n) => int(u64::from(*n)),
+            Pattern::U64(n) => int(*n),
+            Pattern::Int(bounds) => {
+                let max = bounds.max.map_or(int_domain(t).1, |max| max as i128);
+                leaf(Ctor::Int(bounds.min as i128, max))
+            }
+            Pattern::ZConst(n) => {
+                let n = clamp_int(n);
+                leaf(Ctor::Int(n, n))
+            }
+            Pattern::ZRange(bounds) => {
+                leaf(Ctor::Int(clamp_int(&bounds.min), clamp_int(&bounds.max)))
+            }
+            Pattern::Char(c) => leaf(Ctor::Char(*c)),
+            Pattern::Tuple(ps) => {
+                let ctor = Ctor::Tuple(ps.len());
+                let tys = field_types(&ctor, t);
+                let args = Iterator::zip(ps.iter(), tys.iter())
+                    .map(|(p, t)| Pat::lower(p, t))
+                    .collect();
+                Pat::Ctor(ctor, args)
+            }
+            Pattern::Record(pfields) => {
+                let fields: Vec<(Label, ValueType)> = match t {
+                    ValueType::Record(fields) => fields.clone(),
+                    _ => pfields
+                        .iter()
+                        .map(|(l, _)| (l.clone(), ValueType::Any))
+                        .collect(),
+                };
+                let args = fields
+                    .iter()
+                    .map(
+                        |(label, t)| match pfields.iter().find(|(l, _)| l == label) {
+                            Some((_, p)) => Pat::lower(p, t),
+                            None => Pat::Wild,
+                        },
+                    )
+                    .collect();
+                let labels = fields.into_iter().map(|(l, _)| l).collect();
+                Pat::Ctor(Ctor::Record(labels), args)
+            }
+            Pattern::Variant(label, p) => {
+                let ctor = Ctor::Variant(label.clone());
+                let tys = field_types(&ctor, t);
+                Pat::Ctor(ctor, vec![Pat::lower(p, &tys[0])])
+            }
+            Pattern::Seq(ps) => {
+                let ctor = Ctor::Seq(ps.len());
+                let tys = field_types(&ctor, t);
+                let args = Iterator::zip(ps.iter(), tys.iter())
+                    .map(|(p, t)| Pat::lower(p, t))
+                    .collect();
+                Pat::Ctor(ctor, args)
+            }
+            Pattern::Option(None) => leaf(Ctor::None),
+            Pattern::Option(Some(p)) => {
+                let tys = field_types(&Ctor::Some, t);
+                Pat::Ctor(Ctor::Some, vec![Pat::lower(p, &tys[0])])
+            }
+            Pattern::Guard(p, _) => Pat::lower(p, t),
+            Pattern::Or(..) => unreachable!("or-patterns are expanded before lowering"),
+        }
+    }
+
+    fn head_ctor(&self) -> Option<&Ctor> {
+        match self {
+            Pat::Wild => None,
+            Pat::Ctor(ctor, _) => Some(ctor),
+        }
+    }
+}
+
+impl fmt::Display for Pat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list(f: &mut fmt::Formatter<'_>, args: &[Pat]) -> fmt::Result {
+            for (ix, arg) in args.iter().enumerate() {
+                if ix > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{arg}")?;
+            }
+            Ok(())
+        }
+        match self {
+            Pat::Wild => write!(f, "_"),
+            Pat::Ctor(ctor, args) => match ctor {
+                Ctor::Bool(b) => write!(f, "{b}"),
+                Ctor::Variant(label) => write!(f, "{label}({})", args[0]),
+                Ctor::None => write!(f, "None"),
+                Ctor::Some => write!(f, "Some({})", args[0]),
+                Ctor::Tuple(_) => {
+                    write!(f, "(")?;
+                    list(f, args)?;
+                    write!(f, ")")
+                }
+                Ctor::Record(labels) => {
+                    write!(f, "{{ ")?;
+                    for (label, arg) in Iterator::zip(labels.iter(), args.iter()) {
+                        if !matches!(arg, Pat::Wild) {
+                            write!(f, "{label}: {arg}, ")?;
+                        }
+                    }
+                    write!(f, ".. }}")
+                }
+                Ctor::Seq(_) => {
+                    write!(f, "[")?;
+                    list(f, args)?;
+                    write!(f, "]")
+                }
+                Ctor::Int(lo, hi) => write_range(f, *lo, *hi),
+                Ctor::Char(c) => write!(f, "{c:?}"),
+                Ctor::Ranges(ranges) => {
+                    for (ix, (lo, hi)) in ranges.iter().enumerate() {
+                        if ix > 0 {
+                            write!(f, " | ")?;
+                        }
+                        write_range(f, *lo, *hi)?;
+                    }
+                    Ok(())
+                }
+            },
+        }
+    }
+}
+
+fn write_range(f: &mut fmt::Formatter<'_>, lo: i128, hi: i128) -> fmt::Result {
+    if lo == hi {
+        write!(f, "{lo}")
+    } else {
+        write!(f, "{lo}..={hi}")
+    }
+}
+
+/// Fallback integer domain for numeric types of unknown width, wide enough for any primitive integer.
+const ANY_INT: (i128, i128) = (i64::MIN as i128, u64::MAX as i128);
+
+/// Returns the inclusive range of integers inhabiting `t`.
+fn int_domain(t: &ValueType) -> (i128, i128) {
+    match t {
+        ValueType::Base(BaseType::U8) => (0, u8::MAX.into()),
+        ValueType::Base(BaseType::U16) => (0, u16::MAX.into()),
+        ValueType::Base(BaseType::U32) => (0, u32::MAX.into()),
+        ValueType::Base(BaseType::U64) => (0, u64::MAX.into()),
+        ValueType::Signed(SignedIntType::I8) => (i8::MIN.into(), i8::MAX.into()),
+        ValueType::Signed(SignedIntType::I16) => (i16::MIN.into(), i16::MAX.into()),
+        ValueType::Signed(SignedIntType::I32) => (i32::MIN.into(), i32::MAX.into()),
+        ValueType::Signed(SignedIntType::I64) => (i64::MIN.into(), i64::MAX.into()),
+        _ => ANY_INT,
+    }
+}
+
+fn clamp_int(n: &num_bigint::BigInt) -> i128 {
+    i128::try_from(n).map_or_else(
+        |_| {
+            if n.sign() == num_bigint::Sign::Minus {
+                ANY_INT.0
+            } else {
+                ANY_INT.1
+            }
+        },
+        |n| n.clamp(ANY_INT.0, ANY_INT.1),
+    )
+}
+
+/// Returns the types of the fields of a value built with `ctor` (one per argument), given the type `t` of the value.
+fn field_types(ctor: &Ctor, t: &ValueType) -> Vec<ValueType> {
+    match (ctor, t) {
+        (Ctor::Variant(label), ValueType::Union(branches)) => {
+            vec![branches.get(label).cloned().unwrap_or(ValueType::Any)]
+        }
+        (Ctor::Some, ValueType::Option(t)) => vec![t.as_ref().clone()],
+        (Ctor::Tuple(n), ValueType::Tuple(ts)) if ts.len() == *n => ts.clone(),
+        (Ctor::Record(labels), ValueType::Record(fields)) => labels
+            .iter()
+            .map(|label| {
+                fields
+                    .iter()
+                    .find(|(l, _)| l == label)
+                    .map_or(ValueType::Any, |(_, t)| t.clone())
+            })
+            .collect(),
+        (Ctor::Seq(n), ValueType::Seq(t)) => vec![t.as_ref().clone(); *n],
+        (Ctor::Variant(_) | Ctor::Some, _) => vec![ValueType::Any],
+        (Ctor::Tuple(n) | Ctor::Seq(n), _) => vec![ValueType::Any; *n],
+        (Ctor::Record(labels), _) => vec![ValueType::Any; labels.len()],
+        (Ctor::Bool(_) | Ctor::None | Ctor::Int(..) | Ctor::Char(_) | Ctor::Ranges(_), _) => {
+            Vec::new()
+        }
+    }
+}
+
+/// Returns `true` if every value built with `ctor` is also built with `row_ctor`.
+fn covers(row_ctor: &Ctor, ctor: &Ctor) -> bool {
+    match (row_ctor, ctor) {
+        (Ctor::Int(lo0, hi0), Ctor::Int(lo1, hi1)) => lo0 <= lo1 && hi1 <= hi0,
+        _ => row_ctor == ctor,
+    }
+}
+
+/// Specializes `row` to the values built with `ctor`, which takes `arity` arguments, if it can match any of them.
+fn specialize(row: &[Pat], ctor: &Ctor, arity: usize) -> Option<Vec<Pat>> {
+    let (head, rest) = row.split_first()?;
+    let mut out = match head {
+        Pat::Wild => vec![Pat::Wild; arity],
+        Pat::Ctor(row_ctor, args) if covers(row_ctor, ctor) => args.clone(),
+        Pat::Ctor(..) => return None,
+    };
+    out.extend(rest.iter().cloned());
+    Some(out)
+}
+
+/// Splits the inclusive range `lo..=hi` into maximal sub-ranges that each lie either entirely within or
+/// entirely outside each of the integer-ranges among `ctors`.
+fn split_range((lo, hi): (i128, i128), ctors: &[&Ctor]) -> Vec<(i128, i128)> {
+    let mut bounds = vec![lo, hi + 1];
+    for ctor in ctors {
+        if let Ctor::Int(lo1, hi1) = ctor {
+            bounds.extend([*lo1, hi1 + 1].into_iter().filter(|b| lo < *b && *b <= hi));
+        }
+    }
+    bounds.sort_unstable();
+    bounds.dedup();
+    bounds.windows(2).map(|w| (w[0], w[1] - 1)).collect()
+}
+
+/// Sorts `ranges` and merges any that overlap or are adjacent.
+fn merge_ranges(mut ranges: Vec<(i128, i128)>) -> Vec<(i128, i128)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(i128, i128)> = Vec::with_capacity(ranges.len());
+    for (lo, hi) in ranges {
+        match merged.last_mut() {
+            Some((_, last_hi)) if lo <= last_hi.saturating_add(1) => *last_hi = (*last_hi).max(hi),
+            _ => merged.push((lo, hi)),
+        }
+    }
+    merged
+}
+
+/// Constructors to consider for a column of patterns, given the constructors appearing at its head.
+enum Split {
+    /// Every value is built with one of these constructors
+    Complete(Vec<Ctor>),
+    /// Some values are not built with any of the constructors at the head of the column, as exemplified by this pattern
+    Missing(Pat),
+}
+
+fn split(t: &ValueType, heads: &[&Ctor]) -> Split {
+    let Some(first) = heads.first() else {
+        return Split::Missing(Pat::Wild);
+    };
+    let from = |all: Vec<Ctor>| match all.iter().find(|ctor| !heads.contains(ctor)) {
+        None => Split::Complete(all),
+        Some(ctor) => {
+            let args = vec![Pat::Wild; field_types(ctor, t).len()];
+            Split::Missing(Pat::Ctor(ctor.clone(), args))
+        }
+    };
+    match (first, t) {
+        (Ctor::Bool(_), _) => from(vec![Ctor::Bool(false), Ctor::Bool(true)]),
+        (Ctor::None | Ctor::Some, _) => from(vec![Ctor::None, Ctor::Some]),
+        (Ctor::Variant(_), ValueType::Union(branches)) => {
+            from(branches.keys().cloned().map(Ctor::Variant).collect())
+        }
+        (Ctor::Tuple(_) | Ctor::Record(_), _) => Split::Complete(vec![(*first).clone()]),
+        (Ctor::Seq(_), _) => {
+            let len = (0..).find(|n| !heads.contains(&&Ctor::Seq(*n))).unwrap();
+            let ctor = Ctor::Seq(len);
+            let args = vec![Pat::Wild; len];
+            Split::Missing(Pat::Ctor(ctor, args))
+        }
+        (Ctor::Int(..), _) => {
+            let domain = int_domain(t);
+            let ranges = split_range(domain, heads);
+            let (covered, gaps): (Vec<_>, Vec<_>) = ranges
+                .into_iter()
+                .partition(|(lo, hi)| heads.iter().any(|ctor| covers(ctor, &Ctor::Int(*lo, *hi))));
+            if gaps.is_empty() {
+                Split::Complete(
+                    covered
+                        .into_iter()
+                        .map(|(lo, hi)| Ctor::Int(lo, hi))
+                        .collect(),
+                )
+            } else {
+                Split::Missing(Pat::Ctor(Ctor::Ranges(merge_ranges(gaps)), Vec::new()))
+            }
+        }
+        // NOTE - variants of a union of unknown extent, and characters, cannot be exhausted without a catch-all
+        (Ctor::Variant(_) | Ctor::Char(_) | Ctor::Ranges(_), _) => Split::Missing(Pat::Wild),
+    }
+}
+
+/// Returns a witness (one pattern per column) to the usefulness of the row `v` with respect to `rows`, i.e.
+/// values matched by `v` but by none of `rows`, if there are any.
+fn useful(rows: &[Vec<Pat>], v: &[Pat], tys: &[ValueType]) -> Option<Vec<Pat>> {
+    let Some((head, rest)) = v.split_first() else {
+        return rows.is_empty().then(Vec::new);
+    };
+    let heads: Vec<&Ctor> = rows.iter().filter_map(|row| row[0].head_ctor()).collect();
+    match head {
+        Pat::Ctor(Ctor::Int(lo, hi), _) => split_range((*lo, *hi), &heads)
+            .into_iter()
+            .find_map(|(lo, hi)| useful_ctor(rows, &Ctor::Int(lo, hi), None, rest, tys)),
+        Pat::Ctor(ctor, args) => useful_ctor(rows, ctor, Some(args), rest, tys),
+        Pat::Wild => match split(&tys[0], &heads) {
+            Split::Complete(ctors) => ctors
+                .iter()
+                .find_map(|ctor| useful_ctor(rows, ctor, None, rest, tys)),
+            Split::Missing(missing) => {
+                let default: Vec<Vec<Pat>> = rows
+                    .iter()
+                    .filter(|row| matches!(row[0], Pat::Wild))
+                    .map(|row| row[1..].to_vec())
+                    .collect();
+                let mut witness = useful(&default, rest, &tys[1..])?;
+                witness.insert(0, missing);
+                Some(witness)
+            }
+        },
+    }
+}
+
+/// Like [`useful`], for a row `v` whose head is built with `ctor` from `args` (or wildcards, if `None`).
+fn useful_ctor(
+    rows: &[Vec<Pat>],
+    ctor: &Ctor,
+    args: Option<&[Pat]>,
+    rest: &[Pat],
+    tys: &[ValueType],
+) -> Option<Vec<Pat>> {
+    let mut field_tys = field_types(ctor, &tys[0]);
+    let arity = field_tys.len();
+    let specialized: Vec<Vec<Pat>> = rows
+        .iter()
+        .filter_map(|row| specialize(row, ctor, arity))
+        .collect();
+    let mut v = args.map_or_else(|| vec![Pat::Wild; arity], <[Pat]>::to_vec);
+    v.extend(rest.iter().cloned());
+    field_tys.extend(tys[1..].iter().cloned());
+    let witness = useful(&specialized, &v, &field_tys)?;
+    let (fields, tail) = witness.split_at(arity);
+    let mut out = vec![Pat::Ctor(ctor.clone(), fields.to_vec())];
+    out.extend(tail.iter().cloned());
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::*;
+
+    fn kinds(module: &FormatModule) -> Vec<MatchDiagnosticKind> {
+        check_matches(module)
+            .into_iter()
+            .map(|diag| diag.kind)
+            .collect()
+    }
+
+    fn non_exhaustive(witness: &str) -> MatchDiagnosticKind {
+        MatchDiagnosticKind::NonExhaustive {
+            witness: witness.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_byte_match() {
+        let mut module = FormatModule::new();
+        module.define_format(
+            "test.byte",
+            chain(
+                u8(),
+                "b",
+                fmt_match(
+                    var("b"),
+                    [
+                        (Pattern::U8(0), Format::EMPTY),
+                        (Pattern::Int((1u8..=0x7f).into()), Format::EMPTY),
+                        (Pattern::U8(0x10), Format::EMPTY),
+                    ],
+                ),
+            ),
+        );
+        assert_eq!(
+            check_matches(&module)[0].to_string(),
+            "test.byte: unreachable arm #2 in match on `b`"
+        );
+        assert_eq!(
+            kinds(&module),
+            [
+                MatchDiagnosticKind::Unreachable { arm: 2 },
+                non_exhaustive("128..=255"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_int_match_witness() {
+        let mut module = FormatModule::new();
+        module.define_format(
+            "test.u16",
+            chain(
+                u16be(),
+                "n",
+                Format::Compute(Box::new(expr_match(
+                    var("n"),
+                    [
+                        (Pattern::Int((5u16..=9).into()), Expr::Bool(true)),
+                        (Pattern::U16(100), Expr::Bool(true)),
+                        (Pattern::U16(0), Expr::Bool(false)),
+                    ],
+                ))),
+            ),
+        );
+        assert_eq!(
+            kinds(&module),
+            [non_exhaustive("1..=4 | 10..=99 | 101..=65535")]
+        );
+    }
+
+    #[test]
+    fn test_merge_ranges() {
+        assert_eq!(
+            merge_ranges(vec![(10, 12), (0, 3), (4, 5), (11, 20), (30, 30)]),
+            [(0, 5), (10, 20), (30, 30)]
+        );
+    }
+
+    #[test]
+    fn test_union_match() {
+        let mut module = FormatModule::new();
+        let head = Format::alts([("a", u8()), ("b", u16be()), ("c", Format::EMPTY)]);
+        module.define_format(
+            "test.union",
+            chain(
+                head,
+                "x",
+                Format::Compute(Box::new(expr_match(
+                    var("x"),
+                    [
+                        (
+                            Pattern::variant("a", bind("n"))
+                                .guarded(expr_gt(var("n"), Expr::U8(3))),
+                            Expr::Bool(true),
+                        ),
+                        (
+                            pat_or([
+                                Pattern::variant("b", Pattern::Wildcard),
+                                Pattern::variant("c", Pattern::UNIT),
+                            ]),
+                            Expr::Bool(false),
+                        ),
+                        (Pattern::variant("c", Pattern::UNIT), Expr::Bool(false)),
+                    ],
+                ))),
+            ),
+        );
+        assert_eq!(
+            kinds(&module),
+            [
+                MatchDiagnosticKind::Unreachable { arm: 2 },
+                non_exhaustive("a(_)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_tuple_match() {
+        let mut module = FormatModule::new();
+        module.define_format(
+            "test.record",
+            chain(
+                record([
+                    ("flag", Format::Compute(Box::new(Expr::Bool(true)))),
+                    ("opt", Format::Compute(Box::new(expr_some(Expr::U16(1))))),
+                ]),
+                "r",
+                fmt_match(
+                    var("r"),
+                    [
+                        (pat_record([("flag", Pattern::Bool(true))]), Format::EMPTY),
+                        (
+                            pat_record([
+                                ("flag", Pattern::Bool(false)),
+                                ("opt", Pattern::Option(None)),
+                            ]),
+                            Format::EMPTY,
+                        ),
+                    ],
+                ),
+            ),
+        );
+        assert_eq!(
+            kinds(&module),
+            [non_exhaustive("{ flag: false, opt: Some(_), .. }")]
+        );
+
+        let mut module = FormatModule::new();
+        module.define_format(
+            "test.tuple",
+            chain(
+                tuple([is_byte(0), u8()]),
+                "t",
+                fmt_match(
+                    var("t"),
+                    [
+                        (
+                            Pattern::Tuple(vec![
+                                Pattern::Wildcard,
+                                Pattern::Int((0u8..=0x7f).into()),
+                            ]),
+                            Format::EMPTY,
+                        ),
+                        (
+                            Pattern::Tuple(vec![
+                                Pattern::Wildcard,
+                                Pattern::Int((0x80u8..).into()),
+                            ]),
+                            Format::EMPTY,
+                        ),
+                    ],
+                ),
+            ),
+        );
+        assert_eq!(kinds(&module), []);
+    }
+}
@@ -0,0 +1,66 @@
+//! Formats whose decoding is implemented outside of the `Format` language, as ordinary Rust functions.
+//!
+//! Some formats (e.g. general-purpose compression schemes such as DEFLATE, LZW or brotli) are far more
+//! naturally, and efficiently, implemented by hand than as a `Format`. A [`NativeFormat`] declares such
+//! an implementation to a [`FormatModule`](crate::FormatModule), via
+//! [`define_native_format`](crate::FormatModule::define_native_format), after which it can be invoked like
+//! any other format.
+//!
+//! Both backends hand the native implementation the bytes remaining in the current (possibly slice-limited)
+//! context, and expect back the decoded value along with the number of bytes it consumed:
+//!
+//! - The interpreter calls [`NativeFormat::decode`] directly, with the argument values in declaration order.
+//! - Generated code calls the function at [`NativeFormat::rust_path`], which should have the signature
+//!   `fn(&[u8], A0, A1, ...) -> Result<(T, usize), NativeError>`, where `A0, A1, ...` are the generated types of
+//!   the arguments and `T` is the generated type of the format.
+use serde::Serialize;
+
+use crate::decoder::Value;
+use crate::{Label, ValueType};
+
+/// Signature of the interpreter-side implementation of a [`NativeFormat`].
+pub type NativeDecodeFn = fn(&[u8], &[Value]) -> Result<(Value, usize), NativeError>;
+
+/// An externally-implemented format.
+#[derive(Clone, Debug, Serialize)]
+pub struct NativeFormat {
+    /// Type of the values produced by `decode`
+    pub value_type: ValueType,
+    /// Implementation used by the interpreter
+    #[serde(skip)]
+    pub decode: NativeDecodeFn,
+    /// Path of the implementation called from generated code
+    pub rust_path: Label,
+}
+
+impl NativeFormat {
+    pub fn new(value_type: ValueType, decode: NativeDecodeFn, rust_path: impl Into<Label>) -> Self {
+        NativeFormat {
+            value_type,
+            decode,
+            rust_path: rust_path.into(),
+        }
+    }
+}
+
+/// Error reported by the implementation of a [`NativeFormat`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeError {
+    pub message: Label,
+}
+
+impl NativeError {
+    pub fn new(message: impl Into<Label>) -> Self {
+        NativeError {
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for NativeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "native decoder failed: {}", self.message)
+    }
+}
+
+impl std::error::Error for NativeError {}
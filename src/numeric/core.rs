@@ -986,7 +986,7 @@ impl Expr {
                 }
             }
             Expr::Cast(op, _) => Ok(op.out_rep.into()),
-            Expr::NumVar(name) => match scope.get_type_by_name(name) {
+            Expr::NumVar(name) => match scope.get_type_by_name(name)? {
                 crate::ValueKind::View => Err(anyhow!("variable {} is a view", name)),
                 crate::ValueKind::Value(vt) => Ok(vt.clone()),
                 crate::ValueKind::Format(_) => Err(anyhow!("variable {} is a format", name)),
@@ -1095,6 +1095,8 @@ impl<'a> TryFrom<&'a crate::decoder::Value> for StrictValue {
             }
             Raw::Bool(..)
             | Raw::Char(..)
+            | Raw::Float(..)
+            | Raw::Fixed(..)
             | Raw::View { .. }
             | Raw::PhantomData
             | Raw::Permit(Err(None))
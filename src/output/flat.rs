@@ -122,6 +122,12 @@ fn check_covered(
                 path.pop();
             }
         }
+        Format::GenericVar(index, formats, _args, _views) => {
+            let generic = module.get_generic(*index);
+            path.push(generic.name.clone());
+            check_covered(module, path, &generic.instantiate(formats))?;
+            path.pop();
+        }
         Format::Phantom(inner) => {
             check_covered(module, path, inner)?;
         }
@@ -133,6 +139,9 @@ fn check_covered(
         Format::Byte(_) => {
             return Err(format!("uncovered byte: {path:?}"));
         }
+        Format::Native(..) => {
+            return Err(format!("uncovered native format: {path:?}"));
+        }
         Format::ParseFromView(_, format) | Format::DecodeBytes(_, format) => {
             check_covered(module, path, format)?;
         }
@@ -210,7 +219,9 @@ fn check_covered(
         Format::Hint(_hint, format) => check_covered(module, path, format)?,
         #[cfg(feature = "format_enforce")]
         Format::Enforce(format) => check_covered(module, path, format)?,
-        Format::Permit(format, _expr) => check_covered(module, path, format)?,
+        Format::Permit(format, _) | Format::Resync(format, _) => {
+            check_covered(module, path, format)?
+        }
         Format::WithView(_ident, _vf) => {} // FIXME
     }
     Ok(())
@@ -231,6 +242,10 @@ impl<'module, W: io::Write> Context<'module, W> {
                     self.write_flat(value, self.module.get_format(*level))
                 }
             }
+            Format::GenericVar(index, formats, _args, _views) => {
+                let format = self.module.get_generic(*index).instantiate(formats);
+                self.write_flat(value, &format)
+            }
             Format::Phantom(..) => Ok(()),
             Format::Fail => Ok(()),
             Format::EndOfInput => Ok(()),
@@ -238,6 +253,7 @@ impl<'module, W: io::Write> Context<'module, W> {
             Format::Align(_) => Ok(()),
             Format::Pos => Ok(()),
             Format::Byte(_) => Ok(()),
+            Format::Native(..) => Ok(()),
             Format::LiftedOption(None) => match value {
                 Value::Option(None) => Ok(()),
                 other => panic!("expected Value::Option(None), found {other:?}"),
@@ -349,6 +365,17 @@ impl<'module, W: io::Write> Context<'module, W> {
                 self.write_record(value, record_format)
             }
             Format::Permit(format, _) => self.write_flat(value, format),
+            Format::Resync(format, _) => match value {
+                Value::Variant(label, value) if label == "ok" => self.write_flat(value, format),
+                Value::Variant(label, value) if label == "skipped" => {
+                    let len = value
+                        .record_proj("data")
+                        .get_sequence()
+                        .map_or(0, |s| s.len());
+                    writeln!(&mut self.writer, "(skipped {len} bytes)")
+                }
+                _ => panic!("expected resync variant, found {value:?}"),
+            },
             #[cfg(feature = "format_enforce")]
             Format::Enforce(format) => self.write_flat(value, format),
             Format::Hint(StyleHint::AsciiStr, str_format) => self.write_flat(value, str_format),
@@ -1,5 +1,5 @@
 use core::panic;
-use std::{fmt, io, rc::Rc};
+use std::{borrow::Cow, fmt, io, rc::Rc};
 
 use crate::precedence::{Precedence, cond_paren};
 use crate::validation::{Condition, Severity};
@@ -77,6 +77,25 @@ fn fragment_permit_err_none() -> Fragment {
     Fragment::string("NO_VALUE")
 }
 
+// Centralized logic for what Fragment to display for the `skipped` branch of a `{Parsed,}Value` of `Format::Resync`
+fn fragment_resync_skipped(skipped: &Value, loc: ParseLoc) -> Fragment {
+    let bytes = |label: &str| -> Vec<u8> {
+        skipped
+            .record_proj(label)
+            .get_sequence()
+            .map_or(Vec::new(), |seq| {
+                seq.into_iter().map(|b| b.get_as_u8()).collect()
+            })
+    };
+    let error = String::from_utf8_lossy(&bytes("error")).into_owned();
+    let len = bytes("data").len();
+    let region = match loc {
+        ParseLoc::InBuffer { offset, .. } => format!("(SKIPPED {len} bytes at offset {offset})"),
+        ParseLoc::Synthesized => format!("(SKIPPED {len} bytes)"),
+    };
+    Fragment::String(region.into()).join_with_wsp(Fragment::String(error.into()))
+}
+
 impl<'module> TreePrinter<'module> {
     fn is_implied_value_format_old_style_record(&self, format: &Format) -> bool {
         match format {
@@ -106,6 +125,9 @@ impl<'module> TreePrinter<'module> {
             Format::ItemVar(level, _args, _views) => {
                 self.is_implied_value_format(self.module.get_format(*level))
             }
+            Format::GenericVar(index, formats, _args, _views) => {
+                self.is_implied_value_format(&self.module.get_generic(*index).instantiate(formats))
+            }
             Format::EndOfInput => true,
             Format::Phantom(_) => true,
             Format::Byte(bs) => bs.len() == 1,
@@ -113,6 +135,7 @@ impl<'module> TreePrinter<'module> {
             #[cfg(feature = "format_enforce")]
             Format::Enforce(f) => self.is_implied_value_format(f),
             Format::Permit(f, _e) => self.is_implied_value_format(f),
+            Format::Resync(..) => false,
             Format::Hint(StyleHint::Common(_), inner) => self.is_implied_value_format(inner),
             Format::Hint(StyleHint::AsciiChar, inner) => self.is_implied_value_format(inner),
             Format::Hint(StyleHint::AsciiStr, inner) => self.is_implied_value_format(inner),
@@ -155,6 +178,7 @@ impl<'module> TreePrinter<'module> {
             Format::Apply(_) => false,
             Format::Pos => false,
             Format::SkipRemainder => true,
+            Format::Native(..) => false,
             Format::DecodeBytes(_, format) => self.is_implied_value_format(format),
             Format::LetFormat(.., format) | Format::MonadSeq(_, format) => {
                 self.is_implied_value_format(format)
@@ -228,6 +252,7 @@ impl<'module> TreePrinter<'module> {
             Value::Bool(_) => true,
             Value::U8(_) | Value::U16(_) | Value::U32(_) | Value::U64(_) => true,
             Value::Usize(_) => true,
+            Value::Float(_) | Value::Fixed(_) => true,
             Value::Numeric(_) => true,
             Value::View { .. } => true,
             Value::Tuple(values) => values.is_empty(),
@@ -428,8 +453,13 @@ impl<'module> TreePrinter<'module> {
                     self.compile_parsed_decoded_value(value, self.module.get_format(*level))
                 }
             }
+            Format::GenericVar(index, formats, _args, _views) => {
+                let format = self.module.get_generic(*index).instantiate(formats);
+                self.compile_parsed_decoded_value(value, &format)
+            }
             Format::Fail => panic!("uninhabited format (value={value:?}"),
             Format::EndOfInput | Format::SkipRemainder => self.compile_parsed_value(value),
+            Format::Native(..) => self.compile_parsed_value(value),
             Format::Align(_) => self.compile_parsed_value(value),
             Format::Byte(_) => self.compile_parsed_value(value),
             // NOTE : Pos self-documents its position so we don't really need to annotate that...
@@ -592,6 +622,15 @@ impl<'module> TreePrinter<'module> {
                 },
                 _ => panic!("expected Value::Permit, found {value:?}"),
             },
+            Format::Resync(f, _) => match value {
+                ParsedValue::Variant(label, v) if label == "ok" => {
+                    self.compile_parsed_decoded_value(v, f)
+                }
+                ParsedValue::Variant(label, v) if label == "skipped" => {
+                    fragment_resync_skipped(&v.clone_into_value(), v.get_loc())
+                }
+                _ => panic!("expected resync variant, found {value:?}"),
+            },
             #[cfg(feature = "format_enforce")]
             Format::Enforce(f) => self.compile_parsed_decoded_value(value, f),
             // REVIEW[epic=view-format] - is this correct?
@@ -621,10 +660,15 @@ impl<'module> TreePrinter<'module> {
                     self.compile_decoded_value(value, self.module.get_format(*level))
                 }
             }
+            Format::GenericVar(index, formats, _args, _views) => {
+                let format = self.module.get_generic(*index).instantiate(formats);
+                self.compile_decoded_value(value, &format)
+            }
             Format::DecodeBytes(_bytes, f) => self.compile_decoded_value(value, f),
             Format::ParseFromView(_view, f) => self.compile_decoded_value(value, f),
             Format::Fail => panic!("uninhabited format (value={value}"),
             Format::SkipRemainder | Format::EndOfInput => self.compile_value(value),
+            Format::Native(..) => self.compile_value(value),
             Format::Align(_) => self.compile_value(value),
             Format::Byte(_) => self.compile_value(value),
             Format::Pos => self.compile_value(value),
@@ -706,6 +750,13 @@ impl<'module> TreePrinter<'module> {
                 },
                 _ => panic!("expected Value::Permit, found {value}"),
             },
+            Format::Resync(format, _) => match value {
+                Value::Variant(label, v) if label == "ok" => self.compile_decoded_value(v, format),
+                Value::Variant(label, v) if label == "skipped" => {
+                    fragment_resync_skipped(v, ParseLoc::Synthesized)
+                }
+                _ => panic!("expected resync variant, found {value}"),
+            },
             #[cfg(feature = "format_enforce")]
             Format::Enforce(format) => self.compile_decoded_value(value, format),
             Format::Repeat(format)
@@ -818,6 +869,8 @@ impl<'module> TreePrinter<'module> {
             Value::U32(i) => Fragment::DisplayAtom(Rc::new(*i)),
             Value::U64(i) => Fragment::DisplayAtom(Rc::new(*i)),
             Value::Usize(i) => Fragment::DisplayAtom(Rc::new(*i)),
+            Value::Float(x) => Fragment::DisplayAtom(Rc::new(*x)),
+            Value::Fixed(x) => Fragment::DisplayAtom(Rc::new(*x)),
             Value::Numeric(n) => Fragment::DisplayAtom(n.clone()),
             Value::Char(c) => Fragment::DebugAtom(Rc::new(*c)),
             Value::View { offset } => Fragment::string(format!("VIEW[+{offset}]")),
@@ -1751,7 +1804,12 @@ impl<'module> TreePrinter<'module> {
 
     /// Renders an Expr as a prefix-operator (with optional auxiliary arguments in parentheses)
     /// applied to a nested Expr.
-    fn prefix_op(&mut self, op: &'static str, args: Option<&[&Expr]>, operand: &Expr) -> Fragment {
+    fn prefix_op(
+        &mut self,
+        op: impl Into<Cow<'static, str>>,
+        args: Option<&[&Expr]>,
+        operand: &Expr,
+    ) -> Fragment {
         let mut frags = FragmentBuilder::new();
 
         frags.push(Fragment::String(op.into()));
@@ -1801,6 +1859,25 @@ impl<'module> TreePrinter<'module> {
                 .cat(Self::compile_pattern(pat))
                 .cat(Fragment::Char(')'))
                 .group(),
+            Pattern::Record(fields) => Fragment::seq(
+                fields
+                    .iter()
+                    .map(|(label, pat)| {
+                        Fragment::String(label.clone())
+                            .cat(Fragment::String(": ".into()))
+                            .cat(Self::compile_pattern(pat))
+                    })
+                    .chain(std::iter::once(Fragment::string(".."))),
+                Some(Fragment::String(", ".into())),
+            )
+            .delimit(Fragment::String("{ ".into()), Fragment::String(" }".into()))
+            .group(),
+            Pattern::Or(alts) => Fragment::seq(
+                alts.iter().map(Self::compile_pattern),
+                Some(Fragment::String(" | ".into())),
+            )
+            .delimit(Fragment::Char('('), Fragment::Char(')'))
+            .group(),
             Pattern::Int(..)
             | Pattern::ZConst(..)
             | Pattern::ZRange(..)
@@ -1809,11 +1886,12 @@ impl<'module> TreePrinter<'module> {
             | Pattern::U32(..)
             | Pattern::U64(..)
             | Pattern::Bool(..)
-            | Pattern::Char(..) => unreachable!("compile_pattern: unexpected pattern: {pat:?}"),
+            | Pattern::Char(..)
+            | Pattern::Guard(..) => unreachable!("compile_pattern: unexpected pattern: {pat:?}"),
         }
     }
 
-    fn compile_expr(&mut self, expr: &Expr, prec: Precedence) -> Fragment {
+    pub(crate) fn compile_expr(&mut self, expr: &Expr, prec: Precedence) -> Fragment {
         match expr {
             Expr::Match(head, _) => cond_paren(
                 Fragment::String("match ".into())
@@ -1985,6 +2063,21 @@ impl<'module> TreePrinter<'module> {
                 prec,
                 Precedence::CAST_PREFIX,
             ),
+            Expr::F32FromBits(expr) => cond_paren(
+                self.prefix_op("f32-from-bits", None, expr),
+                prec,
+                Precedence::CAST_PREFIX,
+            ),
+            Expr::F64FromBits(expr) => cond_paren(
+                self.prefix_op("f64-from-bits", None, expr),
+                prec,
+                Precedence::CAST_PREFIX,
+            ),
+            Expr::FixedFromBits(fx, expr) => cond_paren(
+                self.prefix_op(format!("fixed-from-bits<{fx}>"), None, expr),
+                prec,
+                Precedence::CAST_PREFIX,
+            ),
             Expr::U16Be(bytes) => cond_paren(
                 self.prefix_op("u16be", None, bytes),
                 prec,
@@ -2063,6 +2156,36 @@ impl<'module> TreePrinter<'module> {
                 prec,
                 Precedence::FUN_APPLICATION,
             ),
+            Expr::Filter(expr, seq) => cond_paren(
+                self.prefix_op("filter", Some(&[expr]), seq),
+                prec,
+                Precedence::FUN_APPLICATION,
+            ),
+            Expr::Zip(seq0, seq1) => cond_paren(
+                self.prefix_op("zip", Some(&[seq0]), seq1),
+                prec,
+                Precedence::FUN_APPLICATION,
+            ),
+            Expr::Reverse(seq) => cond_paren(
+                self.prefix_op("reverse", None, seq),
+                prec,
+                Precedence::FUN_APPLICATION,
+            ),
+            Expr::Any(expr, seq) => cond_paren(
+                self.prefix_op("any", Some(&[expr]), seq),
+                prec,
+                Precedence::FUN_APPLICATION,
+            ),
+            Expr::All(expr, seq) => cond_paren(
+                self.prefix_op("all", Some(&[expr]), seq),
+                prec,
+                Precedence::FUN_APPLICATION,
+            ),
+            Expr::IsSortedBy(expr, seq) => cond_paren(
+                self.prefix_op("is-sorted-by", Some(&[expr]), seq),
+                prec,
+                Precedence::FUN_APPLICATION,
+            ),
             Expr::FlatMapList(expr, _ret_type, seq) => cond_paren(
                 self.prefix_op("flat-map-list", Some(&[expr]), seq),
                 prec,
@@ -2123,6 +2246,19 @@ impl<'module> TreePrinter<'module> {
                 .group(),
             Expr::Seq(..) => Fragment::String("[..]".into()),
             Expr::Numeric(n_tree) => crate::numeric::printer::compile_expr(n_tree, prec),
+            Expr::Call(index, _, args) => {
+                let name = self.module.get_function(*index).name.clone();
+                Fragment::String(name)
+                    .cat(Fragment::Char('('))
+                    .cat(Fragment::seq(
+                        args.iter()
+                            .map(|arg| self.compile_expr(arg, Precedence::default()))
+                            .collect::<Vec<_>>(),
+                        Some(Fragment::String(", ".into())),
+                    ))
+                    .cat(Fragment::Char(')'))
+                    .group()
+            }
         }
     }
 
@@ -2430,8 +2566,40 @@ impl<'module> TreePrinter<'module> {
                 }
                 frag
             }
+            Format::GenericVar(index, formats, args, views) => {
+                let mut frag = Fragment::new();
+                frag.append(Fragment::String(
+                    self.module.get_generic(*index).name.clone(),
+                ));
+                frag.append(Fragment::Char('['));
+                for (ix, f) in formats.iter().enumerate() {
+                    if ix > 0 {
+                        frag.append(Fragment::string(", "));
+                    }
+                    frag.append(self.compile_format(f, Precedence::TOP));
+                }
+                frag.append(Fragment::Char(']'));
+                if !args.is_empty() {
+                    frag.append(Fragment::String("(...)".into()));
+                }
+                if !views.is_empty() {
+                    frag.append(Fragment::String("{...}".into()));
+                }
+                frag
+            }
             Format::Fail => Fragment::string("fail"),
             Format::SkipRemainder => Fragment::string("skip-remainder"),
+            Format::Native(index, args) => {
+                let mut frag = Fragment::new();
+                frag.append(Fragment::string("native "));
+                frag.append(Fragment::String(
+                    self.module.get_native(*index).rust_path.clone(),
+                ));
+                if !args.is_empty() {
+                    frag.append(Fragment::String("(...)".into()));
+                }
+                frag
+            }
             Format::EndOfInput => Fragment::string("end-of-input"),
             Format::Pos => Fragment::string("pos"),
             Format::Align(n) => Fragment::String(format!("align {n}").into()),
@@ -2492,6 +2660,18 @@ impl<'module> TreePrinter<'module> {
                     Precedence::FORMAT_COMPOUND,
                 )
             }
+            Format::Resync(format, pattern) => {
+                let pattern_frag = Fragment::seq(
+                    pattern.iter().map(|bs| self.compile_byteset(bs)),
+                    Some(Fragment::string(", ")),
+                )
+                .delimit(Fragment::Char('['), Fragment::Char(']'));
+                cond_paren(
+                    self.compile_nested_format("resync", Some(&[pattern_frag]), format, prec),
+                    prec,
+                    Precedence::FORMAT_COMPOUND,
+                )
+            }
             #[cfg(feature = "format_enforce")]
             Format::Enforce(format) => cond_paren(
                 self.compile_nested_format("enforce", None, format, prec),
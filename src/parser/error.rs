@@ -1,6 +1,10 @@
 use std::hash::Hasher as _;
 
+use super::Parser;
 use super::offset::ByteOffset;
+use crate::byte_set::ByteSet;
+use crate::limits::LimitExceeded;
+use crate::native::NativeError;
 
 pub type PResult<T> = Result<T, ParseError>;
 
@@ -86,6 +90,106 @@ pub enum ParseError {
     UnsoundOperation(Option<&'static str>, TraceHash),
     /// A numeric evaluation error
     BadEval(crate::numeric::eval::EvalError),
+    /// A byte read at absolute offset `offset` is not a member of the set accepted by its `Format::Byte`
+    UnexpectedByte {
+        found: u8,
+        expected: ByteSet,
+        offset: usize,
+    },
+    /// One of the resource limits placed on the parse (see [`Parser::with_limits`]) would have been exceeded
+    LimitExceeded(LimitExceeded),
+    /// The implementation of a native format reported an error
+    Native(NativeError),
+    /// Any other error, annotated with the formats and record fields being parsed when it occurred
+    Located(Box<LocatedError>),
+}
+
+/// Parse error together with the context in which it occurred.
+#[derive(Clone, Debug)]
+pub struct LocatedError {
+    /// Names of the formats, and labels of the record fields, being parsed when the error occurred (innermost first)
+    pub path: Vec<&'static str>,
+    /// Absolute offset at which the error occurred
+    pub offset: usize,
+    /// The underlying error
+    pub error: ParseError,
+}
+
+impl LocatedError {
+    /// Returns an iterator over the format names and field labels leading up to the error, outermost first.
+    pub fn path(&self) -> impl DoubleEndedIterator<Item = &'static str> + '_ {
+        self.path.iter().rev().copied()
+    }
+}
+
+impl ParseError {
+    /// Records that the error occurred while parsing `frame` (a format name or record field label), which
+    /// encloses any frames recorded so far.
+    ///
+    /// The first frame to be recorded also fixes the offset of the error, as the current offset of `input`
+    /// (unless the error already specifies its own).
+    pub fn within(self, frame: &'static str, input: &Parser<'_>) -> ParseError {
+        match self {
+            ParseError::Located(mut located) => {
+                located.path.push(frame);
+                ParseError::Located(located)
+            }
+            error => {
+                let offset = match error {
+                    ParseError::UnexpectedByte { offset, .. } => offset,
+                    _ => input.get_absolute_offset(),
+                };
+                ParseError::Located(Box::new(LocatedError {
+                    path: vec![frame],
+                    offset,
+                    error,
+                }))
+            }
+        }
+    }
+
+    /// Constructs a [`ParseError::UnexpectedByte`] for the byte `found`, which was the last byte read by `input`.
+    pub fn unexpected_byte(found: u8, expected: ByteSet, input: &Parser<'_>) -> ParseError {
+        let offset = match input.get_current_offset() {
+            ByteOffset::Bytes(_) => input.get_absolute_offset().saturating_sub(1),
+            // NOTE - in bits-mode, the byte containing the last bit read is not tracked separately
+            ByteOffset::Bits { .. } => input.get_absolute_offset(),
+        };
+        ParseError::UnexpectedByte {
+            found,
+            expected,
+            offset,
+        }
+    }
+
+    /// Returns the underlying error, stripped of any context recorded by [`ParseError::within`].
+    pub fn cause(&self) -> &ParseError {
+        match self {
+            ParseError::Located(located) => &located.error,
+            other => other,
+        }
+    }
+
+    /// Returns `true` if the error is a failure to parse the input, which a recovery construct such as
+    /// `Format::Resync` may skip over, rather than a resource limit being exceeded or an internal error.
+    pub fn is_recoverable(&self) -> bool {
+        !matches!(
+            self.cause(),
+            ParseError::LimitExceeded(_) | ParseError::InternalError(_)
+        )
+    }
+}
+
+impl From<LimitExceeded> for ParseError {
+    fn from(err: LimitExceeded) -> Self {
+        Self::LimitExceeded(err)
+    }
+}
+
+impl From<NativeError> for ParseError {
+    fn from(err: NativeError) -> Self {
+        Self::Native(err)
+    }
 }
 
 impl From<crate::numeric::eval::EvalError> for ParseError {
@@ -156,6 +260,31 @@ impl std::fmt::Display for ParseError {
             },
             ParseError::InternalError(e) => write!(f, "unrecoverable internal error: {e}"),
             ParseError::BadEval(e) => write!(f, "{e}"),
+            ParseError::UnexpectedByte {
+                found,
+                expected,
+                offset,
+            } => write!(
+                f,
+                "byte {found:#04x} at offset {offset} is not a member of the expected set {expected}"
+            ),
+            ParseError::LimitExceeded(e) => write!(f, "{e}"),
+            ParseError::Native(e) => write!(f, "{e}"),
+            ParseError::Located(located) => {
+                for (ix, frame) in located.path().enumerate() {
+                    if ix > 0 {
+                        f.write_str(" > ")?;
+                    }
+                    f.write_str(frame)?;
+                }
+                write!(f, " @ {:#X}: ", located.offset)?;
+                match &located.error {
+                    ParseError::UnexpectedByte {
+                        found, expected, ..
+                    } => write!(f, "expected {expected} (found {found:#04x})"),
+                    other => write!(f, "{other}"),
+                }
+            }
         }
     }
 }
@@ -165,6 +294,9 @@ impl std::error::Error for ParseError {
         match self {
             ParseError::InternalError(e) => Some(e),
             ParseError::BadEval(e) => Some(e),
+            ParseError::LimitExceeded(e) => Some(e),
+            ParseError::Native(e) => Some(e),
+            ParseError::Located(located) => Some(&located.error),
             _ => None,
         }
     }
@@ -233,3 +365,66 @@ impl From<StateError> for ParseError {
         ParseError::InternalError(value)
     }
 }
+
+pub type UResult<T> = Result<T, UnparseError>;
+
+/// General error type for failures encountered while encoding (unparsing) a value back into bytes
+/// using generated encoder functions
+#[derive(Clone, Debug)]
+pub enum UnparseError {
+    /// Byte value that the corresponding `Format::Byte` would not have accepted
+    ExcludedByte(u8, TraceHash),
+    /// Sequence whose length disagrees with the repetition count required by its format
+    BadLength { expected: usize, found: usize },
+    /// Encoded contents of a `Format::Slice` exceed the width of the slice
+    SliceOverflow { size: usize, written: usize },
+    /// Value that does not correspond to any branch the format could have produced; u64 value is a trace mechanic for determining which error was triggered
+    ExcludedBranch(TraceHash),
+    /// Error encountered while evaluating an expression the encoding depends on
+    BadEval(ParseError),
+}
+
+impl From<ParseError> for UnparseError {
+    fn from(err: ParseError) -> Self {
+        Self::BadEval(err)
+    }
+}
+
+impl From<crate::numeric::eval::EvalError> for UnparseError {
+    fn from(err: crate::numeric::eval::EvalError) -> Self {
+        Self::BadEval(ParseError::BadEval(err))
+    }
+}
+
+impl std::fmt::Display for UnparseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnparseError::ExcludedByte(b, trace) => write!(
+                f,
+                "byte {b:#04x} is not accepted by its format (trace-hash: {trace})"
+            ),
+            UnparseError::BadLength { expected, found } => write!(
+                f,
+                "sequence has {found} elements, but its format requires exactly {expected}"
+            ),
+            UnparseError::SliceOverflow { size, written } => write!(
+                f,
+                "encoded slice contents ({written} bytes) exceed the slice width of {size} bytes"
+            ),
+            UnparseError::ExcludedBranch(trace) => write!(
+                f,
+                "value does not correspond to any branch of its format (trace-hash: {trace})"
+            ),
+            UnparseError::BadEval(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for UnparseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UnparseError::BadEval(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -2,14 +2,19 @@ pub mod error;
 pub mod offset;
 pub mod view;
 
+use crate::byte_set::{ByteSet, resync_offset};
+use crate::limits::{Budget, Limits};
+use crate::native::NativeError;
 use error::{PResult, ParseError, StateError};
 use offset::{BufferOffset, ByteOffset};
-pub use view::View;
+pub use view::{ByteRun, View};
 
 /// Stateful parser with an associated buffer and offset-tracker.
 pub struct Parser<'a> {
     pub(crate) buffer: View<'a>,
     pub(crate) offset: BufferOffset,
+    /// Resource usage against the limits of the parse, lent to any sub-parsers
+    pub(crate) budget: Budget,
 }
 
 impl<'a> Parser<'a> {}
@@ -20,6 +25,7 @@ impl<'a> From<View<'a>> for Parser<'a> {
         Self {
             buffer: view,
             offset: BufferOffset::new(max_offset),
+            budget: Budget::default(),
         }
     }
 }
@@ -31,9 +37,94 @@ impl<'a> Parser<'a> {
         Self {
             buffer: View::new(buffer),
             offset: BufferOffset::new(max_offset),
+            budget: Budget::default(),
         }
     }
 
+    /// Returns `self` with the remainder of the parse subject to `limits`.
+    ///
+    /// Once exceeded, a limit causes a [`ParseError::LimitExceeded`] to be returned.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.budget = Budget::new(limits);
+        self
+    }
+
+    /// Runs the decoder `f` over a new `Parser` for `buffer` (e.g. the decompressed contents of a value),
+    /// which counts against the same limits as `self`.
+    pub fn with_sub_parser<'b, T>(
+        &mut self,
+        buffer: &'b [u8],
+        f: impl FnOnce(&mut Parser<'b>) -> PResult<T>,
+    ) -> PResult<T> {
+        self.with_sub_parser_from(View::new(buffer), f)
+    }
+
+    /// Runs the decoder `f` over a new `Parser` for `view`, which counts against the same limits as `self`.
+    pub fn with_sub_parser_from<'b, T>(
+        &mut self,
+        view: View<'b>,
+        f: impl FnOnce(&mut Parser<'b>) -> PResult<T>,
+    ) -> PResult<T> {
+        let mut sub_parser = Parser {
+            budget: std::mem::take(&mut self.budget),
+            ..Parser::from(view)
+        };
+        let ret = f(&mut sub_parser);
+        // the budget is handed back whether or not `f` succeeded, as the failure may yet be recovered from
+        self.budget = sub_parser.budget;
+        ret
+    }
+
+    /// Enters a decoder one level of format-nesting deeper than the current one, as one step of the parse.
+    ///
+    /// Must be balanced by a call to [`Parser::leave`] once the decoder has returned, whether or not it succeeded.
+    pub fn enter(&mut self) -> PResult<()> {
+        self.budget.step()?;
+        self.budget.enter()?;
+        Ok(())
+    }
+
+    /// Leaves a decoder entered by [`Parser::enter`].
+    pub fn leave(&mut self) {
+        self.budget.leave();
+    }
+
+    /// Records that another sequence element is about to be parsed.
+    pub fn tally_element(&self) -> PResult<()> {
+        self.budget.tally_elements(1)?;
+        Ok(())
+    }
+
+    /// Runs the implementation `f` of a native format over the bytes remaining in the current context,
+    /// advancing past however many of them it reports having consumed.
+    ///
+    /// Native formats cannot be parsed in bits-mode.
+    pub fn decode_native<T>(
+        &mut self,
+        f: impl FnOnce(&'a [u8]) -> Result<(T, usize), NativeError>,
+    ) -> PResult<T> {
+        let ByteOffset::Bytes(start) = self.get_current_offset() else {
+            return Err(ParseError::InternalError(StateError::BinaryModeError));
+        };
+        let (value, len) = f(&self.buffer.buffer[start..start + self.remaining()])?;
+        self.advance_by(len)?;
+        Ok(value)
+    }
+
+    /// Skips ahead to the next occurrence of `pattern` in the current context, or to its end if there
+    /// is none (see [`resync_offset`]), returning the bytes skipped over.
+    ///
+    /// Resynchronization cannot be performed in bits-mode.
+    pub fn resync(&mut self, pattern: &[ByteSet]) -> PResult<&'a [u8]> {
+        let ByteOffset::Bytes(start) = self.get_current_offset() else {
+            return Err(ParseError::InternalError(StateError::BinaryModeError));
+        };
+        let rest = &self.buffer.buffer[start..start + self.remaining()];
+        let len = resync_offset(rest, pattern);
+        self.advance_by(len)?;
+        Ok(&rest[..len])
+    }
+
     /// Creates a [`View`] starting from the current position in the Parser,
     /// provided it is in Bytes-mode.
     ///
@@ -47,6 +138,34 @@ impl<'a> Parser<'a> {
         self.buffer.offset(cur_offset).unwrap()
     }
 
+    /// Creates a [`View`] starting from the absolute offset `dest_offset` into the Parser's buffer, regardless
+    /// of the current position.
+    ///
+    /// Returns an error if `dest_offset` lies outside of the buffer.
+    pub fn view_at<N>(&self, dest_offset: N) -> PResult<View<'a>>
+    where
+        N: TryInto<usize>,
+    {
+        // NOTE - an offset that does not fit in a `usize` lies past the end of any buffer
+        let dest = dest_offset.try_into().unwrap_or(usize::MAX);
+        if dest < self.buffer.start_offset {
+            return Err(ParseError::NegativeIndex {
+                abs_target: dest,
+                abs_buf_start: self.buffer.start_offset,
+            });
+        }
+        self.buffer.offset(dest - self.buffer.start_offset)
+    }
+
+    /// Starts a [`ByteRun`] at the current position in the Parser, to accumulate the bytes read from it.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the Parser is currently in bits-mode.
+    pub fn byte_run(&self) -> ByteRun<'a> {
+        ByteRun::new(self.view())
+    }
+
     /// Advances the offset by `offset` positions, as if calling [`Self::read_byte`] that many
     /// times and ignoring the output beyond `Ok`/`Err` distinctions.
     ///
@@ -86,6 +205,7 @@ impl<'a> Parser<'a> {
         N: TryInto<usize, Error: std::fmt::Debug> + Copy,
     {
         let dest = dest_offset.try_into().unwrap();
+        self.budget.hop()?;
         if dest < self.buffer.start_offset {
             return Err(ParseError::NegativeIndex {
                 abs_target: dest,
@@ -343,6 +463,14 @@ impl<'a> Parser<'a> {
         self.offset.get_current_offset()
     }
 
+    /// Returns the byte-offset of the current position relative to the start of the original buffer, rather than
+    /// that of the `View` being parsed (if any), for the purposes of error-reporting.
+    ///
+    /// In bits-mode, this is the offset of the byte containing the next bit to be read.
+    pub fn get_absolute_offset(&self) -> usize {
+        self.buffer.start_offset + self.get_current_offset().as_bytes().0
+    }
+
     /// Returns a `u64`-valued byte-offset equivalent to the stored `ByteOffset`, for
     /// use in association with [`Format::Pos`].
     ///
@@ -359,3 +487,46 @@ impl<'a> Parser<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::{Limit, LimitExceeded};
+
+    fn assert_send<T: Send>() {}
+
+    #[test]
+    fn sub_parser_shares_budget() {
+        assert_send::<Parser<'static>>();
+
+        let mut parser = Parser::new(&[]).with_limits(Limits::default().max_seq_elements(2));
+        parser.tally_element().unwrap();
+        let res = parser.with_sub_parser(&[0], |sub_parser| {
+            sub_parser.tally_element()?;
+            sub_parser.tally_element()
+        });
+        let is_exceeded = |res: PResult<()>| {
+            matches!(
+                res,
+                Err(ParseError::LimitExceeded(LimitExceeded {
+                    limit: Limit::SeqElements,
+                    max: 2,
+                }))
+            )
+        };
+        assert!(is_exceeded(res));
+        // the elements tallied by the failed sub-parse still count against the limit
+        assert!(is_exceeded(parser.tally_element()));
+    }
+
+    #[test]
+    fn view_at_out_of_range() {
+        let parser = Parser::new(&[0, 1, 2, 3]);
+        assert_eq!(parser.view_at(2u32).unwrap().start_offset, 2);
+        assert!(matches!(parser.view_at(5u32), Err(ParseError::Overrun(_))));
+        assert!(matches!(
+            parser.view_at(u128::MAX),
+            Err(ParseError::Overrun(_))
+        ));
+    }
+}
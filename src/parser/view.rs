@@ -2,7 +2,8 @@ use crate::parser::error::ParseError as DoodleParseError;
 use crate::{
     alt::prelude::allsorts::{
         binary::{
-            U8, U16Be, U32Be, U64Be,
+            I8, I16Be, I16Le, I32Be, I32Le, I64Be, I64Le, U8, U16Be, U16Le, U32Be, U32Le, U64Be,
+            U64Le,
             read::{self, ReadArray, ReadScope},
         },
         error::ParseError as AllSortsParseError,
@@ -10,7 +11,7 @@ use crate::{
     parser::{error::OverrunKind, offset::ByteOffset},
 };
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
 pub struct View<'a> {
     pub(crate) buffer: &'a [u8],
     pub(crate) start_offset: usize,
@@ -91,4 +92,92 @@ impl<'a> View<'a> {
     pub fn read_array_u64be(&self, len: usize) -> Result<ReadArray<'a, U64Be>, DoodleParseError> {
         self.as_read_array(len)
     }
+
+    pub fn read_array_i8(&self, len: usize) -> Result<ReadArray<'a, I8>, DoodleParseError> {
+        self.as_read_array(len)
+    }
+
+    pub fn read_array_i16be(&self, len: usize) -> Result<ReadArray<'a, I16Be>, DoodleParseError> {
+        self.as_read_array(len)
+    }
+
+    pub fn read_array_i32be(&self, len: usize) -> Result<ReadArray<'a, I32Be>, DoodleParseError> {
+        self.as_read_array(len)
+    }
+
+    pub fn read_array_i64be(&self, len: usize) -> Result<ReadArray<'a, I64Be>, DoodleParseError> {
+        self.as_read_array(len)
+    }
+
+    pub fn read_array_u16le(&self, len: usize) -> Result<ReadArray<'a, U16Le>, DoodleParseError> {
+        self.as_read_array(len)
+    }
+
+    pub fn read_array_u32le(&self, len: usize) -> Result<ReadArray<'a, U32Le>, DoodleParseError> {
+        self.as_read_array(len)
+    }
+
+    pub fn read_array_u64le(&self, len: usize) -> Result<ReadArray<'a, U64Le>, DoodleParseError> {
+        self.as_read_array(len)
+    }
+
+    pub fn read_array_i16le(&self, len: usize) -> Result<ReadArray<'a, I16Le>, DoodleParseError> {
+        self.as_read_array(len)
+    }
+
+    pub fn read_array_i32le(&self, len: usize) -> Result<ReadArray<'a, I32Le>, DoodleParseError> {
+        self.as_read_array(len)
+    }
+
+    pub fn read_array_i64le(&self, len: usize) -> Result<ReadArray<'a, I64Le>, DoodleParseError> {
+        self.as_read_array(len)
+    }
+}
+
+/// Run of consecutive bytes read from a [`View`], which are borrowed from it rather than copied.
+///
+/// Used by generated code, in place of a `Vec<u8>`, to accumulate repetitions of plain bytes.
+#[derive(Copy, Clone, Debug)]
+pub struct ByteRun<'a> {
+    view: View<'a>,
+    len: usize,
+}
+
+impl<'a> ByteRun<'a> {
+    /// Starts an empty run at the beginning of `view`.
+    pub fn new(view: View<'a>) -> Self {
+        Self { view, len: 0 }
+    }
+
+    /// Extends the run by one byte, which must be the next one in the View.
+    pub fn push(&mut self, byte: u8) {
+        debug_assert_eq!(self.view.buffer.get(self.len), Some(&byte));
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bytes of the run, as a slice of the underlying buffer.
+    pub fn into_slice(self) -> &'a [u8] {
+        self.view.read_len(self.len)
+    }
+}
+
+/// Serializes only the absolute offset of the View, mirroring the interpreter's `Value::View`.
+impl serde::Serialize for View<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("View", 1)?;
+        state.serialize_field("offset", &self.start_offset)?;
+        state.end()
+    }
 }
@@ -25,6 +25,14 @@ pub enum Pattern {
     Variant(Label, Box<Pattern>),
     Seq(Vec<Pattern>),
     Option(Option<Box<Pattern>>),
+    /// Matches a record whose listed fields match the corresponding patterns; unlisted fields are ignored.
+    Record(Vec<(Label, Pattern)>),
+    /// Matches if any of the alternatives match, which must all bind the same set of names.
+    Or(Vec<Pattern>),
+    /// Matches if the inner pattern matches and the condition holds with its bindings in scope.
+    ///
+    /// Only permitted as the outermost pattern of a match arm.
+    Guard(Box<Pattern>, Box<Expr>),
 }
 
 impl Pattern {
@@ -57,6 +65,123 @@ impl Pattern {
         Pattern::Binding(name.into())
     }
 
+    pub fn record<Name: IntoLabel>(fields: impl IntoIterator<Item = (Name, Pattern)>) -> Pattern {
+        Pattern::Record(
+            fields
+                .into_iter()
+                .map(|(label, pat)| (label.into(), pat))
+                .collect(),
+        )
+    }
+
+    /// Attaches a boolean `guard` to this pattern, to be used as the pattern of a match arm.
+    pub fn guarded(self, guard: Expr) -> Pattern {
+        Pattern::Guard(Box::new(self), Box::new(guard))
+    }
+
+    /// Splits the guard, if any, off the outermost layer of this pattern.
+    pub fn split_guard(&self) -> (&Pattern, Option<&Expr>) {
+        match self {
+            Pattern::Guard(pat, guard) => (pat, Some(guard)),
+            _ => (self, None),
+        }
+    }
+
+    /// Returns `true` if the guard of this pattern, if any, refers to a variable `name` from outside the pattern.
+    pub(crate) fn guard_mentions(&self, name: &str) -> bool {
+        !self.shadows(name) && self.split_guard().1.is_some_and(|g| g.is_shadowed_by(name))
+    }
+
+    /// Iterates over the immediate subpatterns of this pattern.
+    pub(crate) fn subpatterns(&self) -> Box<dyn Iterator<Item = &Pattern> + '_> {
+        match self {
+            Pattern::Tuple(ps) | Pattern::Seq(ps) | Pattern::Or(ps) => Box::new(ps.iter()),
+            Pattern::Record(fields) => Box::new(fields.iter().map(|(_, p)| p)),
+            Pattern::Variant(_, p) | Pattern::Option(Some(p)) | Pattern::Guard(p, _) => {
+                Box::new(std::iter::once(p.as_ref()))
+            }
+            _ => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Returns the names bound by this pattern, in the order they are bound.
+    ///
+    /// For or-patterns, only the names bound by the first alternative are visited.
+    pub fn bound_names(&self) -> Vec<&Label> {
+        let mut names = Vec::new();
+        self.collect_bound_names(&mut names);
+        names
+    }
+
+    fn collect_bound_names<'a>(&'a self, names: &mut Vec<&'a Label>) {
+        match self {
+            Pattern::Binding(name) => names.push(name),
+            Pattern::Or(alts) => {
+                if let Some(alt) = alts.first() {
+                    alt.collect_bound_names(names);
+                }
+            }
+            _ => {
+                for p in self.subpatterns() {
+                    p.collect_bound_names(names);
+                }
+            }
+        }
+    }
+
+    /// Expands every or-pattern within this pattern, returning the list of or-free patterns
+    /// that together match the same values, in the order they would be tried.
+    pub(crate) fn alternatives(&self) -> Vec<Pattern> {
+        fn product<T: Clone>(acc: Vec<Vec<T>>, options: Vec<T>) -> Vec<Vec<T>> {
+            acc.into_iter()
+                .flat_map(|prefix| {
+                    options.iter().map(move |opt| {
+                        let mut next = prefix.clone();
+                        next.push(opt.clone());
+                        next
+                    })
+                })
+                .collect()
+        }
+        fn expand_all(ps: &[Pattern]) -> Vec<Vec<Pattern>> {
+            ps.iter()
+                .fold(vec![Vec::new()], |acc, p| product(acc, p.alternatives()))
+        }
+        match self {
+            Pattern::Or(alts) => alts.iter().flat_map(Pattern::alternatives).collect(),
+            Pattern::Tuple(ps) => expand_all(ps).into_iter().map(Pattern::Tuple).collect(),
+            Pattern::Seq(ps) => expand_all(ps).into_iter().map(Pattern::Seq).collect(),
+            Pattern::Record(fields) => {
+                let (labels, ps): (Vec<_>, Vec<_>) = fields.iter().cloned().unzip();
+                expand_all(&ps)
+                    .into_iter()
+                    .map(|ps| Pattern::Record(labels.iter().cloned().zip(ps).collect()))
+                    .collect()
+            }
+            Pattern::Variant(label, p) => p
+                .alternatives()
+                .into_iter()
+                .map(|p| Pattern::Variant(label.clone(), Box::new(p)))
+                .collect(),
+            Pattern::Option(Some(p)) => p
+                .alternatives()
+                .into_iter()
+                .map(|p| Pattern::Option(Some(Box::new(p))))
+                .collect(),
+            Pattern::Guard(p, guard) => p
+                .alternatives()
+                .into_iter()
+                .map(|p| Pattern::Guard(Box::new(p), guard.clone()))
+                .collect(),
+            _ => vec![self.clone()],
+        }
+    }
+
+    /// Returns `true` if this pattern contains an or-pattern at any depth.
+    pub(crate) fn has_or(&self) -> bool {
+        matches!(self, Pattern::Or(..)) || self.subpatterns().any(Pattern::has_or)
+    }
+
     pub(crate) fn build_scope(&self, scope: &mut TypeScope<'_>, t: Rc<ValueType>) {
         match (self, t.as_ref()) {
             (Pattern::Binding(name), t) => {
@@ -100,6 +225,18 @@ impl Pattern {
                     panic!("no {label} in {branches:?}");
                 }
             }
+            (Pattern::Record(pfields), ValueType::Record(fields)) => {
+                for (label, p) in pfields {
+                    if let Some((_, t)) = fields.iter().find(|(l, _)| l == label) {
+                        p.build_scope(scope, Rc::new(t.clone()));
+                    } else {
+                        panic!("no {label} in {fields:?}");
+                    }
+                }
+            }
+            // NOTE - every alternative binds the same names at the same types
+            (Pattern::Or(alts), _) if !alts.is_empty() => alts[0].build_scope(scope, t),
+            (Pattern::Guard(p, _), _) => p.build_scope(scope, t),
             (l, r) => panic!("pattern build_scope failed: ({l:?}, {r:?})"),
         }
     }
@@ -149,6 +286,18 @@ impl Pattern {
                     panic!("no {label} in {branches:?}");
                 }
             }
+            (Pattern::Record(pfields), ValueTypeExt::Record(fields)) => {
+                for (label, p) in pfields {
+                    if let Some((_, t)) = fields.iter().find(|(l, _)| l == label) {
+                        p.build_scope_ext(scope, Rc::new(t.clone()));
+                    } else {
+                        panic!("no {label} in {fields:?}");
+                    }
+                }
+            }
+            // NOTE - every alternative binds the same names at the same types
+            (Pattern::Or(alts), _) if !alts.is_empty() => alts[0].build_scope_ext(scope, t),
+            (Pattern::Guard(p, _), _) => p.build_scope_ext(scope, t),
             (l, r) => panic!("pattern build_scope_ext failed: ({l:?}, {r:?})"),
         }
     }
@@ -215,6 +364,9 @@ impl Pattern {
             Pattern::Variant(_, p) => p.shadows(name),
             Pattern::Seq(ps) => ps.iter().any(|p| p.shadows(name)),
             Pattern::Option(opt_p) => opt_p.as_ref().is_some_and(|p| p.shadows(name)),
+            Pattern::Record(fields) => fields.iter().any(|(_, p)| p.shadows(name)),
+            Pattern::Or(alts) => alts.first().is_some_and(|p| p.shadows(name)),
+            Pattern::Guard(p, _) => p.shadows(name),
         }
     }
 }
@@ -1,16 +1,20 @@
 use num_traits::{One, one};
 use std::borrow::Cow;
-use std::ops::{Bound, RangeBounds};
+use std::ops::{Bound, Deref, DerefMut, RangeBounds};
 
 pub use crate::byte_set::ByteSet;
+pub use crate::limits::Limits;
 pub use crate::numeric::eval::*;
 pub use crate::parser::{
-    Parser, View,
-    error::{PResult, ParseError},
+    ByteRun, Parser, View,
+    error::{PResult, ParseError, UResult, UnparseError},
 };
 pub use smallsorts::{
     self as allsorts,
-    binary::{U8, U16Be, U32Be, U64Be, read::ReadArray},
+    binary::{
+        I8, I16Be, I16Le, I32Be, I32Le, I64Be, I64Le, U8, U16Be, U16Le, U32Be, U32Le, U64Be, U64Le,
+        read::ReadArray,
+    },
 };
 
 /// Trait implemented over marker-type proxies that implement the most natural parse for their
@@ -45,17 +49,117 @@ pub trait CommonObject {
     }
 }
 
+/// Deferred parse of a [`CommonObject`] `T` at an offset into a [`View`], along with the arguments to parse it with.
+///
+/// Generated code holds these in place of the values of formats selected to be decoded lazily, so that they are only
+/// parsed when (and each time) [`resolve`](Self::resolve) is called.
+pub struct Lazy<'input, T: CommonObject> {
+    view: View<'input>,
+    offset: usize,
+    len: Option<usize>,
+    args: T::Args<'input>,
+    _marker: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<'input, T: CommonObject> Lazy<'input, T> {
+    /// Defers the parse of a `T` at `offset` into `view`.
+    pub fn new(view: View<'input>, offset: usize, args: T::Args<'input>) -> Self {
+        Self {
+            view,
+            offset,
+            len: None,
+            args,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Confines the deferred parse to a slice of `len` bytes.
+    pub fn with_len(self, len: usize) -> Self {
+        Self {
+            len: Some(len),
+            ..self
+        }
+    }
+
+    /// Returns the offset of the deferred parse, relative to the start of the input.
+    pub fn absolute_offset(&self) -> usize {
+        self.view.start_offset + self.offset
+    }
+
+    /// Performs the deferred parse, counting it against the limits of `parser` (ordinarily, the `Parser` that
+    /// deferred it).
+    pub fn resolve(&self, parser: &mut Parser<'_>) -> PResult<T::Output<'input>>
+    where
+        T::Args<'input>: Clone,
+    {
+        parser.with_sub_parser_from(self.view.offset(self.offset)?, |p| match self.len {
+            None => T::parse(p, self.args.clone()),
+            Some(len) => {
+                p.start_slice(len)?;
+                let ret = T::parse(p, self.args.clone())?;
+                p.end_slice()?;
+                Ok(ret)
+            }
+        })
+    }
+}
+
+impl<'input, T: CommonObject> Clone for Lazy<'input, T>
+where
+    T::Args<'input>: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            args: self.args.clone(),
+            ..*self
+        }
+    }
+}
+
+/// Empty deferred parse, as deserialized in place of any `Lazy` held by a generated type.
+impl<'input, T: CommonObject> Default for Lazy<'input, T>
+where
+    T::Args<'input>: Default,
+{
+    fn default() -> Self {
+        Self::new(View::default(), 0, Default::default())
+    }
+}
+
+impl<T: CommonObject> std::fmt::Debug for Lazy<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Lazy")
+            .field("offset", &self.absolute_offset())
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Serializes only the absolute offset of the deferred parse, as with `View`.
+impl<T: CommonObject> serde::Serialize for Lazy<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("Lazy", 1)?;
+        state.serialize_field("offset", &self.absolute_offset())?;
+        state.end()
+    }
+}
+
 /// Performs a checked_sub operation, returning an error if the result would be negative
+///
+/// The error is converted into the return type of the enclosing function, so this may be used in both decoders and encoders.
 #[macro_export]
 macro_rules! try_sub {
     ( $x:expr_2021, $y:expr_2021, $trace:expr_2021 ) => {
         match $x.checked_sub($y) {
             Some(z) => z,
             None => {
-                return Err(ParseError::UnsoundOperation(
-                    Some("underflow on subtraction"),
-                    $trace,
-                ))
+                return Err(
+                    ParseError::UnsoundOperation(Some("underflow on subtraction"), $trace).into(),
+                )
             }
         }
     };
@@ -105,14 +209,17 @@ where
 
 /// Performs a flat-map operation taking an iterator over `T` and returning a vector over `U`.
 ///
+/// The spans yielded by `f` may be either vectors or borrowed slices (e.g. of the input buffer).
+///
 /// Will short-circuit if `f` returns an `Err` at any point, preserving the error returned.
-pub fn try_flat_map_vec<T, U, E, F>(iter: impl Iterator<Item = T>, f: F) -> Result<Vec<U>, E>
+pub fn try_flat_map_vec<T, U, Us, E, F>(iter: impl Iterator<Item = T>, f: F) -> Result<Vec<U>, E>
 where
-    F: Fn(T) -> Result<Vec<U>, E>,
+    F: Fn(T) -> Result<Us, E>,
+    Us: Into<Vec<U>>,
 {
     let mut res: Vec<U> = Vec::new();
     for x in iter {
-        let mut y = f(x)?;
+        let mut y = f(x)?.into();
         res.append(&mut y);
     }
     Ok(res)
@@ -213,6 +320,84 @@ where
     slice.iter().find(|x| f(x) == query)
 }
 
+/// Collects the elements of an iterator satisfying the predicate `f`, in order.
+///
+/// Will short-circuit if `f` ever returns `Err(_)`, preserving the error returned.
+pub fn try_filter_vec<T, E, F>(iter: impl Iterator<Item = T>, f: F) -> Result<Vec<T>, E>
+where
+    T: Clone,
+    F: Fn(T) -> Result<bool, E>,
+{
+    let mut res: Vec<T> = Vec::new();
+    for x in iter {
+        if f(x.clone())? {
+            res.push(x);
+        }
+    }
+    Ok(res)
+}
+
+/// Pairs up the elements of two iterators, stopping at the end of the shorter of the two.
+pub fn zip_vec<T, U>(lhs: impl Iterator<Item = T>, rhs: impl Iterator<Item = U>) -> Vec<(T, U)> {
+    lhs.zip(rhs).collect()
+}
+
+/// Collects the elements of an iterator into a vector, in reverse order.
+pub fn reverse_vec<T>(iter: impl DoubleEndedIterator<Item = T>) -> Vec<T> {
+    iter.rev().collect()
+}
+
+/// Returns `true` if any element of an iterator satisfies the predicate `f`, stopping at the first that does.
+///
+/// Will short-circuit if `f` ever returns `Err(_)`, preserving the error returned.
+pub fn try_any<T, E, F>(iter: impl Iterator<Item = T>, f: F) -> Result<bool, E>
+where
+    F: Fn(T) -> Result<bool, E>,
+{
+    for x in iter {
+        if f(x)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Returns `true` if every element of an iterator satisfies the predicate `f`, stopping at the first that does not.
+///
+/// Will short-circuit if `f` ever returns `Err(_)`, preserving the error returned.
+pub fn try_all<T, E, F>(iter: impl Iterator<Item = T>, f: F) -> Result<bool, E>
+where
+    F: Fn(T) -> Result<bool, E>,
+{
+    for x in iter {
+        if !f(x)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Returns `true` if `f((prev, next))` holds for every pair of adjacent elements of an iterator
+/// (and so, trivially, if it has fewer than two elements).
+///
+/// Will short-circuit if `f` ever returns `Err(_)`, preserving the error returned.
+pub fn try_is_sorted_by<T, E, F>(mut iter: impl Iterator<Item = T>, f: F) -> Result<bool, E>
+where
+    T: Clone,
+    F: Fn((T, T)) -> Result<bool, E>,
+{
+    let Some(mut prev) = iter.next() else {
+        return Ok(true);
+    };
+    for next in iter {
+        if !f((prev, next.clone()))? {
+            return Ok(false);
+        }
+        prev = next;
+    }
+    Ok(true)
+}
+
 pub fn u16le(input: (u8, u8)) -> u16 {
     u16::from_le_bytes([input.0, input.1])
 }
@@ -523,3 +708,104 @@ pub fn repeat_between_finished(
     };
     Ok(out_of_reps || len == max)
 }
+
+/// Output buffer of a generated encoder.
+///
+/// Dereferences to the bytes written so far, and additionally tracks the absolute offset (`base`) at which
+/// the first of these bytes is located, so that alignment and [`Format::Pos`] are computed correctly when
+/// encoding a value that does not start at the beginning of its input.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UnparseBuffer {
+    base: usize,
+    bytes: Vec<u8>,
+}
+
+impl UnparseBuffer {
+    /// Constructs an empty buffer for an encoding that starts at the beginning of its input.
+    pub const fn new() -> Self {
+        Self::at_offset(0)
+    }
+
+    /// Constructs an empty buffer for an encoding that starts at absolute offset `base`.
+    pub const fn at_offset(base: usize) -> Self {
+        Self {
+            base,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Returns the absolute offset of the next byte to be written.
+    pub fn offset(&self) -> usize {
+        self.base + self.bytes.len()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl Deref for UnparseBuffer {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.bytes
+    }
+}
+
+impl DerefMut for UnparseBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.bytes
+    }
+}
+
+#[inline]
+/// Checks that a sequence being encoded for a fixed-count repetition ([`Format::RepeatCount`]) has exactly
+/// the number of elements (`expected`) that its format requires.
+pub fn check_length(expected: usize, found: usize) -> UResult<()> {
+    if expected != found {
+        return Err(UnparseError::BadLength { expected, found });
+    }
+    Ok(())
+}
+
+/// Completes the encoding of a [`Format::Slice`] of width `size` whose contents were written to `output`
+/// starting at index `start`, padding the remainder of the slice with zero-bytes.
+///
+/// Will return an error if more than `size` bytes were written since `start`.
+pub fn close_slice(output: &mut Vec<u8>, start: usize, size: usize) -> UResult<()> {
+    let written = output.len() - start;
+    if written > size {
+        return Err(UnparseError::SliceOverflow { size, written });
+    }
+    output.resize(start + size, 0);
+    Ok(())
+}
+
+#[inline]
+/// Pads `output` with zero-bytes until its absolute offset is a multiple of `n`, as required by [`Format::Align`].
+pub fn pad_align(output: &mut UnparseBuffer, n: usize) {
+    let rem = output.offset() % n;
+    if rem != 0 {
+        let len = output.len() + (n - rem);
+        output.resize(len, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pad_align_uses_absolute_offset() {
+        let mut output = UnparseBuffer::new();
+        output.push(0xff);
+        pad_align(&mut output, 4);
+        assert_eq!(*output, [0xff, 0, 0, 0]);
+
+        let mut output = UnparseBuffer::at_offset(6);
+        output.push(0xff);
+        pad_align(&mut output, 4);
+        assert_eq!(*output, [0xff, 0]);
+        assert_eq!(output.offset(), 8);
+    }
+}
@@ -1,10 +1,88 @@
+use std::cell::Cell;
+use std::path::Path;
+
 use serde::Serialize;
 
+/// The full contents of an input file, either read into memory or memory-mapped.
+///
+/// Dereferences to `[u8]`, so it can back either a [`ReadCtxt`] or a generated-code [`Parser`](crate::parser::Parser).
+/// A mapped file is only paged in as it is read, which keeps random-access formats (that seek to a few tables
+/// scattered through a large file) from loading the bytes they skip over.
+pub enum Input {
+    Owned(Vec<u8>),
+    Mapped(memmap2::Mmap),
+}
+
+impl Input {
+    /// Reads the entire file at `path` into memory.
+    pub fn read(path: impl AsRef<Path>) -> std::io::Result<Input> {
+        Ok(Input::Owned(std::fs::read(path)?))
+    }
+
+    /// Memory-maps the file at `path`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified (or truncated), by this or any other process, while the returned `Input`
+    /// is alive: the mapped bytes would change (or fault on access) underneath the `&[u8]` it dereferences to.
+    pub unsafe fn map(path: impl AsRef<Path>) -> std::io::Result<Input> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY - upheld by the caller, as per the contract of this method
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Input::Mapped(map))
+    }
+
+    pub fn ctxt(&self) -> ReadCtxt<'_> {
+        ReadCtxt::new(self)
+    }
+}
+
+impl std::ops::Deref for Input {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Input::Owned(bytes) => bytes,
+            Input::Mapped(map) => map,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Serialize)]
 pub struct ReadCtxt<'a> {
     #[serde(skip)]
     pub input: &'a [u8],
     pub offset: usize,
+    #[serde(skip)]
+    window: Option<&'a StreamWindow>,
+}
+
+/// Bookkeeping shared by every `ReadCtxt` over the partially-buffered prefix of a stream.
+///
+/// Records whether a decode ever depended on the end of the buffered bytes (by reading past them, or
+/// by observing how many remain), in which case its outcome may change once more of the stream is read.
+#[derive(Debug)]
+pub struct StreamWindow {
+    /// Absolute stream offset of the first buffered byte
+    base: usize,
+    /// Number of bytes currently buffered
+    len: usize,
+    exhausted: Cell<bool>,
+}
+
+impl StreamWindow {
+    pub fn new(base: usize, len: usize) -> StreamWindow {
+        StreamWindow {
+            base,
+            len,
+            exhausted: Cell::new(false),
+        }
+    }
+
+    /// Returns `true` if any `ReadCtxt` over this window reached the end of the buffered bytes.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted.get()
+    }
 }
 
 impl<'a> std::fmt::Debug for ReadCtxt<'a> {
@@ -21,10 +99,48 @@ impl<'a> std::fmt::Debug for ReadCtxt<'a> {
 impl<'a> ReadCtxt<'a> {
     pub fn new(input: &'a [u8]) -> ReadCtxt<'a> {
         let offset = 0;
-        ReadCtxt { input, offset }
+        ReadCtxt {
+            input,
+            offset,
+            window: None,
+        }
+    }
+
+    /// Creates a new `ReadCtxt` over `input`, the currently-buffered bytes of a stream described by `window`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input` is not exactly as long as the window.
+    pub fn streaming(input: &'a [u8], window: &'a StreamWindow) -> ReadCtxt<'a> {
+        assert_eq!(input.len(), window.len);
+        ReadCtxt {
+            input,
+            offset: 0,
+            window: Some(window),
+        }
+    }
+
+    /// Returns the absolute position of the current offset, accounting for any portion of a stream that
+    /// has already been consumed and discarded.
+    pub fn position(&self) -> usize {
+        match self.window {
+            Some(window) => window.base + self.offset,
+            None => self.offset,
+        }
+    }
+
+    /// Marks the stream window (if any) as exhausted, unless `self` is a `Slice` that ends before the
+    /// buffered bytes do.
+    fn touch_end(&self) {
+        if let Some(window) = self.window
+            && self.input.len() == window.len
+        {
+            window.exhausted.set(true);
+        }
     }
 
     pub fn remaining(&self) -> &'a [u8] {
+        self.touch_end();
         &self.input[self.offset..]
     }
     /// Creates a new `ReadCtxt` with the same `input` as the current `ReadCtxt`, but with an `offset` of `n`.
@@ -34,6 +150,7 @@ impl<'a> ReadCtxt<'a> {
         if n <= self.input.len() {
             Some(ReadCtxt { offset: n, ..*self })
         } else {
+            self.touch_end();
             None
         }
     }
@@ -58,16 +175,15 @@ impl<'a> ReadCtxt<'a> {
             };
             Some((fst, snd))
         } else {
+            self.touch_end();
             None
         }
     }
 
     pub(crate) fn skip_remainder(&self) -> ReadCtxt<'a> {
+        self.touch_end();
         let offset = self.input.len();
-        ReadCtxt {
-            input: self.input,
-            offset,
-        }
+        ReadCtxt { offset, ..*self }
     }
 }
 
@@ -83,6 +199,7 @@ impl<'a> ReadCtxt<'a> {
                 },
             ))
         } else {
+            self.touch_end();
             None
         }
     }
@@ -99,6 +216,7 @@ impl<'a> ReadCtxt<'a> {
                 },
             ))
         } else {
+            self.touch_end();
             None
         }
     }
@@ -115,6 +233,7 @@ impl<'a> ReadCtxt<'a> {
                 },
             ))
         } else {
+            self.touch_end();
             None
         }
     }
@@ -131,7 +250,41 @@ impl<'a> ReadCtxt<'a> {
                 },
             ))
         } else {
+            self.touch_end();
             None
         }
     }
+
+    /// Reads exactly `N` bytes from the current offset, returning them as an array along with the advanced `ReadCtxt`.
+    pub fn read_array<const N: usize>(&self) -> Option<([u8; N], ReadCtxt<'a>)> {
+        if self.offset + N <= self.input.len() {
+            let raw = &self.input[self.offset..self.offset + N];
+            Some((
+                raw.try_into().unwrap(),
+                ReadCtxt {
+                    offset: self.offset + N,
+                    ..*self
+                },
+            ))
+        } else {
+            self.touch_end();
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mapped_input_matches_read() {
+        let read = Input::read("test.png").unwrap();
+        // SAFETY - test files are never modified
+        let mapped = unsafe { Input::map("test.png") }.unwrap();
+        assert!(matches!(mapped, Input::Mapped(_)));
+        assert_eq!(&*read, &*mapped);
+        let (b, rest) = mapped.ctxt().read_byte().unwrap();
+        assert_eq!((b, rest.offset), (0x89, 1));
+    }
 }
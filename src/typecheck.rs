@@ -7,11 +7,12 @@ use crate::try_with;
 use crate::util::ErrTrace as _;
 use crate::valuetype::{SeqBorrowHint, augmented::AugValueType};
 use crate::{
-    Arith, BaseType, DynFormat, Expr, Format, FormatModule, Label, Pattern, UnaryOp, ValueType,
-    ViewExpr, ViewFormat,
+    Arith, BaseType, DynFormat, Expr, ExprFunction, Format, FormatModule, Label, Pattern, UnaryOp,
+    ValueType, ViewExpr, ViewFormat,
 };
 use crate::{
     base_set::PrimIntSet,
+    marker::Endian,
     numeric::{
         MachineRep, NumRep,
         core::{BitWidth, Bounds as ZBounds, Expr as NExpr},
@@ -241,6 +242,9 @@ pub(crate) mod scope {
         pub(crate) scope: &'a UScope<'a>,
         pub(crate) dyn_s: DynScope<'a>,
         pub(crate) views: ViewScope<'a>,
+        /// Whether repetitions of plain bytes are decoded from the input, in bytes-mode, so that they may be
+        /// borrowed from it rather than copied
+        pub(crate) borrow_bytes: bool,
     }
 
     impl<'a> Ctxt<'a> {
@@ -250,8 +254,18 @@ pub(crate) mod scope {
                 scope,
                 dyn_s: DynScope::new(),
                 views: ViewScope::new(),
+                borrow_bytes: true,
             }
         }
+
+        /// Returns a copy of `self` with `borrow_bytes` set to the given value.
+        pub(crate) fn with_borrow_bytes(self, borrow_bytes: bool) -> Ctxt<'a> {
+            Self {
+                borrow_bytes,
+                ..self
+            }
+        }
+
         /// Returns a copy of `self` with the given `UScope` instead of `self.scope`.
         pub(crate) fn with_scope(&'a self, scope: &'a UScope<'a>) -> Ctxt<'a> {
             Self {
@@ -259,6 +273,7 @@ pub(crate) mod scope {
                 dyn_s: self.dyn_s,
                 views: self.views,
                 scope,
+                borrow_bytes: self.borrow_bytes,
             }
         }
 
@@ -268,6 +283,7 @@ pub(crate) mod scope {
                 dyn_s: self.dyn_s,
                 scope: self.scope,
                 views: ViewScope::Single(ViewSingleScope::new(&self.views, name)),
+                borrow_bytes: self.borrow_bytes,
             }
         }
 
@@ -277,6 +293,7 @@ pub(crate) mod scope {
                 dyn_s: self.dyn_s,
                 views: ViewScope::Multi(views),
                 scope: self.scope,
+                borrow_bytes: self.borrow_bytes,
             }
         }
 
@@ -286,6 +303,7 @@ pub(crate) mod scope {
                 dyn_s: DynScope::Single(DynSingleScope::new(&self.dyn_s, name, dynf_var)),
                 scope: self.scope,
                 views: self.views.clone(),
+                borrow_bytes: self.borrow_bytes,
             }
         }
     }
@@ -343,6 +361,8 @@ pub enum UType {
     /// For `std::option::Option<InnerType>`
     Option(Rc<UType>),
     PhantomData(Rc<UType>),
+    /// Deferred parse of a format at an offset, for formats selected to be decoded lazily in generated code
+    Lazy(Rc<UType>),
     // REVIEW[epic=embedded-num] - should this be PrimInt instead?
     Int(IntType),
 }
@@ -362,8 +382,10 @@ impl UType {
         Self::Seq(self, SeqBorrowHint::BufferView)
     }
 
-    pub fn seq_array(self: Rc<Self>) -> Self {
-        Self::Seq(self, SeqBorrowHint::ReadArray)
+    /// Constructs a `UType::Seq` with an elem-type of `self` and a
+    /// borrow-hint of [`SeqBorrowHint::ReadArray`] in the given byte-order.
+    pub fn seq_array(self: Rc<Self>, endian: Endian) -> Self {
+        Self::Seq(self, SeqBorrowHint::ReadArray(endian))
     }
 }
 
@@ -430,7 +452,7 @@ impl UType {
             | UType::Base(..) => Box::new(std::iter::empty()),
             UType::Tuple(ts) => Box::new(ts.iter().cloned()),
             UType::Record(fs) => Box::new(fs.iter().map(|(_l, t)| t.clone())),
-            UType::Seq(t, _) | UType::Option(t) | UType::PhantomData(t) => {
+            UType::Seq(t, _) | UType::Option(t) | UType::PhantomData(t) | UType::Lazy(t) => {
                 Box::new(std::iter::once(t.clone()))
             }
         }
@@ -707,6 +729,15 @@ pub struct TypeChecker {
     varmaps: VarMapMap,
     /// Association between ItemVar/FormatRef levels in the FormatModule and the UVar they are mapped to
     level_vars: HashMap<usize, UVar>,
+    /// Levels that may be decoded from a buffer other than the input, or in bits-mode, whose repetitions of plain bytes
+    /// are therefore never borrowed from the input
+    off_input_levels: BTreeSet<usize>,
+    /// Levels whose parses at an offset (through `ParseFromView` or `WithRelativeOffset`) are deferred in generated code
+    lazy_levels: BTreeSet<usize>,
+    /// Functions of the module being checked, each of whose bodies is inferred upon the first call to it
+    functions: Vec<ExprFunction>,
+    /// Association between the functions of the module and the UVars of their parameters and return value
+    function_vars: HashMap<usize, (Vec<UVar>, UVar)>,
     // /// Scaffolding for compartmentalized external type inference in the arithmetic extension grammar
     // sub_extension: EmbeddedResolver,
 }
@@ -720,6 +751,10 @@ impl TypeChecker {
             aliases: Vec::new(),
             varmaps: VarMapMap::new(),
             level_vars: HashMap::new(),
+            off_input_levels: BTreeSet::new(),
+            lazy_levels: BTreeSet::new(),
+            functions: Vec::new(),
+            function_vars: HashMap::new(),
             // sub_extension: EmbeddedResolver::new(),
         }
     }
@@ -884,7 +919,70 @@ impl TypeChecker {
                 )?;
                 Ok(outer_var)
             }
+            Pattern::Record(fields) => {
+                let rec_var = self.get_new_uvar();
+                for (fname, p) in fields.iter() {
+                    let fld_var = self.infer_var_scope_pattern(p, scope)?;
+                    self.unify_var_proj_field(rec_var, fname.clone(), fld_var)?;
+                }
+                Ok(rec_var)
+            }
+            Pattern::Or(alts) => {
+                let or_var = self.get_new_uvar();
+                let empty = UScope::Empty;
+                let mut first_bindings: Option<Vec<(Label, UVar)>> = None;
+                for alt in alts.iter() {
+                    let mut alt_scope = UMultiScope::new(&empty);
+                    let alt_var = self.infer_var_scope_pattern(alt, &mut alt_scope)?;
+                    self.unify_var_pair(or_var, alt_var)?;
+                    match first_bindings.as_ref() {
+                        None => first_bindings = Some(alt_scope.entries),
+                        Some(first) => {
+                            let mut lhs = first.clone();
+                            let mut rhs = alt_scope.entries;
+                            lhs.sort_by(|(l0, _), (l1, _)| l0.cmp(l1));
+                            rhs.sort_by(|(l0, _), (l1, _)| l0.cmp(l1));
+                            if lhs.len() != rhs.len()
+                                || Iterator::zip(lhs.iter(), rhs.iter()).any(|(l, r)| l.0 != r.0)
+                            {
+                                let names = |bs: Vec<(Label, UVar)>| {
+                                    bs.into_iter().map(|(l, _)| l).collect()
+                                };
+                                return Err(
+                                    TCErrorKind::OrBindingMismatch(names(lhs), names(rhs)).into()
+                                );
+                            }
+                            for ((_, l_var), (_, r_var)) in Iterator::zip(lhs.into_iter(), rhs) {
+                                self.unify_var_pair(l_var, r_var)?;
+                            }
+                        }
+                    }
+                }
+                for (name, var) in first_bindings.unwrap_or_default() {
+                    scope.push(name, var);
+                }
+                Ok(or_var)
+            }
+            Pattern::Guard(..) => Err(TCErrorKind::MisplacedGuard(pat.clone()).into()),
+        }
+    }
+
+    /// Infers the type of the pattern of a match arm, binding its names in `scope`, and checks that its guard,
+    /// if any, is boolean-typed.
+    fn infer_var_arm_pattern(
+        &mut self,
+        pat: &Pattern,
+        scope: &mut UMultiScope<'_>,
+    ) -> TCResult<UVar> {
+        let (pat, guard) = pat.split_guard();
+        let pvar = self.infer_var_scope_pattern(pat, scope)?;
+        if let Some(guard) = guard {
+            let tmp = scope.clone();
+            let guard_scope = UScope::Multi(&tmp);
+            let guard_var = self.infer_var_expr(guard, &guard_scope)?;
+            self.unify_var_utype(guard_var, Rc::new(UType::Base(BaseType::Bool)))?;
         }
+        Ok(pvar)
     }
 
     fn unify_utype_format_match_case(
@@ -896,7 +994,7 @@ impl TypeChecker {
         ctxt: Ctxt<'_>,
     ) -> TCResult<()> {
         let mut child = UMultiScope::new(ctxt.scope);
-        let pvar = self.infer_var_scope_pattern(pat, &mut child)?;
+        let pvar = self.infer_var_arm_pattern(pat, &mut child)?;
         let tmp = child.clone();
         let new_scope = UScope::Multi(&tmp);
         let new_ctxt = ctxt.with_scope(&new_scope);
@@ -920,7 +1018,7 @@ impl TypeChecker {
         scope: &'a UScope<'a>,
     ) -> TCResult<()> {
         let mut child = UMultiScope::new(scope);
-        let pvar = self.infer_var_scope_pattern(pat, &mut child)?;
+        let pvar = self.infer_var_arm_pattern(pat, &mut child)?;
         let tmp = child.clone();
         let new_scope = UScope::Multi(&tmp);
         let local_rhs_var = self.infer_var_expr(rhs_expr, &new_scope)?;
@@ -978,12 +1076,42 @@ impl TypeChecker {
         if let Some(ret) = self.level_vars.get(&level) {
             Ok(*ret)
         } else {
+            let ctxt = ctxt.with_borrow_bytes(!self.off_input_levels.contains(&level));
             let ret = self.infer_var_format(ctxt.module.get_format(level), ctxt)?;
             self.level_vars.insert(level, ret);
             Ok(ret)
         }
     }
 
+    /// Returns the UVars of the parameters and return value of the function at `index`, inferring its body upon
+    /// the first call.
+    ///
+    /// Every call to a function shares these, as generated code declares a single Rust function for each.
+    fn infer_vars_function(&mut self, index: usize) -> TCResult<(Vec<UVar>, UVar)> {
+        if let Some(vars) = self.function_vars.get(&index) {
+            return Ok(vars.clone());
+        }
+        let Some(func) = self.functions.get(index).cloned() else {
+            unreachable!("function {index} not in module")
+        };
+        let mut param_vars = Vec::with_capacity(func.params.len());
+        let mut param_scope = UMultiScope::with_capacity(&UScope::Empty, func.params.len());
+        for (name, vt) in func.params.iter() {
+            let param_var = self.get_new_uvar();
+            self.unify_var_valuetype(param_var, vt)?;
+            param_scope.push(name.clone(), param_var);
+            param_vars.push(param_var);
+        }
+        let ret_var = self.get_new_uvar();
+        self.unify_var_valuetype(ret_var, &func.ret)?;
+        // registered ahead of the body, for any recursive calls within it
+        self.function_vars
+            .insert(index, (param_vars.clone(), ret_var));
+        let body_var = self.infer_var_expr(func.body(), &UScope::Multi(&param_scope))?;
+        self.unify_var_pair(body_var, ret_var)?;
+        Ok((param_vars, ret_var))
+    }
+
     fn infer_var_view_format(
         &mut self,
         view_format: &ViewFormat,
@@ -1009,9 +1137,7 @@ impl TypeChecker {
                 self.unify_var_utype(
                     newvar,
                     // REVIEW - how do we distinguish CaptureBytes (seq_view ~> &'a [u8]) from ReadArray (seq_view ~> ReadArray<'a, K>)?
-                    Rc::new(UType::seq_array(Rc::new(UType::Base(BaseType::from(
-                        *kind,
-                    ))))),
+                    Rc::new(UType::seq_array(Rc::new(UType::from(*kind)), kind.endian())),
                 )?;
                 Ok(newvar)
             }
@@ -1184,7 +1310,8 @@ impl TypeChecker {
             | UType::Record(..)
             | UType::Seq(..)
             | UType::Option(..)
-            | UType::PhantomData(..) => {
+            | UType::PhantomData(..)
+            | UType::Lazy(..) => {
                 unreachable!(
                     "tree-var {} should not depend on non-numeric type ({:?})",
                     path.deepest_tree(graph.origin),
@@ -1272,7 +1399,10 @@ impl TypeChecker {
                 }
                 Ok(())
             }
-            UType::Seq(inner, _) | UType::Option(inner) | UType::PhantomData(inner) => {
+            UType::Seq(inner, _)
+            | UType::Option(inner)
+            | UType::PhantomData(inner)
+            | UType::Lazy(inner) => {
                 self.occurs_in(v, inner.clone())?;
                 Ok(())
             }
@@ -1664,6 +1794,14 @@ impl TypeChecker {
                     Ok(Rc::new(UType::PhantomData(inner)))
                 }
             }
+            (UType::Lazy(l1), UType::Lazy(l2)) => {
+                if l1 == l2 {
+                    Ok(left)
+                } else {
+                    let inner = try_with!(self.unify_utype(l1.clone(), l2.clone()) => "unify_utype@Lazy|Lazy");
+                    Ok(Rc::new(UType::Lazy(inner)))
+                }
+            }
             (UType::Base(b1), UType::Base(b2)) => {
                 if b1 != b2 {
                     return Err(UnificationError::Unsatisfiable(left, right).into());
@@ -1902,6 +2040,52 @@ impl TypeChecker {
         Ok(())
     }
 
+    /// Unifies a [`UVar`] with a declared [`ValueType`], allocating a fresh [`UVar`] for every nested component
+    /// (in pre-order) so that the resulting constraints are in weak head-normal form.
+    fn unify_var_declared(&mut self, uv: UVar, vt: &ValueType) -> TCResult<()> {
+        match vt {
+            ValueType::Tuple(ts) => {
+                let mut uts = Vec::with_capacity(ts.len());
+                for t in ts.iter() {
+                    uts.push(Rc::new(UType::Var(self.init_var_declared(t)?)));
+                }
+                self.unify_var_utype(uv, Rc::new(UType::Tuple(uts)))
+            }
+            ValueType::Record(fs) => {
+                let mut ufs = Vec::with_capacity(fs.len());
+                for (lbl, fvt) in fs.iter() {
+                    ufs.push((
+                        lbl.clone(),
+                        Rc::new(UType::Var(self.init_var_declared(fvt)?)),
+                    ));
+                }
+                self.unify_var_utype(uv, Rc::new(UType::Record(ufs)))
+            }
+            ValueType::Seq(inner) => {
+                let elem_v = self.init_var_declared(inner)?;
+                self.unify_var_utype(uv, Rc::new(Rc::new(UType::Var(elem_v)).seq()))
+            }
+            ValueType::Option(inner) => {
+                let param_v = self.init_var_declared(inner)?;
+                self.unify_var_utype(uv, Rc::new(Rc::new(UType::Var(param_v)).opt()))
+            }
+            ValueType::Union(branches) => {
+                for (lbl, bvt) in branches.iter() {
+                    let branch_v = self.init_var_declared(bvt)?;
+                    self.add_uvar_variant(uv, lbl.clone(), Rc::new(UType::Var(branch_v)))?;
+                }
+                Ok(())
+            }
+            _ => self.unify_var_valuetype(uv, vt),
+        }
+    }
+
+    fn init_var_declared(&mut self, vt: &ValueType) -> TCResult<UVar> {
+        let uv = self.get_new_uvar();
+        self.unify_var_declared(uv, vt)?;
+        Ok(uv)
+    }
+
     /// Takes two standalone `Constraint` objects and attempts to unify them, unifying any intermediate
     /// constraints that may occur in the same position in their expansion.
     ///
@@ -2428,6 +2612,24 @@ impl TypeChecker {
                 let _cx = self.unify_var_baseset(xvar, BaseSet::UAny)?;
                 newvar
             }
+            Expr::F32FromBits(bits) => {
+                let newvar = self.init_var_simple(UType::Base(BaseType::F32))?.0;
+                let ut = self.infer_utype_expr(bits.as_ref(), scope)?;
+                self.unify_utype(ut, Rc::new(UType::Base(BaseType::U32)))?;
+                newvar
+            }
+            Expr::F64FromBits(bits) => {
+                let newvar = self.init_var_simple(UType::Base(BaseType::F64))?.0;
+                let ut = self.infer_utype_expr(bits.as_ref(), scope)?;
+                self.unify_utype(ut, Rc::new(UType::Base(BaseType::U64)))?;
+                newvar
+            }
+            Expr::FixedFromBits(fx, bits) => {
+                let newvar = self.init_var_simple(UType::Base(BaseType::Fixed(*fx)))?.0;
+                let ut = self.infer_utype_expr(bits.as_ref(), scope)?;
+                self.unify_utype(ut, Rc::new(UType::Base(fx.raw_base_type())))?;
+                newvar
+            }
 
             Expr::U16Be(bytes) | Expr::U16Le(bytes) => {
                 let newvar = self.init_var_simple(UType::Base(BaseType::U16))?.0;
@@ -2519,6 +2721,16 @@ impl TypeChecker {
 
                 newvar
             }
+            Expr::Call(index, _, args) => {
+                let newvar = self.get_new_uvar();
+                let (param_vars, ret_var) = self.infer_vars_function(*index)?;
+                for (arg, param_var) in Iterator::zip(args.iter(), param_vars) {
+                    let arg_var = self.infer_var_expr(arg, scope)?;
+                    self.unify_var_pair(arg_var, param_var)?;
+                }
+                self.unify_var_pair(newvar, ret_var)?;
+                newvar
+            }
             Expr::FlatMap(f_expr, seq_expr) => {
                 let newvar = self.get_new_uvar();
 
@@ -2529,7 +2741,8 @@ impl TypeChecker {
 
                 self.unify_var_proj_elem(seq_v, in_v)?;
                 self.unify_var_proj_elem(out_v, out_elem_v)?;
-                self.unify_var_pair(newvar, out_v)?;
+                // NOTE - the flattened sequence is always constructed, even if the lambda yields borrowed slices
+                self.unify_var_utype(newvar, Rc::new(UType::seq(Rc::new(UType::Var(out_elem_v)))))?;
 
                 newvar
             }
@@ -2595,6 +2808,82 @@ impl TypeChecker {
 
                 newvar
             }
+            Expr::Filter(f_expr, seq_expr) => {
+                // NOTE - (x -> Bool) -> [x] -> [x]
+                let newvar = self.get_new_uvar();
+
+                let (in_v, out_v) = self.infer_vars_expr_lambda(f_expr, scope)?;
+                let xs_var = self.infer_var_expr(seq_expr, scope)?;
+
+                self.unify_var_proj_elem(xs_var, in_v)?;
+                self.unify_var_utype(out_v, Rc::new(UType::Base(BaseType::Bool)))?;
+                // NOTE - the filtered sequence is always constructed, even if the input is borrowed
+                self.unify_var_utype(newvar, Rc::new(UType::seq(Rc::new(UType::Var(in_v)))))?;
+
+                newvar
+            }
+            Expr::Zip(seq0, seq1) => {
+                // NOTE - [x] -> [y] -> [(x, y)]
+                let newvar = self.get_new_uvar();
+
+                let xs_var = self.infer_var_expr(seq0, scope)?;
+                let ys_var = self.infer_var_expr(seq1, scope)?;
+                let x_var = self.get_new_uvar();
+                let y_var = self.get_new_uvar();
+                let xy_var = self.get_new_uvar();
+
+                self.unify_var_proj_elem(xs_var, x_var)?;
+                self.unify_var_proj_elem(ys_var, y_var)?;
+                self.unify_var_utype(
+                    xy_var,
+                    Rc::new(UType::Tuple(vec![x_var.into(), y_var.into()])),
+                )?;
+                self.unify_var_utype(newvar, Rc::new(UType::seq(Rc::new(UType::Var(xy_var)))))?;
+
+                newvar
+            }
+            Expr::Reverse(seq_expr) => {
+                let newvar = self.get_new_uvar();
+
+                let xs_var = self.infer_var_expr(seq_expr, scope)?;
+                let x_var = self.get_new_uvar();
+
+                self.unify_var_proj_elem(xs_var, x_var)?;
+                self.unify_var_utype(newvar, Rc::new(UType::seq(Rc::new(UType::Var(x_var)))))?;
+
+                newvar
+            }
+            Expr::Any(f_expr, seq_expr) | Expr::All(f_expr, seq_expr) => {
+                // NOTE - (x -> Bool) -> [x] -> Bool
+                let newvar = self.get_new_uvar();
+
+                let (in_v, out_v) = self.infer_vars_expr_lambda(f_expr, scope)?;
+                let xs_var = self.infer_var_expr(seq_expr, scope)?;
+
+                self.unify_var_proj_elem(xs_var, in_v)?;
+                self.unify_var_utype(out_v, Rc::new(UType::Base(BaseType::Bool)))?;
+                self.unify_var_pair(newvar, out_v)?;
+
+                newvar
+            }
+            Expr::IsSortedBy(f_expr, seq_expr) => {
+                // NOTE - ((x, x) -> Bool) -> [x] -> Bool
+                let newvar = self.get_new_uvar();
+
+                let (pair_var, out_v) = self.infer_vars_expr_lambda(f_expr, scope)?;
+                let xs_var = self.infer_var_expr(seq_expr, scope)?;
+                let x_var = self.get_new_uvar();
+
+                self.unify_var_proj_elem(xs_var, x_var)?;
+                self.unify_var_utype(
+                    pair_var,
+                    Rc::new(UType::Tuple(vec![x_var.into(), x_var.into()])),
+                )?;
+                self.unify_var_utype(out_v, Rc::new(UType::Base(BaseType::Bool)))?;
+                self.unify_var_pair(newvar, out_v)?;
+
+                newvar
+            }
             Expr::FlatMapList(f_expr, ret_type, seq_expr) => {
                 // NOTE - (([y], x) -> [y]) -> Vt(y) -> [x] -> [y]
                 let ys_var = self.get_new_uvar();
@@ -2934,7 +3223,8 @@ impl TypeChecker {
                     | UType::Record(..)
                     | UType::Seq(..)
                     | UType::Option(..)
-                    | UType::PhantomData(..) => Err(TCErrorKind::NonNumeric(uv, cx.clone()).into()),
+                    | UType::PhantomData(..)
+                    | UType::Lazy(..) => Err(TCErrorKind::NonNumeric(uv, cx.clone()).into()),
                     UType::Int(it) => Ok(NumRep::Concrete(it.to_prim().into())),
                 },
                 Constraint::Elem(bs) => match bs {
@@ -3398,6 +3688,9 @@ impl TypeChecker {
                 self.unify_var_utype(newvar, Rc::new(UType::PhantomData(inner_var.into())))?;
                 Ok(newvar)
             }
+            Format::GenericVar(..) => {
+                unreachable!("generic formats must be monomorphized before type-checking")
+            }
             Format::ItemVar(level, args, views) => {
                 let newvar = self.get_new_uvar();
                 let level_var = if !args.is_empty() || !views.is_empty() {
@@ -3439,16 +3732,26 @@ impl TypeChecker {
             Format::SkipRemainder | Format::EndOfInput | Format::Align(_) => {
                 Ok(self.init_var_simple(UType::UNIT)?.0)
             }
+            Format::Native(index, args) => {
+                let newvar = self.get_new_uvar();
+                for arg in args {
+                    self.infer_var_expr(arg, ctxt.scope)?;
+                }
+                self.unify_var_declared(newvar, &ctxt.module.get_native(*index).value_type)?;
+                Ok(newvar)
+            }
             Format::DecodeBytes(expr, inner) => {
                 let newvar = self.get_new_uvar();
 
                 let v_expr = self.infer_var_expr(expr, ctxt.scope)?;
-                let v_inner = self.infer_var_format(inner.as_ref(), ctxt)?;
+                let v_inner =
+                    self.infer_var_format(inner.as_ref(), ctxt.with_borrow_bytes(false))?;
 
                 // we can only apply DecodeBytes to expressions of type `Seq(U8)`.
+                // NOTE - as the least restrictive borrow-hint, `BufferView` allows the bytes to be borrowed from the input
                 self.unify_var_utype(
                     v_expr,
-                    Rc::new(UType::seq(Rc::new(UType::Base(BaseType::U8)))),
+                    Rc::new(UType::seq_view(Rc::new(UType::Base(BaseType::U8)))),
                 )?;
 
                 // provided the previous unification succeeded, our output type is equivalent to the inferred type of the inner format
@@ -3462,9 +3765,9 @@ impl TypeChecker {
                 // view requires discovery but will always have View-kind
                 self.traverse_view_expr(view, ctxt)?;
 
-                // infer inner-format type and equate it with newvar
+                // infer inner-format type and equate it with newvar, or with its deferral
                 let v_inner = self.infer_var_format(inner.as_ref(), ctxt)?;
-                self.unify_var_pair(newvar, v_inner)?;
+                self.unify_var_offset_parse(newvar, inner, v_inner)?;
 
                 Ok(newvar)
             }
@@ -3508,7 +3811,7 @@ impl TypeChecker {
             Format::Repeat(inner) | Format::Repeat1(inner) => {
                 let newvar = self.get_new_uvar();
                 let t = self.infer_utype_format(inner, ctxt)?;
-                self.unify_var_utype(newvar, Rc::new(UType::seq(t)))?;
+                self.unify_var_utype(newvar, Self::repeat_utype(inner, t, ctxt))?;
                 Ok(newvar)
             }
             Format::RepeatCount(n, inner) => {
@@ -3517,7 +3820,7 @@ impl TypeChecker {
                 // NOTE - we don't care about the constraint, only whether it was successfully computed
                 let _constraint = self.unify_utype_baseset(n_type, BaseSet::UAny)?;
                 let inner_t = self.infer_utype_format(inner, ctxt)?;
-                self.unify_var_utype(newvar, Rc::new(UType::seq(inner_t)))?;
+                self.unify_var_utype(newvar, Self::repeat_utype(inner, inner_t, ctxt))?;
                 Ok(newvar)
             }
             Format::RepeatBetween(min, max, inner) => {
@@ -3528,7 +3831,7 @@ impl TypeChecker {
                     self.unify_utype_baseset(Rc::new(UType::Var(min_var)), BaseSet::UAny)?;
                 self.unify_var_pair(min_var, max_var)?;
                 let inner_t = self.infer_utype_format(inner, ctxt)?;
-                self.unify_var_utype(newvar, Rc::new(UType::seq(inner_t)))?;
+                self.unify_var_utype(newvar, Self::repeat_utype(inner, inner_t, ctxt))?;
                 Ok(newvar)
             }
             Format::RepeatUntilLast(f, inner) => {
@@ -3537,7 +3840,7 @@ impl TypeChecker {
                 let inner_t = self.infer_utype_format(inner, ctxt)?;
                 self.unify_var_utype(in_var, inner_t.clone())?;
                 self.unify_var_utype(out_var, Rc::new(UType::Base(BaseType::Bool)))?;
-                self.unify_var_utype(newvar, Rc::new(UType::seq(inner_t)))?;
+                self.unify_var_utype(newvar, Self::repeat_utype(inner, inner_t, ctxt))?;
                 Ok(newvar)
             }
             Format::RepeatUntilSeq(f, inner) => {
@@ -3614,7 +3917,7 @@ impl TypeChecker {
             }
             Format::Bits(inner) => {
                 let newvar = self.get_new_uvar();
-                let inner_t = self.infer_utype_format(inner, ctxt)?;
+                let inner_t = self.infer_utype_format(inner, ctxt.with_borrow_bytes(false))?;
                 self.unify_var_utype(newvar, inner_t)?;
                 Ok(newvar)
             }
@@ -3625,8 +3928,8 @@ impl TypeChecker {
                 self.unify_var_baseset(addr_var, BaseSet::UAny32)?;
                 // REVIEW - addr_var and offs_var only need to be compatible, not identical, but in our current model it is hard to support heterogenous typings
                 self.unify_var_pair(addr_var, offs_var)?;
-                let inner_t = self.infer_utype_format(inner, ctxt)?;
-                self.unify_var_utype(newvar, inner_t)?;
+                let v_inner = self.infer_var_format(inner, ctxt)?;
+                self.unify_var_offset_parse(newvar, inner, v_inner)?;
                 Ok(newvar)
             }
             Format::Map(inner, f) => {
@@ -3737,6 +4040,18 @@ impl TypeChecker {
                 self.unify_var_pair(newvar, dft_v)?;
                 Ok(newvar)
             }
+            Format::Resync(inner, _) => {
+                let newvar = self.get_new_uvar();
+                let inner_v = self.infer_var_format(inner, ctxt)?;
+                let skipped_v = self.init_var_declared(&ValueType::resync_skipped())?;
+                self.add_uvar_variant(newvar, Label::Borrowed("ok"), Rc::new(UType::Var(inner_v)))?;
+                self.add_uvar_variant(
+                    newvar,
+                    Label::Borrowed("skipped"),
+                    Rc::new(UType::Var(skipped_v)),
+                )?;
+                Ok(newvar)
+            }
             Format::LiftedOption(opt_f) => {
                 let newvar = self.get_new_uvar();
                 let inner_var = match opt_f {
@@ -3768,12 +4083,27 @@ impl TypeChecker {
         Ok(uv.into())
     }
 
-    pub(crate) fn infer_module(module: &FormatModule, top_format: &Format) -> TCResult<Self> {
+    /// Infers the types of `top_format` and of every format in `module`, or only of the specified root levels
+    /// (and any formats they reach) if `roots` is provided.
+    ///
+    /// Parses of the `lazy` levels at an offset are typed as [`UType::Lazy`].
+    pub(crate) fn infer_module(
+        module: &FormatModule,
+        top_format: &Format,
+        roots: Option<&[usize]>,
+        lazy: &[usize],
+    ) -> TCResult<Self> {
         let mut this = Self::new();
+        this.off_input_levels = off_input_levels(module, top_format);
+        this.lazy_levels = BTreeSet::from_iter(lazy.iter().copied());
+        this.functions = module.functions().to_vec();
         let scope = UScope::Empty;
         let ctxt = Ctxt::new(module, &scope);
 
-        let mut unexplored = BTreeSet::from_iter(0..module.formats.len());
+        let mut unexplored = match roots {
+            Some(levels) => BTreeSet::from_iter(levels.iter().copied()),
+            None => BTreeSet::from_iter(0..module.formats.len()),
+        };
 
         let _ = this.infer_var_format(top_format, ctxt)?;
         let mut seen_levels = this.level_vars.keys().copied().collect::<BTreeSet<usize>>();
@@ -3795,6 +4125,37 @@ impl TypeChecker {
         Ok(this)
     }
 
+    /// Unifies `newvar` with the type `v_inner` of the format `inner` parsed at an offset, or with its [`UType::Lazy`]
+    /// deferral if `inner` is a parse of a lazy level.
+    fn unify_var_offset_parse(
+        &mut self,
+        newvar: UVar,
+        inner: &Format,
+        v_inner: UVar,
+    ) -> TCResult<()> {
+        match inner.deferrable_level() {
+            Some(level) if self.lazy_levels.contains(&level) => {
+                self.unify_var_utype(newvar, Rc::new(UType::Lazy(v_inner.into())))?;
+            }
+            _ => {
+                self.unify_var_pair(newvar, v_inner)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the type of a repetition of `inner`, whose elements have type `elem_t`.
+    ///
+    /// Repetitions of plain bytes read from the input are hinted as views of the input, which they are
+    /// decoded as unless they are unified with sequences that are not.
+    fn repeat_utype(inner: &Format, elem_t: Rc<UType>, ctxt: Ctxt<'_>) -> Rc<UType> {
+        if ctxt.borrow_bytes && inner.is_plain_byte(ctxt.module) {
+            Rc::new(UType::seq_view(elem_t))
+        } else {
+            Rc::new(UType::seq(elem_t))
+        }
+    }
+
     pub fn lookup_level_var(&self, level: usize) -> Option<UVar> {
         if level == 0 {
             Some(UVar(0))
@@ -3870,6 +4231,8 @@ impl TypeChecker {
             UType::PhantomData(t0) => {
                 Some(AugValueType::PhantomData(Box::new(self.reify(t0.clone())?)))
             }
+            // NOTE - lazy parses are only distinguished in generated code, and otherwise yield the parsed value directly
+            UType::Lazy(t0) => self.reify(t0.clone()),
         }
     }
 
@@ -3925,6 +4288,7 @@ pub(crate) enum Expansion {
     Tuple(Vec<WHNFSolution>),
     ViewObj,
     PhantomData(WHNFSolution),
+    Lazy(WHNFSolution),
 }
 
 // SECTION - specialized methods for elaboration and codegen purposes
@@ -4013,6 +4377,10 @@ impl TypeChecker {
                 let v0 = WHNFSolution::coerce(&t0);
                 Expansion::PhantomData(v0)
             }
+            UType::Lazy(t0) => {
+                let v0 = WHNFSolution::coerce(t0);
+                Expansion::Lazy(v0)
+            }
         }
     }
 
@@ -4048,7 +4416,10 @@ impl TypeChecker {
 ///
 /// Otherwise returns `Ok(Some(vt))` where `vt` is the inferred value-type of `f`
 pub fn typecheck(module: &FormatModule, f: &Format) -> TCResult<Option<ValueType>> {
+    let (module, f) = crate::generic::monomorphize(module, f);
+    let (module, f) = (module.as_ref(), f.as_ref());
     let mut tc = TypeChecker::new();
+    tc.functions = module.functions().to_vec();
     let scope = UScope::new();
     let ctxt = Ctxt::new(module, &scope);
     let _ut = tc.infer_utype_format(f, ctxt)?;
@@ -4060,11 +4431,35 @@ pub fn typecheck(module: &FormatModule, f: &Format) -> TCResult<Option<ValueType
 
 pub type TCResult<T> = Result<T, TCError>;
 
+/// Returns the levels that may be reached, through `ItemVar` references, from within a `DecodeBytes` (which
+/// decodes a buffer other than the input) or a `Bits` (which reads bits rather than bytes), in `top_format` or
+/// in any format of `module`.
+fn off_input_levels(module: &FormatModule, top_format: &Format) -> BTreeSet<usize> {
+    let mut pending = Vec::new();
+    let mut stack = Vec::from_iter(std::iter::once(top_format).chain(module.formats.iter()));
+    while let Some(format) = stack.pop() {
+        match format {
+            Format::DecodeBytes(_, inner) | Format::Bits(inner) => {
+                pending.extend(inner.referenced_levels())
+            }
+            _ => stack.extend(format.subformats()),
+        }
+    }
+    let mut levels = BTreeSet::new();
+    while let Some(level) = pending.pop() {
+        if levels.insert(level) {
+            pending.extend(module.get_format(level).referenced_levels());
+        }
+    }
+    levels
+}
+
 mod __impl {
     use std::borrow::{Borrow, BorrowMut};
     use std::rc::Rc;
 
-    use crate::{BaseType, FormatModule};
+    use crate::numeric::elaborator::IntType;
+    use crate::{BaseKind, BaseType, FormatModule};
 
     use super::{Constraint, Constraints, Ctxt, NVar, ProjShape, UScope, UType, UVar, VMId};
 
@@ -4135,6 +4530,15 @@ mod __impl {
         }
     }
 
+    impl<X: Copy> From<BaseKind<X>> for UType {
+        fn from(value: BaseKind<X>) -> Self {
+            match value.signed_type() {
+                Some(s) => Self::Int(IntType::from(s)),
+                None => Self::Base(value.raw_base_type()),
+            }
+        }
+    }
+
     impl From<UVar> for UType {
         fn from(value: UVar) -> Self {
             Self::Var(value)
@@ -4417,4 +4821,55 @@ mod tests {
         assert_eq!(output, expected);
         Ok(())
     }
+
+    /// Typechecks a record whose field `x` is computed by matching the byte `number` against `arms`.
+    fn infer_match_arms(arms: Vec<(Pattern, Expr)>) -> TCResult<()> {
+        use crate::helper::{byte_in, expr_match, record, var};
+
+        let mut tc = TypeChecker::new();
+        let f = record([
+            ("number", byte_in(0..=255)),
+            ("x", compute(expr_match(var("number"), arms))),
+        ]);
+        let module = FormatModule::new();
+        let scope = UScope::new();
+        tc.infer_utype_format(&f, Ctxt::new(&module, &scope))?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_misplaced_guard() {
+        use crate::helper::{pat_guard, pat_or};
+
+        let guarded = pat_guard(Pattern::U8(0), Expr::Bool(true));
+        let arms = vec![
+            (pat_or([guarded.clone(), Pattern::U8(1)]), Expr::Bool(true)),
+            (Pattern::Wildcard, Expr::Bool(false)),
+        ];
+        let err = infer_match_arms(arms).unwrap_err();
+        assert!(matches!(*err.err, TCErrorKind::MisplacedGuard(ref pat) if *pat == guarded));
+
+        // the same guard is accepted at the top level of the arm
+        let arms = vec![
+            (guarded, Expr::Bool(true)),
+            (Pattern::Wildcard, Expr::Bool(false)),
+        ];
+        assert!(infer_match_arms(arms).is_ok());
+    }
+
+    #[test]
+    fn test_or_binding_mismatch() {
+        use crate::helper::{bind, pat_or};
+
+        let arms = vec![(pat_or([bind("a"), Pattern::U8(0)]), Expr::Bool(true))];
+        let err = infer_match_arms(arms).unwrap_err();
+        assert!(matches!(
+            *err.err,
+            TCErrorKind::OrBindingMismatch(ref lhs, ref rhs) if *lhs == [Label::from("a")] && rhs.is_empty()
+        ));
+
+        // alternatives binding the same names are accepted
+        let arms = vec![(pat_or([bind("a"), bind("a")]), Expr::Bool(true))];
+        assert!(infer_match_arms(arms).is_ok());
+    }
 }
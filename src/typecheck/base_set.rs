@@ -780,7 +780,11 @@ mod __impl {
                 BaseType::U16 => Ok(PrimInt::U16),
                 BaseType::U32 => Ok(PrimInt::U32),
                 BaseType::U64 => Ok(PrimInt::U64),
-                BaseType::Char | BaseType::Bool => Err(TryFromBaseTypeError(value)),
+                BaseType::Char
+                | BaseType::Bool
+                | BaseType::F32
+                | BaseType::F64
+                | BaseType::Fixed(_) => Err(TryFromBaseTypeError(value)),
             }
         }
     }
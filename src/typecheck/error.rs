@@ -215,6 +215,10 @@ pub enum TCErrorKind {
     NonNumeric(UVar, Constraints),
     /// Any issue reconciling BaseType numerics and IntType numerics
     CrossLayerNumeric(CrossLayerNumericError),
+    /// Pattern guard found somewhere other than the outermost pattern of a match arm
+    MisplacedGuard(Pattern),
+    /// Alternatives of an or-pattern bind different sets of names
+    OrBindingMismatch(Vec<Label>, Vec<Label>),
 }
 
 impl TCErrorKind {
@@ -284,6 +288,18 @@ impl std::fmt::Display for TCErrorKind {
             TCErrorKind::CrossLayerNumeric(err) => {
                 write!(f, "cross-layer numeric error: {err}")
             }
+            TCErrorKind::MisplacedGuard(pat) => {
+                write!(
+                    f,
+                    "pattern guard is only permitted as the outermost pattern of a match arm: `{pat:?}`"
+                )
+            }
+            TCErrorKind::OrBindingMismatch(lhs, rhs) => {
+                write!(
+                    f,
+                    "alternatives of or-pattern bind different names: {lhs:?} vs {rhs:?}"
+                )
+            }
         }
     }
 }
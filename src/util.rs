@@ -131,6 +131,14 @@ pub(crate) mod with_err {
             }
         }
 
+        /// Fallible version of [`WithErr::map`], returning the error of `f` if it fails.
+        pub fn try_map<U, E1>(self, f: impl FnOnce(T) -> Result<U, E1>) -> EResult<U, E0, E1> {
+            Ok(WithErr {
+                value: f(self.value)?,
+                errs: self.errs,
+            })
+        }
+
         /// Applies a closure `f` to the value held by `self`, aggregating the errors of `self` with those of the
         /// result.
         ///
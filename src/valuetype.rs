@@ -6,6 +6,7 @@ use serde::Serialize;
 use crate::codegen::rust_ast::MachineSint;
 use crate::{
     Label,
+    marker::Endian,
     numeric::{
         core::{MachineRep, NumRep},
         elaborator::{IntType, PrimInt},
@@ -21,9 +22,36 @@ pub enum BaseType {
     U32,
     U64,
     Char,
+    /// IEEE-754 single-precision float
+    F32,
+    /// IEEE-754 double-precision float
+    F64,
+    /// Binary fixed-point number with a given signedness and integer/fraction split
+    Fixed(FixedType),
+}
+
+impl std::fmt::Display for BaseType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool => write!(f, "bool"),
+            Self::U8 => write!(f, "u8"),
+            Self::U16 => write!(f, "u16"),
+            Self::U32 => write!(f, "u32"),
+            Self::U64 => write!(f, "u64"),
+            Self::Char => write!(f, "char"),
+            Self::F32 => write!(f, "f32"),
+            Self::F64 => write!(f, "f64"),
+            Self::Fixed(fx) => write!(f, "{fx}"),
+        }
+    }
 }
 
 impl BaseType {
+    /// Returns the name of `self` as a static string.
+    ///
+    /// As fixed-point types are parameterized, they are all given the same name, `"fixed"`; the
+    /// [`Display`](std::fmt::Display) impl should be used instead to distinguish them.
+    #[deprecated(note = "use the `Display` impl, which distinguishes between fixed-point types")]
     pub const fn to_static_str(self) -> &'static str {
         match self {
             Self::Bool => "bool",
@@ -32,19 +60,113 @@ impl BaseType {
             Self::U32 => "u32",
             Self::U64 => "u64",
             Self::Char => "char",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::Fixed(_) => "fixed",
         }
     }
+
+    /// Returns `true` if `self` is a machine-integer type.
+    ///
+    /// Note that floating-point and fixed-point types are not considered numeric in this sense,
+    /// as they do not participate in integer arithmetic or casts.
+    pub(crate) fn is_numeric(&self) -> bool {
+        matches!(self, Self::U8 | Self::U16 | Self::U32 | Self::U64)
+    }
 }
 
-impl std::fmt::Display for BaseType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_static_str())
+/// Binary fixed-point representation, determined by signedness, total bit-width, and the number of
+/// fractional bits.
+///
+/// The integer/fraction split is `(width - frac).frac`, so that e.g. the OpenType `Fixed` type is
+/// a signed 32-bit value with 16 fractional bits (`I16F16`), and `F2Dot14` is a signed 16-bit value
+/// with 14 fractional bits (`I2F14`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Hash, PartialOrd, Ord)]
+pub struct FixedType {
+    signed: bool,
+    width: u8,
+    frac: u8,
+}
+
+impl FixedType {
+    /// Signed 16.16 fixed-point (OpenType `Fixed`)
+    pub const I16F16: FixedType = FixedType::new(true, 32, 16);
+    /// Signed 2.14 fixed-point (OpenType `F2DOT14`)
+    pub const I2F14: FixedType = FixedType::new(true, 16, 14);
+    /// Unsigned 16.16 fixed-point
+    pub const U16F16: FixedType = FixedType::new(false, 32, 16);
+    /// Unsigned 8.8 fixed-point
+    pub const U8F8: FixedType = FixedType::new(false, 16, 8);
+
+    /// Constructs a new `FixedType` with the given signedness, total bit-width, and number of fractional bits.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `width` is not one of 8, 16, 32, or 64, or if `frac` exceeds `width`.
+    pub const fn new(signed: bool, width: u8, frac: u8) -> Self {
+        assert!(
+            matches!(width, 8 | 16 | 32 | 64),
+            "fixed-point width must be 8, 16, 32, or 64 bits"
+        );
+        assert!(
+            frac <= width,
+            "fixed-point fraction cannot exceed total width"
+        );
+        FixedType {
+            signed,
+            width,
+            frac,
+        }
+    }
+
+    pub const fn is_signed(self) -> bool {
+        self.signed
+    }
+
+    /// Returns the total number of bits in the representation.
+    pub const fn width(self) -> u8 {
+        self.width
+    }
+
+    /// Returns the number of fractional bits in the representation.
+    pub const fn frac_bits(self) -> u8 {
+        self.frac
+    }
+
+    /// Returns the number of integral bits (including the sign-bit, if any) in the representation.
+    pub const fn int_bits(self) -> u8 {
+        self.width - self.frac
+    }
+
+    /// Returns the unsigned machine-integer type whose bits are reinterpreted as this fixed-point type.
+    pub const fn raw_base_type(self) -> BaseType {
+        match self.width {
+            8 => BaseType::U8,
+            16 => BaseType::U16,
+            32 => BaseType::U32,
+            64 => BaseType::U64,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Interprets the low `width` bits of `bits` as a fixed-point value of this type, and converts it to `f64`.
+    ///
+    /// Conversion is exact for all representations of 53 significant bits or fewer.
+    pub fn to_f64(self, bits: u64) -> f64 {
+        let shift = 64 - self.width as u32;
+        let raw = if self.signed {
+            (((bits << shift) as i64) >> shift) as f64
+        } else {
+            ((bits << shift) >> shift) as f64
+        };
+        raw / (2.0f64).powi(self.frac as i32)
     }
 }
 
-impl BaseType {
-    pub(crate) fn is_numeric(&self) -> bool {
-        matches!(self, Self::U8 | Self::U16 | Self::U32 | Self::U64)
+impl std::fmt::Display for FixedType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.signed { 'I' } else { 'U' };
+        write!(f, "{sign}{}F{}", self.int_bits(), self.frac)
     }
 }
 
@@ -113,8 +235,8 @@ pub enum SeqBorrowHint {
     Constructed,
     /// Hint specific to [`ViewFormat::ReadArray`], which is backed by a fixed slice of the source-buffer
     /// and interpreted dynamically as a series of fixed-width values with a common type and unambiguous
-    /// mapping from bytes to values (e.g. `U16Be`).
-    ReadArray,
+    /// mapping from bytes to values (e.g. `U16Be`), in the given byte-order.
+    ReadArray(Endian),
     /// Hint for an implied view (slice) of the buffer-data, e.g. `ViewFormat::CaptureBytes`.
     BufferView,
 }
@@ -132,7 +254,7 @@ impl SeqBorrowHint {
 
     /// Returns `true` if the hint is [`SeqBorrowHint::ReadArray`].
     pub fn is_read_array(&self) -> bool {
-        matches!(self, Self::ReadArray)
+        matches!(self, Self::ReadArray(_))
     }
 }
 
@@ -227,6 +349,9 @@ impl ValueType {
     pub const I32: ValueType = ValueType::Signed(SignedIntType::I32);
     pub const I64: ValueType = ValueType::Signed(SignedIntType::I64);
 
+    pub const F32: ValueType = ValueType::Base(BaseType::F32);
+    pub const F64: ValueType = ValueType::Base(BaseType::F64);
+
     /// Formalization of the hard-coded `u32` type for sequence lengths to avoid hardcoding U32 directly over multiple modules.
     pub const SEQ_LEN_T: ValueType = ValueType::Base(BaseType::U32);
 
@@ -235,6 +360,23 @@ impl ValueType {
         ValueType::Option(Box::new(ty))
     }
 
+    /// Type of the record describing a region skipped over by [`Format::Resync`](crate::Format::Resync).
+    pub fn resync_skipped() -> ValueType {
+        let bytes = ValueType::Seq(Box::new(ValueType::U8));
+        ValueType::Record(vec![
+            (Label::Borrowed("error"), bytes.clone()),
+            (Label::Borrowed("data"), bytes),
+        ])
+    }
+
+    /// Type of a [`Format::Resync`](crate::Format::Resync) whose inner format has type `ty`.
+    pub fn resync(ty: Self) -> ValueType {
+        ValueType::Union(BTreeMap::from([
+            (Label::Borrowed("ok"), ty),
+            (Label::Borrowed("skipped"), Self::resync_skipped()),
+        ]))
+    }
+
     /// Given a `ValueType::Record` as `self` along with an identifier `label` that is a field of `self`,
     /// returns the corresponding type for said field.
     ///
@@ -420,6 +562,9 @@ pub(crate) mod augmented {
         NumericHole,
         Bool,
         Char,
+        F32,
+        F64,
+        Fixed(FixedType),
         Int(PrimInt),
         Tuple(Vec<AugValueType>),
         Record(Vec<(Label, AugValueType)>),
@@ -442,6 +587,9 @@ pub(crate) mod augmented {
                 BaseType::U16 => AugValueType::Int(PrimInt::U16),
                 BaseType::U32 => AugValueType::Int(PrimInt::U32),
                 BaseType::U64 => AugValueType::Int(PrimInt::U64),
+                BaseType::F32 => AugValueType::F32,
+                BaseType::F64 => AugValueType::F64,
+                BaseType::Fixed(fx) => AugValueType::Fixed(fx),
             }
         }
     }
@@ -453,12 +601,7 @@ pub(crate) mod augmented {
                 ValueType::Any => AugValueType::Any,
                 ValueType::Empty => AugValueType::Empty,
                 ValueType::ViewObj => AugValueType::ViewObj,
-                ValueType::Base(BaseType::Bool) => AugValueType::Bool,
-                ValueType::Base(BaseType::Char) => AugValueType::Char,
-                ValueType::Base(BaseType::U8) => AugValueType::Int(PrimInt::U8),
-                ValueType::Base(BaseType::U16) => AugValueType::Int(PrimInt::U16),
-                ValueType::Base(BaseType::U32) => AugValueType::Int(PrimInt::U32),
-                ValueType::Base(BaseType::U64) => AugValueType::Int(PrimInt::U64),
+                ValueType::Base(b) => AugValueType::from(b),
                 ValueType::Signed(s) => AugValueType::Int(PrimInt::from(s)),
                 ValueType::Tuple(ts) => {
                     AugValueType::Tuple(ts.into_iter().map(From::from).collect())
@@ -488,6 +631,9 @@ pub(crate) mod augmented {
                 AugValueType::NumericHole => ValueType::NumericHole,
                 AugValueType::Bool => ValueType::Base(BaseType::Bool),
                 AugValueType::Char => ValueType::Base(BaseType::Char),
+                AugValueType::F32 => ValueType::Base(BaseType::F32),
+                AugValueType::F64 => ValueType::Base(BaseType::F64),
+                AugValueType::Fixed(fx) => ValueType::Base(BaseType::Fixed(fx)),
                 AugValueType::Tuple(ts) => {
                     ValueType::Tuple(ts.into_iter().map(From::from).collect())
                 }
@@ -10,42 +10,57 @@ pub mod api_helper;
 use doodle::prelude::*;
 use doodle::try_sub;
 
-impl CommonObject for test_inner {
+impl<'a> CommonObject for test_inner<'a> {
 type Args<'x> = ();
 
-type Output<'x> = test_inner;
+type Output<'x> = test_inner<'x>;
 
 fn parse<'input>(p: &mut Parser<'input>, _: ()) -> Result<Self::Output<'input>, ParseError> {
 Decoder_test_inner(p)
 }
 }
 
-/// expected size: 32
+/// expected size: 16
 /// trait-ready: unique decoder function (d#2)
-#[derive(Debug, Clone)]
-pub struct test_inner {
+#[derive(Debug, Copy, Clone)]
+pub struct test_inner<'input> {
 a: u8,
-bs: Vec<u8>
+bs: &'input [u8]
 }
 
 /// expected size: 32
 /// trait-unready: multiple (2) decoders exist (d#{0, 1})
 #[derive(Debug, Clone)]
-pub struct test_outer {
-pairs: Vec<test_inner>,
+pub struct test_outer<'input> {
+pairs: Vec<test_inner<'input>>,
 end: u8
 }
 
 /// d#0
-fn Decoder_test_outer(_input: &mut Parser<'_>) -> Result<test_outer, ParseError> {
+fn Decoder_test_outer<'input>(_input: &mut Parser<'input>) -> Result<test_outer<'input>, ParseError> {
+_input.enter()?;
+let res = Decoder0_body(_input);
+_input.leave();
+res
+}
+
+fn Decoder0_body<'input>(_input: &mut Parser<'input>) -> Result<test_outer<'input>, ParseError> {
 Decoder1(_input)
 }
 
 /// d#1
-fn Decoder1(_input: &mut Parser<'_>) -> Result<test_outer, ParseError> {
+fn Decoder1<'input>(_input: &mut Parser<'input>) -> Result<test_outer<'input>, ParseError> {
+_input.enter()?;
+let res = Decoder1_body(_input);
+_input.leave();
+res.map_err(|e| e.within(r#"test.outer"#, _input))
+}
+
+fn Decoder1_body<'input>(_input: &mut Parser<'input>) -> Result<test_outer<'input>, ParseError> {
 let pairs = {
 let mut accum = Vec::new();
 for _ in 0..2u32 {
+_input.tally_element()?;
 let next_elem = (Decoder_test_inner(_input))?;
 accum.push(next_elem)
 };
@@ -56,24 +71,31 @@ let b = _input.read_byte()?;
 if b == 204 {
 b
 } else {
-return Err(ParseError::ExcludedBranch(13646096770106105413u64));
+return Err(ParseError::unexpected_byte(b, ByteSet::singleton(204), _input));
 }
 };
 PResult::Ok(test_outer { pairs, end })
 }
 
 /// d#2
-fn Decoder_test_inner(_input: &mut Parser<'_>) -> Result<test_inner, ParseError> {
+fn Decoder_test_inner<'input>(_input: &mut Parser<'input>) -> Result<test_inner<'input>, ParseError> {
+_input.enter()?;
+let res = Decoder2_body(_input);
+_input.leave();
+res.map_err(|e| e.within(r#"test.inner"#, _input))
+}
+
+fn Decoder2_body<'input>(_input: &mut Parser<'input>) -> Result<test_inner<'input>, ParseError> {
 let a = {
 let b = _input.read_byte()?;
 if b == 170 {
 b
 } else {
-return Err(ParseError::ExcludedBranch(2206609067086327257u64));
+return Err(ParseError::unexpected_byte(b, ByteSet::singleton(170), _input));
 }
 };
 let bs = {
-let mut accum = Vec::new();
+let mut accum = _input.byte_run();
 while _input.remaining() > 0 {
 let matching_ix = {
 _input.open_peek_context();
@@ -92,7 +114,7 @@ let ret = match _input.read_byte()? {
 },
 
 _ => {
-return Err(ParseError::ExcludedBranch(11876854719037224982u64));
+return Err(ParseError::ExcludedBranch(13646096770106105413u64));
 }
 };
 _input.close_peek_context()?;
@@ -100,12 +122,13 @@ ret
 }
 };
 if matching_ix == 0 {
+_input.tally_element()?;
 let next_elem = {
 let b = _input.read_byte()?;
 if b == 187 {
 b
 } else {
-return Err(ParseError::ExcludedBranch(18270091135093349626u64));
+return Err(ParseError::unexpected_byte(b, ByteSet::singleton(187), _input));
 }
 };
 accum.push(next_elem)
@@ -113,8 +136,54 @@ accum.push(next_elem)
 break
 }
 };
-accum
+accum.into_slice()
 };
 PResult::Ok(test_inner { a, bs })
 }
 
+/// e#0
+fn Encoder_test_outer<'input>(_output: &mut UnparseBuffer, _value: &test_outer<'input>) -> Result<(), UnparseError> {
+(Encoder1(_output, _value))?;
+UResult::Ok(())
+}
+
+/// e#1
+fn Encoder1<'input>(_output: &mut UnparseBuffer, _value: &test_outer<'input>) -> Result<(), UnparseError> {
+(check_length(2u32 as usize, _value.pairs.len()))?;
+for elem in _value.pairs.iter() {
+(Encoder_test_inner(_output, elem))?;
+};
+{
+let b = _value.end;
+if b == 204 {
+_output.push(b);
+} else {
+return Err(UnparseError::ExcludedByte(b, 2206609067086327257u64));
+}
+};
+UResult::Ok(())
+}
+
+/// e#2
+fn Encoder_test_inner<'input>(_output: &mut UnparseBuffer, _value: &test_inner<'input>) -> Result<(), UnparseError> {
+{
+let b = _value.a;
+if b == 170 {
+_output.push(b);
+} else {
+return Err(UnparseError::ExcludedByte(b, 18270091135093349626u64));
+}
+};
+for elem in _value.bs.iter() {
+{
+let b = *elem;
+if b == 187 {
+_output.push(b);
+} else {
+return Err(UnparseError::ExcludedByte(b, 11876854719037224982u64));
+}
+};
+};
+UResult::Ok(())
+}
+